@@ -31,6 +31,7 @@ mod new_block_template;
 mod output_features;
 mod peer;
 mod proof_of_work;
+mod reorg;
 mod signature;
 mod transaction;
 mod transaction_input;
@@ -50,6 +51,7 @@ pub use self::{
     output_features::*,
     peer::*,
     proof_of_work::*,
+    reorg::*,
     signature::*,
     transaction::*,
     transaction_input::*,
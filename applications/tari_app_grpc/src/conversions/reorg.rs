@@ -0,0 +1,53 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::tari_rpc as grpc;
+use tari_core::chain_storage::Reorg;
+use tari_crypto::tari_utilities::ByteArray;
+
+impl From<Reorg> for grpc::Reorg {
+    fn from(reorg: Reorg) -> Self {
+        let depth = reorg.depth();
+        Self {
+            local_time: reorg.local_time.as_u64(),
+            fork_height: reorg.fork_height,
+            fork_hash: reorg.fork_hash,
+            prev_tip_height: reorg.prev_tip_height,
+            prev_tip_hash: reorg.prev_tip_hash,
+            new_tip_height: reorg.new_tip_height,
+            new_tip_hash: reorg.new_tip_hash,
+            depth,
+            removed_blocks: reorg.removed_blocks,
+            added_blocks: reorg.added_blocks,
+            dropped_kernels_unknown: reorg.dropped_kernels.is_none(),
+            dropped_kernels: reorg
+                .dropped_kernels
+                .unwrap_or_default()
+                .into_iter()
+                .map(|sig| grpc::Signature {
+                    public_nonce: Vec::from(sig.get_public_nonce().as_bytes()),
+                    signature: Vec::from(sig.get_signature().as_bytes()),
+                })
+                .collect(),
+        }
+    }
+}
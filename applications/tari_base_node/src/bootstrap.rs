@@ -164,6 +164,7 @@ where B: BlockchainBackend + 'static
                 BaseNodeStateMachineConfig {
                     block_sync_config: BlockSyncConfig {
                         sync_peers,
+                        validation_window: config.block_sync_validation_window,
                         ..Default::default()
                     },
                     horizon_sync_config: HorizonSyncConfig {
@@ -243,6 +244,7 @@ where B: BlockchainBackend + 'static
     fn create_comms_config(&self) -> CommsConfig {
         CommsConfig {
             network: self.config.network,
+            custom_network_byte: self.config.custom_network.as_ref().map(|network| network.network_byte),
             node_identity: self.node_identity.clone(),
             transport_type: self.create_transport_type(),
             datastore_path: self.config.peer_db_path.clone(),
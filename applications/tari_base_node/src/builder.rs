@@ -21,15 +21,18 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::bootstrap::BaseNodeBootstrapper;
+use anyhow::anyhow;
+use futures::FutureExt;
 use log::*;
-use std::sync::Arc;
-use tari_common::{DatabaseType, GlobalConfig};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tari_common::GlobalConfig;
 use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, CommsNode};
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
-    chain_storage::{create_lmdb_database, BlockchainDatabase, BlockchainDatabaseConfig, LMDBDatabase, Validators},
-    consensus::ConsensusManager,
+    chain_storage::{BlockchainBackend, BlockchainDatabase, BlockchainDatabaseConfig, Validators},
+    consensus::{ConsensusManager, NetworkDefinition},
+    crypto::tari_utilities::hex::from_hex,
     mempool::{service::LocalMempoolService, Mempool, MempoolConfig},
     proof_of_work::randomx_factory::{RandomXConfig, RandomXFactory},
     transactions::types::CryptoFactories,
@@ -48,22 +51,26 @@ use tari_core::{
 use tari_p2p::auto_update::SoftwareUpdaterHandle;
 use tari_service_framework::ServiceHandles;
 use tari_shutdown::ShutdownSignal;
-use tokio::sync::watch;
+use tokio::{sync::watch, task, time};
 
 const LOG_TARGET: &str = "c::bn::initialization";
+/// The name of the file in the data directory that the mempool is persisted to
+const MEMPOOL_DUMP_FILE: &str = "mempool.dat";
 
 /// The base node context is a container for all the key structural pieces for the base node application, including the
 /// communications stack, the node state machine and handles to the various services that are registered
 /// on the comms stack.
-pub struct BaseNodeContext {
+pub struct BaseNodeContext<B> {
     config: Arc<GlobalConfig>,
-    blockchain_db: BlockchainDatabase<LMDBDatabase>,
+    blockchain_db: BlockchainDatabase<B>,
     base_node_comms: CommsNode,
     base_node_dht: Dht,
     base_node_handles: ServiceHandles,
+    mempool: Mempool,
+    consensus_rules: ConsensusManager,
 }
 
-impl BaseNodeContext {
+impl<B: BlockchainBackend + 'static> BaseNodeContext<B> {
     /// Starts the node container. This entails the base node state machine.
     /// This call consumes the NodeContainer instance.
     pub async fn run(self) {
@@ -72,6 +79,9 @@ impl BaseNodeContext {
         if let Err(e) = self.state_machine().shutdown_signal().await {
             warn!(target: LOG_TARGET, "Error shutting down Base Node State Machine: {}", e);
         }
+        if self.config.persist_mempool {
+            save_mempool(&self.mempool, mempool_dump_path(&self.config)).await;
+        }
         info!(target: LOG_TARGET, "Initiating communications stack shutdown");
 
         self.base_node_comms.wait_until_shutdown().await;
@@ -123,8 +133,13 @@ impl BaseNodeContext {
         self.base_node_handles.expect_handle()
     }
 
+    /// Returns the consensus rules the node was started with
+    pub fn consensus_rules(&self) -> ConsensusManager {
+        self.consensus_rules.clone()
+    }
+
     /// Returns a BlockchainDatabase handle
-    pub fn blockchain_db(&self) -> BlockchainDatabase<LMDBDatabase> {
+    pub fn blockchain_db(&self) -> BlockchainDatabase<B> {
         self.blockchain_db.clone()
     }
 
@@ -136,74 +151,31 @@ impl BaseNodeContext {
     }
 }
 
-/// Sets up and initializes the base node, creating the context and database
-/// ## Parameters
-/// `config` - The configuration for the base node
-/// `node_identity` - The node identity information of the base node
-/// `wallet_node_identity` - The node identity information of the base node's wallet
-/// `interrupt_signal` - The signal used to stop the application
-/// ## Returns
-/// Result containing the NodeContainer, String will contain the reason on error
-pub async fn configure_and_initialize_node(
-    config: Arc<GlobalConfig>,
-    node_identity: Arc<NodeIdentity>,
-    interrupt_signal: ShutdownSignal,
-    cleanup_orphans_at_startup: bool,
-) -> Result<BaseNodeContext, anyhow::Error> {
-    let result = match &config.db_type {
-        DatabaseType::Memory => {
-            // let backend = MemoryDatabase::<HashDigest>::default();
-            // build_node_context(
-            //     backend,
-            //     node_identity,
-            //     wallet_node_identity,
-            //     config,
-            //     interrupt_signal,
-            //     cleanup_orphans_at_startup,
-            // )
-            // .await?
-            unimplemented!();
-        },
-        DatabaseType::LMDB(p) => {
-            let backend = create_lmdb_database(&p, config.db_config.clone())?;
-            build_node_context(
-                backend,
-                node_identity,
-                config,
-                interrupt_signal,
-                cleanup_orphans_at_startup,
-            )
-            .await?
-        },
-    };
-    Ok(result)
-}
-
-/// Constructs the base node context, this includes setting up the consensus manager, mempool, base node
-/// and state machine
+/// Sets up and initializes the base node, creating the context around the given database backend. This includes
+/// setting up the consensus manager, mempool, base node and state machine
 /// ## Parameters
 /// `backend` - Backend interface
-/// `network` - The NetworkType (rincewind, mainnet, local)
 /// `base_node_identity` - The node identity information of the base node
-/// `wallet_node_identity` - The node identity information of the base node's wallet
 /// `config` - The configuration for the base node
 /// `interrupt_signal` - The signal used to stop the application
+/// `cleanup_orphans_at_startup` - Remove all orphans from the database when it is opened
 /// ## Returns
 /// Result containing the BaseNodeContext, String will contain the reason on error
-async fn build_node_context(
-    backend: LMDBDatabase,
+pub async fn configure_and_initialize_node<B: BlockchainBackend + 'static>(
+    backend: B,
     base_node_identity: Arc<NodeIdentity>,
     config: Arc<GlobalConfig>,
+    mempool_config: MempoolConfig,
     interrupt_signal: ShutdownSignal,
     cleanup_orphans_at_startup: bool,
-) -> Result<BaseNodeContext, anyhow::Error> {
+) -> Result<BaseNodeContext<B>, anyhow::Error> {
     //---------------------------------- Blockchain --------------------------------------------//
 
-    let rules = ConsensusManager::builder(config.network).build();
+    let rules = build_consensus_rules(&config)?;
     let factories = CryptoFactories::default();
     let randomx_factory = RandomXFactory::new(RandomXConfig::default(), config.max_randomx_vms);
     let validators = Validators::new(
-        BodyOnlyValidator::default(),
+        BodyOnlyValidator::new(rules.clone()),
         HeaderValidator::new(rules.clone()),
         OrphanBlockValidator::new(rules.clone(), factories.clone()),
     );
@@ -225,7 +197,16 @@ async fn build_node_context(
         Box::new(TxInputAndMaturityValidator::new(blockchain_db.clone())),
         Box::new(TxConsensusValidator::new(blockchain_db.clone())),
     ]);
-    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    let mempool = Mempool::new(mempool_config, Arc::new(mempool_validator));
+    if config.persist_mempool {
+        restore_mempool(&mempool, mempool_dump_path(&config)).await;
+        task::spawn(persist_mempool_periodically(
+            mempool.clone(),
+            mempool_dump_path(&config),
+            Duration::from_secs(config.mempool_persistence_interval),
+            interrupt_signal.clone(),
+        ));
+    }
 
     //---------------------------------- Base Node  --------------------------------------------//
     debug!(target: LOG_TARGET, "Creating base node state machine.");
@@ -234,7 +215,7 @@ async fn build_node_context(
         config: &config,
         node_identity: base_node_identity,
         db: blockchain_db.clone(),
-        mempool,
+        mempool: mempool.clone(),
         rules: rules.clone(),
         factories: factories.clone(),
         interrupt_signal: interrupt_signal.clone(),
@@ -251,5 +232,80 @@ async fn build_node_context(
         base_node_comms,
         base_node_dht,
         base_node_handles,
+        mempool,
+        consensus_rules: rules,
     })
 }
+
+/// Builds the consensus rules for the configured network, including any custom network definition, checkpoints and
+/// assume-valid hash
+pub fn build_consensus_rules(config: &GlobalConfig) -> Result<ConsensusManager, anyhow::Error> {
+    let mut rules = ConsensusManager::builder(config.network);
+    if let Some(path) = &config.network_definition_file {
+        let definition = NetworkDefinition::load_from_file(path)
+            .map_err(|e| anyhow!("Could not load network definition from {}: {}", path.display(), e))?;
+        info!(
+            target: LOG_TARGET,
+            "Using custom network '{}' defined in {}",
+            definition.name,
+            path.display()
+        );
+        rules = rules.with_network_definition(definition);
+    }
+    for (height, hash) in &config.checkpoints {
+        let hash = from_hex(hash).map_err(|e| anyhow!("Invalid checkpoint hash at height {}: {}", height, e))?;
+        rules = rules.with_checkpoint(*height, hash);
+    }
+    if let Some(hash) = &config.assume_valid_hash {
+        let hash = from_hex(hash).map_err(|e| anyhow!("Invalid assume-valid hash: {}", e))?;
+        rules = rules.with_assume_valid_hash(hash);
+    }
+    Ok(rules.build())
+}
+
+fn mempool_dump_path(config: &GlobalConfig) -> PathBuf {
+    config.data_dir.join(MEMPOOL_DUMP_FILE)
+}
+
+/// Restores the mempool from the dump written before the node was last shut down
+async fn restore_mempool(mempool: &Mempool, path: PathBuf) {
+    let mempool = mempool.clone();
+    match task::spawn_blocking(move || mempool.load_from_file(&path)).await {
+        Ok(Ok(num_restored)) => info!(
+            target: LOG_TARGET,
+            "Restored {} transaction(s) into the mempool", num_restored
+        ),
+        Ok(Err(e)) => warn!(target: LOG_TARGET, "Could not restore the mempool: {}", e),
+        Err(e) => warn!(target: LOG_TARGET, "Could not restore the mempool: {}", e),
+    }
+}
+
+/// Writes the mempool to disk
+async fn save_mempool(mempool: &Mempool, path: PathBuf) {
+    let mempool = mempool.clone();
+    match task::spawn_blocking(move || mempool.save_to_file(&path)).await {
+        Ok(Ok(())) => debug!(target: LOG_TARGET, "Mempool written to disk"),
+        Ok(Err(e)) => warn!(target: LOG_TARGET, "Could not write the mempool to disk: {}", e),
+        Err(e) => warn!(target: LOG_TARGET, "Could not write the mempool to disk: {}", e),
+    }
+}
+
+/// Writes the mempool to disk at the given interval until the node is shut down. An interval of zero disables the
+/// periodic dumps, leaving only the dump written on shutdown.
+async fn persist_mempool_periodically(
+    mempool: Mempool,
+    path: PathBuf,
+    interval: Duration,
+    mut shutdown: ShutdownSignal,
+) {
+    if interval.as_secs() == 0 {
+        return;
+    }
+    loop {
+        let mut delay = time::delay_for(interval).fuse();
+        futures::select! {
+            _ = delay => save_mempool(&mempool, path.clone()).await,
+            _ = shutdown => break,
+        }
+    }
+}
@@ -27,7 +27,8 @@ use log::*;
 use std::{
     cmp,
     fs::File,
-    io::{self, Write},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
     string::ToString,
     sync::Arc,
     time::{Duration, Instant},
@@ -48,7 +49,7 @@ use tari_core::{
         LocalNodeCommsInterface,
     },
     blocks::BlockHeader,
-    chain_storage::{async_db::AsyncBlockchainDb, ChainHeader, LMDBDatabase},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainHeader, SnapshotError, MAX_REORGS_PER_PAGE},
     consensus::ConsensusManager,
     mempool::service::LocalMempoolService,
     proof_of_work::PowAlgorithm,
@@ -58,12 +59,13 @@ use tari_core::{
 use tari_crypto::{ristretto::RistrettoPublicKey, tari_utilities::Hashable};
 use tari_p2p::auto_update::SoftwareUpdaterHandle;
 use tari_wallet::util::emoji::EmojiId;
-use tokio::{runtime, sync::watch};
+use tokio::{runtime, sync::watch, task};
 
-pub struct CommandHandler {
+pub struct CommandHandler<B> {
     executor: runtime::Handle,
     config: Arc<GlobalConfig>,
-    blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
+    blockchain_db: AsyncBlockchainDb<B>,
+    consensus_rules: ConsensusManager,
     discovery_service: DhtDiscoveryRequester,
     dht_metrics_collector: MetricsCollectorHandle,
     rpc_server: RpcServerHandle,
@@ -76,12 +78,13 @@ pub struct CommandHandler {
     software_updater: SoftwareUpdaterHandle,
 }
 
-impl CommandHandler {
-    pub fn new(executor: runtime::Handle, ctx: &BaseNodeContext) -> Self {
+impl<B: BlockchainBackend + 'static> CommandHandler<B> {
+    pub fn new(executor: runtime::Handle, ctx: &BaseNodeContext<B>) -> Self {
         CommandHandler {
             executor,
             config: ctx.config(),
             blockchain_db: ctx.blockchain_db().into(),
+            consensus_rules: ctx.consensus_rules(),
             discovery_service: ctx.base_node_dht().discovery_service_requester(),
             dht_metrics_collector: ctx.base_node_dht().metrics_collector(),
             rpc_server: ctx.rpc_server(),
@@ -690,6 +693,52 @@ impl CommandHandler {
         });
     }
 
+    pub fn list_reorgs(&self, min_depth: u64) {
+        let db = self.blockchain_db.clone();
+        self.executor.spawn(async move {
+            let mut reorgs = Vec::new();
+            let mut start = 0;
+            loop {
+                let page = try_or_print!(db.fetch_reorgs(start, MAX_REORGS_PER_PAGE).await);
+                if page.reorgs.is_empty() {
+                    break;
+                }
+                start = page.next_start;
+                reorgs.extend(page.reorgs.into_iter().filter(|reorg| reorg.depth() >= min_depth));
+            }
+            if reorgs.is_empty() {
+                println!("No reorgs found");
+                return;
+            }
+
+            let mut table = Table::new();
+            table.set_titles(vec![
+                "Time",
+                "Fork Height",
+                "Depth",
+                "Previous Tip",
+                "New Tip",
+                "Dropped Transactions",
+            ]);
+            for reorg in &reorgs {
+                table.add_row(row![
+                    DateTime::<Utc>::from(reorg.local_time),
+                    reorg.fork_height,
+                    reorg.depth(),
+                    format!("#{} ({})", reorg.prev_tip_height, reorg.prev_tip_hash.to_hex()),
+                    format!("#{} ({})", reorg.new_tip_height, reorg.new_tip_hash.to_hex()),
+                    reorg
+                        .dropped_kernels
+                        .as_ref()
+                        .map(|k| k.len().to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ]);
+            }
+            table.print_std();
+            println!("{} reorg(s)", reorgs.len());
+        });
+    }
+
     pub fn reset_offline_peers(&self) {
         let peer_manager = self.peer_manager.clone();
         self.executor.spawn(async move {
@@ -741,7 +790,7 @@ impl CommandHandler {
 
     /// Function to process the get-headers command
     async fn get_chain_headers(
-        blockchain_db: &AsyncBlockchainDb<LMDBDatabase>,
+        blockchain_db: &AsyncBlockchainDb<B>,
         start: u64,
         end: Option<u64>,
     ) -> Result<Vec<ChainHeader>, anyhow::Error> {
@@ -836,6 +885,33 @@ impl CommandHandler {
         });
     }
 
+    pub fn check_db_integrity(&self, repair: bool) {
+        let db = self.blockchain_db.clone();
+        self.executor.spawn(async move {
+            println!("Checking database integrity. This may take some time.");
+            let report = if repair {
+                try_or_print!(db.repair_integrity().await)
+            } else {
+                try_or_print!(db.check_integrity().await)
+            };
+            if report.is_ok() {
+                println!("No integrity issues found");
+                return;
+            }
+            println!("{}", report);
+            if repair {
+                if report.has_repairable_issues() {
+                    println!("Repairable issues have been fixed");
+                }
+            } else if report.has_repairable_issues() {
+                println!("Run `check-db-integrity repair` to fix the repairable issues");
+            }
+            if report.issues().iter().any(|issue| !issue.repairable) {
+                println!("Some issues cannot be repaired. Consider resyncing the database with --rebuild-db.");
+            }
+        });
+    }
+
     #[allow(deprecated)]
     pub fn period_stats(&self, period_end: u64, mut period_ticker_end: u64, period: u64) {
         let mut node = self.node_service.clone();
@@ -940,7 +1016,7 @@ impl CommandHandler {
         pow_algo: Option<PowAlgorithm>,
     ) {
         let db = self.blockchain_db.clone();
-        let network = self.config.network;
+        let consensus_rules = self.consensus_rules.clone();
         self.executor.spawn(async move {
             let mut output = try_or_print!(File::create(&filename));
 
@@ -956,7 +1032,6 @@ impl CommandHandler {
 
             let start_height = cmp::max(start_height, 1);
             let mut prev_header = try_or_print!(db.fetch_chain_header(start_height - 1).await);
-            let consensus_rules = ConsensusManager::builder(network).build();
 
             writeln!(
                 output,
@@ -1047,6 +1122,22 @@ impl CommandHandler {
         });
     }
 
+    pub fn export_snapshot(&self, height: u64, path: PathBuf) {
+        let db = self.blockchain_db.clone().into_inner();
+        self.executor.spawn(async move {
+            println!("Exporting chain state at height {} to {}", height, path.display());
+            let result = task::spawn_blocking(move || {
+                let mut writer = BufWriter::new(File::create(&path)?);
+                let horizon_data = db.export_snapshot(height, &mut writer)?;
+                writer.flush()?;
+                Result::<_, SnapshotError>::Ok(horizon_data)
+            })
+            .await;
+            try_or_print!(try_or_print!(result), "Failed to export snapshot: {error}");
+            println!("Snapshot export complete");
+        });
+    }
+
     /// Function to process the whoami command
     pub fn whoami(&self) {
         println!("{}", self.base_node_identity);
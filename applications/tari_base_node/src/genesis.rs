@@ -0,0 +1,117 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::anyhow;
+use log::*;
+use std::{fs, path::Path};
+use tari_common::{configuration::CustomNetwork, ConfigBootstrap, GlobalConfig};
+use tari_core::{
+    blocks::genesis_generator::{generate_genesis_block, genesis_block_source},
+    consensus::{NetworkConsensus, NetworkDefinition},
+    tari_utilities::{epoch_time::EpochTime, hex::Hex, Hashable},
+    transactions::{
+        tari_amount::MicroTari,
+        types::{CryptoFactories, PrivateKey},
+    },
+};
+
+pub const LOG_TARGET: &str = "base_node::app::genesis";
+
+/// Generates a genesis block for the configured network and writes it to `path` as Rust source, as a serialized
+/// block and as a network definition that can be loaded with the `network_definition_file` setting. The consensus
+/// constants are taken from the configured network definition file if there is one, so a generated definition can be
+/// edited and its genesis block regenerated. The coinbase is spendable with the private key read from
+/// `--genesis-spend-key-file`, its amount and the block timestamp can be given with `--genesis-amount` and
+/// `--genesis-timestamp`. A definition generated from a hardcoded network needs its own network byte, given with
+/// `--genesis-network-byte`.
+pub fn run_generate_genesis(
+    node_config: &GlobalConfig,
+    bootstrap: &ConfigBootstrap,
+    path: &Path,
+) -> Result<(), anyhow::Error> {
+    let key_file = bootstrap
+        .genesis_spend_key_file
+        .as_ref()
+        .ok_or_else(|| anyhow!("The coinbase spend key must be given with --genesis-spend-key-file"))?;
+    let spend_key = fs::read_to_string(key_file)
+        .map_err(|e| anyhow!("Could not read the spend key from {}: {}", key_file.display(), e))?;
+    let spend_key = PrivateKey::from_hex(spend_key.trim())
+        .map_err(|e| anyhow!("Invalid spend key in {}: {}", key_file.display(), e))?;
+
+    let (network, consensus_constants) = match &node_config.network_definition_file {
+        Some(file) => {
+            let definition = NetworkDefinition::load_from_file(file)
+                .map_err(|e| anyhow!("Could not load network definition from {}: {}", file.display(), e))?;
+            (definition.custom_network()?, definition.consensus_constants)
+        },
+        None => {
+            let network_byte = bootstrap
+                .genesis_network_byte
+                .ok_or_else(|| anyhow!("The network byte of the network must be given with --genesis-network-byte"))?;
+            (
+                CustomNetwork::new(node_config.network.to_string(), network_byte, Vec::new())?,
+                NetworkConsensus::from(node_config.network).create_consensus_constants(),
+            )
+        },
+    };
+    let constants = consensus_constants
+        .first()
+        .ok_or_else(|| anyhow!("The network has no consensus constants"))?;
+    let amount = bootstrap
+        .genesis_amount
+        .map(MicroTari::from)
+        .unwrap_or_else(|| constants.emission_amounts().0);
+    let timestamp = bootstrap
+        .genesis_timestamp
+        .map(EpochTime::from)
+        .unwrap_or_else(EpochTime::now);
+    let block = generate_genesis_block(constants, spend_key, amount, timestamp, &CryptoFactories::default())?;
+
+    fs::create_dir_all(path)?;
+    let fn_name = network
+        .name
+        .to_lowercase()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    fs::write(path.join("genesis_block.rs"), genesis_block_source(&block, &fn_name))?;
+    fs::write(path.join("genesis_block.json"), serde_json::to_string_pretty(&block)?)?;
+    let definition = NetworkDefinition::new(network, consensus_constants, block.clone());
+    definition.save_to_file(path.join("network_definition.json"))?;
+
+    info!(
+        target: LOG_TARGET,
+        "Generated genesis block {} for network '{}' in {}",
+        block.hash().to_hex(),
+        definition.name,
+        path.display()
+    );
+    println!(
+        "Generated genesis block {} in {}",
+        block.hash().to_hex(),
+        path.display()
+    );
+    println!(
+        "The coinbase of {} can be spent with the private key in {}",
+        amount,
+        key_file.display()
+    );
+    Ok(())
+}
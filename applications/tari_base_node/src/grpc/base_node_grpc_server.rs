@@ -35,17 +35,17 @@ use tari_app_grpc::{
     tari_rpc::{CalcType, Sorting},
 };
 use tari_app_utilities::consts;
-use tari_common::configuration::Network;
 use tari_comms::PeerManager;
 use tari_core::{
     base_node::{
-        comms_interface::Broadcast,
+        comms_interface::{BlockEvent, Broadcast},
         state_machine_service::states::BlockSyncInfo,
         LocalNodeCommsInterface,
         StateMachineHandle,
     },
     blocks::{Block, BlockHeader, NewBlockTemplate},
-    consensus::{emission::Emission, ConsensusManager, NetworkConsensus},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, Reorg},
+    consensus::{emission::Emission, ConsensusManager},
     crypto::tari_utilities::hex::Hex,
     mempool::{service::LocalMempoolService, TxStorageResponse},
     proof_of_work::PowAlgorithm,
@@ -53,7 +53,10 @@ use tari_core::{
 };
 use tari_crypto::tari_utilities::{message_format::MessageFormat, Hashable};
 use tari_p2p::auto_update::SoftwareUpdaterHandle;
-use tokio::{sync::mpsc, task};
+use tokio::{
+    sync::{broadcast, mpsc},
+    task,
+};
 use tonic::{Request, Response, Status};
 
 const LOG_TARGET: &str = "tari::base_node::grpc";
@@ -72,31 +75,34 @@ const LIST_HEADERS_MAX_NUM_HEADERS: u64 = 10_000;
 const LIST_HEADERS_PAGE_SIZE: usize = 10;
 // The `num_headers` value if none is provided.
 const LIST_HEADERS_DEFAULT_NUM_HEADERS: u64 = 10;
+// The number of reorgs to read from the database at a time. These are then streamed to client.
+const LIST_REORGS_PAGE_SIZE: usize = 10;
 
-pub struct BaseNodeGrpcServer {
+pub struct BaseNodeGrpcServer<B> {
+    blockchain_db: AsyncBlockchainDb<B>,
     node_service: LocalNodeCommsInterface,
     mempool_service: LocalMempoolService,
-    network: NetworkConsensus,
     state_machine_handle: StateMachineHandle,
     peer_manager: Arc<PeerManager>,
     consensus_rules: ConsensusManager,
     software_updater: SoftwareUpdaterHandle,
 }
 
-impl BaseNodeGrpcServer {
+impl<B: BlockchainBackend + 'static> BaseNodeGrpcServer<B> {
     pub fn new(
+        blockchain_db: AsyncBlockchainDb<B>,
         local_node: LocalNodeCommsInterface,
         local_mempool: LocalMempoolService,
-        network: Network,
+        consensus_rules: ConsensusManager,
         state_machine_handle: StateMachineHandle,
         peer_manager: Arc<PeerManager>,
         software_updater: SoftwareUpdaterHandle,
     ) -> Self {
         Self {
+            blockchain_db,
             node_service: local_node,
             mempool_service: local_mempool,
-            consensus_rules: ConsensusManager::builder(network).build(),
-            network: network.into(),
+            consensus_rules,
             state_machine_handle,
             peer_manager,
             software_updater,
@@ -112,7 +118,7 @@ pub async fn get_heights(
 }
 
 #[tonic::async_trait]
-impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
+impl<B: BlockchainBackend + 'static> tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer<B> {
     type FetchMatchingUtxosStream = mpsc::Receiver<Result<tari_rpc::FetchMatchingUtxosResponse, Status>>;
     type GetBlocksStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
     type GetMempoolTransactionsStream = mpsc::Receiver<Result<tari_rpc::GetMempoolTransactionsResponse, Status>>;
@@ -120,6 +126,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     type GetPeersStream = mpsc::Receiver<Result<tari_rpc::GetPeersResponse, Status>>;
     type GetTokensInCirculationStream = mpsc::Receiver<Result<tari_rpc::ValueAtHeightResponse, Status>>;
     type ListHeadersStream = mpsc::Receiver<Result<tari_rpc::BlockHeader, Status>>;
+    type ListReorgsStream = mpsc::Receiver<Result<tari_rpc::Reorg, Status>>;
     type SearchKernelsStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
 
     async fn get_network_difficulty(
@@ -392,6 +399,73 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(rx))
     }
 
+    async fn list_reorgs(
+        &self,
+        request: Request<tari_rpc::ListReorgsRequest>,
+    ) -> Result<Response<Self::ListReorgsStream>, Status> {
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for ListReorgs: since:{}, min_depth:{}, follow:{}",
+            request.since,
+            request.min_depth,
+            request.follow
+        );
+
+        let db = self.blockchain_db.clone();
+        // Subscribe before reading the journal so that no reorg is missed in between
+        let mut block_events = self.node_service.get_block_event_stream();
+        let (mut tx, rx) = mpsc::channel(LIST_REORGS_PAGE_SIZE);
+
+        task::spawn(async move {
+            let is_match =
+                |reorg: &Reorg| reorg.local_time.as_u64() >= request.since && reorg.depth() >= request.min_depth;
+            let mut next_start = 0;
+            loop {
+                // Read the journal a page at a time, from where the previous read stopped
+                loop {
+                    let page = match db.fetch_reorgs(next_start, LIST_REORGS_PAGE_SIZE).await {
+                        Ok(page) => page,
+                        Err(err) => {
+                            warn!(target: LOG_TARGET, "Error reading the reorg journal: {}", err);
+                            let _ = tx.send(Err(Status::internal(err.to_string()))).await;
+                            return;
+                        },
+                    };
+                    if page.reorgs.is_empty() {
+                        break;
+                    }
+                    next_start = page.next_start;
+                    for reorg in page.reorgs.into_iter().filter(|r| is_match(r)) {
+                        if let Err(err) = tx.send(Ok(reorg.into())).await {
+                            warn!(target: LOG_TARGET, "Error sending reorg via GRPC: {}", err);
+                            return;
+                        }
+                    }
+                }
+
+                if !request.follow {
+                    return;
+                }
+                loop {
+                    match block_events.recv().await {
+                        Ok(event) => match &*event {
+                            BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { .. }, _) |
+                            BlockEvent::BlockSyncRewind(_) => break,
+                            _ => {},
+                        },
+                        // Missed events may have included a reorg, check the journal to be sure
+                        Err(broadcast::RecvError::Lagged(_)) => break,
+                        Err(broadcast::RecvError::Closed) => return,
+                    }
+                }
+            }
+        });
+
+        debug!(target: LOG_TARGET, "Sending ListReorgs response stream to client");
+        Ok(Response::new(rx))
+    }
+
     async fn get_new_block_template(
         &self,
         request: Request<tari_rpc::NewBlockTemplateRequest>,
@@ -516,8 +590,10 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             Status::internal(e.to_string())
         })?;
         let response = match res {
-            TxStorageResponse::UnconfirmedPool => tari_rpc::SubmitTransactionResponse {
-                result: tari_rpc::SubmitTransactionResult::Accepted.into(),
+            TxStorageResponse::UnconfirmedPool | TxStorageResponse::UnconfirmedPoolReplaced => {
+                tari_rpc::SubmitTransactionResponse {
+                    result: tari_rpc::SubmitTransactionResult::Accepted.into(),
+                }
             },
             TxStorageResponse::ReorgPool | TxStorageResponse::NotStoredAlreadySpent => {
                 tari_rpc::SubmitTransactionResponse {
@@ -526,7 +602,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             },
             TxStorageResponse::NotStored |
             TxStorageResponse::NotStoredOrphan |
-            TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResponse {
+            TxStorageResponse::NotStoredTimeLocked |
+            TxStorageResponse::NotStoredFeeTooLow |
+            TxStorageResponse::NotStoredBelowMinimumFee => tari_rpc::SubmitTransactionResponse {
                 result: tari_rpc::SubmitTransactionResult::Rejected.into(),
             },
         };
@@ -580,8 +658,10 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 Status::internal(e.to_string())
             })?;
         let response = match res {
-            TxStorageResponse::UnconfirmedPool => tari_rpc::TransactionStateResponse {
-                result: tari_rpc::TransactionLocation::Mempool.into(),
+            TxStorageResponse::UnconfirmedPool | TxStorageResponse::UnconfirmedPoolReplaced => {
+                tari_rpc::TransactionStateResponse {
+                    result: tari_rpc::TransactionLocation::Mempool.into(),
+                }
             },
             TxStorageResponse::ReorgPool | TxStorageResponse::NotStoredAlreadySpent => {
                 tari_rpc::TransactionStateResponse {
@@ -592,7 +672,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             },
             TxStorageResponse::NotStored |
             TxStorageResponse::NotStoredOrphan |
-            TxStorageResponse::NotStoredTimeLocked => tari_rpc::TransactionStateResponse {
+            TxStorageResponse::NotStoredTimeLocked |
+            TxStorageResponse::NotStoredFeeTooLow |
+            TxStorageResponse::NotStoredBelowMinimumFee => tari_rpc::TransactionStateResponse {
                 result: tari_rpc::TransactionLocation::NotStored.into(),
             },
         };
@@ -601,6 +683,40 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(response))
     }
 
+    async fn get_fee_estimate(
+        &self,
+        request: Request<tari_rpc::GetFeeEstimateRequest>,
+    ) -> Result<Response<tari_rpc::GetFeeEstimateResponse>, Status> {
+        let target_blocks = request.into_inner().target_blocks;
+        debug!(
+            target: LOG_TARGET,
+            "Received GetFeeEstimate request from client ({} blocks)", target_blocks
+        );
+        let mut node_handler = self.node_service.clone();
+        let mut mem_handler = self.mempool_service.clone();
+
+        let tip = node_handler
+            .get_metadata()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .height_of_longest_chain();
+        let max_block_weight = self
+            .consensus_rules
+            .consensus_constants(tip)
+            .get_max_block_weight_excluding_coinbase();
+        let fee_per_gram = mem_handler
+            .get_fee_estimate(target_blocks, max_block_weight)
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error estimating fee:{}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(tari_rpc::GetFeeEstimateResponse {
+            fee_per_gram: fee_per_gram.into(),
+        }))
+    }
+
     async fn get_peers(
         &self,
         _request: Request<tari_rpc::GetPeersRequest>,
@@ -883,10 +999,17 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         _request: Request<tari_rpc::Empty>,
     ) -> Result<Response<tari_rpc::ConsensusConstants>, Status> {
         debug!(target: LOG_TARGET, "Incoming GRPC request for GetConstants",);
-        debug!(target: LOG_TARGET, "Sending GetConstants response to client");
         // TODO: Switch to request height
+        let tip = self
+            .node_service
+            .clone()
+            .get_metadata()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .height_of_longest_chain();
+        debug!(target: LOG_TARGET, "Sending GetConstants response to client");
         Ok(Response::new(
-            self.network.create_consensus_constants().pop().unwrap().into(),
+            self.consensus_rules.consensus_constants(tip).clone().into(),
         ))
     }
 
@@ -934,7 +1057,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         heights = heights
             .drain(..cmp::min(heights.len(), GET_TOKENS_IN_CIRCULATION_MAX_HEIGHTS))
             .collect();
-        let consensus_manager = ConsensusManager::builder(self.network.as_network()).build();
+        let consensus_manager = self.consensus_rules.clone();
 
         let (mut tx, rx) = mpsc::channel(GET_TOKENS_IN_CIRCULATION_PAGE_SIZE);
         task::spawn(async move {
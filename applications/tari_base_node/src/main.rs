@@ -89,9 +89,11 @@ mod bootstrap;
 mod builder;
 mod cli;
 mod command_handler;
+mod genesis;
 mod grpc;
 mod parser;
 mod recovery;
+mod snapshot;
 mod status_line;
 mod utils;
 
@@ -111,8 +113,22 @@ use tari_app_utilities::{
     initialization::init_configuration,
     utilities::{setup_runtime, ExitCodes},
 };
-use tari_common::{configuration::bootstrap::ApplicationType, ConfigBootstrap, GlobalConfig};
-use tari_comms::{peer_manager::PeerFeatures, tor::HiddenServiceControllerError};
+use tari_common::{
+    configuration::bootstrap::ApplicationType,
+    ConfigBootstrap,
+    DatabaseType,
+    DefaultConfigLoader,
+    GlobalConfig,
+    NetworkConfigPath,
+};
+use tari_comms::{
+    peer_manager::{NodeIdentity, PeerFeatures},
+    tor::HiddenServiceControllerError,
+};
+use tari_core::{
+    chain_storage::{create_lmdb_database, migrate_lmdb_database, BlockchainBackend, MemoryDatabase},
+    mempool::MempoolConfig,
+};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use tokio::{runtime, task, time};
 use tonic::transport::Server;
@@ -133,23 +149,36 @@ fn main() {
 }
 
 fn main_inner() -> Result<(), ExitCodes> {
-    let (bootstrap, node_config, _) = init_configuration(ApplicationType::BaseNode)?;
+    let (bootstrap, node_config, mut cfg) = init_configuration(ApplicationType::BaseNode)?;
 
     debug!(target: LOG_TARGET, "Using configuration: {:?}", node_config);
 
+    // The mempool is configured from the `[mempool.<network>]` section of the network the node runs on
+    let network_key = MempoolConfig::network_config_key();
+    if cfg.get_str(&network_key).is_err() {
+        cfg.set(&network_key, node_config.network.to_string())
+            .map_err(|e| ExitCodes::ConfigError(e.to_string()))?;
+    }
+    let mempool_config =
+        <MempoolConfig as DefaultConfigLoader>::load_from(&cfg).map_err(|e| ExitCodes::ConfigError(e.to_string()))?;
+
     // Set up the Tokio runtime
     let mut rt = setup_runtime(&node_config).map_err(|e| {
         error!(target: LOG_TARGET, "{}", e);
         ExitCodes::UnknownError
     })?;
 
-    rt.block_on(run_node(node_config.into(), bootstrap))?;
+    rt.block_on(run_node(node_config.into(), mempool_config, bootstrap))?;
 
     Ok(())
 }
 
 /// Sets up the base node and runs the cli_loop
-async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) -> Result<(), ExitCodes> {
+async fn run_node(
+    node_config: Arc<GlobalConfig>,
+    mempool_config: MempoolConfig,
+    bootstrap: ConfigBootstrap,
+) -> Result<(), ExitCodes> {
     // Load or create the Node identity
     let node_identity = setup_node_identity(
         &node_config.base_node_identity_file,
@@ -179,15 +208,91 @@ async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) ->
         return Ok(());
     };
 
+    if let Some(path) = bootstrap.generate_genesis.as_ref() {
+        info!(target: LOG_TARGET, "Generating genesis block into {}", path.display());
+        genesis::run_generate_genesis(&node_config, &bootstrap, path).map_err(|e| {
+            error!(target: LOG_TARGET, "Could not generate genesis block: {}", e);
+            ExitCodes::UnknownError
+        })?;
+        return Ok(());
+    }
+
+    if let Some(path) = bootstrap.import_snapshot.as_ref() {
+        info!(
+            target: LOG_TARGET,
+            "Importing chain state snapshot from {}",
+            path.display()
+        );
+        snapshot::run_import(&node_config, path).map_err(|e| ExitCodes::RecoveryError(e.to_string()))?;
+        return Ok(());
+    }
+
+    if bootstrap.migrate_db_dry_run {
+        let path = match &node_config.db_type {
+            DatabaseType::LMDB(path) => path,
+            DatabaseType::Memory => {
+                println!("The in-memory database does not require migration");
+                return Ok(());
+            },
+        };
+        let report = migrate_lmdb_database(path, node_config.db_config.clone(), true).map_err(|err| {
+            error!(target: LOG_TARGET, "{}", err);
+            ExitCodes::UnknownError
+        })?;
+        println!("{}", report);
+        return Ok(());
+    }
+
     if bootstrap.init {
         info!(target: LOG_TARGET, "Default configuration created. Done.");
         return Ok(());
     }
 
+    match &node_config.db_type {
+        DatabaseType::Memory => {
+            info!(target: LOG_TARGET, "Using an in-memory blockchain database");
+            run_base_node(
+                MemoryDatabase::new(),
+                node_config,
+                mempool_config,
+                node_identity,
+                bootstrap,
+                shutdown,
+            )
+            .await
+        },
+        DatabaseType::LMDB(path) => {
+            let mut backend = create_lmdb_database(path, node_config.db_config.clone()).map_err(|err| {
+                error!(target: LOG_TARGET, "{}", err);
+                ExitCodes::UnknownError
+            })?;
+            backend
+                .set_output_index_enabled(node_config.output_index_enabled)
+                .map_err(|err| {
+                    error!(target: LOG_TARGET, "Could not configure the output index: {}", err);
+                    ExitCodes::UnknownError
+                })?;
+            backend.set_archive_depth(node_config.archive_depth);
+            run_base_node(backend, node_config, mempool_config, node_identity, bootstrap, shutdown).await
+        },
+    }
+}
+
+/// Builds the base node context around the given blockchain backend and runs the node until it is shut down
+async fn run_base_node<B: BlockchainBackend + 'static>(
+    backend: B,
+    node_config: Arc<GlobalConfig>,
+    mempool_config: MempoolConfig,
+    node_identity: Arc<NodeIdentity>,
+    bootstrap: ConfigBootstrap,
+    shutdown: Shutdown,
+) -> Result<(), ExitCodes> {
     // Build, node, build!
     let ctx = builder::configure_and_initialize_node(
-        node_config.clone(),
+        backend,
         node_identity,
+        node_config.clone(),
+        mempool_config,
         shutdown.to_signal(),
         bootstrap.clean_orphans_db,
     )
@@ -217,9 +322,10 @@ async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) ->
     if node_config.grpc_enabled {
         // Go, GRPC, go go
         let grpc = crate::grpc::base_node_grpc_server::BaseNodeGrpcServer::new(
+            ctx.blockchain_db().into(),
             ctx.local_node(),
             ctx.local_mempool(),
-            node_config.network,
+            ctx.consensus_rules(),
             ctx.state_machine(),
             ctx.base_node_comms().peer_manager(),
             ctx.software_updater(),
@@ -253,8 +359,8 @@ async fn run_node(node_config: Arc<GlobalConfig>, bootstrap: ConfigBootstrap) ->
 }
 
 /// Runs the gRPC server
-async fn run_grpc(
-    grpc: crate::grpc::base_node_grpc_server::BaseNodeGrpcServer,
+async fn run_grpc<B: BlockchainBackend + 'static>(
+    grpc: crate::grpc::base_node_grpc_server::BaseNodeGrpcServer<B>,
     grpc_address: SocketAddr,
     interrupt_signal: ShutdownSignal,
 ) -> Result<(), anyhow::Error> {
@@ -273,7 +379,9 @@ async fn run_grpc(
     Ok(())
 }
 
-async fn read_command(mut rustyline: Editor<Parser>) -> Result<(String, Editor<Parser>), String> {
+async fn read_command<B: BlockchainBackend + 'static>(
+    mut rustyline: Editor<Parser<B>>,
+) -> Result<(String, Editor<Parser<B>>), String> {
     task::spawn(async {
         let readline = rustyline.readline(">> ");
 
@@ -308,7 +416,7 @@ async fn read_command(mut rustyline: Editor<Parser>) -> Result<(String, Editor<P
 ///
 /// ## Returns
 /// Doesn't return anything
-async fn cli_loop(parser: Parser, mut shutdown: Shutdown) {
+async fn cli_loop<B: BlockchainBackend + 'static>(parser: Parser<B>, mut shutdown: Shutdown) {
     let cli_config = Config::builder()
         .history_ignore_space(true)
         .completion_type(CompletionType::List)
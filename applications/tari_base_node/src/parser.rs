@@ -32,7 +32,7 @@ use rustyline::{
     Context,
 };
 use rustyline_derive::{Helper, Highlighter, Validator};
-use std::{str::FromStr, string::ToString, sync::Arc, time::Duration};
+use std::{path::PathBuf, str::FromStr, string::ToString, sync::Arc, time::Duration};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 use tari_app_utilities::utilities::{
@@ -41,6 +41,7 @@ use tari_app_utilities::utilities::{
     parse_emoji_id_or_public_key_or_node_id,
 };
 use tari_core::{
+    chain_storage::BlockchainBackend,
     crypto::tari_utilities::hex::from_hex,
     proof_of_work::PowAlgorithm,
     tari_utilities::hex::Hex,
@@ -62,13 +63,16 @@ pub enum BaseNodeCommand {
     DialPeer,
     ResetOfflinePeers,
     RewindBlockchain,
+    ExportSnapshot,
     BanPeer,
     UnbanPeer,
     UnbanAllPeers,
     ListBannedPeers,
     ListConnections,
     ListHeaders,
+    ListReorgs,
     CheckDb,
+    CheckDbIntegrity,
     PeriodStats,
     HeaderStats,
     BlockTiming,
@@ -87,14 +91,14 @@ pub enum BaseNodeCommand {
 
 /// This is used to parse commands from the user and execute them
 #[derive(Helper, Validator, Highlighter)]
-pub struct Parser {
+pub struct Parser<B> {
     commands: Vec<String>,
     hinter: HistoryHinter,
-    command_handler: Arc<CommandHandler>,
+    command_handler: Arc<CommandHandler<B>>,
 }
 
 /// This will go through all instructions and look for potential matches
-impl Completer for Parser {
+impl<B> Completer for Parser<B> {
     type Candidate = String;
 
     fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Result<(usize, Vec<String>), ReadlineError> {
@@ -114,15 +118,15 @@ impl Completer for Parser {
 }
 
 /// This allows us to make hints based on historic inputs
-impl Hinter for Parser {
+impl<B> Hinter for Parser<B> {
     fn hint(&self, line: &str, pos: usize, ctx: &rustyline::Context<'_>) -> Option<String> {
         self.hinter.hint(line, pos, ctx)
     }
 }
 
-impl Parser {
+impl<B: BlockchainBackend + 'static> Parser<B> {
     /// creates a new parser struct
-    pub fn new(command_handler: Arc<CommandHandler>) -> Self {
+    pub fn new(command_handler: Arc<CommandHandler<B>>) -> Self {
         Parser {
             commands: BaseNodeCommand::iter().map(|x| x.to_string()).collect(),
             hinter: HistoryHinter {},
@@ -153,7 +157,7 @@ impl Parser {
         }
     }
 
-    pub fn get_command_handler(&self) -> Arc<CommandHandler> {
+    pub fn get_command_handler(&self) -> Arc<CommandHandler<B>> {
         self.command_handler.clone()
     }
 
@@ -206,9 +210,15 @@ impl Parser {
             RewindBlockchain => {
                 self.process_rewind_blockchain(args);
             },
+            ExportSnapshot => {
+                self.process_export_snapshot(args);
+            },
             CheckDb => {
                 self.command_handler.check_db();
             },
+            CheckDbIntegrity => {
+                self.process_check_db_integrity(args);
+            },
             PeriodStats => {
                 self.process_period_stats(args);
             },
@@ -233,6 +243,9 @@ impl Parser {
             ListHeaders => {
                 self.process_list_headers(args);
             },
+            ListReorgs => {
+                self.process_list_reorgs(args);
+            },
             BlockTiming | CalcTiming => {
                 self.process_block_timing(args);
             },
@@ -309,6 +322,11 @@ impl Parser {
                 println!("Usage: {} [new_height]", command);
                 println!("new_height must be less than the current height.");
             },
+            ExportSnapshot => {
+                println!("Writes the chain state at the given height to a snapshot file.");
+                println!("Usage: {} [height] [file]", command);
+                println!("The snapshot can be loaded into a new pruned node using --import-snapshot.");
+            },
             BanPeer => {
                 println!("Bans a peer");
             },
@@ -324,6 +342,11 @@ impl Parser {
             CheckDb => {
                 println!("Checks the blockchain database for missing blocks and headers");
             },
+            CheckDbIntegrity => {
+                println!("Checks that the blockchain database indexes are consistent with the stored blocks");
+                println!("Usage: {} [repair]", command);
+                println!("If `repair` is given, inconsistent indexes are rebuilt from the stored blocks.");
+            },
             HeaderStats => {
                 println!(
                     "Prints out certain stats to of the block chain in csv format for easy copy, use as follows: "
@@ -352,6 +375,11 @@ impl Parser {
                 println!("list-headers [first header height] [last header height]");
                 println!("list-headers [number of headers starting from the chain tip back]");
             },
+            ListReorgs => {
+                println!("Lists the chain reorgs recorded by this node");
+                println!("Usage: {} [min_depth]", command);
+                println!("If min_depth is given, only reorgs that removed at least that many blocks are listed.");
+            },
             BlockTiming | CalcTiming => {
                 println!("Calculates the maximum, minimum, and average time taken to mine a given range of blocks.");
                 println!("block-timing [start height] [end height]");
@@ -586,6 +614,14 @@ impl Parser {
         self.command_handler.list_headers(start, end)
     }
 
+    fn process_list_reorgs<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let min_depth = match args.next() {
+            Some(s) => try_or_print!(u64::from_str(s).map_err(|_| "min_depth must be an integer.")),
+            None => 0,
+        };
+        self.command_handler.list_reorgs(min_depth);
+    }
+
     /// Function to process the calc-timing command
     fn process_block_timing<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
         let start = args.next().map(u64::from_str).map(Result::ok).flatten();
@@ -674,4 +710,25 @@ impl Parser {
             .and_then(|s| u64::from_str(s).map_err(|_| "new_height must be an integer.")));
         self.command_handler.rewind_blockchain(new_height);
     }
+
+    fn process_export_snapshot<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let height = try_or_print!(args
+            .next()
+            .ok_or("height argument required")
+            .and_then(|s| u64::from_str(s).map_err(|_| "height must be an integer.")));
+        let path = try_or_print!(args.next().ok_or("file argument required"));
+        self.command_handler.export_snapshot(height, PathBuf::from(path));
+    }
+
+    fn process_check_db_integrity<'a, I: Iterator<Item = &'a str>>(&self, mut args: I) {
+        let repair = match args.next() {
+            None => false,
+            Some("repair") => true,
+            Some(_) => {
+                println!("Usage: check-db-integrity [repair]");
+                return;
+            },
+        };
+        self.command_handler.check_db_integrity(repair);
+    }
 }
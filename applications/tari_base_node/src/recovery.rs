@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use crate::builder::build_consensus_rules;
 use anyhow::anyhow;
 use log::*;
 use std::{
@@ -92,11 +93,11 @@ pub async fn run_recovery(node_config: &GlobalConfig) -> Result<(), anyhow::Erro
             return Err(anyhow!("Recovery mode is only available for LMDB"));
         },
     };
-    let rules = ConsensusManager::builder(node_config.network).build();
+    let rules = build_consensus_rules(node_config)?;
     let factories = CryptoFactories::default();
     let randomx_factory = RandomXFactory::new(RandomXConfig::default(), node_config.max_randomx_vms);
     let validators = Validators::new(
-        BodyOnlyValidator::default(),
+        BodyOnlyValidator::new(rules.clone()),
         HeaderValidator::new(rules.clone()),
         OrphanBlockValidator::new(rules.clone(), factories.clone()),
     );
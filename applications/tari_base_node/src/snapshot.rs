@@ -0,0 +1,90 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::builder::build_consensus_rules;
+use anyhow::anyhow;
+use log::*;
+use std::{fs::File, io::BufReader, path::Path};
+use tari_common::{DatabaseType, GlobalConfig};
+use tari_core::{
+    chain_storage::{create_lmdb_database, BlockchainDatabase, BlockchainDatabaseConfig, Validators},
+    proof_of_work::randomx_factory::{RandomXConfig, RandomXFactory},
+    tari_utilities::hex::Hex,
+    transactions::types::CryptoFactories,
+    validation::{
+        block_validators::{BodyOnlyValidator, OrphanBlockValidator},
+        header_validator::HeaderValidator,
+        ChainBalanceValidator,
+        DifficultyCalculator,
+    },
+};
+
+pub const LOG_TARGET: &str = "base_node::app::snapshot";
+
+/// Loads the chain state contained in the snapshot file at `path` into the node's (empty) pruned database. Every
+/// header, kernel signature, range proof and MMR root in the snapshot is validated before it is committed.
+pub fn run_import(node_config: &GlobalConfig, path: &Path) -> Result<(), anyhow::Error> {
+    let backend = match &node_config.db_type {
+        DatabaseType::LMDB(p) => create_lmdb_database(&p, node_config.db_config.clone()).map_err(|e| {
+            error!(target: LOG_TARGET, "Error opening db: {}", e);
+            anyhow!("Could not open DB: {}", e)
+        })?,
+        _ => {
+            error!(target: LOG_TARGET, "Snapshot import is only available for LMDB");
+            return Err(anyhow!("Snapshot import is only available for LMDB"));
+        },
+    };
+    let rules = build_consensus_rules(node_config)?;
+    let factories = CryptoFactories::default();
+    let randomx_factory = RandomXFactory::new(RandomXConfig::default(), node_config.max_randomx_vms);
+    let validators = Validators::new(
+        BodyOnlyValidator::new(rules.clone()),
+        HeaderValidator::new(rules.clone()),
+        OrphanBlockValidator::new(rules.clone(), factories.clone()),
+    );
+    let db_config = BlockchainDatabaseConfig {
+        orphan_storage_capacity: node_config.orphan_storage_capacity,
+        pruning_horizon: node_config.pruning_horizon,
+        pruning_interval: node_config.pruned_mode_cleanup_interval,
+    };
+    let db = BlockchainDatabase::new(
+        backend,
+        rules.clone(),
+        validators,
+        db_config,
+        DifficultyCalculator::new(rules.clone(), randomx_factory),
+        false,
+    )?;
+
+    let reader = BufReader::new(File::open(path)?);
+    println!("Importing snapshot from {}. This may take a while.", path.display());
+    let final_state_validator = ChainBalanceValidator::new(rules, factories.clone());
+    let tip = db.import_snapshot(reader, &final_state_validator, &factories.range_proof)?;
+    info!(
+        target: LOG_TARGET,
+        "Snapshot import complete. Chain tip is now {} at height {}",
+        tip.hash().to_hex(),
+        tip.height()
+    );
+    println!("Snapshot import complete at height {}", tip.height());
+    Ok(())
+}
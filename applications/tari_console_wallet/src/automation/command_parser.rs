@@ -57,6 +57,12 @@ impl Display for ParsedCommand {
             SetBaseNode => "set-base-node",
             SetCustomBaseNode => "set-custom-base-node",
             ClearCustomBaseNode => "clear-custom-base-node",
+            PrepareUnsignedTransaction => "prepare-unsigned-transaction",
+            SignTransaction => "sign-transaction",
+            ImportSignedTransaction => "import-signed-transaction",
+            ExportViewKeys => "export-view-keys",
+            CreateAccount => "create-account",
+            ListAccounts => "list-accounts",
         };
 
         let args = self
@@ -80,6 +86,7 @@ pub enum ParsedArgument {
     Date(DateTime<Utc>),
     OutputToCSVFile(String),
     CSVFileName(String),
+    FileName(String),
     Address(Multiaddr),
 }
 
@@ -95,6 +102,7 @@ impl Display for ParsedArgument {
             Date(v) => write!(f, "{}", v.to_string()),
             OutputToCSVFile(v) => write!(f, "{}", v.to_string()),
             CSVFileName(v) => write!(f, "{}", v.to_string()),
+            FileName(v) => write!(f, "{}", v.to_string()),
             Address(v) => write!(f, "{}", v.to_string()),
         }
     }
@@ -122,6 +130,12 @@ pub fn parse_command(command: &str) -> Result<ParsedCommand, ParseError> {
         SetBaseNode => parse_public_key_and_address(args)?,
         SetCustomBaseNode => parse_public_key_and_address(args)?,
         ClearCustomBaseNode => Vec::new(),
+        PrepareUnsignedTransaction => parse_prepare_unsigned_transaction(args)?,
+        SignTransaction => parse_file_names(args, &["unsigned transaction file", "signed transaction file"])?,
+        ImportSignedTransaction => parse_file_names(args, &["signed transaction file"])?,
+        ExportViewKeys => Vec::new(),
+        CreateAccount => parse_account_name(args)?,
+        ListAccounts => Vec::new(),
     };
 
     Ok(ParsedCommand { command, args })
@@ -255,6 +269,63 @@ fn parse_send_tari(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, Par
     Ok(parsed_args)
 }
 
+fn parse_prepare_unsigned_transaction(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    // amount
+    let amount = args.next().ok_or_else(|| ParseError::Empty("amount".to_string()))?;
+    let amount = MicroTari::from_str(amount)?;
+    parsed_args.push(ParsedArgument::Amount(amount));
+
+    // public key/emoji id
+    let pubkey = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("public key or emoji id".to_string()))?;
+    let pubkey = parse_emoji_id_or_public_key(pubkey).ok_or(ParseError::PublicKey)?;
+    parsed_args.push(ParsedArgument::PublicKey(pubkey));
+
+    // fee per gram
+    let fee_per_gram = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("fee per gram".to_string()))?;
+    let fee_per_gram = MicroTari::from_str(fee_per_gram)?;
+    parsed_args.push(ParsedArgument::Amount(fee_per_gram));
+
+    // unsigned transaction file
+    let file_name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("unsigned transaction file".to_string()))?;
+    parsed_args.push(ParsedArgument::FileName(file_name.to_string()));
+
+    // message
+    let message = args.collect::<Vec<&str>>().join(" ");
+    parsed_args.push(ParsedArgument::Text(message));
+
+    Ok(parsed_args)
+}
+
+fn parse_file_names(mut args: SplitWhitespace, names: &[&str]) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    for name in names {
+        let file_name = args.next().ok_or_else(|| ParseError::Empty(name.to_string()))?;
+        parsed_args.push(ParsedArgument::FileName(file_name.to_string()));
+    }
+
+    Ok(parsed_args)
+}
+
+fn parse_account_name(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
+    let mut parsed_args = Vec::new();
+
+    let name = args
+        .next()
+        .ok_or_else(|| ParseError::Empty("account name".to_string()))?;
+    parsed_args.push(ParsedArgument::Text(name.to_string()));
+
+    Ok(parsed_args)
+}
+
 fn parse_export_utxos(mut args: SplitWhitespace) -> Result<Vec<ParsedArgument>, ParseError> {
     let mut parsed_args = Vec::new();
 
@@ -402,5 +473,40 @@ mod test {
         } else {
             panic!("Parsed csv file name is not the same as provided.");
         }
+
+        let command_str = format!(
+            "prepare-unsigned-transaction 999T {} 5uT unsigned.json msg text",
+            public_key
+        );
+        let parsed = parse_command(&command_str).unwrap();
+
+        if let ParsedArgument::Amount(fee_per_gram) = parsed.args[2].clone() {
+            assert_eq!(fee_per_gram, MicroTari::from(5));
+        } else {
+            panic!("Parsed fee per gram is not the same as provided.");
+        }
+        if let ParsedArgument::FileName(file) = parsed.args[3].clone() {
+            assert_eq!(file, "unsigned.json".to_string());
+        } else {
+            panic!("Parsed file name is not the same as provided.");
+        }
+        if let ParsedArgument::Text(msg) = parsed.args[4].clone() {
+            assert_eq!(msg, "msg text");
+        } else {
+            panic!("Parsed message is not the same as provided.");
+        }
+
+        let command_str = "sign-transaction unsigned.json";
+        let parsed = parse_command(command_str);
+        assert!(parsed.is_err());
+
+        let command_str = "sign-transaction unsigned.json signed.json";
+        let parsed = parse_command(command_str).unwrap();
+
+        if let ParsedArgument::FileName(file) = parsed.args[1].clone() {
+            assert_eq!(file, "signed.json".to_string());
+        } else {
+            panic!("Parsed file name is not the same as provided.");
+        }
     }
 }
@@ -29,7 +29,7 @@ use chrono::{DateTime, Utc};
 use futures::{FutureExt, StreamExt};
 use log::*;
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{LineWriter, Write},
     str::FromStr,
     time::{Duration, Instant},
@@ -52,7 +52,11 @@ use tari_core::{
 };
 use tari_crypto::ristretto::pedersen::PedersenCommitmentFactory;
 use tari_wallet::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+        TxId,
+    },
     transaction_service::handle::{TransactionEvent, TransactionServiceHandle},
     util::emoji::EmojiId,
     WalletSqlite,
@@ -81,6 +85,12 @@ pub enum WalletCommand {
     SetBaseNode,
     SetCustomBaseNode,
     ClearCustomBaseNode,
+    PrepareUnsignedTransaction,
+    SignTransaction,
+    ImportSignedTransaction,
+    ExportViewKeys,
+    CreateAccount,
+    ListAccounts,
 }
 
 #[derive(Debug, EnumString, PartialEq, Clone)]
@@ -149,6 +159,66 @@ pub async fn send_one_sided(
         .map_err(CommandError::TransactionServiceError)
 }
 
+/// Prepare a one-sided transaction to a recipient and write it to a file to be signed by an offline wallet
+pub async fn prepare_unsigned_transaction(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    use ParsedArgument::*;
+    let (amount, dest_pubkey, fee_per_gram, file_name, message) =
+        match (&args[0], &args[1], &args[2], &args[3], &args[4]) {
+            (Amount(amount), PublicKey(key), Amount(fee_per_gram), FileName(file_name), Text(message)) => {
+                Ok((*amount, key.clone(), *fee_per_gram, file_name.clone(), message.clone()))
+            },
+            _ => Err(CommandError::Argument),
+        }?;
+
+    let unsigned = wallet_transaction_service
+        .prepare_unsigned_transaction(dest_pubkey, amount, fee_per_gram, message)
+        .await?;
+    fs::write(&file_name, unsigned.to_json()?).map_err(|e| CommandError::File(e.to_string()))?;
+
+    Ok(unsigned.tx_id)
+}
+
+/// Sign the unsigned transaction in the first file and write the signed transaction to the second file
+pub async fn sign_transaction(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    let (unsigned_file, signed_file) = match (&args[0], &args[1]) {
+        (ParsedArgument::FileName(unsigned_file), ParsedArgument::FileName(signed_file)) => {
+            Ok((unsigned_file, signed_file))
+        },
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let json = fs::read_to_string(unsigned_file).map_err(|e| CommandError::File(e.to_string()))?;
+    let signed = wallet_transaction_service
+        .sign_transaction(UnsignedTransaction::from_json(&json)?)
+        .await?;
+    fs::write(signed_file, signed.to_json()?).map_err(|e| CommandError::File(e.to_string()))?;
+
+    Ok(signed.tx_id)
+}
+
+/// Import a transaction that was signed offline from a file and broadcast it
+pub async fn import_signed_transaction(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    args: Vec<ParsedArgument>,
+) -> Result<TxId, CommandError> {
+    let signed_file = match &args[0] {
+        ParsedArgument::FileName(signed_file) => Ok(signed_file),
+        _ => Err(CommandError::Argument),
+    }?;
+
+    let json = fs::read_to_string(signed_file).map_err(|e| CommandError::File(e.to_string()))?;
+    wallet_transaction_service
+        .import_signed_transaction(SignedTransaction::from_json(&json)?)
+        .await
+        .map_err(CommandError::TransactionServiceError)
+}
+
 pub async fn coin_split(
     args: &[ParsedArgument],
     output_service: &mut OutputManagerHandle,
@@ -587,6 +657,37 @@ pub async fn command_runner(
                     .await?;
                 println!("Custom base node peer saved in wallet database.");
             },
+            PrepareUnsignedTransaction => {
+                let tx_id = prepare_unsigned_transaction(transaction_service.clone(), parsed.args).await?;
+                println!("Prepared unsigned transaction (TxId: {})", tx_id);
+            },
+            SignTransaction => {
+                let tx_id = sign_transaction(transaction_service.clone(), parsed.args).await?;
+                println!("Signed transaction (TxId: {})", tx_id);
+            },
+            ImportSignedTransaction => {
+                let tx_id = import_signed_transaction(transaction_service.clone(), parsed.args).await?;
+                debug!(target: LOG_TARGET, "import-signed-transaction tx_id {}", tx_id);
+                tx_ids.push(tx_id);
+            },
+            ExportViewKeys => {
+                let view_keys = output_service.get_rewind_public_keys().await?;
+                println!("View keys: {}", view_keys.to_view_key_hex());
+            },
+            CreateAccount => {
+                let name = match parsed.args[0].clone() {
+                    ParsedArgument::Text(name) => Ok(name),
+                    _ => Err(CommandError::Argument),
+                }?;
+                output_service.create_account(name.clone()).await?;
+                println!("Created account `{}`", name);
+            },
+            ListAccounts => {
+                for account in output_service.get_accounts().await? {
+                    let balance = output_service.get_account_balance(account.clone()).await?;
+                    println!("{}: {}", account, balance);
+                }
+            },
             ClearCustomBaseNode => {
                 wallet
                     .db
@@ -55,6 +55,8 @@ pub enum CommandError {
     Comms(String),
     #[error("CSV file error `{0}`")]
     CSVFile(String),
+    #[error("File error `{0}`")]
+    File(String),
     #[error("Wallet error `{0}`")]
     WalletError(#[from] WalletError),
     #[error("Wallet storage error `{0}`")]
@@ -4,10 +4,15 @@ use std::convert::TryFrom;
 use tari_app_grpc::{
     conversions::naive_datetime_to_timestamp,
     tari_rpc::{
-        payment_recipient::PaymentType,
+        payment_recipient::{PaymentType, UtxoSelectionStrategy},
         wallet_server,
+        Account,
         CoinSplitRequest,
         CoinSplitResponse,
+        CreateAccountRequest,
+        CreateAccountResponse,
+        GetAccountsRequest,
+        GetAccountsResponse,
         GetBalanceRequest,
         GetBalanceResponse,
         GetCoinbaseRequest,
@@ -20,8 +25,16 @@ use tari_app_grpc::{
         GetTransactionInfoResponse,
         GetVersionRequest,
         GetVersionResponse,
+        ImportSignedTransactionRequest,
+        ImportSignedTransactionResponse,
         ImportUtxosRequest,
         ImportUtxosResponse,
+        PrepareUnsignedTransactionRequest,
+        PrepareUnsignedTransactionResponse,
+        SetReceivingAccountRequest,
+        SetReceivingAccountResponse,
+        SignTransactionRequest,
+        SignTransactionResponse,
         TransactionDirection,
         TransactionInfo,
         TransactionStatus,
@@ -36,7 +49,12 @@ use tari_core::{
     transactions::{tari_amount::MicroTari, transaction::UnblindedOutput, types::Signature},
 };
 use tari_wallet::{
-    output_manager_service::handle::OutputManagerHandle,
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+        service::{Balance, UTXOSelectionStrategy},
+        DEFAULT_ACCOUNT,
+    },
     transaction_service::{handle::TransactionServiceHandle, storage::models},
     WalletSqlite,
 };
@@ -84,18 +102,15 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
-    async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+    async fn get_balance(&self, request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        let account = account_or_default(request.into_inner().account);
         let mut output_service = self.get_output_manager_service();
         let balance;
-        match output_service.get_balance().await {
+        match output_service.get_account_balance(account).await {
             Ok(b) => balance = b,
             Err(e) => return Err(Status::not_found(format!("GetBalance error! {}", e))),
         }
-        Ok(Response::new(GetBalanceResponse {
-            available_balance: balance.available_balance.0,
-            pending_incoming_balance: balance.pending_incoming_balance.0,
-            pending_outgoing_balance: balance.pending_outgoing_balance.0,
-        }))
+        Ok(Response::new(convert_balance(balance)))
     }
 
     async fn get_coinbase(
@@ -126,6 +141,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map(|(idx, dest)| -> Result<_, String> {
                 let pk = CommsPublicKey::from_hex(&dest.address)
                     .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                let utxo_selection = convert_utxo_selection_strategy(dest.utxo_selection_strategy)
+                    .ok_or_else(|| format!("UTXO selection strategy at index {} is invalid", idx))?;
                 Ok((
                     dest.address,
                     pk,
@@ -133,6 +150,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     dest.fee_per_gram,
                     dest.message,
                     dest.payment_type,
+                    utxo_selection,
+                    account_or_default(dest.account),
                 ))
             })
             .collect::<Result<Vec<_>, _>>()
@@ -140,14 +159,23 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
         let mut standard_transfers = Vec::new();
         let mut one_sided_transfers = Vec::new();
-        for (address, pk, amount, fee_per_gram, message, payment_type) in recipients.into_iter() {
+        for (address, pk, amount, fee_per_gram, message, payment_type, utxo_selection, account) in
+            recipients.into_iter()
+        {
             let mut transaction_service = self.get_transaction_service();
             if payment_type == PaymentType::StandardMimblewimble as i32 {
                 standard_transfers.push(async move {
                     (
                         address,
                         transaction_service
-                            .send_transaction(pk, amount.into(), fee_per_gram.into(), message)
+                            .send_transaction_from_account(
+                                pk,
+                                amount.into(),
+                                fee_per_gram.into(),
+                                message,
+                                utxo_selection,
+                                account,
+                            )
                             .await,
                     )
                 });
@@ -156,7 +184,14 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     (
                         address,
                         transaction_service
-                            .send_one_sided_transaction(pk, amount.into(), fee_per_gram.into(), message)
+                            .send_one_sided_transaction_from_account(
+                                pk,
+                                amount.into(),
+                                fee_per_gram.into(),
+                                message,
+                                utxo_selection,
+                                account,
+                            )
                             .await,
                     )
                 });
@@ -230,17 +265,20 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
     async fn get_completed_transactions(
         &self,
-        _request: Request<GetCompletedTransactionsRequest>,
+        request: Request<GetCompletedTransactionsRequest>,
     ) -> Result<Response<Self::GetCompletedTransactionsStream>, Status> {
         debug!(
             target: LOG_TARGET,
             "Incoming GRPC request for GetAllCompletedTransactions"
         );
+        let account = request.into_inner().account;
         let mut transaction_service = self.get_transaction_service();
-        let transactions = transaction_service
-            .get_completed_transactions()
-            .await
-            .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+        let transactions = if account.is_empty() {
+            transaction_service.get_completed_transactions().await
+        } else {
+            transaction_service.get_account_completed_transactions(account).await
+        }
+        .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
 
         let (mut sender, receiver) = mpsc::channel(transactions.len());
         task::spawn(async move {
@@ -265,6 +303,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         message: txn.message,
                         valid: txn.valid,
                         is_found: true,
+                        account: txn.account,
                     }),
                 };
                 match sender.send(Ok(response)).await {
@@ -298,12 +337,13 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let mut wallet = self.wallet.clone();
 
         let tx_id = wallet
-            .coin_split(
+            .coin_split_from_account(
                 MicroTari::from(message.amount_per_split),
                 message.split_count as usize,
                 MicroTari::from(message.fee_per_gram),
                 message.message,
                 lock_height,
+                account_or_default(message.account),
             )
             .await
             .map_err(|e| Status::internal(format!("{:?}", e)))?;
@@ -338,6 +378,114 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
         Ok(Response::new(ImportUtxosResponse { tx_ids }))
     }
+
+    async fn prepare_unsigned_transaction(
+        &self,
+        request: Request<PrepareUnsignedTransactionRequest>,
+    ) -> Result<Response<PrepareUnsignedTransactionResponse>, Status> {
+        let message = request.into_inner();
+        let pk = CommsPublicKey::from_hex(&message.address)
+            .map_err(|_| Status::invalid_argument("Destination address is malformed"))?;
+
+        let unsigned = self
+            .get_transaction_service()
+            .prepare_unsigned_transaction(pk, message.amount.into(), message.fee_per_gram.into(), message.message)
+            .await
+            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+        let unsigned_transaction = unsigned.to_json().map_err(|e| Status::internal(format!("{:?}", e)))?;
+
+        Ok(Response::new(PrepareUnsignedTransactionResponse {
+            tx_id: unsigned.tx_id,
+            unsigned_transaction,
+        }))
+    }
+
+    async fn sign_transaction(
+        &self,
+        request: Request<SignTransactionRequest>,
+    ) -> Result<Response<SignTransactionResponse>, Status> {
+        let message = request.into_inner();
+        let unsigned = UnsignedTransaction::from_json(&message.unsigned_transaction)
+            .map_err(|e| Status::invalid_argument(format!("{:?}", e)))?;
+
+        let signed = self
+            .get_transaction_service()
+            .sign_transaction(unsigned)
+            .await
+            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+        let signed_transaction = signed.to_json().map_err(|e| Status::internal(format!("{:?}", e)))?;
+
+        Ok(Response::new(SignTransactionResponse {
+            tx_id: signed.tx_id,
+            signed_transaction,
+        }))
+    }
+
+    async fn import_signed_transaction(
+        &self,
+        request: Request<ImportSignedTransactionRequest>,
+    ) -> Result<Response<ImportSignedTransactionResponse>, Status> {
+        let message = request.into_inner();
+        let signed = SignedTransaction::from_json(&message.signed_transaction)
+            .map_err(|e| Status::invalid_argument(format!("{:?}", e)))?;
+
+        let tx_id = self
+            .get_transaction_service()
+            .import_signed_transaction(signed)
+            .await
+            .map_err(|e| Status::internal(format!("{:?}", e)))?;
+
+        Ok(Response::new(ImportSignedTransactionResponse { tx_id }))
+    }
+
+    async fn create_account(
+        &self,
+        request: Request<CreateAccountRequest>,
+    ) -> Result<Response<CreateAccountResponse>, Status> {
+        let message = request.into_inner();
+        self.get_output_manager_service()
+            .create_account(message.name)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(CreateAccountResponse {}))
+    }
+
+    async fn get_accounts(&self, _: Request<GetAccountsRequest>) -> Result<Response<GetAccountsResponse>, Status> {
+        let mut output_service = self.get_output_manager_service();
+        let names = output_service
+            .get_accounts()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let mut accounts = Vec::with_capacity(names.len());
+        for name in names {
+            let balance = output_service
+                .get_account_balance(name.clone())
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            accounts.push(Account {
+                name,
+                balance: Some(convert_balance(balance)),
+            });
+        }
+
+        Ok(Response::new(GetAccountsResponse { accounts }))
+    }
+
+    async fn set_receiving_account(
+        &self,
+        request: Request<SetReceivingAccountRequest>,
+    ) -> Result<Response<SetReceivingAccountResponse>, Status> {
+        let message = request.into_inner();
+        let mut wallet = self.wallet.clone();
+        wallet
+            .set_receiving_account(message.name)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(SetReceivingAccountResponse {}))
+    }
 }
 
 fn convert_wallet_transaction_into_transaction_info(
@@ -361,6 +509,7 @@ fn convert_wallet_transaction_into_transaction_info(
             message: tx.message,
             valid: true,
             is_found: true,
+            account: tx.account,
         },
         PendingOutbound(tx) => TransactionInfo {
             tx_id: tx.tx_id,
@@ -376,6 +525,7 @@ fn convert_wallet_transaction_into_transaction_info(
             message: tx.message,
             valid: true,
             is_found: true,
+            account: tx.account,
         },
         Completed(tx) => TransactionInfo {
             tx_id: tx.tx_id,
@@ -395,6 +545,61 @@ fn convert_wallet_transaction_into_transaction_info(
             message: tx.message,
             valid: tx.valid,
             is_found: true,
+            account: tx.account,
         },
     }
 }
+
+fn convert_balance(balance: Balance) -> GetBalanceResponse {
+    GetBalanceResponse {
+        available_balance: balance.available_balance.0,
+        pending_incoming_balance: balance.pending_incoming_balance.0,
+        pending_outgoing_balance: balance.pending_outgoing_balance.0,
+    }
+}
+
+/// An empty account name in a request refers to the default account
+fn account_or_default(account: String) -> String {
+    if account.is_empty() {
+        DEFAULT_ACCOUNT.to_string()
+    } else {
+        account
+    }
+}
+
+/// Converts the gRPC UTXO selection strategy. Returns None if the value is not a known strategy, and `Some(None)` if
+/// the wallet should choose the strategy.
+fn convert_utxo_selection_strategy(value: i32) -> Option<Option<UTXOSelectionStrategy>> {
+    let strategy = match UtxoSelectionStrategy::from_i32(value)? {
+        UtxoSelectionStrategy::Default => None,
+        UtxoSelectionStrategy::Smallest => Some(UTXOSelectionStrategy::Smallest),
+        UtxoSelectionStrategy::MaturityThenSmallest => Some(UTXOSelectionStrategy::MaturityThenSmallest),
+        UtxoSelectionStrategy::Largest => Some(UTXOSelectionStrategy::Largest),
+        UtxoSelectionStrategy::BranchAndBound => Some(UTXOSelectionStrategy::BranchAndBound),
+        UtxoSelectionStrategy::ConsolidateWhenCheap => Some(UTXOSelectionStrategy::ConsolidateWhenCheap),
+        UtxoSelectionStrategy::Privacy => Some(UTXOSelectionStrategy::Privacy),
+    };
+    Some(strategy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_converts_utxo_selection_strategies() {
+        assert_eq!(
+            convert_utxo_selection_strategy(UtxoSelectionStrategy::Default as i32),
+            Some(None)
+        );
+        assert_eq!(
+            convert_utxo_selection_strategy(UtxoSelectionStrategy::Privacy as i32),
+            Some(Some(UTXOSelectionStrategy::Privacy))
+        );
+        assert_eq!(
+            convert_utxo_selection_strategy(UtxoSelectionStrategy::BranchAndBound as i32),
+            Some(Some(UTXOSelectionStrategy::BranchAndBound))
+        );
+        assert_eq!(convert_utxo_selection_strategy(100), None);
+    }
+}
@@ -47,7 +47,7 @@ use tari_shutdown::ShutdownSignal;
 use tari_wallet::{
     base_node_service::config::BaseNodeServiceConfig,
     error::{WalletError, WalletStorageError},
-    output_manager_service::{config::OutputManagerServiceConfig, TxoValidationType},
+    output_manager_service::{config::OutputManagerServiceConfig, handle::PublicRewindKeys, TxoValidationType},
     storage::{database::WalletDatabase, sqlite_utilities::initialize_sqlite_database_backends},
     transaction_service::{
         config::{TransactionRoutingMechanism, TransactionServiceConfig},
@@ -119,7 +119,7 @@ pub async fn change_password(
     arg_password: Option<String>,
     shutdown_signal: ShutdownSignal,
 ) -> Result<(), ExitCodes> {
-    let mut wallet = init_wallet(config, arg_password, None, None, shutdown_signal).await?;
+    let mut wallet = init_wallet(config, arg_password, None, None, None, shutdown_signal).await?;
 
     let passphrase = prompt_password("New wallet password: ")?;
     let confirmed = prompt_password("Confirm new password: ")?;
@@ -248,6 +248,7 @@ pub async fn init_wallet(
     arg_password: Option<String>,
     seed_words_file_name: Option<PathBuf>,
     recovery_master_key: Option<PrivateKey>,
+    watch_only_keys: Option<PublicRewindKeys>,
     shutdown_signal: ShutdownSignal,
 ) -> Result<WalletSqlite, ExitCodes> {
     fs::create_dir_all(
@@ -290,6 +291,12 @@ pub async fn init_wallet(
         "Databases Initialized. Wallet encrypted? {}.", wallet_encrypted
     );
 
+    if let Some(keys) = watch_only_keys {
+        wallet_db.set_watch_only_keys(keys).await?;
+        info!(target: LOG_TARGET, "Created a watch-only wallet.");
+    }
+    let watch_only = wallet_db.get_watch_only_keys().await?.is_some();
+
     let node_address = match wallet_db.get_node_address().await? {
         None => config.public_address.clone(),
         Some(a) => a,
@@ -317,6 +324,7 @@ pub async fn init_wallet(
 
     let comms_config = CommsConfig {
         network: config.network,
+        custom_network_byte: config.custom_network.as_ref().map(|network| network.network_byte),
         node_identity,
         user_agent: format!("tari/wallet/{}", env!("CARGO_PKG_VERSION")),
         transport_type,
@@ -425,10 +433,11 @@ pub async fn init_wallet(
 
         debug!(target: LOG_TARGET, "Wallet encrypted.");
 
-        if interactive && recovery_master_key.is_none() {
+        // A watch-only wallet has no seed words of its own
+        if interactive && recovery_master_key.is_none() && !watch_only {
             confirm_seed_words(&mut wallet).await?;
         }
-        if let Some(file_name) = seed_words_file_name {
+        if let Some(file_name) = seed_words_file_name.filter(|_| !watch_only) {
             let seed_words = wallet.output_manager_service.get_seed_words().await?.join(" ");
             let _ = fs::write(file_name, seed_words)
                 .map_err(|e| ExitCodes::WalletError(format!("Problem writing seed words to file: {}", e)));
@@ -576,6 +585,22 @@ pub fn tari_splash_screen(heading: &str) {
 pub(crate) fn boot(bootstrap: &ConfigBootstrap, config: &GlobalConfig) -> Result<WalletBoot, ExitCodes> {
     let wallet_exists = config.console_wallet_db_file.exists();
 
+    // watch-only wallet from exported view keys
+    if bootstrap.watch_only.is_some() {
+        if bootstrap.recovery {
+            return Err(ExitCodes::InputError(
+                "A watch-only wallet cannot be recovered from seed words".to_string(),
+            ));
+        }
+        if wallet_exists {
+            return Err(ExitCodes::WalletError(format!(
+                "Wallet already exists at {:#?}. A watch-only wallet can only be created in a new directory!",
+                config.console_wallet_db_file
+            )));
+        }
+        return Ok(WalletBoot::New);
+    }
+
     // forced recovery
     if bootstrap.recovery {
         if wallet_exists {
@@ -25,6 +25,7 @@ use tari_app_utilities::{consts, initialization::init_configuration, utilities::
 use tari_common::{configuration::bootstrap::ApplicationType, ConfigBootstrap};
 use tari_core::transactions::types::PrivateKey;
 use tari_shutdown::Shutdown;
+use tari_wallet::output_manager_service::handle::PublicRewindKeys;
 use wallet_modes::{command_mode, grpc_mode, recovery_mode, script_mode, tui_mode, WalletMode};
 
 pub const LOG_TARGET: &str = "wallet::console_wallet::main";
@@ -85,6 +86,7 @@ fn main_inner() -> Result<(), ExitCodes> {
     let mut boot_mode = boot(&bootstrap, &global_config)?;
 
     let recovery_master_key: Option<PrivateKey> = get_recovery_master_key(boot_mode, &bootstrap)?;
+    let watch_only_keys = get_watch_only_keys(&bootstrap)?;
 
     if bootstrap.init {
         info!(target: LOG_TARGET, "Default configuration created. Done.");
@@ -108,6 +110,7 @@ fn main_inner() -> Result<(), ExitCodes> {
         arg_password,
         seed_words_file_name,
         recovery_master_key,
+        watch_only_keys,
         shutdown_signal,
     ))?;
 
@@ -185,3 +188,11 @@ fn get_recovery_master_key(
         Ok(None)
     }
 }
+
+fn get_watch_only_keys(bootstrap: &ConfigBootstrap) -> Result<Option<PublicRewindKeys>, ExitCodes> {
+    bootstrap
+        .watch_only
+        .as_ref()
+        .map(|keys| PublicRewindKeys::from_view_key_hex(keys).map_err(|e| ExitCodes::InputError(e.to_string())))
+        .transpose()
+}
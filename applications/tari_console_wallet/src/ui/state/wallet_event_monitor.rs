@@ -83,6 +83,10 @@ impl WalletEventMonitor {
                                         self.trigger_tx_state_refresh(tx_id).await;
                                         notifier.transaction_cancelled(tx_id);
                                     },
+                                    TransactionEvent::TransactionFeeBumped(original_tx_id, tx_id) => {
+                                        self.trigger_tx_state_refresh(original_tx_id).await;
+                                        self.trigger_tx_state_refresh(tx_id).await;
+                                    },
                                     TransactionEvent::ReceivedTransaction(tx_id) |
                                     TransactionEvent::ReceivedTransactionReply(tx_id) |
                                     TransactionEvent::TransactionBroadcast(tx_id) |
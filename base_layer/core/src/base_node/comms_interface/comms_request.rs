@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::NewBlockTemplate,
+    blocks::{NewBlockTemplate, ShortId},
     chain_storage::MmrTree,
     proof_of_work::PowAlgorithm,
     transactions::types::{Commitment, HashOutput, Signature},
@@ -56,6 +56,11 @@ pub enum NodeCommsRequest {
     GetNewBlockTemplate(GetNewBlockTemplateRequest),
     GetNewBlock(NewBlockTemplate),
     FetchKernelByExcessSig(Signature),
+    FetchMempoolTransactionsByShortIds {
+        block_hash: HashOutput,
+        short_id_nonce: u64,
+        short_ids: Vec<ShortId>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -88,6 +93,14 @@ impl Display for NodeCommsRequest {
                 s.get_public_nonce().to_hex(),
                 s.get_signature().to_hex()
             ),
+            FetchMempoolTransactionsByShortIds {
+                block_hash, short_ids, ..
+            } => write!(
+                f,
+                "FetchMempoolTransactionsByShortIds (block={}, n={})",
+                block_hash.to_hex(),
+                short_ids.len()
+            ),
         }
     }
 }
@@ -21,11 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::{block_header::BlockHeader, Block, NewBlockTemplate},
+    blocks::{block_header::BlockHeader, Block, NewBlockTemplate, ShortId},
     chain_storage::HistoricalBlock,
     proof_of_work::Difficulty,
     transactions::{
-        transaction::{TransactionKernel, TransactionOutput},
+        transaction::{Transaction, TransactionKernel, TransactionOutput},
         types::HashOutput,
     },
 };
@@ -52,6 +52,10 @@ pub enum NodeCommsResponse {
     TargetDifficulty(Difficulty),
     FetchHeadersAfterResponse(Vec<BlockHeader>),
     MmrNodes(Vec<HashOutput>, Vec<u8>),
+    FetchMempoolTransactionsByShortIdsResponse {
+        transactions: Vec<Transaction>,
+        not_found: Vec<ShortId>,
+    },
 }
 
 impl Display for NodeCommsResponse {
@@ -79,6 +83,15 @@ impl Display for NodeCommsResponse {
             TargetDifficulty(_) => write!(f, "TargetDifficulty"),
             FetchHeadersAfterResponse(_) => write!(f, "FetchHeadersAfterResponse"),
             MmrNodes(_, _) => write!(f, "MmrNodes"),
+            FetchMempoolTransactionsByShortIdsResponse {
+                transactions,
+                not_found,
+            } => write!(
+                f,
+                "FetchMempoolTransactionsByShortIdsResponse (found={}, not_found={})",
+                transactions.len(),
+                not_found.len()
+            ),
         }
     }
 }
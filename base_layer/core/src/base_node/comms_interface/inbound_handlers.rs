@@ -29,7 +29,7 @@ use crate::{
         },
         OutboundNodeCommsInterface,
     },
-    blocks::{block_header::BlockHeader, Block, NewBlock, NewBlockTemplate},
+    blocks::{block_header::BlockHeader, Block, CompactBlock, NewBlock, NewBlockTemplate, ShortIdHasher},
     chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainBlock},
     consensus::{ConsensusConstants, ConsensusManager},
     mempool::{async_mempool, Mempool},
@@ -348,7 +348,7 @@ where T: BlockchainBackend + 'static
 
                 let mut header = BlockHeader::from_previous(best_block_header.header());
                 let constants = self.consensus_manager.consensus_constants(header.height);
-                header.version = constants.blockchain_version();
+                header.version = self.consensus_manager.header_version(header.height);
                 header.pow.pow_algo = request.algo;
 
                 let constants_weight = constants.get_max_block_weight_excluding_coinbase();
@@ -406,6 +406,22 @@ where T: BlockchainBackend + 'static
 
                 Ok(NodeCommsResponse::TransactionKernels(kernels))
             },
+            NodeCommsRequest::FetchMempoolTransactionsByShortIds {
+                block_hash,
+                short_id_nonce,
+                short_ids,
+            } => {
+                let hasher = ShortIdHasher::new(&block_hash, short_id_nonce);
+                let (transactions, not_found) =
+                    async_mempool::retrieve_by_short_ids(self.mempool.clone(), hasher, short_ids).await?;
+                Ok(NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse {
+                    transactions: transactions
+                        .into_iter()
+                        .map(|tx| Arc::try_unwrap(tx).unwrap_or_else(|tx| (*tx).clone()))
+                        .collect(),
+                    not_found,
+                })
+            },
         }
     }
 
@@ -418,7 +434,7 @@ where T: BlockchainBackend + 'static
         new_block: NewBlock,
         source_peer: NodeId,
     ) -> Result<(), CommsInterfaceError> {
-        let NewBlock { block_hash } = new_block;
+        let NewBlock { block_hash, compact } = new_block;
 
         // Only a single block request can complete at a time.
         // As multiple NewBlock requests arrive from propagation, this semaphore prevents multiple requests to nodes for
@@ -435,6 +451,48 @@ where T: BlockchainBackend + 'static
             return Ok(());
         }
 
+        if let Some(compact) = compact {
+            if compact.header.hash() == block_hash {
+                match self.reconstruct_compact_block(compact, &source_peer).await {
+                    Ok(Some(block)) => {
+                        match self
+                            .handle_block(Arc::new(block), true.into(), Some(source_peer.clone()))
+                            .await
+                        {
+                            Ok(_) => return Ok(()),
+                            // The transactions that were matched to the compact block may not be the ones that were
+                            // mined, so the full block is requested before the block is judged
+                            Err(e) => warn!(
+                                target: LOG_TARGET,
+                                "Block `{}` reconstructed from its compact form was rejected: {}",
+                                block_hash.to_hex(),
+                                e
+                            ),
+                        }
+                    },
+                    Ok(None) => debug!(
+                        target: LOG_TARGET,
+                        "Could not reconstruct block `{}` from its compact form",
+                        block_hash.to_hex()
+                    ),
+                    Err(e) => debug!(
+                        target: LOG_TARGET,
+                        "Could not reconstruct block `{}` from its compact form: {}",
+                        block_hash.to_hex(),
+                        e
+                    ),
+                }
+            } else {
+                // TODO: #banheuristic - peer propagated a compact block that does not match the announced hash
+                debug!(
+                    target: LOG_TARGET,
+                    "Compact block from peer `{}` does not match the announced block hash `{}`",
+                    source_peer.short_str(),
+                    block_hash.to_hex()
+                );
+            }
+        }
+
         debug!(
             target: LOG_TARGET,
             "Block with hash `{}` is unknown. Requesting it from peer `{}`.",
@@ -467,6 +525,61 @@ where T: BlockchainBackend + 'static
         }
     }
 
+    /// Reconstructs a block from its compact form using the transactions in the mempool. Transactions that are not in
+    /// the mempool are requested from the peer that announced the block. Returns None if the block could not be
+    /// reconstructed, in which case the full block should be requested instead.
+    async fn reconstruct_compact_block(
+        &mut self,
+        compact: CompactBlock,
+        source_peer: &NodeId,
+    ) -> Result<Option<Block>, CommsInterfaceError> {
+        let (transactions, missing) = async_mempool::retrieve_by_short_ids(
+            self.mempool.clone(),
+            compact.short_id_hasher(),
+            compact.short_ids.clone(),
+        )
+        .await?;
+        let mut transactions = transactions
+            .into_iter()
+            .map(|tx| Arc::try_unwrap(tx).unwrap_or_else(|tx| (*tx).clone()))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "{} of {} transaction(s) in block `{}` not found in the mempool. Requesting them from peer `{}`.",
+                missing.len(),
+                compact.short_ids.len(),
+                compact.header.hash().to_hex(),
+                source_peer.short_str()
+            );
+            let (mut missing_transactions, not_found) = self
+                .outbound_nci
+                .request_transactions_by_short_ids_from_peer(
+                    compact.header.hash(),
+                    compact.short_id_nonce,
+                    missing,
+                    source_peer.clone(),
+                )
+                .await?;
+            if !not_found.is_empty() {
+                debug!(
+                    target: LOG_TARGET,
+                    "Peer `{}` could not provide {} transaction(s)",
+                    source_peer.short_str(),
+                    not_found.len()
+                );
+                return Ok(None);
+            }
+            transactions.append(&mut missing_transactions);
+        }
+
+        let prev_header = self
+            .blockchain_db
+            .fetch_header_by_block_hash(compact.header.prev_hash.clone())
+            .await?;
+        Ok(compact.reconstruct(&transactions, prev_header.as_ref()))
+    }
+
     /// Handle inbound blocks from remote nodes and local services.
     pub async fn handle_block(
         &self,
@@ -502,16 +615,23 @@ where T: BlockchainBackend + 'static
 
                 self.blockchain_db.cleanup_orphans().await?;
 
+                // Propagate the compact form of the block, so that peers only need to fetch the transactions that are
+                // not in their mempools
+                let new_block = if should_propagate && broadcast.is_true() {
+                    Some(NewBlock::from(block.as_ref()))
+                } else {
+                    None
+                };
+
                 self.publish_block_event(BlockEvent::ValidBlockAdded(block, block_add_result, broadcast));
 
-                if should_propagate && broadcast.is_true() {
+                if let Some(new_block) = new_block {
                     info!(
                         target: LOG_TARGET,
                         "Propagate block ({}) to network.",
                         block_hash.to_hex()
                     );
                     let exclude_peers = source_peer.into_iter().collect();
-                    let new_block = NewBlock::new(block_hash.clone());
                     self.outbound_nci.propagate_block(new_block, exclude_peers).await?;
                 }
                 Ok(block_hash)
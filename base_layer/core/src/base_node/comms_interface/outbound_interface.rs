@@ -22,9 +22,12 @@
 
 use crate::{
     base_node::comms_interface::{error::CommsInterfaceError, NodeCommsRequest, NodeCommsResponse},
-    blocks::{block_header::BlockHeader, NewBlock},
+    blocks::{block_header::BlockHeader, NewBlock, ShortId},
     chain_storage::HistoricalBlock,
-    transactions::{transaction::TransactionOutput, types::HashOutput},
+    transactions::{
+        transaction::{Transaction, TransactionOutput},
+        types::HashOutput,
+    },
 };
 use futures::channel::mpsc::UnboundedSender;
 use log::*;
@@ -228,6 +231,37 @@ impl OutboundNodeCommsInterface {
         }
     }
 
+    /// Fetch the transactions containing the kernels with the provided short IDs of a compact block from the mempool of
+    /// a specific base node. Returns the transactions that were found, along with the short IDs for which the base node
+    /// could not provide a transaction.
+    pub async fn request_transactions_by_short_ids_from_peer(
+        &mut self,
+        block_hash: HashOutput,
+        short_id_nonce: u64,
+        short_ids: Vec<ShortId>,
+        node_id: NodeId,
+    ) -> Result<(Vec<Transaction>, Vec<ShortId>), CommsInterfaceError> {
+        if let NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse {
+            transactions,
+            not_found,
+        } = self
+            .request_sender
+            .call((
+                NodeCommsRequest::FetchMempoolTransactionsByShortIds {
+                    block_hash,
+                    short_id_nonce,
+                    short_ids,
+                },
+                Some(node_id),
+            ))
+            .await??
+        {
+            Ok((transactions, not_found))
+        } else {
+            Err(CommsInterfaceError::UnexpectedApiResponse)
+        }
+    }
+
     /// Transmit a block to remote base nodes, excluding the provided peers.
     pub async fn propagate_block(
         &self,
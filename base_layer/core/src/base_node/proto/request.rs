@@ -30,7 +30,9 @@ use crate::{
             BlockHeights,
             FetchHeadersAfter as ProtoFetchHeadersAfter,
             HashOutputs,
+            ShortIds,
         },
+        utils::try_convert_short_ids,
     },
     transactions::types::{Commitment, HashOutput, Signature},
 };
@@ -82,6 +84,11 @@ impl TryInto<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             FetchKernelByExcessSig(sig) => ci::NodeCommsRequest::FetchKernelByExcessSig(
                 Signature::try_from(sig).map_err(|err: ByteArrayError| err.to_string())?,
             ),
+            FetchMempoolTransactionsByShortIds(request) => ci::NodeCommsRequest::FetchMempoolTransactionsByShortIds {
+                short_ids: try_convert_short_ids(&request.short_ids)?,
+                block_hash: request.block_hash,
+                short_id_nonce: request.short_id_nonce,
+            },
         };
         Ok(request)
     }
@@ -119,6 +126,15 @@ impl From<ci::NodeCommsRequest> for ProtoNodeCommsRequest {
             },
             GetNewBlock(block_template) => ProtoNodeCommsRequest::GetNewBlock(block_template.into()),
             FetchKernelByExcessSig(signature) => ProtoNodeCommsRequest::FetchKernelByExcessSig(signature.into()),
+            FetchMempoolTransactionsByShortIds {
+                block_hash,
+                short_id_nonce,
+                short_ids,
+            } => ProtoNodeCommsRequest::FetchMempoolTransactionsByShortIds(ShortIds {
+                block_hash,
+                short_id_nonce,
+                short_ids: short_ids.iter().map(|short_id| short_id.to_vec()).collect(),
+            }),
         }
     }
 }
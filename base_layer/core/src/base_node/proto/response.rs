@@ -31,6 +31,7 @@ use crate::{
         base_node as base_node_proto,
         base_node::{
             BlockHeaders as ProtoBlockHeaders,
+            FetchMempoolTransactionsResponse as ProtoFetchMempoolTransactionsResponse,
             HistoricalBlocks as ProtoHistoricalBlocks,
             MmrNodes as ProtoMmrNodes,
             NewBlockResponse as ProtoNewBlockResponse,
@@ -38,6 +39,7 @@ use crate::{
             TransactionOutputs as ProtoTransactionOutputs,
         },
         core as core_proto_types,
+        utils::try_convert_short_ids,
     },
     tari_utilities::convert::try_convert_all,
 };
@@ -86,6 +88,12 @@ impl TryInto<ci::NodeCommsResponse> for ProtoNodeCommsResponse {
             },
             TargetDifficulty(difficulty) => ci::NodeCommsResponse::TargetDifficulty(Difficulty::from(difficulty)),
             MmrNodes(response) => ci::NodeCommsResponse::MmrNodes(response.added, response.deleted),
+            FetchMempoolTransactionsByShortIdsResponse(response) => {
+                ci::NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse {
+                    transactions: try_convert_all(response.transactions)?,
+                    not_found: try_convert_short_ids(&response.not_found)?,
+                }
+            },
         };
 
         Ok(response)
@@ -127,6 +135,15 @@ impl From<ci::NodeCommsResponse> for ProtoNodeCommsResponse {
             }),
             TargetDifficulty(difficulty) => ProtoNodeCommsResponse::TargetDifficulty(difficulty.as_u64()),
             MmrNodes(added, deleted) => ProtoNodeCommsResponse::MmrNodes(ProtoMmrNodes { added, deleted }),
+            FetchMempoolTransactionsByShortIdsResponse {
+                transactions,
+                not_found,
+            } => ProtoNodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse(
+                ProtoFetchMempoolTransactionsResponse {
+                    transactions: transactions.into_iter().map(Into::into).collect(),
+                    not_found: not_found.iter().map(|short_id| short_id.to_vec()).collect(),
+                },
+            ),
         }
     }
 }
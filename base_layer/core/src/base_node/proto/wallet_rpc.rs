@@ -48,6 +48,10 @@ pub enum TxSubmissionRejectionReason {
     Orphan,
     TimeLocked,
     ValidationFailed,
+    /// The transaction does not pay enough to replace a conflicting transaction in the mempool
+    FeeTooLow,
+    /// The transaction pays less than the current minimum fee per gram of the mempool
+    BelowMinimumFee,
 }
 
 impl Display for TxSubmissionRejectionReason {
@@ -58,6 +62,8 @@ impl Display for TxSubmissionRejectionReason {
             TxSubmissionRejectionReason::Orphan => "Orphan",
             TxSubmissionRejectionReason::TimeLocked => "Time Locked",
             TxSubmissionRejectionReason::ValidationFailed => "Validation Failed",
+            TxSubmissionRejectionReason::FeeTooLow => "Fee Too Low",
+            TxSubmissionRejectionReason::BelowMinimumFee => "Below Minimum Fee",
             TxSubmissionRejectionReason::None => "None",
         };
         fmt.write_str(&response)
@@ -76,6 +82,8 @@ impl TryFrom<proto::TxSubmissionRejectionReason> for TxSubmissionRejectionReason
             Orphan => TxSubmissionRejectionReason::Orphan,
             TimeLocked => TxSubmissionRejectionReason::TimeLocked,
             ValidationFailed => TxSubmissionRejectionReason::ValidationFailed,
+            FeeTooLow => TxSubmissionRejectionReason::FeeTooLow,
+            BelowMinimumFee => TxSubmissionRejectionReason::BelowMinimumFee,
         })
     }
 }
@@ -90,6 +98,8 @@ impl From<TxSubmissionRejectionReason> for proto::TxSubmissionRejectionReason {
             Orphan => proto::TxSubmissionRejectionReason::Orphan,
             TimeLocked => proto::TxSubmissionRejectionReason::TimeLocked,
             ValidationFailed => proto::TxSubmissionRejectionReason::ValidationFailed,
+            FeeTooLow => proto::TxSubmissionRejectionReason::FeeTooLow,
+            BelowMinimumFee => proto::TxSubmissionRejectionReason::BelowMinimumFee,
         }
     }
 }
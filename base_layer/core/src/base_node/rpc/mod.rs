@@ -26,7 +26,10 @@ mod service;
 use crate::base_node::StateMachineHandle;
 use crate::proto::{
     base_node::{
+        FeeEstimateRequest,
+        FeeEstimateResponse,
         FetchMatchingUtxos,
+        FetchSpendingBlocksResponse,
         FetchUtxosResponse,
         Signatures,
         TipInfoResponse,
@@ -72,6 +75,20 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
 
     #[rpc(method = 5)]
     async fn get_tip_info(&self, request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus>;
+
+    #[rpc(method = 6)]
+    async fn get_fee_estimate(
+        &self,
+        request: Request<FeeEstimateRequest>,
+    ) -> Result<Response<FeeEstimateResponse>, RpcStatus>;
+
+    /// Returns the blocks that spent any of the given outputs, along with the outputs each of them created. Unknown and
+    /// unspent outputs are ignored. The base node must have its output index enabled.
+    #[rpc(method = 7)]
+    async fn fetch_spending_blocks(
+        &self,
+        request: Request<FetchMatchingUtxos>,
+    ) -> Result<Response<FetchSpendingBlocksResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
@@ -26,9 +26,13 @@ use crate::{
     mempool::{service::MempoolHandle, TxStorageResponse},
     proto::{
         base_node::{
+            FeeEstimateRequest,
+            FeeEstimateResponse,
             FetchMatchingUtxos,
+            FetchSpendingBlocksResponse,
             FetchUtxosResponse,
             Signatures as SignaturesProto,
+            SpendingBlock,
             TipInfoResponse,
             TxLocation,
             TxQueryBatchResponse,
@@ -43,6 +47,7 @@ use crate::{
 };
 use std::convert::TryFrom;
 use tari_comms::protocol::rpc::{Request, Response, RpcStatus};
+use tari_crypto::tari_utilities::hex::Hex;
 
 const LOG_TARGET: &str = "c::base_node::rpc";
 
@@ -117,7 +122,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             .await
             .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
         {
-            TxStorageResponse::UnconfirmedPool => TxQueryResponse {
+            TxStorageResponse::UnconfirmedPool | TxStorageResponse::UnconfirmedPoolReplaced => TxQueryResponse {
                 location: TxLocation::InMempool as i32,
                 block_hash: None,
                 confirmations: 0,
@@ -128,6 +133,8 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredTimeLocked |
             TxStorageResponse::NotStoredAlreadySpent |
+            TxStorageResponse::NotStoredFeeTooLow |
+            TxStorageResponse::NotStoredBelowMinimumFee |
             TxStorageResponse::NotStored => TxQueryResponse {
                 location: TxLocation::NotStored as i32,
                 block_hash: None,
@@ -164,7 +171,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
             .await
             .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
         {
-            TxStorageResponse::UnconfirmedPool => TxSubmissionResponse {
+            TxStorageResponse::UnconfirmedPool | TxStorageResponse::UnconfirmedPoolReplaced => TxSubmissionResponse {
                 accepted: true,
                 rejection_reason: TxSubmissionRejectionReason::None.into(),
                 is_synced,
@@ -186,6 +193,18 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 rejection_reason: TxSubmissionRejectionReason::ValidationFailed.into(),
                 is_synced,
             },
+            // The transaction double spends a transaction in the mempool without paying enough to replace it
+            TxStorageResponse::NotStoredFeeTooLow => TxSubmissionResponse {
+                accepted: false,
+                rejection_reason: TxSubmissionRejectionReason::FeeTooLow.into(),
+                is_synced,
+            },
+            // The transaction pays less than the current minimum fee of the mempool, which may fall again
+            TxStorageResponse::NotStoredBelowMinimumFee => TxSubmissionResponse {
+                accepted: false,
+                rejection_reason: TxSubmissionRejectionReason::BelowMinimumFee.into(),
+                is_synced,
+            },
             TxStorageResponse::NotStoredAlreadySpent | TxStorageResponse::ReorgPool => {
                 // Is this transaction a double spend or has this transaction been mined?
                 match transaction.first_kernel_excess_sig() {
@@ -325,4 +344,79 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
             is_synced,
         }))
     }
+
+    async fn get_fee_estimate(
+        &self,
+        request: Request<FeeEstimateRequest>,
+    ) -> Result<Response<FeeEstimateResponse>, RpcStatus> {
+        let target_blocks = request.into_message().target_blocks;
+        let state_machine = self.state_machine();
+        let status_watch = state_machine.get_status_info_watch();
+        let is_synced = match (*status_watch.borrow()).state_info {
+            StateInfo::Listening(li) => li.is_synced(),
+            _ => false,
+        };
+
+        let max_block_weight = self
+            .db
+            .inner()
+            .consensus_constants()
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+            .get_max_block_weight_excluding_coinbase();
+        let fee_per_gram = self
+            .mempool()
+            .get_fee_estimate(target_blocks, max_block_weight)
+            .await
+            .map_err(RpcStatus::log_internal_error(LOG_TARGET))?;
+
+        Ok(Response::new(FeeEstimateResponse {
+            fee_per_gram: fee_per_gram.into(),
+            is_synced,
+        }))
+    }
+
+    async fn fetch_spending_blocks(
+        &self,
+        request: Request<FetchMatchingUtxos>,
+    ) -> Result<Response<FetchSpendingBlocksResponse>, RpcStatus> {
+        let message = request.into_message();
+
+        let state_machine = self.state_machine();
+        let status_watch = state_machine.get_status_info_watch();
+        let is_synced = match (*status_watch.borrow()).state_info {
+            StateInfo::Listening(li) => li.is_synced(),
+            _ => false,
+        };
+
+        let db = self.db();
+        let mut blocks: Vec<SpendingBlock> = Vec::new();
+        for output_hash in message.output_hashes {
+            let (block_hash, height) = match db
+                .fetch_output_spent_in_block(output_hash.clone())
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+            {
+                Some(spent_in) => spent_in,
+                None => continue,
+            };
+            if let Some(block) = blocks.iter_mut().find(|b| b.block_hash == block_hash) {
+                block.spent_output_hashes.push(output_hash);
+                continue;
+            }
+
+            let block = db
+                .fetch_block_by_hash(block_hash.clone())
+                .await
+                .map_err(RpcStatus::log_internal_error(LOG_TARGET))?
+                .ok_or_else(|| RpcStatus::not_found(format!("Block {} not found", block_hash.to_hex())))?;
+            blocks.push(SpendingBlock {
+                block_hash,
+                height,
+                spent_output_hashes: vec![output_hash],
+                outputs: block.block().body.outputs().iter().cloned().map(Into::into).collect(),
+            });
+        }
+
+        Ok(Response::new(FetchSpendingBlocksResponse { blocks, is_synced }))
+    }
 }
@@ -25,3 +25,5 @@ pub use error::BlockSyncError;
 
 mod synchronizer;
 pub use synchronizer::BlockSynchronizer;
+
+mod validation_pipeline;
@@ -20,16 +20,16 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use super::error::BlockSyncError;
+use super::{error::BlockSyncError, validation_pipeline::ValidationPipeline};
 use crate::{
-    base_node::sync::{hooks::Hooks, rpc},
+    base_node::sync::{hooks::Hooks, rpc, BlockSyncConfig},
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainBlock},
     proto::base_node::SyncBlocksRequest,
     tari_utilities::{hex::Hex, Hashable},
     transactions::aggregated_body::AggregateBody,
     validation::CandidateBlockBodyValidation,
 };
-use futures::StreamExt;
+use futures::{Future, FutureExt, StreamExt};
 use log::*;
 use num_format::{Locale, ToFormattedString};
 use std::{
@@ -47,6 +47,7 @@ use tokio::task;
 const LOG_TARGET: &str = "c::bn::block_sync";
 
 pub struct BlockSynchronizer<B> {
+    config: BlockSyncConfig,
     db: AsyncBlockchainDb<B>,
     connectivity: ConnectivityRequester,
     sync_peer: Option<PeerConnection>,
@@ -56,12 +57,14 @@ pub struct BlockSynchronizer<B> {
 
 impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
     pub fn new(
+        config: BlockSyncConfig,
         db: AsyncBlockchainDb<B>,
         connectivity: ConnectivityRequester,
         sync_peer: Option<PeerConnection>,
         block_validator: Arc<dyn CandidateBlockBodyValidation<B>>,
     ) -> Self {
         Self {
+            config,
             db,
             connectivity,
             sync_peer,
@@ -156,6 +159,9 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         let mut block_stream = client.sync_blocks(request).await?;
         let mut prev_hash = best_full_block_hash;
         let mut current_block = None;
+        // Stateless validation runs concurrently for up to `validation_window` blocks. Blocks are committed in order
+        // as their validation completes.
+        let mut pipeline = ValidationPipeline::new(self.config.validation_window);
         while let Some(block) = block_stream.next().await {
             let block = block?;
 
@@ -167,8 +173,6 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                     BlockSyncError::ReceivedInvalidBlockBody("Peer sent hash for block header we do not have".into())
                 })?;
 
-            let header_hash = header.hash().clone();
-
             if header.header().prev_hash != prev_hash {
                 return Err(BlockSyncError::PeerSentBlockThatDidNotFormAChain {
                     expected: prev_hash.to_hex(),
@@ -176,7 +180,7 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                 });
             }
 
-            prev_hash = header_hash.clone();
+            prev_hash = header.hash().clone();
 
             let body = block
                 .body
@@ -192,46 +196,19 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
                 body.to_counts_string(),
             );
 
-            let timer = Instant::now();
             let block = Arc::new(header.upgrade_to_chain_block(body));
-            self.validate_block(block.clone()).await?;
-
-            debug!(
-                target: LOG_TARGET,
-                "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
-                timer.elapsed(),
-                block.header().height,
-                block.header().pow_algo(),
-                block.block().body.to_counts_string(),
-            );
-
-            let timer = Instant::now();
-            self.db
-                .write_transaction()
-                .insert_block_body(block.clone())
-                .set_best_block(
-                    block.height(),
-                    header_hash,
-                    block.accumulated_data().total_accumulated_difficulty,
-                )
-                .commit()
-                .await?;
+            pipeline.push(self.validate_block_stateless(block));
 
-            self.hooks
-                .call_on_progress_block_hooks(block.clone(), tip_height, &[peer.clone()]);
+            if pipeline.is_full() {
+                let block = pipeline.next().await.expect("a full pipeline cannot be empty")?;
+                self.commit_block(peer, tip_height, block.clone()).await?;
+                current_block = Some(block);
+            }
+        }
 
-            debug!(
-                target: LOG_TARGET,
-                "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}",
-                block.height(),
-                timer.elapsed(),
-                block
-                    .accumulated_data()
-                    .total_accumulated_difficulty
-                    .to_formatted_string(&Locale::en),
-                block.accumulated_data().accumulated_monero_difficulty,
-                block.accumulated_data().accumulated_sha_difficulty,
-            );
+        while let Some(block) = pipeline.next().await {
+            let block = block?;
+            self.commit_block(peer, tip_height, block.clone()).await?;
             current_block = Some(block);
         }
 
@@ -255,16 +232,92 @@ impl<B: BlockchainBackend + 'static> BlockSynchronizer<B> {
         Ok(())
     }
 
-    async fn validate_block(&self, block: Arc<ChainBlock>) -> Result<(), BlockSyncError> {
+    /// Runs the checks that do not depend on the chain state on the blocking thread pool, returning the block once it
+    /// has passed. The database is only read to find out whether the block is assumed valid, so the read lock is not
+    /// held while the proofs and signatures are verified.
+    fn validate_block_stateless(
+        &self,
+        block: Arc<ChainBlock>,
+    ) -> impl Future<Output = Result<Arc<ChainBlock>, BlockSyncError>> {
+        let validator = self.block_validator.clone();
+        let db = self.db.clone();
+        task::spawn_blocking(move || {
+            let timer = Instant::now();
+            let assumed_valid = {
+                let db = db.inner().db_read_access()?;
+                validator.is_assumed_valid(block.block(), &*db)?
+            };
+            validator.validate_body_stateless(block.block(), assumed_valid)?;
+            debug!(
+                target: LOG_TARGET,
+                "Stateless validation of block body #{} completed in {:.0?}",
+                block.height(),
+                timer.elapsed()
+            );
+            Result::<_, BlockSyncError>::Ok(block)
+        })
+        .map(|result| result.expect("block validator panicked"))
+    }
+
+    async fn validate_block_stateful(&self, block: Arc<ChainBlock>) -> Result<(), BlockSyncError> {
         let validator = self.block_validator.clone();
 
         let db = self.db.clone();
         task::spawn_blocking(move || {
             let db = db.inner().db_read_access()?;
-            validator.validate_body(block.block(), &*db)?;
+            validator.validate_body_stateful(block.block(), &*db)?;
             Result::<_, BlockSyncError>::Ok(())
         })
         .await
         .expect("block validator panicked")
     }
+
+    /// Validates the block against the current chain state and commits it. Blocks must be committed in chain order.
+    async fn commit_block(
+        &mut self,
+        peer: &NodeId,
+        tip_height: u64,
+        block: Arc<ChainBlock>,
+    ) -> Result<(), BlockSyncError> {
+        let timer = Instant::now();
+        self.validate_block_stateful(block.clone()).await?;
+
+        debug!(
+            target: LOG_TARGET,
+            "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
+            timer.elapsed(),
+            block.header().height,
+            block.header().pow_algo(),
+            block.block().body.to_counts_string(),
+        );
+
+        let timer = Instant::now();
+        self.db
+            .write_transaction()
+            .insert_block_body(block.clone())
+            .set_best_block(
+                block.height(),
+                block.hash().clone(),
+                block.accumulated_data().total_accumulated_difficulty,
+            )
+            .commit()
+            .await?;
+
+        self.hooks
+            .call_on_progress_block_hooks(block.clone(), tip_height, &[peer.clone()]);
+
+        debug!(
+            target: LOG_TARGET,
+            "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}",
+            block.height(),
+            timer.elapsed(),
+            block
+                .accumulated_data()
+                .total_accumulated_difficulty
+                .to_formatted_string(&Locale::en),
+            block.accumulated_data().accumulated_monero_difficulty,
+            block.accumulated_data().accumulated_sha_difficulty,
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,137 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use futures::{stream::FuturesOrdered, Future, StreamExt};
+
+/// Holds the block validations that are running concurrently during block sync. At most `window` validations are
+/// pending at a time and their outputs are returned in the order the validations were added, so that blocks are
+/// committed in chain order while the blocks after them are still being validated.
+pub(super) struct ValidationPipeline<F: Future> {
+    window: usize,
+    pending: FuturesOrdered<F>,
+}
+
+impl<F: Future> ValidationPipeline<F> {
+    /// Creates a pipeline for the given number of concurrent validations. A window of zero is treated as one.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            pending: FuturesOrdered::new(),
+        }
+    }
+
+    /// Adds a validation that has been started. The caller must take the oldest output with [next](Self::next)
+    /// before adding another validation to a full pipeline.
+    pub fn push(&mut self, validation: F) {
+        debug_assert!(!self.is_full(), "validation pipeline is full");
+        self.pending.push(validation);
+    }
+
+    /// Returns true if the number of pending validations has reached the window
+    pub fn is_full(&self) -> bool {
+        self.pending.len() >= self.window
+    }
+
+    /// Waits for the oldest pending validation and returns its output, or `None` if no validations are pending
+    pub async fn next(&mut self) -> Option<F::Output> {
+        self.pending.next().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{channel::oneshot, future::BoxFuture, FutureExt};
+
+    /// Creates a validation that completes with the result sent on the returned sender
+    fn validation() -> (
+        oneshot::Sender<Result<u64, String>>,
+        BoxFuture<'static, Result<u64, String>>,
+    ) {
+        let (tx, rx) = oneshot::channel();
+        (tx, rx.map(|result| result.expect("sender dropped")).boxed())
+    }
+
+    #[test]
+    fn it_bounds_the_number_of_pending_validations() {
+        let mut pipeline = ValidationPipeline::new(3);
+        let mut senders = Vec::new();
+        for _ in 0..3 {
+            assert!(!pipeline.is_full());
+            let (tx, validation) = validation();
+            pipeline.push(validation);
+            senders.push(tx);
+        }
+        assert!(pipeline.is_full());
+
+        // Taking an output frees a slot
+        senders.remove(0).send(Ok(1)).unwrap();
+        assert_eq!(pipeline.next().now_or_never().unwrap(), Some(Ok(1)));
+        assert!(!pipeline.is_full());
+
+        // A zero window still allows one validation at a time
+        let mut pipeline = ValidationPipeline::new(0);
+        let (_tx, validation) = validation();
+        pipeline.push(validation);
+        assert!(pipeline.is_full());
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_returns_outputs_in_the_order_the_validations_were_added() {
+        let mut pipeline = ValidationPipeline::new(4);
+        let mut senders = Vec::new();
+        for _ in 0..4 {
+            let (tx, validation) = validation();
+            pipeline.push(validation);
+            senders.push(tx);
+        }
+
+        // Validations complete in reverse order
+        for (height, tx) in senders.into_iter().enumerate().rev() {
+            tx.send(Ok(height as u64)).unwrap();
+        }
+        for height in 0..4 {
+            assert_eq!(pipeline.next().await, Some(Ok(height)));
+        }
+        assert_eq!(pipeline.next().await, None);
+    }
+
+    #[tokio_macros::test_basic]
+    async fn it_does_not_return_outputs_past_a_pending_validation() {
+        let mut pipeline = ValidationPipeline::new(3);
+        let (tx1, validation1) = validation();
+        let (tx2, validation2) = validation();
+        let (tx3, validation3) = validation();
+        pipeline.push(validation1);
+        pipeline.push(validation2);
+        pipeline.push(validation3);
+
+        // The third block passes before the second one fails
+        tx3.send(Ok(3)).unwrap();
+        tx1.send(Ok(1)).unwrap();
+        assert_eq!(pipeline.next().await, Some(Ok(1)));
+        assert!(pipeline.next().now_or_never().is_none());
+
+        tx2.send(Err("invalid block 2".to_string())).unwrap();
+        assert_eq!(pipeline.next().await, Some(Err("invalid block 2".to_string())));
+    }
+}
@@ -31,6 +31,8 @@ pub struct BlockSyncConfig {
     pub ban_period: Duration,
     pub short_ban_period: Duration,
     pub sync_peers: Vec<NodeId>,
+    /// The number of downloaded blocks for which the stateless body validation may run concurrently during block sync
+    pub validation_window: usize,
 }
 
 impl Default for BlockSyncConfig {
@@ -42,6 +44,7 @@ impl Default for BlockSyncConfig {
             ban_period: Duration::from_secs(30 * 60),
             short_ban_period: Duration::from_secs(60),
             sync_peers: Default::default(),
+            validation_window: 8,
         }
     }
 }
@@ -61,6 +61,8 @@ pub enum BlockHeaderSyncError {
     NetworkSilence,
     #[error("Invalid protocol response: {0}")]
     InvalidProtocolResponse(String),
+    #[error("Peer chain splits from the local chain at height {split_height}, below checkpoint #{checkpoint_height}")]
+    ChainSplitBelowCheckpoint { split_height: u64, checkpoint_height: u64 },
     #[error("Headers did not form a chain. Expected {actual} to equal the previous hash {expected}")]
     ChainLinkBroken { actual: String, expected: String },
 }
@@ -57,6 +57,7 @@ pub struct HeaderSynchronizer<'a, B> {
     config: BlockSyncConfig,
     db: AsyncBlockchainDb<B>,
     header_validator: BlockHeaderSyncValidator<B>,
+    consensus_rules: ConsensusManager,
     connectivity: ConnectivityRequester,
     sync_peers: &'a [NodeId],
     hooks: Hooks,
@@ -73,7 +74,8 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
     ) -> Self {
         Self {
             config,
-            header_validator: BlockHeaderSyncValidator::new(db.clone(), consensus_rules, randomx_factory),
+            header_validator: BlockHeaderSyncValidator::new(db.clone(), consensus_rules.clone(), randomx_factory),
+            consensus_rules,
             db,
             connectivity,
             sync_peers,
@@ -388,6 +390,23 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             ));
         }
 
+        // Reject a chain that forks off below a checkpoint that the local chain has already passed
+        let split_height = local_tip_header.height().saturating_sub(steps_back);
+        if let Some(checkpoint_height) = self.consensus_rules.last_checkpoint_height(local_tip_header.height()) {
+            if split_height < checkpoint_height {
+                let _ = self
+                    .ban_peer_long(peer.clone(), BanReason::ChainSplitBelowCheckpoint {
+                        split_height,
+                        checkpoint_height,
+                    })
+                    .await;
+                return Err(BlockHeaderSyncError::ChainSplitBelowCheckpoint {
+                    split_height,
+                    checkpoint_height,
+                });
+            }
+        }
+
         // If the peer returned no new headers, this means header sync is done.
         if headers.is_empty() {
             if fork_hash_index > 0 {
@@ -434,7 +453,6 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         );
 
         // Basic sanity check that the peer sent tip height greater than the split.
-        let split_height = local_tip_header.height().saturating_sub(steps_back);
         if remote_tip_height < split_height {
             self.ban_peer_short(peer.clone(), BanReason::PeerSentInvalidTipHeight {
                 actual: remote_tip_height,
@@ -659,6 +677,8 @@ enum BanReason {
     ValidationFailed(#[from] ValidationError),
     #[error("Peer could not find the location of a chain split")]
     ChainSplitNotFound,
+    #[error("Peer chain splits at height {split_height}, below checkpoint #{checkpoint_height}")]
+    ChainSplitBelowCheckpoint { split_height: u64, checkpoint_height: u64 },
     #[error("Failed to synchronize headers from peer: {0}")]
     GeneralHeaderSyncFailure(BlockHeaderSyncError),
     #[error("Peer did not respond timeously during RPC negotiation")]
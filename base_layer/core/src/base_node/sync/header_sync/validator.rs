@@ -33,14 +33,18 @@ use crate::{
     },
     common::rolling_vec::RollingVec,
     consensus::ConsensusManager,
-    proof_of_work::{randomx_factory::RandomXFactory, PowAlgorithm},
+    proof_of_work::{randomx_factory::RandomXFactory, PowAlgorithmRegistry, PowError},
     tari_utilities::{epoch_time::EpochTime, hash::Hashable, hex::Hex},
     transactions::types::HashOutput,
-    validation::helpers::{
-        check_header_timestamp_greater_than_median,
-        check_pow_data,
-        check_target_difficulty,
-        check_timestamp_ftl,
+    validation::{
+        helpers::{
+            check_header_checkpoint,
+            check_header_timestamp_greater_than_median,
+            check_pow_data,
+            check_target_difficulty,
+            check_timestamp_ftl,
+        },
+        ValidationError,
     },
 };
 use log::*;
@@ -53,7 +57,7 @@ pub struct BlockHeaderSyncValidator<B> {
     db: AsyncBlockchainDb<B>,
     state: Option<State>,
     consensus_rules: ConsensusManager,
-    randomx_factory: RandomXFactory,
+    pow_algorithms: PowAlgorithmRegistry,
 }
 
 #[derive(Debug, Clone)]
@@ -71,7 +75,7 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             db,
             state: None,
             consensus_rules,
-            randomx_factory,
+            pow_algorithms: PowAlgorithmRegistry::new(randomx_factory),
         }
     }
 
@@ -98,10 +102,13 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             })?;
         debug!(
             target: LOG_TARGET,
-            "Setting header validator state ({} timestamp(s), target difficulties: {} SHA3, {} Monero)",
+            "Setting header validator state ({} timestamp(s), target difficulties: {})",
             timestamps.len(),
-            target_difficulties.get(PowAlgorithm::Sha3).len(),
-            target_difficulties.get(PowAlgorithm::Monero).len(),
+            target_difficulties
+                .iter()
+                .map(|(algo, window)| format!("{} {}", window.len(), algo))
+                .collect::<Vec<_>>()
+                .join(", "),
         );
         self.state = Some(State {
             current_height: start_header.height,
@@ -132,16 +139,27 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
         }
         check_timestamp_ftl(&header, &self.consensus_rules)?;
 
+        check_header_checkpoint(&header, &self.consensus_rules)?;
+
         check_header_timestamp_greater_than_median(&header, &state.timestamps)?;
 
         let constants = self.consensus_rules.consensus_constants(header.height);
-        let target_difficulty = state.target_difficulties.get(header.pow_algo()).calculate(
-            constants.min_pow_difficulty(header.pow_algo()),
-            constants.max_pow_difficulty(header.pow_algo()),
-        );
-        let achieved_target = check_target_difficulty(&header, target_difficulty, &self.randomx_factory)?;
-
-        check_pow_data(&header, &self.consensus_rules, &*self.db.inner().db_read_access()?)?;
+        let target_difficulty = state
+            .target_difficulties
+            .get(header.pow_algo())
+            .ok_or_else(|| ValidationError::from(PowError::UnsupportedAlgorithm(header.pow_algo())))?
+            .calculate(
+                constants.min_pow_difficulty(header.pow_algo()),
+                constants.max_pow_difficulty(header.pow_algo()),
+            );
+        let achieved_target = check_target_difficulty(&header, target_difficulty, &self.pow_algorithms)?;
+
+        check_pow_data(
+            &header,
+            &self.pow_algorithms,
+            &self.consensus_rules,
+            &*self.db.inner().db_read_access()?,
+        )?;
 
         // Header is valid, add this header onto the validation state for the next round
         // Mutable borrow done later in the function to allow multiple immutable borrows before this line. This has
@@ -159,7 +177,10 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
 
         state.current_height = header.height;
         // Add a "more recent" datapoint onto the target difficulty
-        state.target_difficulties.add_back(&header, target_difficulty);
+        state
+            .target_difficulties
+            .add_back(&header, target_difficulty)
+            .map_err(ValidationError::from)?;
 
         let accumulated_data = BlockHeaderAccumulatedData::builder(&state.previous_accum)
             .with_hash(header.hash())
@@ -239,6 +260,7 @@ mod test {
         crypto::tari_utilities::{hex::Hex, Hashable},
         proof_of_work::{randomx_factory::RandomXFactory, PowAlgorithm},
         test_helpers::blockchain::{create_new_blockchain, TempDatabase},
+        validation::ValidationError,
     };
     use tari_common::configuration::Network;
     use tari_test_utils::unpack_enum;
@@ -289,8 +311,8 @@ mod test {
             validator.initialize_state(&tip.header().hash()).await.unwrap();
             let state = validator.state();
             assert!(state.valid_headers.is_empty());
-            assert_eq!(state.target_difficulties.get(PowAlgorithm::Sha3).len(), 2);
-            assert!(state.target_difficulties.get(PowAlgorithm::Monero).is_empty());
+            assert_eq!(state.target_difficulties.get(PowAlgorithm::Sha3).unwrap().len(), 2);
+            assert!(state.target_difficulties.get(PowAlgorithm::Monero).unwrap().is_empty());
             assert_eq!(state.timestamps.len(), 2);
             assert_eq!(state.current_height, 1);
         }
@@ -333,5 +355,33 @@ mod test {
             assert_eq!(actual, 10);
             assert_eq!(expected, 3);
         }
+
+        #[tokio_macros::test_basic]
+        async fn it_fails_if_header_does_not_match_checkpoint() {
+            let rules = ConsensusManager::builder(Network::LocalNet)
+                .with_checkpoint(2, vec![1; 32])
+                .build();
+            let db = create_new_blockchain();
+            let mut validator = BlockHeaderSyncValidator::new(db.clone().into(), rules, RandomXFactory::default());
+            let db = AsyncBlockchainDb::from(db);
+            let tip = db.fetch_tip_header().await.unwrap();
+            validator.initialize_state(tip.hash()).await.unwrap();
+            let next = BlockHeader::from_previous(tip.header());
+            validator.validate(next).unwrap();
+            let tip = validator.valid_headers().last().cloned().unwrap();
+            let next = BlockHeader::from_previous(tip.header());
+            let err = validator.validate(next).unwrap_err();
+            unpack_enum!(BlockHeaderSyncError::ValidationFailed(err) = err);
+            unpack_enum!(
+                ValidationError::CheckpointMismatch {
+                    height,
+                    expected,
+                    actual
+                } = err
+            );
+            assert_eq!(height, 2);
+            assert_eq!(expected, vec![1u8; 32].to_hex());
+            assert_ne!(actual, expected);
+        }
     }
 }
@@ -32,18 +32,27 @@ use crate::{
     transactions::{
         aggregated_body::AggregateBody,
         tari_amount::MicroTari,
-        transaction::{Transaction, TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
-        types::CryptoFactories,
+        transaction::{
+            KernelFeatures,
+            Transaction,
+            TransactionError,
+            TransactionInput,
+            TransactionKernel,
+            TransactionOutput,
+        },
+        types::{CryptoFactories, HashDigest},
     },
 };
+use digest::Digest;
 use log::*;
+use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::{
     fmt,
     fmt::{Display, Formatter},
 };
 use tari_common_types::types::BlockHash;
-use tari_crypto::tari_utilities::Hashable;
+use tari_crypto::tari_utilities::{ByteArray, Hashable};
 use thiserror::Error;
 
 #[derive(Clone, Debug, PartialEq, Error)]
@@ -245,13 +254,19 @@ impl Hashable for Block {
 }
 
 //---------------------------------- NewBlock --------------------------------------------//
+/// The message that is propagated to announce a new block. When it contains the compact form of the block, the receiver
+/// attempts to reconstruct the block from the transactions in its mempool, otherwise it requests the full block.
 pub struct NewBlock {
     pub block_hash: BlockHash,
+    pub compact: Option<CompactBlock>,
 }
 
 impl NewBlock {
     pub fn new(block_hash: BlockHash) -> Self {
-        Self { block_hash }
+        Self {
+            block_hash,
+            compact: None,
+        }
     }
 }
 
@@ -259,6 +274,133 @@ impl From<&Block> for NewBlock {
     fn from(block: &Block) -> Self {
         Self {
             block_hash: block.hash(),
+            compact: Some(CompactBlock::from(block)),
+        }
+    }
+}
+
+/// The number of bytes in the short ID of a kernel in a compact block
+pub const SHORT_ID_LENGTH: usize = 6;
+
+/// The short ID of a kernel in a compact block
+pub type ShortId = [u8; SHORT_ID_LENGTH];
+
+/// Derives the short IDs of the kernels in a compact block. A short ID is a truncated hash of the excess signature of
+/// the kernel, salted with the block hash and a random nonce chosen by the sender of the compact block, so that
+/// transactions cannot be crafted to collide with the short IDs of other transactions.
+#[derive(Clone, Debug)]
+pub struct ShortIdHasher {
+    salt: Vec<u8>,
+}
+
+impl ShortIdHasher {
+    pub fn new(block_hash: &[u8], nonce: u64) -> Self {
+        let salt = HashDigest::new()
+            .chain(block_hash)
+            .chain(nonce.to_le_bytes())
+            .finalize()
+            .to_vec();
+        Self { salt }
+    }
+
+    pub fn short_id(&self, kernel: &TransactionKernel) -> ShortId {
+        let hash = HashDigest::new()
+            .chain(&self.salt)
+            .chain(kernel.excess_sig.get_signature().as_bytes())
+            .finalize();
+        let mut short_id = [0u8; SHORT_ID_LENGTH];
+        short_id.copy_from_slice(&hash[..SHORT_ID_LENGTH]);
+        short_id
+    }
+}
+
+/// A block in which every non-coinbase transaction is identified by the short IDs of its kernels. The coinbase kernels
+/// and outputs cannot be found in a mempool, so they are included in full.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactBlock {
+    pub header: BlockHeader,
+    pub coinbase_kernels: Vec<TransactionKernel>,
+    pub coinbase_outputs: Vec<TransactionOutput>,
+    /// The nonce that salts the short IDs
+    pub short_id_nonce: u64,
+    pub short_ids: Vec<ShortId>,
+}
+
+impl CompactBlock {
+    /// Returns the hasher that derives the short IDs of this compact block
+    pub fn short_id_hasher(&self) -> ShortIdHasher {
+        ShortIdHasher::new(&self.header.hash(), self.short_id_nonce)
+    }
+
+    /// Reconstructs the block from the transactions that contain the announced kernels. Returns None if the
+    /// transactions do not contain exactly the announced kernels, or do not add the number of outputs and kernels to
+    /// the MMRs that the header expects given the header of the previous block.
+    pub fn reconstruct(self, transactions: &[Transaction], prev_header: Option<&BlockHeader>) -> Option<Block> {
+        let hasher = self.short_id_hasher();
+        let CompactBlock {
+            header,
+            mut coinbase_kernels,
+            mut coinbase_outputs,
+            short_ids,
+            ..
+        } = self;
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        let mut kernels = Vec::new();
+        for tx in transactions {
+            inputs.extend(tx.body.inputs().iter().cloned());
+            outputs.extend(tx.body.outputs().iter().cloned());
+            kernels.extend(tx.body.kernels().iter().cloned());
+        }
+
+        if kernels.len() != short_ids.len() ||
+            !kernels
+                .iter()
+                .all(|kernel| short_ids.contains(&hasher.short_id(kernel)))
+        {
+            return None;
+        }
+        outputs.append(&mut coinbase_outputs);
+        kernels.append(&mut coinbase_kernels);
+        if let Some(prev_header) = prev_header {
+            if header.output_mmr_size != prev_header.output_mmr_size + outputs.len() as u64 ||
+                header.kernel_mmr_size != prev_header.kernel_mmr_size + kernels.len() as u64
+            {
+                return None;
+            }
+        }
+
+        let mut body = AggregateBody::new(inputs, outputs, kernels);
+        body.sort();
+        Some(Block::new(header, body))
+    }
+}
+
+impl From<&Block> for CompactBlock {
+    fn from(block: &Block) -> Self {
+        let short_id_nonce = OsRng.next_u64();
+        let hasher = ShortIdHasher::new(&block.hash(), short_id_nonce);
+        let mut coinbase_kernels = Vec::new();
+        let mut short_ids = Vec::new();
+        for kernel in block.body.kernels() {
+            if kernel.features.contains(KernelFeatures::COINBASE_KERNEL) {
+                coinbase_kernels.push(kernel.clone());
+            } else {
+                short_ids.push(hasher.short_id(kernel));
+            }
+        }
+        Self {
+            header: block.header.clone(),
+            coinbase_kernels,
+            coinbase_outputs: block
+                .body
+                .outputs()
+                .iter()
+                .filter(|output| output.is_coinbase())
+                .cloned()
+                .collect(),
+            short_id_nonce,
+            short_ids,
         }
     }
 }
@@ -77,6 +77,10 @@ pub enum BlockHeaderValidationError {
     ProofOfWorkError(#[from] PowError),
     #[error("Monero seed hash too old")]
     OldSeedHash,
+    #[error("Invalid header version {actual}, expected version {expected}")]
+    InvalidVersion { expected: u16, actual: u16 },
+    #[error("Header signals unknown version bits: {0:#06x}")]
+    UnknownVersionBits(u16),
 }
 
 /// The BlockHeader contains all the metadata for the block, including proof of work, a link to the previous block
@@ -0,0 +1,330 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Generates genesis blocks for new networks, along with the Rust source that `genesis_block.rs` uses for the
+//! hardcoded networks.
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    chain_storage::{calculate_genesis_mmr_roots, ChainStorageError},
+    consensus::{ConsensusConstants, ConsensusManager},
+    proof_of_work::PowAlgorithm,
+    transactions::{
+        tari_amount::MicroTari,
+        types::{CryptoFactories, PrivateKey},
+        CoinbaseBuildError,
+        CoinbaseBuilder,
+    },
+    validation::{block_validators::validate_genesis_block, ValidationError},
+};
+use rand::rngs::OsRng;
+use std::fmt::Write;
+use tari_common::configuration::Network;
+use tari_crypto::{
+    keys::SecretKey,
+    tari_utilities::{
+        epoch_time::EpochTime,
+        hex::{to_hex, Hex},
+    },
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GenesisBlockError {
+    #[error("Could not build the coinbase transaction: {0}")]
+    CoinbaseBuildError(#[from] CoinbaseBuildError),
+    #[error("Could not calculate the MMR roots: {0}")]
+    ChainStorageError(#[from] ChainStorageError),
+    #[error("The generated genesis block is invalid: {0}")]
+    ValidationError(#[from] ValidationError),
+}
+
+/// Generates a genesis block containing a single coinbase of `amount` that is spendable with `spend_key`. The MMR
+/// roots are calculated and the block is checked with [validate_genesis_block], so `amount` must equal the block
+/// reward at height 0 of the emission schedule in `consensus_constants`.
+pub fn generate_genesis_block(
+    consensus_constants: &ConsensusConstants,
+    spend_key: PrivateKey,
+    amount: MicroTari,
+    timestamp: EpochTime,
+    factories: &CryptoFactories,
+) -> Result<Block, GenesisBlockError> {
+    let (tx, _) = CoinbaseBuilder::new(factories.clone())
+        .with_block_height(0)
+        .with_fees(0.into())
+        .with_spend_key(spend_key)
+        .with_nonce(PrivateKey::random(&mut OsRng))
+        .build_with_reward(consensus_constants, amount)?;
+
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .with_consensus_constants(consensus_constants.clone())
+        .build();
+    let mut header = BlockHeader::new(rules.header_version(0));
+    header.timestamp = timestamp;
+    header.total_kernel_offset = tx.offset;
+    header.total_script_offset = tx.script_offset;
+    let mut block = Block::new(header, tx.body);
+    block.body.sort();
+
+    let roots = calculate_genesis_mmr_roots(&block)?;
+    block.header.kernel_mr = roots.kernel_mr;
+    block.header.kernel_mmr_size = roots.kernel_mmr_size;
+    block.header.input_mr = roots.input_mr;
+    block.header.output_mr = roots.output_mr;
+    block.header.witness_mr = roots.witness_mr;
+    block.header.output_mmr_size = roots.output_mmr_size;
+
+    validate_genesis_block(&block, &rules, factories)?;
+    Ok(block)
+}
+
+/// Returns the Rust source of a `get_<name>_genesis_block_raw` function that returns the given block, in the form
+/// used by `genesis_block.rs`
+pub fn genesis_block_source(block: &Block, name: &str) -> String {
+    let mut src = String::new();
+    // Writing to a String cannot fail
+    let _ = write_genesis_block_source(&mut src, block, name);
+    src
+}
+
+fn write_genesis_block_source(src: &mut String, block: &Block, name: &str) -> std::fmt::Result {
+    let header = &block.header;
+    writeln!(src, "pub fn get_{}_genesis_block_raw() -> Block {{", name)?;
+    writeln!(src, "    let mut body = AggregateBody::new(")?;
+    writeln!(src, "        vec![],")?;
+    writeln!(src, "        vec![")?;
+    for output in block.body.outputs() {
+        writeln!(src, "            TransactionOutput {{")?;
+        writeln!(src, "                features: OutputFeatures {{")?;
+        writeln!(
+            src,
+            "                    flags: OutputFlags::from_bits({}).unwrap(),",
+            output.features.flags.bits()
+        )?;
+        writeln!(src, "                    maturity: {},", output.features.maturity)?;
+        writeln!(src, "                }},")?;
+        writeln!(
+            src,
+            "                commitment: Commitment::from_hex(\"{}\").unwrap(),",
+            output.commitment.to_hex()
+        )?;
+        writeln!(
+            src,
+            "                proof: BulletRangeProof::from_hex(\"{}\").unwrap(),",
+            output.proof.to_hex()
+        )?;
+        writeln!(
+            src,
+            "                script: TariScript::from_bytes(&from_hex(\"{}\").unwrap()).unwrap(),",
+            to_hex(&output.script.as_bytes())
+        )?;
+        writeln!(
+            src,
+            "                sender_offset_public_key: PublicKey::from_hex(\"{}\").unwrap(),",
+            output.sender_offset_public_key.to_hex()
+        )?;
+        writeln!(src, "                metadata_signature: ComSignature::new(")?;
+        writeln!(
+            src,
+            "                    Commitment::from_hex(\"{}\").unwrap(),",
+            output.metadata_signature.public_nonce().to_hex()
+        )?;
+        writeln!(
+            src,
+            "                    PrivateKey::from_hex(\"{}\").unwrap(),",
+            output.metadata_signature.u().to_hex()
+        )?;
+        writeln!(
+            src,
+            "                    PrivateKey::from_hex(\"{}\").unwrap(),",
+            output.metadata_signature.v().to_hex()
+        )?;
+        writeln!(src, "                ),")?;
+        writeln!(src, "            }},")?;
+    }
+    writeln!(src, "        ],")?;
+    writeln!(src, "        vec![")?;
+    for kernel in block.body.kernels() {
+        writeln!(src, "            TransactionKernel {{")?;
+        writeln!(
+            src,
+            "                features: KernelFeatures::from_bits({}).unwrap(),",
+            kernel.features.bits()
+        )?;
+        writeln!(src, "                fee: MicroTari({}),", kernel.fee.0)?;
+        writeln!(src, "                lock_height: {},", kernel.lock_height)?;
+        writeln!(
+            src,
+            "                excess: Commitment::from_hex(\"{}\").unwrap(),",
+            kernel.excess.to_hex()
+        )?;
+        writeln!(src, "                excess_sig: Signature::new(")?;
+        writeln!(
+            src,
+            "                    PublicKey::from_hex(\"{}\").unwrap(),",
+            kernel.excess_sig.get_public_nonce().to_hex()
+        )?;
+        writeln!(
+            src,
+            "                    PrivateKey::from_hex(\"{}\").unwrap(),",
+            kernel.excess_sig.get_signature().to_hex()
+        )?;
+        writeln!(src, "                ),")?;
+        writeln!(src, "            }},")?;
+    }
+    writeln!(src, "        ],")?;
+    writeln!(src, "    );")?;
+    writeln!(src, "    body.sort();")?;
+    writeln!(src, "    Block {{")?;
+    writeln!(src, "        header: BlockHeader {{")?;
+    writeln!(src, "            version: {},", header.version)?;
+    writeln!(src, "            height: 0,")?;
+    writeln!(src, "            prev_hash: vec![0; BLOCK_HASH_LENGTH],")?;
+    writeln!(src, "            timestamp: {}.into(),", header.timestamp.as_u64())?;
+    writeln!(
+        src,
+        "            output_mr: from_hex(\"{}\").unwrap(),",
+        header.output_mr.to_hex()
+    )?;
+    writeln!(
+        src,
+        "            witness_mr: from_hex(\"{}\").unwrap(),",
+        header.witness_mr.to_hex()
+    )?;
+    writeln!(src, "            output_mmr_size: {},", header.output_mmr_size)?;
+    writeln!(
+        src,
+        "            kernel_mr: from_hex(\"{}\").unwrap(),",
+        header.kernel_mr.to_hex()
+    )?;
+    writeln!(src, "            kernel_mmr_size: {},", header.kernel_mmr_size)?;
+    writeln!(
+        src,
+        "            input_mr: from_hex(\"{}\").unwrap(),",
+        header.input_mr.to_hex()
+    )?;
+    writeln!(
+        src,
+        "            total_kernel_offset: PrivateKey::from_hex(\"{}\").unwrap(),",
+        header.total_kernel_offset.to_hex()
+    )?;
+    writeln!(
+        src,
+        "            total_script_offset: PrivateKey::from_hex(\"{}\").unwrap(),",
+        header.total_script_offset.to_hex()
+    )?;
+    writeln!(src, "            nonce: {},", header.nonce)?;
+    writeln!(src, "            pow: ProofOfWork {{")?;
+    writeln!(
+        src,
+        "                pow_algo: PowAlgorithm::new({}),",
+        header.pow.pow_algo.as_u8()
+    )?;
+    writeln!(src, "                pow_data: vec![],")?;
+    writeln!(src, "            }},")?;
+    writeln!(src, "        }},")?;
+    writeln!(src, "        body,")?;
+    writeln!(src, "    }}")?;
+    writeln!(src, "}}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::transaction::OutputFlags;
+
+    #[test]
+    fn generate_valid_genesis_block() {
+        let factories = CryptoFactories::default();
+        let constants = ConsensusConstants::localnet().remove(0);
+        let reward = constants.emission_amounts().0;
+        let timestamp = EpochTime::from(1_625_630_400);
+        let block = generate_genesis_block(
+            &constants,
+            PrivateKey::random(&mut OsRng),
+            reward,
+            timestamp,
+            &factories,
+        )
+        .unwrap();
+        assert_eq!(block.header.height, 0);
+        assert_eq!(block.header.timestamp, timestamp);
+        assert_eq!(block.header.output_mmr_size, 1);
+        assert_eq!(block.header.kernel_mmr_size, 1);
+        assert!(block.body.outputs()[0]
+            .features
+            .flags
+            .contains(OutputFlags::COINBASE_OUTPUT));
+        assert_eq!(
+            block.body.outputs()[0].features.maturity,
+            constants.coinbase_lock_height()
+        );
+
+        let json = serde_json::to_string(&block).unwrap();
+        let decoded = serde_json::from_str::<Block>(&json).unwrap();
+        assert_eq!(decoded, block);
+
+        let src = genesis_block_source(&block, "staging");
+        assert!(src.starts_with("pub fn get_staging_genesis_block_raw() -> Block {"));
+        assert!(src.contains(&block.header.kernel_mr.to_hex()));
+        assert!(src.contains(&block.body.outputs()[0].commitment.to_hex()));
+        assert!(src.contains(&format!("pow_algo: PowAlgorithm::new({}),", PowAlgorithm::Sha3.as_u8())));
+    }
+
+    #[test]
+    fn genesis_amount_must_match_emission() {
+        let factories = CryptoFactories::default();
+        let constants = ConsensusConstants::localnet().remove(0);
+        let reward = constants.emission_amounts().0;
+        let err = generate_genesis_block(
+            &constants,
+            PrivateKey::random(&mut OsRng),
+            reward + MicroTari::from(1),
+            EpochTime::now(),
+            &factories,
+        )
+        .unwrap_err();
+        assert!(matches!(err, GenesisBlockError::ValidationError(_)));
+    }
+
+    #[test]
+    fn genesis_must_balance() {
+        let factories = CryptoFactories::default();
+        let constants = ConsensusConstants::localnet().remove(0);
+        let reward = constants.emission_amounts().0;
+        let mut block = generate_genesis_block(
+            &constants,
+            PrivateKey::random(&mut OsRng),
+            reward,
+            EpochTime::now(),
+            &factories,
+        )
+        .unwrap();
+        // The offset is not committed to by the MMR roots
+        block.header.total_kernel_offset = PrivateKey::random(&mut OsRng);
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .with_consensus_constants(constants)
+            .build();
+        let err = validate_genesis_block(&block, &rules, &factories).unwrap_err();
+        assert!(matches!(err, ValidationError::TransactionError(_)));
+    }
+}
@@ -28,11 +28,22 @@ pub mod block_header;
 #[cfg(feature = "base_node")]
 pub mod genesis_block;
 #[cfg(feature = "base_node")]
+pub mod genesis_generator;
+#[cfg(feature = "base_node")]
 mod new_block_template;
 #[cfg(feature = "base_node")]
 mod new_blockheader_template;
 #[cfg(feature = "base_node")]
-pub use block::{Block, BlockBuilder, BlockValidationError, NewBlock};
+pub use block::{
+    Block,
+    BlockBuilder,
+    BlockValidationError,
+    CompactBlock,
+    NewBlock,
+    ShortId,
+    ShortIdHasher,
+    SHORT_ID_LENGTH,
+};
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub use block_header::{BlockHeader, BlockHeaderValidationError};
 #[cfg(feature = "base_node")]
@@ -23,7 +23,7 @@
 use crate::{
     blocks::{Block, BlockHeader},
     chain_storage::ChainStorageError,
-    proof_of_work::{AchievedTargetDifficulty, Difficulty, PowAlgorithm},
+    proof_of_work::{AchievedTargetDifficulty, Difficulty, PowAlgorithm, PowError},
     tari_utilities::Hashable,
     transactions::{
         aggregated_body::AggregateBody,
@@ -52,13 +52,13 @@ use tari_mmr::{pruned_hashset::PrunedHashSet, ArrayLike};
 
 const LOG_TARGET: &str = "c::bn::acc_data";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 // Helper struct to serialize and deserialize Bitmap
 pub struct DeletedBitmap {
     pub(super) deleted: Bitmap,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockAccumulatedData {
     pub(super) kernels: PrunedHashSet,
     pub(super) outputs: PrunedHashSet,
@@ -236,6 +236,9 @@ impl BlockHeaderAccumulatedDataBuilder<'_> {
             ChainStorageError::InvalidOperation("Current achieved difficulty not provided".to_string())
         })?;
 
+        // The accumulated difficulty is only tracked for Monero and SHA3. Adding the work of any other algorithm to
+        // either total would let it inflate the chain strength used for fork choice, so those are rejected until
+        // they have an accumulation rule of their own.
         let (monero_diff, blake_diff) = match achieved_target.pow_algo() {
             PowAlgorithm::Monero => (
                 previous_accum.accumulated_monero_difficulty + achieved_target.achieved(),
@@ -245,6 +248,7 @@ impl BlockHeaderAccumulatedDataBuilder<'_> {
                 previous_accum.accumulated_monero_difficulty,
                 previous_accum.accumulated_sha_difficulty + achieved_target.achieved(),
             ),
+            algo => return Err(PowError::UnsupportedAlgorithm(algo).into()),
         };
 
         let total_kernel_offset = self
@@ -485,4 +489,51 @@ mod test {
             ChainHeader::try_construct(genesis.header().clone(), genesis.accumulated_data().clone()).unwrap();
         }
     }
+
+    mod block_header_accumulated_data_builder {
+        use super::*;
+
+        fn build(pow_algo: PowAlgorithm) -> Result<BlockHeaderAccumulatedData, ChainStorageError> {
+            let genesis = get_weatherwax_genesis_block();
+            BlockHeaderAccumulatedData::builder(genesis.accumulated_data())
+                .with_hash(vec![1u8; 32])
+                .with_achieved_target_difficulty(
+                    AchievedTargetDifficulty::try_construct(pow_algo, 10.into(), 15.into()).unwrap(),
+                )
+                .with_total_kernel_offset(Default::default())
+                .build()
+        }
+
+        #[test]
+        fn it_accumulates_monero_and_sha3_separately() {
+            let genesis = get_weatherwax_genesis_block();
+            let previous = genesis.accumulated_data();
+            let sha3 = build(PowAlgorithm::Sha3).unwrap();
+            assert_eq!(
+                sha3.accumulated_sha_difficulty,
+                previous.accumulated_sha_difficulty + 15.into()
+            );
+            assert_eq!(
+                sha3.accumulated_monero_difficulty,
+                previous.accumulated_monero_difficulty
+            );
+            let monero = build(PowAlgorithm::Monero).unwrap();
+            assert_eq!(
+                monero.accumulated_monero_difficulty,
+                previous.accumulated_monero_difficulty + 15.into()
+            );
+            assert_eq!(monero.accumulated_sha_difficulty, previous.accumulated_sha_difficulty);
+        }
+
+        #[test]
+        fn it_rejects_algorithms_without_an_accumulation_rule() {
+            let err = build(PowAlgorithm::new(7)).unwrap_err();
+            assert!(matches!(
+                err,
+                ChainStorageError::ProofOfWorkError {
+                    source: PowError::UnsupportedAlgorithm(algo)
+                } if algo == PowAlgorithm::new(7)
+            ));
+        }
+    }
 }
@@ -34,8 +34,10 @@ use crate::{
         DbTransaction,
         HistoricalBlock,
         HorizonData,
+        IntegrityReport,
         MmrTree,
         PrunedOutput,
+        ReorgPage,
         TargetDifficulties,
     },
     common::rolling_vec::RollingVec,
@@ -137,11 +139,21 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_horizon_data() -> Option<HorizonData>, "fetch_horizon_data");
 
+    make_async_fn!(check_integrity() -> IntegrityReport, "check_integrity");
+
+    make_async_fn!(repair_integrity() -> IntegrityReport, "repair_integrity");
+
     //---------------------------------- TXO --------------------------------------------//
     make_async_fn!(fetch_utxo(hash: HashOutput) -> Option<TransactionOutput>, "fetch_utxo");
 
     make_async_fn!(fetch_utxos(hashes: Vec<HashOutput>, is_spent_as_of: Option<HashOutput>) -> Vec<Option<(TransactionOutput, bool)>>, "fetch_utxos");
 
+    make_async_fn!(fetch_outputs_by_commitment(commitment: Commitment) -> Vec<(TransactionOutput, u32, u64)>, "fetch_outputs_by_commitment");
+
+    make_async_fn!(fetch_outputs_by_script_hash(script_hash: Vec<u8>) -> Vec<(TransactionOutput, u32, u64)>, "fetch_outputs_by_script_hash");
+
+    make_async_fn!(fetch_output_spent_in_block(output_hash: HashOutput) -> Option<(HashOutput, u64)>, "fetch_output_spent_in_block");
+
     make_async_fn!(fetch_utxos_by_mmr_position(start: u64, end: u64, end_header_hash: HashOutput) -> (Vec<PrunedOutput>, Bitmap), "fetch_utxos_by_mmr_position");
 
     //---------------------------------- Kernel --------------------------------------------//
@@ -158,6 +170,8 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(rewind_to_hash(hash: BlockHash) -> Vec<Arc<ChainBlock>>, "rewind_to_hash");
 
+    make_async_fn!(fetch_reorgs(start: u64, limit: usize) -> ReorgPage, "fetch_reorgs");
+
     //---------------------------------- Headers --------------------------------------------//
     make_async_fn!(fetch_header(height: u64) -> Option<BlockHeader>, "fetch_header");
 
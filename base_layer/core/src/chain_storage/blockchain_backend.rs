@@ -11,11 +11,13 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
+        IntegrityReport,
         MmrTree,
+        Reorg,
     },
     transactions::{
         transaction::{TransactionInput, TransactionKernel, TransactionOutput},
-        types::{HashOutput, Signature},
+        types::{Commitment, HashOutput, Signature},
     },
 };
 use croaring::Bitmap;
@@ -107,6 +109,28 @@ pub trait BlockchainBackend: Send + Sync {
         output_hash: &HashOutput,
     ) -> Result<Option<(TransactionOutput, u32, u64)>, ChainStorageError>;
 
+    /// Fetch all unpruned outputs with the given commitment. Returns each output, its leaf index in the output MMR and
+    /// the height of the block it was mined in. Backends may require the output index to be enabled for this query.
+    fn fetch_outputs_by_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError>;
+
+    /// Fetch all unpruned outputs locked with the script that hashes to `script_hash`. Returns each output, its leaf
+    /// index in the output MMR and the height of the block it was mined in. Backends may require the output index to
+    /// be enabled for this query.
+    fn fetch_outputs_by_script_hash(
+        &self,
+        script_hash: &[u8],
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError>;
+
+    /// Fetch the hash and height of the block that spent the output with the given hash, or `None` if the output is
+    /// unspent or unknown. Backends may require the output index to be enabled for this query.
+    fn fetch_output_spent_in_block(
+        &self,
+        output_hash: &HashOutput,
+    ) -> Result<Option<(HashOutput, u64)>, ChainStorageError>;
+
     /// Fetch all outputs in a block
     fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError>;
 
@@ -132,6 +156,10 @@ pub trait BlockchainBackend: Send + Sync {
     /// Returns the kernel count
     fn kernel_count(&self) -> Result<usize, ChainStorageError>;
 
+    /// Returns up to `limit` reorgs from the reorg journal, oldest first, starting from the sequence number `start`.
+    /// Every recorded reorg gets the next sequence number, which is returned with it.
+    fn fetch_reorgs(&self, start: u64, limit: usize) -> Result<Vec<(u64, Reorg)>, ChainStorageError>;
+
     /// Fetches an current tip orphan by hash or returns None if the orphan is not found or is not a tip of any
     /// alternate chain
     fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError>;
@@ -151,4 +179,10 @@ pub trait BlockchainBackend: Send + Sync {
     fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError>;
 
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError>;
+
+    /// Checks that the indexes and accumulated data of the backend are consistent with the blocks that it stores.
+    fn check_integrity(&self) -> Result<IntegrityReport, ChainStorageError>;
+    /// Checks the integrity of the backend and rebuilds any indexes that were found to be inconsistent. Returns the
+    /// report of the check that was run before the repair.
+    fn repair_integrity(&mut self) -> Result<IntegrityReport, ChainStorageError>;
 }
@@ -31,26 +31,44 @@ use crate::{
         db_transaction::{DbKey, DbTransaction, DbValue},
         error::ChainStorageError,
         pruned_output::PrunedOutput,
+        snapshot,
         BlockAddResult,
         BlockchainBackend,
         ChainBlock,
         ChainHeader,
         HistoricalBlock,
         HorizonData,
+        IntegrityReport,
         MmrTree,
         Optional,
         OrNotFound,
+        Reorg,
+        ReorgPage,
+        SnapshotError,
         TargetDifficulties,
+        MAX_REORGS_PER_PAGE,
     },
     common::rolling_vec::RollingVec,
-    consensus::{chain_strength_comparer::ChainStrengthComparer, ConsensusConstants, ConsensusManager},
+    consensus::{
+        chain_strength_comparer::ChainStrengthComparer,
+        ConsensusConstants,
+        ConsensusFeature,
+        ConsensusManager,
+    },
     proof_of_work::{monero_rx::MoneroPowData, PowAlgorithm, TargetDifficultyWindow},
     tari_utilities::epoch_time::EpochTime,
     transactions::{
         transaction::{TransactionKernel, TransactionOutput},
-        types::{Commitment, HashDigest, HashOutput, Signature},
+        types::{Commitment, HashDigest, HashOutput, RangeProofService, Signature},
+    },
+    validation::{
+        DifficultyCalculator,
+        FinalHorizonStateValidation,
+        HeaderValidation,
+        OrphanValidation,
+        PostOrphanBodyValidation,
+        ValidationError,
     },
-    validation::{DifficultyCalculator, HeaderValidation, OrphanValidation, PostOrphanBodyValidation, ValidationError},
 };
 use croaring::Bitmap;
 use log::*;
@@ -58,6 +76,7 @@ use std::{
     cmp,
     cmp::Ordering,
     collections::VecDeque,
+    io::{Read, Write},
     mem,
     ops::Bound,
     sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
@@ -158,7 +177,8 @@ macro_rules! try_fetch {
 ///
 /// You typically don't interact with `BlockChainDatabase` directly, since it doesn't enforce any consensus rules; it
 /// only really stores and fetches blockchain components. To create an instance of `BlockchainDatabase', you must
-/// provide it with the backend it is going to use; for example, for a memory-backed DB:
+/// provide it with the backend it is going to use; for example, [MemoryDatabase](crate::chain_storage::MemoryDatabase)
+/// for a memory-backed DB or [LMDBDatabase](crate::chain_storage::LMDBDatabase) for persistent storage.
 pub struct BlockchainDatabase<B> {
     db: Arc<RwLock<B>>,
     validators: Validators<B>,
@@ -223,6 +243,18 @@ where B: BlockchainBackend
         Ok(self.consensus_manager.consensus_constants(height))
     }
 
+    /// Returns true if the consensus feature is active for the next block on the main chain
+    pub fn is_feature_active(&self, feature: ConsensusFeature) -> Result<bool, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let metadata = db.fetch_chain_metadata()?;
+        self.consensus_manager.is_feature_active_on_chain(
+            feature,
+            metadata.height_of_longest_chain() + 1,
+            metadata.best_block(),
+            &*db,
+        )
+    }
+
     // Be careful about making this method public. Rather use `db_and_metadata_read_access`
     // so that metadata and db are read in the correct order so that deadlocks don't occur
     pub fn db_read_access(&self) -> Result<RwLockReadGuard<B>, ChainStorageError> {
@@ -288,6 +320,37 @@ where B: BlockchainBackend
         Ok(db.fetch_output(&hash)?.map(|(out, _index, _)| out))
     }
 
+    /// Returns all unpruned outputs with the given commitment, along with their leaf index in the output MMR and the
+    /// height of the block they were mined in. The LMDB backend requires the output index to be enabled for this query.
+    pub fn fetch_outputs_by_commitment(
+        &self,
+        commitment: Commitment,
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_outputs_by_commitment(&commitment)
+    }
+
+    /// Returns all unpruned outputs whose script hashes to `script_hash`, along with their leaf index in the output MMR
+    /// and the height of the block they were mined in. The LMDB backend requires the output index to be enabled for
+    /// this query.
+    pub fn fetch_outputs_by_script_hash(
+        &self,
+        script_hash: Vec<u8>,
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_outputs_by_script_hash(&script_hash)
+    }
+
+    /// Returns the hash and height of the block in which the output was spent, or `None` if it is unspent. The LMDB
+    /// backend requires the output index to be enabled for this query.
+    pub fn fetch_output_spent_in_block(
+        &self,
+        output_hash: HashOutput,
+    ) -> Result<Option<(HashOutput, u64)>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_output_spent_in_block(&output_hash)
+    }
+
     /// Return a list of matching utxos, with each being `None` if not found. If found, the transaction
     /// output, and a boolean indicating if the UTXO was spent as of the block hash specified or the tip if not
     /// specified.
@@ -630,15 +693,15 @@ where B: BlockchainBackend
         targets.add_front(
             current_header.header(),
             current_header.accumulated_data().target_difficulty,
-        );
+        )?;
 
         while current_header.height() > 0 && !targets.is_full() {
             current_header = db.fetch_chain_header_in_all_chains(&current_header.header().prev_hash)?;
-            if !targets.is_algo_full(current_header.header().pow_algo()) {
+            if !targets.is_algo_full(current_header.header().pow_algo())? {
                 targets.add_front(
                     current_header.header(),
                     current_header.accumulated_data().target_difficulty,
-                );
+                )?;
             }
             if targets.is_full() {
                 break;
@@ -864,29 +927,94 @@ where B: BlockchainBackend
     }
 
     /// Rewind the blockchain state to the block height given and return the blocks that were removed and orphaned.
+    /// The rewind is recorded in the reorg journal.
     ///
     /// The operation will fail if
     /// * The block height is in the future
     pub fn rewind_to_height(&self, height: u64) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
-        rewind_to_height(&mut *db, height)
+        let removed_blocks = rewind_to_height(&mut *db, height)?;
+        record_rewind(&mut *db, &removed_blocks)?;
+        Ok(removed_blocks)
     }
 
     /// Rewind the blockchain state to the block hash making the block at that hash the new tip.
-    /// Returns the removed blocks.
+    /// Returns the removed blocks. The rewind is recorded in the reorg journal.
     ///
     /// The operation will fail if
     /// * The block hash does not exist
     /// * The block hash is before the horizon block height determined by the pruning horizon
     pub fn rewind_to_hash(&self, hash: BlockHash) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
         let mut db = self.db_write_access()?;
-        rewind_to_hash(&mut *db, hash)
+        let removed_blocks = rewind_to_hash(&mut *db, hash)?;
+        record_rewind(&mut *db, &removed_blocks)?;
+        Ok(removed_blocks)
     }
 
     pub fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
         let db = self.db_read_access()?;
         db.fetch_horizon_data()
     }
+
+    /// Returns a page of at most `limit` reorgs from the reorg journal, oldest first, starting from the journal
+    /// sequence number `start`. The page size is capped at [MAX_REORGS_PER_PAGE].
+    pub fn fetch_reorgs(&self, start: u64, limit: usize) -> Result<ReorgPage, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let reorgs = db.fetch_reorgs(start, cmp::min(limit, MAX_REORGS_PER_PAGE))?;
+        let next_start = reorgs.last().map(|(seq, _)| seq + 1).unwrap_or(start);
+        Ok(ReorgPage {
+            reorgs: reorgs.into_iter().map(|(_, reorg)| reorg).collect(),
+            next_start,
+        })
+    }
+
+    /// Checks the consistency of the backend's indexes and accumulated data. See
+    /// [BlockchainBackend::check_integrity].
+    pub fn check_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.check_integrity()
+    }
+
+    /// Rebuilds any backend indexes that are inconsistent with the stored blocks, returning the report of the issues
+    /// found before the repair. See [BlockchainBackend::repair_integrity].
+    pub fn repair_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        let mut db = self.db_write_access()?;
+        db.repair_integrity()
+    }
+
+    /// Writes a snapshot of the chain state at the given height, which can be used to bootstrap a pruned node with
+    /// [import_snapshot](Self::import_snapshot). Returns the horizon data of the snapshot.
+    ///
+    /// The operation will fail if
+    /// * The height is greater than the chain tip
+    /// * The height is below the pruned height of this database
+    pub fn export_snapshot<W: Write>(&self, height: u64, writer: W) -> Result<HorizonData, SnapshotError> {
+        snapshot::export_snapshot(self, height, writer)
+    }
+
+    /// Verifies and imports a chain state snapshot into this database. Headers are validated using the header
+    /// validator of this database and the resulting horizon state is checked with `final_state_validator`. Returns the
+    /// new chain tip.
+    ///
+    /// The operation will fail if
+    /// * This database contains any blocks or headers other than the genesis block
+    /// * This database is not configured as a pruned node
+    /// * The snapshot fails validation
+    pub fn import_snapshot<R: Read>(
+        &self,
+        reader: R,
+        final_state_validator: &dyn FinalHorizonStateValidation<B>,
+        prover: &RangeProofService,
+    ) -> Result<ChainHeader, SnapshotError> {
+        snapshot::import_snapshot(
+            self,
+            &*self.validators.header,
+            &self.difficulty_calculator,
+            final_state_validator,
+            prover,
+            reader,
+        )
+    }
 }
 
 fn unexpected_result<T>(req: DbKey, res: DbValue) -> Result<T, ChainStorageError> {
@@ -978,6 +1106,40 @@ pub fn calculate_mmr_roots<T: BlockchainBackend>(db: &T, block: &Block) -> Resul
     Ok(mmr_roots)
 }
 
+/// Calculates the MMR roots of a genesis block. A genesis block has no previous state to build on, so it may not
+/// contain any inputs.
+pub fn calculate_genesis_mmr_roots(block: &Block) -> Result<MmrRoots, ChainStorageError> {
+    if !block.body.inputs().is_empty() {
+        return Err(ChainStorageError::InvalidOperation(
+            "A genesis block cannot contain inputs".to_string(),
+        ));
+    }
+    let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(Vec::new());
+    let mut output_mmr = MutableMmr::<HashDigest, _>::new(Vec::new(), Bitmap::create())?;
+    let mut witness_mmr = MerkleMountainRange::<HashDigest, _>::new(Vec::new());
+    let input_mmr = MutableMmr::<HashDigest, _>::new(Vec::new(), Bitmap::create())?;
+
+    for kernel in block.body.kernels().iter() {
+        kernel_mmr.push(kernel.hash())?;
+    }
+
+    for output in block.body.outputs().iter() {
+        output_mmr.push(output.hash())?;
+        witness_mmr.push(output.witness_hash())?;
+    }
+
+    output_mmr.compress();
+
+    Ok(MmrRoots {
+        kernel_mr: kernel_mmr.get_merkle_root()?,
+        kernel_mmr_size: kernel_mmr.get_leaf_count()? as u64,
+        input_mr: input_mmr.get_merkle_root()?,
+        output_mr: output_mmr.get_merkle_root()?,
+        output_mmr_size: witness_mmr.get_leaf_count()? as u64,
+        witness_mr: witness_mmr.get_merkle_root()?,
+    })
+}
+
 pub fn fetch_header<T: BlockchainBackend>(db: &T, block_num: u64) -> Result<BlockHeader, ChainStorageError> {
     fetch!(db, block_num, BlockHeader)
 }
@@ -1374,6 +1536,31 @@ fn rewind_to_height<T: BlockchainBackend>(
     Ok(removed_blocks)
 }
 
+/// Records a rewind of the main chain in the reorg journal, if any blocks were removed
+fn record_rewind<T: BlockchainBackend>(
+    db: &mut T,
+    removed_blocks: &[Arc<ChainBlock>],
+) -> Result<(), ChainStorageError> {
+    if removed_blocks.is_empty() {
+        return Ok(());
+    }
+    let new_tip = db.fetch_tip_header()?;
+    if let Some(reorg) = Reorg::from_rewound_blocks(removed_blocks, &new_tip) {
+        record_reorg(db, reorg);
+    }
+    Ok(())
+}
+
+/// Appends a reorg to the reorg journal. The chain has already been changed at this point, so failing to journal it is
+/// not fatal.
+fn record_reorg<T: BlockchainBackend>(db: &mut T, reorg: Reorg) {
+    let mut txn = DbTransaction::new();
+    txn.insert_reorg(reorg);
+    if let Err(e) = db.write(txn) {
+        warn!(target: LOG_TARGET, "Failed to record reorg in the reorg journal: {}", e);
+    }
+}
+
 fn rewind_to_hash<T: BlockchainBackend>(
     db: &mut T,
     block_hash: BlockHash,
@@ -1524,9 +1711,13 @@ fn handle_possible_reorg<T: BlockchainBackend>(
             num_removed_blocks,
             num_added_blocks,
         );
+        let added_blocks = Vec::from(reorg_chain);
+        if let Some(reorg) = Reorg::from_reorged_blocks(&removed_blocks, &added_blocks) {
+            record_reorg(db, reorg);
+        }
         Ok(BlockAddResult::ChainReorg {
             removed: removed_blocks,
-            added: reorg_chain.into(),
+            added: added_blocks,
         })
     } else {
         trace!(
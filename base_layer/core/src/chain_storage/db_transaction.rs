@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
     blocks::{Block, BlockHeader},
-    chain_storage::{error::ChainStorageError, ChainBlock, ChainHeader, MmrTree},
+    chain_storage::{error::ChainStorageError, ChainBlock, ChainHeader, MmrTree, Reorg},
     transactions::{
         transaction::{TransactionInput, TransactionKernel, TransactionOutput},
         types::{Commitment, HashOutput},
@@ -264,6 +264,12 @@ impl DbTransaction {
         self.operations
             .push(WriteOperation::InsertMoneroSeedHeight(monero_seed, height));
     }
+
+    /// Appends a reorg to the reorg journal
+    pub fn insert_reorg(&mut self, reorg: Reorg) -> &mut Self {
+        self.operations.push(WriteOperation::InsertReorg(Box::new(reorg)));
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -306,6 +312,7 @@ pub enum WriteOperation {
     DeleteOrphanChainTip(HashOutput),
     InsertOrphanChainTip(HashOutput),
     InsertMoneroSeedHeight(Vec<u8>, u64),
+    InsertReorg(Box<Reorg>),
     UpdatePrunedHashSet {
         mmr_tree: MmrTree,
         header_hash: HashOutput,
@@ -397,6 +404,7 @@ impl fmt::Display for WriteOperation {
             InsertMoneroSeedHeight(data, height) => {
                 write!(f, "Insert Monero seed string {} for height: {}", data.to_hex(), height)
             },
+            InsertReorg(reorg) => write!(f, "InsertReorg({})", reorg),
             InsertChainOrphanBlock(block) => write!(f, "InsertChainOrphanBlock({})", block.hash().to_hex()),
             UpdatePrunedHashSet {
                 mmr_tree, header_hash, ..
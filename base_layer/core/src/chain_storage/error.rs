@@ -105,6 +105,10 @@ pub enum ChainStorageError {
     CannotAcquireFileLock,
     #[error("IO Error: `{0}`")]
     IoError(#[from] std::io::Error),
+    #[error("The output index is not enabled for this database")]
+    OutputIndexNotEnabled,
+    #[error("Database schema version {found} is newer than the latest supported version {supported}")]
+    UnsupportedSchemaVersion { found: u32, supported: u32 },
 }
 
 impl ChainStorageError {
@@ -0,0 +1,100 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt;
+
+/// A single inconsistency found between two tables of a blockchain backend
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrityIssue {
+    /// The table in which the inconsistent entry was found
+    pub table: &'static str,
+    /// The key of the inconsistent entry
+    pub key: String,
+    pub description: String,
+    /// True if the entry belongs to an index that can be rebuilt from the data it is derived from
+    pub repairable: bool,
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.table, self.key, self.description)?;
+        if self.repairable {
+            f.write_str(" (repairable)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of a database integrity check
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Returns true if no inconsistencies were found
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn issues(&self) -> &[IntegrityIssue] {
+        &self.issues
+    }
+
+    /// Returns true if at least one issue can be fixed by rebuilding an index
+    pub fn has_repairable_issues(&self) -> bool {
+        self.issues.iter().any(|issue| issue.repairable)
+    }
+
+    /// Returns true if an issue was found in the given table
+    pub fn has_issues_in(&self, table: &str) -> bool {
+        self.issues.iter().any(|issue| issue.table == table)
+    }
+
+    pub(crate) fn push<K: Into<String>, D: Into<String>>(
+        &mut self,
+        table: &'static str,
+        key: K,
+        description: D,
+        repairable: bool,
+    ) {
+        self.issues.push(IntegrityIssue {
+            table,
+            key: key.into(),
+            description: description.into(),
+            repairable,
+        });
+    }
+}
+
+impl fmt::Display for IntegrityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return f.write_str("No database inconsistencies found");
+        }
+        writeln!(f, "{} database inconsistencies found:", self.issues.len())?;
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
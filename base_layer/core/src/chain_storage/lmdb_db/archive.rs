@@ -0,0 +1,270 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Append-only, compressed flat files for the bodies of finalized blocks. When a block is archived its inputs, output
+//! data and kernels are removed from the LMDB tables and written to the current block file. The inputs of a block are
+//! written as one record and every output and kernel as a separate record, so that a single output or kernel can be
+//! read without decompressing the rest of the block. The LMDB archive index maps the block hash to the locations of
+//! its records.
+
+use crate::chain_storage::{
+    lmdb_db::{TransactionInputRowData, TransactionKernelRowData, TransactionOutputRowData},
+    ChainStorageError,
+};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use log::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    convert::TryFrom,
+    fmt::Display,
+    fs,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+};
+
+const LOG_TARGET: &str = "c::cs::lmdb_db::archive";
+
+/// A new block file is started once the current one reaches this size
+const MAX_BLOCK_FILE_SIZE: u64 = 128 * 1024 * 1024;
+const BLOCK_FILE_PREFIX: &str = "blocks_";
+const BLOCK_FILE_EXTENSION: &str = "dat";
+
+/// The body of a block that is moved out of LMDB when it is archived. Only outputs and kernels whose data is still in
+/// LMDB are archived.
+#[derive(Debug, Default)]
+pub(crate) struct ArchivedBlockBody {
+    pub inputs: Vec<TransactionInputRowData>,
+    pub outputs: Vec<TransactionOutputRowData>,
+    pub kernels: Vec<TransactionKernelRowData>,
+}
+
+/// The location of a compressed record in the block files
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct ArchiveLocation {
+    pub file_number: u32,
+    pub offset: u64,
+    pub length: u32,
+}
+
+/// The archive index entry of a block. Outputs and kernels are keyed by their MMR position.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ArchivedBlockIndex {
+    pub inputs: Option<ArchiveLocation>,
+    pub outputs: Vec<(u32, ArchiveLocation)>,
+    pub kernels: Vec<(u32, ArchiveLocation)>,
+}
+
+impl ArchivedBlockIndex {
+    pub fn output(&self, mmr_position: u32) -> Option<&ArchiveLocation> {
+        find_location(&self.outputs, mmr_position)
+    }
+
+    pub fn kernel(&self, mmr_position: u32) -> Option<&ArchiveLocation> {
+        find_location(&self.kernels, mmr_position)
+    }
+}
+
+pub(crate) struct BlockArchive {
+    path: PathBuf,
+    current_file_number: AtomicU32,
+    unsynced_files: Mutex<BTreeSet<u32>>,
+}
+
+impl BlockArchive {
+    /// Opens the block archive in the given directory. The directory is only created once the first block is archived.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ChainStorageError> {
+        let path = path.as_ref().to_path_buf();
+        let mut current_file_number = 0;
+        if path.exists() {
+            for entry in fs::read_dir(&path)? {
+                let entry = entry?;
+                if let Some(file_number) = parse_file_number(&entry.path()) {
+                    current_file_number = current_file_number.max(file_number);
+                }
+            }
+        }
+        Ok(Self {
+            path,
+            current_file_number: AtomicU32::new(current_file_number),
+            unsynced_files: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    /// Compresses and appends the records of the block body to the current block file, returning the archive index
+    /// entry of the block. The records are not synced to disk, [sync](BlockArchive::sync) must be called before the
+    /// index entry is committed. Records that are written but never indexed, for instance because the LMDB
+    /// transaction failed, are unreachable and ignored.
+    pub fn append(&self, body: &ArchivedBlockBody) -> Result<ArchivedBlockIndex, ChainStorageError> {
+        // Record offsets are relative to the start of `data` until the offset in the block file is known
+        let mut data = Vec::new();
+        let mut records = ArchivedBlockIndex::default();
+        if !body.inputs.is_empty() {
+            records.inputs = Some(append_record(&mut data, &body.inputs)?);
+        }
+        for row in &body.outputs {
+            if let Some(ref output) = row.output {
+                records
+                    .outputs
+                    .push((row.mmr_position, append_record(&mut data, output)?));
+            }
+        }
+        for row in &body.kernels {
+            if let Some(ref kernel) = row.kernel {
+                records
+                    .kernels
+                    .push((row.mmr_position, append_record(&mut data, kernel)?));
+            }
+        }
+
+        fs::create_dir_all(&self.path)?;
+        let mut file_number = self.current_file_number.load(Ordering::SeqCst);
+        let mut file = open_for_append(&self.path, file_number)?;
+        let mut offset = file.seek(SeekFrom::End(0))?;
+        if offset > 0 && offset + data.len() as u64 > MAX_BLOCK_FILE_SIZE {
+            file_number += 1;
+            self.current_file_number.store(file_number, Ordering::SeqCst);
+            debug!(
+                target: LOG_TARGET,
+                "Starting block file {}",
+                block_file_path(&self.path, file_number).display()
+            );
+            file = open_for_append(&self.path, file_number)?;
+            offset = file.seek(SeekFrom::End(0))?;
+        }
+        file.write_all(&data)?;
+        self.unsynced_files
+            .lock()
+            .expect("Block archive lock poisoned")
+            .insert(file_number);
+
+        let locations = records
+            .inputs
+            .iter_mut()
+            .chain(records.outputs.iter_mut().map(|(_, location)| location))
+            .chain(records.kernels.iter_mut().map(|(_, location)| location));
+        for location in locations {
+            location.file_number = file_number;
+            location.offset += offset;
+        }
+        Ok(records)
+    }
+
+    /// Syncs the block files that have been appended to since the last sync to disk
+    pub fn sync(&self) -> Result<(), ChainStorageError> {
+        let mut unsynced_files = self.unsynced_files.lock().expect("Block archive lock poisoned");
+        for file_number in unsynced_files.iter() {
+            OpenOptions::new()
+                .append(true)
+                .open(block_file_path(&self.path, *file_number))?
+                .sync_data()?;
+        }
+        unsynced_files.clear();
+        Ok(())
+    }
+
+    /// Reads the archived inputs of a block
+    pub fn read_inputs(&self, index: &ArchivedBlockIndex) -> Result<Vec<TransactionInputRowData>, ChainStorageError> {
+        match index.inputs {
+            Some(ref location) => self.read(location),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Reads and decompresses the record at the given location
+    pub fn read<T: DeserializeOwned>(&self, location: &ArchiveLocation) -> Result<T, ChainStorageError> {
+        let path = block_file_path(&self.path, location.file_number);
+        let mut file = File::open(&path).map_err(|e| {
+            ChainStorageError::CriticalError(format!("Could not open block file {}: {}", path.display(), e))
+        })?;
+        file.seek(SeekFrom::Start(location.offset))?;
+        let mut data = vec![0u8; location.length as usize];
+        file.read_exact(&mut data)?;
+        bincode::deserialize_from(DeflateDecoder::new(data.as_slice()))
+            .map_err(|e| corrupted_record(&path, location.offset, e))
+    }
+}
+
+fn append_record<T: Serialize>(data: &mut Vec<u8>, record: &T) -> Result<ArchiveLocation, ChainStorageError> {
+    let offset = data.len();
+    let mut encoder = DeflateEncoder::new(data, Compression::default());
+    bincode::serialize_into(&mut encoder, record).map_err(|e| ChainStorageError::AccessError(e.to_string()))?;
+    let data = encoder.finish()?;
+    let length = u32::try_from(data.len() - offset).map_err(|_| {
+        ChainStorageError::AccessError(format!(
+            "Archive record of {} bytes exceeds the maximum record size",
+            data.len() - offset
+        ))
+    })?;
+    Ok(ArchiveLocation {
+        file_number: 0,
+        offset: offset as u64,
+        length,
+    })
+}
+
+fn find_location(locations: &[(u32, ArchiveLocation)], mmr_position: u32) -> Option<&ArchiveLocation> {
+    locations
+        .binary_search_by_key(&mmr_position, |(position, _)| *position)
+        .ok()
+        .map(|i| &locations[i].1)
+}
+
+fn corrupted_record<E: Display>(path: &Path, offset: u64, err: E) -> ChainStorageError {
+    ChainStorageError::CorruptedDatabase(format!(
+        "Could not decode archived record at {}:{}: {}",
+        path.display(),
+        offset,
+        err
+    ))
+}
+
+fn open_for_append(path: &Path, file_number: u32) -> Result<File, ChainStorageError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(block_file_path(path, file_number))
+        .map_err(Into::into)
+}
+
+fn block_file_path(path: &Path, file_number: u32) -> PathBuf {
+    path.join(format!(
+        "{}{:05}.{}",
+        BLOCK_FILE_PREFIX, file_number, BLOCK_FILE_EXTENSION
+    ))
+}
+
+fn parse_file_number(path: &Path) -> Option<u32> {
+    if path.extension()?.to_str()? != BLOCK_FILE_EXTENSION {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()?
+        .strip_prefix(BLOCK_FILE_PREFIX)?
+        .parse()
+        .ok()
+}
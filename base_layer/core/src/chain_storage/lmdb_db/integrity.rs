@@ -0,0 +1,727 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Consistency checks between the tables of the LMDB backend. The derived indexes (e.g. `txos_hash_to_index`) can be
+//! rebuilt from the tables they index, so inconsistencies in them are repairable without resynchronising the chain.
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    chain_storage::{
+        lmdb_db::{
+            lmdb::{
+                lmdb_clear,
+                lmdb_delete,
+                lmdb_fetch_keys_starting_with,
+                lmdb_filter_map_key_values,
+                lmdb_filter_map_values,
+                lmdb_get,
+                lmdb_insert_dup,
+                lmdb_replace,
+            },
+            TransactionInputRowData,
+            TransactionKernelRowData,
+            TransactionOutputRowData,
+            LMDB_DB_BLOCK_ACCUMULATED_DATA,
+            LMDB_DB_BLOCK_HASHES,
+            LMDB_DB_KERNEL_EXCESS_INDEX,
+            LMDB_DB_KERNEL_EXCESS_SIG_INDEX,
+            LMDB_DB_KERNEL_MMR_SIZE_INDEX,
+            LMDB_DB_ORPHAN_CHAIN_TIPS,
+            LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA,
+            LMDB_DB_ORPHAN_PARENT_MAP_INDEX,
+            LMDB_DB_TXOS_HASH_TO_INDEX,
+            LMDB_DB_UTXO_MMR_SIZE_INDEX,
+        },
+        BlockAccumulatedData,
+        BlockHeaderAccumulatedData,
+        ChainStorageError,
+        IntegrityReport,
+    },
+    transactions::{
+        transaction::TransactionKernel,
+        types::{HashOutput, Signature},
+    },
+};
+use croaring::Bitmap;
+use lmdb_zero::{ConstTransaction, Database, WriteTransaction};
+use log::*;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
+use tari_crypto::tari_utilities::{hash::Hashable, hex::to_hex, ByteArray};
+
+const LOG_TARGET: &str = "c::cs::lmdb_db::integrity";
+
+/// The maximum number of MMR positions listed in a single issue description
+const MAX_LISTED_POSITIONS: usize = 10;
+
+type KernelIndexValue = (HashOutput, u32, HashOutput);
+
+/// Checks that `txos_hash_to_index` contains exactly one correct entry for every row in `utxos`
+pub fn check_txo_hash_index(
+    txn: &ConstTransaction<'_>,
+    utxos_db: &Database,
+    txos_hash_to_index_db: &Database,
+    report: &mut IntegrityReport,
+) -> Result<(), ChainStorageError> {
+    let utxos: BTreeMap<String, (HashOutput, u32)> =
+        lmdb_filter_map_key_values(txn, utxos_db, |key, row: TransactionOutputRowData| {
+            Ok(Some((row_key(key), (row.hash, row.mmr_position))))
+        })?
+        .into_iter()
+        .collect();
+    let index: BTreeMap<HashOutput, (u32, String)> =
+        lmdb_filter_map_key_values(txn, txos_hash_to_index_db, |key, value: (u32, String)| {
+            Ok(Some((key.to_vec(), value)))
+        })?
+        .into_iter()
+        .collect();
+    debug!(
+        target: LOG_TARGET,
+        "Checking {} UTXO rows against {} index entries",
+        utxos.len(),
+        index.len()
+    );
+
+    for (hash, (mmr_position, key)) in &index {
+        match utxos.get(key) {
+            None => report.push(
+                LMDB_DB_TXOS_HASH_TO_INDEX,
+                to_hex(hash),
+                format!("refers to UTXO `{}` which does not exist", key),
+                true,
+            ),
+            Some((row_hash, _)) if row_hash != hash => report.push(
+                LMDB_DB_TXOS_HASH_TO_INDEX,
+                to_hex(hash),
+                format!("refers to UTXO `{}` which has hash {}", key, to_hex(row_hash)),
+                true,
+            ),
+            Some((_, row_position)) if row_position != mmr_position => report.push(
+                LMDB_DB_TXOS_HASH_TO_INDEX,
+                to_hex(hash),
+                format!(
+                    "has MMR position {} but UTXO `{}` has MMR position {}",
+                    mmr_position, key, row_position
+                ),
+                true,
+            ),
+            Some(_) => {},
+        }
+    }
+
+    for (key, (hash, _)) in &utxos {
+        match index.get(hash) {
+            None => report.push(
+                LMDB_DB_TXOS_HASH_TO_INDEX,
+                to_hex(hash),
+                format!("entry for UTXO `{}` is missing", key),
+                true,
+            ),
+            Some((_, index_key)) if index_key != key => report.push(
+                LMDB_DB_TXOS_HASH_TO_INDEX,
+                to_hex(hash),
+                format!("refers to UTXO `{}` instead of `{}`", index_key, key),
+                true,
+            ),
+            Some(_) => {},
+        }
+    }
+    Ok(())
+}
+
+/// Rebuilds `txos_hash_to_index` from `utxos`
+pub fn rebuild_txo_hash_index(
+    txn: &WriteTransaction<'_>,
+    utxos_db: &Database,
+    txos_hash_to_index_db: &Database,
+) -> Result<(), ChainStorageError> {
+    let rows = lmdb_filter_map_key_values(txn, utxos_db, |key, row: TransactionOutputRowData| {
+        Ok(Some((row.hash, (row.mmr_position, row_key(key)))))
+    })?;
+    info!(
+        target: LOG_TARGET,
+        "Rebuilding {} from {} UTXOs",
+        LMDB_DB_TXOS_HASH_TO_INDEX,
+        rows.len()
+    );
+    lmdb_clear(txn, txos_hash_to_index_db)?;
+    for (hash, value) in rows {
+        lmdb_replace(txn, txos_hash_to_index_db, hash.as_slice(), &value)?;
+    }
+    Ok(())
+}
+
+/// Checks that the kernel excess and excess signature indexes contain exactly one correct entry for every kernel.
+/// `read_archived_kernel` reads the kernels that have been moved to the block archive.
+pub fn check_kernel_indexes<F>(
+    txn: &ConstTransaction<'_>,
+    kernels_db: &Database,
+    kernel_excess_index: &Database,
+    kernel_excess_sig_index: &Database,
+    read_archived_kernel: F,
+    report: &mut IntegrityReport,
+) -> Result<(), ChainStorageError>
+where
+    F: Fn(&KernelIndexValue) -> Result<TransactionKernel, ChainStorageError>,
+{
+    let kernels: BTreeMap<String, (Vec<u8>, Vec<u8>, KernelIndexValue)> =
+        fetch_kernel_index_entries(txn, kernels_db, read_archived_kernel)?
+            .into_iter()
+            .collect();
+    let excess_keys = kernels
+        .values()
+        .map(|(excess, _, value)| (excess.clone(), value.clone()));
+    check_kernel_index(
+        txn,
+        LMDB_DB_KERNEL_EXCESS_INDEX,
+        kernel_excess_index,
+        excess_keys,
+        report,
+    )?;
+    let excess_sig_keys = kernels
+        .values()
+        .map(|(_, excess_sig, value)| (excess_sig.clone(), value.clone()));
+    check_kernel_index(
+        txn,
+        LMDB_DB_KERNEL_EXCESS_SIG_INDEX,
+        kernel_excess_sig_index,
+        excess_sig_keys,
+        report,
+    )?;
+    Ok(())
+}
+
+fn check_kernel_index<I: Iterator<Item = (Vec<u8>, KernelIndexValue)>>(
+    txn: &ConstTransaction<'_>,
+    table: &'static str,
+    index_db: &Database,
+    expected: I,
+    report: &mut IntegrityReport,
+) -> Result<(), ChainStorageError> {
+    let expected: BTreeMap<Vec<u8>, KernelIndexValue> = expected.collect();
+    let index: BTreeMap<Vec<u8>, KernelIndexValue> =
+        lmdb_filter_map_key_values(txn, index_db, |key, value: KernelIndexValue| {
+            Ok(Some((key.to_vec(), value)))
+        })?
+        .into_iter()
+        .collect();
+
+    for (key, value) in &index {
+        match expected.get(key) {
+            None => report.push(
+                table,
+                to_hex(key),
+                format!("refers to kernel `{}` which does not exist", kernel_key(value)),
+                true,
+            ),
+            Some(expected_value) if expected_value != value => report.push(
+                table,
+                to_hex(key),
+                format!(
+                    "refers to kernel `{}` instead of `{}`",
+                    kernel_key(value),
+                    kernel_key(expected_value)
+                ),
+                true,
+            ),
+            Some(_) => {},
+        }
+    }
+    for (key, value) in &expected {
+        if !index.contains_key(key) {
+            report.push(
+                table,
+                to_hex(key),
+                format!("entry for kernel `{}` is missing", kernel_key(value)),
+                true,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Rebuilds the kernel excess and excess signature indexes from `kernels`. `read_archived_kernel` reads the kernels
+/// that have been moved to the block archive.
+pub fn rebuild_kernel_indexes<F>(
+    txn: &WriteTransaction<'_>,
+    kernels_db: &Database,
+    kernel_excess_index: &Database,
+    kernel_excess_sig_index: &Database,
+    read_archived_kernel: F,
+) -> Result<(), ChainStorageError>
+where
+    F: Fn(&KernelIndexValue) -> Result<TransactionKernel, ChainStorageError>,
+{
+    let rows = fetch_kernel_index_entries(txn, kernels_db, read_archived_kernel)?;
+    info!(
+        target: LOG_TARGET,
+        "Rebuilding {} and {} from {} kernels",
+        LMDB_DB_KERNEL_EXCESS_INDEX,
+        LMDB_DB_KERNEL_EXCESS_SIG_INDEX,
+        rows.len()
+    );
+    lmdb_clear(txn, kernel_excess_index)?;
+    lmdb_clear(txn, kernel_excess_sig_index)?;
+    for (_, (excess, excess_sig, value)) in rows {
+        lmdb_replace(txn, kernel_excess_index, excess.as_slice(), &value)?;
+        lmdb_replace(txn, kernel_excess_sig_index, excess_sig.as_slice(), &value)?;
+    }
+    Ok(())
+}
+
+/// Checks that the block hash and MMR size indexes contain exactly one correct entry for every header
+pub fn check_header_indexes(
+    txn: &ConstTransaction<'_>,
+    headers_db: &Database,
+    block_hashes_db: &Database,
+    kernel_mmr_size_index: &Database,
+    output_mmr_size_index: &Database,
+    report: &mut IntegrityReport,
+) -> Result<(), ChainStorageError> {
+    let headers: BTreeMap<u64, (HashOutput, u64, u64)> = lmdb_filter_map_values(txn, headers_db, |h: BlockHeader| {
+        Ok(Some((h.height, (h.hash(), h.kernel_mmr_size, h.output_mmr_size))))
+    })?
+    .into_iter()
+    .collect();
+    let block_hashes: BTreeMap<HashOutput, u64> =
+        lmdb_filter_map_key_values(txn, block_hashes_db, |key, height: u64| {
+            Ok(Some((key.to_vec(), height)))
+        })?
+        .into_iter()
+        .collect();
+    let kernel_sizes: BTreeMap<Vec<u8>, u64> =
+        lmdb_filter_map_key_values(txn, kernel_mmr_size_index, |key, height: u64| {
+            Ok(Some((key.to_vec(), height)))
+        })?
+        .into_iter()
+        .collect();
+    let output_sizes: BTreeMap<Vec<u8>, (u64, HashOutput)> =
+        lmdb_filter_map_key_values(txn, output_mmr_size_index, |key, value: (u64, HashOutput)| {
+            Ok(Some((key.to_vec(), value)))
+        })?
+        .into_iter()
+        .collect();
+
+    for (height, (hash, kernel_mmr_size, output_mmr_size)) in &headers {
+        if block_hashes.get(hash) != Some(height) {
+            report.push(
+                LMDB_DB_BLOCK_HASHES,
+                to_hex(hash),
+                format!("entry for header at height {} is missing or incorrect", height),
+                true,
+            );
+        }
+        if kernel_sizes.get(&kernel_mmr_size.to_be_bytes().to_vec()) != Some(height) {
+            report.push(
+                LMDB_DB_KERNEL_MMR_SIZE_INDEX,
+                kernel_mmr_size.to_string(),
+                format!("entry for header at height {} is missing or incorrect", height),
+                true,
+            );
+        }
+        match output_sizes.get(&output_mmr_size.to_be_bytes().to_vec()) {
+            Some((index_height, index_hash)) if index_height == height && index_hash == hash => {},
+            _ => report.push(
+                LMDB_DB_UTXO_MMR_SIZE_INDEX,
+                output_mmr_size.to_string(),
+                format!("entry for header at height {} is missing or incorrect", height),
+                true,
+            ),
+        }
+    }
+
+    for (hash, height) in &block_hashes {
+        if headers.get(height).map(|(h, _, _)| h) != Some(hash) {
+            report.push(
+                LMDB_DB_BLOCK_HASHES,
+                to_hex(hash),
+                format!("refers to height {} which does not contain this header", height),
+                true,
+            );
+        }
+    }
+    for (size, height) in &kernel_sizes {
+        let is_valid = match (headers.get(height), be_bytes_to_u64(size)) {
+            (Some((_, kernel_mmr_size, _)), Some(size)) => *kernel_mmr_size == size,
+            _ => false,
+        };
+        if !is_valid {
+            report.push(
+                LMDB_DB_KERNEL_MMR_SIZE_INDEX,
+                format_size_key(size),
+                format!("refers to height {} which does not have this kernel MMR size", height),
+                true,
+            );
+        }
+    }
+    for (size, (height, hash)) in &output_sizes {
+        let is_valid = match (headers.get(height), be_bytes_to_u64(size)) {
+            (Some((header_hash, _, output_mmr_size)), Some(size)) => *output_mmr_size == size && header_hash == hash,
+            _ => false,
+        };
+        if !is_valid {
+            report.push(
+                LMDB_DB_UTXO_MMR_SIZE_INDEX,
+                format_size_key(size),
+                format!(
+                    "refers to header {} at height {} which does not have this output MMR size",
+                    to_hex(hash),
+                    height
+                ),
+                true,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Rebuilds the block hash and MMR size indexes from `headers`
+pub fn rebuild_header_indexes(
+    txn: &WriteTransaction<'_>,
+    headers_db: &Database,
+    block_hashes_db: &Database,
+    kernel_mmr_size_index: &Database,
+    output_mmr_size_index: &Database,
+) -> Result<(), ChainStorageError> {
+    let headers = lmdb_filter_map_values(txn, headers_db, |h: BlockHeader| Ok(Some(h)))?;
+    info!(
+        target: LOG_TARGET,
+        "Rebuilding {}, {} and {} from {} headers",
+        LMDB_DB_BLOCK_HASHES,
+        LMDB_DB_KERNEL_MMR_SIZE_INDEX,
+        LMDB_DB_UTXO_MMR_SIZE_INDEX,
+        headers.len()
+    );
+    lmdb_clear(txn, block_hashes_db)?;
+    lmdb_clear(txn, kernel_mmr_size_index)?;
+    lmdb_clear(txn, output_mmr_size_index)?;
+    for header in headers {
+        let hash = header.hash();
+        lmdb_replace(txn, block_hashes_db, hash.as_slice(), &header.height)?;
+        lmdb_replace(
+            txn,
+            kernel_mmr_size_index,
+            &header.kernel_mmr_size.to_be_bytes(),
+            &header.height,
+        )?;
+        lmdb_replace(
+            txn,
+            output_mmr_size_index,
+            &header.output_mmr_size.to_be_bytes(),
+            &(header.height, hash.as_slice()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Checks that the deleted bitmap of every block above the pruned height is the deleted bitmap of the previous block
+/// plus the outputs spent by the inputs of the block. These bitmaps are consensus data, so discrepancies are not
+/// repairable.
+pub fn check_deleted_bitmaps(
+    txn: &ConstTransaction<'_>,
+    headers_db: &Database,
+    block_accumulated_data_db: &Database,
+    inputs_db: &Database,
+    pruned_height: u64,
+    chain_height: u64,
+    report: &mut IntegrityReport,
+) -> Result<(), ChainStorageError> {
+    let mut prev_deleted = None;
+    for height in pruned_height..=chain_height {
+        let data: Option<BlockAccumulatedData> = lmdb_get(txn, block_accumulated_data_db, &height)?;
+        let data = match data {
+            Some(data) => data,
+            None => {
+                report.push(
+                    LMDB_DB_BLOCK_ACCUMULATED_DATA,
+                    height.to_string(),
+                    "block accumulated data is missing for a block in the main chain",
+                    false,
+                );
+                prev_deleted = None;
+                continue;
+            },
+        };
+
+        // The inputs of blocks at or below the pruned height are not stored
+        if let Some(prev_deleted) = prev_deleted.take() {
+            let header: Option<BlockHeader> = lmdb_get(txn, headers_db, &height)?;
+            let header = match header {
+                Some(header) => header,
+                None => {
+                    report.push(
+                        LMDB_DB_BLOCK_ACCUMULATED_DATA,
+                        height.to_string(),
+                        "there is no header for this block",
+                        false,
+                    );
+                    continue;
+                },
+            };
+            let inputs: Vec<TransactionInputRowData> =
+                lmdb_fetch_keys_starting_with(&to_hex(&header.hash()), txn, inputs_db)?;
+            let mut expected = Bitmap::create();
+            for input in &inputs {
+                expected.add(input.mmr_position);
+            }
+            expected.or_inplace(&prev_deleted);
+
+            let unexpected = data.deleted().andnot(&expected);
+            if !unexpected.is_empty() {
+                report.push(
+                    LMDB_DB_BLOCK_ACCUMULATED_DATA,
+                    height.to_string(),
+                    format!(
+                        "deleted bitmap contains outputs that are not spent by this or a previous block: {}",
+                        format_positions(&unexpected)
+                    ),
+                    false,
+                );
+            }
+            let missing = expected.andnot(data.deleted());
+            if !missing.is_empty() {
+                report.push(
+                    LMDB_DB_BLOCK_ACCUMULATED_DATA,
+                    height.to_string(),
+                    format!(
+                        "deleted bitmap does not contain outputs spent by this or a previous block: {}",
+                        format_positions(&missing)
+                    ),
+                    false,
+                );
+            }
+        }
+        prev_deleted = Some(data.deleted().clone());
+    }
+    Ok(())
+}
+
+/// Checks that the orphan parent map, orphan chain tips and orphan accumulated data tables only refer to stored
+/// orphans, and that every orphan is in the parent map
+pub fn check_orphan_indexes(
+    txn: &ConstTransaction<'_>,
+    orphans_db: &Database,
+    orphan_parent_map_index: &Database,
+    orphan_chain_tips_db: &Database,
+    orphan_header_accumulated_data_db: &Database,
+    report: &mut IntegrityReport,
+) -> Result<(), ChainStorageError> {
+    let orphans: BTreeMap<HashOutput, HashOutput> =
+        lmdb_filter_map_key_values(txn, orphans_db, |key, block: Block| {
+            Ok(Some((key.to_vec(), block.header.prev_hash)))
+        })?
+        .into_iter()
+        .collect();
+    let parent_map: BTreeSet<(HashOutput, HashOutput)> =
+        lmdb_filter_map_key_values(txn, orphan_parent_map_index, |key, child: HashOutput| {
+            Ok(Some((key.to_vec(), child)))
+        })?
+        .into_iter()
+        .collect();
+
+    for (hash, prev_hash) in &orphans {
+        if !parent_map.contains(&(prev_hash.clone(), hash.clone())) {
+            report.push(
+                LMDB_DB_ORPHAN_PARENT_MAP_INDEX,
+                to_hex(prev_hash),
+                format!("entry for orphan {} is missing", to_hex(hash)),
+                true,
+            );
+        }
+    }
+    for (parent, child) in &parent_map {
+        match orphans.get(child) {
+            None => report.push(
+                LMDB_DB_ORPHAN_PARENT_MAP_INDEX,
+                to_hex(parent),
+                format!("refers to orphan {} which does not exist", to_hex(child)),
+                true,
+            ),
+            Some(prev_hash) if prev_hash != parent => report.push(
+                LMDB_DB_ORPHAN_PARENT_MAP_INDEX,
+                to_hex(parent),
+                format!(
+                    "refers to orphan {} which has parent {}",
+                    to_hex(child),
+                    to_hex(prev_hash)
+                ),
+                true,
+            ),
+            Some(_) => {},
+        }
+    }
+
+    for hash in fetch_dangling_orphan_keys::<HashOutput>(txn, orphan_chain_tips_db, &orphans)? {
+        report.push(
+            LMDB_DB_ORPHAN_CHAIN_TIPS,
+            to_hex(&hash),
+            "refers to an orphan that does not exist",
+            true,
+        );
+    }
+    for hash in
+        fetch_dangling_orphan_keys::<BlockHeaderAccumulatedData>(txn, orphan_header_accumulated_data_db, &orphans)?
+    {
+        report.push(
+            LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA,
+            to_hex(&hash),
+            "accumulated data is stored for an orphan that does not exist",
+            true,
+        );
+    }
+    Ok(())
+}
+
+/// Rebuilds the orphan parent map from `orphans`, and removes orphan chain tips and accumulated data that do not refer
+/// to a stored orphan
+pub fn repair_orphan_indexes(
+    txn: &WriteTransaction<'_>,
+    orphans_db: &Database,
+    orphan_parent_map_index: &Database,
+    orphan_chain_tips_db: &Database,
+    orphan_header_accumulated_data_db: &Database,
+) -> Result<(), ChainStorageError> {
+    let orphans: BTreeMap<HashOutput, HashOutput> =
+        lmdb_filter_map_key_values(txn, orphans_db, |key, block: Block| {
+            Ok(Some((key.to_vec(), block.header.prev_hash)))
+        })?
+        .into_iter()
+        .collect();
+    info!(
+        target: LOG_TARGET,
+        "Rebuilding {} from {} orphans",
+        LMDB_DB_ORPHAN_PARENT_MAP_INDEX,
+        orphans.len()
+    );
+    lmdb_clear(txn, orphan_parent_map_index)?;
+    for (hash, prev_hash) in &orphans {
+        lmdb_insert_dup(txn, orphan_parent_map_index, prev_hash.as_slice(), hash)?;
+    }
+
+    for hash in fetch_dangling_orphan_keys::<HashOutput>(txn, orphan_chain_tips_db, &orphans)? {
+        debug!(target: LOG_TARGET, "Removing orphan chain tip {}", to_hex(&hash));
+        lmdb_delete(txn, orphan_chain_tips_db, hash.as_slice())?;
+    }
+    for hash in
+        fetch_dangling_orphan_keys::<BlockHeaderAccumulatedData>(txn, orphan_header_accumulated_data_db, &orphans)?
+    {
+        debug!(
+            target: LOG_TARGET,
+            "Removing orphan accumulated data for {}",
+            to_hex(&hash)
+        );
+        lmdb_delete(txn, orphan_header_accumulated_data_db, hash.as_slice())?;
+    }
+    Ok(())
+}
+
+/// Returns the keys in `db` that are not the hash of a stored orphan
+fn fetch_dangling_orphan_keys<V: serde::de::DeserializeOwned>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    orphans: &BTreeMap<HashOutput, HashOutput>,
+) -> Result<Vec<HashOutput>, ChainStorageError> {
+    lmdb_filter_map_key_values(txn, db, |key, _: V| {
+        if orphans.contains_key(key) {
+            Ok(None)
+        } else {
+            Ok(Some(key.to_vec()))
+        }
+    })
+}
+
+/// Fetches the excess and excess signature index keys and the index value of every kernel, keyed by the kernel row key
+fn fetch_kernel_index_entries<F>(
+    txn: &ConstTransaction<'_>,
+    kernels_db: &Database,
+    read_archived_kernel: F,
+) -> Result<Vec<(String, (Vec<u8>, Vec<u8>, KernelIndexValue))>, ChainStorageError>
+where
+    F: Fn(&KernelIndexValue) -> Result<TransactionKernel, ChainStorageError>,
+{
+    // Archived kernels are read once the cursor over the kernels table is closed
+    let rows = lmdb_filter_map_key_values(txn, kernels_db, |key, row: TransactionKernelRowData| {
+        let keys = row
+            .kernel
+            .as_ref()
+            .map(|kernel| (kernel.excess.as_bytes().to_vec(), excess_sig_key(&kernel.excess_sig)));
+        Ok(Some((
+            row_key(key),
+            keys,
+            (row.header_hash, row.mmr_position, row.hash),
+        )))
+    })?;
+    rows.into_iter()
+        .map(|(key, keys, value)| {
+            let (excess, excess_sig) = match keys {
+                Some(keys) => keys,
+                None => {
+                    let kernel = read_archived_kernel(&value)?;
+                    (kernel.excess.as_bytes().to_vec(), excess_sig_key(&kernel.excess_sig))
+                },
+            };
+            Ok((key, (excess, excess_sig, value)))
+        })
+        .collect()
+}
+
+fn row_key(key: &[u8]) -> String {
+    String::from_utf8_lossy(key).into_owned()
+}
+
+fn kernel_key((header_hash, mmr_position, hash): &KernelIndexValue) -> String {
+    format!("{}-{:010}-{}", to_hex(header_hash), mmr_position, to_hex(hash))
+}
+
+fn excess_sig_key(excess_sig: &Signature) -> Vec<u8> {
+    let mut key = Vec::<u8>::new();
+    key.extend(excess_sig.get_public_nonce().as_bytes());
+    key.extend(excess_sig.get_signature().as_bytes());
+    key
+}
+
+fn be_bytes_to_u64(bytes: &[u8]) -> Option<u64> {
+    <[u8; 8]>::try_from(bytes).ok().map(u64::from_be_bytes)
+}
+
+fn format_size_key(bytes: &[u8]) -> String {
+    be_bytes_to_u64(bytes)
+        .map(|size| size.to_string())
+        .unwrap_or_else(|| to_hex(bytes))
+}
+
+fn format_positions(positions: &Bitmap) -> String {
+    let listed = positions
+        .iter()
+        .take(MAX_LISTED_POSITIONS)
+        .map(|pos| pos.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let count = positions.cardinality() as usize;
+    if count > MAX_LISTED_POSITIONS {
+        format!("{} and {} more", listed, count - MAX_LISTED_POSITIONS)
+    } else {
+        listed
+    }
+}
@@ -36,7 +36,7 @@ use lmdb_zero::{
 };
 use log::*;
 use serde::{de::DeserializeOwned, Serialize};
-use std::fmt::Debug;
+use std::{convert::TryInto, fmt::Debug};
 use tari_crypto::tari_utilities::hex::to_hex;
 
 pub const LOG_TARGET: &str = "c::cs::lmdb_db::lmdb";
@@ -136,6 +136,12 @@ where K: AsLmdbBytes + ?Sized {
     Ok(())
 }
 
+/// Removes every entry from the given table
+pub fn lmdb_clear(txn: &WriteTransaction<'_>, db: &Database) -> Result<(), ChainStorageError> {
+    txn.access().clear_db(&db)?;
+    Ok(())
+}
+
 pub fn lmdb_delete_key_value<K, V>(
     txn: &WriteTransaction<'_>,
     db: &Database,
@@ -258,6 +264,49 @@ where V: DeserializeOwned {
     }
 }
 
+/// Returns the largest key of a table with integer keys, or `None` if the table is empty
+pub fn lmdb_last_u64_key(txn: &ConstTransaction<'_>, db: &Database) -> Result<Option<u64>, ChainStorageError> {
+    let mut cursor = txn.cursor(db)?;
+    let access = txn.access();
+    match cursor.last::<[u8], [u8]>(&access).to_opt()? {
+        Some((key, _)) => u64_from_key(key).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Returns up to `limit` rows of a table with integer keys, in key order, starting from the first key that is greater
+/// than or equal to `start`
+pub fn lmdb_fetch_u64_range<V>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    start: u64,
+    limit: usize,
+) -> Result<Vec<(u64, V)>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    let mut cursor = txn.cursor(db)?;
+    let access = txn.access();
+    let mut result = Vec::new();
+    let mut row = cursor
+        .seek_range_k::<[u8], [u8]>(&access, &start.to_ne_bytes()[..])
+        .to_opt()?;
+    while let Some((key, val)) = row {
+        if result.len() >= limit {
+            break;
+        }
+        result.push((u64_from_key(key)?, deserialize(val)?));
+        row = cursor.next::<[u8], [u8]>(&access).to_opt()?;
+    }
+    Ok(result)
+}
+
+fn u64_from_key(key: &[u8]) -> Result<u64, ChainStorageError> {
+    key.try_into()
+        .map(u64::from_ne_bytes)
+        .map_err(|_| ChainStorageError::AccessError(format!("Invalid integer key: {}", to_hex(key))))
+}
+
 pub fn lmdb_exists<K>(txn: &ConstTransaction<'_>, db: &Database, key: &K) -> Result<bool, ChainStorageError>
 where K: AsLmdbBytes + ?Sized {
     let access = txn.access();
@@ -367,3 +416,76 @@ where
     }
     Ok(result)
 }
+
+/// Fetches at most `limit` rows with their keys, starting at the first key after `after`, or at the first row if
+/// `after` is `None`
+pub fn lmdb_fetch_key_values_after<V>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    after: Option<&[u8]>,
+    limit: usize,
+) -> Result<Vec<(Vec<u8>, V)>, ChainStorageError>
+where
+    V: DeserializeOwned,
+{
+    let access = txn.access();
+    let mut cursor = txn.cursor(db).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not get read cursor from lmdb: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })?;
+    let iter = CursorIter::new(
+        MaybeOwned::Borrowed(&mut cursor),
+        &access,
+        |c, a| match after {
+            Some(key) => c.seek_range_k::<[u8], [u8]>(a, key),
+            None => c.first(a),
+        },
+        Cursor::next::<[u8], [u8]>,
+    )?;
+
+    let mut result = Vec::with_capacity(limit);
+    for row in iter {
+        let (key, val) = row?;
+        // The seek lands on `after` itself if it is still in the table
+        if after == Some(key) {
+            continue;
+        }
+        if result.len() == limit {
+            break;
+        }
+        result.push((key.to_vec(), deserialize::<V>(val)?));
+    }
+    Ok(result)
+}
+
+/// Like [lmdb_filter_map_values], but the key of each row is also passed to `f`
+pub fn lmdb_filter_map_key_values<F, V, R>(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+    f: F,
+) -> Result<Vec<R>, ChainStorageError>
+where
+    F: Fn(&[u8], V) -> Result<Option<R>, ChainStorageError>,
+    V: DeserializeOwned,
+{
+    let access = txn.access();
+    let mut cursor = txn.cursor(db).map_err(|e| {
+        error!(target: LOG_TARGET, "Could not get read cursor from lmdb: {:?}", e);
+        ChainStorageError::AccessError(e.to_string())
+    })?;
+    let iter = CursorIter::new(
+        MaybeOwned::Borrowed(&mut cursor),
+        &access,
+        |c, a| c.first(a),
+        Cursor::next::<[u8], [u8]>,
+    )?;
+
+    let mut result = vec![];
+    for row in iter {
+        let (key, val) = row?;
+        if let Some(r) = f(key, deserialize::<V>(val)?)? {
+            result.push(r);
+        }
+    }
+    Ok(result)
+}
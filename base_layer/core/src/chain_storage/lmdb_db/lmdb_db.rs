@@ -26,12 +26,16 @@ use crate::{
         db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation},
         error::{ChainStorageError, OrNotFound},
         lmdb_db::{
+            archive::{ArchivedBlockBody, ArchivedBlockIndex, BlockArchive},
+            integrity,
             lmdb::{
+                lmdb_clear,
                 lmdb_delete,
                 lmdb_delete_key_value,
                 lmdb_delete_keys_starting_with,
                 lmdb_exists,
                 lmdb_fetch_keys_starting_with,
+                lmdb_fetch_u64_range,
                 lmdb_filter_map_values,
                 lmdb_first_after,
                 lmdb_get,
@@ -39,12 +43,15 @@ use crate::{
                 lmdb_insert,
                 lmdb_insert_dup,
                 lmdb_last,
+                lmdb_last_u64_key,
                 lmdb_len,
                 lmdb_replace,
             },
+            migrations::{self, MigrationReport},
             TransactionInputRowData,
             TransactionKernelRowData,
             TransactionOutputRowData,
+            LMDB_DB_ARCHIVE_INDEX,
             LMDB_DB_BLOCK_ACCUMULATED_DATA,
             LMDB_DB_BLOCK_HASHES,
             LMDB_DB_HEADERS,
@@ -60,7 +67,11 @@ use crate::{
             LMDB_DB_ORPHAN_CHAIN_TIPS,
             LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA,
             LMDB_DB_ORPHAN_PARENT_MAP_INDEX,
+            LMDB_DB_REORGS,
+            LMDB_DB_TXOS_COMMITMENT_INDEX,
             LMDB_DB_TXOS_HASH_TO_INDEX,
+            LMDB_DB_TXOS_SCRIPT_HASH_INDEX,
+            LMDB_DB_TXOS_SPENT_INDEX,
             LMDB_DB_UTXOS,
             LMDB_DB_UTXO_MMR_SIZE_INDEX,
         },
@@ -68,8 +79,11 @@ use crate::{
         ChainBlock,
         ChainHeader,
         HorizonData,
+        IntegrityReport,
         MmrTree,
         PrunedOutput,
+        Reorg,
+        MAX_REORG_JOURNAL_ENTRIES,
     },
     crypto::tari_utilities::hex::to_hex,
     transactions::{
@@ -96,6 +110,10 @@ type DatabaseRef = Arc<Database<'static>>;
 
 pub const LOG_TARGET: &str = "c::cs::lmdb_db::lmdb_db";
 
+/// The maximum number of blocks that are moved to the block archive in a single database transaction. A node that
+/// enables the archive on an existing chain catches up over several blocks rather than in one very large transaction.
+const MAX_BLOCKS_ARCHIVED_PER_TRANSACTION: u64 = 100;
+
 /// This is a lmdb-based blockchain database for persistent storage of the chain state.
 pub struct LMDBDatabase {
     env: Arc<Environment>,
@@ -108,6 +126,9 @@ pub struct LMDBDatabase {
     utxos_db: DatabaseRef,
     inputs_db: DatabaseRef,
     txos_hash_to_index_db: DatabaseRef,
+    txos_commitment_index: DatabaseRef,
+    txos_script_hash_index: DatabaseRef,
+    txos_spent_index: DatabaseRef,
     kernels_db: DatabaseRef,
     kernel_excess_index: DatabaseRef,
     kernel_excess_sig_index: DatabaseRef,
@@ -118,14 +139,19 @@ pub struct LMDBDatabase {
     orphan_header_accumulated_data_db: DatabaseRef,
     orphan_chain_tips_db: DatabaseRef,
     orphan_parent_map_index: DatabaseRef,
+    reorgs_db: DatabaseRef,
+    archive_index: DatabaseRef,
+    is_output_index_enabled: bool,
+    archive: BlockArchive,
+    archive_depth: Option<u64>,
     _file_lock: Arc<File>,
 }
 
 impl LMDBDatabase {
-    pub fn new(store: LMDBStore, file_lock: File) -> Result<Self, ChainStorageError> {
+    pub fn new<P: AsRef<Path>>(store: LMDBStore, file_lock: File, path: P) -> Result<Self, ChainStorageError> {
         let env = store.env();
 
-        let res = Self {
+        let mut res = Self {
             metadata_db: get_database(&store, LMDB_DB_METADATA)?,
             headers_db: get_database(&store, LMDB_DB_HEADERS)?,
             header_accumulated_data_db: get_database(&store, LMDB_DB_HEADER_ACCUMULATED_DATA)?,
@@ -134,6 +160,9 @@ impl LMDBDatabase {
             utxos_db: get_database(&store, LMDB_DB_UTXOS)?,
             inputs_db: get_database(&store, LMDB_DB_INPUTS)?,
             txos_hash_to_index_db: get_database(&store, LMDB_DB_TXOS_HASH_TO_INDEX)?,
+            txos_commitment_index: get_database(&store, LMDB_DB_TXOS_COMMITMENT_INDEX)?,
+            txos_script_hash_index: get_database(&store, LMDB_DB_TXOS_SCRIPT_HASH_INDEX)?,
+            txos_spent_index: get_database(&store, LMDB_DB_TXOS_SPENT_INDEX)?,
             kernels_db: get_database(&store, LMDB_DB_KERNELS)?,
             kernel_excess_index: get_database(&store, LMDB_DB_KERNEL_EXCESS_INDEX)?,
             kernel_excess_sig_index: get_database(&store, LMDB_DB_KERNEL_EXCESS_SIG_INDEX)?,
@@ -144,14 +173,226 @@ impl LMDBDatabase {
             monero_seed_height_db: get_database(&store, LMDB_DB_MONERO_SEED_HEIGHT)?,
             orphan_chain_tips_db: get_database(&store, LMDB_DB_ORPHAN_CHAIN_TIPS)?,
             orphan_parent_map_index: get_database(&store, LMDB_DB_ORPHAN_PARENT_MAP_INDEX)?,
+            reorgs_db: get_database(&store, LMDB_DB_REORGS)?,
+            archive_index: get_database(&store, LMDB_DB_ARCHIVE_INDEX)?,
+            is_output_index_enabled: false,
+            archive: BlockArchive::open(path.as_ref().join("archive"))?,
+            archive_depth: None,
             env,
             env_config: store.env_config(),
             _file_lock: Arc::new(file_lock),
         };
 
+        res.is_output_index_enabled = {
+            let txn = res.read_transaction()?;
+            fetch_is_output_index_enabled(&txn, &res.metadata_db)?
+        };
+
         Ok(res)
     }
 
+    /// Returns true if the commitment, script hash and spent output indexes are being maintained
+    pub fn is_output_index_enabled(&self) -> bool {
+        self.is_output_index_enabled
+    }
+
+    /// Enables or disables the commitment, script hash and spent output indexes. Enabling the index on a database that
+    /// already contains blocks rebuilds it from the stored outputs and inputs, which may take some time on a large
+    /// chain. Disabling the index removes all index entries.
+    pub fn set_output_index_enabled(&mut self, enabled: bool) -> Result<(), ChainStorageError> {
+        if self.is_output_index_enabled == enabled {
+            return Ok(());
+        }
+        LMDBStore::resize_if_required(&self.env, &self.env_config)?;
+        let write_txn = self.write_transaction()?;
+        lmdb_clear(&write_txn, &self.txos_commitment_index)?;
+        lmdb_clear(&write_txn, &self.txos_script_hash_index)?;
+        lmdb_clear(&write_txn, &self.txos_spent_index)?;
+        if enabled {
+            let timer = Instant::now();
+            info!(target: LOG_TARGET, "Building output index. This may take some time.");
+            self.build_output_index(&write_txn)?;
+            info!(target: LOG_TARGET, "Output index built in {:.2?}", timer.elapsed());
+        }
+        self.set_metadata(
+            &write_txn,
+            MetadataKey::OutputIndexEnabled,
+            MetadataValue::OutputIndexEnabled(enabled),
+        )?;
+        write_txn
+            .commit()
+            .map_err(|e| ChainStorageError::AccessError(e.to_string()))?;
+        self.is_output_index_enabled = enabled;
+        Ok(())
+    }
+
+    /// Returns the depth below which block bodies are moved to the block archive, or None if archiving is disabled
+    pub fn archive_depth(&self) -> Option<u64> {
+        self.archive_depth
+    }
+
+    /// Sets the depth below the chain tip at which the inputs, outputs and kernels of a block are moved out of LMDB
+    /// into compressed block files in the `archive` directory next to the database. Blocks are archived as the chain
+    /// grows. Disabling archiving does not restore archived blocks, which remain readable from the block files.
+    pub fn set_archive_depth(&mut self, depth: Option<u64>) {
+        self.archive_depth = depth;
+    }
+
+    fn build_output_index(&self, txn: &WriteTransaction<'_>) -> Result<(), ChainStorageError> {
+        // Collect the index entries before inserting because the cursor and the inserts cannot share the transaction
+        // accessor
+        let outputs = lmdb_filter_map_values(txn, &self.utxos_db, |row: TransactionOutputRowData| {
+            let entry = match row.output {
+                Some(ref output) => Some((output.commitment.as_bytes().to_vec(), script_hash(output)?)),
+                None => None,
+            };
+            Ok(Some((row.header_hash, row.mmr_position, entry)))
+        })?;
+        for (header_hash, mmr_position, entry) in outputs {
+            let entry = match entry {
+                Some(entry) => Some(entry),
+                // The output has either been pruned or archived
+                None => self
+                    .fetch_archived_output(txn, &header_hash, mmr_position)?
+                    .map(|output| {
+                        Ok::<_, ChainStorageError>((output.commitment.as_bytes().to_vec(), script_hash(&output)?))
+                    })
+                    .transpose()?,
+            };
+            if let Some((commitment, script_hash)) = entry {
+                let key = output_key(&header_hash, mmr_position);
+                lmdb_insert_dup(txn, &self.txos_commitment_index, commitment.as_slice(), &key)?;
+                lmdb_insert_dup(txn, &self.txos_script_hash_index, script_hash.as_slice(), &key)?;
+            }
+        }
+
+        let mut inputs = lmdb_filter_map_values(txn, &self.inputs_db, |row: TransactionInputRowData| {
+            Ok(Some((row.input.output_hash(), row.header_hash)))
+        })?;
+        let archive_index =
+            lmdb_filter_map_values(txn, &self.archive_index, |index: ArchivedBlockIndex| Ok(Some(index)))?;
+        for index in archive_index {
+            inputs.extend(
+                self.archive
+                    .read_inputs(&index)?
+                    .into_iter()
+                    .map(|row| (row.input.output_hash(), row.header_hash)),
+            );
+        }
+        for (output_hash, header_hash) in inputs {
+            self.insert_spent_index(txn, &output_hash, header_hash)?;
+        }
+        Ok(())
+    }
+
+    fn insert_output_index(
+        &self,
+        txn: &WriteTransaction<'_>,
+        output: &TransactionOutput,
+        key: &str,
+    ) -> Result<(), ChainStorageError> {
+        lmdb_insert_dup(txn, &self.txos_commitment_index, output.commitment.as_bytes(), &key)?;
+        lmdb_insert_dup(txn, &self.txos_script_hash_index, script_hash(output)?.as_slice(), &key)
+    }
+
+    fn delete_output_index(
+        &self,
+        txn: &WriteTransaction<'_>,
+        output: &TransactionOutput,
+        key: &str,
+    ) -> Result<(), ChainStorageError> {
+        lmdb_delete_key_value(txn, &self.txos_commitment_index, output.commitment.as_bytes(), &key)?;
+        lmdb_delete_key_value(txn, &self.txos_script_hash_index, script_hash(output)?.as_slice(), &key)
+    }
+
+    fn insert_spent_index(
+        &self,
+        txn: &WriteTransaction<'_>,
+        output_hash: &HashOutput,
+        header_hash: HashOutput,
+    ) -> Result<(), ChainStorageError> {
+        let height =
+            self.fetch_height_from_hash(txn, &header_hash)
+                .or_not_found("BlockHash", "hash", header_hash.to_hex())?;
+        lmdb_insert(
+            txn,
+            &self.txos_spent_index,
+            output_hash.as_slice(),
+            &(header_hash, height),
+            "txos_spent_index",
+        )
+    }
+
+    fn fetch_indexed_outputs(
+        &self,
+        index: &Database,
+        key: &[u8],
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        if !self.is_output_index_enabled {
+            return Err(ChainStorageError::OutputIndexNotEnabled);
+        }
+        let txn = self.read_transaction()?;
+        let keys: Vec<String> = lmdb_get_multiple(&txn, index, key)?;
+        let mut outputs = Vec::with_capacity(keys.len());
+        for key in keys {
+            let row: TransactionOutputRowData =
+                lmdb_get(&txn, &self.utxos_db, key.as_str()).or_not_found("TransactionOutput", "key", key.clone())?;
+            // Pruned outputs are removed from the index, so this is always expected to be Some unless the output has
+            // been archived
+            let output = match row.output {
+                Some(output) => Some(output),
+                None => self.fetch_archived_output(&txn, &row.header_hash, row.mmr_position)?,
+            };
+            if let Some(output) = output {
+                outputs.push((output, row.mmr_position, row.mined_height));
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn run_integrity_checks(&self, txn: &ConstTransaction<'_>) -> Result<IntegrityReport, ChainStorageError> {
+        let mut report = IntegrityReport::default();
+        integrity::check_txo_hash_index(txn, &self.utxos_db, &self.txos_hash_to_index_db, &mut report)?;
+        integrity::check_kernel_indexes(
+            txn,
+            &self.kernels_db,
+            &self.kernel_excess_index,
+            &self.kernel_excess_sig_index,
+            |key| self.read_archived_kernel(txn, key),
+            &mut report,
+        )?;
+        integrity::check_header_indexes(
+            txn,
+            &self.headers_db,
+            &self.block_hashes_db,
+            &self.kernel_mmr_size_index,
+            &self.output_mmr_size_index,
+            &mut report,
+        )?;
+        // An empty database has no chain height, and so no block data to check
+        if lmdb_len(txn, &self.headers_db)? > 0 {
+            integrity::check_deleted_bitmaps(
+                txn,
+                &self.headers_db,
+                &self.block_accumulated_data_db,
+                &self.inputs_db,
+                // The inputs of archived blocks are not stored in LMDB
+                fetch_pruned_height(txn, &self.metadata_db)?.max(fetch_archived_height(txn, &self.metadata_db)?),
+                fetch_chain_height(txn, &self.metadata_db)?,
+                &mut report,
+            )?;
+        }
+        integrity::check_orphan_indexes(
+            txn,
+            &self.orphans_db,
+            &self.orphan_parent_map_index,
+            &self.orphan_chain_tips_db,
+            &self.orphan_header_accumulated_data_db,
+            &mut report,
+        )?;
+        Ok(report)
+    }
+
     /// Try to establish a read lock on the LMDB database. If an exclusive write lock has been previously acquired, this
     /// method will block until that lock is released.
     fn read_transaction(&self) -> Result<ReadTransaction<'_>, ChainStorageError> {
@@ -167,6 +408,7 @@ impl LMDBDatabase {
     fn apply_db_transaction(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
         use WriteOperation::*;
         let write_txn = self.write_transaction()?;
+        let mut best_block_height = None;
         for op in txn.into_operations() {
             trace!(target: LOG_TARGET, "[apply_db_transaction] WriteOperation: {}", op);
             match op {
@@ -239,6 +481,9 @@ impl LMDBDatabase {
                 InsertMoneroSeedHeight(data, height) => {
                     self.insert_monero_seed_height(&write_txn, &data, height)?;
                 },
+                InsertReorg(reorg) => {
+                    self.insert_reorg(&write_txn, &reorg)?;
+                },
                 SetAccumulatedDataForOrphan(chain_header) => {
                     self.set_accumulated_data_for_orphan(
                         &write_txn,
@@ -281,6 +526,7 @@ impl LMDBDatabase {
                     hash,
                     accumulated_difficulty,
                 } => {
+                    best_block_height = Some(height);
                     self.set_metadata(&write_txn, MetadataKey::ChainHeight, MetadataValue::ChainHeight(height))?;
                     self.set_metadata(&write_txn, MetadataKey::BestBlock, MetadataValue::BestBlock(hash))?;
                     self.set_metadata(
@@ -317,9 +563,199 @@ impl LMDBDatabase {
         write_txn
             .commit()
             .map_err(|e| ChainStorageError::AccessError(e.to_string()))?;
+        if let (Some(height), Some(depth)) = (best_block_height, self.archive_depth) {
+            // The transaction has been committed, so a failure to archive is not an error for the caller. The blocks
+            // are archived when the next block is added.
+            let archive_horizon = height.saturating_sub(depth);
+            if let Err(e) = self.archive_blocks(archive_horizon) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not archive blocks below height {}: {}", archive_horizon, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the bodies of the main chain blocks below `archive_horizon` that have not yet been archived to the block
+    /// archive. The inputs of an archived block are removed from LMDB along with the data of its outputs and kernels.
+    /// The output and kernel rows, and the indexes that refer to them, remain in LMDB.
+    ///
+    /// The block bodies are appended to the block files and synced to disk once for the whole batch before the write
+    /// transaction that removes them from LMDB and commits the archive index is started.
+    fn archive_blocks(&self, archive_horizon: u64) -> Result<(), ChainStorageError> {
+        let txn = self.read_transaction()?;
+        // Pruned nodes discard the data of spent outputs and do not keep a block archive
+        if fetch_pruning_horizon(&txn, &self.metadata_db)? > 0 {
+            return Ok(());
+        }
+        let archived_height = fetch_archived_height(&txn, &self.metadata_db)?;
+        if archived_height >= archive_horizon {
+            return Ok(());
+        }
+
+        let end_height = archive_horizon.min(archived_height + MAX_BLOCKS_ARCHIVED_PER_TRANSACTION);
+        let mut blocks = Vec::with_capacity((end_height - archived_height) as usize);
+        for height in archived_height..end_height {
+            let header: BlockHeader =
+                lmdb_get(&txn, &self.headers_db, &height)?.ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader".to_string(),
+                    field: "height".to_string(),
+                    value: height.to_string(),
+                })?;
+            let hash = header.hash();
+            let hash_hex = hash.to_hex();
+            let body = ArchivedBlockBody {
+                inputs: lmdb_fetch_keys_starting_with(&hash_hex, &txn, &self.inputs_db)?,
+                outputs: lmdb_fetch_keys_starting_with(&hash_hex, &txn, &self.utxos_db)?,
+                kernels: lmdb_fetch_keys_starting_with(&hash_hex, &txn, &self.kernels_db)?,
+            };
+            let index = self.archive.append(&body)?;
+            blocks.push((height, hash, body, index));
+        }
+        drop(txn);
+        self.archive.sync()?;
+
+        let write_txn = self.write_transaction()?;
+        for (height, hash, body, index) in blocks {
+            let hash_hex = hash.to_hex();
+            lmdb_delete_keys_starting_with::<TransactionInputRowData>(&write_txn, &self.inputs_db, &hash_hex)?;
+            for mut row in body.outputs {
+                if row.output.take().is_some() {
+                    let key = output_key(&row.header_hash, row.mmr_position);
+                    lmdb_replace(&write_txn, &self.utxos_db, key.as_str(), &row)?;
+                }
+            }
+            for mut row in body.kernels {
+                if row.kernel.take().is_some() {
+                    let key = kernel_key(&row.header_hash, row.mmr_position, &row.hash);
+                    lmdb_replace(&write_txn, &self.kernels_db, key.as_str(), &row)?;
+                }
+            }
+            trace!(
+                target: LOG_TARGET,
+                "Archived block {} ({}) with {} input(s), {} output(s) and {} kernel(s)",
+                height,
+                hash_hex,
+                body.inputs.len(),
+                index.outputs.len(),
+                index.kernels.len()
+            );
+            lmdb_replace(&write_txn, &self.archive_index, hash.as_slice(), &index)?;
+        }
+        self.set_metadata(
+            &write_txn,
+            MetadataKey::ArchivedHeight,
+            MetadataValue::ArchivedHeight(end_height),
+        )?;
+        write_txn.commit()?;
+        debug!(
+            target: LOG_TARGET,
+            "Archived blocks {} to {}",
+            archived_height,
+            end_height - 1
+        );
         Ok(())
     }
 
+    #[allow(clippy::ptr_arg)]
+    fn fetch_archive_index(
+        &self,
+        txn: &ConstTransaction<'_>,
+        header_hash: &HashOutput,
+    ) -> Result<Option<ArchivedBlockIndex>, ChainStorageError> {
+        lmdb_get(txn, &self.archive_index, header_hash.as_slice())
+    }
+
+    /// Fetches the archived inputs of a block, or None if the block has not been archived
+    #[allow(clippy::ptr_arg)]
+    fn fetch_archived_inputs(
+        &self,
+        txn: &ConstTransaction<'_>,
+        header_hash: &HashOutput,
+    ) -> Result<Option<Vec<TransactionInputRowData>>, ChainStorageError> {
+        self.fetch_archive_index(txn, header_hash)?
+            .map(|index| self.archive.read_inputs(&index))
+            .transpose()
+    }
+
+    /// Fetches the data of an output that has been moved to the block archive
+    #[allow(clippy::ptr_arg)]
+    fn fetch_archived_output(
+        &self,
+        txn: &ConstTransaction<'_>,
+        header_hash: &HashOutput,
+        mmr_position: u32,
+    ) -> Result<Option<TransactionOutput>, ChainStorageError> {
+        let index = self.fetch_archive_index(txn, header_hash)?;
+        self.read_archived_output(index.as_ref(), mmr_position)
+    }
+
+    /// Reads the data of an output from the block archive given the archive index entry of its block. Returns None if
+    /// the output is not in the archive.
+    fn read_archived_output(
+        &self,
+        index: Option<&ArchivedBlockIndex>,
+        mmr_position: u32,
+    ) -> Result<Option<TransactionOutput>, ChainStorageError> {
+        index
+            .and_then(|index| index.output(mmr_position))
+            .map(|location| self.archive.read(location))
+            .transpose()
+    }
+
+    /// Returns the kernel of a kernel row, reading it from the block archive if it has been archived. `index` is the
+    /// archive index entry of the block of the kernel.
+    fn kernel_from_row(
+        &self,
+        index: Option<&ArchivedBlockIndex>,
+        row: TransactionKernelRowData,
+    ) -> Result<TransactionKernel, ChainStorageError> {
+        if let Some(kernel) = row.kernel {
+            return Ok(kernel);
+        }
+        let location = index.and_then(|index| index.kernel(row.mmr_position)).ok_or_else(|| {
+            ChainStorageError::CorruptedDatabase(format!(
+                "Kernel `{}` is not in LMDB or the block archive",
+                kernel_key(&row.header_hash, row.mmr_position, &row.hash)
+            ))
+        })?;
+        self.archive.read(location)
+    }
+
+    /// Reads an archived kernel given the header hash, MMR position and hash of its kernel row
+    fn read_archived_kernel(
+        &self,
+        txn: &ConstTransaction<'_>,
+        (header_hash, mmr_position, hash): &(HashOutput, u32, HashOutput),
+    ) -> Result<TransactionKernel, ChainStorageError> {
+        let index = self.fetch_archive_index(txn, header_hash)?;
+        self.kernel_from_row(index.as_ref(), TransactionKernelRowData {
+            kernel: None,
+            header_hash: header_hash.clone(),
+            mmr_position: *mmr_position,
+            hash: hash.clone(),
+        })
+    }
+
+    /// Returns the kernels of the kernel rows of a block, reading the archived kernels from the block archive
+    #[allow(clippy::ptr_arg)]
+    fn kernels_from_rows(
+        &self,
+        txn: &ConstTransaction<'_>,
+        header_hash: &HashOutput,
+        rows: Vec<TransactionKernelRowData>,
+    ) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let index = if rows.iter().any(|row| row.kernel.is_none()) {
+            self.fetch_archive_index(txn, header_hash)?
+        } else {
+            None
+        };
+        rows.into_iter()
+            .map(|row| self.kernel_from_row(index.as_ref(), row))
+            .collect()
+    }
+
     fn prune_output(
         &self,
         txn: &WriteTransaction<'_>,
@@ -330,6 +766,11 @@ impl LMDBDatabase {
         let result = output.output.take();
         // output.output is None
         lmdb_replace(txn, &self.utxos_db, key, &output)?;
+        if self.is_output_index_enabled {
+            if let Some(ref pruned) = result {
+                self.delete_output_index(txn, pruned, key)?;
+            }
+        }
         Ok(result)
     }
 
@@ -343,7 +784,10 @@ impl LMDBDatabase {
     ) -> Result<(), ChainStorageError> {
         let output_hash = output.hash();
         let proof_hash = output.proof.hash();
-        let key = format!("{}-{:010}", header_hash.to_hex(), mmr_position,);
+        let key = output_key(&header_hash, mmr_position);
+        if self.is_output_index_enabled {
+            self.insert_output_index(txn, &output, &key)?;
+        }
         lmdb_insert(
             txn,
             &*self.txos_hash_to_index_db,
@@ -446,7 +890,7 @@ impl LMDBDatabase {
             &*self.kernels_db,
             key.as_str(),
             &TransactionKernelRowData {
-                kernel,
+                kernel: Some(kernel),
                 header_hash,
                 mmr_position,
                 hash,
@@ -464,6 +908,9 @@ impl LMDBDatabase {
     ) -> Result<(), ChainStorageError> {
         let hash = input.hash();
         let key = format!("{}-{:010}-{}", header_hash.to_hex(), mmr_position, hash.to_hex());
+        if self.is_output_index_enabled {
+            self.insert_spent_index(txn, &input.output_hash(), header_hash.clone())?;
+        }
         lmdb_insert(
             txn,
             &*self.inputs_db,
@@ -667,25 +1114,37 @@ impl LMDBDatabase {
             .fetch_height_from_hash(&write_txn, &hash)
             .or_not_found("Block", "hash", hash.to_hex())?;
         lmdb_delete(&write_txn, &self.block_accumulated_data_db, &height)?;
+        // The body of an archived block is in the block archive. The archive records are left in the block file but are
+        // no longer reachable, and the block is archived again if it is added back to the main chain.
+        let archive_index = self.fetch_archive_index(&write_txn, &hash)?;
         let rows = lmdb_delete_keys_starting_with::<TransactionOutputRowData>(&write_txn, &self.utxos_db, &hash_hex)?;
 
-        for utxo in rows {
+        for mut utxo in rows {
             trace!(target: LOG_TARGET, "Deleting UTXO `{}`", to_hex(&utxo.hash));
             lmdb_delete(&write_txn, &self.txos_hash_to_index_db, utxo.hash.as_slice())?;
+            if self.is_output_index_enabled {
+                if utxo.output.is_none() {
+                    utxo.output = self.read_archived_output(archive_index.as_ref(), utxo.mmr_position)?;
+                }
+                if let Some(ref output) = utxo.output {
+                    self.delete_output_index(&write_txn, output, &output_key(&utxo.header_hash, utxo.mmr_position))?;
+                }
+            }
         }
         debug!(target: LOG_TARGET, "Deleting kernels...");
         let kernels =
             lmdb_delete_keys_starting_with::<TransactionKernelRowData>(&write_txn, &self.kernels_db, &hash_hex)?;
-        for kernel in kernels {
+        for row in kernels {
+            let kernel = self.kernel_from_row(archive_index.as_ref(), row)?;
             trace!(
                 target: LOG_TARGET,
                 "Deleting excess `{}`",
-                to_hex(kernel.kernel.excess.as_bytes())
+                to_hex(kernel.excess.as_bytes())
             );
-            lmdb_delete(&write_txn, &self.kernel_excess_index, kernel.kernel.excess.as_bytes())?;
+            lmdb_delete(&write_txn, &self.kernel_excess_index, kernel.excess.as_bytes())?;
             let mut excess_sig_key = Vec::<u8>::new();
-            excess_sig_key.extend(kernel.kernel.excess_sig.get_public_nonce().as_bytes());
-            excess_sig_key.extend(kernel.kernel.excess_sig.get_signature().as_bytes());
+            excess_sig_key.extend(kernel.excess_sig.get_public_nonce().as_bytes());
+            excess_sig_key.extend(kernel.excess_sig.get_signature().as_bytes());
             trace!(
                 target: LOG_TARGET,
                 "Deleting excess signature `{}`",
@@ -694,7 +1153,28 @@ impl LMDBDatabase {
             lmdb_delete(&write_txn, &self.kernel_excess_sig_index, excess_sig_key.as_slice())?;
         }
         debug!(target: LOG_TARGET, "Deleting Inputs...");
-        lmdb_delete_keys_starting_with::<TransactionInputRowData>(&write_txn, &self.inputs_db, &hash_hex)?;
+        let mut inputs =
+            lmdb_delete_keys_starting_with::<TransactionInputRowData>(&write_txn, &self.inputs_db, &hash_hex)?;
+        if let Some(ref archive_index) = archive_index {
+            inputs.extend(self.archive.read_inputs(archive_index)?);
+            debug!(
+                target: LOG_TARGET,
+                "Removing block `{}` from the archive index", hash_hex
+            );
+            lmdb_delete(&write_txn, &self.archive_index, hash.as_slice())?;
+        }
+        if height < fetch_archived_height(&write_txn, &self.metadata_db)? {
+            self.set_metadata(
+                &write_txn,
+                MetadataKey::ArchivedHeight,
+                MetadataValue::ArchivedHeight(height),
+            )?;
+        }
+        if self.is_output_index_enabled {
+            for row in inputs {
+                lmdb_delete(&write_txn, &self.txos_spent_index, row.input.output_hash().as_slice())?;
+            }
+        }
         Ok(())
     }
 
@@ -939,6 +1419,23 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    /// Appends a reorg to the reorg journal and prunes the oldest reorgs that no longer fit in it
+    fn insert_reorg(&self, write_txn: &WriteTransaction<'_>, reorg: &Reorg) -> Result<(), ChainStorageError> {
+        let key = lmdb_last_u64_key(&write_txn, &self.reorgs_db)?
+            .map(|k| k + 1)
+            .unwrap_or(0);
+        lmdb_insert(&write_txn, &self.reorgs_db, &key, reorg, "reorgs")?;
+
+        let num_pruned = (lmdb_len(&write_txn, &self.reorgs_db)? as u64).saturating_sub(MAX_REORG_JOURNAL_ENTRIES);
+        if num_pruned > 0 {
+            let pruned = lmdb_fetch_u64_range::<Reorg>(&write_txn, &self.reorgs_db, 0, num_pruned as usize)?;
+            for (key, _) in pruned {
+                lmdb_delete(&write_txn, &self.reorgs_db, &key)?;
+            }
+        }
+        Ok(())
+    }
+
     fn update_pruned_hash_set(
         &self,
         write_txn: &WriteTransaction<'_>,
@@ -977,7 +1474,7 @@ impl LMDBDatabase {
                 &(pos as u64).to_be_bytes(),
             )
             .or_not_found("BlockHeader", "mmr_position", pos.to_string())?;
-            let key = format!("{}-{:010}", hash.to_hex(), pos);
+            let key = output_key(&hash, pos);
             debug!(target: LOG_TARGET, "Pruning output: {}", key);
             self.prune_output(&write_txn, &key)?;
         }
@@ -1043,7 +1540,30 @@ impl LMDBDatabase {
     }
 }
 
+/// Opens the LMDB chain database at the given path, creating it if it does not exist, and applies any pending schema
+/// migrations.
 pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Result<LMDBDatabase, ChainStorageError> {
+    let (lmdb_store, file_lock) = open_lmdb_store(&path, config)?;
+    migrations::migrate(&lmdb_store, false)?;
+    LMDBDatabase::new(lmdb_store, file_lock, path)
+}
+
+/// Applies any pending schema migrations to the LMDB chain database at the given path without otherwise opening it.
+/// If `dry_run` is true, the database is left unchanged and the report describes the migrations that would be
+/// applied.
+pub fn migrate_lmdb_database<P: AsRef<Path>>(
+    path: P,
+    config: LMDBConfig,
+    dry_run: bool,
+) -> Result<MigrationReport, ChainStorageError> {
+    let (lmdb_store, _file_lock) = open_lmdb_store(path, config)?;
+    migrations::migrate(&lmdb_store, dry_run)
+}
+
+pub(super) fn open_lmdb_store<P: AsRef<Path>>(
+    path: P,
+    config: LMDBConfig,
+) -> Result<(LMDBStore, File), ChainStorageError> {
     let flags = db::CREATE;
     let _ = std::fs::create_dir_all(&path);
 
@@ -1052,7 +1572,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
     let lmdb_store = LMDBBuilder::new()
         .set_path(path)
         .set_env_config(config)
-        .set_max_number_of_databases(20)
+        .set_max_number_of_databases(30)
         .add_database(LMDB_DB_METADATA, flags)
         .add_database(LMDB_DB_HEADERS, flags | db::INTEGERKEY)
         .add_database(LMDB_DB_HEADER_ACCUMULATED_DATA, flags | db::INTEGERKEY)
@@ -1061,6 +1581,9 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
         .add_database(LMDB_DB_UTXOS, flags)
         .add_database(LMDB_DB_INPUTS, flags)
         .add_database(LMDB_DB_TXOS_HASH_TO_INDEX, flags)
+        .add_database(LMDB_DB_TXOS_COMMITMENT_INDEX, flags | db::DUPSORT)
+        .add_database(LMDB_DB_TXOS_SCRIPT_HASH_INDEX, flags | db::DUPSORT)
+        .add_database(LMDB_DB_TXOS_SPENT_INDEX, flags)
         .add_database(LMDB_DB_KERNELS, flags)
         .add_database(LMDB_DB_KERNEL_EXCESS_INDEX, flags)
         .add_database(LMDB_DB_KERNEL_EXCESS_SIG_INDEX, flags)
@@ -1071,9 +1594,11 @@ pub fn create_lmdb_database<P: AsRef<Path>>(path: P, config: LMDBConfig) -> Resu
         .add_database(LMDB_DB_MONERO_SEED_HEIGHT, flags)
         .add_database(LMDB_DB_ORPHAN_CHAIN_TIPS, flags)
         .add_database(LMDB_DB_ORPHAN_PARENT_MAP_INDEX, flags | db::DUPSORT)
+        .add_database(LMDB_DB_REORGS, flags | db::INTEGERKEY)
+        .add_database(LMDB_DB_ARCHIVE_INDEX, flags)
         .build()
         .map_err(|err| ChainStorageError::CriticalError(format!("Could not create LMDB store:{}", err)))?;
-    LMDBDatabase::new(lmdb_store, file_lock)
+    Ok((lmdb_store, file_lock))
 }
 
 pub fn create_recovery_lmdb_database<P: AsRef<Path>>(path: P) -> Result<(), ChainStorageError> {
@@ -1365,12 +1890,8 @@ impl BlockchainBackend for LMDBDatabase {
 
     fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
         let txn = self.read_transaction()?;
-        Ok(
-            lmdb_fetch_keys_starting_with(header_hash.to_hex().as_str(), &txn, &self.kernels_db)?
-                .into_iter()
-                .map(|f: TransactionKernelRowData| f.kernel)
-                .collect(),
-        )
+        let rows = lmdb_fetch_keys_starting_with(header_hash.to_hex().as_str(), &txn, &self.kernels_db)?;
+        self.kernels_from_rows(&txn, header_hash, rows)
     }
 
     fn fetch_kernel_by_excess(
@@ -1382,8 +1903,14 @@ impl BlockchainBackend for LMDBDatabase {
             lmdb_get::<_, (HashOutput, u32, HashOutput)>(&txn, &self.kernel_excess_index, excess)?
         {
             let key = format!("{}-{:010}-{}", header_hash.to_hex(), mmr_position, hash.to_hex());
-            Ok(lmdb_get(&txn, &self.kernels_db, key.as_str())?
-                .map(|kernel: TransactionKernelRowData| (kernel.kernel, header_hash)))
+            lmdb_get(&txn, &self.kernels_db, key.as_str())?
+                .map(|row| {
+                    Ok((
+                        self.kernels_from_rows(&txn, &header_hash, vec![row])?.remove(0),
+                        header_hash,
+                    ))
+                })
+                .transpose()
         } else {
             Ok(None)
         }
@@ -1401,8 +1928,14 @@ impl BlockchainBackend for LMDBDatabase {
             lmdb_get::<_, (HashOutput, u32, HashOutput)>(&txn, &self.kernel_excess_sig_index, key.as_slice())?
         {
             let key = format!("{}-{:010}-{}", header_hash.to_hex(), mmr_position, hash.to_hex());
-            Ok(lmdb_get(&txn, &self.kernels_db, key.as_str())?
-                .map(|kernel: TransactionKernelRowData| (kernel.kernel, header_hash)))
+            lmdb_get(&txn, &self.kernels_db, key.as_str())?
+                .map(|row| {
+                    Ok((
+                        self.kernels_from_rows(&txn, &header_hash, vec![row])?.remove(0),
+                        header_hash,
+                    ))
+                })
+                .transpose()
         } else {
             Ok(None)
         }
@@ -1448,17 +1981,16 @@ impl BlockchainBackend for LMDBDatabase {
                     })?
                     .hash;
 
-                result.extend(
-                    lmdb_fetch_keys_starting_with::<TransactionKernelRowData>(
-                        hash.to_hex().as_str(),
-                        &txn,
-                        &self.kernels_db,
-                    )?
-                    .into_iter()
-                    .skip(skip_amount)
-                    .take(total_size - result.len())
-                    .map(|f| f.kernel),
-                );
+                let rows = lmdb_fetch_keys_starting_with::<TransactionKernelRowData>(
+                    hash.to_hex().as_str(),
+                    &txn,
+                    &self.kernels_db,
+                )?
+                .into_iter()
+                .skip(skip_amount)
+                .take(total_size - result.len())
+                .collect();
+                result.extend(self.kernels_from_rows(&txn, &hash, rows)?);
 
                 skip_amount = 0;
             }
@@ -1525,32 +2057,44 @@ impl BlockchainBackend for LMDBDatabase {
                         value: height.to_string(),
                     })?;
 
-            result.extend(
-                lmdb_fetch_keys_starting_with::<TransactionOutputRowData>(
-                    accum_data.hash.to_hex().as_str(),
-                    &txn,
-                    &self.utxos_db,
-                )?
-                .into_iter()
-                .skip(skip_amount)
-                .take(total_size - result.len())
-                .map(|row| {
-                    if deleted.contains(row.mmr_position) {
-                        return PrunedOutput::Pruned {
-                            output_hash: row.hash,
-                            range_proof_hash: row.range_proof_hash,
-                        };
-                    }
-                    if let Some(output) = row.output {
-                        PrunedOutput::NotPruned { output }
-                    } else {
-                        PrunedOutput::Pruned {
-                            output_hash: row.hash,
-                            range_proof_hash: row.range_proof_hash,
-                        }
-                    }
-                }),
-            );
+            let rows = lmdb_fetch_keys_starting_with::<TransactionOutputRowData>(
+                accum_data.hash.to_hex().as_str(),
+                &txn,
+                &self.utxos_db,
+            )?
+            .into_iter()
+            .skip(skip_amount)
+            .take(total_size - result.len())
+            .collect::<Vec<_>>();
+            // Outputs that are not spent as of `deleted` and have been archived are read from the block archive
+            let archive_index = if rows
+                .iter()
+                .any(|row| row.output.is_none() && !deleted.contains(row.mmr_position))
+            {
+                self.fetch_archive_index(&txn, &accum_data.hash)?
+            } else {
+                None
+            };
+            for row in rows {
+                if deleted.contains(row.mmr_position) {
+                    result.push(PrunedOutput::Pruned {
+                        output_hash: row.hash,
+                        range_proof_hash: row.range_proof_hash,
+                    });
+                    continue;
+                }
+                let output = match row.output {
+                    Some(output) => Some(output),
+                    None => self.read_archived_output(archive_index.as_ref(), row.mmr_position)?,
+                };
+                result.push(match output {
+                    Some(output) => PrunedOutput::NotPruned { output },
+                    None => PrunedOutput::Pruned {
+                        output_hash: row.hash,
+                        range_proof_hash: row.range_proof_hash,
+                    },
+                });
+            }
 
             // Builds a BitMap of the deleted UTXO MMR indexes that occurred at the current height
             let mut diff_bitmap = self
@@ -1591,7 +2135,10 @@ impl BlockchainBackend for LMDBDatabase {
                 index,
                 key
             );
-            if let Some(output) = lmdb_get::<_, TransactionOutputRowData>(&txn, &self.utxos_db, key.as_str())? {
+            if let Some(mut output) = lmdb_get::<_, TransactionOutputRowData>(&txn, &self.utxos_db, key.as_str())? {
+                if output.output.is_none() {
+                    output.output = self.fetch_archived_output(&txn, &output.header_hash, output.mmr_position)?;
+                }
                 if output.output.is_none() {
                     error!(
                         target: LOG_TARGET,
@@ -1616,30 +2163,64 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_outputs_by_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        self.fetch_indexed_outputs(&self.txos_commitment_index, commitment.as_bytes())
+    }
+
+    fn fetch_outputs_by_script_hash(
+        &self,
+        script_hash: &[u8],
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        self.fetch_indexed_outputs(&self.txos_script_hash_index, script_hash)
+    }
+
+    fn fetch_output_spent_in_block(
+        &self,
+        output_hash: &HashOutput,
+    ) -> Result<Option<(HashOutput, u64)>, ChainStorageError> {
+        if !self.is_output_index_enabled {
+            return Err(ChainStorageError::OutputIndexNotEnabled);
+        }
+        let txn = self.read_transaction()?;
+        lmdb_get(&txn, &self.txos_spent_index, output_hash.as_slice())
+    }
+
     fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
         let txn = self.read_transaction()?;
-        Ok(
-            lmdb_fetch_keys_starting_with(header_hash.to_hex().as_str(), &txn, &self.utxos_db)?
-                .into_iter()
-                .map(|f: TransactionOutputRowData| match f.output {
+        let rows: Vec<TransactionOutputRowData> =
+            lmdb_fetch_keys_starting_with(header_hash.to_hex().as_str(), &txn, &self.utxos_db)?;
+        let archive_index = if rows.iter().any(|row| row.output.is_none()) {
+            self.fetch_archive_index(&txn, header_hash)?
+        } else {
+            None
+        };
+        rows.into_iter()
+            .map(|f| {
+                let output = match f.output {
+                    Some(output) => Some(output),
+                    None => self.read_archived_output(archive_index.as_ref(), f.mmr_position)?,
+                };
+                Ok(match output {
                     Some(o) => PrunedOutput::NotPruned { output: o },
                     None => PrunedOutput::Pruned {
                         output_hash: f.hash,
                         range_proof_hash: f.range_proof_hash,
                     },
                 })
-                .collect(),
-        )
+            })
+            .collect()
     }
 
     fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
         let txn = self.read_transaction()?;
-        Ok(
-            lmdb_fetch_keys_starting_with(header_hash.to_hex().as_str(), &txn, &self.inputs_db)?
-                .into_iter()
-                .map(|f: TransactionInputRowData| f.input)
-                .collect(),
-        )
+        let rows = match self.fetch_archived_inputs(&txn, header_hash)? {
+            Some(inputs) => inputs,
+            None => lmdb_fetch_keys_starting_with(header_hash.to_hex().as_str(), &txn, &self.inputs_db)?,
+        };
+        Ok(rows.into_iter().map(|f| f.input).collect())
     }
 
     fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError> {
@@ -1718,6 +2299,11 @@ impl BlockchainBackend for LMDBDatabase {
         lmdb_len(&txn, &self.kernels_db)
     }
 
+    fn fetch_reorgs(&self, start: u64, limit: usize) -> Result<Vec<(u64, Reorg)>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_fetch_u64_range(&txn, &self.reorgs_db, start, limit)
+    }
+
     fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
         trace!(target: LOG_TARGET, "Call to fetch_orphan_chain_tips()");
         let txn = self.read_transaction()?;
@@ -1853,6 +2439,68 @@ impl BlockchainBackend for LMDBDatabase {
         let txn = self.read_transaction()?;
         fetch_horizon_data(&txn, &self.metadata_db)
     }
+
+    fn check_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let timer = Instant::now();
+        let report = self.run_integrity_checks(&txn)?;
+        info!(
+            target: LOG_TARGET,
+            "Integrity check found {} issue(s) in {:.2?}",
+            report.issues().len(),
+            timer.elapsed()
+        );
+        Ok(report)
+    }
+
+    fn repair_integrity(&mut self) -> Result<IntegrityReport, ChainStorageError> {
+        LMDBStore::resize_if_required(&self.env, &self.env_config)?;
+        let write_txn = self.write_transaction()?;
+        let report = self.run_integrity_checks(&write_txn)?;
+        if !report.has_repairable_issues() {
+            return Ok(report);
+        }
+        if report.has_issues_in(LMDB_DB_TXOS_HASH_TO_INDEX) {
+            integrity::rebuild_txo_hash_index(&write_txn, &self.utxos_db, &self.txos_hash_to_index_db)?;
+        }
+        if report.has_issues_in(LMDB_DB_KERNEL_EXCESS_INDEX) || report.has_issues_in(LMDB_DB_KERNEL_EXCESS_SIG_INDEX) {
+            integrity::rebuild_kernel_indexes(
+                &write_txn,
+                &self.kernels_db,
+                &self.kernel_excess_index,
+                &self.kernel_excess_sig_index,
+                |key| self.read_archived_kernel(&write_txn, key),
+            )?;
+        }
+        if report.has_issues_in(LMDB_DB_BLOCK_HASHES) ||
+            report.has_issues_in(LMDB_DB_KERNEL_MMR_SIZE_INDEX) ||
+            report.has_issues_in(LMDB_DB_UTXO_MMR_SIZE_INDEX)
+        {
+            integrity::rebuild_header_indexes(
+                &write_txn,
+                &self.headers_db,
+                &self.block_hashes_db,
+                &self.kernel_mmr_size_index,
+                &self.output_mmr_size_index,
+            )?;
+        }
+        if report.has_issues_in(LMDB_DB_ORPHAN_PARENT_MAP_INDEX) ||
+            report.has_issues_in(LMDB_DB_ORPHAN_CHAIN_TIPS) ||
+            report.has_issues_in(LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA)
+        {
+            integrity::repair_orphan_indexes(
+                &write_txn,
+                &self.orphans_db,
+                &self.orphan_parent_map_index,
+                &self.orphan_chain_tips_db,
+                &self.orphan_header_accumulated_data_db,
+            )?;
+        }
+        write_txn
+            .commit()
+            .map_err(|e| ChainStorageError::AccessError(e.to_string()))?;
+        Ok(report)
+    }
 }
 
 // Fetch the chain metadata
@@ -1931,7 +2579,65 @@ fn fetch_accumulated_work(txn: &ConstTransaction<'_>, db: &Database) -> Result<u
     }
 }
 
-// Fetches the pruning horizon from the provided metadata db.
+// Fetches whether the output index is enabled from the provided metadata db.
+fn fetch_is_output_index_enabled(txn: &ConstTransaction<'_>, db: &Database) -> Result<bool, ChainStorageError> {
+    let k = MetadataKey::OutputIndexEnabled;
+    let val: Option<MetadataValue> = lmdb_get(&txn, &db, &(k as u32))?;
+    match val {
+        Some(MetadataValue::OutputIndexEnabled(enabled)) => Ok(enabled),
+        _ => Ok(false),
+    }
+}
+
+// Fetches the schema version from the provided metadata db, or None if the database predates schema versioning.
+pub(super) fn fetch_schema_version(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+) -> Result<Option<u32>, ChainStorageError> {
+    let k = MetadataKey::SchemaVersion;
+    let val: Option<MetadataValue> = lmdb_get(&txn, &db, &(k as u32))?;
+    match val {
+        Some(MetadataValue::SchemaVersion(version)) => Ok(Some(version)),
+        _ => Ok(None),
+    }
+}
+
+pub(super) fn set_schema_version(
+    txn: &WriteTransaction<'_>,
+    db: &Database,
+    version: u32,
+) -> Result<(), ChainStorageError> {
+    let k = MetadataKey::SchemaVersion;
+    lmdb_replace(txn, db, &(k as u32), &MetadataValue::SchemaVersion(version))
+}
+
+// Fetches the key that an interrupted schema migration resumes after from the provided metadata db.
+pub(super) fn fetch_migration_resume_key(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+) -> Result<Option<Vec<u8>>, ChainStorageError> {
+    let k = MetadataKey::MigrationResumeKey;
+    let val: Option<MetadataValue> = lmdb_get(&txn, &db, &(k as u32))?;
+    match val {
+        Some(MetadataValue::MigrationResumeKey(key)) => Ok(Some(key)),
+        _ => Ok(None),
+    }
+}
+
+/// Sets the key that the schema migration resumes after, or clears it if the migration is complete
+pub(super) fn set_migration_resume_key(
+    txn: &WriteTransaction<'_>,
+    db: &Database,
+    key: Option<&[u8]>,
+) -> Result<(), ChainStorageError> {
+    let k = MetadataKey::MigrationResumeKey as u32;
+    match key {
+        Some(key) => lmdb_replace(txn, db, &k, &MetadataValue::MigrationResumeKey(key.to_vec())),
+        None if lmdb_exists(txn, db, &k)? => lmdb_delete(txn, db, &k),
+        None => Ok(()),
+    }
+}
+
 fn fetch_pruning_horizon(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64, ChainStorageError> {
     let k = MetadataKey::PruningHorizon;
     let val: Option<MetadataValue> = lmdb_get(&txn, &db, &(k as u32))?;
@@ -1941,6 +2647,34 @@ fn fetch_pruning_horizon(txn: &ConstTransaction<'_>, db: &Database) -> Result<u6
     }
 }
 
+// Fetches the height below which all main chain blocks have been archived from the provided metadata db.
+fn fetch_archived_height(txn: &ConstTransaction<'_>, db: &Database) -> Result<u64, ChainStorageError> {
+    let k = MetadataKey::ArchivedHeight;
+    let val: Option<MetadataValue> = lmdb_get(&txn, &db, &(k as u32))?;
+    match val {
+        Some(MetadataValue::ArchivedHeight(height)) => Ok(height),
+        _ => Ok(0),
+    }
+}
+
+/// The key of an output in the utxos table
+fn output_key(header_hash: &[u8], mmr_position: u32) -> String {
+    format!("{}-{:010}", to_hex(header_hash), mmr_position)
+}
+
+/// The key of a kernel in the kernels table
+fn kernel_key(header_hash: &[u8], mmr_position: u32, hash: &[u8]) -> String {
+    format!("{}-{:010}-{}", to_hex(header_hash), mmr_position, to_hex(hash))
+}
+
+fn script_hash(output: &TransactionOutput) -> Result<HashOutput, ChainStorageError> {
+    output
+        .script
+        .as_hash::<HashDigest>()
+        .map(|hash| hash.to_vec())
+        .map_err(|e| ChainStorageError::InvalidOperation(format!("Could not hash output script: {}", e)))
+}
+
 fn get_database(store: &LMDBStore, name: &str) -> Result<DatabaseRef, ChainStorageError> {
     let handle = store
         .get_handle(name)
@@ -1949,13 +2683,17 @@ fn get_database(store: &LMDBStore, name: &str) -> Result<DatabaseRef, ChainStora
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
-enum MetadataKey {
+pub(super) enum MetadataKey {
     ChainHeight,
     BestBlock,
     AccumulatedWork,
     PruningHorizon,
     PrunedHeight,
     HorizonData,
+    OutputIndexEnabled,
+    SchemaVersion,
+    ArchivedHeight,
+    MigrationResumeKey,
 }
 
 impl fmt::Display for MetadataKey {
@@ -1967,19 +2705,27 @@ impl fmt::Display for MetadataKey {
             MetadataKey::PrunedHeight => f.write_str("Effective pruned height"),
             MetadataKey::BestBlock => f.write_str("Chain tip block hash"),
             MetadataKey::HorizonData => f.write_str("Database info"),
+            MetadataKey::OutputIndexEnabled => f.write_str("Output index enabled"),
+            MetadataKey::SchemaVersion => f.write_str("Database schema version"),
+            MetadataKey::ArchivedHeight => f.write_str("Archived height"),
+            MetadataKey::MigrationResumeKey => f.write_str("Migration resume key"),
         }
     }
 }
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
-enum MetadataValue {
+pub(super) enum MetadataValue {
     ChainHeight(u64),
     BestBlock(BlockHash),
     AccumulatedWork(u128),
     PruningHorizon(u64),
     PrunedHeight(u64),
     HorizonData(HorizonData),
+    OutputIndexEnabled(bool),
+    SchemaVersion(u32),
+    ArchivedHeight(u64),
+    MigrationResumeKey(Vec<u8>),
 }
 
 impl fmt::Display for MetadataValue {
@@ -1991,6 +2737,10 @@ impl fmt::Display for MetadataValue {
             MetadataValue::PrunedHeight(height) => write!(f, "Effective pruned height is {}", height),
             MetadataValue::BestBlock(hash) => write!(f, "Chain tip block hash is {}", hash.to_hex()),
             MetadataValue::HorizonData(_) => write!(f, "Horizon data"),
+            MetadataValue::OutputIndexEnabled(enabled) => write!(f, "Output index enabled is {}", enabled),
+            MetadataValue::SchemaVersion(version) => write!(f, "Database schema version is {}", version),
+            MetadataValue::ArchivedHeight(height) => write!(f, "Blocks below height {} are archived", height),
+            MetadataValue::MigrationResumeKey(key) => write!(f, "Migration resumes after key {}", key.to_hex()),
         }
     }
 }
@@ -0,0 +1,414 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Versioned migrations of the LMDB chain database schema. The schema version is stored in the metadata table and
+//! every migration with a higher version is applied, in order, when the database is opened.
+
+mod v1;
+mod v2;
+
+use crate::chain_storage::{
+    lmdb_db::{
+        lmdb::lmdb_len,
+        lmdb_db::{fetch_migration_resume_key, fetch_schema_version, set_migration_resume_key, set_schema_version},
+        LMDB_DB_METADATA,
+    },
+    ChainStorageError,
+};
+use lmdb_zero::WriteTransaction;
+use log::*;
+use std::{fmt, time::Instant};
+use tari_storage::lmdb_store::LMDBStore;
+
+const LOG_TARGET: &str = "c::cs::lmdb_db::migrations";
+
+/// Applies the pending schema migrations to the store. Each migration is applied in bounded batches, and every batch is
+/// committed along with the key to resume from, so that a large table is never held in memory at once and an
+/// interrupted migration carries on where it stopped. The schema version is only advanced once a migration is
+/// complete. If `dry_run` is true, every batch is run in a single transaction that is discarded, so the returned
+/// report describes the changes without making them.
+pub fn migrate(store: &LMDBStore, dry_run: bool) -> Result<MigrationReport, ChainStorageError> {
+    let migrations = vec![v1::MigrationV1.boxed(), v2::MigrationV2.boxed()];
+    let latest_version = migrations.len() as u32;
+
+    let metadata_db = store
+        .get_handle(LMDB_DB_METADATA)
+        .ok_or_else(|| ChainStorageError::CriticalError(format!("Could not get `{}` database", LMDB_DB_METADATA)))?
+        .db();
+    let env = store.env();
+    if !dry_run {
+        LMDBStore::resize_if_required(&env, &store.env_config())?;
+    }
+    let mut txn = WriteTransaction::new(&*env)?;
+
+    // If the database is empty there is nothing to migrate, so set it to the latest version
+    if lmdb_len(&txn, &metadata_db)? == 0 {
+        debug!(target: LOG_TARGET, "New database does not require migration");
+        set_schema_version(&txn, &metadata_db, latest_version)?;
+        if !dry_run {
+            txn.commit()?;
+        }
+        return Ok(MigrationReport::new(latest_version, latest_version, dry_run));
+    }
+
+    let version = fetch_schema_version(&txn, &metadata_db)?.unwrap_or(0);
+    if version > latest_version {
+        return Err(ChainStorageError::UnsupportedSchemaVersion {
+            found: version,
+            supported: latest_version,
+        });
+    }
+    let mut report = MigrationReport::new(version, latest_version, dry_run);
+    if version == latest_version {
+        debug!(
+            target: LOG_TARGET,
+            "Database at version {}. No migration required.", latest_version
+        );
+        return Ok(report);
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Migrating database from version {} to {}{}",
+        version,
+        latest_version,
+        if dry_run { " (dry run)" } else { "" }
+    );
+    let mut resume_key = fetch_migration_resume_key(&txn, &metadata_db)?;
+    for (version, migration) in (1..).zip(migrations.iter()).skip(version as usize) {
+        info!(
+            target: LOG_TARGET,
+            "Applying migration {}/{}: {}{}",
+            version,
+            latest_version,
+            migration.description(),
+            if resume_key.is_some() { " (resuming)" } else { "" }
+        );
+        let timer = Instant::now();
+        let mut entries_migrated = 0;
+        loop {
+            let (entries, next_key) = migration.migrate_batch(store, &txn, resume_key.as_deref())?;
+            entries_migrated += entries;
+            resume_key = next_key;
+            set_migration_resume_key(&txn, &metadata_db, resume_key.as_deref())?;
+            if resume_key.is_none() {
+                set_schema_version(&txn, &metadata_db, version)?;
+            }
+            if !dry_run {
+                txn.commit()?;
+                LMDBStore::resize_if_required(&env, &store.env_config())?;
+                txn = WriteTransaction::new(&*env)?;
+            }
+            if resume_key.is_none() {
+                break;
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Migration {}: {} entries migrated so far", version, entries_migrated
+            );
+        }
+        info!(
+            target: LOG_TARGET,
+            "Migration {} complete. {} entries migrated in {:.2?}",
+            version,
+            entries_migrated,
+            timer.elapsed()
+        );
+        report.steps.push(MigrationStep {
+            version,
+            description: migration.description(),
+            entries_migrated,
+        });
+    }
+
+    if dry_run {
+        info!(target: LOG_TARGET, "Dry run complete. Discarding database changes.");
+    }
+    Ok(report)
+}
+
+/// A summary of the migrations applied to a database
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    /// The schema version of the database before migrating
+    pub from_version: u32,
+    /// The schema version of the database after migrating
+    pub to_version: u32,
+    /// True if the migrations were not committed
+    pub dry_run: bool,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl MigrationReport {
+    fn new(from_version: u32, to_version: u32, dry_run: bool) -> Self {
+        Self {
+            from_version,
+            to_version,
+            dry_run,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns true if the database was already at the latest schema version
+    pub fn is_up_to_date(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_up_to_date() {
+            return write!(f, "Database schema is at the latest version ({})", self.to_version);
+        }
+        writeln!(
+            f,
+            "Database schema {} from version {} to {}:",
+            if self.dry_run { "would be migrated" } else { "migrated" },
+            self.from_version,
+            self.to_version
+        )?;
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single migration applied to a database
+#[derive(Debug, Clone)]
+pub struct MigrationStep {
+    pub version: u32,
+    pub description: &'static str,
+    pub entries_migrated: u64,
+}
+
+impl fmt::Display for MigrationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "v{}: {} ({} entries)",
+            self.version, self.description, self.entries_migrated
+        )
+    }
+}
+
+trait Migration {
+    /// A short description of the schema change
+    fn description(&self) -> &'static str;
+
+    /// Migrates a bounded batch of entries from the previous schema version, starting after `resume_key`, or at the
+    /// start if it is `None`. Returns the number of entries that were changed and the key to resume the next batch
+    /// after, or `None` once the migration is complete.
+    fn migrate_batch(
+        &self,
+        store: &LMDBStore,
+        txn: &WriteTransaction<'_>,
+        resume_key: Option<&[u8]>,
+    ) -> Result<(u64, Option<Vec<u8>>), ChainStorageError>;
+}
+
+trait MigrationExt: Migration {
+    fn boxed(self) -> Box<dyn Migration>
+    where Self: Sized + 'static {
+        Box::new(self)
+    }
+}
+
+impl<T> MigrationExt for T where T: Migration {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        chain_storage::lmdb_db::{
+            lmdb::{lmdb_delete, lmdb_get, lmdb_replace},
+            lmdb_db::{open_lmdb_store, MetadataKey, MetadataValue},
+            TransactionKernelRowData,
+            LMDB_DB_KERNELS,
+        },
+        transactions::helpers::create_test_kernel,
+    };
+    use lmdb_zero::{Database, ReadTransaction};
+    use std::sync::Arc;
+    use tari_crypto::tari_utilities::Hashable;
+    use tari_storage::lmdb_store::LMDBConfig;
+    use tempfile::tempdir;
+
+    fn metadata_db(store: &LMDBStore) -> Arc<Database<'static>> {
+        store.get_handle(LMDB_DB_METADATA).unwrap().db()
+    }
+
+    fn schema_version(store: &LMDBStore) -> Option<u32> {
+        let env = store.env();
+        let txn = ReadTransaction::new(&*env).unwrap();
+        fetch_schema_version(&txn, &metadata_db(store)).unwrap()
+    }
+
+    /// Writes the metadata of a database created before the schema was versioned
+    fn seed_unversioned_database(store: &LMDBStore) {
+        let env = store.env();
+        let txn = WriteTransaction::new(&*env).unwrap();
+        let db = metadata_db(store);
+        lmdb_replace(
+            &txn,
+            &db,
+            &(MetadataKey::ChainHeight as u32),
+            &MetadataValue::ChainHeight(10),
+        )
+        .unwrap();
+        lmdb_delete(&txn, &db, &(MetadataKey::SchemaVersion as u32)).unwrap();
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn it_migrates_an_unversioned_database() {
+        let temp_dir = tempdir().unwrap();
+        let (store, _file_lock) = open_lmdb_store(temp_dir.path(), LMDBConfig::default()).unwrap();
+        migrate(&store, false).unwrap();
+        seed_unversioned_database(&store);
+        assert_eq!(schema_version(&store), None);
+
+        let report = migrate(&store, true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, 2);
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[0].version, 1);
+        assert_eq!(report.steps[1].version, 2);
+        // A dry run does not change the database
+        assert_eq!(schema_version(&store), None);
+
+        let report = migrate(&store, false).unwrap();
+        assert!(!report.is_up_to_date());
+        assert_eq!(report.from_version, 0);
+        assert_eq!(schema_version(&store), Some(2));
+        // Existing data is kept
+        let env = store.env();
+        let txn = ReadTransaction::new(&*env).unwrap();
+        let height: Option<MetadataValue> =
+            lmdb_get(&txn, &metadata_db(&store), &(MetadataKey::ChainHeight as u32)).unwrap();
+        assert!(matches!(height, Some(MetadataValue::ChainHeight(10))));
+        drop(txn);
+
+        let report = migrate(&store, false).unwrap();
+        assert!(report.is_up_to_date());
+    }
+
+    #[test]
+    fn it_rejects_a_database_from_a_newer_version() {
+        let temp_dir = tempdir().unwrap();
+        let (store, _file_lock) = open_lmdb_store(temp_dir.path(), LMDBConfig::default()).unwrap();
+        migrate(&store, false).unwrap();
+        {
+            let env = store.env();
+            let txn = WriteTransaction::new(&*env).unwrap();
+            set_schema_version(&txn, &metadata_db(&store), 3).unwrap();
+            txn.commit().unwrap();
+        }
+
+        let err = migrate(&store, false).unwrap_err();
+        assert!(matches!(err, ChainStorageError::UnsupportedSchemaVersion {
+            found: 3,
+            supported: 2
+        }));
+        assert_eq!(schema_version(&store), Some(3));
+    }
+
+    #[test]
+    fn it_migrates_v1_kernel_rows() {
+        let temp_dir = tempdir().unwrap();
+        let (store, _file_lock) = open_lmdb_store(temp_dir.path(), LMDBConfig::default()).unwrap();
+        migrate(&store, false).unwrap();
+        let kernels_db = store.get_handle(LMDB_DB_KERNELS).unwrap().db();
+        let kernel = create_test_kernel(100.into(), 0);
+        let key = "00-0000000000-00";
+        {
+            let env = store.env();
+            let txn = WriteTransaction::new(&*env).unwrap();
+            lmdb_replace(&txn, &kernels_db, key, &v2::TransactionKernelRowDataV1 {
+                kernel: kernel.clone(),
+                header_hash: vec![0; 32],
+                mmr_position: 0,
+                hash: kernel.hash(),
+            })
+            .unwrap();
+            set_schema_version(&txn, &metadata_db(&store), 1).unwrap();
+            txn.commit().unwrap();
+        }
+
+        let report = migrate(&store, false).unwrap();
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(report.steps[0].entries_migrated, 1);
+        let env = store.env();
+        let txn = ReadTransaction::new(&*env).unwrap();
+        let row: TransactionKernelRowData = lmdb_get(&txn, &kernels_db, key).unwrap().unwrap();
+        assert_eq!(row.kernel, Some(kernel));
+        assert_eq!(row.mmr_position, 0);
+    }
+
+    #[test]
+    fn it_resumes_an_interrupted_migration() {
+        let temp_dir = tempdir().unwrap();
+        let (store, _file_lock) = open_lmdb_store(temp_dir.path(), LMDBConfig::default()).unwrap();
+        migrate(&store, false).unwrap();
+        let kernels_db = store.get_handle(LMDB_DB_KERNELS).unwrap().db();
+        let kernel = create_test_kernel(100.into(), 0);
+        let migrated_key = "00-0000000000-00";
+        let pending_key = "01-0000000000-00";
+        {
+            // The first row was rewritten by a batch that was committed before the migration was interrupted
+            let env = store.env();
+            let txn = WriteTransaction::new(&*env).unwrap();
+            lmdb_replace(&txn, &kernels_db, migrated_key, &TransactionKernelRowData {
+                kernel: Some(kernel.clone()),
+                header_hash: vec![0; 32],
+                mmr_position: 0,
+                hash: kernel.hash(),
+            })
+            .unwrap();
+            lmdb_replace(&txn, &kernels_db, pending_key, &v2::TransactionKernelRowDataV1 {
+                kernel: kernel.clone(),
+                header_hash: vec![1; 32],
+                mmr_position: 1,
+                hash: kernel.hash(),
+            })
+            .unwrap();
+            set_schema_version(&txn, &metadata_db(&store), 1).unwrap();
+            set_migration_resume_key(&txn, &metadata_db(&store), Some(migrated_key.as_bytes())).unwrap();
+            txn.commit().unwrap();
+        }
+
+        let report = migrate(&store, false).unwrap();
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(report.steps[0].entries_migrated, 1);
+        assert_eq!(schema_version(&store), Some(2));
+        let env = store.env();
+        let txn = ReadTransaction::new(&*env).unwrap();
+        assert_eq!(fetch_migration_resume_key(&txn, &metadata_db(&store)).unwrap(), None);
+        for (key, mmr_position) in &[(migrated_key, 0), (pending_key, 1)] {
+            let row: TransactionKernelRowData = lmdb_get(&txn, &kernels_db, *key).unwrap().unwrap();
+            assert_eq!(row.kernel, Some(kernel.clone()));
+            assert_eq!(row.mmr_position, *mmr_position);
+        }
+    }
+}
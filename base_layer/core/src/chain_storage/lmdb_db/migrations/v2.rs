@@ -0,0 +1,95 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    chain_storage::{
+        lmdb_db::{
+            lmdb::{lmdb_fetch_key_values_after, lmdb_replace},
+            migrations::Migration,
+            TransactionKernelRowData,
+            LMDB_DB_KERNELS,
+        },
+        ChainStorageError,
+    },
+    transactions::{transaction::TransactionKernel, types::HashOutput},
+};
+use lmdb_zero::WriteTransaction;
+use serde::{Deserialize, Serialize};
+use tari_storage::lmdb_store::LMDBStore;
+
+/// The maximum number of kernel rows rewritten in a single migration transaction
+const MAX_KERNELS_MIGRATED_PER_TRANSACTION: usize = 10_000;
+
+/// Kernel rows hold an optional kernel, so that the kernels of archived blocks can be moved to the block archive.
+/// Every kernel row is rewritten in the new format, in key order.
+pub struct MigrationV2;
+
+/// The kernel row format of schema version 1
+#[derive(Serialize, Deserialize)]
+pub(super) struct TransactionKernelRowDataV1 {
+    pub kernel: TransactionKernel,
+    pub header_hash: HashOutput,
+    pub mmr_position: u32,
+    pub hash: HashOutput,
+}
+
+impl Migration for MigrationV2 {
+    fn description(&self) -> &'static str {
+        "Store the kernel of kernel rows as optional for the block archive"
+    }
+
+    fn migrate_batch(
+        &self,
+        store: &LMDBStore,
+        txn: &WriteTransaction<'_>,
+        resume_key: Option<&[u8]>,
+    ) -> Result<(u64, Option<Vec<u8>>), ChainStorageError> {
+        let kernels_db = store
+            .get_handle(LMDB_DB_KERNELS)
+            .ok_or_else(|| ChainStorageError::CriticalError(format!("Could not get `{}` database", LMDB_DB_KERNELS)))?
+            .db();
+        let rows = lmdb_fetch_key_values_after::<TransactionKernelRowDataV1>(
+            txn,
+            &kernels_db,
+            resume_key,
+            MAX_KERNELS_MIGRATED_PER_TRANSACTION,
+        )?;
+        let num_rows = rows.len();
+        let mut last_key = None;
+        for (key, row) in rows {
+            lmdb_replace(txn, &kernels_db, key.as_slice(), &TransactionKernelRowData {
+                kernel: Some(row.kernel),
+                header_hash: row.header_hash,
+                mmr_position: row.mmr_position,
+                hash: row.hash,
+            })?;
+            last_key = Some(key);
+        }
+        // A short batch reached the end of the table
+        let resume_key = if num_rows < MAX_KERNELS_MIGRATED_PER_TRANSACTION {
+            None
+        } else {
+            last_key
+        };
+        Ok((num_rows as u64, resume_key))
+    }
+}
@@ -20,15 +20,19 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod archive;
+mod integrity;
 mod lmdb;
 #[allow(clippy::module_inception)]
 mod lmdb_db;
+mod migrations;
 
 use crate::transactions::{
     transaction::{TransactionInput, TransactionKernel, TransactionOutput},
     types::HashOutput,
 };
-pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
+pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, migrate_lmdb_database, LMDBDatabase};
+pub use migrations::{MigrationReport, MigrationStep};
 use serde::{Deserialize, Serialize};
 
 pub const LMDB_DB_METADATA: &str = "metadata";
@@ -39,6 +43,9 @@ pub const LMDB_DB_BLOCK_HASHES: &str = "block_hashes";
 pub const LMDB_DB_UTXOS: &str = "utxos";
 pub const LMDB_DB_INPUTS: &str = "inputs";
 pub const LMDB_DB_TXOS_HASH_TO_INDEX: &str = "txos_hash_to_index";
+pub const LMDB_DB_TXOS_COMMITMENT_INDEX: &str = "txos_commitment_index";
+pub const LMDB_DB_TXOS_SCRIPT_HASH_INDEX: &str = "txos_script_hash_index";
+pub const LMDB_DB_TXOS_SPENT_INDEX: &str = "txos_spent_index";
 pub const LMDB_DB_KERNELS: &str = "kernels";
 pub const LMDB_DB_KERNEL_EXCESS_INDEX: &str = "kernel_excess_index";
 pub const LMDB_DB_KERNEL_EXCESS_SIG_INDEX: &str = "kernel_excess_sig_index";
@@ -49,6 +56,8 @@ pub const LMDB_DB_MONERO_SEED_HEIGHT: &str = "monero_seed_height";
 pub const LMDB_DB_ORPHAN_HEADER_ACCUMULATED_DATA: &str = "orphan_accumulated_data";
 pub const LMDB_DB_ORPHAN_CHAIN_TIPS: &str = "orphan_chain_tips";
 pub const LMDB_DB_ORPHAN_PARENT_MAP_INDEX: &str = "orphan_parent_map_index";
+pub const LMDB_DB_REORGS: &str = "reorgs";
+pub const LMDB_DB_ARCHIVE_INDEX: &str = "archive_index";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct TransactionOutputRowData {
@@ -70,7 +79,8 @@ pub(crate) struct TransactionInputRowData {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(crate) struct TransactionKernelRowData {
-    pub kernel: TransactionKernel,
+    /// None once the kernel has been moved to the block archive
+    pub kernel: Option<TransactionKernel>,
     pub header_hash: HashOutput,
     pub mmr_position: u32,
     pub hash: HashOutput,
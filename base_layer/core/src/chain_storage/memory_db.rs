@@ -0,0 +1,1640 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    chain_storage::{
+        accumulated_data::{BlockAccumulatedData, BlockHeaderAccumulatedData, DeletedBitmap},
+        db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation},
+        error::ChainStorageError,
+        BlockchainBackend,
+        ChainBlock,
+        ChainHeader,
+        HorizonData,
+        IntegrityReport,
+        MmrTree,
+        PrunedOutput,
+        Reorg,
+        MAX_REORG_JOURNAL_ENTRIES,
+    },
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction::{TransactionInput, TransactionKernel, TransactionOutput},
+        types::{Commitment, HashDigest, HashOutput, Signature},
+    },
+};
+use croaring::Bitmap;
+use log::*;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    convert::TryFrom,
+    hash::Hash as StdHash,
+    sync::Arc,
+    time::Instant,
+};
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    types::{BlockHash, BLOCK_HASH_LENGTH},
+};
+use tari_crypto::tari_utilities::{hash::Hashable, hex::Hex, ByteArray};
+use tari_mmr::{pruned_hashset::PrunedHashSet, Hash, MerkleMountainRange, MutableMmr};
+
+const LOG_TARGET: &str = "c::cs::memory_db";
+
+/// Key used for all tables that are indexed by block, i.e. (header hash, MMR position)
+type BlockIndexKey = (HashOutput, u32);
+
+/// A pure in-memory blockchain backend. The tables mirror those of [LMDBDatabase](crate::chain_storage::LMDBDatabase)
+/// so that both backends behave identically from the point of view of `BlockchainDatabase`.
+///
+/// Writes are applied in place. Every change made while applying a transaction is recorded in an undo log, which is
+/// replayed in reverse if any operation fails, so a transaction is either applied in full or not at all as required by
+/// [BlockchainBackend].
+#[derive(Default)]
+pub struct MemoryDatabase {
+    db: InnerDatabase,
+}
+
+impl MemoryDatabase {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct OutputRow {
+    output: Option<TransactionOutput>,
+    mmr_position: u32,
+    hash: HashOutput,
+    range_proof_hash: HashOutput,
+    mined_height: u64,
+}
+
+#[derive(Debug, Clone)]
+struct InputRow {
+    input: TransactionInput,
+}
+
+#[derive(Debug, Clone)]
+struct KernelRow {
+    kernel: TransactionKernel,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Metadata {
+    chain_height: Option<u64>,
+    best_block: Option<BlockHash>,
+    accumulated_work: Option<u128>,
+    pruning_horizon: u64,
+    pruned_height: u64,
+    horizon_data: Option<HorizonData>,
+}
+
+/// Restores a single table entry (or the chain metadata) to the value it had before it was changed
+type UndoEntry = Box<dyn FnOnce(&mut InnerDatabase) + Send + Sync>;
+
+#[derive(Default)]
+struct InnerDatabase {
+    metadata: Metadata,
+    headers: BTreeMap<u64, BlockHeader>,
+    header_accumulated_data: BTreeMap<u64, BlockHeaderAccumulatedData>,
+    block_accumulated_data: BTreeMap<u64, BlockAccumulatedData>,
+    block_hashes: HashMap<HashOutput, u64>,
+    utxos: BTreeMap<BlockIndexKey, OutputRow>,
+    inputs: BTreeMap<BlockIndexKey, InputRow>,
+    txos_hash_to_index: HashMap<HashOutput, BlockIndexKey>,
+    kernels: BTreeMap<BlockIndexKey, KernelRow>,
+    kernel_excess_index: HashMap<Vec<u8>, BlockIndexKey>,
+    kernel_excess_sig_index: HashMap<Vec<u8>, BlockIndexKey>,
+    kernel_mmr_size_index: BTreeMap<u64, u64>,
+    output_mmr_size_index: BTreeMap<u64, (u64, HashOutput)>,
+    orphans: HashMap<HashOutput, Arc<Block>>,
+    orphan_header_accumulated_data: HashMap<HashOutput, BlockHeaderAccumulatedData>,
+    orphan_chain_tips: HashSet<HashOutput>,
+    orphan_parent_map_index: HashMap<HashOutput, BTreeSet<HashOutput>>,
+    monero_seed_height: HashMap<Vec<u8>, u64>,
+    reorgs: BTreeMap<u64, Reorg>,
+    /// The changes made by the transaction currently being applied, oldest first
+    undo_log: Vec<UndoEntry>,
+}
+
+/// The key-value collections that make up the tables of the [MemoryDatabase]. Changes to every table go through this
+/// trait so that they can be recorded in the undo log.
+trait Table {
+    type Key;
+    type Value;
+
+    /// Inserts the value, returning the value previously stored under the key
+    fn put(&mut self, key: Self::Key, value: Self::Value) -> Option<Self::Value>;
+    /// Removes and returns the value stored under the key
+    fn take(&mut self, key: &Self::Key) -> Option<Self::Value>;
+}
+
+impl<K: Ord, V> Table for BTreeMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn take(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+}
+
+impl<K: Eq + StdHash, V> Table for HashMap<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn take(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+}
+
+impl<K: Eq + StdHash> Table for HashSet<K> {
+    type Key = K;
+    type Value = ();
+
+    fn put(&mut self, key: K, _: ()) -> Option<()> {
+        if self.insert(key) {
+            None
+        } else {
+            Some(())
+        }
+    }
+
+    fn take(&mut self, key: &K) -> Option<()> {
+        if self.remove(key) {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+/// Selects one of the tables of the [InnerDatabase]
+type TableSelector<T> = fn(&mut InnerDatabase) -> &mut T;
+
+impl InnerDatabase {
+    /// Inserts a value into a table and records how to restore the entry it replaced
+    fn put<T>(&mut self, table: TableSelector<T>, key: T::Key, value: T::Value)
+    where
+        T: Table + 'static,
+        T::Key: Clone + Send + Sync + 'static,
+        T::Value: Send + Sync + 'static,
+    {
+        let previous = table(self).put(key.clone(), value);
+        self.undo_log.push(Box::new(move |db| {
+            let table = table(db);
+            match previous {
+                Some(value) => {
+                    table.put(key, value);
+                },
+                None => {
+                    table.take(&key);
+                },
+            }
+        }));
+    }
+
+    /// Removes a value from a table and records how to restore it
+    fn take<T>(&mut self, table: TableSelector<T>, key: &T::Key) -> Option<T::Value>
+    where
+        T: Table + 'static,
+        T::Key: Clone + Send + Sync + 'static,
+        T::Value: Clone + Send + Sync + 'static,
+    {
+        let previous = table(self).take(key)?;
+        let key = key.clone();
+        let restored = previous.clone();
+        self.undo_log.push(Box::new(move |db| {
+            table(db).put(key, restored);
+        }));
+        Some(previous)
+    }
+
+    /// Updates the chain metadata and records how to restore it
+    fn update_metadata<F: FnOnce(&mut Metadata)>(&mut self, update: F) {
+        let previous = self.metadata.clone();
+        self.undo_log.push(Box::new(move |db| db.metadata = previous));
+        update(&mut self.metadata);
+    }
+
+    /// Appends a reorg to the reorg journal and prunes the oldest reorgs that no longer fit in it
+    fn push_reorg(&mut self, reorg: Reorg) {
+        let key = self.reorgs.keys().next_back().map(|k| k + 1).unwrap_or(0);
+        self.put(|db| &mut db.reorgs, key, reorg);
+        let num_pruned = (self.reorgs.len() as u64).saturating_sub(MAX_REORG_JOURNAL_ENTRIES) as usize;
+        let pruned = self.reorgs.keys().take(num_pruned).copied().collect::<Vec<_>>();
+        for key in pruned {
+            self.take(|db| &mut db.reorgs, &key);
+        }
+    }
+
+    /// Reverts every change recorded in the undo log, newest first
+    fn rollback(&mut self) {
+        let undo_log = std::mem::take(&mut self.undo_log);
+        for undo in undo_log.into_iter().rev() {
+            undo(self);
+        }
+    }
+
+    fn apply_db_transaction(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
+        use WriteOperation::*;
+        for op in txn.into_operations() {
+            trace!(target: LOG_TARGET, "[apply_db_transaction] WriteOperation: {}", op);
+            match op {
+                InsertOrphanBlock(block) => self.insert_orphan_block(block)?,
+                InsertChainHeader { header } => {
+                    self.insert_header(header.header(), header.accumulated_data())?;
+                },
+                InsertBlockBody { block } => {
+                    self.insert_block_body(block.header(), block.block().body.clone())?;
+                },
+                InsertKernel {
+                    header_hash,
+                    kernel,
+                    mmr_position,
+                } => {
+                    self.insert_kernel(header_hash, *kernel, mmr_position)?;
+                },
+                InsertOutput {
+                    header_hash,
+                    header_height,
+                    output,
+                    mmr_position,
+                } => {
+                    self.insert_output(header_hash, header_height, *output, mmr_position)?;
+                },
+                InsertPrunedOutput {
+                    header_hash,
+                    header_height,
+                    output_hash,
+                    proof_hash,
+                    mmr_position,
+                } => {
+                    self.insert_pruned_output(header_hash, header_height, output_hash, proof_hash, mmr_position)?;
+                },
+                InsertInput {
+                    header_hash,
+                    input,
+                    mmr_position,
+                } => {
+                    self.insert_input(header_hash, *input, mmr_position)?;
+                },
+                DeleteHeader(height) => {
+                    self.delete_header(height)?;
+                },
+                DeleteOrphan(hash) => {
+                    self.delete_orphan(&hash)?;
+                },
+                DeleteOrphanChainTip(hash) => {
+                    if self.take(|db| &mut db.orphan_chain_tips, &hash).is_none() {
+                        return Err(ChainStorageError::ValueNotFound {
+                            entity: "OrphanChainTip".to_string(),
+                            field: "hash".to_string(),
+                            value: hash.to_hex(),
+                        });
+                    }
+                },
+                InsertOrphanChainTip(hash) => {
+                    if self.orphan_chain_tips.contains(&hash) {
+                        return Err(insert_error("orphan_chain_tips"));
+                    }
+                    self.put(|db| &mut db.orphan_chain_tips, hash, ());
+                },
+                DeleteBlock(hash) => {
+                    self.delete_block_body(&hash)?;
+                },
+                InsertMoneroSeedHeight(data, height) => {
+                    let current_height = self.monero_seed_height.get(&data).copied().unwrap_or(std::u64::MAX);
+                    if height < current_height {
+                        self.put(|db| &mut db.monero_seed_height, data, height);
+                    }
+                },
+                InsertReorg(reorg) => {
+                    self.push_reorg(*reorg);
+                },
+                SetAccumulatedDataForOrphan(chain_header) => {
+                    self.set_accumulated_data_for_orphan(chain_header.hash(), chain_header.accumulated_data())?;
+                },
+                InsertChainOrphanBlock(chain_block) => {
+                    self.insert_orphan_block(chain_block.to_arc_block())?;
+                    self.set_accumulated_data_for_orphan(chain_block.hash(), chain_block.accumulated_data())?;
+                },
+                UpdatePrunedHashSet {
+                    mmr_tree,
+                    header_hash,
+                    pruned_hash_set,
+                } => {
+                    self.update_pruned_hash_set(mmr_tree, &header_hash, *pruned_hash_set)?;
+                },
+                UpdateDeletedBlockAccumulatedDataWithDiff { header_hash, deleted } => {
+                    self.update_deleted_block_accumulated_data_with_diff(&header_hash, deleted)?;
+                },
+                PruneOutputsAndUpdateHorizon {
+                    output_positions,
+                    horizon,
+                } => {
+                    self.prune_outputs_and_update_horizon(output_positions, horizon)?;
+                },
+                UpdateKernelSum {
+                    header_hash,
+                    kernel_sum,
+                } => {
+                    let height = self.fetch_height_from_hash(&header_hash)?;
+                    let mut data = self.block_accumulated_data.get(&height).cloned().unwrap_or_default();
+                    data.kernel_sum = kernel_sum;
+                    self.put(|db| &mut db.block_accumulated_data, height, data);
+                },
+                SetBestBlock {
+                    height,
+                    hash,
+                    accumulated_difficulty,
+                } => {
+                    self.update_metadata(|metadata| {
+                        metadata.chain_height = Some(height);
+                        metadata.best_block = Some(hash);
+                        metadata.accumulated_work = Some(accumulated_difficulty);
+                    });
+                },
+                SetPruningHorizonConfig(pruning_horizon) => {
+                    self.update_metadata(|metadata| metadata.pruning_horizon = pruning_horizon);
+                },
+                SetPrunedHeight {
+                    height,
+                    kernel_sum,
+                    utxo_sum,
+                } => {
+                    self.update_metadata(|metadata| {
+                        metadata.pruned_height = height;
+                        metadata.horizon_data = Some(HorizonData::new(kernel_sum, utxo_sum));
+                    });
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn insert_output(
+        &mut self,
+        header_hash: HashOutput,
+        header_height: u64,
+        output: TransactionOutput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError> {
+        let output_hash = output.hash();
+        let range_proof_hash = output.proof.hash();
+        self.insert_output_row(header_hash, OutputRow {
+            output: Some(output),
+            mmr_position,
+            hash: output_hash,
+            range_proof_hash,
+            mined_height: header_height,
+        })
+    }
+
+    fn insert_pruned_output(
+        &mut self,
+        header_hash: HashOutput,
+        header_height: u64,
+        output_hash: HashOutput,
+        proof_hash: HashOutput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError> {
+        if !self.block_hashes.contains_key(&header_hash) {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Unable to insert pruned output because header {} does not exist",
+                header_hash.to_hex()
+            )));
+        }
+        self.insert_output_row(header_hash, OutputRow {
+            output: None,
+            mmr_position,
+            hash: output_hash,
+            range_proof_hash: proof_hash,
+            mined_height: header_height,
+        })
+    }
+
+    fn insert_output_row(&mut self, header_hash: HashOutput, row: OutputRow) -> Result<(), ChainStorageError> {
+        let key = (header_hash, row.mmr_position);
+        if self.txos_hash_to_index.contains_key(&row.hash) {
+            return Err(insert_error("txos_hash_to_index"));
+        }
+        if self.utxos.contains_key(&key) {
+            return Err(insert_error("utxos"));
+        }
+        self.put(|db| &mut db.txos_hash_to_index, row.hash.clone(), key.clone());
+        self.put(|db| &mut db.utxos, key, row);
+        Ok(())
+    }
+
+    fn insert_kernel(
+        &mut self,
+        header_hash: HashOutput,
+        kernel: TransactionKernel,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError> {
+        let key = (header_hash, mmr_position);
+        let excess_key = kernel.excess.as_bytes().to_vec();
+        let excess_sig_key = excess_sig_key(&kernel.excess_sig);
+        if self.kernel_excess_index.contains_key(&excess_key) {
+            return Err(insert_error("kernel_excess_index"));
+        }
+        if self.kernel_excess_sig_index.contains_key(&excess_sig_key) {
+            return Err(insert_error("kernel_excess_sig_index"));
+        }
+        if self.kernels.contains_key(&key) {
+            return Err(insert_error("kernels"));
+        }
+        self.put(|db| &mut db.kernel_excess_index, excess_key, key.clone());
+        self.put(|db| &mut db.kernel_excess_sig_index, excess_sig_key, key.clone());
+        self.put(|db| &mut db.kernels, key, KernelRow { kernel });
+        Ok(())
+    }
+
+    fn insert_input(
+        &mut self,
+        header_hash: HashOutput,
+        input: TransactionInput,
+        mmr_position: u32,
+    ) -> Result<(), ChainStorageError> {
+        let key = (header_hash, mmr_position);
+        if self.inputs.contains_key(&key) {
+            return Err(insert_error("inputs"));
+        }
+        self.put(|db| &mut db.inputs, key, InputRow { input });
+        Ok(())
+    }
+
+    fn insert_orphan_block(&mut self, block: Arc<Block>) -> Result<(), ChainStorageError> {
+        let hash = block.hash();
+        if self.orphans.contains_key(&hash) {
+            return Err(insert_error("orphans"));
+        }
+        let parent_hash = block.header.prev_hash.clone();
+        let mut children = self
+            .orphan_parent_map_index
+            .get(&parent_hash)
+            .cloned()
+            .unwrap_or_default();
+        children.insert(hash.clone());
+        self.put(|db| &mut db.orphan_parent_map_index, parent_hash, children);
+        self.put(|db| &mut db.orphans, hash, block);
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn set_accumulated_data_for_orphan(
+        &mut self,
+        header_hash: &HashOutput,
+        accumulated_data: &BlockHeaderAccumulatedData,
+    ) -> Result<(), ChainStorageError> {
+        if !self.orphans.contains_key(header_hash) {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "set_accumulated_data_for_orphan: orphan {} does not exist",
+                header_hash.to_hex()
+            )));
+        }
+        if self.orphan_header_accumulated_data.contains_key(header_hash) {
+            return Err(insert_error("orphan_header_accumulated_data"));
+        }
+        self.put(
+            |db| &mut db.orphan_header_accumulated_data,
+            header_hash.clone(),
+            accumulated_data.clone(),
+        );
+        Ok(())
+    }
+
+    /// Inserts the header and header accumulated data.
+    fn insert_header(
+        &mut self,
+        header: &BlockHeader,
+        accum_data: &BlockHeaderAccumulatedData,
+    ) -> Result<(), ChainStorageError> {
+        if let Some(current_header_at_height) = self.headers.get(&header.height) {
+            let hash = current_header_at_height.hash();
+            if hash != accum_data.hash {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "There is a different header stored at height {} already. New header ({}), current header: ({})",
+                    header.height,
+                    accum_data.hash.to_hex(),
+                    hash.to_hex(),
+                )));
+            }
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "The header at height {} already exists. Existing header hash: {}",
+                header.height,
+                hash.to_hex()
+            )));
+        }
+
+        if let Some(last_header) = self.fetch_last_header() {
+            if last_header.height != header.height.saturating_sub(1) {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "Attempted to insert a header out of order. Was expecting chain height to be {} but current last \
+                     header height is {}",
+                    header.height - 1,
+                    last_header.height
+                )));
+            }
+
+            let hash = last_header.hash();
+            if hash != header.prev_hash {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "Attempted to insert a block header at height {} that didn't form a chain. Previous block \
+                     hash:{}, new block's previous hash:{}",
+                    header.height,
+                    hash.to_hex(),
+                    header.prev_hash.to_hex()
+                )));
+            }
+        } else if header.height != 0 {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "The first header inserted must have height 0. Height provided: {}",
+                header.height
+            )));
+        }
+
+        if self.kernel_mmr_size_index.contains_key(&header.kernel_mmr_size) {
+            return Err(insert_error("kernel_mmr_size_index"));
+        }
+        if self.output_mmr_size_index.contains_key(&header.output_mmr_size) {
+            return Err(insert_error("output_mmr_size_index"));
+        }
+        let hash = header.hash();
+        self.put(|db| &mut db.header_accumulated_data, header.height, accum_data.clone());
+        self.put(|db| &mut db.block_hashes, hash.clone(), header.height);
+        self.put(|db| &mut db.headers, header.height, header.clone());
+        self.put(
+            |db| &mut db.kernel_mmr_size_index,
+            header.kernel_mmr_size,
+            header.height,
+        );
+        self.put(
+            |db| &mut db.output_mmr_size_index,
+            header.output_mmr_size,
+            (header.height, hash),
+        );
+        Ok(())
+    }
+
+    fn delete_header(&mut self, height: u64) -> Result<(), ChainStorageError> {
+        if self.block_accumulated_data.contains_key(&height) {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Attempted to delete header at height {} while block accumulated data still exists",
+                height
+            )));
+        }
+
+        let header = self
+            .fetch_last_header()
+            .cloned()
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHeader".to_string(),
+                field: "height".to_string(),
+                value: "last_header".to_string(),
+            })?;
+        if header.height != height {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Attempted to delete a header at height {} that was not the last header (which is at height {}). \
+                 Headers must be deleted in reverse order.",
+                height, header.height
+            )));
+        }
+
+        let hash = header.hash();
+        if self.kernels.range(block_range(&hash)).next().is_some() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Cannot delete header {} ({}) because there are kernels linked to it",
+                header.height,
+                hash.to_hex()
+            )));
+        }
+        if self.utxos.range(block_range(&hash)).next().is_some() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Cannot delete header at height {} ({}) because there are UTXOs linked to it",
+                height,
+                hash.to_hex()
+            )));
+        }
+
+        self.take(|db| &mut db.block_hashes, &hash);
+        self.take(|db| &mut db.headers, &height);
+        self.take(|db| &mut db.header_accumulated_data, &height);
+        self.take(|db| &mut db.kernel_mmr_size_index, &header.kernel_mmr_size);
+        self.take(|db| &mut db.output_mmr_size_index, &header.output_mmr_size);
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn delete_block_body(&mut self, hash: &HashOutput) -> Result<(), ChainStorageError> {
+        debug!(target: LOG_TARGET, "Deleting block `{}`", hash.to_hex());
+        let height = self.fetch_height_from_hash(hash)?;
+        self.take(|db| &mut db.block_accumulated_data, &height);
+
+        debug!(target: LOG_TARGET, "Deleting UTXOs...");
+        let keys = self
+            .utxos
+            .range(block_range(hash))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            if let Some(row) = self.take(|db| &mut db.utxos, &key) {
+                trace!(target: LOG_TARGET, "Deleting UTXO `{}`", row.hash.to_hex());
+                self.take(|db| &mut db.txos_hash_to_index, &row.hash);
+            }
+        }
+
+        debug!(target: LOG_TARGET, "Deleting kernels...");
+        let keys = self
+            .kernels
+            .range(block_range(hash))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            if let Some(row) = self.take(|db| &mut db.kernels, &key) {
+                trace!(
+                    target: LOG_TARGET,
+                    "Deleting excess `{}`",
+                    row.kernel.excess.as_bytes().to_hex()
+                );
+                self.take(|db| &mut db.kernel_excess_index, &row.kernel.excess.as_bytes().to_vec());
+                self.take(
+                    |db| &mut db.kernel_excess_sig_index,
+                    &excess_sig_key(&row.kernel.excess_sig),
+                );
+            }
+        }
+
+        debug!(target: LOG_TARGET, "Deleting Inputs...");
+        let keys = self
+            .inputs
+            .range(block_range(hash))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.take(|db| &mut db.inputs, &key);
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn delete_orphan(&mut self, hash: &HashOutput) -> Result<(), ChainStorageError> {
+        if let Some(orphan) = self.take(|db| &mut db.orphans, hash) {
+            let parent_hash = &orphan.header.prev_hash;
+            if let Some(mut children) = self.orphan_parent_map_index.get(parent_hash).cloned() {
+                children.remove(hash);
+                if children.is_empty() {
+                    self.take(|db| &mut db.orphan_parent_map_index, parent_hash);
+                } else {
+                    self.put(|db| &mut db.orphan_parent_map_index, parent_hash.clone(), children);
+                }
+            }
+
+            // Orphan is a tip hash
+            if self.take(|db| &mut db.orphan_chain_tips, hash).is_some() {
+                // Parent becomes a tip hash
+                if self.orphans.contains_key(parent_hash) {
+                    self.put(|db| &mut db.orphan_chain_tips, parent_hash.clone(), ());
+                }
+            }
+
+            self.take(|db| &mut db.orphan_header_accumulated_data, hash);
+        }
+        Ok(())
+    }
+
+    fn insert_block_body(&mut self, header: &BlockHeader, body: AggregateBody) -> Result<(), ChainStorageError> {
+        let block_hash = header.hash();
+        debug!(
+            target: LOG_TARGET,
+            "Inserting block body for header `{}`: {}",
+            block_hash.to_hex(),
+            body.to_counts_string()
+        );
+
+        let current_header_at_height =
+            self.headers
+                .get(&header.height)
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader".to_string(),
+                    field: "height".to_string(),
+                    value: header.height.to_string(),
+                })?;
+        let hash = current_header_at_height.hash();
+        if hash != block_hash {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Could not insert this block body because there is a different header stored at height {}. New header \
+                 ({}), current header: ({})",
+                header.height,
+                block_hash.to_hex(),
+                hash.to_hex()
+            )));
+        }
+
+        let (inputs, outputs, kernels) = body.dissolve();
+
+        let data = if header.height == 0 {
+            BlockAccumulatedData::default()
+        } else {
+            self.block_accumulated_data
+                .get(&(header.height - 1))
+                .cloned()
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockAccumulatedData".to_string(),
+                    field: "prev_hash".to_string(),
+                    value: header.prev_hash.to_hex(),
+                })?
+        };
+
+        let mut total_kernel_sum = Commitment::from_bytes(&[0u8; 32]).expect("Could not create commitment");
+        let BlockAccumulatedData {
+            kernels: pruned_kernel_set,
+            outputs: pruned_output_set,
+            deleted,
+            range_proofs: pruned_proof_set,
+            ..
+        } = data;
+
+        let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_kernel_set);
+        for kernel in kernels {
+            total_kernel_sum = &total_kernel_sum + &kernel.excess;
+            let pos = kernel_mmr.push(kernel.hash())?;
+            self.insert_kernel(block_hash.clone(), kernel, pos as u32)?;
+        }
+
+        let mut output_mmr = MutableMmr::<HashDigest, _>::new(pruned_output_set, deleted.deleted)?;
+        let mut witness_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_proof_set);
+        for output in outputs {
+            output_mmr.push(output.hash())?;
+            witness_mmr.push(output.witness_hash())?;
+            let mmr_position = (witness_mmr.get_leaf_count()? - 1) as u32;
+            self.insert_output(block_hash.clone(), header.height, output, mmr_position)?;
+        }
+
+        for input in inputs {
+            let index = self
+                .fetch_mmr_leaf_index(MmrTree::Utxo, &input.output_hash())?
+                .ok_or(ChainStorageError::UnspendableInput)?;
+            if !output_mmr.delete(index) {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "Could not delete index {} from the output MMR",
+                    index
+                )));
+            }
+            self.insert_input(block_hash.clone(), input, index)?;
+        }
+        output_mmr.compress();
+
+        if self.block_accumulated_data.contains_key(&header.height) {
+            return Err(insert_error("block_accumulated_data"));
+        }
+        let accumulated_data = BlockAccumulatedData::new(
+            kernel_mmr.get_pruned_hash_set()?,
+            output_mmr.mmr().get_pruned_hash_set()?,
+            witness_mmr.get_pruned_hash_set()?,
+            output_mmr.deleted().clone(),
+            total_kernel_sum,
+        );
+        self.put(|db| &mut db.block_accumulated_data, header.height, accumulated_data);
+
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn update_deleted_block_accumulated_data_with_diff(
+        &mut self,
+        header_hash: &HashOutput,
+        mut deleted: Bitmap,
+    ) -> Result<(), ChainStorageError> {
+        let height = self.fetch_height_from_hash(header_hash)?;
+        if height == 0 {
+            return Err(ChainStorageError::InvalidOperation(
+                "Tried to update genesis block delete bitmap".to_string(),
+            ));
+        }
+        if let Some(prev_block_accum_data) = self.block_accumulated_data.get(&(height - 1)) {
+            deleted.or_inplace(prev_block_accum_data.deleted());
+        }
+        let mut data = self.block_accumulated_data.get(&height).cloned().unwrap_or_default();
+        data.deleted = DeletedBitmap { deleted };
+        self.put(|db| &mut db.block_accumulated_data, height, data);
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn update_pruned_hash_set(
+        &mut self,
+        mmr_tree: MmrTree,
+        header_hash: &HashOutput,
+        pruned_hash_set: PrunedHashSet,
+    ) -> Result<(), ChainStorageError> {
+        let height = self.fetch_height_from_hash(header_hash)?;
+        let mut block_accum_data = self.block_accumulated_data.get(&height).cloned().unwrap_or_default();
+        match mmr_tree {
+            MmrTree::Kernel => block_accum_data.kernels = pruned_hash_set,
+            MmrTree::Utxo => block_accum_data.outputs = pruned_hash_set,
+            MmrTree::Witness => block_accum_data.range_proofs = pruned_hash_set,
+        }
+        self.put(|db| &mut db.block_accumulated_data, height, block_accum_data);
+        Ok(())
+    }
+
+    fn prune_outputs_and_update_horizon(
+        &mut self,
+        output_positions: Vec<u32>,
+        horizon: u64,
+    ) -> Result<(), ChainStorageError> {
+        for pos in output_positions {
+            // The output at leaf index `pos` is contained in the first block with an output MMR size greater than `pos`
+            let (_height, hash) = self
+                .output_mmr_size_index
+                .range(u64::from(pos) + 1..)
+                .next()
+                .map(|(_, v)| v.clone())
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeader".to_string(),
+                    field: "mmr_position".to_string(),
+                    value: pos.to_string(),
+                })?;
+            debug!(target: LOG_TARGET, "Pruning output: {}-{:010}", hash.to_hex(), pos);
+            let key = (hash, pos);
+            let mut row = self
+                .utxos
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "TransactionOutput".to_string(),
+                    field: "key".to_string(),
+                    value: format!("{}-{:010}", key.0.to_hex(), pos),
+                })?;
+            row.output = None;
+            self.put(|db| &mut db.utxos, key, row);
+        }
+        self.update_metadata(|metadata| metadata.pruned_height = horizon);
+        Ok(())
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &Hash) -> Result<Option<u32>, ChainStorageError> {
+        match tree {
+            MmrTree::Utxo => Ok(self.txos_hash_to_index.get(hash).map(|(_, index)| *index)),
+            _ => Err(ChainStorageError::InvalidQuery(format!(
+                "fetch_mmr_leaf_index is not supported for the {} MMR",
+                tree
+            ))),
+        }
+    }
+
+    #[allow(clippy::ptr_arg)]
+    fn fetch_height_from_hash(&self, header_hash: &HashOutput) -> Result<u64, ChainStorageError> {
+        self.block_hashes
+            .get(header_hash)
+            .copied()
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHash".to_string(),
+                field: "hash".to_string(),
+                value: header_hash.to_hex(),
+            })
+    }
+
+    fn fetch_last_header(&self) -> Option<&BlockHeader> {
+        self.headers.values().next_back()
+    }
+
+    fn fetch_chain_header_by_height(
+        &self,
+        height: u64,
+        function: &'static str,
+    ) -> Result<ChainHeader, ChainStorageError> {
+        let header = self
+            .headers
+            .get(&height)
+            .cloned()
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHeader".to_string(),
+                field: "height".to_string(),
+                value: height.to_string(),
+            })?;
+        let accum_data =
+            self.header_accumulated_data
+                .get(&height)
+                .cloned()
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "BlockHeaderAccumulatedData".to_string(),
+                    field: "height".to_string(),
+                    value: height.to_string(),
+                })?;
+
+        ChainHeader::try_construct(header, accum_data).ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+            function,
+            details: format!("Accumulated data mismatch at height #{}", height),
+        })
+    }
+
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        let metadata = &self.metadata;
+        let not_found = |field: &str| ChainStorageError::ValueNotFound {
+            entity: "ChainMetadata".to_string(),
+            field: field.to_string(),
+            value: "".to_string(),
+        };
+        Ok(ChainMetadata::new(
+            metadata.chain_height.ok_or_else(|| not_found("ChainHeight"))?,
+            metadata.best_block.clone().ok_or_else(|| not_found("BestBlock"))?,
+            metadata.pruning_horizon,
+            metadata.pruned_height,
+            metadata.accumulated_work.ok_or_else(|| not_found("AccumulatedWork"))?,
+        ))
+    }
+}
+
+impl BlockchainBackend for MemoryDatabase {
+    fn write(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
+        if txn.operations().is_empty() {
+            return Ok(());
+        }
+
+        let mark = Instant::now();
+        let num_operations = txn.operations().len();
+        let result = self.db.apply_db_transaction(txn);
+        match result {
+            Ok(_) => {
+                self.db.undo_log.clear();
+                trace!(
+                    target: LOG_TARGET,
+                    "Database completed {} operation(s) in {:.0?}",
+                    num_operations,
+                    mark.elapsed()
+                );
+                Ok(())
+            },
+            Err(e) => {
+                error!(target: LOG_TARGET, "Failed to apply DB transaction: {}", e);
+                self.db.rollback();
+                Err(e)
+            },
+        }
+    }
+
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ChainStorageError> {
+        let res = match key {
+            DbKey::BlockHeader(k) => self
+                .db
+                .headers
+                .get(k)
+                .map(|val| DbValue::BlockHeader(Box::new(val.clone()))),
+            DbKey::BlockHash(hash) => {
+                if hash.len() != BLOCK_HASH_LENGTH {
+                    return Err(ChainStorageError::InvalidQuery(format!(
+                        "Invalid block hash length. Expected length: {} Got: {}",
+                        BLOCK_HASH_LENGTH,
+                        hash.len()
+                    )));
+                }
+                self.db
+                    .block_hashes
+                    .get(hash)
+                    .and_then(|height| self.db.headers.get(height))
+                    .map(|val| DbValue::BlockHash(Box::new(val.clone())))
+            },
+            DbKey::OrphanBlock(k) => self
+                .db
+                .orphans
+                .get(k)
+                .map(|val| DbValue::OrphanBlock(Box::new((**val).clone()))),
+        };
+        Ok(res)
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, ChainStorageError> {
+        Ok(match key {
+            DbKey::BlockHeader(k) => self.db.headers.contains_key(k),
+            DbKey::BlockHash(h) => self.db.block_hashes.contains_key(h),
+            DbKey::OrphanBlock(k) => self.db.orphans.contains_key(k),
+        })
+    }
+
+    fn fetch_chain_header_by_height(&self, height: u64) -> Result<ChainHeader, ChainStorageError> {
+        self.db
+            .fetch_chain_header_by_height(height, "fetch_chain_header_by_height")
+    }
+
+    fn fetch_header_accumulated_data(
+        &self,
+        hash: &HashOutput,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError> {
+        Ok(self
+            .db
+            .block_hashes
+            .get(hash)
+            .and_then(|height| self.db.header_accumulated_data.get(height))
+            .cloned())
+    }
+
+    fn fetch_chain_header_in_all_chains(&self, hash: &HashOutput) -> Result<ChainHeader, ChainStorageError> {
+        if let Some(height) = self.db.block_hashes.get(hash) {
+            return self
+                .db
+                .fetch_chain_header_by_height(*height, "fetch_chain_header_in_all_chains");
+        }
+
+        if let Some(accum) = self.db.orphan_header_accumulated_data.get(hash) {
+            let orphan = self
+                .db
+                .orphans
+                .get(hash)
+                .ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+                    function: "fetch_chain_header_in_all_chains",
+                    details: format!(
+                        "Orphan accumulated data exists but the corresponding orphan header {} does not",
+                        hash.to_hex()
+                    ),
+                })?;
+            return ChainHeader::try_construct(orphan.header.clone(), accum.clone()).ok_or_else(|| {
+                ChainStorageError::DataInconsistencyDetected {
+                    function: "fetch_chain_header_in_all_chains",
+                    details: format!("accumulated data mismatch for orphan header {}", hash.to_hex()),
+                }
+            });
+        }
+
+        Err(ChainStorageError::ValueNotFound {
+            entity: "chain_header_in_all_chains".to_string(),
+            field: "hash".to_string(),
+            value: hash.to_hex(),
+        })
+    }
+
+    fn fetch_header_containing_kernel_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        let height = self
+            .db
+            .kernel_mmr_size_index
+            .range(mmr_position..)
+            .next()
+            .map(|(_, height)| *height)
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "kernel_mmr_size_index".to_string(),
+                field: "mmr_position".to_string(),
+                value: mmr_position.to_string(),
+            })?;
+        self.db
+            .fetch_chain_header_by_height(height, "fetch_header_containing_kernel_mmr")
+    }
+
+    fn fetch_header_containing_utxo_mmr(&self, mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        let height = self
+            .db
+            .output_mmr_size_index
+            .range(mmr_position..)
+            .next()
+            .map(|(_, (height, _))| *height)
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "output_mmr_size_index".to_string(),
+                field: "mmr_position".to_string(),
+                value: mmr_position.to_string(),
+            })?;
+        self.db
+            .fetch_chain_header_by_height(height, "fetch_header_containing_utxo_mmr")
+    }
+
+    fn is_empty(&self) -> Result<bool, ChainStorageError> {
+        Ok(self.db.headers.is_empty())
+    }
+
+    fn fetch_block_accumulated_data(
+        &self,
+        header_hash: &HashOutput,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        Ok(self
+            .db
+            .block_hashes
+            .get(header_hash)
+            .and_then(|height| self.db.block_accumulated_data.get(height))
+            .cloned())
+    }
+
+    fn fetch_block_accumulated_data_by_height(
+        &self,
+        height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        Ok(self.db.block_accumulated_data.get(&height).cloned())
+    }
+
+    fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        Ok(self
+            .db
+            .kernels
+            .range(block_range(header_hash))
+            .map(|(_, row)| row.kernel.clone())
+            .collect())
+    }
+
+    fn fetch_kernel_by_excess(
+        &self,
+        excess: &[u8],
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        Ok(self
+            .db
+            .kernel_excess_index
+            .get(excess)
+            .and_then(|key| self.db.kernels.get(key).map(|row| (row.kernel.clone(), key.0.clone()))))
+    }
+
+    fn fetch_kernel_by_excess_sig(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        Ok(self
+            .db
+            .kernel_excess_sig_index
+            .get(&excess_sig_key(excess_sig))
+            .and_then(|key| self.db.kernels.get(key).map(|row| (row.kernel.clone(), key.0.clone()))))
+    }
+
+    fn fetch_kernels_by_mmr_position(&self, start: u64, end: u64) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let start_height = match self.db.kernel_mmr_size_index.range(start + 1..).next() {
+            Some((_, height)) => *height,
+            None => return Ok(vec![]),
+        };
+        let end_height = self
+            .db
+            .kernel_mmr_size_index
+            .range(end + 1..)
+            .next()
+            .map(|(_, height)| *height)
+            .unwrap_or(start_height);
+
+        let previous_mmr_count = if start_height == 0 {
+            0
+        } else {
+            self.db
+                .headers
+                .get(&(start_height - 1))
+                .map(|h| h.kernel_mmr_size)
+                .ok_or_else(|| value_not_found("BlockHeader", "height", (start_height - 1).to_string()))?
+        };
+
+        let total_size = (end - start) as usize + 1;
+        let mut result = Vec::with_capacity(total_size);
+        let mut skip_amount = (start - previous_mmr_count) as usize;
+        for height in start_height..=end_height {
+            let hash = &self
+                .db
+                .header_accumulated_data
+                .get(&height)
+                .ok_or_else(|| value_not_found("BlockHeader", "height", height.to_string()))?
+                .hash;
+            result.extend(
+                self.db
+                    .kernels
+                    .range(block_range(hash))
+                    .skip(skip_amount)
+                    .take(total_size - result.len())
+                    .map(|(_, row)| row.kernel.clone()),
+            );
+            skip_amount = 0;
+        }
+        Ok(result)
+    }
+
+    fn fetch_utxos_by_mmr_position(
+        &self,
+        start: u64,
+        end: u64,
+        deleted: &Bitmap,
+    ) -> Result<(Vec<PrunedOutput>, Bitmap), ChainStorageError> {
+        let start_height = self
+            .db
+            .output_mmr_size_index
+            .range(start + 1..)
+            .next()
+            .map(|(_, (height, _))| *height)
+            .ok_or_else(|| {
+                ChainStorageError::InvalidQuery(format!(
+                    "Unable to find block height from start output MMR index {}",
+                    start
+                ))
+            })?;
+        let end_height = self
+            .db
+            .output_mmr_size_index
+            .range(end + 1..)
+            .next()
+            .map(|(_, (height, _))| *height)
+            .unwrap_or(start_height);
+
+        let previous_mmr_count = if start_height == 0 {
+            0
+        } else {
+            self.db
+                .headers
+                .get(&(start_height - 1))
+                .map(|h| h.output_mmr_size)
+                .ok_or_else(|| value_not_found("BlockHeader", "height", (start_height - 1).to_string()))?
+        };
+
+        let total_size = end
+            .checked_sub(start)
+            .and_then(|v| v.checked_add(1))
+            .and_then(|v| usize::try_from(v).ok())
+            .ok_or_else(|| {
+                ChainStorageError::InvalidQuery("fetch_utxos_by_mmr_position: end is less than start".to_string())
+            })?;
+        let mut result = Vec::with_capacity(total_size);
+        let mut skip_amount = (start - previous_mmr_count) as usize;
+        let mut difference_bitmap = Bitmap::create();
+
+        for height in start_height..=end_height {
+            let hash = &self
+                .db
+                .header_accumulated_data
+                .get(&height)
+                .ok_or_else(|| value_not_found("BlockHeader", "height", height.to_string()))?
+                .hash;
+            result.extend(
+                self.db
+                    .utxos
+                    .range(block_range(hash))
+                    .skip(skip_amount)
+                    .take(total_size - result.len())
+                    .map(|(_, row)| match row.output {
+                        Some(ref output) if !deleted.contains(row.mmr_position) => {
+                            PrunedOutput::NotPruned { output: output.clone() }
+                        },
+                        _ => PrunedOutput::Pruned {
+                            output_hash: row.hash.clone(),
+                            range_proof_hash: row.range_proof_hash.clone(),
+                        },
+                    }),
+            );
+
+            // Builds a BitMap of the deleted UTXO MMR indexes that occurred at the current height
+            let mut diff_bitmap = self
+                .db
+                .block_accumulated_data
+                .get(&height)
+                .ok_or_else(|| value_not_found("BlockAccumulatedData", "height", height.to_string()))?
+                .deleted()
+                .clone();
+            if height > 0 {
+                let prev_accum = self
+                    .db
+                    .block_accumulated_data
+                    .get(&(height - 1))
+                    .ok_or_else(|| value_not_found("BlockAccumulatedData", "height", height.to_string()))?;
+                diff_bitmap.xor_inplace(prev_accum.deleted());
+            }
+            difference_bitmap.or_inplace(&diff_bitmap);
+
+            skip_amount = 0;
+        }
+
+        difference_bitmap.run_optimize();
+        Ok((result, difference_bitmap))
+    }
+
+    fn fetch_output(
+        &self,
+        output_hash: &HashOutput,
+    ) -> Result<Option<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        let row = match self
+            .db
+            .txos_hash_to_index
+            .get(output_hash)
+            .and_then(|key| self.db.utxos.get(key))
+        {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        match row.output {
+            Some(ref output) => Ok(Some((output.clone(), row.mmr_position, row.mined_height))),
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Fetch output: {} has been pruned",
+                    output_hash.to_hex()
+                );
+                Ok(None)
+            },
+        }
+    }
+
+    // The in-memory backend does not keep secondary output indexes, the tables are small enough to scan instead
+    fn fetch_outputs_by_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        Ok(self
+            .db
+            .utxos
+            .values()
+            .filter_map(|row| {
+                row.output
+                    .as_ref()
+                    .filter(|output| output.commitment == *commitment)
+                    .map(|output| (output.clone(), row.mmr_position, row.mined_height))
+            })
+            .collect())
+    }
+
+    fn fetch_outputs_by_script_hash(
+        &self,
+        script_hash: &[u8],
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        Ok(self
+            .db
+            .utxos
+            .values()
+            .filter_map(|row| {
+                row.output
+                    .as_ref()
+                    .filter(|output| {
+                        output
+                            .script
+                            .as_hash::<HashDigest>()
+                            .map(|hash| hash[..] == *script_hash)
+                            .unwrap_or(false)
+                    })
+                    .map(|output| (output.clone(), row.mmr_position, row.mined_height))
+            })
+            .collect())
+    }
+
+    fn fetch_output_spent_in_block(
+        &self,
+        output_hash: &HashOutput,
+    ) -> Result<Option<(HashOutput, u64)>, ChainStorageError> {
+        let mmr_position = match self.db.txos_hash_to_index.get(output_hash) {
+            Some((_, mmr_position)) => *mmr_position,
+            None => return Ok(None),
+        };
+        // Inputs are keyed by the MMR position of the output that they spend
+        match self.db.inputs.keys().find(|(_, pos)| *pos == mmr_position) {
+            Some((header_hash, _)) => Ok(Some((
+                header_hash.clone(),
+                self.db.fetch_height_from_hash(header_hash)?,
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
+        Ok(self
+            .db
+            .utxos
+            .range(block_range(header_hash))
+            .map(|(_, row)| match row.output {
+                Some(ref output) => PrunedOutput::NotPruned { output: output.clone() },
+                None => PrunedOutput::Pruned {
+                    output_hash: row.hash.clone(),
+                    range_proof_hash: row.range_proof_hash.clone(),
+                },
+            })
+            .collect())
+    }
+
+    fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        Ok(self
+            .db
+            .inputs
+            .range(block_range(header_hash))
+            .map(|(_, row)| row.input.clone())
+            .collect())
+    }
+
+    fn fetch_mmr_size(&self, tree: MmrTree) -> Result<u64, ChainStorageError> {
+        match tree {
+            MmrTree::Kernel => Ok(self.db.kernels.len() as u64),
+            // Every output has exactly one witness leaf, so the output and witness MMRs are always the same size
+            MmrTree::Utxo | MmrTree::Witness => Ok(self.db.utxos.len() as u64),
+        }
+    }
+
+    fn fetch_mmr_leaf_index(&self, tree: MmrTree, hash: &Hash) -> Result<Option<u32>, ChainStorageError> {
+        self.db.fetch_mmr_leaf_index(tree, hash)
+    }
+
+    fn orphan_count(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.db.orphans.len())
+    }
+
+    fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
+        self.db.fetch_last_header().cloned().ok_or_else(|| {
+            ChainStorageError::InvalidOperation("Cannot fetch last header because database is empty".to_string())
+        })
+    }
+
+    fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        let metadata = self.db.fetch_chain_metadata()?;
+        self.db
+            .fetch_chain_header_by_height(metadata.height_of_longest_chain(), "fetch_tip_header")
+    }
+
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        self.db.fetch_chain_metadata()
+    }
+
+    fn utxo_count(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.db.utxos.len())
+    }
+
+    fn kernel_count(&self) -> Result<usize, ChainStorageError> {
+        Ok(self.db.kernels.len())
+    }
+
+    fn fetch_reorgs(&self, start: u64, limit: usize) -> Result<Vec<(u64, Reorg)>, ChainStorageError> {
+        Ok(self
+            .db
+            .reorgs
+            .range(start..)
+            .take(limit)
+            .map(|(key, reorg)| (*key, reorg.clone()))
+            .collect())
+    }
+
+    fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        if !self.db.orphan_chain_tips.contains(hash) {
+            return Ok(None);
+        }
+
+        let orphan = self
+            .db
+            .orphans
+            .get(hash)
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "Orphan".to_string(),
+                field: "hash".to_string(),
+                value: hash.to_hex(),
+            })?;
+        let accumulated_data =
+            self.db
+                .orphan_header_accumulated_data
+                .get(hash)
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "Orphan accumulated data".to_string(),
+                    field: "hash".to_string(),
+                    value: hash.to_hex(),
+                })?;
+
+        let chain_header =
+            ChainHeader::try_construct(orphan.header.clone(), accumulated_data.clone()).ok_or_else(|| {
+                ChainStorageError::DataInconsistencyDetected {
+                    function: "fetch_orphan_chain_tip_by_hash",
+                    details: format!("Accumulated data mismatch at height #{}", orphan.header.height),
+                }
+            })?;
+        Ok(Some(chain_header))
+    }
+
+    fn fetch_orphan_children_of(&self, hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
+        let children = match self.db.orphan_parent_map_index.get(&hash) {
+            Some(children) => children,
+            None => return Ok(vec![]),
+        };
+        children
+            .iter()
+            .map(|hash| {
+                self.db.orphans.get(hash).map(|block| (**block).clone()).ok_or_else(|| {
+                    ChainStorageError::ValueNotFound {
+                        entity: "Orphan".to_string(),
+                        field: "hash".to_string(),
+                        value: hash.to_hex(),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn fetch_orphan_chain_block(&self, hash: HashOutput) -> Result<Option<ChainBlock>, ChainStorageError> {
+        let block = match self.db.orphans.get(&hash) {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        match self.db.orphan_header_accumulated_data.get(&hash) {
+            Some(accumulated_data) => {
+                let chain_block =
+                    ChainBlock::try_construct(block.clone(), accumulated_data.clone()).ok_or_else(|| {
+                        ChainStorageError::DataInconsistencyDetected {
+                            function: "fetch_orphan_chain_block",
+                            details: format!("Accumulated data mismatch for hash {}", hash.to_hex()),
+                        }
+                    })?;
+                Ok(Some(chain_block))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn delete_oldest_orphans(
+        &mut self,
+        horizon_height: u64,
+        orphan_storage_capacity: usize,
+    ) -> Result<(), ChainStorageError> {
+        let num_over_limit = self.db.orphans.len().saturating_sub(orphan_storage_capacity);
+        if num_over_limit == 0 {
+            return Ok(());
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Orphan block storage limit of {} reached, performing cleanup of {} entries.",
+            orphan_storage_capacity,
+            num_over_limit,
+        );
+
+        let mut orphans = self
+            .db
+            .orphans
+            .iter()
+            .map(|(hash, block)| (block.header.height, hash.clone()))
+            .collect::<Vec<_>>();
+        orphans.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut txn = DbTransaction::new();
+        for (removed_count, (height, block_hash)) in orphans.into_iter().enumerate() {
+            if height > horizon_height && removed_count >= num_over_limit {
+                break;
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Discarding orphan block #{} ({}).",
+                height,
+                block_hash.to_hex()
+            );
+            txn.delete_orphan(block_hash);
+        }
+        self.write(txn)
+    }
+
+    fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError> {
+        Ok(self.db.monero_seed_height.get(seed).copied().unwrap_or(0))
+    }
+
+    fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
+        Ok(self.db.metadata.horizon_data.clone())
+    }
+
+    fn check_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        // Failed transactions are rolled back using the undo log, so the indexes cannot be left partially written
+        Ok(IntegrityReport::default())
+    }
+
+    fn repair_integrity(&mut self) -> Result<IntegrityReport, ChainStorageError> {
+        self.check_integrity()
+    }
+}
+
+/// Returns the key range containing every row belonging to the block with the given hash
+#[allow(clippy::ptr_arg)]
+fn block_range(header_hash: &HashOutput) -> std::ops::RangeInclusive<BlockIndexKey> {
+    (header_hash.clone(), 0)..=(header_hash.clone(), u32::MAX)
+}
+
+fn excess_sig_key(excess_sig: &Signature) -> Vec<u8> {
+    let mut key = Vec::<u8>::new();
+    key.extend(excess_sig.get_public_nonce().as_bytes());
+    key.extend(excess_sig.get_signature().as_bytes());
+    key
+}
+
+fn value_not_found(entity: &str, field: &str, value: String) -> ChainStorageError {
+    ChainStorageError::ValueNotFound {
+        entity: entity.to_string(),
+        field: field.to_string(),
+        value,
+    }
+}
+
+fn insert_error(table: &'static str) -> ChainStorageError {
+    ChainStorageError::InsertError {
+        table,
+        error: "Key already exists".to_string(),
+    }
+}
@@ -44,12 +44,14 @@ mod block_add_result;
 pub use block_add_result::BlockAddResult;
 mod blockchain_database;
 pub use blockchain_database::{
+    calculate_genesis_mmr_roots,
     calculate_mmr_roots,
     fetch_header,
     fetch_headers,
     fetch_target_difficulty_for_next_block,
     BlockchainDatabase,
     BlockchainDatabaseConfig,
+    MmrRoots,
     Validators,
 };
 
@@ -73,14 +75,26 @@ pub use historical_block::HistoricalBlock;
 mod horizon_data;
 pub use horizon_data::HorizonData;
 
+mod integrity;
+pub use integrity::{IntegrityIssue, IntegrityReport};
+
 mod pruned_output;
 pub use pruned_output::PrunedOutput;
 
+mod reorg;
+pub use reorg::{Reorg, ReorgPage, MAX_REORGS_PER_PAGE, MAX_REORG_JOURNAL_ENTRIES};
+
+mod snapshot;
+pub use snapshot::{SnapshotError, SNAPSHOT_VERSION};
+
 mod lmdb_db;
 pub use lmdb_db::{
     create_lmdb_database,
     create_recovery_lmdb_database,
+    migrate_lmdb_database,
     LMDBDatabase,
+    MigrationReport,
+    MigrationStep,
     LMDB_DB_BLOCK_HASHES,
     LMDB_DB_HEADERS,
     LMDB_DB_KERNELS,
@@ -90,5 +104,8 @@ pub use lmdb_db::{
     LMDB_DB_UTXOS,
 };
 
+mod memory_db;
+pub use memory_db::MemoryDatabase;
+
 mod target_difficulties;
 pub use target_difficulties::TargetDifficulties;
@@ -0,0 +1,159 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    chain_storage::{ChainBlock, ChainHeader},
+    transactions::{
+        transaction::KernelFeatures,
+        types::{HashOutput, Signature},
+    },
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+use tari_crypto::tari_utilities::{epoch_time::EpochTime, hex::Hex};
+
+/// The maximum number of reorgs kept in the reorg journal. The oldest reorgs are pruned when a new reorg is recorded
+/// in a full journal.
+pub const MAX_REORG_JOURNAL_ENTRIES: u64 = 1000;
+/// The maximum number of reorgs returned by a single read of the reorg journal
+pub const MAX_REORGS_PER_PAGE: usize = 100;
+
+/// A record of a chain reorganisation, kept in the reorg journal of the blockchain database
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Reorg {
+    /// The local time at which the reorg happened
+    pub local_time: EpochTime,
+    /// The height of the last block shared by the old and new chains
+    pub fork_height: u64,
+    pub fork_hash: HashOutput,
+    pub prev_tip_height: u64,
+    pub prev_tip_hash: HashOutput,
+    pub new_tip_height: u64,
+    pub new_tip_hash: HashOutput,
+    /// The hashes of the removed blocks, from the old tip to the fork
+    pub removed_blocks: Vec<HashOutput>,
+    /// The hashes of the added blocks, from the fork to the new tip
+    pub added_blocks: Vec<HashOutput>,
+    /// The excess signatures of the transaction kernels that were mined in the removed blocks but are not in the new
+    /// chain. `None` if the blocks of the new chain were not known when the reorg was recorded.
+    pub dropped_kernels: Option<Vec<Signature>>,
+}
+
+impl Reorg {
+    /// Creates the record of a reorg from the blocks that were removed from the main chain, ordered from the old tip
+    /// to the fork (height descending), and the blocks that replaced them, ordered from the fork to the new tip.
+    /// Returns `None` if either list is empty.
+    pub fn from_reorged_blocks(removed: &[Arc<ChainBlock>], added: &[Arc<ChainBlock>]) -> Option<Self> {
+        let prev_tip = removed.first()?;
+        let first_added = added.first()?;
+        let new_tip = added.last()?;
+
+        Some(Self {
+            local_time: EpochTime::now(),
+            fork_height: first_added.height() - 1,
+            fork_hash: first_added.header().prev_hash.clone(),
+            prev_tip_height: prev_tip.height(),
+            prev_tip_hash: prev_tip.hash().clone(),
+            new_tip_height: new_tip.height(),
+            new_tip_hash: new_tip.hash().clone(),
+            removed_blocks: removed.iter().map(|b| b.hash().clone()).collect(),
+            added_blocks: added.iter().map(|b| b.hash().clone()).collect(),
+            dropped_kernels: Some(dropped_kernels(removed, added)),
+        })
+    }
+
+    /// Creates the record of a rewind of the main chain, e.g. when block sync switches to a stronger chain, from the
+    /// blocks that were removed, ordered from the old tip to the fork, and the new tip. The blocks of the new chain are
+    /// added later and are not part of the record, so the dropped kernels are unknown: most of the removed kernels are
+    /// usually mined again on the new chain. Returns `None` if no blocks were removed.
+    pub fn from_rewound_blocks(removed: &[Arc<ChainBlock>], new_tip: &ChainHeader) -> Option<Self> {
+        let prev_tip = removed.first()?;
+
+        Some(Self {
+            local_time: EpochTime::now(),
+            fork_height: new_tip.height(),
+            fork_hash: new_tip.hash().clone(),
+            prev_tip_height: prev_tip.height(),
+            prev_tip_hash: prev_tip.hash().clone(),
+            new_tip_height: new_tip.height(),
+            new_tip_hash: new_tip.hash().clone(),
+            removed_blocks: removed.iter().map(|b| b.hash().clone()).collect(),
+            added_blocks: Vec::new(),
+            dropped_kernels: None,
+        })
+    }
+
+    /// The number of blocks that were removed from the main chain
+    pub fn depth(&self) -> u64 {
+        self.removed_blocks.len() as u64
+    }
+}
+
+/// The excess signatures of the transaction kernels in the removed blocks that are not in the added blocks. Coinbases
+/// are excluded because they cannot be mined on another chain.
+fn dropped_kernels(removed: &[Arc<ChainBlock>], added: &[Arc<ChainBlock>]) -> Vec<Signature> {
+    let added_kernels = added
+        .iter()
+        .flat_map(|b| b.block().body.kernels())
+        .map(|k| &k.excess_sig)
+        .collect::<Vec<_>>();
+    removed
+        .iter()
+        .flat_map(|b| b.block().body.kernels())
+        .filter(|k| !k.features.contains(KernelFeatures::COINBASE_KERNEL))
+        .map(|k| &k.excess_sig)
+        .filter(|sig| !added_kernels.contains(sig))
+        .cloned()
+        .collect()
+}
+
+/// A page of reorgs read from the reorg journal
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReorgPage {
+    /// The reorgs in the page, oldest first
+    pub reorgs: Vec<Reorg>,
+    /// The journal sequence number to read the next page from
+    pub next_start: u64,
+}
+
+impl fmt::Display for Reorg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let datetime: DateTime<Utc> = self.local_time.into();
+        write!(
+            f,
+            "{}: reorg of depth {} at fork #{} ({}) from tip #{} ({}) to #{} ({}), {} transaction(s) dropped",
+            datetime.to_rfc2822(),
+            self.depth(),
+            self.fork_height,
+            self.fork_hash.to_hex(),
+            self.prev_tip_height,
+            self.prev_tip_hash.to_hex(),
+            self.new_tip_height,
+            self.new_tip_hash.to_hex(),
+            self.dropped_kernels
+                .as_ref()
+                .map(|k| k.len().to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        )
+    }
+}
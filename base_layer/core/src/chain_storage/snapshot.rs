@@ -0,0 +1,549 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Chain state snapshots allow a pruned node to bootstrap from a file instead of synchronizing the horizon state from
+//! its peers.
+//!
+//! A snapshot contains every header, every kernel and the (possibly pruned) outputs of every block up to and including
+//! the snapshot height, along with the per-block deleted bitmap diffs and the resulting [HorizonData]. Nothing in the
+//! file is trusted on import: headers are validated with the database's header validator, every block's MMR roots are
+//! recomputed and checked against its header and the final state is checked with a [FinalHorizonStateValidation]
+//! before the chain tip is moved to the snapshot height.
+
+use crate::{
+    blocks::BlockHeader,
+    chain_storage::{
+        BlockHeaderAccumulatedData,
+        BlockchainBackend,
+        BlockchainDatabase,
+        ChainHeader,
+        ChainStorageError,
+        DbTransaction,
+        HorizonData,
+        MmrTree,
+        PrunedOutput,
+    },
+    transactions::{
+        transaction::{TransactionError, TransactionKernel},
+        types::{Commitment, HashDigest, HashOutput, RangeProofService},
+    },
+    validation::{DifficultyCalculator, FinalHorizonStateValidation, HeaderValidation, ValidationError},
+};
+use croaring::Bitmap;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+    num::TryFromIntError,
+};
+use tari_crypto::tari_utilities::{hex::Hex, Hashable};
+use tari_mmr::{error::MerkleMountainRangeError, MerkleMountainRange, MutableMmr};
+use thiserror::Error;
+
+const LOG_TARGET: &str = "c::cs::snapshot";
+
+/// Identifies a chain state snapshot file
+const SNAPSHOT_MAGIC: [u8; 8] = *b"TARISNAP";
+/// The current version of the snapshot file format
+pub const SNAPSHOT_VERSION: u16 = 1;
+/// The number of blocks removed per database transaction when discarding a failed import
+const DISCARD_BATCH_SIZE: u64 = 1000;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Chain storage error: {0}")]
+    ChainStorageError(#[from] ChainStorageError),
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
+    #[error("Final state validation failed: {0}")]
+    FinalStateValidationFailed(ValidationError),
+    #[error("MerkleMountainRangeError: {0}")]
+    MerkleMountainRangeError(#[from] MerkleMountainRangeError),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Snapshot (de)serialization failed: {0}")]
+    SerializationError(#[from] bincode::Error),
+    #[error("Cannot export a snapshot at height {height}: {reason}")]
+    InvalidExportHeight { height: u64, reason: String },
+    #[error("The database cannot import a snapshot: {0}")]
+    DatabaseNotEmpty(String),
+    #[error("Invalid snapshot: {0}")]
+    InvalidSnapshot(String),
+    #[error("MMR did not match for {mmr_tree} at height {at_height}. Expected {actual_hex} to equal {expected_hex}")]
+    InvalidMmrRoot {
+        mmr_tree: MmrTree,
+        at_height: u64,
+        expected_hex: String,
+        actual_hex: String,
+    },
+    #[error("Invalid kernel signature: {0}")]
+    InvalidKernelSignature(TransactionError),
+    #[error("Invalid range proof for output:{0} : {1}")]
+    InvalidRangeProof(String, String),
+}
+
+impl From<TryFromIntError> for SnapshotError {
+    fn from(err: TryFromIntError) -> Self {
+        SnapshotError::InvalidSnapshot(err.to_string())
+    }
+}
+
+/// The first record in a snapshot file
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotPreamble {
+    magic: [u8; 8],
+    version: u16,
+    genesis_hash: HashOutput,
+    height: u64,
+}
+
+/// The data for a single block, one record is written for each block from height 1 up to the snapshot height
+#[derive(Serialize, Deserialize)]
+struct SnapshotBlock {
+    header: BlockHeader,
+    kernels: Vec<TransactionKernel>,
+    outputs: Vec<PrunedOutput>,
+    /// The serialized bitmap of the output MMR positions that were spent in this block
+    deleted_diff: Vec<u8>,
+}
+
+/// Writes a snapshot of the chain state at `height` to `writer`. Outputs that have been spent at the snapshot height
+/// are written as pruned outputs.
+pub(super) fn export_snapshot<B: BlockchainBackend, W: Write>(
+    db: &BlockchainDatabase<B>,
+    height: u64,
+    mut writer: W,
+) -> Result<HorizonData, SnapshotError> {
+    let metadata = db.get_chain_metadata()?;
+    if height > metadata.height_of_longest_chain() {
+        return Err(SnapshotError::InvalidExportHeight {
+            height,
+            reason: format!("the chain tip is at height {}", metadata.height_of_longest_chain()),
+        });
+    }
+    if height < metadata.pruned_height() {
+        return Err(SnapshotError::InvalidExportHeight {
+            height,
+            reason: format!("the database has been pruned up to height {}", metadata.pruned_height()),
+        });
+    }
+
+    let genesis = db.fetch_chain_header(0)?;
+    let snapshot_header = db.fetch_chain_header(height)?;
+    let deleted = db
+        .fetch_block_accumulated_data(snapshot_header.hash().clone())?
+        .deleted()
+        .clone();
+
+    bincode::serialize_into(&mut writer, &SnapshotPreamble {
+        magic: SNAPSHOT_MAGIC,
+        version: SNAPSHOT_VERSION,
+        genesis_hash: genesis.hash().clone(),
+        height,
+    })?;
+
+    let mut kernel_sum = Commitment::default();
+    let mut utxo_sum = Commitment::default();
+    let mut prev_output_mmr_size = 0;
+    for h in 0..=height {
+        let (header, kernels, outputs, deleted_diff) = {
+            let backend = db.db_read_access()?;
+            let header = backend.fetch_chain_header_by_height(h)?;
+            let kernels = backend.fetch_kernels_in_block(header.hash())?;
+            let (outputs, deleted_diff) = backend.fetch_utxos_by_mmr_position(
+                prev_output_mmr_size,
+                header.header().output_mmr_size - 1,
+                &deleted,
+            )?;
+            (header, kernels, outputs, deleted_diff)
+        };
+        prev_output_mmr_size = header.header().output_mmr_size;
+
+        for kernel in &kernels {
+            kernel_sum = &kernel.excess + &kernel_sum;
+        }
+        for output in &outputs {
+            if let PrunedOutput::NotPruned { output } = output {
+                utxo_sum = &output.commitment + &utxo_sum;
+            }
+        }
+
+        // The genesis block is part of every node's consensus rules, so it is never included in the snapshot
+        if h == 0 {
+            continue;
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Exporting block #{} ({} kernel(s), {} output(s))",
+            h,
+            kernels.len(),
+            outputs.len()
+        );
+        bincode::serialize_into(&mut writer, &SnapshotBlock {
+            header: header.into_header(),
+            kernels,
+            outputs,
+            deleted_diff: deleted_diff.serialize(),
+        })?;
+    }
+
+    // Make sure that the chain did not reorg while the snapshot was being written
+    if db.fetch_chain_header(height)?.hash() != snapshot_header.hash() {
+        return Err(SnapshotError::InvalidExportHeight {
+            height,
+            reason: "the chain reorged while the snapshot was being exported".to_string(),
+        });
+    }
+
+    let horizon_data = HorizonData::new(kernel_sum, utxo_sum);
+    bincode::serialize_into(&mut writer, &horizon_data)?;
+    writer.flush()?;
+    info!(
+        target: LOG_TARGET,
+        "Exported chain state snapshot at height {} ({})",
+        height,
+        snapshot_header.hash().to_hex()
+    );
+    Ok(horizon_data)
+}
+
+/// Reads a snapshot from `reader` and commits it to `db`, which must not contain any blocks other than the genesis
+/// block. Headers and block data are committed block by block once they have been verified. The chain tip and pruned
+/// height are only set once the final horizon state has been validated. If the import fails at any point, every block
+/// that was committed is removed again so that the database only contains the genesis block and the import can be
+/// retried.
+pub(super) fn import_snapshot<B: BlockchainBackend, R: Read>(
+    db: &BlockchainDatabase<B>,
+    header_validator: &dyn HeaderValidation<B>,
+    difficulty_calculator: &DifficultyCalculator,
+    final_state_validator: &dyn FinalHorizonStateValidation<B>,
+    prover: &RangeProofService,
+    mut reader: R,
+) -> Result<ChainHeader, SnapshotError> {
+    let preamble: SnapshotPreamble = bincode::deserialize_from(&mut reader)?;
+    if preamble.magic != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidSnapshot("Not a chain state snapshot".to_string()));
+    }
+    if preamble.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::InvalidSnapshot(format!(
+            "Unsupported snapshot version {}. Expected version {}",
+            preamble.version, SNAPSHOT_VERSION
+        )));
+    }
+
+    let metadata = db.get_chain_metadata()?;
+    if !metadata.is_pruned_node() {
+        return Err(SnapshotError::DatabaseNotEmpty(
+            "snapshots can only be imported by pruned nodes".to_string(),
+        ));
+    }
+    let last_header = db.fetch_last_header()?;
+    if metadata.height_of_longest_chain() != 0 || last_header.height != 0 {
+        return Err(SnapshotError::DatabaseNotEmpty(format!(
+            "the database already contains blocks or headers up to height {}",
+            last_header.height
+        )));
+    }
+
+    let genesis_header = db.fetch_chain_header(0)?;
+    if genesis_header.hash() != &preamble.genesis_hash {
+        return Err(SnapshotError::InvalidSnapshot(format!(
+            "Snapshot was created for a chain with genesis block {} but this node's genesis block is {}",
+            preamble.genesis_hash.to_hex(),
+            genesis_header.hash().to_hex()
+        )));
+    }
+    info!(
+        target: LOG_TARGET,
+        "Importing chain state snapshot up to height {}", preamble.height
+    );
+
+    let result = import_blocks(
+        db,
+        header_validator,
+        difficulty_calculator,
+        final_state_validator,
+        prover,
+        reader,
+        genesis_header,
+        preamble.height,
+    );
+    if let Err(err) = &result {
+        warn!(
+            target: LOG_TARGET,
+            "Chain state snapshot import failed, discarding imported blocks: {}", err
+        );
+        if let Err(discard_err) = discard_imported_blocks(db) {
+            error!(
+                target: LOG_TARGET,
+                "Failed to discard the blocks of a failed snapshot import: {}", discard_err
+            );
+        }
+    }
+    result
+}
+
+/// Imports the blocks following the genesis block and the horizon data from the snapshot, and moves the chain tip to
+/// the snapshot height
+#[allow(clippy::too_many_arguments)]
+fn import_blocks<B: BlockchainBackend, R: Read>(
+    db: &BlockchainDatabase<B>,
+    header_validator: &dyn HeaderValidation<B>,
+    difficulty_calculator: &DifficultyCalculator,
+    final_state_validator: &dyn FinalHorizonStateValidation<B>,
+    prover: &RangeProofService,
+    mut reader: R,
+    genesis_header: ChainHeader,
+    snapshot_height: u64,
+) -> Result<ChainHeader, SnapshotError> {
+    let mut prev_header = genesis_header;
+
+    let (pruned_kernel_set, pruned_output_set, pruned_proof_set, mut deleted) =
+        db.fetch_block_accumulated_data(prev_header.hash().clone())?.dissolve();
+    let mut kernel_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_kernel_set);
+    let mut output_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_output_set);
+    let mut witness_mmr = MerkleMountainRange::<HashDigest, _>::new(pruned_proof_set);
+
+    // The genesis block is not part of the snapshot, so its outputs and kernels are taken from the local database
+    let mut kernel_sum = Commitment::default();
+    let mut utxo_sum = Commitment::default();
+    let mut unpruned_positions = Bitmap::create();
+    {
+        let backend = db.db_read_access()?;
+        for kernel in backend.fetch_kernels_in_block(prev_header.hash())? {
+            kernel_sum = &kernel.excess + &kernel_sum;
+        }
+        for (pos, output) in backend
+            .fetch_outputs_in_block(prev_header.hash())?
+            .into_iter()
+            .enumerate()
+        {
+            if let PrunedOutput::NotPruned { output } = output {
+                utxo_sum = &output.commitment + &utxo_sum;
+                unpruned_positions.add(u32::try_from(pos)?);
+            }
+        }
+    }
+
+    for height in 1..=snapshot_height {
+        let block: SnapshotBlock = bincode::deserialize_from(&mut reader)?;
+        let header = block.header;
+        if header.height != height {
+            return Err(SnapshotError::InvalidSnapshot(format!(
+                "Expected header at height {} but got height {}",
+                height, header.height
+            )));
+        }
+        if header.prev_hash != *prev_header.hash() {
+            return Err(SnapshotError::InvalidSnapshot(format!(
+                "Header #{} does not build on the previous header {}",
+                height,
+                prev_header.hash().to_hex()
+            )));
+        }
+        let hash = header.hash();
+
+        let achieved_target = header_validator.validate(&*db.db_read_access()?, &header, difficulty_calculator)?;
+        let accumulated_data = BlockHeaderAccumulatedData::builder(prev_header.accumulated_data())
+            .with_hash(hash.clone())
+            .with_achieved_target_difficulty(achieved_target)
+            .with_total_kernel_offset(header.total_kernel_offset.clone())
+            .build()?;
+        // NOTE: Panic is impossible, accumulated data constructed from header
+        let chain_header = ChainHeader::try_construct(header, accumulated_data).unwrap();
+        let header = chain_header.header();
+
+        let mut txn = DbTransaction::new();
+        txn.insert_chain_header(chain_header.clone());
+
+        let expected_num_kernels = header.kernel_mmr_size - prev_header.header().kernel_mmr_size;
+        if block.kernels.len() as u64 != expected_num_kernels {
+            return Err(SnapshotError::InvalidSnapshot(format!(
+                "Expected {} kernel(s) for block #{} but got {}",
+                expected_num_kernels,
+                height,
+                block.kernels.len()
+            )));
+        }
+        let mut kernel_position = prev_header.header().kernel_mmr_size;
+        for kernel in block.kernels {
+            kernel
+                .verify_signature()
+                .map_err(SnapshotError::InvalidKernelSignature)?;
+            kernel_sum = &kernel.excess + &kernel_sum;
+            kernel_mmr.push(kernel.hash())?;
+            txn.insert_kernel(kernel, hash.clone(), u32::try_from(kernel_position)?);
+            kernel_position += 1;
+        }
+        check_mmr_root(
+            MmrTree::Kernel,
+            height,
+            &header.kernel_mr,
+            kernel_mmr.get_merkle_root()?,
+        )?;
+
+        let expected_num_outputs = header.output_mmr_size - prev_header.header().output_mmr_size;
+        if block.outputs.len() as u64 != expected_num_outputs {
+            return Err(SnapshotError::InvalidSnapshot(format!(
+                "Expected {} output(s) for block #{} but got {}",
+                expected_num_outputs,
+                height,
+                block.outputs.len()
+            )));
+        }
+        let mut output_position = u32::try_from(prev_header.header().output_mmr_size)?;
+        for output in block.outputs {
+            match output {
+                PrunedOutput::NotPruned { output } => {
+                    match output.verify_range_proof(prover) {
+                        Ok(true) => {},
+                        Ok(false) => {
+                            return Err(SnapshotError::InvalidRangeProof(
+                                output.hash().to_hex(),
+                                "The range proof is not valid".to_string(),
+                            ))
+                        },
+                        Err(err) => {
+                            return Err(SnapshotError::InvalidRangeProof(
+                                output.hash().to_hex(),
+                                err.to_string(),
+                            ))
+                        },
+                    }
+                    output_mmr.push(output.hash())?;
+                    witness_mmr.push(output.witness_hash())?;
+                    utxo_sum = &output.commitment + &utxo_sum;
+                    unpruned_positions.add(output_position);
+                    txn.insert_utxo(output, hash.clone(), height, output_position);
+                },
+                PrunedOutput::Pruned {
+                    output_hash,
+                    range_proof_hash,
+                } => {
+                    output_mmr.push(output_hash.clone())?;
+                    witness_mmr.push(range_proof_hash.clone())?;
+                    txn.insert_pruned_utxo(output_hash, range_proof_hash, hash.clone(), height, output_position);
+                },
+            }
+            output_position += 1;
+        }
+
+        deleted.or_inplace(&Bitmap::deserialize(&block.deleted_diff));
+        deleted.run_optimize();
+        let pruned_output_set = output_mmr.get_pruned_hash_set()?;
+        let mutable_output_mmr = MutableMmr::<HashDigest, _>::new(pruned_output_set.clone(), deleted.clone())?;
+        check_mmr_root(
+            MmrTree::Utxo,
+            height,
+            &header.output_mr,
+            mutable_output_mmr.get_merkle_root()?,
+        )?;
+        check_mmr_root(
+            MmrTree::Witness,
+            height,
+            &header.witness_mr,
+            witness_mmr.get_merkle_root()?,
+        )?;
+
+        txn.update_pruned_hash_set(MmrTree::Kernel, hash.clone(), kernel_mmr.get_pruned_hash_set()?);
+        txn.update_pruned_hash_set(MmrTree::Utxo, hash.clone(), pruned_output_set);
+        txn.update_pruned_hash_set(MmrTree::Witness, hash.clone(), witness_mmr.get_pruned_hash_set()?);
+        txn.update_deleted_with_diff(hash, deleted.clone());
+        db.write(txn)?;
+
+        trace!(target: LOG_TARGET, "Imported block #{}", height);
+        prev_header = chain_header;
+    }
+
+    // Outputs that are spent at the snapshot height must have been pruned, otherwise they would be counted in the UTXO
+    // sum
+    if unpruned_positions.and_cardinality(&deleted) > 0 {
+        return Err(SnapshotError::InvalidSnapshot(
+            "Snapshot contains spent outputs that have not been pruned".to_string(),
+        ));
+    }
+
+    let horizon_data: HorizonData = bincode::deserialize_from(&mut reader)?;
+    if horizon_data.kernel_sum() != &kernel_sum || horizon_data.utxo_sum() != &utxo_sum {
+        return Err(SnapshotError::InvalidSnapshot(
+            "The horizon data does not match the snapshot contents".to_string(),
+        ));
+    }
+
+    final_state_validator
+        .validate(prev_header.height(), &utxo_sum, &kernel_sum, &*db.db_read_access()?)
+        .map_err(SnapshotError::FinalStateValidationFailed)?;
+
+    let mut txn = DbTransaction::new();
+    txn.set_best_block(
+        prev_header.height(),
+        prev_header.hash().clone(),
+        prev_header.accumulated_data().total_accumulated_difficulty,
+    )
+    .set_pruned_height(prev_header.height(), kernel_sum, utxo_sum);
+    db.write(txn)?;
+
+    info!(
+        target: LOG_TARGET,
+        "Chain state snapshot imported. Chain tip is now #{} ({})",
+        prev_header.height(),
+        prev_header.hash().to_hex()
+    );
+    Ok(prev_header)
+}
+
+/// Removes every block and header above the genesis block. The chain tip is only moved once an import has succeeded,
+/// so any blocks above the genesis block were written by a failed import.
+fn discard_imported_blocks<B: BlockchainBackend>(db: &BlockchainDatabase<B>) -> Result<(), ChainStorageError> {
+    let mut height = db.fetch_last_header()?.height;
+    while height > 0 {
+        let mut txn = DbTransaction::new();
+        let batch_end = height.saturating_sub(DISCARD_BATCH_SIZE);
+        while height > batch_end {
+            let hash = db.fetch_chain_header(height)?.hash().clone();
+            txn.delete_block(hash);
+            txn.delete_header(height);
+            height -= 1;
+        }
+        db.write(txn)?;
+    }
+    debug!(target: LOG_TARGET, "Discarded the blocks of a failed snapshot import");
+    Ok(())
+}
+
+fn check_mmr_root(
+    mmr_tree: MmrTree,
+    at_height: u64,
+    expected: &HashOutput,
+    actual: HashOutput,
+) -> Result<(), SnapshotError> {
+    if actual != *expected {
+        return Err(SnapshotError::InvalidMmrRoot {
+            mmr_tree,
+            at_height,
+            expected_hex: expected.to_hex(),
+            actual_hex: actual.to_hex(),
+        });
+    }
+    Ok(())
+}
@@ -23,54 +23,63 @@
 use crate::{
     blocks::BlockHeader,
     consensus::ConsensusManager,
-    proof_of_work::{Difficulty, PowAlgorithm, TargetDifficultyWindow},
+    proof_of_work::{Difficulty, PowAlgorithm, PowError, TargetDifficultyWindow},
 };
+use std::collections::HashMap;
 
+/// The target difficulty windows of each PoW algorithm registered in the consensus constants
 #[derive(Debug, Clone)]
 pub struct TargetDifficulties {
-    monero: TargetDifficultyWindow,
-    sha3: TargetDifficultyWindow,
+    windows: HashMap<PowAlgorithm, TargetDifficultyWindow>,
 }
 
 impl TargetDifficulties {
     pub fn new(consensus_rules: &ConsensusManager, height: u64) -> Self {
-        Self {
-            monero: consensus_rules.new_target_difficulty(PowAlgorithm::Monero, height),
-            sha3: consensus_rules.new_target_difficulty(PowAlgorithm::Sha3, height),
-        }
+        let windows = consensus_rules
+            .consensus_constants(height)
+            .pow_algorithms()
+            .map(|algo| (algo, consensus_rules.new_target_difficulty(algo, height)))
+            .collect();
+        Self { windows }
     }
 
-    pub fn add_back(&mut self, header: &BlockHeader, target_difficulty: Difficulty) {
-        self.get_mut(header.pow_algo())
+    /// Adds the target difficulty of the header to the back of its algorithm's window. Returns
+    /// `PowError::UnsupportedAlgorithm` if the header was mined with an algorithm that is not registered.
+    pub fn add_back(&mut self, header: &BlockHeader, target_difficulty: Difficulty) -> Result<(), PowError> {
+        self.get_mut(header.pow_algo())?
             .add_back(header.timestamp(), target_difficulty);
+        Ok(())
     }
 
-    pub fn add_front(&mut self, header: &BlockHeader, target_difficulty: Difficulty) {
-        self.get_mut(header.pow_algo())
+    /// Adds the target difficulty of the header to the front of its algorithm's window. Returns
+    /// `PowError::UnsupportedAlgorithm` if the header was mined with an algorithm that is not registered.
+    pub fn add_front(&mut self, header: &BlockHeader, target_difficulty: Difficulty) -> Result<(), PowError> {
+        self.get_mut(header.pow_algo())?
             .add_front(header.timestamp(), target_difficulty);
+        Ok(())
     }
 
-    pub fn is_algo_full(&self, algo: PowAlgorithm) -> bool {
-        self.get(algo).is_full()
+    /// Returns true if the window for `algo` is full, or `PowError::UnsupportedAlgorithm` if `algo` is not
+    /// registered.
+    pub fn is_algo_full(&self, algo: PowAlgorithm) -> Result<bool, PowError> {
+        self.get(algo)
+            .map(|window| window.is_full())
+            .ok_or(PowError::UnsupportedAlgorithm(algo))
     }
 
     pub fn is_full(&self) -> bool {
-        self.sha3.is_full() && self.monero.is_full()
+        self.windows.values().all(|window| window.is_full())
     }
 
-    pub fn get(&self, algo: PowAlgorithm) -> &TargetDifficultyWindow {
-        use PowAlgorithm::*;
-        match algo {
-            Monero => &self.monero,
-            Sha3 => &self.sha3,
-        }
+    pub fn get(&self, algo: PowAlgorithm) -> Option<&TargetDifficultyWindow> {
+        self.windows.get(&algo)
     }
 
-    fn get_mut(&mut self, algo: PowAlgorithm) -> &mut TargetDifficultyWindow {
-        use PowAlgorithm::*;
-        match algo {
-            Monero => &mut self.monero,
-            Sha3 => &mut self.sha3,
-        }
+    fn get_mut(&mut self, algo: PowAlgorithm) -> Result<&mut TargetDifficultyWindow, PowError> {
+        self.windows.get_mut(&algo).ok_or(PowError::UnsupportedAlgorithm(algo))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&PowAlgorithm, &TargetDifficultyWindow)> {
+        self.windows.iter()
     }
 }
@@ -0,0 +1,150 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::Block,
+    chain_storage::{BlockchainBackend, BlockchainDatabase, ChainStorageError, DbTransaction, MemoryDatabase, MmrTree},
+    tari_utilities::Hashable,
+    test_helpers::{blockchain::create_new_memory_blockchain, create_block},
+    transactions::types::HashDigest,
+};
+use std::sync::Arc;
+use tari_test_utils::unpack_enum;
+
+fn add_many_chained_blocks(size: usize, db: &BlockchainDatabase<MemoryDatabase>) -> Vec<Arc<Block>> {
+    let mut prev_block = Arc::new(db.fetch_block(0).unwrap().try_into_block().unwrap());
+    let mut blocks = Vec::with_capacity(size);
+    for i in 1..=size as u64 {
+        let mut block = create_block(1, i, vec![]);
+        block.header.prev_hash = prev_block.hash().clone();
+        block.header.output_mmr_size = prev_block.header.output_mmr_size + block.body.outputs().len() as u64;
+        block.header.kernel_mmr_size = prev_block.header.kernel_mmr_size + block.body.kernels().len() as u64;
+        let block = Arc::new(block);
+        prev_block = block.clone();
+        db.add_block(block.clone()).unwrap().assert_added();
+        blocks.push(block);
+    }
+    blocks
+}
+
+#[test]
+fn it_inserts_the_genesis_block() {
+    let db = create_new_memory_blockchain();
+    assert_eq!(db.get_height().unwrap(), 0);
+    let genesis = db.fetch_block(0).unwrap();
+    assert_eq!(
+        db.fetch_mmr_size(MmrTree::Utxo).unwrap(),
+        genesis.block().body.outputs().len() as u64
+    );
+    assert_eq!(
+        db.fetch_mmr_size(MmrTree::Kernel).unwrap(),
+        genesis.block().body.kernels().len() as u64
+    );
+}
+
+#[test]
+fn it_adds_and_fetches_blocks() {
+    let db = create_new_memory_blockchain();
+    let blocks = add_many_chained_blocks(3, &db);
+    assert_eq!(db.get_height().unwrap(), 3);
+    let fetched = db.fetch_blocks(1..).unwrap();
+    assert_eq!(fetched.len(), 3);
+    for (block, fetched) in blocks.iter().zip(fetched.iter()) {
+        assert_eq!(block.hash(), fetched.block().hash());
+    }
+    let tip = db.fetch_tip_header().unwrap();
+    assert_eq!(tip.hash(), &blocks[2].hash());
+}
+
+#[test]
+fn it_rewinds_to_height() {
+    let db = create_new_memory_blockchain();
+    let blocks = add_many_chained_blocks(4, &db);
+    let removed = db.rewind_to_height(2).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert_eq!(db.get_height().unwrap(), 2);
+    assert_eq!(db.fetch_tip_header().unwrap().hash(), &blocks[1].hash());
+    assert!(db.fetch_header(3).unwrap().is_none());
+    // Rewound blocks are kept as orphans
+    assert_eq!(db.orphan_count().unwrap(), 2);
+    assert_eq!(db.fetch_orphan(blocks[3].hash()).unwrap().hash(), blocks[3].hash());
+}
+
+#[test]
+fn it_fetches_outputs_by_commitment_and_script_hash() {
+    let db = create_new_memory_blockchain();
+    let genesis = db.fetch_block(0).unwrap();
+    let output = genesis.block().body.outputs()[0].clone();
+    let found = db.fetch_outputs_by_commitment(output.commitment.clone()).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, output);
+    assert_eq!(found[0].2, 0);
+    let script_hash = output.script.as_hash::<HashDigest>().unwrap().to_vec();
+    let found = db.fetch_outputs_by_script_hash(script_hash).unwrap();
+    assert!(found.iter().any(|(o, _, _)| *o == output));
+    assert_eq!(db.fetch_output_spent_in_block(output.hash()).unwrap(), None);
+}
+
+#[test]
+fn it_does_not_apply_a_partially_failed_transaction() {
+    let mut db = MemoryDatabase::new();
+    let block = Arc::new(create_block(1, 1, vec![]));
+    let mut txn = DbTransaction::new();
+    txn.insert_orphan(block.clone());
+    // Deleting a header from an empty database fails, so the orphan must not be stored
+    txn.delete_header(0);
+    let err = db.write(txn).unwrap_err();
+    unpack_enum!(ChainStorageError::ValueNotFound { .. } = err);
+    assert_eq!(db.orphan_count().unwrap(), 0);
+
+    let mut txn = DbTransaction::new();
+    txn.insert_orphan(block.clone());
+    db.write(txn).unwrap();
+    assert_eq!(db.orphan_count().unwrap(), 1);
+    let mut txn = DbTransaction::new();
+    txn.insert_orphan(block);
+    let err = db.write(txn).unwrap_err();
+    unpack_enum!(ChainStorageError::InsertError { .. } = err);
+}
+
+#[test]
+fn it_rolls_back_every_change_of_a_failed_transaction() {
+    let db = create_new_memory_blockchain();
+    let blocks = add_many_chained_blocks(2, &db);
+    let metadata = db.get_chain_metadata().unwrap();
+
+    let mut txn = DbTransaction::new();
+    txn.delete_block(blocks[1].hash());
+    txn.set_best_block(1, blocks[0].hash(), 1);
+    // Only the last header may be deleted, so this operation fails after the others have been applied
+    txn.delete_header(0);
+    let err = db.write(txn).unwrap_err();
+    unpack_enum!(ChainStorageError::InvalidOperation(_s) = err);
+
+    assert_eq!(db.get_chain_metadata().unwrap(), metadata);
+    let tip = db.fetch_block(2).unwrap();
+    assert_eq!(tip.block().hash(), blocks[1].hash());
+    assert_eq!(
+        db.fetch_mmr_size(MmrTree::Utxo).unwrap(),
+        blocks[1].header.output_mmr_size
+    );
+}
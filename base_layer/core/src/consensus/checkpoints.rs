@@ -0,0 +1,92 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Checkpoints that are hardcoded for the public networks. A checkpoint is the header hash that the main chain must
+//! contain at a given height, so a node will not sync or reorg to a chain that forks below the last checkpoint. The
+//! genesis block is always a checkpoint and is added by the [ConsensusManagerBuilder](super::ConsensusManagerBuilder).
+//!
+//! Checkpoints are taken from the main chain of a fully synced node (the `list-headers` command of the base node prints
+//! the header hash at a height) and must be buried well below the reorg depth that can be expected on the network.
+//! Until a network has entries in its table, only its genesis block is protected.
+
+use tari_common::configuration::Network;
+use tari_common_types::types::BlockHash;
+use tari_crypto::tari_utilities::hex::from_hex;
+
+/// Mainnet has not been launched, so there are no checkpoints past the genesis block
+const MAINNET_CHECKPOINTS: &[(u64, &str)] = &[];
+
+/// Checkpoints of the Weatherwax testnet, as (height, header hash) pairs in ascending height order
+const WEATHERWAX_CHECKPOINTS: &[(u64, &str)] = &[];
+
+/// Returns the hardcoded checkpoints of the given network. Retired testnets and LocalNet have none.
+pub(super) fn hardcoded_checkpoints(network: Network) -> Vec<(u64, BlockHash)> {
+    checkpoints_for(network)
+        .iter()
+        .map(|(height, hash)| {
+            let hash = from_hex(hash).expect("Hardcoded checkpoint hashes are valid hex");
+            (*height, hash)
+        })
+        .collect()
+}
+
+fn checkpoints_for(network: Network) -> &'static [(u64, &'static str)] {
+    match network {
+        Network::MainNet => MAINNET_CHECKPOINTS,
+        Network::Weatherwax => WEATHERWAX_CHECKPOINTS,
+        Network::Ridcully | Network::Stibbons | Network::LocalNet => &[],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tari_common_types::types::BLOCK_HASH_LENGTH;
+
+    #[test]
+    fn hardcoded_checkpoints_are_valid() {
+        for network in &[
+            Network::MainNet,
+            Network::Ridcully,
+            Network::Stibbons,
+            Network::Weatherwax,
+            Network::LocalNet,
+        ] {
+            let checkpoints = checkpoints_for(*network);
+            assert!(
+                checkpoints.windows(2).all(|w| w[0].0 < w[1].0),
+                "Checkpoints of {} are not in ascending height order",
+                network
+            );
+            for (height, hash) in checkpoints {
+                assert!(*height > 0, "The genesis block checkpoint is added by the builder");
+                let hash = from_hex(hash).unwrap();
+                assert_eq!(
+                    hash.len(),
+                    BLOCK_HASH_LENGTH,
+                    "Invalid checkpoint hash at height {}",
+                    height
+                );
+            }
+        }
+    }
+}
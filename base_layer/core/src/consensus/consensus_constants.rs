@@ -26,12 +26,13 @@ use crate::{
     transactions::tari_amount::{uT, MicroTari, T},
 };
 use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, ops::Add};
 use tari_common::configuration::Network;
 use tari_crypto::tari_utilities::epoch_time::EpochTime;
 
 /// This is the inner struct used to control all consensus values.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsensusConstants {
     /// The height at which these constants become effective
     effective_from_height: u64,
@@ -52,7 +53,7 @@ pub struct ConsensusConstants {
     /// This is the initial emission curve amount
     pub(in crate::consensus) emission_initial: MicroTari,
     /// This is the emission curve delay for the int
-    pub(in crate::consensus) emission_decay: &'static [u64],
+    pub(in crate::consensus) emission_decay: Vec<u64>,
     /// This is the emission curve tail amount
     pub(in crate::consensus) emission_tail: MicroTari,
     /// This is the maximum age a monero merge mined seed can be reused
@@ -65,7 +66,7 @@ pub struct ConsensusConstants {
 }
 
 /// This is just a convenience  wrapper to put all the info into a hashmap per diff algo
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PowAlgorithmConstants {
     /// NB this is very important to set this as 6 * the target time
     pub max_target_time: u64,
@@ -86,7 +87,7 @@ impl ConsensusConstants {
 
     /// This gets the emission curve values as (initial, decay, tail)
     pub fn emission_amounts(&self) -> (MicroTari, &[u64], MicroTari) {
-        (self.emission_initial, self.emission_decay, self.emission_tail)
+        (self.emission_initial, &self.emission_decay, self.emission_tail)
     }
 
     /// The min height maturity a coinbase utxo must have.
@@ -135,6 +136,11 @@ impl ConsensusConstants {
         self.proof_of_work.len() as u64
     }
 
+    /// The PoW algorithms that blocks may be mined with under these constants
+    pub fn pow_algorithms(&self) -> impl Iterator<Item = PowAlgorithm> + '_ {
+        self.proof_of_work.keys().copied()
+    }
+
     /// The target time used by the difficulty adjustment algorithms, their target time is the target block interval /
     /// algo block percentage
     pub fn get_diff_target_block_interval(&self, pow_algo: PowAlgorithm) -> u64 {
@@ -207,7 +213,7 @@ impl ConsensusConstants {
             max_block_transaction_weight: 19500,
             median_timestamp_count: 11,
             emission_initial: 5_538_846_115 * uT,
-            emission_decay: &EMISSION_DECAY,
+            emission_decay: EMISSION_DECAY.to_vec(),
             emission_tail: 100.into(),
             max_randomx_seed_height: std::u64::MAX,
             proof_of_work: algos,
@@ -240,7 +246,7 @@ impl ConsensusConstants {
             max_block_transaction_weight: 19500,
             median_timestamp_count: 11,
             emission_initial: 5_538_846_115 * uT,
-            emission_decay: &EMISSION_DECAY,
+            emission_decay: EMISSION_DECAY.to_vec(),
             emission_tail: 100.into(),
             max_randomx_seed_height: std::u64::MAX,
             proof_of_work: algos,
@@ -300,7 +306,7 @@ impl ConsensusConstants {
                 max_block_transaction_weight: 19500,
                 median_timestamp_count: 11,
                 emission_initial: 5_538_846_115 * uT,
-                emission_decay: &EMISSION_DECAY,
+                emission_decay: EMISSION_DECAY.to_vec(),
                 emission_tail: 100.into(),
                 max_randomx_seed_height: std::u64::MAX,
                 proof_of_work: algos,
@@ -315,7 +321,7 @@ impl ConsensusConstants {
                 max_block_transaction_weight: 19500,
                 median_timestamp_count: 11,
                 emission_initial: 5_538_846_115 * uT,
-                emission_decay: &EMISSION_DECAY,
+                emission_decay: EMISSION_DECAY.to_vec(),
                 emission_tail: 100.into(),
                 max_randomx_seed_height: std::u64::MAX,
                 proof_of_work: algos2,
@@ -348,7 +354,7 @@ impl ConsensusConstants {
             max_block_transaction_weight: 19500,
             median_timestamp_count: 11,
             emission_initial: 5_538_846_115 * uT,
-            emission_decay: &EMISSION_DECAY,
+            emission_decay: EMISSION_DECAY.to_vec(),
             emission_tail: 100.into(),
             max_randomx_seed_height: std::u64::MAX,
             proof_of_work: algos,
@@ -381,7 +387,7 @@ impl ConsensusConstants {
             max_block_transaction_weight: 19500,
             median_timestamp_count: 11,
             emission_initial: 10_000_000.into(),
-            emission_decay: &EMISSION_DECAY,
+            emission_decay: EMISSION_DECAY.to_vec(),
             emission_tail: 100.into(),
             max_randomx_seed_height: std::u64::MAX,
             proof_of_work: algos,
@@ -443,14 +449,9 @@ impl ConsensusConstantsBuilder {
         self
     }
 
-    pub fn with_emission_amounts(
-        mut self,
-        intial_amount: MicroTari,
-        decay: &'static [u64],
-        tail_amount: MicroTari,
-    ) -> Self {
+    pub fn with_emission_amounts(mut self, intial_amount: MicroTari, decay: &[u64], tail_amount: MicroTari) -> Self {
         self.consensus.emission_initial = intial_amount;
-        self.consensus.emission_decay = decay;
+        self.consensus.emission_decay = decay.to_vec();
         self.consensus.emission_tail = tail_amount;
         self
     }
@@ -0,0 +1,141 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::{Display, Error, Formatter};
+
+/// The low bits of a header version carry the blockchain version of the consensus rules the block was built for.
+pub const HEADER_BASE_VERSION_MASK: u16 = 0x00ff;
+/// The high bits of a header version are used by miners to signal readiness for version-bit activated features.
+pub const HEADER_VERSION_SIGNAL_SHIFT: u16 = 8;
+/// The number of signalling bits available in a header version
+pub const HEADER_VERSION_SIGNAL_BITS: u8 = 8;
+
+/// Named consensus changes that can be activated on a network without replacing the consensus constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ConsensusFeature {
+    /// The base version of a header must equal the blockchain version of the consensus constants at its height and
+    /// only the signalling bits of configured activations may be set.
+    StrictHeaderVersion,
+    /// Kernels in a block may not have a lock height greater than the height of the block.
+    KernelLockHeight,
+}
+
+impl Display for ConsensusFeature {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        use ConsensusFeature::*;
+        match self {
+            StrictHeaderVersion => f.write_str("StrictHeaderVersion"),
+            KernelLockHeight => f.write_str("KernelLockHeight"),
+        }
+    }
+}
+
+/// The activation state of a [ConsensusFeature] for the blocks in a signalling window. The state of a version bit
+/// activated feature only changes at window boundaries and is derived from the headers of the previous windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureState {
+    /// The start height of the signalling has not been reached
+    Defined,
+    /// Blocks signal for the feature and the signals in every window are counted
+    Started,
+    /// The signalling threshold was reached in the previous window, the feature is active from the next window
+    LockedIn,
+    /// The feature is active for all blocks
+    Active,
+}
+
+/// Describes when a [ConsensusFeature] becomes active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureActivation {
+    /// The feature is active for all blocks at or above the given height.
+    Height(u64),
+    /// The feature is activated by miners signalling a version bit.
+    VersionBit(VersionBitActivation),
+}
+
+impl FeatureActivation {
+    /// Returns the header version mask of the signalling bit, if this is a version bit activation
+    pub fn signal_mask(&self) -> Option<u16> {
+        match self {
+            FeatureActivation::Height(_) => None,
+            FeatureActivation::VersionBit(activation) => Some(activation.signal_mask()),
+        }
+    }
+
+    /// Returns the header version mask of the signalling bit if blocks at the given height may signal this feature
+    pub fn signal_mask_at(&self, height: u64) -> Option<u16> {
+        match self {
+            FeatureActivation::Height(_) => None,
+            FeatureActivation::VersionBit(activation) if height < activation.start_height => None,
+            FeatureActivation::VersionBit(activation) => Some(activation.signal_mask()),
+        }
+    }
+
+    /// Returns true if the feature is active for every block at the given height, regardless of signalling
+    pub fn is_active_at(&self, height: u64) -> bool {
+        match self {
+            FeatureActivation::Height(activation_height) => height >= *activation_height,
+            FeatureActivation::VersionBit(activation) => activation.mandatory_height.map_or(false, |h| height >= h),
+        }
+    }
+}
+
+/// The parameters of a version bit activation. The chain is split into windows of `window` blocks, starting at height
+/// 0. Signals are counted from the first window that starts at or after `start_height`. Once `threshold` blocks in a
+/// window signal the feature it is locked in, and it becomes active for all blocks one window later. From
+/// `mandatory_height`, if set, the feature is active for all blocks regardless of signalling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionBitActivation {
+    pub bit: u8,
+    pub start_height: u64,
+    pub window: u64,
+    pub threshold: u64,
+    pub mandatory_height: Option<u64>,
+}
+
+impl VersionBitActivation {
+    /// Returns the header version mask of the signalling bit
+    pub fn signal_mask(&self) -> u16 {
+        1 << (HEADER_VERSION_SIGNAL_SHIFT + u16::from(self.bit))
+    }
+
+    /// Returns true if the header version signals this feature
+    pub fn is_signalled_by(&self, version: u16) -> bool {
+        version & self.signal_mask() != 0
+    }
+
+    /// Returns the height of the first block of the signalling window that contains the given height
+    pub fn window_start(&self, height: u64) -> u64 {
+        height - height % self.window
+    }
+
+    /// Returns the state of the feature for the signalling window starting at `window_start`. The state of the window
+    /// before it is passed as `previous`, along with the number of blocks in that window that signalled the feature.
+    pub fn next_state(&self, window_start: u64, previous: FeatureState, signals: u64) -> FeatureState {
+        match previous {
+            FeatureState::Defined if window_start >= self.start_height => FeatureState::Started,
+            FeatureState::Started if signals >= self.threshold => FeatureState::LockedIn,
+            FeatureState::LockedIn | FeatureState::Active => FeatureState::Active,
+            state => state,
+        }
+    }
+}
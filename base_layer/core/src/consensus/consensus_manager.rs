@@ -30,20 +30,33 @@ use crate::{
         },
         Block,
     },
-    chain_storage::{ChainBlock, ChainStorageError},
+    chain_storage::{BlockchainBackend, ChainBlock, ChainHeader, ChainStorageError},
     consensus::{
         chain_strength_comparer::{strongest_chain, ChainStrengthComparer},
+        checkpoints::hardcoded_checkpoints,
+        consensus_features::{ConsensusFeature, FeatureActivation, FeatureState, HEADER_VERSION_SIGNAL_BITS},
         emission::{Emission, EmissionSchedule},
         ConsensusConstants,
         NetworkConsensus,
+        NetworkDefinition,
     },
     proof_of_work::{DifficultyAdjustmentError, PowAlgorithm, TargetDifficultyWindow},
     transactions::tari_amount::MicroTari,
 };
-use std::{convert::TryFrom, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    convert::TryFrom,
+    sync::{Arc, RwLock},
+};
 use tari_common::configuration::Network;
+use tari_common_types::types::BlockHash;
+use tari_crypto::tari_utilities::hash::Hashable;
 use thiserror::Error;
 
+/// The maximum number of signalling window states that are cached. Every fork adds the states of its own windows, so
+/// the cache is bounded and the oldest states are recalculated from the headers if they are needed again.
+const MAX_CACHED_FEATURE_STATES: usize = 1_000;
+
 #[derive(Debug, Error)]
 #[allow(clippy::large_enum_variant)]
 pub enum ConsensusManagerError {
@@ -57,6 +70,8 @@ pub enum ConsensusManagerError {
     PoisonedAccess(String),
     #[error("No Difficulty adjustment manager present")]
     MissingDifficultyAdjustmentManager,
+    #[error("Invalid activation for consensus feature {feature}: {details}")]
+    InvalidFeatureActivation { feature: ConsensusFeature, details: String },
 }
 
 /// Container struct for consensus rules. This can be cheaply cloned.
@@ -141,6 +156,156 @@ impl ConsensusManager {
     pub fn network(&self) -> NetworkConsensus {
         self.inner.network
     }
+
+    /// Returns all checkpoints (height -> header hash) that the main chain must contain. The genesis block is always
+    /// the checkpoint at height 0.
+    pub fn checkpoints(&self) -> &BTreeMap<u64, BlockHash> {
+        &self.inner.checkpoints
+    }
+
+    /// Returns the header hash that the main chain must contain at the given height, if a checkpoint is set
+    pub fn checkpoint_at(&self, height: u64) -> Option<&BlockHash> {
+        self.inner.checkpoints.get(&height)
+    }
+
+    /// Returns the height of the highest checkpoint at or below the given height
+    pub fn last_checkpoint_height(&self, height: u64) -> Option<u64> {
+        self.inner.checkpoints.range(..=height).next_back().map(|(h, _)| *h)
+    }
+
+    /// Returns the trusted header hash, if set. Range proofs and signatures of blocks buried below this block are not
+    /// verified during sync.
+    pub fn assume_valid_hash(&self) -> Option<&BlockHash> {
+        self.inner.assume_valid_hash.as_ref()
+    }
+
+    /// Returns the activation configured for the given feature, if any
+    pub fn feature_activation(&self, feature: ConsensusFeature) -> Option<&FeatureActivation> {
+        self.inner.features.get(&feature)
+    }
+
+    /// Returns true if the feature is active for every block at the given height. Features activated by version bit
+    /// signalling are only active here once their mandatory height is reached, use
+    /// [is_feature_active_on_chain](Self::is_feature_active_on_chain) to take the signalling into account.
+    pub fn is_feature_active(&self, feature: ConsensusFeature, height: u64) -> bool {
+        self.feature_activation(feature)
+            .map_or(false, |activation| activation.is_active_at(height))
+    }
+
+    /// Returns true if the feature is active for a block at the given height whose parent is `prev_hash`. The parent
+    /// may be on the main chain or on a fork.
+    pub fn is_feature_active_on_chain<B: BlockchainBackend>(
+        &self,
+        feature: ConsensusFeature,
+        height: u64,
+        prev_hash: &BlockHash,
+        db: &B,
+    ) -> Result<bool, ChainStorageError> {
+        if self.is_feature_active(feature, height) {
+            return Ok(true);
+        }
+        Ok(self.feature_state(feature, height, prev_hash, db)? == FeatureState::Active)
+    }
+
+    /// Returns the activation state of the feature for a block at the given height whose parent is `prev_hash`. The
+    /// state of a version bit activation is derived from the signals in the headers of the windows before the block,
+    /// walking back from `prev_hash` so that a fork is judged by its own headers. The state of each window is cached by
+    /// the hash of the last header before the window, so the headers of a window are only counted once for every
+    /// chain.
+    pub fn feature_state<B: BlockchainBackend>(
+        &self,
+        feature: ConsensusFeature,
+        height: u64,
+        prev_hash: &BlockHash,
+        db: &B,
+    ) -> Result<FeatureState, ChainStorageError> {
+        let activation = match self.feature_activation(feature) {
+            Some(FeatureActivation::VersionBit(activation)) => activation,
+            Some(FeatureActivation::Height(activation_height)) if height >= *activation_height => {
+                return Ok(FeatureState::Active)
+            },
+            Some(FeatureActivation::Height(_)) | None => return Ok(FeatureState::Defined),
+        };
+
+        // Walk back along the chain of the block to the most recent window with a known state, counting the signals
+        // in each window on the way
+        let mut window_start = activation.window_start(height);
+        let mut header: Option<ChainHeader> = None;
+        let mut pending = Vec::new();
+        let mut state = loop {
+            if window_start < activation.start_height {
+                break FeatureState::Defined;
+            }
+            if window_start == 0 {
+                break FeatureState::Started;
+            }
+            let mut current = match header.take() {
+                Some(header) => header,
+                None => db.fetch_chain_header_in_all_chains(prev_hash)?,
+            };
+            while current.height() >= window_start {
+                current = db.fetch_chain_header_in_all_chains(&current.header().prev_hash)?;
+            }
+            let hash = current.hash().clone();
+            if let Some(state) = self.cached_feature_state(feature, &hash)? {
+                break state;
+            }
+
+            let prev_window_start = window_start - activation.window;
+            let mut signals = 0;
+            loop {
+                if activation.is_signalled_by(current.header().version) {
+                    signals += 1;
+                }
+                if current.height() == prev_window_start {
+                    break;
+                }
+                current = db.fetch_chain_header_in_all_chains(&current.header().prev_hash)?;
+            }
+            pending.push((window_start, hash, signals));
+            header = Some(current);
+            window_start = prev_window_start;
+        };
+
+        for (window_start, hash, signals) in pending.into_iter().rev() {
+            let signals = if state == FeatureState::Started { signals } else { 0 };
+            state = activation.next_state(window_start, state, signals);
+            self.inner
+                .feature_states
+                .write()
+                .map_err(|_| ChainStorageError::AccessError("Write lock on feature states failed".into()))?
+                .insert(feature, hash, state);
+        }
+        Ok(state)
+    }
+
+    fn cached_feature_state(
+        &self,
+        feature: ConsensusFeature,
+        hash: &BlockHash,
+    ) -> Result<Option<FeatureState>, ChainStorageError> {
+        let feature_states = self
+            .inner
+            .feature_states
+            .read()
+            .map_err(|_| ChainStorageError::AccessError("Read lock on feature states failed".into()))?;
+        Ok(feature_states.get(feature, hash))
+    }
+
+    /// Returns the header version bits that blocks at the given height may set to signal configured features
+    pub fn signal_bits(&self, height: u64) -> u16 {
+        self.inner
+            .features
+            .values()
+            .filter_map(|activation| activation.signal_mask_at(height))
+            .fold(0, |bits, mask| bits | mask)
+    }
+
+    /// Returns the header version for a new block at the given height. It signals every configured feature that can
+    /// be signalled at that height.
+    pub fn header_version(&self, height: u64) -> u16 {
+        self.consensus_constants(height).blockchain_version() | self.signal_bits(height)
+    }
 }
 
 /// This is the used to control all consensus values.
@@ -156,6 +321,41 @@ struct ConsensusManagerInner {
     pub gen_block: Option<ChainBlock>,
     /// The comparer used to determine which chain is stronger for reorgs.
     pub chain_strength_comparer: Box<dyn ChainStrengthComparer + Send + Sync>,
+    /// Header hashes that the main chain must contain at the given heights
+    pub checkpoints: BTreeMap<u64, BlockHash>,
+    /// Blocks that are ancestors of this trusted block skip range proof and signature verification
+    pub assume_valid_hash: Option<BlockHash>,
+    /// Consensus features and the heights or version bits that activate them
+    pub features: BTreeMap<ConsensusFeature, FeatureActivation>,
+    /// The states of version bit activated features by the hash of the header before the signalling window
+    pub feature_states: RwLock<FeatureStateCache>,
+}
+
+/// The activation states of version bit activated features, keyed by the hash of the header before the signalling
+/// window. Holds at most `MAX_CACHED_FEATURE_STATES` states and evicts the oldest first.
+#[derive(Debug, Default)]
+struct FeatureStateCache {
+    states: HashMap<(ConsensusFeature, BlockHash), FeatureState>,
+    insertion_order: VecDeque<(ConsensusFeature, BlockHash)>,
+}
+
+impl FeatureStateCache {
+    fn get(&self, feature: ConsensusFeature, hash: &BlockHash) -> Option<FeatureState> {
+        self.states.get(&(feature, hash.clone())).copied()
+    }
+
+    fn insert(&mut self, feature: ConsensusFeature, hash: BlockHash, state: FeatureState) {
+        let key = (feature, hash);
+        if self.states.insert(key.clone(), state).is_some() {
+            return;
+        }
+        self.insertion_order.push_back(key);
+        if self.insertion_order.len() > MAX_CACHED_FEATURE_STATES {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.states.remove(&oldest);
+            }
+        }
+    }
 }
 
 /// Constructor for the consensus manager struct
@@ -164,6 +364,9 @@ pub struct ConsensusManagerBuilder {
     network: NetworkConsensus,
     gen_block: Option<ChainBlock>,
     chain_strength_comparer: Option<Box<dyn ChainStrengthComparer + Send + Sync>>,
+    checkpoints: BTreeMap<u64, BlockHash>,
+    assume_valid_hash: Option<BlockHash>,
+    features: BTreeMap<ConsensusFeature, FeatureActivation>,
 }
 
 impl ConsensusManagerBuilder {
@@ -174,6 +377,9 @@ impl ConsensusManagerBuilder {
             network: network.into(),
             gen_block: None,
             chain_strength_comparer: None,
+            checkpoints: BTreeMap::new(),
+            assume_valid_hash: None,
+            features: BTreeMap::new(),
         }
     }
 
@@ -189,6 +395,67 @@ impl ConsensusManagerBuilder {
         self
     }
 
+    /// Uses the consensus constants and genesis block of a custom network definition. Custom networks run as
+    /// [Network::LocalNet], the genesis block is ignored for any other network.
+    pub fn with_network_definition(mut self, definition: NetworkDefinition) -> Self {
+        self.gen_block = Some(definition.genesis_chain_block());
+        self.consensus_constants = definition.consensus_constants;
+        self
+    }
+
+    /// Adds a checkpoint. Chains that do not contain the given header hash at the given height are rejected. The
+    /// checkpoint at height 0 is always the genesis block of the network.
+    pub fn with_checkpoint(mut self, height: u64, hash: BlockHash) -> Self {
+        self.checkpoints.insert(height, hash);
+        self
+    }
+
+    /// Adds multiple checkpoints, see [with_checkpoint](Self::with_checkpoint)
+    pub fn with_checkpoints<I: IntoIterator<Item = (u64, BlockHash)>>(mut self, checkpoints: I) -> Self {
+        self.checkpoints.extend(checkpoints);
+        self
+    }
+
+    /// Sets a trusted header hash. Range proofs and signatures are not verified for blocks that are ancestors of this
+    /// block on the main chain.
+    pub fn with_assume_valid_hash(mut self, hash: BlockHash) -> Self {
+        self.assume_valid_hash = Some(hash);
+        self
+    }
+
+    /// Activates a consensus feature. Version bit activations must use a bit that is not used by another feature, and
+    /// a threshold that can be reached within the signalling window.
+    pub fn with_feature_activation(
+        mut self,
+        feature: ConsensusFeature,
+        activation: FeatureActivation,
+    ) -> Result<Self, ConsensusManagerError> {
+        if let FeatureActivation::VersionBit(version_bit) = activation {
+            let invalid = |details: String| ConsensusManagerError::InvalidFeatureActivation { feature, details };
+            if version_bit.bit >= HEADER_VERSION_SIGNAL_BITS {
+                return Err(invalid(format!("version bit {} is out of range", version_bit.bit)));
+            }
+            if self
+                .features
+                .iter()
+                .any(|(f, a)| *f != feature && a.signal_mask() == activation.signal_mask())
+            {
+                return Err(invalid(format!("version bit {} is already in use", version_bit.bit)));
+            }
+            if version_bit.window == 0 {
+                return Err(invalid("the signalling window is empty".to_string()));
+            }
+            if version_bit.threshold == 0 || version_bit.threshold > version_bit.window {
+                return Err(invalid(format!(
+                    "threshold {} must be between 1 and the window size {}",
+                    version_bit.threshold, version_bit.window
+                )));
+            }
+        }
+        self.features.insert(feature, activation);
+        Ok(self)
+    }
+
     pub fn on_ties(mut self, chain_strength_comparer: Box<dyn ChainStrengthComparer + Send + Sync>) -> Self {
         self.chain_strength_comparer = Some(chain_strength_comparer);
         self
@@ -206,11 +473,33 @@ impl ConsensusManagerBuilder {
             &self.consensus_constants[0].emission_decay,
             self.consensus_constants[0].emission_tail,
         );
+        // Configured checkpoints take precedence over the hardcoded checkpoints of the network
+        let mut checkpoints = hardcoded_checkpoints(self.network.as_network())
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        checkpoints.extend(self.checkpoints);
+        let genesis_hash = match self.network.as_network() {
+            Network::MainNet => get_mainnet_genesis_block().hash().clone(),
+            Network::Ridcully => get_ridcully_genesis_block().hash().clone(),
+            Network::Stibbons => get_stibbons_genesis_block().hash().clone(),
+            Network::Weatherwax => get_weatherwax_genesis_block().hash().clone(),
+            Network::LocalNet => self
+                .gen_block
+                .as_ref()
+                .map(|b| b.hash().clone())
+                .unwrap_or_else(|| get_weatherwax_genesis_block().hash().clone()),
+        };
+        checkpoints.insert(0, genesis_hash);
+
         let inner = ConsensusManagerInner {
             consensus_constants: self.consensus_constants,
             network: self.network,
             emission,
             gen_block: self.gen_block,
+            checkpoints,
+            assume_valid_hash: self.assume_valid_hash,
+            features: self.features,
+            feature_states: RwLock::new(FeatureStateCache::default()),
             chain_strength_comparer: self.chain_strength_comparer.unwrap_or_else(|| {
                 strongest_chain()
                     .by_accumulated_difficulty()
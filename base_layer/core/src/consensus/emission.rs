@@ -36,7 +36,7 @@ pub trait Emission {
 #[derive(Debug, Clone)]
 pub struct EmissionSchedule {
     initial: MicroTari,
-    decay: &'static [u64],
+    decay: Vec<u64>,
     tail: MicroTari,
 }
 
@@ -65,12 +65,16 @@ impl EmissionSchedule {
     ///
     /// The shift right operation will overflow if shifting more than 63 bits. `new` will panic if any of the decay
     /// values are greater than or equal to 64.
-    pub fn new(initial: MicroTari, decay: &'static [u64], tail: MicroTari) -> EmissionSchedule {
+    pub fn new(initial: MicroTari, decay: &[u64], tail: MicroTari) -> EmissionSchedule {
         assert!(
             decay.iter().all(|i| *i < 64),
             "Decay value would overflow. All `decay` values must be less than 64"
         );
-        EmissionSchedule { initial, decay, tail }
+        EmissionSchedule {
+            initial,
+            decay: decay.to_vec(),
+            tail,
+        }
     }
 
     /// Return an iterator over the block reward and total supply. This is the most efficient way to iterate through
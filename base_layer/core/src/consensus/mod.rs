@@ -22,14 +22,20 @@
 
 #[cfg(feature = "base_node")]
 pub(crate) mod chain_strength_comparer;
+#[cfg(feature = "base_node")]
+mod checkpoints;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub mod consensus_constants;
 #[cfg(feature = "base_node")]
+mod consensus_features;
+#[cfg(feature = "base_node")]
 mod consensus_manager;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub mod emission;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 mod network;
+#[cfg(feature = "base_node")]
+mod network_definition;
 
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub const WEIGHT_PER_INPUT: u64 = 1;
@@ -41,6 +47,18 @@ pub const KERNEL_WEIGHT: u64 = 3; // Constant weight per transaction; covers ker
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use consensus_constants::{ConsensusConstants, ConsensusConstantsBuilder};
 #[cfg(feature = "base_node")]
+pub use consensus_features::{
+    ConsensusFeature,
+    FeatureActivation,
+    FeatureState,
+    VersionBitActivation,
+    HEADER_BASE_VERSION_MASK,
+    HEADER_VERSION_SIGNAL_BITS,
+    HEADER_VERSION_SIGNAL_SHIFT,
+};
+#[cfg(feature = "base_node")]
 pub use consensus_manager::{ConsensusManager, ConsensusManagerBuilder, ConsensusManagerError};
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use network::NetworkConsensus;
+#[cfg(feature = "base_node")]
+pub use network_definition::{NetworkDefinition, NetworkDefinitionError};
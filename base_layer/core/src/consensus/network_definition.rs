@@ -0,0 +1,254 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::Block,
+    chain_storage::{BlockHeaderAccumulatedData, ChainBlock},
+    consensus::{ConsensusConstants, ConsensusManager},
+    transactions::types::CryptoFactories,
+    validation::block_validators::validate_genesis_block,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path, sync::Arc};
+use tari_common::configuration::{CustomNetwork, Network};
+use tari_crypto::tari_utilities::Hashable;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NetworkDefinitionError {
+    #[error("Could not access network definition file: {0}")]
+    IoError(#[from] io::Error),
+    #[error("Could not parse network definition: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("Invalid network definition: {0}")]
+    InvalidDefinition(String),
+}
+
+/// The definition of a private network that is loaded from a file instead of being hardcoded. Custom networks run
+/// with the [LocalNet](tari_common::configuration::Network::LocalNet) configuration and the consensus constants and
+/// genesis block given here. Their name, network byte and peer seeds form the
+/// [CustomNetwork](tari_common::configuration::CustomNetwork) identity that keeps them apart from LocalNet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkDefinition {
+    /// The name of the network, which is also the name of its data directory
+    pub name: String,
+    /// The byte that identifies the network in the p2p wire protocol. It must differ from those of the hardcoded
+    /// networks.
+    pub network_byte: u8,
+    /// The peer seeds of the network
+    #[serde(default)]
+    pub peer_seeds: Vec<String>,
+    /// The consensus constants of the network, ordered by the height from which they are effective
+    pub consensus_constants: Vec<ConsensusConstants>,
+    /// The genesis block of the network
+    pub genesis_block: Block,
+}
+
+impl NetworkDefinition {
+    pub fn new(network: CustomNetwork, consensus_constants: Vec<ConsensusConstants>, genesis_block: Block) -> Self {
+        Self {
+            name: network.name,
+            network_byte: network.network_byte,
+            peer_seeds: network.peer_seeds,
+            consensus_constants,
+            genesis_block,
+        }
+    }
+
+    /// Returns the identity of the network
+    pub fn custom_network(&self) -> Result<CustomNetwork, NetworkDefinitionError> {
+        CustomNetwork::new(self.name.clone(), self.network_byte, self.peer_seeds.clone())
+            .map_err(|e| invalid(&e.to_string()))
+    }
+
+    /// Loads and validates a network definition from a JSON file
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, NetworkDefinitionError> {
+        let contents = fs::read_to_string(path)?;
+        let definition = serde_json::from_str::<Self>(&contents)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Writes the network definition to a JSON file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), NetworkDefinitionError> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Checks that the identity of the network is valid, that the consensus constants are ordered and usable and that
+    /// the genesis block is valid for them
+    pub fn validate(&self) -> Result<(), NetworkDefinitionError> {
+        self.custom_network()?;
+        let first = self
+            .consensus_constants
+            .first()
+            .ok_or_else(|| invalid("at least one set of consensus constants is required"))?;
+        if first.effective_from_height() != 0 {
+            return Err(invalid("the first consensus constants must be effective from height 0"));
+        }
+        let is_ordered = self
+            .consensus_constants
+            .windows(2)
+            .all(|w| w[0].effective_from_height() < w[1].effective_from_height());
+        if !is_ordered {
+            return Err(invalid(
+                "consensus constants must be ordered by strictly increasing effective height",
+            ));
+        }
+        for constants in &self.consensus_constants {
+            if constants.get_pow_algo_count() == 0 {
+                return Err(invalid(&format!(
+                    "no proof of work algorithms are configured from height {}",
+                    constants.effective_from_height()
+                )));
+            }
+            if constants.get_difficulty_block_window() == 0 {
+                return Err(invalid(&format!(
+                    "the difficulty block window from height {} must be greater than 0",
+                    constants.effective_from_height()
+                )));
+            }
+            let (_, decay, _) = constants.emission_amounts();
+            if decay.iter().any(|d| *d >= 64) {
+                return Err(invalid(&format!(
+                    "all emission decay values from height {} must be less than 64",
+                    constants.effective_from_height()
+                )));
+            }
+        }
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .with_network_definition(self.clone())
+            .build();
+        validate_genesis_block(&self.genesis_block, &rules, &CryptoFactories::default())
+            .map_err(|e| invalid(&format!("the genesis block is invalid: {}", e)))?;
+        Ok(())
+    }
+
+    /// Returns the genesis block of the network along with its accumulated data
+    pub fn genesis_chain_block(&self) -> ChainBlock {
+        let block = self.genesis_block.clone();
+        let accumulated_data = BlockHeaderAccumulatedData {
+            hash: block.hash(),
+            total_kernel_offset: block.header.total_kernel_offset.clone(),
+            achieved_difficulty: 1.into(),
+            total_accumulated_difficulty: 1,
+            accumulated_monero_difficulty: 1.into(),
+            accumulated_sha_difficulty: 1.into(),
+            target_difficulty: 1.into(),
+        };
+        // NOTE: Panic is impossible, accumulated_data is created from the block
+        ChainBlock::try_construct(Arc::new(block), accumulated_data).unwrap()
+    }
+}
+
+fn invalid(reason: &str) -> NetworkDefinitionError {
+    NetworkDefinitionError::InvalidDefinition(reason.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        blocks::genesis_generator::generate_genesis_block,
+        consensus::ConsensusConstantsBuilder,
+        transactions::types::PrivateKey,
+    };
+    use rand::rngs::OsRng;
+    use tari_crypto::{keys::SecretKey, tari_utilities::epoch_time::EpochTime};
+    use tempfile::tempdir;
+
+    fn create_genesis_block(constants: &ConsensusConstants) -> Block {
+        generate_genesis_block(
+            constants,
+            PrivateKey::random(&mut OsRng),
+            constants.emission_amounts().0,
+            EpochTime::now(),
+            &CryptoFactories::default(),
+        )
+        .unwrap()
+    }
+
+    fn staging_network() -> CustomNetwork {
+        CustomNetwork::new("staging".to_string(), 0x7f, vec![]).unwrap()
+    }
+
+    #[test]
+    fn save_and_load_network_definition() {
+        let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+            .with_coinbase_lockheight(5)
+            .build();
+        let genesis = create_genesis_block(&constants);
+        let definition = NetworkDefinition::new(staging_network(), vec![constants], genesis.clone());
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("staging.json");
+        definition.save_to_file(&path).unwrap();
+
+        let loaded = NetworkDefinition::load_from_file(&path).unwrap();
+        assert_eq!(loaded.custom_network().unwrap(), staging_network());
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .with_network_definition(loaded)
+            .build();
+        assert_eq!(rules.consensus_constants(0).coinbase_lock_height(), 5);
+        assert_eq!(rules.get_genesis_block().hash(), &genesis.hash());
+        assert_eq!(rules.checkpoint_at(0), Some(&genesis.hash()));
+    }
+
+    #[test]
+    fn invalid_network_definition() {
+        let genesis = create_genesis_block(&ConsensusConstants::localnet()[0]);
+        let mut definition = NetworkDefinition::new(staging_network(), vec![], genesis);
+        assert!(definition.validate().is_err());
+
+        definition.consensus_constants = ConsensusConstants::localnet();
+        definition.validate().unwrap();
+
+        // Custom networks cannot share the network byte of a hardcoded network
+        definition.network_byte = Network::LocalNet.as_byte();
+        assert!(definition.validate().is_err());
+        definition.network_byte = staging_network().network_byte;
+
+        definition.consensus_constants = ConsensusConstants::stibbons();
+        definition.consensus_constants.reverse();
+        assert!(definition.validate().is_err());
+
+        // The genesis block of another network does not pay the block reward of these constants
+        definition.consensus_constants = ConsensusConstants::localnet();
+        let weatherwax = ConsensusManager::builder(Network::Weatherwax)
+            .build()
+            .get_genesis_block();
+        let genesis = std::mem::replace(&mut definition.genesis_block, weatherwax.block().clone());
+        assert!(definition.validate().is_err());
+
+        definition.genesis_block = genesis;
+        definition.genesis_block.header.height = 1;
+        assert!(definition.validate().is_err());
+
+        // The emission decay is checked for every set of constants
+        definition.consensus_constants = ConsensusConstants::stibbons();
+        definition.consensus_constants[1].emission_decay = vec![64];
+        assert!(matches!(
+            definition.validate(),
+            Err(NetworkDefinitionError::InvalidDefinition(reason)) if reason.contains("decay")
+        ));
+    }
+}
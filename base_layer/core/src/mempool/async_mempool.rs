@@ -21,9 +21,9 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortId, ShortIdHasher},
     mempool::{error::MempoolError, Mempool, StateResponse, StatsResponse, TxStorageResponse},
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{tari_amount::MicroTari, transaction::Transaction, types::Signature},
 };
 use std::sync::Arc;
 
@@ -63,6 +63,8 @@ make_async!(process_published_block(published_block: Arc<Block>) -> ());
 make_async!(process_reorg(removed_blocks: Vec<Arc<Block>>, new_blocks: Vec<Arc<Block>>) -> ());
 make_async!(snapshot() -> Vec<Arc<Transaction>>);
 make_async!(retrieve(total_weight: u64) -> Vec<Arc<Transaction>>);
+make_async!(retrieve_by_short_ids(hasher: ShortIdHasher, short_ids: Vec<ShortId>) -> (Vec<Arc<Transaction>>, Vec<ShortId>));
 make_async!(has_tx_with_excess_sig(excess_sig: Signature) -> TxStorageResponse);
+make_async!(estimate_fee_per_gram(target_blocks: u64, max_block_weight: u64) -> MicroTari);
 make_async!(stats() -> StatsResponse);
 make_async!(state() -> StateResponse);
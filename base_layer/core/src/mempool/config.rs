@@ -20,7 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{consts, reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig};
+use crate::mempool::{
+    consts,
+    fee_estimator::FeeEstimatorConfig,
+    reorg_pool::ReorgPoolConfig,
+    unconfirmed_pool::UnconfirmedPoolConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tari_common::{configuration::seconds, NetworkConfigPath};
@@ -30,6 +35,7 @@ use tari_common::{configuration::seconds, NetworkConfigPath};
 pub struct MempoolConfig {
     pub unconfirmed_pool: UnconfirmedPoolConfig,
     pub reorg_pool: ReorgPoolConfig,
+    pub fee_estimator: FeeEstimatorConfig,
 }
 
 impl Default for MempoolConfig {
@@ -37,6 +43,7 @@ impl Default for MempoolConfig {
         Self {
             unconfirmed_pool: UnconfirmedPoolConfig::default(),
             reorg_pool: ReorgPoolConfig::default(),
+            fee_estimator: FeeEstimatorConfig::default(),
         }
     }
 }
@@ -90,11 +97,11 @@ mod test {
         let mut config = Config::new();
 
         config
-            .set("mempool.unconfirmed_pool.storage_capacity", 3)
+            .set("mempool.unconfirmed_pool.max_total_weight", 3)
             .expect("Could not set ''");
         let my_config = MempoolConfig::load_from(&config).expect("Could not load configuration");
         // [ ] mempool.mainnet, [X]  mempool = 3, [X] Default
-        assert_eq!(my_config.unconfirmed_pool.storage_capacity, 3);
+        assert_eq!(my_config.unconfirmed_pool.max_total_weight, 3);
         // [ ] mempool.mainnet, [ ]  mempool, [X] Default = 512
         assert_eq!(
             my_config.reorg_pool.storage_capacity,
@@ -104,7 +111,7 @@ mod test {
         assert_eq!(my_config.reorg_pool.tx_ttl, MEMPOOL_REORG_POOL_CACHE_TTL);
 
         config
-            .set("mempool.mainnet.unconfirmed_pool.storage_capacity", 20)
+            .set("mempool.mainnet.unconfirmed_pool.max_total_weight", 20)
             .expect("Could not set ''");
 
         config
@@ -113,7 +120,7 @@ mod test {
         // use_network = mainnet
         let my_config = MempoolConfig::load_from(&config).expect("Could not load configuration");
         // [ ] mempool.mainnet, [X]  mempool = 3, [X] Default
-        assert_eq!(my_config.unconfirmed_pool.storage_capacity, 20);
+        assert_eq!(my_config.unconfirmed_pool.max_total_weight, 20);
         // [ ] mempool.mainnet, [ ]  mempool, [X] Default = 512
         assert_eq!(
             my_config.reorg_pool.storage_capacity,
@@ -22,11 +22,38 @@
 
 use std::time::Duration;
 
-/// The maximum number of transactions that can be stored in the Unconfirmed Transaction pool
-pub const MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY: usize = 40_000;
+/// The maximum combined weight of the transactions in the unconfirmed pool, roughly 20 full blocks
+pub const MEMPOOL_UNCONFIRMED_POOL_MAX_TOTAL_WEIGHT: u64 = 400_000;
+/// The time after which transactions that are still waiting in the unconfirmed pool are evicted
+pub const MEMPOOL_UNCONFIRMED_POOL_TX_EXPIRY: Duration = Duration::from_secs(72 * 60 * 60);
+/// The time it takes for the minimum fee per gram of the unconfirmed pool to halve after it was raised by evictions
+pub const MEMPOOL_UNCONFIRMED_POOL_FEE_FLOOR_HALF_LIFE: Duration = Duration::from_secs(12 * 60 * 60);
+/// The amount (in µT per gram) by which the minimum fee per gram of the unconfirmed pool is raised above that of the
+/// transactions evicted because the pool is full
+pub const MEMPOOL_UNCONFIRMED_POOL_FEE_FLOOR_INCREMENT: u64 = 1;
+/// The number of recently evicted transactions that are reported in the mempool state
+pub const MEMPOOL_UNCONFIRMED_POOL_EVICTION_HISTORY_SIZE: usize = 1_000;
 /// The maximum number of transactions that can be skipped when compiling a set of highest priority transactions,
 /// skipping over large transactions are performed in an attempt to fit more transactions into the remaining space.
 pub const MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT: usize = 20;
+/// The minimum amount (in µT per gram) by which the fee per gram of a transaction must exceed that of the transactions
+/// it conflicts with in order to replace them
+pub const MEMPOOL_UNCONFIRMED_POOL_MIN_REPLACEMENT_FEE_PER_GRAM_INCREMENT: u64 = 1;
+/// The maximum number of transactions in the package formed by a transaction and its unconfirmed ancestors
+pub const MEMPOOL_UNCONFIRMED_POOL_MAX_ANCESTOR_COUNT: usize = 25;
+/// The maximum combined weight of the package formed by a transaction and its unconfirmed ancestors
+pub const MEMPOOL_UNCONFIRMED_POOL_MAX_ANCESTOR_WEIGHT: u64 = 10_000;
+/// The maximum number of transactions in the package formed by a transaction and all unconfirmed transactions that
+/// depend on it
+pub const MEMPOOL_UNCONFIRMED_POOL_MAX_DESCENDANT_COUNT: usize = 25;
+
+/// The number of recent blocks whose mined transactions are used to estimate fees
+pub const MEMPOOL_FEE_ESTIMATOR_TRACKED_BLOCKS: usize = 100;
+/// The minimum fee per gram (in µT) that the fee estimator will return
+pub const MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM: u64 = 1;
+/// The fraction of the transactions paying at least the fee per gram of a bucket that must have been mined within the
+/// target number of blocks for the bucket to be used as the estimate
+pub const MEMPOOL_FEE_ESTIMATOR_SUCCESS_THRESHOLD: f64 = 0.85;
 
 /// The maximum number of transactions that can be stored in the Reorg pool
 pub const MEMPOOL_REORG_POOL_STORAGE_CAPACITY: usize = 5_000;
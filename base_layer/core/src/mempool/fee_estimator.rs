@@ -0,0 +1,353 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::Block,
+    mempool::consts::{
+        MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM,
+        MEMPOOL_FEE_ESTIMATOR_SUCCESS_THRESHOLD,
+        MEMPOOL_FEE_ESTIMATOR_TRACKED_BLOCKS,
+    },
+    transactions::{tari_amount::MicroTari, transaction::Transaction, types::Signature},
+};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::{HashMap, VecDeque},
+};
+
+pub const LOG_TARGET: &str = "c::mp::fee_estimator";
+
+/// The ratio between the lower bounds of consecutive fee per gram buckets
+const FEE_BUCKET_SPACING: f64 = 1.1;
+/// The number of fee per gram buckets, the highest bucket starts at roughly 1_000_000 µT per gram
+const NUM_FEE_BUCKETS: usize = 145;
+/// The minimum number of mined transactions a bucket must contain before it is used for estimates
+const MIN_BUCKET_SAMPLES: usize = 3;
+
+/// Configuration for the FeeEstimator
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct FeeEstimatorConfig {
+    /// The number of recent blocks whose mined transactions are used to estimate fees
+    pub tracked_blocks: usize,
+    /// The minimum fee per gram (in µT) that will be estimated
+    pub min_fee_per_gram: u64,
+}
+
+impl Default for FeeEstimatorConfig {
+    fn default() -> Self {
+        Self {
+            tracked_blocks: MEMPOOL_FEE_ESTIMATOR_TRACKED_BLOCKS,
+            min_fee_per_gram: MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM,
+        }
+    }
+}
+
+// A transaction that was mined, along with the number of blocks it waited in the mempool before being mined
+#[derive(Clone)]
+struct MinedTransaction {
+    excess_sig: Signature,
+    entry_height: u64,
+    bucket: usize,
+    blocks_to_confirm: u64,
+}
+
+// The tracked transactions mined in a block
+struct MinedBlock {
+    height: u64,
+    txs: Vec<MinedTransaction>,
+}
+
+/// The FeeEstimator estimates the fee per gram that a transaction must pay to be mined within a given number of
+/// blocks. It records the height at which each transaction entered the unconfirmed pool and, when the transaction is
+/// mined, the number of blocks it waited grouped into fee per gram buckets. An estimate is the lowest bucket for which
+/// most transactions over the last `tracked_blocks` blocks were mined within the target, raised if needed to outbid the
+/// current backlog in the unconfirmed pool.
+pub struct FeeEstimator {
+    config: FeeEstimatorConfig,
+    tip_height: Option<u64>,
+    unconfirmed_txs: HashMap<Signature, (u64, usize)>,
+    mined_blocks: VecDeque<MinedBlock>,
+}
+
+impl FeeEstimator {
+    pub fn new(config: FeeEstimatorConfig) -> Self {
+        Self {
+            config,
+            tip_height: None,
+            unconfirmed_txs: HashMap::new(),
+            mined_blocks: VecDeque::new(),
+        }
+    }
+
+    /// Records a transaction that was accepted into the unconfirmed pool. Transactions received before the first block
+    /// has been processed are not tracked, as the height at which they arrived is unknown. Transactions that are
+    /// resubmitted, e.g. after a reorg, keep the height at which they first arrived.
+    pub fn track_transaction(&mut self, tx: &Transaction) {
+        if let (Some(height), Some(excess_sig)) = (self.tip_height, tx.first_kernel_excess_sig()) {
+            let bucket = Self::bucket_index(tx.calculate_ave_fee_per_gram());
+            self.unconfirmed_txs
+                .entry(excess_sig.clone())
+                .or_insert((height, bucket));
+        }
+    }
+
+    /// Records the transactions mined in the given block, and how long they waited in the unconfirmed pool.
+    pub fn process_block(&mut self, block: &Block) {
+        let height = block.header.height;
+        let mut mined = Vec::new();
+        for kernel in block.body.kernels() {
+            if let Some((entry_height, bucket)) = self.unconfirmed_txs.remove(&kernel.excess_sig) {
+                mined.push(MinedTransaction {
+                    excess_sig: kernel.excess_sig.clone(),
+                    entry_height,
+                    bucket,
+                    blocks_to_confirm: cmp::max(height.saturating_sub(entry_height), 1),
+                });
+            }
+        }
+        trace!(
+            target: LOG_TARGET,
+            "Recorded {} tracked transaction(s) mined in block {}",
+            mined.len(),
+            height
+        );
+        self.mined_blocks.push_back(MinedBlock { height, txs: mined });
+        while self.mined_blocks.len() > self.config.tracked_blocks {
+            self.mined_blocks.pop_front();
+        }
+        self.tip_height = Some(height);
+    }
+
+    /// Forgets the transactions mined in a block that was removed from the main chain by a reorg. The transactions in
+    /// the block are tracked as unconfirmed again from the height at which they first arrived, as they return to the
+    /// unconfirmed pool. Blocks must be rewound from the tip downwards, before the blocks that replace them are
+    /// processed.
+    pub fn rewind_block(&mut self, block: &Block) {
+        let height = block.header.height;
+        while self.mined_blocks.back().map_or(false, |mined| mined.height >= height) {
+            if let Some(mined) = self.mined_blocks.pop_back() {
+                for tx in mined.txs {
+                    self.unconfirmed_txs
+                        .entry(tx.excess_sig)
+                        .or_insert((tx.entry_height, tx.bucket));
+                }
+            }
+        }
+        trace!(target: LOG_TARGET, "Rewound fee estimates to before block {}", height);
+        self.tip_height = Some(height.saturating_sub(1));
+    }
+
+    /// Stops tracking the transactions that are no longer in the unconfirmed pool without having been mined.
+    pub fn retain<F>(&mut self, mut is_unconfirmed: F)
+    where F: FnMut(&Signature) -> bool {
+        self.unconfirmed_txs.retain(|excess_sig, _| is_unconfirmed(excess_sig));
+    }
+
+    /// Returns the fee per gram required for a transaction to be mined within `target_blocks` blocks. The `backlog`
+    /// contains the transactions currently waiting in the unconfirmed pool and `max_block_weight` is the weight
+    /// available to transactions in each block.
+    pub fn estimate_fee_per_gram(
+        &self,
+        target_blocks: u64,
+        backlog: &[&Transaction],
+        max_block_weight: u64,
+    ) -> MicroTari {
+        let target_blocks = cmp::max(target_blocks, 1);
+        let estimate = self
+            .estimate_from_history(target_blocks)
+            .max(Self::estimate_from_backlog(target_blocks, backlog, max_block_weight))
+            .max(self.config.min_fee_per_gram);
+        MicroTari(estimate)
+    }
+
+    // Returns the lowest fee per gram for which the transactions paying that much or more were mined within the
+    // target number of blocks, or zero if too few transactions have been mined to tell.
+    fn estimate_from_history(&self, target_blocks: u64) -> u64 {
+        let mut total = [0usize; NUM_FEE_BUCKETS];
+        let mut confirmed = [0usize; NUM_FEE_BUCKETS];
+        for tx in self.mined_blocks.iter().flat_map(|mined| mined.txs.iter()) {
+            total[tx.bucket] += 1;
+            if tx.blocks_to_confirm <= target_blocks {
+                confirmed[tx.bucket] += 1;
+            }
+        }
+
+        // The counts are accumulated from the highest bucket down, so that each bucket is judged by all of the
+        // transactions that paid at least its fee per gram
+        let mut lowest_bucket = None;
+        let mut total_above = 0;
+        let mut confirmed_above = 0;
+        for bucket in (0..NUM_FEE_BUCKETS).rev() {
+            total_above += total[bucket];
+            confirmed_above += confirmed[bucket];
+            if total[bucket] == 0 || total_above < MIN_BUCKET_SAMPLES {
+                continue;
+            }
+            if confirmed_above as f64 / total_above as f64 >= MEMPOOL_FEE_ESTIMATOR_SUCCESS_THRESHOLD {
+                lowest_bucket = Some(bucket);
+            } else {
+                break;
+            }
+        }
+        lowest_bucket.map(Self::bucket_fee_per_gram).unwrap_or(0)
+    }
+
+    // Returns the fee per gram needed to outbid the transactions in the backlog that would fill the blocks up to the
+    // target, or zero if the whole backlog fits.
+    fn estimate_from_backlog(target_blocks: u64, backlog: &[&Transaction], max_block_weight: u64) -> u64 {
+        let mut txs = backlog
+            .iter()
+            .map(|tx| (tx.calculate_ave_fee_per_gram(), tx.calculate_weight()))
+            .collect::<Vec<_>>();
+        txs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(cmp::Ordering::Equal));
+
+        let available_weight = max_block_weight.saturating_mul(target_blocks);
+        let mut total_weight = 0u64;
+        for (fee_per_gram, weight) in txs {
+            total_weight += weight;
+            if total_weight > available_weight {
+                return fee_per_gram.floor() as u64 + 1;
+            }
+        }
+        0
+    }
+
+    fn bucket_index(fee_per_gram: f64) -> usize {
+        if fee_per_gram < 1.0 {
+            return 0;
+        }
+        cmp::min(
+            (fee_per_gram.ln() / FEE_BUCKET_SPACING.ln()).floor() as usize,
+            NUM_FEE_BUCKETS - 1,
+        )
+    }
+
+    fn bucket_fee_per_gram(bucket: usize) -> u64 {
+        FEE_BUCKET_SPACING.powi(bucket as i32).ceil() as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        blocks::BlockHeader,
+        transactions::{aggregated_body::AggregateBody, tari_amount::MicroTari},
+        tx,
+    };
+
+    fn block_with(height: u64, txs: &[&Transaction]) -> Block {
+        let mut header = BlockHeader::new(0);
+        header.height = height;
+        let kernels = txs.iter().flat_map(|tx| tx.body.kernels().clone()).collect();
+        Block::new(header, AggregateBody::new(vec![], vec![], kernels))
+    }
+
+    #[test]
+    fn bucket_boundaries() {
+        assert_eq!(FeeEstimator::bucket_index(0.5), 0);
+        assert_eq!(FeeEstimator::bucket_index(1.0), 0);
+        assert_eq!(FeeEstimator::bucket_index(25.0), 33);
+        assert!(FeeEstimator::bucket_fee_per_gram(33) <= 25);
+        assert!(FeeEstimator::bucket_fee_per_gram(34) > 25);
+        assert_eq!(FeeEstimator::bucket_index(f64::MAX), NUM_FEE_BUCKETS - 1);
+    }
+
+    #[test]
+    fn estimates_from_mined_transactions() {
+        let mut estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        estimator.process_block(&block_with(1, &[]));
+
+        // High fee transactions are mined in the next block, low fee transactions wait for 3 blocks
+        let high_fee_txs = (0..3)
+            .map(|_| tx!(MicroTari(10_000), fee: MicroTari(100), inputs: 1, outputs: 1).0)
+            .collect::<Vec<_>>();
+        let low_fee_txs = (0..3)
+            .map(|_| tx!(MicroTari(10_000), fee: MicroTari(5), inputs: 1, outputs: 1).0)
+            .collect::<Vec<_>>();
+        high_fee_txs
+            .iter()
+            .chain(low_fee_txs.iter())
+            .for_each(|tx| estimator.track_transaction(tx));
+        estimator.process_block(&block_with(2, &high_fee_txs.iter().collect::<Vec<_>>()));
+        estimator.process_block(&block_with(3, &[]));
+        estimator.process_block(&block_with(4, &low_fee_txs.iter().collect::<Vec<_>>()));
+
+        let next_block = estimator.estimate_fee_per_gram(1, &[], 19_500).0;
+        assert!(next_block > 5 && next_block <= 100, "{}", next_block);
+        let three_blocks = estimator.estimate_fee_per_gram(3, &[], 19_500).0;
+        assert!(three_blocks <= 5, "{}", three_blocks);
+        assert!(three_blocks >= MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM);
+    }
+
+    #[test]
+    fn rewinds_reorged_blocks() {
+        let mut estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        estimator.process_block(&block_with(1, &[]));
+        let txs = (0..3)
+            .map(|_| tx!(MicroTari(10_000), fee: MicroTari(100), inputs: 1, outputs: 1).0)
+            .collect::<Vec<_>>();
+        txs.iter().for_each(|tx| estimator.track_transaction(tx));
+        let block = block_with(2, &txs.iter().collect::<Vec<_>>());
+        estimator.process_block(&block);
+        assert!(estimator.estimate_fee_per_gram(1, &[], 19_500).0 > MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM);
+
+        // The block is reorged out and the transactions are only mined again two blocks later
+        estimator.rewind_block(&block);
+        assert_eq!(
+            estimator.estimate_fee_per_gram(1, &[], 19_500),
+            MicroTari(MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM)
+        );
+        estimator.process_block(&block_with(2, &[]));
+        estimator.process_block(&block_with(3, &txs.iter().collect::<Vec<_>>()));
+        assert_eq!(
+            estimator.estimate_fee_per_gram(1, &[], 19_500),
+            MicroTari(MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM)
+        );
+        assert!(estimator.estimate_fee_per_gram(2, &[], 19_500).0 > MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM);
+    }
+
+    #[test]
+    fn estimates_from_backlog() {
+        let estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        let tx1 = tx!(MicroTari(10_000), fee: MicroTari(50), inputs: 1, outputs: 1).0;
+        let tx2 = tx!(MicroTari(10_000), fee: MicroTari(20), inputs: 1, outputs: 1).0;
+        let backlog = vec![&tx1, &tx2];
+
+        // Both transactions fit into the next block
+        let block_weight = tx1.calculate_weight() + tx2.calculate_weight();
+        assert_eq!(
+            estimator.estimate_fee_per_gram(1, &backlog, block_weight),
+            MicroTari(MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM)
+        );
+        // Only tx1 fits, so tx2 must be outbid
+        let fee = estimator.estimate_fee_per_gram(1, &backlog, tx1.calculate_weight());
+        assert_eq!(fee.0, tx2.calculate_ave_fee_per_gram().floor() as u64 + 1);
+        // Over two blocks both fit again
+        assert_eq!(
+            estimator.estimate_fee_per_gram(2, &backlog, tx1.calculate_weight()),
+            MicroTari(MEMPOOL_FEE_ESTIMATOR_MIN_FEE_PER_GRAM)
+        );
+    }
+}
@@ -21,19 +21,23 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortId, ShortIdHasher},
     mempool::{
         error::MempoolError,
         mempool_storage::MempoolStorage,
+        persistence::MempoolDump,
         MempoolConfig,
         StateResponse,
         StatsResponse,
         TxStorageResponse,
     },
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{tari_amount::MicroTari, transaction::Transaction, types::Signature},
     validation::MempoolTransactionValidation,
 };
-use std::sync::{Arc, RwLock};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, RwLock},
+};
 
 /// The Mempool consists of an Unconfirmed Transaction Pool, Pending Pool, Orphan Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions have not yet been included in a block, and transactions
@@ -41,6 +45,8 @@ use std::sync::{Arc, RwLock};
 #[derive(Clone)]
 pub struct Mempool {
     pool_storage: Arc<RwLock<MempoolStorage>>,
+    // Held while the mempool is written to disk so that the periodic and shutdown dumps do not overlap
+    dump_lock: Arc<Mutex<()>>,
 }
 
 impl Mempool {
@@ -48,6 +54,7 @@ impl Mempool {
     pub fn new(config: MempoolConfig, validator: Arc<dyn MempoolTransactionValidation>) -> Self {
         Self {
             pool_storage: Arc::new(RwLock::new(MempoolStorage::new(config, validator))),
+            dump_lock: Arc::new(Mutex::new(())),
         }
     }
 
@@ -99,6 +106,56 @@ impl Mempool {
             .retrieve(total_weight)
     }
 
+    /// Writes the transactions in the unconfirmed and reorg pools to the given file, so that they can be restored after
+    /// a restart using `load_from_file`. Concurrent calls are serialised, so the file always holds the latest dump.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), MempoolError> {
+        let _dump_lock = self
+            .dump_lock
+            .lock()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?;
+        let dump = self
+            .pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .dump()?;
+        dump.write_to_file(path)
+    }
+
+    /// Restores the transactions saved with `save_to_file`, revalidating them against the current chain tip. Returns
+    /// the number of transactions that were restored, which is zero if the file does not exist.
+    pub fn load_from_file(&self, path: &Path) -> Result<usize, MempoolError> {
+        match MempoolDump::read_from_file(path)? {
+            Some(dump) => self
+                .pool_storage
+                .write()
+                .map_err(|e| MempoolError::BackendError(e.to_string()))?
+                .restore(dump),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the transactions in the Mempool that contain a kernel with one of the given short IDs, along with the
+    /// short IDs for which no transaction was found.
+    pub fn retrieve_by_short_ids(
+        &self,
+        hasher: ShortIdHasher,
+        short_ids: Vec<ShortId>,
+    ) -> Result<(Vec<Arc<Transaction>>, Vec<ShortId>), MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .retrieve_by_short_ids(&hasher, &short_ids)
+    }
+
+    /// Returns the fee per gram that a transaction should pay to be mined within `target_blocks` blocks, where
+    /// `max_block_weight` is the weight available to transactions in each block.
+    pub fn estimate_fee_per_gram(&self, target_blocks: u64, max_block_weight: u64) -> Result<MicroTari, MempoolError> {
+        self.pool_storage
+            .read()
+            .map_err(|e| MempoolError::BackendError(e.to_string()))?
+            .estimate_fee_per_gram(target_blocks, max_block_weight)
+    }
+
     /// Check if the specified transaction is stored in the Mempool.
     pub fn has_tx_with_excess_sig(&self, excess_sig: Signature) -> Result<TxStorageResponse, MempoolError> {
         self.pool_storage
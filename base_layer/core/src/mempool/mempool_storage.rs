@@ -21,9 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortId, ShortIdHasher},
     mempool::{
         error::MempoolError,
+        fee_estimator::FeeEstimator,
+        persistence::MempoolDump,
         reorg_pool::ReorgPool,
         unconfirmed_pool::UnconfirmedPool,
         MempoolConfig,
@@ -31,11 +33,19 @@ use crate::{
         StatsResponse,
         TxStorageResponse,
     },
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{
+        tari_amount::MicroTari,
+        transaction::Transaction,
+        types::{HashOutput, Signature},
+    },
     validation::{MempoolTransactionValidation, ValidationError},
 };
 use log::*;
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use tari_crypto::tari_utilities::{hex::Hex, Hashable};
 
 pub const LOG_TARGET: &str = "c::mp::mempool_storage";
@@ -47,6 +57,8 @@ pub struct MempoolStorage {
     unconfirmed_pool: UnconfirmedPool,
     reorg_pool: ReorgPool,
     validator: Arc<dyn MempoolTransactionValidation>,
+    reorg_pool_tx_ttl: Duration,
+    fee_estimator: FeeEstimator,
 }
 
 impl MempoolStorage {
@@ -56,6 +68,8 @@ impl MempoolStorage {
             unconfirmed_pool: UnconfirmedPool::new(config.unconfirmed_pool),
             reorg_pool: ReorgPool::new(config.reorg_pool),
             validator: validators,
+            reorg_pool_tx_ttl: config.reorg_pool.tx_ttl,
+            fee_estimator: FeeEstimator::new(config.fee_estimator),
         }
     }
 
@@ -72,14 +86,10 @@ impl MempoolStorage {
                 .unwrap_or_else(|| "None".into())
         );
         match self.validator.validate(&tx) {
-            Ok(()) => {
-                self.unconfirmed_pool.insert(tx, None)?;
-                Ok(TxStorageResponse::UnconfirmedPool)
-            },
+            Ok(()) => self.insert_into_unconfirmed_pool(tx, None),
             Err(ValidationError::UnknownInputs(dependent_outputs)) => {
                 if self.unconfirmed_pool.verify_outputs_exist(&dependent_outputs) {
-                    self.unconfirmed_pool.insert(tx, Some(dependent_outputs))?;
-                    Ok(TxStorageResponse::UnconfirmedPool)
+                    self.insert_into_unconfirmed_pool(tx, Some(dependent_outputs))
                 } else {
                     warn!(target: LOG_TARGET, "Validation failed due to unknown inputs");
                     Ok(TxStorageResponse::NotStoredOrphan)
@@ -100,6 +110,19 @@ impl MempoolStorage {
         }
     }
 
+    // Insert a validated transaction into the UTxPool, and track it for fee estimation if it was stored
+    fn insert_into_unconfirmed_pool(
+        &mut self,
+        tx: Arc<Transaction>,
+        dependent_outputs: Option<Vec<HashOutput>>,
+    ) -> Result<TxStorageResponse, MempoolError> {
+        let response = self.unconfirmed_pool.insert(tx.clone(), dependent_outputs)?;
+        if response.is_stored() {
+            self.fee_estimator.track_transaction(&tx);
+        }
+        Ok(response)
+    }
+
     // Insert a set of new transactions into the UTxPool.
     fn insert_txs(&mut self, txs: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
         for tx in txs {
@@ -111,11 +134,16 @@ impl MempoolStorage {
     /// Update the Mempool based on the received published block.
     pub fn process_published_block(&mut self, published_block: Arc<Block>) -> Result<(), MempoolError> {
         trace!(target: LOG_TARGET, "Mempool processing new block: {}", published_block);
+        self.fee_estimator.process_block(&published_block);
         // Move published txs to ReOrgPool and discard double spends
         self.reorg_pool.insert_txs(
             self.unconfirmed_pool
                 .remove_published_and_discard_deprecated_transactions(&published_block),
         )?;
+        self.unconfirmed_pool.remove_expired_transactions();
+        let unconfirmed_pool = &self.unconfirmed_pool;
+        self.fee_estimator
+            .retain(|excess_sig| unconfirmed_pool.has_tx_with_excess_sig(excess_sig));
 
         Ok(())
     }
@@ -148,6 +176,12 @@ impl MempoolStorage {
         let previous_tip = removed_blocks.last().map(|block| block.header.height);
         let new_tip = new_blocks.last().map(|block| block.header.height);
 
+        // The transactions mined in the removed blocks no longer count towards the fee estimates, the new blocks are
+        // recorded as they are processed below
+        for block in removed_blocks.iter().rev() {
+            self.fee_estimator.rewind_block(block);
+        }
+
         // Clear out all transactions from the unconfirmed pool and re-submit them to the unconfirmed mempool for
         // validation. This is important as invalid transactions that have not been mined yet may remain in the mempool
         // after a reorg.
@@ -183,6 +217,9 @@ impl MempoolStorage {
                 );
             }
         }
+        let unconfirmed_pool = &self.unconfirmed_pool;
+        self.fee_estimator
+            .retain(|excess_sig| unconfirmed_pool.has_tx_with_excess_sig(excess_sig));
 
         Ok(())
     }
@@ -194,6 +231,49 @@ impl MempoolStorage {
         Ok(txs)
     }
 
+    /// Returns the transactions in the unconfirmed and reorg pools that contain a kernel with one of the given short
+    /// IDs, along with the short IDs for which no transaction was found. Short IDs that match the kernels of more than
+    /// one transaction are ambiguous and reported as not found.
+    pub fn retrieve_by_short_ids(
+        &self,
+        hasher: &ShortIdHasher,
+        short_ids: &[ShortId],
+    ) -> Result<(Vec<Arc<Transaction>>, Vec<ShortId>), MempoolError> {
+        let mut txs_by_short_id = HashMap::new();
+        let mut ambiguous = HashSet::new();
+        for tx in self
+            .unconfirmed_pool
+            .snapshot()
+            .into_iter()
+            .chain(self.reorg_pool.snapshot()?)
+        {
+            for kernel in tx.body.kernels() {
+                let short_id = hasher.short_id(kernel);
+                if let Some(existing) = txs_by_short_id.insert(short_id, tx.clone()) {
+                    if existing.body.kernels()[0].excess_sig != tx.body.kernels()[0].excess_sig {
+                        ambiguous.insert(short_id);
+                    }
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        let mut found_keys = HashSet::new();
+        let mut not_found = Vec::new();
+        for short_id in short_ids {
+            match txs_by_short_id.get(short_id) {
+                Some(tx) if !ambiguous.contains(short_id) => {
+                    // A transaction with several kernels is only returned once
+                    if found_keys.insert(tx.body.kernels()[0].excess_sig.clone()) {
+                        found.push(tx.clone());
+                    }
+                },
+                _ => not_found.push(*short_id),
+            }
+        }
+        Ok((found, not_found))
+    }
+
     /// Returns a list of transaction ranked by transaction priority up to a given weight.
     /// Will only return transactions that will fit into a block
     pub fn retrieve(&mut self, total_weight: u64) -> Result<Vec<Arc<Transaction>>, MempoolError> {
@@ -202,6 +282,92 @@ impl MempoolStorage {
         Ok(results.retrieved_transactions)
     }
 
+    /// Takes a snapshot of the transactions in the unconfirmed and reorg pools that can be written to disk.
+    pub fn dump(&self) -> Result<MempoolDump, MempoolError> {
+        let unconfirmed_txs = self
+            .unconfirmed_pool
+            .snapshot_with_age()
+            .into_iter()
+            .map(|(tx, age)| (tx.as_ref().clone(), age))
+            .collect();
+        let reorg_txs = self
+            .reorg_pool
+            .snapshot()?
+            .iter()
+            .map(|tx| tx.as_ref().clone())
+            .collect();
+        Ok(MempoolDump::new(unconfirmed_txs, reorg_txs))
+    }
+
+    /// Restores the transactions from a mempool dump, revalidating them against the current chain tip. Transactions
+    /// that have since been mined or have become invalid are discarded. Unconfirmed pool transactions are discarded
+    /// if they have expired, and otherwise keep the time at which they entered the pool. Reorg pool transactions are
+    /// discarded if the dump is older than their Time-to-live, and are returned to the unconfirmed pool if the block
+    /// that contained them has been reorged out while the node was offline. Returns the number of transactions that
+    /// were restored.
+    pub fn restore(&mut self, dump: MempoolDump) -> Result<usize, MempoolError> {
+        let mut num_restored = 0;
+        let mut pending: Vec<(Arc<Transaction>, Duration)> = Vec::new();
+        for dumped_tx in dump.unconfirmed_txs {
+            let age = dumped_tx.age();
+            if !self.unconfirmed_pool.is_expired(age) {
+                pending.push((Arc::new(dumped_tx.transaction), age));
+            }
+        }
+        if dump.age() < self.reorg_pool_tx_ttl {
+            for tx in dump.reorg_txs {
+                match self.validator.validate(&tx) {
+                    Err(ValidationError::ContainsSTxO) => {
+                        self.reorg_pool.insert_txs(vec![Arc::new(tx)])?;
+                        num_restored += 1;
+                    },
+                    _ => pending.push((Arc::new(tx), Duration::from_secs(0))),
+                }
+            }
+        }
+
+        // Transactions that spend the outputs of other unconfirmed transactions are orphans until their parents have
+        // been restored, so keep retrying them for as long as progress is being made
+        loop {
+            let num_pending = pending.len();
+            let mut orphans = Vec::new();
+            for (tx, age) in pending {
+                match self.insert(tx.clone())? {
+                    TxStorageResponse::NotStoredOrphan => orphans.push((tx, age)),
+                    response if response.is_stored() => {
+                        if let Some(excess_sig) = tx.first_kernel_excess_sig() {
+                            self.unconfirmed_pool.set_age(excess_sig, age);
+                        }
+                        num_restored += 1;
+                    },
+                    _ => {},
+                }
+            }
+            if orphans.is_empty() || orphans.len() == num_pending {
+                break;
+            }
+            pending = orphans;
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Restored {} transaction(s) into the mempool", num_restored
+        );
+        Ok(num_restored)
+    }
+
+    /// Returns the fee per gram that a transaction should pay to be mined within `target_blocks` blocks, based on the
+    /// transactions mined in recent blocks and the transactions waiting in the unconfirmed pool. The estimate is never
+    /// below the minimum fee per gram currently required to enter the unconfirmed pool.
+    pub fn estimate_fee_per_gram(&self, target_blocks: u64, max_block_weight: u64) -> Result<MicroTari, MempoolError> {
+        let backlog = self.unconfirmed_pool.snapshot();
+        let backlog = backlog.iter().map(|tx| tx.as_ref()).collect::<Vec<_>>();
+        let estimate = self
+            .fee_estimator
+            .estimate_fee_per_gram(target_blocks, &backlog, max_block_weight);
+        let min_fee_per_gram = MicroTari(self.unconfirmed_pool.min_fee_per_gram().ceil() as u64);
+        Ok(estimate.max(min_fee_per_gram))
+    }
+
     /// Check if the specified transaction is stored in the Mempool.
     pub fn has_tx_with_excess_sig(&self, excess_sig: Signature) -> Result<TxStorageResponse, MempoolError> {
         if self.unconfirmed_pool.has_tx_with_excess_sig(&excess_sig) {
@@ -250,6 +416,7 @@ impl MempoolStorage {
         Ok(StateResponse {
             unconfirmed_pool,
             reorg_pool,
+            evicted: self.unconfirmed_pool.recently_evicted(),
         })
     }
 }
@@ -30,11 +30,15 @@ mod consts;
 #[cfg(feature = "base_node")]
 mod error;
 #[cfg(feature = "base_node")]
+mod fee_estimator;
+#[cfg(feature = "base_node")]
 #[allow(clippy::module_inception)]
 mod mempool;
 #[cfg(feature = "base_node")]
 mod mempool_storage;
 #[cfg(feature = "base_node")]
+mod persistence;
+#[cfg(feature = "base_node")]
 mod priority;
 #[cfg(feature = "base_node")]
 mod reorg_pool;
@@ -95,10 +99,39 @@ impl Display for StatsResponse {
     }
 }
 
+/// The reason a transaction was removed from the unconfirmed pool before it was mined
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EvictionReason {
+    /// The transaction waited in the pool for longer than the configured expiry time
+    Expired,
+    /// The transaction was evicted to make space for higher priority transactions
+    PoolFull,
+    /// The transaction was replaced by a conflicting transaction that pays a higher fee
+    Replaced,
+}
+
+impl Display for EvictionReason {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let reason = match self {
+            EvictionReason::Expired => "Expired",
+            EvictionReason::PoolFull => "Pool full",
+            EvictionReason::Replaced => "Replaced",
+        };
+        fmt.write_str(reason)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvictedTransaction {
+    pub excess_sig: Signature,
+    pub reason: EvictionReason,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct StateResponse {
     pub unconfirmed_pool: Vec<Transaction>,
     pub reorg_pool: Vec<Signature>,
+    pub evicted: Vec<EvictedTransaction>,
 }
 
 impl Display for StateResponse {
@@ -121,6 +154,14 @@ impl Display for StateResponse {
         for excess_sig in &self.reorg_pool {
             fmt.write_str(&format!("    {}\n", excess_sig.get_signature().to_hex()))?;
         }
+        fmt.write_str("--- Recently Evicted ---\n")?;
+        for evicted in &self.evicted {
+            fmt.write_str(&format!(
+                "    {} Reason:{}\n",
+                evicted.excess_sig.get_signature().to_hex(),
+                evicted.reason
+            ))?;
+        }
         Ok(())
     }
 }
@@ -128,16 +169,26 @@ impl Display for StateResponse {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TxStorageResponse {
     UnconfirmedPool,
+    /// The transaction was stored in the unconfirmed pool, replacing the transactions that it conflicts with
+    UnconfirmedPoolReplaced,
     ReorgPool,
     NotStoredOrphan,
     NotStoredTimeLocked,
     NotStoredAlreadySpent,
+    /// The transaction conflicts with transactions in the unconfirmed pool and does not pay enough to replace them
+    NotStoredFeeTooLow,
+    /// The transaction pays less than the minimum fee per gram of the unconfirmed pool. The minimum rises while the
+    /// pool is full and decays again, so the transaction may be accepted later.
+    NotStoredBelowMinimumFee,
     NotStored,
 }
 
 impl TxStorageResponse {
     pub fn is_stored(&self) -> bool {
-        matches!(self, Self::UnconfirmedPool | Self::ReorgPool)
+        matches!(
+            self,
+            Self::UnconfirmedPool | Self::UnconfirmedPoolReplaced | Self::ReorgPool
+        )
     }
 }
 
@@ -145,10 +196,13 @@ impl Display for TxStorageResponse {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         let storage = match self {
             TxStorageResponse::UnconfirmedPool => "Unconfirmed pool",
+            TxStorageResponse::UnconfirmedPoolReplaced => "Unconfirmed pool, replacing conflicting transactions",
             TxStorageResponse::ReorgPool => "Reorg pool",
             TxStorageResponse::NotStoredOrphan => "Not stored orphan transaction",
             TxStorageResponse::NotStoredTimeLocked => "Not stored time locked transaction",
             TxStorageResponse::NotStoredAlreadySpent => "Not stored output already spent",
+            TxStorageResponse::NotStoredFeeTooLow => "Not stored fee too low to replace conflicting transactions",
+            TxStorageResponse::NotStoredBelowMinimumFee => "Not stored fee below the unconfirmed pool minimum",
             TxStorageResponse::NotStored => "Not stored",
         };
         fmt.write_str(&storage)
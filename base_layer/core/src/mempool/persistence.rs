@@ -0,0 +1,171 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{mempool::error::MempoolError, transactions::transaction::Transaction};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The version of the mempool dump format. It is written ahead of the dump, and dumps written with a different version
+/// are ignored.
+const MEMPOOL_DUMP_VERSION: u32 = 2;
+
+/// A snapshot of the transactions in the mempool that is written to disk so that the mempool can be restored when the
+/// base node restarts.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MempoolDump {
+    /// Unix timestamp (in seconds) of when the dump was taken
+    timestamp: u64,
+    pub unconfirmed_txs: Vec<DumpedTransaction>,
+    pub reorg_txs: Vec<Transaction>,
+}
+
+/// A transaction from the unconfirmed pool along with the time at which it entered the pool, so that it does not
+/// outlive its expiry across restarts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DumpedTransaction {
+    pub transaction: Transaction,
+    /// Unix timestamp (in seconds) of when the transaction entered the unconfirmed pool
+    inserted_at: u64,
+}
+
+impl DumpedTransaction {
+    /// The time that has passed since the transaction entered the unconfirmed pool
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.inserted_at))
+    }
+}
+
+impl MempoolDump {
+    /// Create a dump from the unconfirmed pool transactions, along with the time that they have been waiting in the
+    /// pool, and the reorg pool transactions.
+    pub fn new(unconfirmed_txs: Vec<(Transaction, Duration)>, reorg_txs: Vec<Transaction>) -> Self {
+        let timestamp = unix_now();
+        Self {
+            timestamp,
+            unconfirmed_txs: unconfirmed_txs
+                .into_iter()
+                .map(|(transaction, age)| DumpedTransaction {
+                    transaction,
+                    inserted_at: timestamp.saturating_sub(age.as_secs()),
+                })
+                .collect(),
+            reorg_txs,
+        }
+    }
+
+    /// The time that has passed since the dump was taken
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.timestamp))
+    }
+
+    /// Write the dump to the given file. The dump is written to a temporary file first and then moved into place, so
+    /// that a crash while writing never leaves a truncated dump behind. Callers must not write to the same path
+    /// concurrently.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), MempoolError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        let file = File::create(&tmp_path).map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        let mut writer = BufWriter::new(file);
+        bincode::serialize_into(&mut writer, &MEMPOOL_DUMP_VERSION)
+            .map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        bincode::serialize_into(&mut writer, self).map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        let file = writer
+            .into_inner()
+            .map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        file.sync_all()
+            .map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        fs::rename(&tmp_path, path).map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Read a dump from the given file. Returns None if the file does not exist or was written with a different
+    /// version of the dump format.
+    pub fn read_from_file(path: &Path) -> Result<Option<Self>, MempoolError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(MempoolError::PersistenceError(e.to_string())),
+        };
+        let mut reader = BufReader::new(file);
+        // Check the version before reading the rest of the dump, which may have a different layout
+        let version: u32 =
+            bincode::deserialize_from(&mut reader).map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        if version != MEMPOOL_DUMP_VERSION {
+            return Ok(None);
+        }
+        let dump = bincode::deserialize_from(&mut reader).map_err(|e| MempoolError::PersistenceError(e.to_string()))?;
+        Ok(Some(dump))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{transactions::tari_amount::MicroTari, tx};
+    use tempfile::tempdir;
+
+    #[test]
+    fn write_and_read_dump() {
+        let tx1 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0;
+        let tx2 = tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0;
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mempool.dat");
+
+        assert!(MempoolDump::read_from_file(&path).unwrap().is_none());
+        MempoolDump::new(vec![(tx1.clone(), Duration::from_secs(600))], vec![tx2.clone()])
+            .write_to_file(&path)
+            .unwrap();
+        let dump = MempoolDump::read_from_file(&path).unwrap().unwrap();
+        assert_eq!(dump.unconfirmed_txs.len(), 1);
+        assert_eq!(dump.unconfirmed_txs[0].transaction, tx1);
+        let age = dump.unconfirmed_txs[0].age();
+        assert!(age >= Duration::from_secs(600) && age < Duration::from_secs(660));
+        assert_eq!(dump.reorg_txs, vec![tx2]);
+        assert!(dump.age() < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn ignore_dump_with_other_version() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mempool.dat");
+        // Only the version is read, the rest of the dump is not in a format that this version understands
+        let mut contents = bincode::serialize(&(MEMPOOL_DUMP_VERSION + 1)).unwrap();
+        contents.extend_from_slice(&[0xff; 16]);
+        fs::write(&path, contents).unwrap();
+        assert!(MempoolDump::read_from_file(&path).unwrap().is_none());
+    }
+}
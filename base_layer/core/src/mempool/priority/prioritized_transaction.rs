@@ -24,7 +24,7 @@ use crate::{
     mempool::priority::PriorityError,
     transactions::{transaction::Transaction, types::HashOutput},
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 use tari_crypto::tari_utilities::message_format::MessageFormat;
 
 /// Create a unique unspent transaction priority based on the transaction fee, maturity of the oldest input UTXO and the
@@ -36,7 +36,13 @@ pub struct FeePriority(Vec<u8>);
 impl FeePriority {
     pub fn try_from(transaction: &Transaction) -> Result<Self, PriorityError> {
         // The weights have been normalised, so the fee priority is now equal to the fee per gram ± a few pct points
-        let fee_per_byte = (transaction.calculate_ave_fee_per_gram() * 1000.0) as usize; // Include 3 decimal places before flooring
+        Self::try_from_fee_per_gram(transaction, transaction.calculate_ave_fee_per_gram())
+    }
+
+    /// Create a priority for the transaction that ranks it by the given fee per gram instead of its own, e.g. the fee
+    /// per gram of the package formed by the transaction and its unconfirmed ancestors.
+    pub fn try_from_fee_per_gram(transaction: &Transaction, fee_per_gram: f64) -> Result<Self, PriorityError> {
+        let fee_per_byte = (fee_per_gram * 1000.0) as usize; // Include 3 decimal places before flooring
         let mut fee_priority = fee_per_byte.to_binary()?;
         fee_priority.reverse(); // Requires Big-endian for BtreeMap sorting
 
@@ -63,6 +69,7 @@ pub struct PrioritizedTransaction {
     pub priority: FeePriority,
     pub weight: u64,
     pub depended_output_hashes: Vec<HashOutput>,
+    pub inserted_at: Instant,
 }
 
 impl PrioritizedTransaction {
@@ -79,6 +86,7 @@ impl PrioritizedTransaction {
             weight: transaction.calculate_weight(),
             transaction: Arc::new(transaction),
             depended_output_hashes,
+            inserted_at: Instant::now(),
         })
     }
 }
@@ -43,6 +43,7 @@ impl TryInto<MempoolResponse> for ProtoMempoolResponse {
                     .ok_or_else(|| "Invalid or unrecognised `TxStorageResponse` enum".to_string())?;
                 MempoolResponse::TxStorage(tx_storage_response.try_into()?)
             },
+            FeeEstimate(fee_per_gram) => MempoolResponse::FeeEstimate(fee_per_gram.into()),
         };
         Ok(response)
     }
@@ -72,6 +73,7 @@ impl From<MempoolResponse> for ProtoMempoolResponse {
                 let tx_storage_response: ProtoTxStorageResponse = tx_storage_response.into();
                 ProtoMempoolResponse::TxStorage(tx_storage_response.into())
             },
+            FeeEstimate(fee_per_gram) => ProtoMempoolResponse::FeeEstimate(fee_per_gram.into()),
         }
     }
 }
@@ -20,7 +20,16 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{proto::mempool::StateResponse as ProtoStateResponse, StateResponse};
+use crate::mempool::{
+    proto::mempool::{
+        EvictedTransaction as ProtoEvictedTransaction,
+        EvictionReason as ProtoEvictionReason,
+        StateResponse as ProtoStateResponse,
+    },
+    EvictedTransaction,
+    EvictionReason,
+    StateResponse,
+};
 use std::convert::{TryFrom, TryInto};
 // use crate::transactions::proto::types::Signature as ProtoSignature;
 use crate::{
@@ -51,6 +60,41 @@ impl From<Signature> for ProtoSignature {
     }
 }
 
+//--------------------------------- EvictedTransaction --------------------------------------//
+
+impl TryFrom<ProtoEvictedTransaction> for EvictedTransaction {
+    type Error = String;
+
+    fn try_from(evicted: ProtoEvictedTransaction) -> Result<Self, Self::Error> {
+        let excess_sig = evicted
+            .excess_sig
+            .ok_or_else(|| "excess_sig not provided".to_string())?
+            .try_into()
+            .map_err(|err: ByteArrayError| err.to_string())?;
+        let reason = match ProtoEvictionReason::from_i32(evicted.reason) {
+            Some(ProtoEvictionReason::Expired) => EvictionReason::Expired,
+            Some(ProtoEvictionReason::PoolFull) => EvictionReason::PoolFull,
+            Some(ProtoEvictionReason::Replaced) => EvictionReason::Replaced,
+            Some(ProtoEvictionReason::None) | None => return Err("Invalid eviction reason".to_string()),
+        };
+        Ok(Self { excess_sig, reason })
+    }
+}
+
+impl From<EvictedTransaction> for ProtoEvictedTransaction {
+    fn from(evicted: EvictedTransaction) -> Self {
+        let reason = match evicted.reason {
+            EvictionReason::Expired => ProtoEvictionReason::Expired,
+            EvictionReason::PoolFull => ProtoEvictionReason::PoolFull,
+            EvictionReason::Replaced => ProtoEvictionReason::Replaced,
+        };
+        Self {
+            excess_sig: Some(evicted.excess_sig.into()),
+            reason: reason as i32,
+        }
+    }
+}
+
 //--------------------------------- StateResponse -------------------------------------------//
 
 impl TryFrom<ProtoStateResponse> for StateResponse {
@@ -69,6 +113,11 @@ impl TryFrom<ProtoStateResponse> for StateResponse {
                 .map(TryInto::try_into)
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|err: ByteArrayError| err.to_string())?,
+            evicted: state
+                .evicted
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<Vec<_>, _>>()?,
         })
     }
 }
@@ -78,6 +127,7 @@ impl From<StateResponse> for ProtoStateResponse {
         Self {
             unconfirmed_pool: state.unconfirmed_pool.into_iter().map(Into::into).collect(),
             reorg_pool: state.reorg_pool.into_iter().map(Into::into).collect(),
+            evicted: state.evicted.into_iter().map(Into::into).collect(),
         }
     }
 }
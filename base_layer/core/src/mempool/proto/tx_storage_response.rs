@@ -31,6 +31,7 @@ impl TryFrom<proto::TxStorageResponse> for TxStorageResponse {
         Ok(match tx_storage {
             None => return Err("TxStorageResponse not provided".to_string()),
             UnconfirmedPool => TxStorageResponse::UnconfirmedPool,
+            UnconfirmedPoolReplaced => TxStorageResponse::UnconfirmedPoolReplaced,
             ReorgPool => TxStorageResponse::ReorgPool,
             NotStored => TxStorageResponse::NotStored,
         })
@@ -42,11 +43,14 @@ impl From<TxStorageResponse> for proto::TxStorageResponse {
         use TxStorageResponse::*;
         match resp {
             UnconfirmedPool => proto::TxStorageResponse::UnconfirmedPool,
+            UnconfirmedPoolReplaced => proto::TxStorageResponse::UnconfirmedPoolReplaced,
             ReorgPool => proto::TxStorageResponse::ReorgPool,
             NotStored => proto::TxStorageResponse::NotStored,
             NotStoredOrphan => proto::TxStorageResponse::NotStored,
             NotStoredTimeLocked => proto::TxStorageResponse::NotStored,
             NotStoredAlreadySpent => proto::TxStorageResponse::NotStored,
+            NotStoredFeeTooLow => proto::TxStorageResponse::NotStored,
+            NotStoredBelowMinimumFee => proto::TxStorageResponse::NotStored,
         }
     }
 }
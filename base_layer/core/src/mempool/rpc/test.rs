@@ -73,6 +73,7 @@ mod get_state {
             unconfirmed_pool: vec![],
 
             reorg_pool: vec![],
+            evicted: vec![],
         };
         mempool.set_get_state_response(expected_state.clone()).await;
 
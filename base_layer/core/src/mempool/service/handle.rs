@@ -28,7 +28,7 @@ use crate::{
         StatsResponse,
         TxStorageResponse,
     },
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{tari_amount::MicroTari, transaction::Transaction, types::Signature},
 };
 use tari_service_framework::{reply_channel::TrySenderService, Service};
 
@@ -79,4 +79,22 @@ impl MempoolHandle {
             _ => panic!("Incorrect response"),
         }
     }
+
+    pub async fn get_fee_estimate(
+        &mut self,
+        target_blocks: u64,
+        max_block_weight: u64,
+    ) -> Result<MicroTari, MempoolServiceError> {
+        match self
+            .inner
+            .call(MempoolRequest::GetFeeEstimate {
+                target_blocks,
+                max_block_weight,
+            })
+            .await??
+        {
+            MempoolResponse::FeeEstimate(fee_per_gram) => Ok(fee_per_gram),
+            _ => panic!("Incorrect response"),
+        }
+    }
 }
@@ -85,6 +85,12 @@ impl MempoolInboundHandlers {
                 );
                 Ok(MempoolResponse::TxStorage(self.submit_transaction(tx, vec![]).await?))
             },
+            GetFeeEstimate {
+                target_blocks,
+                max_block_weight,
+            } => Ok(MempoolResponse::FeeEstimate(
+                async_mempool::estimate_fee_per_gram(self.mempool.clone(), target_blocks, max_block_weight).await?,
+            )),
         }
     }
 
@@ -133,7 +139,10 @@ impl MempoolInboundHandlers {
                     "Transaction inserted into mempool: {}, pool: {}.", kernel_excess_sig, tx_storage
                 );
                 // propagate the tx if it was accepted to the unconfirmed pool
-                if matches!(tx_storage, TxStorageResponse::UnconfirmedPool) {
+                if matches!(
+                    tx_storage,
+                    TxStorageResponse::UnconfirmedPool | TxStorageResponse::UnconfirmedPoolReplaced
+                ) {
                     debug!(
                         target: LOG_TARGET,
                         "Propagate transaction ({}) to network.", kernel_excess_sig,
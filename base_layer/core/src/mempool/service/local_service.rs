@@ -28,7 +28,7 @@ use crate::{
         StatsResponse,
         TxStorageResponse,
     },
-    transactions::{transaction::Transaction, types::Signature},
+    transactions::{tari_amount::MicroTari, transaction::Transaction, types::Signature},
 };
 use tari_service_framework::{reply_channel::SenderService, Service};
 use tokio::sync::broadcast;
@@ -111,6 +111,25 @@ impl LocalMempoolService {
             _ => Err(MempoolServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the fee per gram that a transaction should pay to be mined within `target_blocks` blocks
+    pub async fn get_fee_estimate(
+        &mut self,
+        target_blocks: u64,
+        max_block_weight: u64,
+    ) -> Result<MicroTari, MempoolServiceError> {
+        match self
+            .request_sender
+            .call(MempoolRequest::GetFeeEstimate {
+                target_blocks,
+                max_block_weight,
+            })
+            .await??
+        {
+            MempoolResponse::FeeEstimate(fee_per_gram) => Ok(fee_per_gram),
+            _ => Err(MempoolServiceError::UnexpectedApiResponse),
+        }
+    }
 }
 
 #[cfg(test)]
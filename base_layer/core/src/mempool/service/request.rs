@@ -34,6 +34,7 @@ pub enum MempoolRequest {
     GetState,
     GetTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
+    GetFeeEstimate { target_blocks: u64, max_block_weight: u64 },
 }
 
 impl Display for MempoolRequest {
@@ -48,6 +49,9 @@ impl Display for MempoolRequest {
                 "SubmitTransaction ({})",
                 tx.body.kernels()[0].excess_sig.get_signature().to_hex()
             )),
+            MempoolRequest::GetFeeEstimate { target_blocks, .. } => {
+                f.write_str(&format!("GetFeeEstimate ({} blocks)", target_blocks))
+            },
         }
     }
 }
@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{StateResponse, StatsResponse, TxStorageResponse};
+use crate::{
+    mempool::{StateResponse, StatsResponse, TxStorageResponse},
+    transactions::tari_amount::MicroTari,
+};
 use serde::{Deserialize, Serialize};
 use std::{fmt, fmt::Formatter};
 use tari_common_types::waiting_requests::RequestKey;
@@ -31,6 +34,7 @@ pub enum MempoolResponse {
     Stats(StatsResponse),
     State(StateResponse),
     TxStorage(TxStorageResponse),
+    FeeEstimate(MicroTari),
 }
 
 impl fmt::Display for MempoolResponse {
@@ -40,6 +44,7 @@ impl fmt::Display for MempoolResponse {
             Stats(_) => write!(f, "Stats"),
             State(_) => write!(f, "State"),
             TxStorage(_) => write!(f, "TxStorage"),
+            FeeEstimate(_) => write!(f, "FeeEstimate"),
         }
     }
 }
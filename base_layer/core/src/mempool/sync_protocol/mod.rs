@@ -74,7 +74,7 @@ pub use initializer::MempoolSyncInitializer;
 
 use crate::{
     base_node::StateMachineHandle,
-    mempool::{async_mempool, proto, Mempool, MempoolServiceConfig},
+    mempool::{async_mempool, proto, Mempool, MempoolServiceConfig, TxStorageResponse},
     proto as shared_proto,
     transactions::transaction::Transaction,
 };
@@ -527,7 +527,14 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         }
 
         let stored_result = async_mempool::insert(self.mempool.clone(), Arc::new(txn)).await?;
-        if stored_result.is_stored() {
+        if stored_result == TxStorageResponse::UnconfirmedPoolReplaced {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction `{}` from peer `{}` replaced conflicting transaction(s)",
+                excess_sig_hex,
+                self.peer_node_id.short_str()
+            );
+        } else if stored_result.is_stored() {
             debug!(
                 target: LOG_TARGET,
                 "Inserted transaction `{}` from peer `{}`",
@@ -20,12 +20,15 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::mempool::{
-    service::{MempoolHandle, MempoolRequest, MempoolResponse},
-    MempoolServiceError,
-    StateResponse,
-    StatsResponse,
-    TxStorageResponse,
+use crate::{
+    mempool::{
+        service::{MempoolHandle, MempoolRequest, MempoolResponse},
+        MempoolServiceError,
+        StateResponse,
+        StatsResponse,
+        TxStorageResponse,
+    },
+    transactions::tari_amount::MicroTari,
 };
 use futures::StreamExt;
 use std::sync::{
@@ -49,6 +52,7 @@ pub struct MempoolMockState {
     get_state: Arc<Mutex<StateResponse>>,
     get_tx_state_by_excess_sig: Arc<Mutex<TxStorageResponse>>,
     submit_transaction: Arc<Mutex<TxStorageResponse>>,
+    get_fee_estimate: Arc<Mutex<MicroTari>>,
     calls: Arc<AtomicUsize>,
 }
 
@@ -64,9 +68,11 @@ impl Default for MempoolMockState {
             get_state: Arc::new(Mutex::new(StateResponse {
                 unconfirmed_pool: vec![],
                 reorg_pool: vec![],
+                evicted: vec![],
             })),
             get_tx_state_by_excess_sig: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
             submit_transaction: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
+            get_fee_estimate: Arc::new(Mutex::new(MicroTari(1))),
             calls: Arc::new(Default::default()),
         }
     }
@@ -89,6 +95,10 @@ impl MempoolMockState {
         *self.submit_transaction.lock().await = resp;
     }
 
+    pub async fn set_get_fee_estimate_response(&self, fee_per_gram: MicroTari) {
+        *self.get_fee_estimate.lock().await = fee_per_gram;
+    }
+
     fn inc_call_count(&self) {
         self.calls.fetch_add(1, Ordering::SeqCst);
     }
@@ -135,6 +145,7 @@ impl MempoolServiceMock {
             SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
                 self.state.submit_transaction.lock().await.clone(),
             )),
+            GetFeeEstimate { .. } => Ok(MempoolResponse::FeeEstimate(*self.state.get_fee_estimate.lock().await)),
         }
     }
 }
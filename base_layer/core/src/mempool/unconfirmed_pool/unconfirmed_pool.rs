@@ -23,9 +23,23 @@
 use crate::{
     blocks::Block,
     mempool::{
-        consts::{MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY, MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT},
+        consts::{
+            MEMPOOL_UNCONFIRMED_POOL_EVICTION_HISTORY_SIZE,
+            MEMPOOL_UNCONFIRMED_POOL_FEE_FLOOR_HALF_LIFE,
+            MEMPOOL_UNCONFIRMED_POOL_FEE_FLOOR_INCREMENT,
+            MEMPOOL_UNCONFIRMED_POOL_MAX_ANCESTOR_COUNT,
+            MEMPOOL_UNCONFIRMED_POOL_MAX_ANCESTOR_WEIGHT,
+            MEMPOOL_UNCONFIRMED_POOL_MAX_DESCENDANT_COUNT,
+            MEMPOOL_UNCONFIRMED_POOL_MAX_TOTAL_WEIGHT,
+            MEMPOOL_UNCONFIRMED_POOL_MIN_REPLACEMENT_FEE_PER_GRAM_INCREMENT,
+            MEMPOOL_UNCONFIRMED_POOL_TX_EXPIRY,
+            MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT,
+        },
         priority::{FeePriority, PrioritizedTransaction},
         unconfirmed_pool::UnconfirmedPoolError,
+        EvictedTransaction,
+        EvictionReason,
+        TxStorageResponse,
     },
     transactions::{
         transaction::Transaction,
@@ -35,9 +49,11 @@ use crate::{
 use log::*;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     sync::Arc,
+    time::{Duration, Instant},
 };
+use tari_common::configuration::seconds;
 use tari_crypto::tari_utilities::{hex::Hex, Hashable};
 
 pub const LOG_TARGET: &str = "c::mp::unconfirmed_pool::unconfirmed_pool_storage";
@@ -45,18 +61,51 @@ pub const LOG_TARGET: &str = "c::mp::unconfirmed_pool::unconfirmed_pool_storage"
 /// Configuration for the UnconfirmedPool
 #[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct UnconfirmedPoolConfig {
-    /// The maximum number of transactions that can be stored in the Unconfirmed Transaction pool
-    pub storage_capacity: usize,
+    /// The maximum combined weight of the transactions that can be stored in the Unconfirmed Transaction pool. The
+    /// lowest priority transactions are evicted to make space for higher priority transactions once it is reached.
+    pub max_total_weight: u64,
+    /// Transactions that have been waiting in the pool for longer than this are evicted.
+    #[serde(with = "seconds")]
+    pub tx_expiry: Duration,
+    /// Evicting transactions because the pool is full raises the minimum fee per gram that new transactions must pay
+    /// to just above that of the evicted transactions. The minimum fee is halved every `fee_floor_half_life` after it
+    /// was last raised.
+    #[serde(with = "seconds")]
+    pub fee_floor_half_life: Duration,
+    /// The amount (in µT per gram) by which the minimum fee per gram is raised above the fee per gram of the
+    /// transactions evicted because the pool is full. The minimum fee stops decaying once it falls below half of this.
+    pub fee_floor_increment: u64,
     /// The maximum number of transactions that can be skipped when compiling a set of highest priority transactions,
     /// skipping over large transactions are performed in an attempt to fit more transactions into the remaining space.
     pub weight_tx_skip_count: usize,
+    /// The minimum amount (in µT per gram) by which the fee per gram of a transaction must exceed the fee per gram of
+    /// each transaction it conflicts with in order to replace them. The replacement must also pay a higher total fee
+    /// than all of the transactions it replaces.
+    pub min_replacement_fee_per_gram_increment: u64,
+    /// The maximum number of transactions in the package formed by a transaction and its unconfirmed ancestors in the
+    /// pool. Transactions that would exceed this limit are not stored.
+    pub max_ancestor_count: usize,
+    /// The maximum combined weight of the package formed by a transaction and its unconfirmed ancestors in the pool.
+    /// Transactions that would exceed this limit are not stored.
+    pub max_ancestor_weight: u64,
+    /// The maximum number of transactions in the package formed by a transaction and all transactions in the pool that
+    /// depend on it. Transactions that would grow the package of any of their ancestors beyond this limit are not
+    /// stored.
+    pub max_descendant_count: usize,
 }
 
 impl Default for UnconfirmedPoolConfig {
     fn default() -> Self {
         Self {
-            storage_capacity: MEMPOOL_UNCONFIRMED_POOL_STORAGE_CAPACITY,
+            max_total_weight: MEMPOOL_UNCONFIRMED_POOL_MAX_TOTAL_WEIGHT,
+            tx_expiry: MEMPOOL_UNCONFIRMED_POOL_TX_EXPIRY,
+            fee_floor_half_life: MEMPOOL_UNCONFIRMED_POOL_FEE_FLOOR_HALF_LIFE,
+            fee_floor_increment: MEMPOOL_UNCONFIRMED_POOL_FEE_FLOOR_INCREMENT,
             weight_tx_skip_count: MEMPOOL_UNCONFIRMED_POOL_WEIGHT_TRANSACTION_SKIP_COUNT,
+            min_replacement_fee_per_gram_increment: MEMPOOL_UNCONFIRMED_POOL_MIN_REPLACEMENT_FEE_PER_GRAM_INCREMENT,
+            max_ancestor_count: MEMPOOL_UNCONFIRMED_POOL_MAX_ANCESTOR_COUNT,
+            max_ancestor_weight: MEMPOOL_UNCONFIRMED_POOL_MAX_ANCESTOR_WEIGHT,
+            max_descendant_count: MEMPOOL_UNCONFIRMED_POOL_MAX_DESCENDANT_COUNT,
         }
     }
 }
@@ -68,12 +117,87 @@ impl Default for UnconfirmedPoolConfig {
 /// transactions in the pool according to TXPriority, it allows transactions to be inserted in sorted order by their
 /// priority. The txs_by_priority BTreeMap makes it easier to select the set of highest priority transactions that can
 /// be included in a block. The excess_sig of a transaction is used a key to uniquely identify a specific transaction in
-/// these containers.
+/// these containers. The txs_by_input HashMap maps the hash of each output spent by a transaction in the pool to that
+/// transaction, and is used to enforce that no two transactions in the pool spend the same output.
+///
+/// Transactions that spend the outputs of other transactions in the pool are ranked by the fee per gram of their
+/// package, i.e. the transaction together with all of its unconfirmed ancestors, when compiling the set of highest
+/// priority transactions. This allows a high fee child to pay for its low fee parents (CPFP). The ancestor package and
+/// the descendant package, i.e. the transaction together with all transactions in the pool that depend on it, of every
+/// transaction are cached in `packages` and updated whenever a related transaction enters or leaves the pool. The
+/// number of ancestors and descendants of a transaction is limited, which bounds the cost of these updates.
+///
+/// The pool is bounded by the combined weight of its transactions. Transactions are evicted when they expire or to
+/// make space for higher priority transactions, and the most recent evictions are kept along with the reason for them.
+/// Transactions are evicted in the order of the fee per gram of their descendant package, kept in
+/// txs_by_descendant_score, so that a low fee parent is not evicted while a child is paying for it.
 pub struct UnconfirmedPool {
     config: UnconfirmedPoolConfig,
     txs_by_signature: HashMap<Signature, PrioritizedTransaction>,
     txs_by_priority: BTreeMap<FeePriority, Signature>,
+    txs_by_descendant_score: BTreeMap<FeePriority, Signature>,
     txs_by_output: HashMap<HashOutput, Vec<Signature>>,
+    txs_by_input: HashMap<HashOutput, Signature>,
+    txs_by_dependency: HashMap<HashOutput, Vec<Signature>>,
+    packages: HashMap<Signature, PackageScores>,
+    total_weight: u64,
+    fee_floor: f64,
+    fee_floor_updated: Instant,
+    recently_evicted: VecDeque<EvictedTransaction>,
+    clock: Arc<dyn Clock>,
+}
+
+/// The source of the current time for the pool, which tests replace to control the expiry of transactions
+trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The combined fee and weight of a package of transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PackageStats {
+    count: usize,
+    fee: u64,
+    weight: u64,
+}
+
+impl PackageStats {
+    fn add(&mut self, transaction: &PrioritizedTransaction) {
+        self.count += 1;
+        self.fee += transaction.transaction.body.get_total_fee().0;
+        self.weight += transaction.weight;
+    }
+
+    fn remove(&mut self, transaction: &PrioritizedTransaction) {
+        self.count = self.count.saturating_sub(1);
+        self.fee = self.fee.saturating_sub(transaction.transaction.body.get_total_fee().0);
+        self.weight = self.weight.saturating_sub(transaction.weight);
+    }
+
+    fn fee_per_gram(&self) -> f64 {
+        if self.weight == 0 {
+            return 0.0;
+        }
+        self.fee as f64 / self.weight as f64
+    }
+}
+
+/// The cached packages of a transaction in the pool
+#[derive(Debug, Clone)]
+struct PackageScores {
+    /// The transaction together with its unconfirmed ancestors in the pool
+    ancestors: PackageStats,
+    /// The transaction together with all transactions in the pool that depend on it, directly or indirectly
+    descendants: PackageStats,
+    /// The key of the transaction in txs_by_descendant_score
+    descendant_priority: FeePriority,
 }
 
 // helper class to reduce type complexity
@@ -85,65 +209,594 @@ pub struct RetrieveResults {
 impl UnconfirmedPool {
     /// Create a new UnconfirmedPool with the specified configuration
     pub fn new(config: UnconfirmedPoolConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    fn with_clock(config: UnconfirmedPoolConfig, clock: Arc<dyn Clock>) -> Self {
         Self {
             config,
             txs_by_signature: HashMap::new(),
             txs_by_priority: BTreeMap::new(),
+            txs_by_descendant_score: BTreeMap::new(),
             txs_by_output: HashMap::new(),
+            txs_by_input: HashMap::new(),
+            txs_by_dependency: HashMap::new(),
+            packages: HashMap::new(),
+            total_weight: 0,
+            fee_floor: 0.0,
+            fee_floor_updated: clock.now(),
+            recently_evicted: VecDeque::new(),
+            clock,
         }
     }
 
-    fn lowest_priority(&self) -> &FeePriority {
-        self.txs_by_priority.iter().next().unwrap().0
+    /// Returns the minimum fee per gram that a new transaction must pay to be stored in the pool. The floor is raised
+    /// when transactions are evicted because the pool is full and decays back towards zero afterwards.
+    pub fn min_fee_per_gram(&self) -> f64 {
+        let half_life = self.config.fee_floor_half_life.as_secs_f64();
+        if self.fee_floor <= 0.0 || half_life <= 0.0 {
+            return 0.0;
+        }
+        let half_lives = self.elapsed_since(self.fee_floor_updated).as_secs_f64() / half_life;
+        let fee_floor = self.fee_floor * 0.5f64.powf(half_lives);
+        // Stop decaying once the floor no longer makes a meaningful difference
+        if fee_floor < self.config.fee_floor_increment as f64 / 2.0 {
+            return 0.0;
+        }
+        fee_floor
     }
 
-    fn remove_lowest_priority_tx(&mut self) {
-        if let Some((priority, sig)) = self.txs_by_priority.iter().next().map(|(p, s)| (p.clone(), s.clone())) {
-            self.txs_by_signature.remove(&sig);
-            self.txs_by_priority.remove(&priority);
+    // Raise the fee floor to just above the fee per gram of a transaction that was evicted because the pool is full
+    fn raise_fee_floor(&mut self, evicted_fee_per_gram: f64) {
+        let fee_floor = evicted_fee_per_gram + self.config.fee_floor_increment as f64;
+        if fee_floor > self.min_fee_per_gram() {
+            self.fee_floor = fee_floor;
+            self.fee_floor_updated = self.clock.now();
         }
     }
 
+    // Returns the time that has passed since the given instant according to the clock of the pool
+    fn elapsed_since(&self, instant: Instant) -> Duration {
+        self.clock.now().saturating_duration_since(instant)
+    }
+
     /// Insert a new transaction into the UnconfirmedPool. Low priority transactions will be removed to make space for
-    /// higher priority transactions. The lowest priority transactions will be removed when the maximum capacity is
-    /// reached and the new transaction has a higher priority than the currently stored lowest priority transaction.
+    /// higher priority transactions. The transactions with the lowest priority descendant packages, along with the
+    /// transactions that depend on them, will be removed when the maximum total weight is reached and the package of
+    /// the new transaction has a higher priority than them. Expired transactions are removed first.
+    ///
+    /// A transaction is not stored if its fee per gram is below the current minimum fee per gram of the pool.
+    ///
+    /// A transaction that spends an output that is already spent by transactions in the pool replaces those
+    /// transactions, and the transactions that depend on them, if it pays a higher total fee than all of them and its
+    /// fee per gram exceeds that of each conflicting transaction by at least the configured increment. Otherwise it is
+    /// not stored.
+    ///
+    /// A transaction is also not stored if the package formed by it and its unconfirmed ancestors would exceed the
+    /// configured ancestor count or weight limits, or if it would exceed the descendant count limit of any of its
+    /// ancestors.
     #[allow(clippy::map_entry)]
     pub fn insert(
         &mut self,
         tx: Arc<Transaction>,
         dependent_outputs: Option<Vec<HashOutput>>,
-    ) -> Result<(), UnconfirmedPoolError> {
+    ) -> Result<TxStorageResponse, UnconfirmedPoolError> {
         let tx_key = tx
             .first_kernel_excess_sig()
             .ok_or(UnconfirmedPoolError::TransactionNoKernels)?;
-        if !self.txs_by_signature.contains_key(tx_key) {
-            let prioritized_tx = PrioritizedTransaction::convert_from_transaction((*tx).clone(), dependent_outputs)?;
-            if self.txs_by_signature.len() >= self.config.storage_capacity {
-                if prioritized_tx.priority < *self.lowest_priority() {
-                    return Ok(());
-                }
-                self.remove_lowest_priority_tx();
-            }
-            self.txs_by_priority
-                .insert(prioritized_tx.priority.clone(), tx_key.clone());
-            self.txs_by_signature.insert(tx_key.clone(), prioritized_tx);
-            for output in tx.body.outputs().clone() {
-                self.txs_by_output
-                    .entry(output.hash())
-                    .or_default()
-                    .push(tx_key.clone());
+        if self.txs_by_signature.contains_key(tx_key) {
+            return Ok(TxStorageResponse::UnconfirmedPool);
+        }
+
+        let fee_per_gram = tx.calculate_ave_fee_per_gram();
+        let min_fee_per_gram = self.min_fee_per_gram();
+        if fee_per_gram < min_fee_per_gram {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction with signature {} pays {:.2} µT/g, which is below the minimum of {:.2} µT/g for the \
+                 unconfirmed pool",
+                tx_key.get_signature().to_hex(),
+                fee_per_gram,
+                min_fee_per_gram
+            );
+            return Ok(TxStorageResponse::NotStoredBelowMinimumFee);
+        }
+
+        let mut prioritized_tx = PrioritizedTransaction::convert_from_transaction((*tx).clone(), dependent_outputs)?;
+        prioritized_tx.inserted_at = self.clock.now();
+        if prioritized_tx.weight > self.config.max_total_weight {
+            return Ok(TxStorageResponse::NotStored);
+        }
+        if self.total_weight + prioritized_tx.weight > self.config.max_total_weight {
+            self.remove_expired_transactions();
+        }
+
+        let conflicts = self.find_conflicting_transactions(&tx);
+        let replaced = self.with_descendants(&conflicts);
+        if !conflicts.is_empty() && !self.is_valid_replacement(&tx, &conflicts, &replaced)? {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction with signature {} conflicts with {} transaction(s) in the unconfirmed pool and does not \
+                 pay enough to replace them",
+                tx_key.get_signature().to_hex(),
+                conflicts.len()
+            );
+            return Ok(TxStorageResponse::NotStoredFeeTooLow);
+        }
+
+        let package = self.ancestor_package(&prioritized_tx)?;
+        if package.count > self.config.max_ancestor_count || package.weight > self.config.max_ancestor_weight {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction with signature {} not stored in unconfirmed pool, its package of {} transaction(s) with \
+                 weight {} exceeds the ancestor limits",
+                tx_key.get_signature().to_hex(),
+                package.count,
+                package.weight
+            );
+            return Ok(TxStorageResponse::NotStored);
+        }
+        if let Some(ancestor) = self.find_ancestor_at_descendant_limit(&prioritized_tx)? {
+            debug!(
+                target: LOG_TARGET,
+                "Transaction with signature {} not stored in unconfirmed pool, the descendant package of its ancestor \
+                 {} already holds the maximum of {} transaction(s)",
+                tx_key.get_signature().to_hex(),
+                ancestor.get_signature().to_hex(),
+                self.config.max_descendant_count
+            );
+            return Ok(TxStorageResponse::NotStored);
+        }
+        let package_priority = FeePriority::try_from_fee_per_gram(&tx, package.fee_per_gram())?;
+
+        let mut replaced_weight = 0;
+        for signature in &replaced {
+            replaced_weight += self
+                .txs_by_signature
+                .get(signature)
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?
+                .weight;
+        }
+        let required_weight =
+            (self.total_weight - replaced_weight + prioritized_tx.weight).saturating_sub(self.config.max_total_weight);
+        let mut evicted = Vec::new();
+        let mut evicted_fee_per_gram = 0.0;
+        if required_weight > 0 {
+            // The transactions that the new transaction depends on cannot be evicted to make space for it
+            let mut protected = self
+                .get_ancestors(&prioritized_tx)?
+                .into_iter()
+                .map(|(signature, _)| signature)
+                .collect::<HashSet<_>>();
+            protected.extend(replaced.iter().cloned());
+            match self.find_transactions_to_evict(&package_priority, &protected, required_weight) {
+                Some((signatures, fee_per_gram)) => {
+                    evicted = signatures;
+                    evicted_fee_per_gram = fee_per_gram;
+                },
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Transaction with signature {} not stored, the unconfirmed pool is full",
+                        tx_key.get_signature().to_hex()
+                    );
+                    return Ok(TxStorageResponse::NotStored);
+                },
             }
+        }
+
+        for signature in &replaced {
             debug!(
                 target: LOG_TARGET,
-                "Inserted transaction with signature {} into unconfirmed pool:",
+                "Transaction with signature {} replaced by {} in unconfirmed pool",
+                signature.get_signature().to_hex(),
                 tx_key.get_signature().to_hex()
             );
+        }
+        self.evict_transactions(&replaced, EvictionReason::Replaced);
+        if !self.evict_transactions(&evicted, EvictionReason::PoolFull).is_empty() {
+            self.raise_fee_floor(evicted_fee_per_gram);
+        }
+
+        self.total_weight += prioritized_tx.weight;
+        self.txs_by_priority
+            .insert(prioritized_tx.priority.clone(), tx_key.clone());
+        self.txs_by_signature.insert(tx_key.clone(), prioritized_tx);
+        for output in tx.body.outputs().clone() {
+            self.txs_by_output
+                .entry(output.hash())
+                .or_default()
+                .push(tx_key.clone());
+        }
+        for input in tx.body.inputs() {
+            self.txs_by_input.insert(input.output_hash(), tx_key.clone());
+        }
+        for output_hash in &self.txs_by_signature[tx_key].depended_output_hashes {
+            self.txs_by_dependency
+                .entry(output_hash.clone())
+                .or_default()
+                .push(tx_key.clone());
+        }
+        self.add_to_packages(tx_key)?;
+        debug!(
+            target: LOG_TARGET,
+            "Inserted transaction with signature {} into unconfirmed pool:",
+            tx_key.get_signature().to_hex()
+        );
+
+        trace!(target: LOG_TARGET, "{}", tx);
+        if replaced.is_empty() {
+            Ok(TxStorageResponse::UnconfirmedPool)
+        } else {
+            Ok(TxStorageResponse::UnconfirmedPoolReplaced)
+        }
+    }
+
+    // Returns the transactions with the lowest priority descendant packages, along with their descendants, that must be
+    // evicted to free up `required_weight` for a transaction whose package has the given priority, and the highest fee
+    // per gram of the evicted packages. Returns None if not enough lower priority transactions can be evicted.
+    fn find_transactions_to_evict(
+        &self,
+        priority: &FeePriority,
+        protected: &HashSet<Signature>,
+        required_weight: u64,
+    ) -> Option<(Vec<Signature>, f64)> {
+        let mut evicted = Vec::new();
+        let mut visited = HashSet::new();
+        let mut freed_weight = 0;
+        let mut evicted_fee_per_gram = 0.0f64;
+        for (lowest_priority, signature) in &self.txs_by_descendant_score {
+            if freed_weight >= required_weight || lowest_priority >= priority {
+                break;
+            }
+            if protected.contains(signature) || visited.contains(signature) {
+                continue;
+            }
+            if let Some(package) = self.packages.get(signature) {
+                evicted_fee_per_gram = evicted_fee_per_gram.max(package.descendants.fee_per_gram());
+            }
+            for signature in self.with_descendants(&[signature.clone()]) {
+                if protected.contains(&signature) || !visited.insert(signature.clone()) {
+                    continue;
+                }
+                if let Some(ptx) = self.txs_by_signature.get(&signature) {
+                    freed_weight += ptx.weight;
+                    evicted.push(signature);
+                }
+            }
+        }
+        if freed_weight >= required_weight {
+            Some((evicted, evicted_fee_per_gram))
+        } else {
+            None
+        }
+    }
+
+    // Removes the given transactions from the pool and records the reason for their eviction
+    fn evict_transactions(&mut self, signatures: &[Signature], reason: EvictionReason) -> Vec<Arc<Transaction>> {
+        let mut removed_txs = Vec::new();
+        for signature in signatures {
+            if let Some(transaction) = self.delete_transaction(signature) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Evicted transaction with signature {} from unconfirmed pool ({})",
+                    signature.get_signature().to_hex(),
+                    reason
+                );
+                if self.recently_evicted.len() >= MEMPOOL_UNCONFIRMED_POOL_EVICTION_HISTORY_SIZE {
+                    self.recently_evicted.pop_front();
+                }
+                self.recently_evicted.push_back(EvictedTransaction {
+                    excess_sig: signature.clone(),
+                    reason,
+                });
+                removed_txs.push(transaction);
+            }
+        }
+        removed_txs
+    }
 
-            trace!(target: LOG_TARGET, "{}", tx);
+    /// Remove all transactions that have been waiting in the pool for longer than the configured expiry time, along
+    /// with the transactions that depend on them.
+    pub fn remove_expired_transactions(&mut self) -> Vec<Arc<Transaction>> {
+        let expired = self
+            .txs_by_signature
+            .iter()
+            .filter(|(_, ptx)| self.is_expired(self.elapsed_since(ptx.inserted_at)))
+            .map(|(signature, _)| signature.clone())
+            .collect::<Vec<_>>();
+        if expired.is_empty() {
+            return Vec::new();
+        }
+        let expired = self.with_descendants(&expired);
+        self.evict_transactions(&expired, EvictionReason::Expired)
+    }
+
+    /// Returns the most recently evicted transactions, oldest first.
+    pub fn recently_evicted(&self) -> Vec<EvictedTransaction> {
+        self.recently_evicted.iter().cloned().collect()
+    }
+
+    // Returns the transactions in the pool that spend any of the outputs spent by the given transaction
+    fn find_conflicting_transactions(&self, tx: &Transaction) -> Vec<Signature> {
+        let mut conflicts = Vec::new();
+        for input in tx.body.inputs() {
+            if let Some(signature) = self.txs_by_input.get(&input.output_hash()) {
+                if !conflicts.contains(signature) {
+                    conflicts.push(signature.clone());
+                }
+            }
+        }
+        conflicts
+    }
+
+    // Returns the given transactions along with all transactions in the pool that spend their outputs, directly or
+    // indirectly
+    fn with_descendants(&self, signatures: &[Signature]) -> Vec<Signature> {
+        let mut result = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = signatures.to_vec();
+        while let Some(signature) = pending.pop() {
+            if !visited.insert(signature.clone()) {
+                continue;
+            }
+            if let Some(ptx) = self.txs_by_signature.get(&signature) {
+                for output in ptx.transaction.body.outputs() {
+                    let output_hash = output.hash();
+                    if let Some(child) = self.txs_by_input.get(&output_hash) {
+                        pending.push(child.clone());
+                    }
+                    if let Some(children) = self.txs_by_dependency.get(&output_hash) {
+                        pending.extend(children.iter().cloned());
+                    }
+                }
+            }
+            result.push(signature);
+        }
+        result
+    }
+
+    // Returns the transactions in the pool that create the outputs the given transaction depends on, directly or
+    // indirectly
+    fn get_ancestors<'a>(
+        &'a self,
+        transaction: &'a PrioritizedTransaction,
+    ) -> Result<HashMap<Signature, &'a PrioritizedTransaction>, UnconfirmedPoolError> {
+        let mut ancestors = HashMap::new();
+        let mut pending = vec![transaction];
+        while let Some(prioritized_transaction) = pending.pop() {
+            for output_hash in &prioritized_transaction.depended_output_hashes {
+                let signatures = match self.txs_by_output.get(output_hash) {
+                    Some(signatures) if !signatures.is_empty() => signatures,
+                    // The parent has left the pool, the transaction will be re-validated when it is selected
+                    _ => continue,
+                };
+                let signature = self.find_highest_priority_transaction(signatures)?;
+                if !ancestors.contains_key(&signature) {
+                    let parent = self
+                        .txs_by_signature
+                        .get(&signature)
+                        .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+                    ancestors.insert(signature, parent);
+                    pending.push(parent);
+                }
+            }
+        }
+        Ok(ancestors)
+    }
+
+    // Returns the combined fee and weight of the given transaction and its unconfirmed ancestors in the pool
+    fn ancestor_package(&self, transaction: &PrioritizedTransaction) -> Result<PackageStats, UnconfirmedPoolError> {
+        let mut package = PackageStats::default();
+        package.add(transaction);
+        for ancestor in self.get_ancestors(transaction)?.values() {
+            package.add(ancestor);
+        }
+        Ok(package)
+    }
+
+    // Returns the combined fee and weight of the given transaction and all transactions in the pool that depend on it
+    fn descendant_package(&self, signature: &Signature) -> PackageStats {
+        let mut package = PackageStats::default();
+        for descendant in self.with_descendants(&[signature.clone()]) {
+            if let Some(descendant) = self.txs_by_signature.get(&descendant) {
+                package.add(descendant);
+            }
+        }
+        package
+    }
+
+    // Returns every transaction in the pool whose descendant package includes the given transaction, i.e. the
+    // transactions that create the outputs it spends or depends on, directly or indirectly. This is the reverse of
+    // `with_descendants`, excluding the transaction itself.
+    fn depended_on_transactions(&self, transaction: &PrioritizedTransaction) -> HashSet<Signature> {
+        let mut result = HashSet::new();
+        let mut pending = vec![transaction];
+        while let Some(prioritized_transaction) = pending.pop() {
+            let spent_output_hashes = prioritized_transaction
+                .transaction
+                .body
+                .inputs()
+                .iter()
+                .map(|input| input.output_hash())
+                .chain(prioritized_transaction.depended_output_hashes.iter().cloned());
+            for output_hash in spent_output_hashes {
+                for signature in self.txs_by_output.get(&output_hash).into_iter().flatten() {
+                    if result.insert(signature.clone()) {
+                        if let Some(parent) = self.txs_by_signature.get(signature) {
+                            pending.push(parent);
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    // Returns a transaction in the pool whose descendant package would exceed the descendant count limit if the given
+    // transaction were added to the pool
+    fn find_ancestor_at_descendant_limit(
+        &self,
+        transaction: &PrioritizedTransaction,
+    ) -> Result<Option<Signature>, UnconfirmedPoolError> {
+        for signature in self.depended_on_transactions(transaction) {
+            let package = self
+                .packages
+                .get(&signature)
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+            if package.descendants.count >= self.config.max_descendant_count {
+                return Ok(Some(signature));
+            }
+        }
+        Ok(None)
+    }
+
+    // Caches the packages of a transaction in the pool and moves it to its new position in txs_by_descendant_score
+    fn store_packages(
+        &mut self,
+        signature: &Signature,
+        ancestors: PackageStats,
+        descendants: PackageStats,
+    ) -> Result<(), UnconfirmedPoolError> {
+        let ptx = self
+            .txs_by_signature
+            .get(signature)
+            .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+        let descendant_priority = FeePriority::try_from_fee_per_gram(&ptx.transaction, descendants.fee_per_gram())?;
+        if let Some(previous) = self.packages.remove(signature) {
+            self.txs_by_descendant_score.remove(&previous.descendant_priority);
+        }
+        self.txs_by_descendant_score
+            .insert(descendant_priority.clone(), signature.clone());
+        self.packages.insert(signature.clone(), PackageScores {
+            ancestors,
+            descendants,
+            descendant_priority,
+        });
+        Ok(())
+    }
+
+    // Recalculates the cached ancestor and descendant packages of the given transactions
+    fn update_packages(&mut self, signatures: &[Signature]) -> Result<(), UnconfirmedPoolError> {
+        for signature in signatures {
+            let ptx = match self.txs_by_signature.get(signature) {
+                Some(ptx) => ptx,
+                None => continue,
+            };
+            let ancestors = self.ancestor_package(ptx)?;
+            let descendants = self.descendant_package(signature);
+            self.store_packages(signature, ancestors, descendants)?;
+        }
+        Ok(())
+    }
+
+    // Caches the packages of a transaction that entered the pool and adds it to the descendant packages of the
+    // transactions it depends on. The packages of the other transactions in the pool are only recalculated if the
+    // transaction joins transactions that were already in the pool, e.g. a parent that arrives after its child.
+    fn add_to_packages(&mut self, signature: &Signature) -> Result<(), UnconfirmedPoolError> {
+        let ptx = self
+            .txs_by_signature
+            .get(signature)
+            .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+        let ancestors = self.depended_on_transactions(ptx).into_iter().collect::<Vec<_>>();
+        let mut descendants = self.with_descendants(&[signature.clone()]);
+        if descendants.len() > 1 {
+            descendants.extend(ancestors);
+            return self.update_packages(&descendants);
+        }
+
+        let ancestor_package = self.ancestor_package(ptx)?;
+        let mut descendant_package = PackageStats::default();
+        descendant_package.add(ptx);
+        self.store_packages(signature, ancestor_package, descendant_package)?;
+        for ancestor in &ancestors {
+            let mut scores = self
+                .packages
+                .get(ancestor)
+                .cloned()
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+            scores.descendants.add(&self.txs_by_signature[signature]);
+            self.store_packages(ancestor, scores.ancestors, scores.descendants)?;
+        }
+        Ok(())
+    }
+
+    // Removes a transaction that left the pool from the cached descendant packages of the transactions it depended on,
+    // and recalculates the ancestor packages of the transactions that depended on it
+    fn remove_from_packages(
+        &mut self,
+        removed: &PrioritizedTransaction,
+        ancestors: &[Signature],
+        descendants: &[Signature],
+    ) -> Result<(), UnconfirmedPoolError> {
+        for ancestor in ancestors {
+            if let Some(mut scores) = self.packages.get(ancestor).cloned() {
+                scores.descendants.remove(removed);
+                self.store_packages(ancestor, scores.ancestors, scores.descendants)?;
+            }
+        }
+        for descendant in descendants {
+            let ancestor_package = match self.txs_by_signature.get(descendant) {
+                Some(ptx) => self.ancestor_package(ptx)?,
+                None => continue,
+            };
+            if let Some(scores) = self.packages.get(descendant).cloned() {
+                self.store_packages(descendant, ancestor_package, scores.descendants)?;
+            }
         }
         Ok(())
     }
 
+    // Checks the replace-by-fee rules for a transaction that conflicts with the `conflicts` transactions in the pool,
+    // which would replace the `replaced` transactions
+    fn is_valid_replacement(
+        &self,
+        tx: &Transaction,
+        conflicts: &[Signature],
+        replaced: &[Signature],
+    ) -> Result<bool, UnconfirmedPoolError> {
+        // The replacement cannot spend the outputs of a transaction that it replaces
+        let spends_replaced_output = replaced.iter().any(|signature| {
+            self.txs_by_signature.get(signature).map_or(false, |ptx| {
+                ptx.transaction.body.outputs().iter().any(|output| {
+                    tx.body
+                        .inputs()
+                        .iter()
+                        .any(|input| input.output_hash() == output.hash())
+                })
+            })
+        });
+        if spends_replaced_output {
+            return Ok(false);
+        }
+
+        let mut replaced_fee = 0u64;
+        for signature in replaced {
+            let ptx = self
+                .txs_by_signature
+                .get(signature)
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+            replaced_fee += ptx.transaction.body.get_total_fee().0;
+        }
+        if tx.body.get_total_fee().0 <= replaced_fee {
+            return Ok(false);
+        }
+
+        let fee_per_gram = tx.calculate_ave_fee_per_gram();
+        for signature in conflicts {
+            let ptx = self
+                .txs_by_signature
+                .get(signature)
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+            let conflict_fee_per_gram = ptx.transaction.calculate_ave_fee_per_gram();
+            let increment = self.config.min_replacement_fee_per_gram_increment as f64;
+            if fee_per_gram <= conflict_fee_per_gram || fee_per_gram < conflict_fee_per_gram + increment {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
     /// TThis will search the unconfirmed pool for the set of outputs and return true if all of them are found
     pub fn verify_outputs_exist(&mut self, outputs: &[HashOutput]) -> bool {
         for hash in outputs {
@@ -168,19 +821,41 @@ impl UnconfirmedPool {
         self.txs_by_signature.contains_key(excess_sig)
     }
 
-    /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block
+    /// Returns a set of the highest priority unconfirmed transactions, that can be included in a block. Transactions
+    /// are ranked by the fee per gram of the package formed with their unconfirmed ancestors that have not been
+    /// selected yet.
     pub fn highest_priority_txs(&mut self, total_weight: u64) -> Result<RetrieveResults, UnconfirmedPoolError> {
         let mut selected_txs = HashMap::new();
         let mut curr_weight: u64 = 0;
         let mut curr_skip_count: usize = 0;
         let mut transactions_to_remove_and_recheck = Vec::new();
-        for (_, tx_key) in self.txs_by_priority.iter().rev() {
-            if selected_txs.contains_key(tx_key) {
+        // Rank every transaction by the fee per gram of its ancestor package, so that a high fee child pulls its low
+        // fee parents into the selection ahead of transactions that pay less per gram than the package as a whole
+        let mut packages = HashMap::with_capacity(self.txs_by_signature.len());
+        let mut txs_by_package_priority = BTreeMap::new();
+        for (tx_key, prioritized_transaction) in &self.txs_by_signature {
+            let package = self
+                .packages
+                .get(tx_key)
+                .ok_or(UnconfirmedPoolError::StorageOutofSync)?
+                .ancestors;
+            let priority =
+                FeePriority::try_from_fee_per_gram(&prioritized_transaction.transaction, package.fee_per_gram())?;
+            txs_by_package_priority.insert(priority.clone(), tx_key.clone());
+            packages.insert(tx_key.clone(), (priority, package));
+        }
+        while let Some((priority, tx_key)) = txs_by_package_priority
+            .iter()
+            .next_back()
+            .map(|(priority, tx_key)| (priority.clone(), tx_key.clone()))
+        {
+            txs_by_package_priority.remove(&priority);
+            if selected_txs.contains_key(&tx_key) {
                 continue;
             }
             let prioritized_transaction = self
                 .txs_by_signature
-                .get(tx_key)
+                .get(&tx_key)
                 .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
 
             let mut total_transaction_weight = 0;
@@ -198,9 +873,38 @@ impl UnconfirmedPool {
             {
                 if !UnconfirmedPool::find_duplicate_input(&selected_txs, &potential_transactions_to_insert) {
                     curr_weight += total_transaction_weight;
+                    let newly_selected = potential_transactions_to_insert.keys().cloned().collect::<Vec<_>>();
                     for (key, transaction) in potential_transactions_to_insert {
                         selected_txs.insert((key).clone(), transaction.transaction.clone());
                     }
+                    // The selected transactions no longer count towards the packages of their descendants
+                    for key in &newly_selected {
+                        let selected = self
+                            .txs_by_signature
+                            .get(key)
+                            .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+                        for descendant in self.with_descendants(&[key.clone()]) {
+                            if selected_txs.contains_key(&descendant) {
+                                continue;
+                            }
+                            if let Some((priority, package)) = packages.get_mut(&descendant) {
+                                package.remove(selected);
+                                let descendant_transaction = self
+                                    .txs_by_signature
+                                    .get(&descendant)
+                                    .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+                                let updated_priority = FeePriority::try_from_fee_per_gram(
+                                    &descendant_transaction.transaction,
+                                    package.fee_per_gram(),
+                                )?;
+                                // Transactions that have already been skipped are not ranked again
+                                if txs_by_package_priority.remove(priority).is_some() {
+                                    txs_by_package_priority.insert(updated_priority.clone(), descendant.clone());
+                                }
+                                *priority = updated_priority;
+                            }
+                        }
+                    }
                 }
             } else {
                 transactions_to_remove_and_recheck.append(&mut potential_transactions_to_remove_and_recheck);
@@ -327,7 +1031,12 @@ impl UnconfirmedPool {
             .map(|(_key, val)| val.transaction)
             .collect();
         self.txs_by_priority.clear();
+        self.txs_by_descendant_score.clear();
         self.txs_by_output.clear();
+        self.txs_by_input.clear();
+        self.txs_by_dependency.clear();
+        self.packages.clear();
+        self.total_weight = 0;
 
         mempool_txs
     }
@@ -406,8 +1115,20 @@ impl UnconfirmedPool {
 
     // Helper function to ensure that all transactions are safely deleted in order and from all storage
     fn delete_transaction(&mut self, signature: &Signature) -> Option<Arc<Transaction>> {
+        // The packages of the ancestors and descendants of the transaction change once it leaves the pool
+        let ancestors = self
+            .txs_by_signature
+            .get(signature)
+            .map(|ptx| self.depended_on_transactions(ptx).into_iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let mut descendants = self.with_descendants(&[signature.clone()]);
+        descendants.retain(|descendant| descendant != signature);
         if let Some(prioritized_transaction) = self.txs_by_signature.remove(signature) {
             self.txs_by_priority.remove(&prioritized_transaction.priority);
+            if let Some(package) = self.packages.remove(signature) {
+                self.txs_by_descendant_score.remove(&package.descendant_priority);
+            }
+            self.total_weight -= prioritized_transaction.weight;
             for output in prioritized_transaction.transaction.as_ref().body.outputs() {
                 let key = output.hash();
                 if let Some(signatures) = self.txs_by_output.get_mut(&key) {
@@ -417,6 +1138,28 @@ impl UnconfirmedPool {
                     }
                 }
             }
+            for input in prioritized_transaction.transaction.body.inputs() {
+                let key = input.output_hash();
+                if self.txs_by_input.get(&key) == Some(signature) {
+                    self.txs_by_input.remove(&key);
+                }
+            }
+            for output_hash in &prioritized_transaction.depended_output_hashes {
+                if let Some(signatures) = self.txs_by_dependency.get_mut(output_hash) {
+                    signatures.retain(|x| x != signature);
+                    if signatures.is_empty() {
+                        self.txs_by_dependency.remove(output_hash);
+                    }
+                }
+            }
+            if let Err(e) = self.remove_from_packages(&prioritized_transaction, &ancestors, &descendants) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not update the packages of the transactions related to {} in the unconfirmed pool: {}",
+                    signature.get_signature().to_hex(),
+                    e
+                );
+            }
             trace!(
                 target: LOG_TARGET,
                 "Deleted transaction: {}",
@@ -453,17 +1196,67 @@ impl UnconfirmedPool {
             .collect()
     }
 
+    /// Returns all transactions stored in the UnconfirmedPool along with the time that they have been waiting in the
+    /// pool.
+    pub fn snapshot_with_age(&self) -> Vec<(Arc<Transaction>, Duration)> {
+        self.txs_by_signature
+            .values()
+            .map(|ptx| (ptx.transaction.clone(), self.elapsed_since(ptx.inserted_at)))
+            .collect()
+    }
+
+    /// Returns true if a transaction that has been waiting for the given time has expired.
+    pub fn is_expired(&self, age: Duration) -> bool {
+        age >= self.config.tx_expiry
+    }
+
+    /// Sets the time at which the transaction entered the pool to the given time ago, e.g. when it is restored after a
+    /// restart, so that it still expires at the same time.
+    pub fn set_age(&mut self, signature: &Signature, age: Duration) {
+        let now = self.clock.now();
+        if let Some(ptx) = self.txs_by_signature.get_mut(signature) {
+            ptx.inserted_at = now.checked_sub(age).unwrap_or(ptx.inserted_at);
+        }
+    }
+
     /// Returns the total weight of all transactions stored in the pool.
     pub fn calculate_weight(&self) -> u64 {
-        self.txs_by_signature
-            .iter()
-            .fold(0, |weight, (_, ptx)| weight + ptx.transaction.calculate_weight())
+        self.total_weight
     }
 
     #[cfg(test)]
     /// Returns false if there are any inconsistencies in the internal mempool state, otherwise true
     fn check_status(&self) -> bool {
-        if self.txs_by_priority.len() != self.txs_by_signature.len() {
+        if self.txs_by_priority.len() != self.txs_by_signature.len() ||
+            self.txs_by_descendant_score.len() != self.txs_by_signature.len() ||
+            self.packages.len() != self.txs_by_signature.len()
+        {
+            return false;
+        }
+        if !self
+            .txs_by_descendant_score
+            .iter()
+            .all(|(priority, tx_key)| self.packages.get(tx_key).map(|p| &p.descendant_priority) == Some(priority))
+        {
+            return false;
+        }
+        if self.txs_by_signature.values().map(|ptx| ptx.weight).sum::<u64>() != self.total_weight {
+            return false;
+        }
+        // The cached packages are updated incrementally, so they must match packages calculated from scratch
+        if !self.txs_by_signature.iter().all(|(tx_key, ptx)| {
+            self.packages.get(tx_key).map_or(false, |package| {
+                Some(package.ancestors) == self.ancestor_package(ptx).ok() &&
+                    package.descendants == self.descendant_package(tx_key)
+            })
+        }) {
+            return false;
+        }
+        if !self
+            .txs_by_input
+            .values()
+            .all(|tx_key| self.txs_by_signature.contains_key(tx_key))
+        {
             return false;
         }
         self.txs_by_priority
@@ -488,8 +1281,27 @@ mod test {
         },
         tx,
     };
+    use std::sync::Mutex;
     use tari_common::configuration::Network;
 
+    struct TestClock(Mutex<Instant>);
+
+    impl TestClock {
+        fn new() -> Self {
+            Self(Mutex::new(Instant::now()))
+        }
+
+        fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
     #[test]
     fn test_find_duplicate_input() {
         let tx1 = Arc::new(tx!(MicroTari(5000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
@@ -525,8 +1337,12 @@ mod test {
         let tx5 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(55), inputs: 5, outputs: 1).0);
 
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
-            storage_capacity: 4,
+            max_total_weight: tx1.calculate_weight() +
+                tx3.calculate_weight() +
+                tx4.calculate_weight() +
+                tx5.calculate_weight(),
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone(), tx5.clone()])
@@ -592,21 +1408,366 @@ mod test {
         let tx3 = Arc::new(tx3);
 
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
-            storage_capacity: 4,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
 
-        unconfirmed_pool
-            .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone()])
-            .unwrap();
-        assert_eq!(unconfirmed_pool.len(), 3);
+        unconfirmed_pool.insert_txs(vec![tx1.clone(), tx2.clone()]).unwrap();
+        // tx3 pays the same fee per gram as tx2, so it cannot replace it
+        assert_eq!(
+            unconfirmed_pool.insert(tx3.clone(), None).unwrap(),
+            TxStorageResponse::NotStoredFeeTooLow
+        );
+        assert_eq!(unconfirmed_pool.len(), 2);
 
         let desired_weight = tx1.calculate_weight() + tx2.calculate_weight() + tx3.calculate_weight() + 1000;
         let results = unconfirmed_pool.highest_priority_txs(desired_weight).unwrap();
         assert!(results.retrieved_transactions.contains(&tx1));
-        // Whether tx2 or tx3 is selected is non-deterministic
-        assert!(results.retrieved_transactions.contains(&tx2) ^ results.retrieved_transactions.contains(&tx3));
+        assert!(results.retrieved_transactions.contains(&tx2));
+        assert_eq!(results.retrieved_transactions.len(), 2);
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_replace_by_fee() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(30), inputs: 2, outputs: 1).0);
+        // tx3 spends an input of tx1 and an input of tx2 and pays more than both
+        let mut tx3 = tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 2, outputs: 1).0;
+        tx3.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+        tx3.body.inputs_mut()[1] = tx2.body.inputs()[1].clone();
+        let tx3 = Arc::new(tx3);
+        // tx4 spends the same input as tx1 but does not pay enough more per gram
+        let mut tx4 = tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0;
+        tx4.body.inputs_mut()[0] = tx1.body.inputs()[0].clone();
+        let tx4 = Arc::new(tx4);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            weight_tx_skip_count: 3,
+            min_replacement_fee_per_gram_increment: 5,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert_txs(vec![tx1.clone(), tx2.clone()]).unwrap();
+        assert_eq!(
+            unconfirmed_pool.insert(tx4.clone(), None).unwrap(),
+            TxStorageResponse::NotStoredFeeTooLow
+        );
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+
+        assert_eq!(
+            unconfirmed_pool.insert(tx3.clone(), None).unwrap(),
+            TxStorageResponse::UnconfirmedPoolReplaced
+        );
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+        // A replaced transaction cannot return without outbidding its replacement
+        assert_eq!(
+            unconfirmed_pool.insert(tx1.clone(), None).unwrap(),
+            TxStorageResponse::NotStoredFeeTooLow
+        );
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_child_pays_for_parent() {
+        let parent = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let child = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(200), inputs: 2, outputs: 1).0);
+        let other = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 2, outputs: 1).0);
+        let child_dependencies = Some(vec![parent.body.outputs()[0].hash()]);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), other.clone()])
+            .unwrap();
+        unconfirmed_pool
+            .insert(child.clone(), child_dependencies.clone())
+            .unwrap();
+
+        // The package of parent and child pays more per gram than the other transaction, so the child lifts its
+        // parent into the block
+        let desired_weight = parent.calculate_weight() + child.calculate_weight();
+        let results = unconfirmed_pool.highest_priority_txs(desired_weight).unwrap();
         assert_eq!(results.retrieved_transactions.len(), 2);
+        assert!(results.retrieved_transactions.contains(&parent));
+        assert!(results.retrieved_transactions.contains(&child));
+
+        // Two independent transactions that each pay more per gram than the package are preferred over it, even though
+        // the child on its own pays the most
+        let other2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(150), inputs: 2, outputs: 1).0);
+        let other3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(150), inputs: 2, outputs: 1).0);
+        unconfirmed_pool
+            .insert_txs(vec![other2.clone(), other3.clone()])
+            .unwrap();
+        let desired_weight = other2.calculate_weight() + other3.calculate_weight();
+        let results = unconfirmed_pool.highest_priority_txs(desired_weight).unwrap();
+        assert_eq!(results.retrieved_transactions.len(), 2);
+        assert!(results.retrieved_transactions.contains(&other2));
+        assert!(results.retrieved_transactions.contains(&other3));
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_package_scores_are_maintained() {
+        let parent = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let child = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(200), inputs: 2, outputs: 1).0);
+        let parent_key = parent.first_kernel_excess_sig().unwrap().clone();
+        let child_key = child.first_kernel_excess_sig().unwrap().clone();
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        unconfirmed_pool.insert(parent.clone(), None).unwrap();
+        unconfirmed_pool
+            .insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]))
+            .unwrap();
+
+        let parent_package = unconfirmed_pool.packages[&parent_key].clone();
+        assert_eq!(parent_package.ancestors.count, 1);
+        assert_eq!(parent_package.descendants.count, 2);
+        assert_eq!(parent_package.descendants.fee, 220);
+        let child_package = unconfirmed_pool.packages[&child_key].clone();
+        assert_eq!(child_package.ancestors.count, 2);
+        assert_eq!(child_package.ancestors.fee, 220);
+        assert_eq!(child_package.descendants.count, 1);
+
+        // Once the parent is mined, the child no longer carries it in its package
+        unconfirmed_pool.delete_transaction(&parent_key);
+        let child_package = unconfirmed_pool.packages[&child_key].clone();
+        assert_eq!(child_package.ancestors.count, 1);
+        assert_eq!(child_package.ancestors.fee, 200);
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_selected_ancestors_are_not_counted_twice() {
+        let parent = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let child1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(200), inputs: 2, outputs: 1).0);
+        let child2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(100), inputs: 2, outputs: 1).0);
+        let other = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(80), inputs: 2, outputs: 1).0);
+        let dependencies = Some(vec![parent.body.outputs()[0].hash()]);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), other.clone()])
+            .unwrap();
+        unconfirmed_pool.insert(child1.clone(), dependencies.clone()).unwrap();
+        unconfirmed_pool.insert(child2.clone(), dependencies).unwrap();
+
+        // The package of parent and child2 pays less per gram than the other transaction, but once the parent has been
+        // selected along with child1, child2 only has to pay for itself
+        let desired_weight = parent.calculate_weight() + child1.calculate_weight() + child2.calculate_weight();
+        let results = unconfirmed_pool.highest_priority_txs(desired_weight).unwrap();
+        assert_eq!(results.retrieved_transactions.len(), 3);
+        assert!(results.retrieved_transactions.contains(&parent));
+        assert!(results.retrieved_transactions.contains(&child1));
+        assert!(results.retrieved_transactions.contains(&child2));
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_child_pays_for_parent_when_full() {
+        let parent = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let child = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(200), inputs: 2, outputs: 1).0);
+        let other = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let new = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(60), inputs: 2, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            max_total_weight: parent.calculate_weight() + child.calculate_weight() + other.calculate_weight(),
+            weight_tx_skip_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_txs(vec![parent.clone(), other.clone()])
+            .unwrap();
+        unconfirmed_pool
+            .insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]))
+            .unwrap();
+
+        // The parent pays the least on its own, but the child pays for it, so the other transaction is evicted instead
+        assert_eq!(
+            unconfirmed_pool.insert(new.clone(), None).unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(unconfirmed_pool.len(), 3);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&child.body.kernels()[0].excess_sig));
+        assert_eq!(unconfirmed_pool.recently_evicted(), vec![EvictedTransaction {
+            excess_sig: other.body.kernels()[0].excess_sig.clone(),
+            reason: EvictionReason::PoolFull,
+        }]);
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_ancestor_limits() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            weight_tx_skip_count: 3,
+            max_ancestor_count: 2,
+            ..Default::default()
+        });
+        assert_eq!(
+            unconfirmed_pool.insert(tx1.clone(), None).unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx2.clone(), Some(vec![tx1.body.outputs()[0].hash()]))
+                .unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        // tx3 would form a chain of three unconfirmed transactions
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx3.clone(), Some(vec![tx2.body.outputs()[0].hash()]))
+                .unwrap(),
+            TxStorageResponse::NotStored
+        );
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            weight_tx_skip_count: 3,
+            max_ancestor_weight: tx1.calculate_weight() + tx2.calculate_weight() - 1,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert(tx1.clone(), None).unwrap();
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx2.clone(), Some(vec![tx1.body.outputs()[0].hash()]))
+                .unwrap(),
+            TxStorageResponse::NotStored
+        );
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_descendant_limit() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 2).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+        let tx4 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            weight_tx_skip_count: 3,
+            max_descendant_count: 3,
+            ..Default::default()
+        });
+        unconfirmed_pool.insert(tx1.clone(), None).unwrap();
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx2.clone(), Some(vec![tx1.body.outputs()[0].hash()]))
+                .unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx3.clone(), Some(vec![tx1.body.outputs()[1].hash()]))
+                .unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        // tx4 would be the fourth transaction in the descendant package of tx1
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx4.clone(), Some(vec![tx2.body.outputs()[0].hash()]))
+                .unwrap(),
+            TxStorageResponse::NotStored
+        );
+        assert!(unconfirmed_pool.check_status());
+
+        // Once a descendant leaves the pool, the package of tx1 has room again
+        unconfirmed_pool.delete_transaction(&tx3.body.kernels()[0].excess_sig);
+        assert!(unconfirmed_pool.check_status());
+        assert_eq!(
+            unconfirmed_pool
+                .insert(tx4.clone(), Some(vec![tx2.body.outputs()[0].hash()]))
+                .unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(unconfirmed_pool.len(), 3);
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_weight_limit_and_fee_floor() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+        let tx3 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(10), inputs: 2, outputs: 1).0);
+        let tx4 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(40), inputs: 2, outputs: 1).0);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            max_total_weight: tx1.calculate_weight(),
+            weight_tx_skip_count: 3,
+            fee_floor_increment: 0,
+            ..Default::default()
+        });
+        assert_eq!(
+            unconfirmed_pool.insert(tx1.clone(), None).unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(unconfirmed_pool.min_fee_per_gram(), 0.0);
+        // The pool is full, so tx1 is evicted to make space for the higher priority tx2
+        assert_eq!(
+            unconfirmed_pool.insert(tx2.clone(), None).unwrap(),
+            TxStorageResponse::UnconfirmedPool
+        );
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert_eq!(unconfirmed_pool.calculate_weight(), tx2.calculate_weight());
+        assert_eq!(unconfirmed_pool.recently_evicted(), vec![EvictedTransaction {
+            excess_sig: tx1.body.kernels()[0].excess_sig.clone(),
+            reason: EvictionReason::PoolFull,
+        }]);
+        // The eviction raised the fee floor to the fee per gram of tx1
+        assert!(unconfirmed_pool.min_fee_per_gram() > tx3.calculate_ave_fee_per_gram());
+        assert_eq!(
+            unconfirmed_pool.insert(tx3.clone(), None).unwrap(),
+            TxStorageResponse::NotStoredBelowMinimumFee
+        );
+        // tx4 pays enough to enter the pool, but less than the transaction it would have to evict
+        assert_eq!(
+            unconfirmed_pool.insert(tx4.clone(), None).unwrap(),
+            TxStorageResponse::NotStored
+        );
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.check_status());
+    }
+
+    #[test]
+    fn test_remove_expired_transactions() {
+        let tx1 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+        let tx2 = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 2, outputs: 1).0);
+
+        let clock = Arc::new(TestClock::new());
+        let mut unconfirmed_pool = UnconfirmedPool::with_clock(
+            UnconfirmedPoolConfig {
+                tx_expiry: Duration::from_secs(60),
+                ..Default::default()
+            },
+            clock.clone(),
+        );
+        unconfirmed_pool.insert(tx1.clone(), None).unwrap();
+        clock.advance(Duration::from_secs(30));
+        unconfirmed_pool.insert(tx2.clone(), None).unwrap();
+        assert!(unconfirmed_pool.remove_expired_transactions().is_empty());
+        clock.advance(Duration::from_secs(30));
+
+        let removed = unconfirmed_pool.remove_expired_transactions();
+        assert_eq!(removed, vec![tx1.clone()]);
+        assert_eq!(unconfirmed_pool.len(), 1);
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+        assert_eq!(unconfirmed_pool.recently_evicted(), vec![EvictedTransaction {
+            excess_sig: tx1.body.kernels()[0].excess_sig.clone(),
+            reason: EvictionReason::Expired,
+        }]);
+        assert!(unconfirmed_pool.check_status());
     }
 
     #[test]
@@ -621,8 +1782,8 @@ mod test {
         let tx6 = Arc::new(tx!(MicroTari(10_000), fee: MicroTari(75), inputs:2, outputs: 1).0);
 
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
-            storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_txs(vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone(), tx5.clone()])
@@ -668,8 +1829,8 @@ mod test {
         let tx6 = Arc::new(tx6);
 
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
-            storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_txs(vec![
@@ -709,11 +1870,14 @@ mod test {
         let (tx6, _, _) = tx!(MicroTari(450_000), fee: MicroTari(50), inputs:5, outputs:5);
         tx3.body.set_kernel(tx5.body.kernels()[0].clone());
         tx4.body.set_kernel(tx6.body.kernels()[0].clone());
+        // Spend different inputs so that the transactions do not replace each other
+        *tx3.body.inputs_mut() = tx5.body.inputs().clone();
+        *tx4.body.inputs_mut() = tx6.body.inputs().clone();
 
         // Insert multiple transactions with the same outputs into the mempool
         let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
-            storage_capacity: 10,
             weight_tx_skip_count: 3,
+            ..Default::default()
         });
         let txns = vec![
             Arc::new(tx1.clone()),
@@ -22,7 +22,7 @@
 
 #[cfg(feature = "base_node")]
 use crate::proof_of_work::monero_rx::MergeMineError;
-use crate::proof_of_work::Difficulty;
+use crate::proof_of_work::{Difficulty, PowAlgorithm};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -33,6 +33,8 @@ pub enum PowError {
     AchievedDifficultyTooLow { target: Difficulty, achieved: Difficulty },
     #[error("Invalid target difficulty (expected: {expected}, got: {got})")]
     InvalidTargetDifficulty { expected: Difficulty, got: Difficulty },
+    #[error("Proof of work algorithm {0} is not supported")]
+    UnsupportedAlgorithm(PowAlgorithm),
     #[cfg(feature = "base_node")]
     #[error("Invalid merge mining data or operation: {0}")]
     MergeMineError(#[from] MergeMineError),
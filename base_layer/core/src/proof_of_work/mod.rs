@@ -48,10 +48,10 @@ pub use proof_of_work_algorithm::PowAlgorithm;
 
 #[cfg(feature = "base_node")]
 mod sha3_pow;
-#[cfg(feature = "base_node")]
-pub use sha3_pow::sha3_difficulty;
 #[cfg(all(test, feature = "base_node"))]
 pub use sha3_pow::test as sha3_test;
+#[cfg(feature = "base_node")]
+pub use sha3_pow::{sha3_difficulty, Sha3Pow};
 
 #[cfg(feature = "base_node")]
 mod target_difficulty;
@@ -63,3 +63,8 @@ pub mod lwma_diff;
 
 #[cfg(feature = "base_node")]
 pub mod randomx_factory;
+
+#[cfg(feature = "base_node")]
+mod pow_algorithm_registry;
+#[cfg(feature = "base_node")]
+pub use pow_algorithm_registry::{PowAlgorithmRegistry, ProofOfWorkAlgorithm};
@@ -42,11 +42,19 @@ pub const LOG_TARGET: &str = "c::pow::monero_rx";
 ///  Calculates the achieved Monero difficulty for the `BlockHeader`. An error is returned if the BlockHeader does not
 /// contain valid Monero PoW data.
 pub fn monero_difficulty(header: &BlockHeader, randomx_factory: &RandomXFactory) -> Result<Difficulty, MergeMineError> {
+    monero_difficulty_with_hash(header, randomx_factory).map(|(diff, _)| diff)
+}
+
+/// Calculates the achieved Monero difficulty for the `BlockHeader`, along with the RandomX hash it was derived from.
+pub fn monero_difficulty_with_hash(
+    header: &BlockHeader,
+    randomx_factory: &RandomXFactory,
+) -> Result<(Difficulty, Vec<u8>), MergeMineError> {
     let monero_pow_data = verify_header(&header)?;
     debug!(target: LOG_TARGET, "Valid Monero data: {:?}", monero_pow_data);
     let blockhashing_blob = monero_pow_data.to_blockhashing_blob();
     let vm = randomx_factory.create(monero_pow_data.randomx_key())?;
-    get_random_x_difficulty(&blockhashing_blob, &vm).map(|(diff, _)| diff)
+    get_random_x_difficulty(&blockhashing_blob, &vm)
 }
 
 fn get_random_x_difficulty(input: &[u8], vm: &RandomXVMInstance) -> Result<(Difficulty, Vec<u8>), MergeMineError> {
@@ -31,6 +31,7 @@ pub use helpers::{
     deserialize_monero_block_from_hex,
     extract_tari_hash,
     monero_difficulty,
+    monero_difficulty_with_hash,
     serialize_monero_block_to_hex,
 };
 
@@ -43,6 +44,9 @@ pub use pow_data::MoneroPowData;
 mod merkle_tree;
 pub use merkle_tree::{create_merkle_proof, tree_hash};
 
+mod randomx_pow;
+pub use randomx_pow::RandomXPow;
+
 // Re-exports
 pub use monero::{
     consensus::{deserialize, serialize},
@@ -0,0 +1,79 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use super::{monero_difficulty, monero_difficulty_with_hash, MoneroPowData};
+use crate::{
+    blocks::{BlockHeader, BlockHeaderValidationError},
+    chain_storage::BlockchainBackend,
+    consensus::ConsensusManager,
+    proof_of_work::{randomx_factory::RandomXFactory, Difficulty, PowAlgorithm, PowError, ProofOfWorkAlgorithm},
+    validation::ValidationError,
+};
+
+/// The RandomX proof of work algorithm, merge mined with Monero. The Monero block header, coinbase and merkle proof
+/// are carried in the header's `pow_data`.
+#[derive(Clone, Default)]
+pub struct RandomXPow {
+    randomx_factory: RandomXFactory,
+}
+
+impl RandomXPow {
+    pub fn new(randomx_factory: RandomXFactory) -> Self {
+        Self { randomx_factory }
+    }
+}
+
+impl ProofOfWorkAlgorithm for RandomXPow {
+    fn pow_algo(&self) -> PowAlgorithm {
+        PowAlgorithm::Monero
+    }
+
+    fn hash(&self, header: &BlockHeader) -> Result<Vec<u8>, PowError> {
+        let (_, hash) = monero_difficulty_with_hash(header, &self.randomx_factory)?;
+        Ok(hash)
+    }
+
+    fn achieved_difficulty(&self, header: &BlockHeader) -> Result<Difficulty, PowError> {
+        Ok(monero_difficulty(header, &self.randomx_factory)?)
+    }
+
+    fn validate_pow_data(
+        &self,
+        header: &BlockHeader,
+        rules: &ConsensusManager,
+        db: &dyn BlockchainBackend,
+    ) -> Result<(), ValidationError> {
+        let monero_data =
+            MoneroPowData::from_header(header).map_err(|e| ValidationError::CustomError(e.to_string()))?;
+        let seed_height = db.fetch_monero_seed_first_seen_height(&monero_data.randomx_key)?;
+        if (seed_height != 0) &&
+            (header.height.saturating_sub(seed_height) >
+                rules.consensus_constants(header.height).max_randomx_seed_height())
+        {
+            return Err(ValidationError::BlockHeaderError(
+                BlockHeaderValidationError::OldSeedHash,
+            ));
+        }
+
+        Ok(())
+    }
+}
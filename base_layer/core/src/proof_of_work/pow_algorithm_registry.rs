@@ -0,0 +1,221 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{
+    blocks::BlockHeader,
+    chain_storage::BlockchainBackend,
+    consensus::{ConsensusConstants, ConsensusManager},
+    proof_of_work::{
+        monero_rx::RandomXPow,
+        randomx_factory::RandomXFactory,
+        Difficulty,
+        PowAlgorithm,
+        PowError,
+        Sha3Pow,
+    },
+    validation::ValidationError,
+};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// A proof of work algorithm that blocks can be mined with. Implementations provide everything the validators need to
+/// know about an algorithm, so adding a new one does not require touching each validation site.
+pub trait ProofOfWorkAlgorithm: Send + Sync {
+    /// The algorithm identifier carried in the `pow_algo` field of headers mined with this algorithm
+    fn pow_algo(&self) -> PowAlgorithm;
+
+    /// Returns the proof of work hash of the header that the achieved difficulty is derived from
+    fn hash(&self, header: &BlockHeader) -> Result<Vec<u8>, PowError>;
+
+    /// Returns the difficulty achieved by the header
+    fn achieved_difficulty(&self, header: &BlockHeader) -> Result<Difficulty, PowError>;
+
+    /// Validates the algorithm specific `pow_data` of the header against the consensus rules and the chain state
+    fn validate_pow_data(
+        &self,
+        header: &BlockHeader,
+        rules: &ConsensusManager,
+        db: &dyn BlockchainBackend,
+    ) -> Result<(), ValidationError>;
+
+    /// The target block interval in seconds used by the difficulty adjustment for this algorithm
+    fn target_block_interval(&self, constants: &ConsensusConstants) -> u64 {
+        constants.get_diff_target_block_interval(self.pow_algo())
+    }
+}
+
+/// The set of proof of work algorithms known to the node, keyed by their `PowAlgorithm` identifier. Identifiers are
+/// open ended, so an algorithm is added by registering an implementation with an unused id and adding its
+/// `PowAlgorithmConstants` to the consensus constants. No new `PowAlgorithm` variants or proto changes are needed.
+#[derive(Clone)]
+pub struct PowAlgorithmRegistry {
+    algorithms: HashMap<PowAlgorithm, Arc<dyn ProofOfWorkAlgorithm>>,
+}
+
+impl PowAlgorithmRegistry {
+    /// Creates a registry containing the built-in SHA3 and RandomX (Monero merge mined) algorithms
+    pub fn new(randomx_factory: RandomXFactory) -> Self {
+        let mut registry = Self {
+            algorithms: HashMap::new(),
+        };
+        registry.register(Arc::new(Sha3Pow));
+        registry.register(Arc::new(RandomXPow::new(randomx_factory)));
+        registry
+    }
+
+    /// Registers an algorithm, replacing any algorithm previously registered for the same `PowAlgorithm`
+    pub fn register(&mut self, algorithm: Arc<dyn ProofOfWorkAlgorithm>) {
+        self.algorithms.insert(algorithm.pow_algo(), algorithm);
+    }
+
+    /// Returns the algorithm registered for `pow_algo`, or `PowError::UnsupportedAlgorithm` if there is none
+    pub fn get(&self, pow_algo: PowAlgorithm) -> Result<&dyn ProofOfWorkAlgorithm, PowError> {
+        self.algorithms
+            .get(&pow_algo)
+            .map(|algorithm| algorithm.as_ref())
+            .ok_or(PowError::UnsupportedAlgorithm(pow_algo))
+    }
+
+    pub fn contains(&self, pow_algo: PowAlgorithm) -> bool {
+        self.algorithms.contains_key(&pow_algo)
+    }
+}
+
+impl Default for PowAlgorithmRegistry {
+    fn default() -> Self {
+        Self::new(RandomXFactory::default())
+    }
+}
+
+impl fmt::Debug for PowAlgorithmRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.algorithms.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        consensus::{consensus_constants::PowAlgorithmConstants, ConsensusConstantsBuilder, ConsensusManagerBuilder},
+        proto,
+        test_helpers::blockchain::create_test_db,
+        validation::helpers::{check_pow_data, check_target_difficulty},
+    };
+    use std::convert::TryFrom;
+    use tari_common::configuration::Network;
+
+    const CUSTOM_POW: PowAlgorithm = PowAlgorithm::new(7);
+
+    struct FixedDifficultyPow(Difficulty);
+
+    impl ProofOfWorkAlgorithm for FixedDifficultyPow {
+        fn pow_algo(&self) -> PowAlgorithm {
+            CUSTOM_POW
+        }
+
+        fn hash(&self, _header: &BlockHeader) -> Result<Vec<u8>, PowError> {
+            Ok(vec![0u8; 32])
+        }
+
+        fn achieved_difficulty(&self, _header: &BlockHeader) -> Result<Difficulty, PowError> {
+            Ok(self.0)
+        }
+
+        fn validate_pow_data(
+            &self,
+            _header: &BlockHeader,
+            _rules: &ConsensusManager,
+            _db: &dyn BlockchainBackend,
+        ) -> Result<(), ValidationError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_registers_the_built_in_algorithms() {
+        let registry = PowAlgorithmRegistry::default();
+        assert!(registry.contains(PowAlgorithm::Sha3));
+        assert!(registry.contains(PowAlgorithm::Monero));
+        assert_eq!(registry.get(PowAlgorithm::Sha3).unwrap().pow_algo(), PowAlgorithm::Sha3);
+        assert_eq!(
+            registry.get(PowAlgorithm::Monero).unwrap().pow_algo(),
+            PowAlgorithm::Monero
+        );
+
+        let rules = ConsensusManagerBuilder::new(Network::LocalNet).build();
+        let constants = rules.consensus_constants(0);
+        assert_eq!(
+            registry
+                .get(PowAlgorithm::Sha3)
+                .unwrap()
+                .target_block_interval(constants),
+            constants.get_diff_target_block_interval(PowAlgorithm::Sha3)
+        );
+    }
+
+    #[test]
+    fn it_validates_headers_of_a_registered_custom_algorithm() {
+        let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+            .add_proof_of_work(CUSTOM_POW, PowAlgorithmConstants {
+                max_target_time: 1800,
+                min_difficulty: 1.into(),
+                max_difficulty: 1.into(),
+                target_time: 300,
+            })
+            .build();
+        let rules = ConsensusManagerBuilder::new(Network::LocalNet)
+            .with_consensus_constants(constants)
+            .build();
+        let db = create_test_db();
+        let mut header = BlockHeader::new(rules.consensus_constants(0).blockchain_version());
+        header.pow.pow_algo = CUSTOM_POW;
+        header.pow.pow_data = vec![1, 2, 3];
+
+        // The header survives the wire format without any changes to the proto definitions
+        let header = BlockHeader::try_from(proto::core::BlockHeader::from(header)).unwrap();
+        assert_eq!(header.pow.pow_algo, CUSTOM_POW);
+
+        let registry = PowAlgorithmRegistry::default();
+        assert!(matches!(
+            check_pow_data(&header, &registry, &rules, &db),
+            Err(ValidationError::ProofOfWorkError(PowError::UnsupportedAlgorithm(algo))) if algo == CUSTOM_POW
+        ));
+        assert!(check_target_difficulty(&header, 1_000.into(), &registry).is_err());
+
+        let mut registry = PowAlgorithmRegistry::default();
+        registry.register(Arc::new(FixedDifficultyPow(1_000.into())));
+        assert!(registry.contains(PowAlgorithm::Sha3));
+        assert!(registry.contains(PowAlgorithm::Monero));
+        check_pow_data(&header, &registry, &rules, &db).unwrap();
+        let achieved = check_target_difficulty(&header, 1_000.into(), &registry).unwrap();
+        assert_eq!(achieved.pow_algo(), CUSTOM_POW);
+        assert_eq!(achieved.achieved(), 1_000.into());
+        assert!(check_target_difficulty(&header, 1_001.into(), &registry).is_err());
+        assert_eq!(
+            registry
+                .get(CUSTOM_POW)
+                .unwrap()
+                .target_block_interval(rules.consensus_constants(0)),
+            300
+        );
+    }
+}
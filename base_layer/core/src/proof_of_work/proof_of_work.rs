@@ -63,22 +63,12 @@ impl ProofOfWork {
     #[allow(deprecated)]
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(256);
-        buf.put_u8(self.pow_algo as u8);
+        buf.put_u8(self.pow_algo.as_u8());
         buf.put_slice(&self.pow_data);
         buf
     }
 }
 
-impl Display for PowAlgorithm {
-    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
-        let algo = match self {
-            PowAlgorithm::Monero => "Monero",
-            PowAlgorithm::Sha3 => "Sha3",
-        };
-        fmt.write_str(&algo.to_string())
-    }
-}
-
 impl Display for ProofOfWork {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
         writeln!(fmt, "Mining algorithm: {}", self.pow_algo)?;
@@ -19,27 +19,50 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
-
-#[repr(u8)]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Hash, Eq)]
-pub enum PowAlgorithm {
-    Monero = 0,
-    Sha3 = 1,
-}
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::TryFrom, fmt};
+
+/// Identifies the proof of work algorithm a block was mined with. It is carried in the `pow_algo` field of headers.
+/// The built-in algorithms have named constants, other algorithms only need an unused id and an implementation
+/// registered in the [PowAlgorithmRegistry](crate::proof_of_work::PowAlgorithmRegistry).
+///
+/// The binary encoding is the same as that of the enum this replaced, so stored headers remain readable.
+#[derive(Clone, Copy, PartialEq, Hash, Eq)]
+pub struct PowAlgorithm(u8);
 
+#[allow(non_upper_case_globals)]
 impl PowAlgorithm {
+    pub const Monero: PowAlgorithm = PowAlgorithm(0);
+    pub const Sha3: PowAlgorithm = PowAlgorithm(1);
+
+    /// Returns the algorithm with the given id
+    pub const fn new(id: u8) -> Self {
+        PowAlgorithm(id)
+    }
+
     pub fn is_monero(&self) -> bool {
-        matches!(self, Self::Monero)
+        *self == Self::Monero
     }
 
     pub fn is_sha3(&self) -> bool {
-        matches!(self, Self::Sha3)
+        *self == Self::Sha3
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        self.0
     }
 
     pub fn as_u64(&self) -> u64 {
-        *self as u64
+        u64::from(self.0)
+    }
+
+    /// Returns the name of a built-in algorithm
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            Self::Monero => Some("Monero"),
+            Self::Sha3 => Some("Sha3"),
+            _ => None,
+        }
     }
 }
 
@@ -47,10 +70,77 @@ impl TryFrom<u64> for PowAlgorithm {
     type Error = String;
 
     fn try_from(v: u64) -> Result<Self, Self::Error> {
-        match v {
-            0 => Ok(PowAlgorithm::Monero),
-            1 => Ok(PowAlgorithm::Sha3),
-            _ => Err("Invalid PoWAlgorithm".into()),
+        u8::try_from(v)
+            .map(PowAlgorithm)
+            .map_err(|_| "Invalid PoWAlgorithm".into())
+    }
+}
+
+impl fmt::Display for PowAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "PowAlgorithm({})", self.0),
         }
     }
 }
+
+impl fmt::Debug for PowAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Serialize for PowAlgorithm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        if serializer.is_human_readable() {
+            match self.name() {
+                Some(name) => serializer.serialize_str(name),
+                None => serializer.serialize_str(&self.0.to_string()),
+            }
+        } else {
+            serializer.serialize_u32(u32::from(self.0))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PowAlgorithm {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            match s.as_str() {
+                "Monero" => Ok(Self::Monero),
+                "Sha3" => Ok(Self::Sha3),
+                _ => s.parse::<u8>().map(PowAlgorithm).map_err(de::Error::custom),
+            }
+        } else {
+            let id = u32::deserialize(deserializer)?;
+            u8::try_from(id).map(PowAlgorithm).map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn serialization_matches_the_enum_encoding() {
+        assert_eq!(bincode::serialize(&PowAlgorithm::Monero).unwrap(), vec![0, 0, 0, 0]);
+        assert_eq!(bincode::serialize(&PowAlgorithm::Sha3).unwrap(), vec![1, 0, 0, 0]);
+        let custom = PowAlgorithm::new(7);
+        let decoded: PowAlgorithm = bincode::deserialize(&bincode::serialize(&custom).unwrap()).unwrap();
+        assert_eq!(decoded, custom);
+
+        let mut map = HashMap::new();
+        map.insert(PowAlgorithm::Sha3, 1);
+        map.insert(custom, 2);
+        let json = serde_json::to_string(&map).unwrap();
+        assert!(json.contains("\"Sha3\""));
+        assert!(json.contains("\"7\""));
+        assert_eq!(serde_json::from_str::<HashMap<PowAlgorithm, i32>>(&json).unwrap(), map);
+    }
+}
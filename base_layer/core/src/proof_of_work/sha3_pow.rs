@@ -22,7 +22,16 @@
 
 use crate::{
     blocks::BlockHeader,
-    proof_of_work::{difficulty::util::big_endian_difficulty, Difficulty},
+    chain_storage::BlockchainBackend,
+    consensus::ConsensusManager,
+    proof_of_work::{
+        difficulty::util::big_endian_difficulty,
+        Difficulty,
+        PowAlgorithm,
+        PowError,
+        ProofOfWorkAlgorithm,
+    },
+    validation::ValidationError,
 };
 use sha3::{Digest, Sha3_256};
 use tari_crypto::tari_utilities::ByteArray;
@@ -63,6 +72,38 @@ fn sha3_difficulty_with_hash(header: &BlockHeader) -> (Difficulty, Vec<u8>) {
     (difficulty, hash.to_vec())
 }
 
+/// The SHA3 proof of work algorithm. SHA3 blocks do not carry any additional proof of work data.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha3Pow;
+
+impl ProofOfWorkAlgorithm for Sha3Pow {
+    fn pow_algo(&self) -> PowAlgorithm {
+        PowAlgorithm::Sha3
+    }
+
+    fn hash(&self, header: &BlockHeader) -> Result<Vec<u8>, PowError> {
+        Ok(sha3_difficulty_with_hash(header).1)
+    }
+
+    fn achieved_difficulty(&self, header: &BlockHeader) -> Result<Difficulty, PowError> {
+        Ok(sha3_difficulty(header))
+    }
+
+    fn validate_pow_data(
+        &self,
+        header: &BlockHeader,
+        _rules: &ConsensusManager,
+        _db: &dyn BlockchainBackend,
+    ) -> Result<(), ValidationError> {
+        if !header.pow.pow_data.is_empty() {
+            return Err(ValidationError::CustomError(
+                "Proof of work data must be empty for Sha3 blocks".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use crate::{
@@ -22,14 +22,15 @@
 
 use super::core as proto;
 use crate::{
-    blocks::{Block, NewBlock, NewBlockHeaderTemplate, NewBlockTemplate},
+    blocks::{Block, CompactBlock, NewBlock, NewBlockHeaderTemplate, NewBlockTemplate},
     chain_storage::{BlockHeaderAccumulatedData, HistoricalBlock},
     proof_of_work::ProofOfWork,
+    proto::utils::try_convert_short_ids,
     transactions::types::BlindingFactor,
 };
 use std::convert::{TryFrom, TryInto};
 use tari_common_types::types::BLOCK_HASH_LENGTH;
-use tari_crypto::tari_utilities::ByteArray;
+use tari_crypto::tari_utilities::{convert::try_convert_all, ByteArray};
 
 //---------------------------------- Block --------------------------------------------//
 
@@ -234,14 +235,36 @@ impl TryFrom<proto::NewBlock> for NewBlock {
             ));
         }
 
-        Ok(Self { block_hash })
+        let compact = match new_block.header {
+            Some(header) => Some(CompactBlock {
+                header: header.try_into()?,
+                coinbase_kernels: try_convert_all(new_block.coinbase_kernels)?,
+                coinbase_outputs: try_convert_all(new_block.coinbase_outputs)?,
+                short_id_nonce: new_block.short_id_nonce,
+                short_ids: try_convert_short_ids(&new_block.short_ids)?,
+            }),
+            None => None,
+        };
+
+        Ok(Self { block_hash, compact })
     }
 }
 
 impl From<NewBlock> for proto::NewBlock {
     fn from(new_block: NewBlock) -> Self {
-        Self {
-            block_hash: new_block.block_hash,
+        match new_block.compact {
+            Some(compact) => Self {
+                block_hash: new_block.block_hash,
+                header: Some(compact.header.into()),
+                coinbase_kernels: compact.coinbase_kernels.into_iter().map(Into::into).collect(),
+                coinbase_outputs: compact.coinbase_outputs.into_iter().map(Into::into).collect(),
+                short_ids: compact.short_ids.iter().map(|short_id| short_id.to_vec()).collect(),
+                short_id_nonce: compact.short_id_nonce,
+            },
+            None => Self {
+                block_hash: new_block.block_hash,
+                ..Default::default()
+            },
         }
     }
 }
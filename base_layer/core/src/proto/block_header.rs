@@ -108,7 +108,7 @@ impl TryFrom<proto::ProofOfWork> for ProofOfWork {
 impl From<ProofOfWork> for proto::ProofOfWork {
     fn from(pow: ProofOfWork) -> Self {
         Self {
-            pow_algo: pow.pow_algo as u64,
+            pow_algo: pow.pow_algo.as_u64(),
             pow_data: pow.pow_data,
         }
     }
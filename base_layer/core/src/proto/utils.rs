@@ -20,8 +20,9 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::blocks::{ShortId, SHORT_ID_LENGTH};
 use prost_types::Timestamp;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use tari_crypto::tari_utilities::epoch_time::EpochTime;
 
 /// Tries to convert a series of `T`s to `U`s, returning an error at the first failure
@@ -39,6 +40,21 @@ where
 }
 
 /// Utility function that converts a `prost::Timestamp` to a `chrono::DateTime`
+pub(crate) fn try_convert_short_ids(short_ids: &[Vec<u8>]) -> Result<Vec<ShortId>, String> {
+    short_ids
+        .iter()
+        .map(|short_id| {
+            ShortId::try_from(short_id.as_slice()).map_err(|_| {
+                format!(
+                    "Short ID has an incorrect length. (len={}, expected={})",
+                    short_id.len(),
+                    SHORT_ID_LENGTH
+                )
+            })
+        })
+        .collect()
+}
+
 pub(crate) fn timestamp_to_datetime(timestamp: Timestamp) -> EpochTime {
     (timestamp.seconds as u64).into()
 }
@@ -36,15 +36,18 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
+        IntegrityReport,
         LMDBDatabase,
+        MemoryDatabase,
         MmrTree,
         PrunedOutput,
+        Reorg,
         Validators,
     },
     consensus::{chain_strength_comparer::ChainStrengthComparerBuilder, ConsensusConstantsBuilder, ConsensusManager},
     transactions::{
         transaction::{TransactionInput, TransactionKernel, TransactionOutput},
-        types::{CryptoFactories, HashOutput, Signature},
+        types::{Commitment, CryptoFactories, HashOutput, Signature},
     },
     validation::{
         block_validators::{BodyOnlyValidator, OrphanBlockValidator},
@@ -55,7 +58,7 @@ use crate::{
 use croaring::Bitmap;
 use std::{
     fs,
-    ops::Deref,
+    ops::{Deref, DerefMut},
     path::{Path, PathBuf},
 };
 use tari_common::configuration::Network;
@@ -81,6 +84,32 @@ pub fn create_new_blockchain() -> BlockchainDatabase<TempDatabase> {
     create_store_with_consensus_and_validators(consensus_manager, validators)
 }
 
+/// Create a new in-memory blockchain database containing only the genesis block.
+pub fn create_new_memory_blockchain() -> BlockchainDatabase<MemoryDatabase> {
+    let network = Network::Weatherwax;
+    let consensus_constants = ConsensusConstantsBuilder::new(network).build();
+    let genesis = get_weatherwax_genesis_block();
+    let rules = ConsensusManager::builder(network)
+        .with_consensus_constants(consensus_constants)
+        .with_block(genesis)
+        .on_ties(ChainStrengthComparerBuilder::new().by_height().build())
+        .build();
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    BlockchainDatabase::new(
+        MemoryDatabase::new(),
+        rules.clone(),
+        validators,
+        BlockchainDatabaseConfig::default(),
+        DifficultyCalculator::new(rules, Default::default()),
+        false,
+    )
+    .unwrap()
+}
+
 pub fn create_store_with_consensus_and_validators(
     rules: ConsensusManager,
     validators: Validators<TempDatabase>,
@@ -108,7 +137,7 @@ pub fn create_store_with_consensus_and_validators_and_config(
 pub fn create_store_with_consensus(rules: ConsensusManager) -> BlockchainDatabase<TempDatabase> {
     let factories = CryptoFactories::default();
     let validators = Validators::new(
-        BodyOnlyValidator::default(),
+        BodyOnlyValidator::new(rules.clone()),
         MockValidator::new(true),
         OrphanBlockValidator::new(rules.clone(), factories),
     );
@@ -148,6 +177,12 @@ impl Deref for TempDatabase {
     }
 }
 
+impl DerefMut for TempDatabase {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.db
+    }
+}
+
 impl Drop for TempDatabase {
     fn drop(&mut self) {
         if Path::new(&self.path).exists() {
@@ -250,6 +285,27 @@ impl BlockchainBackend for TempDatabase {
         self.db.fetch_output(output_hash)
     }
 
+    fn fetch_outputs_by_commitment(
+        &self,
+        commitment: &Commitment,
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        self.db.fetch_outputs_by_commitment(commitment)
+    }
+
+    fn fetch_outputs_by_script_hash(
+        &self,
+        script_hash: &[u8],
+    ) -> Result<Vec<(TransactionOutput, u32, u64)>, ChainStorageError> {
+        self.db.fetch_outputs_by_script_hash(script_hash)
+    }
+
+    fn fetch_output_spent_in_block(
+        &self,
+        output_hash: &HashOutput,
+    ) -> Result<Option<(HashOutput, u64)>, ChainStorageError> {
+        self.db.fetch_output_spent_in_block(output_hash)
+    }
+
     fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<PrunedOutput>, ChainStorageError> {
         self.db.fetch_outputs_in_block(header_hash)
     }
@@ -290,6 +346,10 @@ impl BlockchainBackend for TempDatabase {
         self.db.kernel_count()
     }
 
+    fn fetch_reorgs(&self, start: u64, limit: usize) -> Result<Vec<(u64, Reorg)>, ChainStorageError> {
+        self.db.fetch_reorgs(start, limit)
+    }
+
     fn fetch_orphan_chain_tip_by_hash(&self, hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
         self.db.fetch_orphan_chain_tip_by_hash(hash)
     }
@@ -317,4 +377,12 @@ impl BlockchainBackend for TempDatabase {
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
         self.db.fetch_horizon_data()
     }
+
+    fn check_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        self.db.check_integrity()
+    }
+
+    fn repair_integrity(&mut self) -> Result<IntegrityReport, ChainStorageError> {
+        self.db.repair_integrity()
+    }
 }
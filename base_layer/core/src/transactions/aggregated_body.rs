@@ -20,6 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::transactions::{
+    batch_verification,
     fee::Fee,
     tari_amount::*,
     transaction::*,
@@ -191,17 +192,11 @@ impl AggregateBody {
         self.sorted = true;
     }
 
-    /// Verify the signatures in all kernels contained in this aggregate body. Clients must provide an offset that
-    /// will be added to the public key used in the signature verification.
+    /// Verify the signatures in all kernels contained in this aggregate body as a batch. If the batch fails, the
+    /// error identifies the offending kernel.
     pub fn verify_kernel_signatures(&self) -> Result<(), TransactionError> {
         trace!(target: LOG_TARGET, "Checking kernel signatures",);
-        for kernel in self.kernels.iter() {
-            kernel.verify_signature().map_err(|e| {
-                warn!(target: LOG_TARGET, "Kernel ({}) signature failed {:?}.", kernel, e);
-                e
-            })?;
-        }
-        Ok(())
+        batch_verification::verify_kernel_signatures(&self.kernels)
     }
 
     pub fn get_total_fee(&self) -> MicroTari {
@@ -317,7 +312,23 @@ impl AggregateBody {
         self.validate_kernel_sum(total_offset, &factories.commitment)?;
 
         self.validate_range_proofs(&factories.range_proof)?;
-        self.verify_metadata_signatures()?;
+        self.verify_metadata_signatures(&factories.commitment)?;
+        self.validate_script_offset(script_offset_g, &factories.commitment)
+    }
+
+    /// Validates that the kernel sum and script offset balance. Unlike `validate_internal_consistency`, kernel
+    /// signatures, range proofs and metadata signatures are not verified.
+    pub fn validate_balance(
+        &self,
+        tx_offset: &BlindingFactor,
+        script_offset: &BlindingFactor,
+        total_reward: MicroTari,
+        factories: &CryptoFactories,
+    ) -> Result<(), TransactionError> {
+        let total_offset = factories.commitment.commit_value(&tx_offset, total_reward.0);
+        let script_offset_g = PublicKey::from_secret_key(&script_offset);
+
+        self.validate_kernel_sum(total_offset, &factories.commitment)?;
         self.validate_script_offset(script_offset_g, &factories.commitment)
     }
 
@@ -405,24 +416,18 @@ impl AggregateBody {
         Ok(())
     }
 
-    fn validate_range_proofs(&self, range_proof_service: &RangeProofService) -> Result<(), TransactionError> {
+    /// Verify the range proofs of all outputs contained in this aggregate body as a batch. If the batch fails, the
+    /// error identifies the offending output.
+    pub fn validate_range_proofs(&self, range_proof_service: &RangeProofService) -> Result<(), TransactionError> {
         trace!(target: LOG_TARGET, "Checking range proofs");
-        for o in &self.outputs {
-            if !o.verify_range_proof(&range_proof_service)? {
-                return Err(TransactionError::ValidationError(
-                    "Range proof could not be verified".into(),
-                ));
-            }
-        }
-        Ok(())
+        batch_verification::verify_range_proofs(&self.outputs, range_proof_service)
     }
 
-    fn verify_metadata_signatures(&self) -> Result<(), TransactionError> {
+    /// Verify the metadata signatures of all outputs contained in this aggregate body as a batch. If the batch fails,
+    /// the error identifies the offending output.
+    pub fn verify_metadata_signatures(&self, factory: &CommitmentFactory) -> Result<(), TransactionError> {
         trace!(target: LOG_TARGET, "Checking sender signatures");
-        for o in &self.outputs {
-            o.verify_metadata_signature()?;
-        }
-        Ok(())
+        batch_verification::verify_metadata_signatures(&self.outputs, factory)
     }
 
     /// Returns the byte size or weight of a body
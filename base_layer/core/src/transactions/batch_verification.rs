@@ -0,0 +1,457 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Batched verification of range proofs, kernel signatures and metadata signatures.
+//!
+//! Items are verified together using random weights, so that a single multiscalar multiplication replaces one
+//! verification per item. When a batch fails, each item is verified individually to find the offending one.
+//!
+//! The `RangeProofService` of tari_crypto only verifies a single proof, so range proofs are batched here by
+//! recomputing the verification equation of each bulletproof (as done by `RangeProof::verify_single`) with the
+//! generators of the bulletproofs factory, and summing the weighted equations into one check.
+
+use crate::transactions::{
+    transaction::{TransactionError, TransactionKernel, TransactionOutput},
+    types::{CommitmentFactory, PrivateKey, PublicKey, RangeProofService},
+};
+use bulletproofs::BulletproofGens;
+use curve25519_dalek_ng::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use log::*;
+use merlin::Transcript;
+use rand::rngs::OsRng;
+use std::iter;
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+    range_proof::RangeProofService as RangeProofServiceTrait,
+    tari_utilities::{hex::Hex, ByteArray},
+};
+
+pub const LOG_TARGET: &str = "c::tx::batch_verification";
+
+/// Verifies the excess signatures of all the given kernels. For random weights `w_i`, the batch is valid if
+/// `(sum w_i * s_i) * G == sum w_i * R_i + sum (w_i * e_i) * P_i`.
+pub fn verify_kernel_signatures(kernels: &[TransactionKernel]) -> Result<(), TransactionError> {
+    if kernels.len() < 2 {
+        return find_invalid_kernel_signature(kernels);
+    }
+
+    let mut s_sum = PrivateKey::default();
+    let mut scalars = Vec::with_capacity(kernels.len() * 2);
+    let mut points = Vec::with_capacity(kernels.len() * 2);
+    for kernel in kernels {
+        let challenge = PrivateKey::from_bytes(&kernel.build_signature_challenge())
+            .map_err(|err| TransactionError::InvalidSignatureError(err.to_string()))?;
+        let weight = PrivateKey::random(&mut OsRng);
+        s_sum = s_sum + &(&weight * kernel.excess_sig.get_signature());
+        scalars.push(&weight * &challenge);
+        points.push(kernel.excess.as_public_key().clone());
+        scalars.push(weight);
+        points.push(kernel.excess_sig.get_public_nonce().clone());
+    }
+
+    if PublicKey::from_secret_key(&s_sum) == PublicKey::batch_mul(&scalars, &points) {
+        return Ok(());
+    }
+    debug!(
+        target: LOG_TARGET,
+        "Batch verification of {} kernel signature(s) failed, verifying individually",
+        kernels.len()
+    );
+    find_invalid_kernel_signature(kernels)
+}
+
+/// Verifies the metadata signatures of all the given outputs. For random weights `w_i`, the batch is valid if
+/// `commit(sum w_i * u_i, sum w_i * v_i) == sum w_i * R_i + sum (w_i * e_i) * C_i`, where `C_i` is the output
+/// commitment plus the sender offset public key.
+pub fn verify_metadata_signatures(
+    outputs: &[TransactionOutput],
+    factory: &CommitmentFactory,
+) -> Result<(), TransactionError> {
+    if outputs.len() < 2 {
+        return find_invalid_metadata_signature(outputs);
+    }
+
+    let mut u_sum = PrivateKey::default();
+    let mut v_sum = PrivateKey::default();
+    let mut scalars = Vec::with_capacity(outputs.len() * 2);
+    let mut points = Vec::with_capacity(outputs.len() * 2);
+    for output in outputs {
+        let signature = &output.metadata_signature;
+        let challenge = PrivateKey::from_bytes(&output.metadata_signature_challenge())
+            .map_err(|err| TransactionError::InvalidSignatureError(err.to_string()))?;
+        let weight = PrivateKey::random(&mut OsRng);
+        u_sum = u_sum + &(&weight * signature.u());
+        v_sum = v_sum + &(&weight * signature.v());
+        let public_commitment = &output.commitment + &output.sender_offset_public_key;
+        scalars.push(&weight * &challenge);
+        points.push(public_commitment.as_public_key().clone());
+        scalars.push(weight);
+        points.push(signature.public_nonce().as_public_key().clone());
+    }
+
+    let lhs = factory.commit(&u_sum, &v_sum);
+    if lhs.as_public_key() == &PublicKey::batch_mul(&scalars, &points) {
+        return Ok(());
+    }
+    debug!(
+        target: LOG_TARGET,
+        "Batch verification of {} metadata signature(s) failed, verifying individually",
+        outputs.len()
+    );
+    find_invalid_metadata_signature(outputs)
+}
+
+/// Verifies the range proofs of all the given outputs against their commitments. The verification equation of each
+/// proof is multiplied by a random weight and all of them are checked with one multiscalar multiplication.
+pub fn verify_range_proofs(
+    outputs: &[TransactionOutput],
+    range_proof_service: &RangeProofService,
+) -> Result<(), TransactionError> {
+    if outputs.len() < 2 {
+        return find_invalid_range_proof(outputs, range_proof_service);
+    }
+
+    if batch_verify_range_proofs(outputs, range_proof_service.range()) {
+        return Ok(());
+    }
+    debug!(
+        target: LOG_TARGET,
+        "Batch verification of {} range proof(s) failed, verifying individually",
+        outputs.len()
+    );
+    find_invalid_range_proof(outputs, range_proof_service)
+}
+
+/// Returns true if all the range proofs are valid proofs of `bits` bit values. Malformed proofs make the batch fail.
+fn batch_verify_range_proofs(outputs: &[TransactionOutput], bits: usize) -> bool {
+    if ![8, 16, 32, 64].contains(&bits) {
+        return false;
+    }
+    // The range proof service commits to values with H and to blinding factors with G
+    let factory = CommitmentFactory::default();
+    let value_base = match to_point(factory.commit_value(&PrivateKey::default(), 1).as_bytes()) {
+        Some(point) => point,
+        None => return false,
+    };
+    let blinding_base = match to_point(factory.commit_value(&PrivateKey::from(1u64), 0).as_bytes()) {
+        Some(point) => point,
+        None => return false,
+    };
+    let bp_gens = BulletproofGens::new(bits, 1);
+
+    let mut value_base_scalar = Scalar::zero();
+    let mut blinding_base_scalar = Scalar::zero();
+    let mut g_scalars = vec![Scalar::zero(); bits];
+    let mut h_scalars = vec![Scalar::zero(); bits];
+    let mut scalars = Vec::new();
+    let mut points = Vec::new();
+    for output in outputs {
+        let proof = match ParsedRangeProof::from_bytes(output.proof.as_bytes()) {
+            Some(proof) if proof.l_vec.len() == bits.trailing_zeros() as usize => proof,
+            _ => return false,
+        };
+        let commitment = CompressedRistretto::from_slice(output.commitment.as_bytes());
+        let check = match proof.verification_scalars(&commitment, bits) {
+            Some(check) => check,
+            None => return false,
+        };
+        let weight = Scalar::random(&mut OsRng);
+
+        value_base_scalar += weight * check.value_base;
+        blinding_base_scalar += weight * check.blinding_base;
+        for (acc, g) in g_scalars.iter_mut().zip(check.g) {
+            *acc += weight * g;
+        }
+        for (acc, h) in h_scalars.iter_mut().zip(check.h) {
+            *acc += weight * h;
+        }
+        scalars.extend(check.dynamic.into_iter().map(|scalar| weight * scalar));
+        points.extend(
+            iter::once(proof.a)
+                .chain(iter::once(proof.s))
+                .chain(iter::once(proof.t_1))
+                .chain(iter::once(proof.t_2))
+                .chain(proof.l_vec)
+                .chain(proof.r_vec)
+                .chain(iter::once(commitment))
+                .map(|point| point.decompress()),
+        );
+    }
+
+    let gens = bp_gens.share(0);
+    let result = RistrettoPoint::optional_multiscalar_mul(
+        iter::once(value_base_scalar)
+            .chain(iter::once(blinding_base_scalar))
+            .chain(g_scalars)
+            .chain(h_scalars)
+            .chain(scalars),
+        iter::once(Some(value_base))
+            .chain(iter::once(Some(blinding_base)))
+            .chain(gens.G(bits).map(|point| Some(*point)))
+            .chain(gens.H(bits).map(|point| Some(*point)))
+            .chain(points),
+    );
+    result.map(|point| point.is_identity()).unwrap_or(false)
+}
+
+fn to_point(bytes: &[u8]) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(bytes).decompress()
+}
+
+fn to_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(bytes);
+    Scalar::from_canonical_bytes(buf)
+}
+
+/// The fields of a serialized bulletproof for a single value
+struct ParsedRangeProof {
+    a: CompressedRistretto,
+    s: CompressedRistretto,
+    t_1: CompressedRistretto,
+    t_2: CompressedRistretto,
+    t_x: Scalar,
+    t_x_blinding: Scalar,
+    e_blinding: Scalar,
+    l_vec: Vec<CompressedRistretto>,
+    r_vec: Vec<CompressedRistretto>,
+    ipp_a: Scalar,
+    ipp_b: Scalar,
+}
+
+/// The scalars of the verification equation of one proof. `dynamic` lines up with the points A, S, T_1, T_2, L_vec,
+/// R_vec and the value commitment.
+struct RangeProofCheck {
+    value_base: Scalar,
+    blinding_base: Scalar,
+    g: Vec<Scalar>,
+    h: Vec<Scalar>,
+    dynamic: Vec<Scalar>,
+}
+
+impl ParsedRangeProof {
+    /// Parses the serialization of `RangeProof::to_bytes`: A, S, T_1, T_2, t_x, t_x_blinding and e_blinding followed
+    /// by the inner product proof, which is the interleaved L and R vectors followed by a and b.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() % 32 != 0 || bytes.len() < 9 * 32 {
+            return None;
+        }
+        let chunks = bytes.chunks(32).collect::<Vec<_>>();
+        let ipp = &chunks[7..];
+        if ipp.len() % 2 != 0 {
+            return None;
+        }
+        let lg_n = (ipp.len() - 2) / 2;
+        Some(Self {
+            a: CompressedRistretto::from_slice(chunks[0]),
+            s: CompressedRistretto::from_slice(chunks[1]),
+            t_1: CompressedRistretto::from_slice(chunks[2]),
+            t_2: CompressedRistretto::from_slice(chunks[3]),
+            t_x: to_scalar(chunks[4])?,
+            t_x_blinding: to_scalar(chunks[5])?,
+            e_blinding: to_scalar(chunks[6])?,
+            l_vec: (0..lg_n).map(|i| CompressedRistretto::from_slice(ipp[2 * i])).collect(),
+            r_vec: (0..lg_n)
+                .map(|i| CompressedRistretto::from_slice(ipp[2 * i + 1]))
+                .collect(),
+            ipp_a: to_scalar(ipp[2 * lg_n])?,
+            ipp_b: to_scalar(ipp[2 * lg_n + 1])?,
+        })
+    }
+
+    /// Replays the Fiat-Shamir transcript of the range proof service and returns the scalars of the verification
+    /// equation, or None if the proof contains an identity point
+    fn verification_scalars(&self, commitment: &CompressedRistretto, n: usize) -> Option<RangeProofCheck> {
+        let mut transcript = Transcript::new(b"tari");
+        transcript.append_message(b"dom-sep", b"rangeproof v1");
+        transcript.append_u64(b"n", n as u64);
+        transcript.append_u64(b"m", 1);
+        transcript.append_message(b"V", commitment.as_bytes());
+        append_point(&mut transcript, b"A", &self.a)?;
+        append_point(&mut transcript, b"S", &self.s)?;
+        let y = challenge_scalar(&mut transcript, b"y");
+        let z = challenge_scalar(&mut transcript, b"z");
+        append_point(&mut transcript, b"T_1", &self.t_1)?;
+        append_point(&mut transcript, b"T_2", &self.t_2)?;
+        let x = challenge_scalar(&mut transcript, b"x");
+        transcript.append_message(b"t_x", self.t_x.as_bytes());
+        transcript.append_message(b"t_x_blinding", self.t_x_blinding.as_bytes());
+        transcript.append_message(b"e_blinding", self.e_blinding.as_bytes());
+        let w = challenge_scalar(&mut transcript, b"w");
+
+        // Inner product proof challenges
+        transcript.append_message(b"dom-sep", b"ipp v1");
+        transcript.append_u64(b"n", n as u64);
+        let lg_n = self.l_vec.len();
+        let mut challenges = Vec::with_capacity(lg_n);
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            append_point(&mut transcript, b"L", l)?;
+            append_point(&mut transcript, b"R", r)?;
+            challenges.push(challenge_scalar(&mut transcript, b"u"));
+        }
+        let mut challenges_inv = challenges.clone();
+        let all_inv = Scalar::batch_invert(&mut challenges_inv);
+        let challenges_sq = challenges.iter().map(|u| u * u).collect::<Vec<_>>();
+        let challenges_inv_sq = challenges_inv.iter().map(|u| u * u).collect::<Vec<_>>();
+        let mut s = Vec::with_capacity(n);
+        s.push(all_inv);
+        for i in 1..n {
+            let lg_i = (31 - (i as u32).leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            s.push(s[i - k] * challenges_sq[(lg_n - 1) - lg_i]);
+        }
+
+        // A random weight that combines the two equations of the proof
+        let c = Scalar::random(&mut OsRng);
+        let zz = z * z;
+        let y_inv = y.invert();
+        let mut g = Vec::with_capacity(n);
+        let mut h = Vec::with_capacity(n);
+        let mut exp_y_inv = Scalar::one();
+        let mut exp_2 = Scalar::one();
+        for i in 0..n {
+            g.push(-z - self.ipp_a * s[i]);
+            h.push(z + exp_y_inv * (zz * exp_2 - self.ipp_b * s[n - 1 - i]));
+            exp_y_inv *= y_inv;
+            exp_2 += exp_2;
+        }
+        // delta(y, z) = (z - z^2) * <1, y^n> - z^3 * <1, 2^n>, where exp_2 is now 2^n
+        let sum_y = sum_of_powers(&y, n);
+        let delta = (z - zz) * sum_y - zz * z * (exp_2 - Scalar::one());
+
+        let dynamic = iter::once(Scalar::one())
+            .chain(iter::once(x))
+            .chain(iter::once(c * x))
+            .chain(iter::once(c * x * x))
+            .chain(challenges_sq)
+            .chain(challenges_inv_sq)
+            .chain(iter::once(c * zz))
+            .collect();
+        Some(RangeProofCheck {
+            value_base: w * (self.t_x - self.ipp_a * self.ipp_b) + c * (delta - self.t_x),
+            blinding_base: -self.e_blinding - c * self.t_x_blinding,
+            g,
+            h,
+            dynamic,
+        })
+    }
+}
+
+fn append_point(transcript: &mut Transcript, label: &'static [u8], point: &CompressedRistretto) -> Option<()> {
+    if point.is_identity() {
+        return None;
+    }
+    transcript.append_message(label, point.as_bytes());
+    Some(())
+}
+
+fn challenge_scalar(transcript: &mut Transcript, label: &'static [u8]) -> Scalar {
+    let mut buf = [0u8; 64];
+    transcript.challenge_bytes(label, &mut buf);
+    Scalar::from_bytes_mod_order_wide(&buf)
+}
+
+fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
+    let mut sum = Scalar::zero();
+    let mut exp_x = Scalar::one();
+    for _ in 0..n {
+        sum += exp_x;
+        exp_x *= x;
+    }
+    sum
+}
+
+fn find_invalid_range_proof(
+    outputs: &[TransactionOutput],
+    range_proof_service: &RangeProofService,
+) -> Result<(), TransactionError> {
+    for (i, output) in outputs.iter().enumerate() {
+        if !output.verify_range_proof(range_proof_service)? {
+            warn!(
+                target: LOG_TARGET,
+                "Range proof of output {} ({}) is invalid",
+                i,
+                output.commitment.to_hex()
+            );
+            return Err(TransactionError::ValidationError(format!(
+                "Range proof of output {} ({}) could not be verified",
+                i,
+                output.commitment.to_hex()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn find_invalid_kernel_signature(kernels: &[TransactionKernel]) -> Result<(), TransactionError> {
+    for (i, kernel) in kernels.iter().enumerate() {
+        kernel.verify_signature().map_err(|err| {
+            warn!(target: LOG_TARGET, "Kernel ({}) signature failed {:?}.", kernel, err);
+            TransactionError::InvalidSignatureError(format!(
+                "Signature of kernel {} ({}) is invalid",
+                i,
+                kernel.excess.to_hex()
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+fn find_invalid_metadata_signature(outputs: &[TransactionOutput]) -> Result<(), TransactionError> {
+    for (i, output) in outputs.iter().enumerate() {
+        output.verify_metadata_signature().map_err(|err| {
+            warn!(
+                target: LOG_TARGET,
+                "Output ({}) metadata signature failed {:?}.", output, err
+            );
+            TransactionError::InvalidSignatureError(format!(
+                "Metadata signature of output {} ({}) is invalid",
+                i,
+                output.commitment.to_hex()
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transactions::{helpers, types::MAX_RANGE_PROOF_RANGE};
+
+    #[test]
+    fn it_batch_verifies_range_proofs() {
+        let (tx, _, _) = helpers::create_tx(50000000.into(), 15.into(), 1, 2, 1, 3);
+        let mut outputs = tx.body.outputs().clone();
+        assert!(outputs.len() > 1);
+        assert!(batch_verify_range_proofs(&outputs, MAX_RANGE_PROOF_RANGE));
+        assert!(!batch_verify_range_proofs(&outputs, 32));
+
+        outputs[1].proof = outputs[0].proof.clone();
+        assert!(!batch_verify_range_proofs(&outputs, MAX_RANGE_PROOF_RANGE));
+    }
+}
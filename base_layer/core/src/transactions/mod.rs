@@ -1,4 +1,5 @@
 pub mod aggregated_body;
+pub mod batch_verification;
 pub mod bullet_rangeproofs;
 pub mod fee;
 pub mod tari_amount;
@@ -596,13 +596,7 @@ impl TransactionOutput {
 
     /// Verify that the metadata signature is valid
     pub fn verify_metadata_signature(&self) -> Result<(), TransactionError> {
-        let challenge = TransactionOutput::build_metadata_signature_challenge(
-            &self.script,
-            &self.features,
-            &self.sender_offset_public_key,
-            &self.metadata_signature.public_nonce(),
-            &self.commitment,
-        );
+        let challenge = self.metadata_signature_challenge();
         if !self.metadata_signature.verify_challenge(
             &(&self.commitment + &self.sender_offset_public_key),
             &challenge,
@@ -615,6 +609,17 @@ impl TransactionOutput {
         Ok(())
     }
 
+    /// Builds the challenge that the metadata signature of this output signs
+    pub fn metadata_signature_challenge(&self) -> MessageHash {
+        TransactionOutput::build_metadata_signature_challenge(
+            &self.script,
+            &self.features,
+            &self.sender_offset_public_key,
+            &self.metadata_signature.public_nonce(),
+            &self.commitment,
+        )
+    }
+
     /// Attempt to rewind the range proof to reveal the proof message and committed value
     pub fn rewind_range_proof_value_only(
         &self,
@@ -1008,12 +1013,7 @@ impl Default for KernelBuilder {
 impl TransactionKernel {
     pub fn verify_signature(&self) -> Result<(), TransactionError> {
         let excess = self.excess.as_public_key();
-        let r = self.excess_sig.get_public_nonce();
-        let m = TransactionMetadata {
-            lock_height: self.lock_height,
-            fee: self.fee,
-        };
-        let c = build_challenge(r, &m);
+        let c = self.build_signature_challenge();
         if self.excess_sig.verify_challenge(excess, &c) {
             Ok(())
         } else {
@@ -1022,6 +1022,15 @@ impl TransactionKernel {
             ))
         }
     }
+
+    /// Builds the challenge that the excess signature of this kernel signs
+    pub fn build_signature_challenge(&self) -> MessageHash {
+        let m = TransactionMetadata {
+            lock_height: self.lock_height,
+            fee: self.fee,
+        };
+        build_challenge(self.excess_sig.get_public_nonce(), &m)
+    }
 }
 
 impl Hashable for TransactionKernel {
@@ -1665,4 +1674,33 @@ mod test {
         assert_eq!(&full_rewind_result.proof_message, proof_message);
         assert_eq!(full_rewind_result.blinding_factor, test_params.spend_key);
     }
+
+    #[test]
+    fn batch_verification_identifies_invalid_items() {
+        let (tx, _, outputs) = helpers::create_tx(50000000.into(), 15.into(), 1, 2, 1, 2);
+        let schema = txn_schema!(from: vec![outputs[1].clone()], to: vec![1 * T, 2 * T]);
+        let (tx2, _, _) = helpers::spend_utxos(schema);
+        let mut tx = tx + tx2;
+        let factories = CryptoFactories::default();
+        assert_eq!(tx.body.kernels().len(), 2);
+        assert!(tx.body.verify_kernel_signatures().is_ok());
+        assert!(tx.body.verify_metadata_signatures(&factories.commitment).is_ok());
+        assert!(tx.body.validate_range_proofs(&factories.range_proof).is_ok());
+
+        let mut kernel = tx.body.kernels()[0].clone();
+        kernel.excess_sig = tx.body.kernels()[1].excess_sig.clone();
+        tx.body.add_kernel(kernel);
+        let err = tx.body.verify_kernel_signatures().unwrap_err();
+        assert!(err.to_string().contains("kernel 2"));
+
+        let metadata_signature = tx.body.outputs()[0].metadata_signature.clone();
+        tx.body.outputs_mut()[1].metadata_signature = metadata_signature;
+        let err = tx.body.verify_metadata_signatures(&factories.commitment).unwrap_err();
+        assert!(err.to_string().contains("output 1"));
+
+        let proof = tx.body.outputs()[0].proof.clone();
+        tx.body.outputs_mut()[2].proof = proof;
+        let err = tx.body.validate_range_proofs(&factories.range_proof).unwrap_err();
+        assert!(err.to_string().contains("output 2"));
+    }
 }
@@ -22,15 +22,22 @@
 use crate::{
     blocks::{Block, BlockValidationError},
     chain_storage,
-    chain_storage::{BlockchainBackend, ChainBlock, MmrTree},
-    consensus::ConsensusManager,
+    chain_storage::{BlockchainBackend, ChainBlock, MmrRoots, MmrTree},
+    consensus::{ConsensusFeature, ConsensusManager},
     transactions::{
         aggregated_body::AggregateBody,
         transaction::{KernelFeatures, OutputFlags, TransactionError},
         types::CryptoFactories,
     },
     validation::{
-        helpers::{check_accounting_balance, check_block_weight, check_coinbase_output, is_all_unique_and_sorted},
+        helpers::{
+            check_accounting_balance,
+            check_accounting_balance_assume_valid,
+            check_block_weight,
+            check_coinbase_output,
+            is_all_unique_and_sorted,
+            is_assumed_valid,
+        },
         traits::PostOrphanBodyValidation,
         CandidateBlockBodyValidation,
         OrphanValidation,
@@ -115,17 +122,26 @@ impl OrphanValidation for OrphanBlockValidator {
 
 /// This validator checks whether a block satisfies *all* consensus rules. If a block passes this validator, it is the
 /// next block on the blockchain.
-#[derive(Default)]
-pub struct BodyOnlyValidator {}
+pub struct BodyOnlyValidator {
+    rules: ConsensusManager,
+}
+
+impl BodyOnlyValidator {
+    pub fn new(rules: ConsensusManager) -> Self {
+        Self { rules }
+    }
+}
 
 impl<B: BlockchainBackend> PostOrphanBodyValidation<B> for BodyOnlyValidator {
     /// The consensus checks that are done (in order of cheapest to verify to most expensive):
     /// 1. Does the block satisfy the stateless checks?
+    /// 1. Have the kernel lock heights been reached (once [ConsensusFeature::KernelLockHeight] is active)?
     /// 1. Are all inputs currently in the UTXO set?
     /// 1. Are all inputs and outputs not in the STXO set?
     /// 1. Are the block header MMR roots valid?
     fn validate_body_for_valid_orphan(&self, block: &ChainBlock, backend: &B) -> Result<(), ValidationError> {
         let block_id = format!("block #{} ({})", block.header().height, block.hash().to_hex());
+        check_kernel_lock_heights(block.block(), &self.rules, backend)?;
         check_inputs_are_utxos(&block.block(), backend)?;
         check_not_duplicate_txos(&block.block(), backend)?;
         trace!(
@@ -144,6 +160,29 @@ impl<B: BlockchainBackend> PostOrphanBodyValidation<B> for BodyOnlyValidator {
     }
 }
 
+/// This function checks that the lock heights of the kernels in the block have been reached, once
+/// [ConsensusFeature::KernelLockHeight] is active for the block on the chain that it extends.
+fn check_kernel_lock_heights<B: BlockchainBackend>(
+    block: &Block,
+    rules: &ConsensusManager,
+    db: &B,
+) -> Result<(), ValidationError> {
+    if rules.is_feature_active_on_chain(
+        ConsensusFeature::KernelLockHeight,
+        block.header.height,
+        &block.header.prev_hash,
+        db,
+    )? {
+        block.check_kernel_rules()?;
+        trace!(
+            target: LOG_TARGET,
+            "Block validation: Kernel lock heights are ok for block #{}",
+            block.header.height
+        );
+    }
+    Ok(())
+}
+
 // This function checks for duplicate inputs and outputs. There should be no duplicate inputs or outputs in a block
 fn check_sorting_and_duplicates(body: &AggregateBody) -> Result<(), ValidationError> {
     if !is_all_unique_and_sorted(body.inputs()) {
@@ -207,6 +246,10 @@ fn check_not_duplicate_txos<B: BlockchainBackend>(block: &Block, db: &B) -> Resu
 
 fn check_mmr_roots<B: BlockchainBackend>(block: &Block, db: &B) -> Result<(), ValidationError> {
     let mmr_roots = chain_storage::calculate_mmr_roots(db, &block)?;
+    check_header_mmr_roots(block, &mmr_roots)
+}
+
+fn check_header_mmr_roots(block: &Block, mmr_roots: &MmrRoots) -> Result<(), ValidationError> {
     let header = &block.header;
     if header.input_mr != mmr_roots.input_mr {
         warn!(
@@ -294,101 +337,19 @@ impl<B: BlockchainBackend> BlockValidator<B> {
             phantom_data: Default::default(),
         }
     }
-
-    /// This function checks that all inputs in the blocks are valid UTXO's to be spend
-    fn check_inputs(&self, block: &Block) -> Result<(), ValidationError> {
-        let inputs = block.body.inputs();
-        for (i, input) in inputs.iter().enumerate() {
-            // Check for duplicates and/or incorrect sorting
-            if i > 0 && input <= &inputs[i - 1] {
-                return Err(ValidationError::UnsortedOrDuplicateInput);
-            }
-
-            // Check maturity
-            if input.features.maturity > block.header.height {
-                warn!(
-                    target: LOG_TARGET,
-                    "Input found that has not yet matured to spending height: {}", input
-                );
-                return Err(TransactionError::InputMaturity.into());
-            }
-        }
-        Ok(())
-    }
-
-    fn check_outputs(&self, block: &Block) -> Result<(), ValidationError> {
-        let outputs = block.body.outputs();
-        let mut coinbase_output = None;
-        for (j, output) in outputs.iter().enumerate() {
-            if output.features.flags.contains(OutputFlags::COINBASE_OUTPUT) {
-                if coinbase_output.is_some() {
-                    return Err(ValidationError::TransactionError(TransactionError::MoreThanOneCoinbase));
-                }
-                coinbase_output = Some(output);
-            }
-
-            if j > 0 && output <= &outputs[j - 1] {
-                return Err(ValidationError::UnsortedOrDuplicateOutput);
-            }
-        }
-
-        let coinbase_output = match coinbase_output {
-            Some(output) => output,
-            // No coinbase found
-            None => {
-                warn!(
-                    target: LOG_TARGET,
-                    "Block #{} failed to validate: no coinbase UTXO", block.header.height
-                );
-                return Err(ValidationError::TransactionError(TransactionError::NoCoinbase));
-            },
-        };
-
-        let mut coinbase_kernel = None;
-        for kernel in block.body.kernels() {
-            if kernel.features.contains(KernelFeatures::COINBASE_KERNEL) {
-                if coinbase_kernel.is_some() {
-                    return Err(ValidationError::TransactionError(TransactionError::MoreThanOneCoinbase));
-                }
-                coinbase_kernel = Some(kernel);
-            }
-        }
-
-        let coinbase_kernel = match coinbase_kernel {
-            Some(kernel) => kernel,
-            // No coinbase found
-            None => {
-                warn!(
-                    target: LOG_TARGET,
-                    "Block #{} failed to validate: no coinbase kernel", block.header.height
-                );
-                return Err(ValidationError::TransactionError(TransactionError::NoCoinbase));
-            },
-        };
-
-        let reward = self.rules.calculate_coinbase_and_fees(block);
-        let rhs = &coinbase_kernel.excess +
-            &self
-                .factories
-                .commitment
-                .commit_value(&Default::default(), reward.into());
-        if rhs != coinbase_output.commitment {
-            warn!(
-                target: LOG_TARGET,
-                "Coinbase {} amount validation failed", coinbase_output
-            );
-            return Err(ValidationError::TransactionError(TransactionError::InvalidCoinbase));
-        }
-
-        Ok(())
-    }
 }
 
 impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B> {
+    fn is_assumed_valid(&self, block: &Block, backend: &B) -> Result<bool, ValidationError> {
+        is_assumed_valid(block, &self.rules, backend)
+    }
+
     /// The following consensus checks are done:
-    /// 1. Does the block satisfy the stateless checks?
-    /// 1. Are the block header MMR roots valid?
-    fn validate_body(&self, block: &Block, backend: &B) -> Result<(), ValidationError> {
+    /// 1. Is the block weight of the block under the prescribed limit?
+    /// 1. Are the inputs and outputs sorted, unique and mature?
+    /// 1. Is there precisely one correctly defined coinbase?
+    /// 1. Is the accounting correct?
+    fn validate_body_stateless(&self, block: &Block, assumed_valid: bool) -> Result<(), ValidationError> {
         let block_id = format!("block #{}", block.header.height);
         trace!(target: LOG_TARGET, "Validating {}", block_id);
 
@@ -396,16 +357,33 @@ impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B>
         check_block_weight(block, &constants)?;
         trace!(target: LOG_TARGET, "SV - Block weight is ok for {} ", &block_id);
 
-        self.check_inputs(block)?;
-        self.check_outputs(block)?;
+        check_inputs(block)?;
+        check_outputs(block, &self.rules, &self.factories)?;
 
-        check_accounting_balance(block, &self.rules, &self.factories)?;
-        trace!(target: LOG_TARGET, "SV - accounting balance correct for {}", &block_id);
+        if assumed_valid {
+            check_accounting_balance_assume_valid(block, &self.rules, &self.factories)?;
+            trace!(
+                target: LOG_TARGET,
+                "SV - accounting balance correct for {} (assume-valid, proofs and signatures not verified)",
+                &block_id
+            );
+        } else {
+            check_accounting_balance(block, &self.rules, &self.factories)?;
+            trace!(target: LOG_TARGET, "SV - accounting balance correct for {}", &block_id);
+        }
         debug!(
             target: LOG_TARGET,
             "{} has PASSED stateless VALIDATION check.", &block_id
         );
+        Ok(())
+    }
 
+    /// The following consensus checks are done:
+    /// 1. Have the kernel lock heights been reached (once [ConsensusFeature::KernelLockHeight] is active)?
+    /// 1. Are the block header MMR roots valid?
+    fn validate_body_stateful(&self, block: &Block, backend: &B) -> Result<(), ValidationError> {
+        let block_id = format!("block #{}", block.header.height);
+        check_kernel_lock_heights(block, &self.rules, backend)?;
         check_mmr_roots(&block, backend)?;
         trace!(
             target: LOG_TARGET,
@@ -417,3 +395,115 @@ impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for BlockValidator<B>
         Ok(())
     }
 }
+
+/// Validates a genesis block, such as one loaded from a network definition file. The block weight, the sorting and
+/// maturity of the inputs and outputs, the coinbase amount, the kernel and metadata signatures, the range proofs and
+/// the accounting balance are checked as in [BlockValidator]. A genesis block has no previous state, so its MMR roots
+/// are calculated from the block alone and no database is needed.
+pub fn validate_genesis_block(
+    block: &Block,
+    rules: &ConsensusManager,
+    factories: &CryptoFactories,
+) -> Result<(), ValidationError> {
+    if block.header.height != 0 {
+        return Err(ValidationError::CustomError(format!(
+            "Genesis block must be at height 0 but was at height {}",
+            block.header.height
+        )));
+    }
+    check_block_weight(block, rules.consensus_constants(0))?;
+    check_inputs(block)?;
+    check_outputs(block, rules, factories)?;
+    block.body.verify_kernel_signatures()?;
+    block.body.validate_range_proofs(&factories.range_proof)?;
+    block.body.verify_metadata_signatures(&factories.commitment)?;
+    check_accounting_balance(block, rules, factories)?;
+    let mmr_roots = chain_storage::calculate_genesis_mmr_roots(block)?;
+    check_header_mmr_roots(block, &mmr_roots)?;
+    debug!(target: LOG_TARGET, "Genesis block {} is VALID", block.hash().to_hex());
+    Ok(())
+}
+
+/// This function checks that all inputs in the blocks are valid UTXO's to be spend
+fn check_inputs(block: &Block) -> Result<(), ValidationError> {
+    let inputs = block.body.inputs();
+    for (i, input) in inputs.iter().enumerate() {
+        // Check for duplicates and/or incorrect sorting
+        if i > 0 && input <= &inputs[i - 1] {
+            return Err(ValidationError::UnsortedOrDuplicateInput);
+        }
+
+        // Check maturity
+        if input.features.maturity > block.header.height {
+            warn!(
+                target: LOG_TARGET,
+                "Input found that has not yet matured to spending height: {}", input
+            );
+            return Err(TransactionError::InputMaturity.into());
+        }
+    }
+    Ok(())
+}
+
+fn check_outputs(block: &Block, rules: &ConsensusManager, factories: &CryptoFactories) -> Result<(), ValidationError> {
+    let outputs = block.body.outputs();
+    let mut coinbase_output = None;
+    for (j, output) in outputs.iter().enumerate() {
+        if output.features.flags.contains(OutputFlags::COINBASE_OUTPUT) {
+            if coinbase_output.is_some() {
+                return Err(ValidationError::TransactionError(TransactionError::MoreThanOneCoinbase));
+            }
+            coinbase_output = Some(output);
+        }
+
+        if j > 0 && output <= &outputs[j - 1] {
+            return Err(ValidationError::UnsortedOrDuplicateOutput);
+        }
+    }
+
+    let coinbase_output = match coinbase_output {
+        Some(output) => output,
+        // No coinbase found
+        None => {
+            warn!(
+                target: LOG_TARGET,
+                "Block #{} failed to validate: no coinbase UTXO", block.header.height
+            );
+            return Err(ValidationError::TransactionError(TransactionError::NoCoinbase));
+        },
+    };
+
+    let mut coinbase_kernel = None;
+    for kernel in block.body.kernels() {
+        if kernel.features.contains(KernelFeatures::COINBASE_KERNEL) {
+            if coinbase_kernel.is_some() {
+                return Err(ValidationError::TransactionError(TransactionError::MoreThanOneCoinbase));
+            }
+            coinbase_kernel = Some(kernel);
+        }
+    }
+
+    let coinbase_kernel = match coinbase_kernel {
+        Some(kernel) => kernel,
+        // No coinbase found
+        None => {
+            warn!(
+                target: LOG_TARGET,
+                "Block #{} failed to validate: no coinbase kernel", block.header.height
+            );
+            return Err(ValidationError::TransactionError(TransactionError::NoCoinbase));
+        },
+    };
+
+    let reward = rules.calculate_coinbase_and_fees(block);
+    let rhs = &coinbase_kernel.excess + &factories.commitment.commit_value(&Default::default(), reward.into());
+    if rhs != coinbase_output.commitment {
+        warn!(
+            target: LOG_TARGET,
+            "Coinbase {} amount validation failed", coinbase_output
+        );
+        return Err(ValidationError::TransactionError(TransactionError::InvalidCoinbase));
+    }
+
+    Ok(())
+}
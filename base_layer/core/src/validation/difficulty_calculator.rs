@@ -24,18 +24,37 @@ use crate::{
     blocks::BlockHeader,
     chain_storage::{fetch_target_difficulty_for_next_block, BlockchainBackend},
     consensus::ConsensusManager,
-    proof_of_work::{randomx_factory::RandomXFactory, AchievedTargetDifficulty},
+    proof_of_work::{
+        randomx_factory::RandomXFactory,
+        AchievedTargetDifficulty,
+        PowAlgorithmRegistry,
+        ProofOfWorkAlgorithm,
+    },
     validation::{helpers::check_target_difficulty, ValidationError},
 };
+use std::sync::Arc;
 
 pub struct DifficultyCalculator {
     rules: ConsensusManager,
-    randomx_factory: RandomXFactory,
+    pow_algorithms: PowAlgorithmRegistry,
 }
 
 impl DifficultyCalculator {
     pub fn new(rules: ConsensusManager, randomx_factory: RandomXFactory) -> Self {
-        Self { rules, randomx_factory }
+        Self {
+            rules,
+            pow_algorithms: PowAlgorithmRegistry::new(randomx_factory),
+        }
+    }
+
+    /// Registers an additional proof of work algorithm, or replaces the built-in one with the same `PowAlgorithm`
+    pub fn with_pow_algorithm(mut self, algorithm: Arc<dyn ProofOfWorkAlgorithm>) -> Self {
+        self.pow_algorithms.register(algorithm);
+        self
+    }
+
+    pub fn pow_algorithms(&self) -> &PowAlgorithmRegistry {
+        &self.pow_algorithms
     }
 
     pub fn check_achieved_and_target_difficulty<B: BlockchainBackend>(
@@ -50,7 +69,7 @@ impl DifficultyCalculator {
             constants.min_pow_difficulty(block_header.pow.pow_algo),
             constants.max_pow_difficulty(block_header.pow.pow_algo),
         );
-        let achieved_target = check_target_difficulty(block_header, target, &self.randomx_factory)?;
+        let achieved_target = check_target_difficulty(block_header, target, &self.pow_algorithms)?;
 
         Ok(achieved_target)
     }
@@ -81,6 +81,12 @@ pub enum ValidationError {
     MaxTransactionWeightExceeded,
     #[error("End of time: {0}")]
     EndOfTimeError(String),
+    #[error("Header #{height} does not match the checkpoint. Expected hash: {expected}, actual hash: {actual}")]
+    CheckpointMismatch {
+        height: u64,
+        expected: String,
+        actual: String,
+    },
 }
 
 // ChainStorageError has a ValidationError variant, so to prevent a cyclic dependency we use a string representation in
@@ -4,7 +4,13 @@ use crate::{
     consensus::ConsensusManager,
     proof_of_work::AchievedTargetDifficulty,
     validation::{
-        helpers::{check_header_timestamp_greater_than_median, check_pow_data, check_timestamp_ftl},
+        helpers::{
+            check_header_checkpoint,
+            check_header_timestamp_greater_than_median,
+            check_header_version,
+            check_pow_data,
+            check_timestamp_ftl,
+        },
         DifficultyCalculator,
         HeaderValidation,
         ValidationError,
@@ -54,6 +60,8 @@ impl HeaderValidator {
 impl<TBackend: BlockchainBackend> HeaderValidation<TBackend> for HeaderValidator {
     /// The consensus checks that are done (in order of cheapest to verify to most expensive):
     /// 1. Is the block timestamp within the Future Time Limit (FTL)?
+    /// 1. Is the header version valid for the features active at its height?
+    /// 1. Does the header match the checkpoint at its height (if any)?
     /// 1. Is the Proof of Work valid?
     /// 1. Is the achieved difficulty of this block >= the target difficulty for this block?
 
@@ -70,13 +78,25 @@ impl<TBackend: BlockchainBackend> HeaderValidation<TBackend> for HeaderValidator
             "BlockHeader validation: FTL timestamp is ok for {} ",
             header_id
         );
+        check_header_version(header, &self.rules, backend)?;
+        trace!(
+            target: LOG_TARGET,
+            "BlockHeader validation: Version is ok for {} ",
+            header_id
+        );
+        check_header_checkpoint(header, &self.rules)?;
+        trace!(
+            target: LOG_TARGET,
+            "BlockHeader validation: Checkpoint is ok for {} ",
+            header_id
+        );
         self.check_median_timestamp(backend, header)?;
         trace!(
             target: LOG_TARGET,
             "BlockHeader validation: Median timestamp is ok for {} ",
             header_id
         );
-        check_pow_data(header, &self.rules, backend)?;
+        check_pow_data(header, difficulty_calculator.pow_algorithms(), &self.rules, backend)?;
         let achieved_target = difficulty_calculator.check_achieved_and_target_difficulty(backend, header)?;
 
         trace!(
@@ -26,18 +26,9 @@ use crate::{
         Block,
         BlockValidationError,
     },
-    chain_storage::BlockchainBackend,
-    consensus::{ConsensusConstants, ConsensusManager},
-    proof_of_work::{
-        monero_difficulty,
-        monero_rx::MoneroPowData,
-        randomx_factory::RandomXFactory,
-        sha3_difficulty,
-        AchievedTargetDifficulty,
-        Difficulty,
-        PowAlgorithm,
-        PowError,
-    },
+    chain_storage::{BlockchainBackend, DbKey, DbValue},
+    consensus::{ConsensusConstants, ConsensusFeature, ConsensusManager, HEADER_BASE_VERSION_MASK},
+    proof_of_work::{AchievedTargetDifficulty, Difficulty, PowAlgorithmRegistry, PowError},
     transactions::types::CryptoFactories,
     validation::ValidationError,
 };
@@ -46,6 +37,73 @@ use tari_crypto::tari_utilities::{epoch_time::EpochTime, hash::Hashable, hex::He
 
 pub const LOG_TARGET: &str = "c::val::helpers";
 
+/// This function tests that the header matches the checkpoint at its height, if one is set
+pub fn check_header_checkpoint(
+    block_header: &BlockHeader,
+    consensus_manager: &ConsensusManager,
+) -> Result<(), ValidationError> {
+    if let Some(expected) = consensus_manager.checkpoint_at(block_header.height) {
+        let actual = block_header.hash();
+        if &actual != expected {
+            warn!(
+                target: LOG_TARGET,
+                "Header #{} ({}) does not match the checkpoint ({})",
+                block_header.height,
+                actual.to_hex(),
+                expected.to_hex()
+            );
+            return Err(ValidationError::CheckpointMismatch {
+                height: block_header.height,
+                expected: expected.to_hex(),
+                actual: actual.to_hex(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// This function tests that the header version is valid for its height. The version is only checked once
+/// [ConsensusFeature::StrictHeaderVersion] is active for the header on the chain that it extends.
+pub fn check_header_version<B: BlockchainBackend>(
+    block_header: &BlockHeader,
+    consensus_manager: &ConsensusManager,
+    db: &B,
+) -> Result<(), ValidationError> {
+    if !consensus_manager.is_feature_active_on_chain(
+        ConsensusFeature::StrictHeaderVersion,
+        block_header.height,
+        &block_header.prev_hash,
+        db,
+    )? {
+        return Ok(());
+    }
+    let expected = consensus_manager
+        .consensus_constants(block_header.height)
+        .blockchain_version();
+    let actual = block_header.version & HEADER_BASE_VERSION_MASK;
+    if actual != expected {
+        warn!(
+            target: LOG_TARGET,
+            "Header #{} has version {} but version {} is required", block_header.height, actual, expected
+        );
+        return Err(ValidationError::BlockHeaderError(
+            BlockHeaderValidationError::InvalidVersion { expected, actual },
+        ));
+    }
+    let unknown_bits =
+        block_header.version & !HEADER_BASE_VERSION_MASK & !consensus_manager.signal_bits(block_header.height);
+    if unknown_bits != 0 {
+        warn!(
+            target: LOG_TARGET,
+            "Header #{} signals unknown version bits {:#06x}", block_header.height, unknown_bits
+        );
+        return Err(ValidationError::BlockHeaderError(
+            BlockHeaderValidationError::UnknownVersionBits(unknown_bits),
+        ));
+    }
+    Ok(())
+}
+
 /// This function tests that the block timestamp is less than the FTL
 pub fn check_timestamp_ftl(
     block_header: &BlockHeader,
@@ -116,49 +174,26 @@ pub fn check_header_timestamp_greater_than_median(
     Ok(())
 }
 
-/// Check the PoW data in the BlockHeader. This currently only applies to blocks merged mined with Monero.
+/// Check the PoW data in the BlockHeader using the algorithm registered for the header's `pow_algo`.
 pub fn check_pow_data<B: BlockchainBackend>(
     block_header: &BlockHeader,
+    pow_algorithms: &PowAlgorithmRegistry,
     rules: &ConsensusManager,
     db: &B,
 ) -> Result<(), ValidationError> {
-    use PowAlgorithm::*;
-    match block_header.pow.pow_algo {
-        Monero => {
-            let monero_data =
-                MoneroPowData::from_header(block_header).map_err(|e| ValidationError::CustomError(e.to_string()))?;
-            let seed_height = db.fetch_monero_seed_first_seen_height(&monero_data.randomx_key)?;
-            if (seed_height != 0) &&
-                (block_header.height - seed_height >
-                    rules.consensus_constants(block_header.height).max_randomx_seed_height())
-            {
-                return Err(ValidationError::BlockHeaderError(
-                    BlockHeaderValidationError::OldSeedHash,
-                ));
-            }
-
-            Ok(())
-        },
-        Sha3 => {
-            if !block_header.pow.pow_data.is_empty() {
-                return Err(ValidationError::CustomError(
-                    "Proof of work data must be empty for Sha3 blocks".to_string(),
-                ));
-            }
-            Ok(())
-        },
-    }
+    pow_algorithms
+        .get(block_header.pow_algo())?
+        .validate_pow_data(block_header, rules, db)
 }
 
 pub fn check_target_difficulty(
     block_header: &BlockHeader,
     target: Difficulty,
-    randomx_factory: &RandomXFactory,
+    pow_algorithms: &PowAlgorithmRegistry,
 ) -> Result<AchievedTargetDifficulty, ValidationError> {
-    let achieved = match block_header.pow_algo() {
-        PowAlgorithm::Monero => monero_difficulty(block_header, randomx_factory)?,
-        PowAlgorithm::Sha3 => sha3_difficulty(block_header),
-    };
+    let achieved = pow_algorithms
+        .get(block_header.pow_algo())?
+        .achieved_difficulty(block_header)?;
 
     match AchievedTargetDifficulty::try_construct(block_header.pow_algo(), target, achieved) {
         Some(achieved_target) => Ok(achieved_target),
@@ -196,10 +231,36 @@ pub fn check_block_weight(block: &Block, consensus_constants: &ConsensusConstant
     }
 }
 
+/// Checks that the kernel sum and script offset of the block balance, and verifies the range proofs and signatures.
+/// This also applies to a genesis block, which has to pay exactly the block reward at height 0.
 pub fn check_accounting_balance(
     block: &Block,
     rules: &ConsensusManager,
     factories: &CryptoFactories,
+) -> Result<(), ValidationError> {
+    let offset = &block.header.total_kernel_offset;
+    let script_offset = &block.header.total_script_offset;
+    let total_coinbase = rules.calculate_coinbase_and_fees(block);
+    block
+        .body
+        .validate_internal_consistency(&offset, &script_offset, total_coinbase, factories)
+        .map_err(|err| {
+            warn!(
+                target: LOG_TARGET,
+                "Internal validation failed on block:{}:{}",
+                block.hash().to_hex(),
+                err
+            );
+            ValidationError::TransactionError(err)
+        })
+}
+
+/// Checks that the kernel sum and script offset of the block balance without verifying range proofs or signatures.
+/// This is only used for blocks that are buried below the assume-valid block.
+pub fn check_accounting_balance_assume_valid(
+    block: &Block,
+    rules: &ConsensusManager,
+    factories: &CryptoFactories,
 ) -> Result<(), ValidationError> {
     if block.header.height == 0 {
         // Gen block does not need to be checked for this.
@@ -210,11 +271,11 @@ pub fn check_accounting_balance(
     let total_coinbase = rules.calculate_coinbase_and_fees(block);
     block
         .body
-        .validate_internal_consistency(&offset, &script_offset, total_coinbase, factories)
+        .validate_balance(&offset, &script_offset, total_coinbase, factories)
         .map_err(|err| {
             warn!(
                 target: LOG_TARGET,
-                "Internal validation failed on block:{}:{}",
+                "Balance validation failed on block:{}:{}",
                 block.hash().to_hex(),
                 err
             );
@@ -222,6 +283,31 @@ pub fn check_accounting_balance(
         })
 }
 
+/// Returns true if the block is on the main chain at or below the assume-valid block. The assume-valid header must
+/// already be part of the main chain, which is the case once header sync has completed.
+pub fn is_assumed_valid<B: BlockchainBackend>(
+    block: &Block,
+    rules: &ConsensusManager,
+    db: &B,
+) -> Result<bool, ValidationError> {
+    let trusted_hash = match rules.assume_valid_hash() {
+        Some(hash) => hash,
+        None => return Ok(false),
+    };
+    let trusted_height = match db.fetch(&DbKey::BlockHash(trusted_hash.clone()))? {
+        Some(DbValue::BlockHash(header)) => header.height,
+        _ => return Ok(false),
+    };
+    if block.header.height > trusted_height {
+        return Ok(false);
+    }
+    let header = match db.fetch(&DbKey::BlockHeader(block.header.height))? {
+        Some(DbValue::BlockHeader(header)) => header,
+        _ => return Ok(false),
+    };
+    Ok(header.hash() == block.hash())
+}
+
 pub fn check_coinbase_output(
     block: &Block,
     rules: &ConsensusManager,
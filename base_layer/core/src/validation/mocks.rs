@@ -67,7 +67,11 @@ impl MockValidator {
 }
 
 impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for MockValidator {
-    fn validate_body(&self, _item: &Block, _db: &B) -> Result<(), ValidationError> {
+    fn is_assumed_valid(&self, _item: &Block, _db: &B) -> Result<bool, ValidationError> {
+        Ok(false)
+    }
+
+    fn validate_body_stateless(&self, _item: &Block, _assumed_valid: bool) -> Result<(), ValidationError> {
         if self.is_valid.load(Ordering::SeqCst) {
             Ok(())
         } else {
@@ -76,6 +80,10 @@ impl<B: BlockchainBackend> CandidateBlockBodyValidation<B> for MockValidator {
             ))
         }
     }
+
+    fn validate_body_stateful(&self, _item: &Block, _db: &B) -> Result<(), ValidationError> {
+        Ok(())
+    }
 }
 
 impl<B: BlockchainBackend> PostOrphanBodyValidation<B> for MockValidator {
@@ -21,11 +21,22 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    blocks::BlockHeader,
-    consensus::ConsensusManagerBuilder,
+    blocks::{Block, BlockHeader, BlockHeaderValidationError},
+    chain_storage::{ChainBlock, ChainHeader, DbTransaction},
+    consensus::{
+        ConsensusFeature,
+        ConsensusManagerBuilder,
+        ConsensusManagerError,
+        FeatureActivation,
+        FeatureState,
+        VersionBitActivation,
+        HEADER_VERSION_SIGNAL_BITS,
+    },
     test_helpers::{blockchain::create_store_with_consensus, create_chain_header},
-    validation::header_iter::HeaderIter,
+    transactions::aggregated_body::AggregateBody,
+    validation::{header_iter::HeaderIter, helpers::check_header_version, ValidationError},
 };
+use std::sync::Arc;
 use tari_common::configuration::Network;
 
 #[test]
@@ -76,6 +87,195 @@ fn header_iter_fetch_in_chunks() {
     })
 }
 
+#[test]
+fn feature_activation_and_header_version() {
+    let strict_header_version = VersionBitActivation {
+        bit: 1,
+        start_height: 4,
+        window: 4,
+        threshold: 3,
+        mandatory_height: Some(100),
+    };
+    let rules = ConsensusManagerBuilder::new(Network::LocalNet)
+        .with_feature_activation(ConsensusFeature::KernelLockHeight, FeatureActivation::Height(10))
+        .unwrap()
+        .with_feature_activation(
+            ConsensusFeature::StrictHeaderVersion,
+            FeatureActivation::VersionBit(strict_header_version),
+        )
+        .unwrap()
+        .build();
+    let version = rules.consensus_constants(0).blockchain_version();
+    let signal = 1 << 9;
+
+    assert!(!rules.is_feature_active(ConsensusFeature::KernelLockHeight, 9));
+    assert!(rules.is_feature_active(ConsensusFeature::KernelLockHeight, 10));
+    assert!(!rules.is_feature_active(ConsensusFeature::StrictHeaderVersion, 99));
+    assert!(rules.is_feature_active(ConsensusFeature::StrictHeaderVersion, 100));
+    assert_eq!(rules.signal_bits(3), 0);
+    assert_eq!(rules.signal_bits(4), signal);
+    assert_eq!(rules.header_version(4), version | signal);
+
+    // Window 4..8 has 2 signals, below the threshold. Window 8..12 has 3 signals, which locks the feature in for the
+    // window 12..16, and activates it from height 16.
+    let signalling = [5, 7, 8, 10, 11];
+    let db = create_store_with_consensus(rules.clone());
+    let extend = |prev: &ChainHeader, heights: std::ops::RangeInclusive<u64>, signalling: &[u64], uniq: u64| {
+        heights.fold(vec![prev.clone()], |mut acc, i| {
+            let prev = acc.last().unwrap();
+            let mut header = BlockHeader::new(if signalling.contains(&i) {
+                version | signal
+            } else {
+                version
+            });
+            header.height = i;
+            header.prev_hash = prev.hash().clone();
+            // These have to be unique
+            header.kernel_mmr_size = 2 + i + uniq;
+            header.output_mmr_size = 4001 + i;
+            let chain_header = create_chain_header(header, &prev.accumulated_data());
+            acc.push(chain_header);
+            acc
+        })
+    };
+    let headers = extend(&db.fetch_chain_header(0).unwrap(), 1..=16, &signalling, 0);
+    let hashes = headers.iter().map(|h| h.hash().clone()).collect::<Vec<_>>();
+    // A fork from height 7 that does not signal after the fork point, and runs past the main chain tip
+    let fork = extend(&headers[7], 8..=20, &[], 1000);
+    db.insert_valid_headers(headers.into_iter().skip(1).collect()).unwrap();
+    let mut txn = DbTransaction::new();
+    for header in fork.iter().skip(1) {
+        let block = Arc::new(Block::new(header.header().clone(), AggregateBody::empty()));
+        txn.insert_chained_orphan(Arc::new(
+            ChainBlock::try_construct(block, header.accumulated_data().clone()).unwrap(),
+        ));
+    }
+    db.commit(txn).unwrap();
+    let db = db.db_read_access().unwrap();
+
+    let state = |height: u64| {
+        rules
+            .feature_state(
+                ConsensusFeature::StrictHeaderVersion,
+                height,
+                &hashes[height as usize - 1],
+                &*db,
+            )
+            .unwrap()
+    };
+    assert_eq!(state(3), FeatureState::Defined);
+    assert_eq!(state(4), FeatureState::Started);
+    assert_eq!(state(11), FeatureState::Started);
+    assert_eq!(state(12), FeatureState::LockedIn);
+    assert_eq!(state(15), FeatureState::LockedIn);
+    assert_eq!(state(16), FeatureState::Active);
+    // Served from the cached window states
+    assert_eq!(state(17), FeatureState::Active);
+    assert!(!rules
+        .is_feature_active_on_chain(ConsensusFeature::KernelLockHeight, 9, &hashes[8], &*db)
+        .unwrap());
+    assert!(rules
+        .is_feature_active_on_chain(ConsensusFeature::KernelLockHeight, 10, &hashes[9], &*db)
+        .unwrap());
+
+    // The fork is judged by its own signals, including past the main chain tip
+    let fork_state = |height: u64| {
+        rules
+            .feature_state(
+                ConsensusFeature::StrictHeaderVersion,
+                height,
+                fork[(height - 8) as usize].hash(),
+                &*db,
+            )
+            .unwrap()
+    };
+    assert_eq!(fork_state(11), FeatureState::Started);
+    assert_eq!(fork_state(12), FeatureState::Started);
+    assert_eq!(fork_state(17), FeatureState::Started);
+    assert_eq!(fork_state(21), FeatureState::Started);
+    // The main chain states are unaffected by the fork
+    assert_eq!(state(12), FeatureState::LockedIn);
+    assert_eq!(state(17), FeatureState::Active);
+
+    // Signalling alone does not opt a block into the stricter rules
+    let mut header = BlockHeader::new((version + 1) | signal);
+    header.height = 15;
+    header.prev_hash = hashes[14].clone();
+    check_header_version(&header, &rules, &*db).unwrap();
+
+    // Once active, the base version must match and unknown signal bits are rejected
+    header.height = 17;
+    header.prev_hash = hashes[16].clone();
+    let err = check_header_version(&header, &rules, &*db).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::BlockHeaderError(BlockHeaderValidationError::InvalidVersion { .. })
+    ));
+    header.version = version | 1 << 12;
+    let err = check_header_version(&header, &rules, &*db).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::BlockHeaderError(BlockHeaderValidationError::UnknownVersionBits(_))
+    ));
+    header.version = version | signal;
+    check_header_version(&header, &rules, &*db).unwrap();
+}
+
+#[test]
+fn invalid_feature_activations() {
+    let activation = VersionBitActivation {
+        bit: 1,
+        start_height: 0,
+        window: 10,
+        threshold: 8,
+        mandatory_height: None,
+    };
+    let builder = ConsensusManagerBuilder::new(Network::LocalNet)
+        .with_feature_activation(
+            ConsensusFeature::StrictHeaderVersion,
+            FeatureActivation::VersionBit(activation),
+        )
+        .unwrap();
+
+    let err = builder
+        .with_feature_activation(
+            ConsensusFeature::KernelLockHeight,
+            FeatureActivation::VersionBit(activation),
+        )
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err, ConsensusManagerError::InvalidFeatureActivation {
+        feature: ConsensusFeature::KernelLockHeight,
+        ..
+    }));
+
+    for activation in &[
+        VersionBitActivation {
+            bit: HEADER_VERSION_SIGNAL_BITS,
+            ..activation
+        },
+        VersionBitActivation {
+            window: 0,
+            ..activation
+        },
+        VersionBitActivation {
+            threshold: 11,
+            ..activation
+        },
+        VersionBitActivation {
+            threshold: 0,
+            ..activation
+        },
+    ] {
+        assert!(ConsensusManagerBuilder::new(Network::LocalNet)
+            .with_feature_activation(
+                ConsensusFeature::KernelLockHeight,
+                FeatureActivation::VersionBit(*activation)
+            )
+            .is_err());
+    }
+}
+
 #[test]
 #[ignore]
 // TODO: Fix this test with the new DB structure
@@ -31,7 +31,23 @@ use crate::{
 /// A validator that determines if a block body is valid, assuming that the header has already been
 /// validated
 pub trait CandidateBlockBodyValidation<B: BlockchainBackend>: Send + Sync {
-    fn validate_body(&self, block: &Block, backend: &B) -> Result<(), ValidationError>;
+    /// Returns true if the block is buried below the assume-valid block, in which case its range proofs and signatures
+    /// are not verified by [validate_body_stateless](Self::validate_body_stateless)
+    fn is_assumed_valid(&self, block: &Block, backend: &B) -> Result<bool, ValidationError>;
+
+    /// Checks that do not depend on the UTXO set or MMR state. These may be run concurrently for many blocks, and do
+    /// not need access to the database, so `assumed_valid` must be resolved with
+    /// [is_assumed_valid](Self::is_assumed_valid) beforehand.
+    fn validate_body_stateless(&self, block: &Block, assumed_valid: bool) -> Result<(), ValidationError>;
+
+    /// Checks against the chain state. Blocks must be validated in chain order, after the previous block was committed.
+    fn validate_body_stateful(&self, block: &Block, backend: &B) -> Result<(), ValidationError>;
+
+    fn validate_body(&self, block: &Block, backend: &B) -> Result<(), ValidationError> {
+        let assumed_valid = self.is_assumed_valid(block, backend)?;
+        self.validate_body_stateless(block, assumed_valid)?;
+        self.validate_body_stateful(block, backend)
+    }
 }
 
 /// A validator that validates a body after it has been determined to be a valid orphan
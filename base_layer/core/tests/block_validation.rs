@@ -61,7 +61,7 @@ fn test_genesis_block() {
     let rules = ConsensusManagerBuilder::new(network).build();
     let backend = create_test_db();
     let validators = Validators::new(
-        BodyOnlyValidator::default(),
+        BodyOnlyValidator::new(rules.clone()),
         HeaderValidator::new(rules.clone()),
         OrphanBlockValidator::new(rules.clone(), factories),
     );
@@ -207,6 +207,16 @@ fn inputs_are_not_maleable() {
     input_mut.input_data = inputs![StackItem::PublicKey(malicious_pubkey)];
     input_mut.script_signature = comsig_sign(&input.spending_key, input.value, input_mut, script_private_key);
 
+    // The malleated block is internally consistent, only the chain state checks can reject it
+    validator.validate_body_stateless(&block, false).unwrap();
+    let err = validator
+        .validate_body_stateful(&block, &*blockchain.store().db_read_access().unwrap())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::BlockError(BlockValidationError::MismatchedMmrRoots)
+    ));
+
     let err = validator
         .validate_body(&block, &*blockchain.store().db_read_access().unwrap())
         .unwrap_err();
@@ -34,7 +34,7 @@ use crate::helpers::{
         generate_new_block_with_coinbase,
     },
     database::create_orphan_block,
-    sample_blockchains::{create_new_blockchain, create_new_blockchain_lmdb},
+    sample_blockchains::{create_blockchain_db_no_cut_through, create_new_blockchain, create_new_blockchain_lmdb},
     test_blockchain::TestBlockchain,
 };
 use rand::{rngs::OsRng, RngCore};
@@ -44,32 +44,41 @@ use tari_core::{
     blocks::{genesis_block, Block, BlockHeader},
     chain_storage::{
         create_lmdb_database,
+        migrate_lmdb_database,
         BlockAddResult,
         BlockchainBackend,
         BlockchainDatabase,
         BlockchainDatabaseConfig,
         ChainStorageError,
         DbTransaction,
+        Reorg,
+        SnapshotError,
         Validators,
+        MAX_REORGS_PER_PAGE,
+        MAX_REORG_JOURNAL_ENTRIES,
     },
     consensus::{emission::Emission, ConsensusConstantsBuilder, ConsensusManagerBuilder},
     proof_of_work::Difficulty,
     test_helpers::blockchain::{
         create_store_with_consensus,
         create_store_with_consensus_and_validators,
+        create_store_with_consensus_and_validators_and_config,
         create_test_blockchain_db,
         create_test_db,
     },
     transactions::{
         helpers::{schema_to_transaction, spend_utxos},
         tari_amount::{uT, MicroTari, T},
-        types::CryptoFactories,
+        types::{CryptoFactories, HashDigest},
     },
     tx,
     txn_schema,
     validation::{mocks::MockValidator, DifficultyCalculator, ValidationError},
 };
-use tari_crypto::{script::StackItem, tari_utilities::Hashable};
+use tari_crypto::{
+    script::StackItem,
+    tari_utilities::{epoch_time::EpochTime, Hashable},
+};
 use tari_storage::lmdb_store::LMDBConfig;
 use tari_test_utils::{paths::create_temporary_data_path, unpack_enum};
 
@@ -235,6 +244,62 @@ fn rewind_to_height() {
     assert_eq!(db.get_height().unwrap(), 3);
     db.rewind_to_height(1).unwrap();
     assert_eq!(db.get_height().unwrap(), 1);
+
+    // Only the rewind that removed blocks is journaled
+    let reorgs = db.fetch_reorgs(0, 10).unwrap().reorgs;
+    assert_eq!(reorgs.len(), 1);
+    assert_eq!(reorgs[0].fork_height, 1);
+    assert_eq!(&reorgs[0].new_tip_hash, blocks[1].hash());
+    assert_eq!(reorgs[0].removed_blocks, vec![
+        blocks[3].hash().clone(),
+        blocks[2].hash().clone()
+    ]);
+    assert!(reorgs[0].added_blocks.is_empty());
+    // The blocks of the new chain are not known yet, so neither are the dropped transactions
+    assert!(reorgs[0].dropped_kernels.is_none());
+}
+
+#[test]
+fn reorg_journal_is_pruned_and_paged() {
+    let db = create_test_blockchain_db();
+    let num_reorgs = MAX_REORG_JOURNAL_ENTRIES + 5;
+    for height in 0..num_reorgs {
+        let mut txn = DbTransaction::new();
+        txn.insert_reorg(Reorg {
+            local_time: EpochTime::now(),
+            fork_height: height,
+            fork_hash: vec![],
+            prev_tip_height: height + 1,
+            prev_tip_hash: vec![],
+            new_tip_height: height + 1,
+            new_tip_hash: vec![],
+            removed_blocks: vec![vec![]],
+            added_blocks: vec![vec![]],
+            dropped_kernels: Some(vec![]),
+        });
+        db.commit(txn).unwrap();
+    }
+
+    // The oldest reorgs were pruned and pages are capped in size
+    let page = db.fetch_reorgs(0, usize::MAX).unwrap();
+    assert_eq!(page.reorgs.len(), MAX_REORGS_PER_PAGE);
+    assert_eq!(page.reorgs[0].fork_height, 5);
+    assert_eq!(page.next_start, 5 + MAX_REORGS_PER_PAGE as u64);
+
+    let mut reorgs = page.reorgs;
+    let mut start = page.next_start;
+    loop {
+        let page = db.fetch_reorgs(start, MAX_REORGS_PER_PAGE).unwrap();
+        if page.reorgs.is_empty() {
+            assert_eq!(page.next_start, start);
+            break;
+        }
+        start = page.next_start;
+        reorgs.extend(page.reorgs);
+    }
+    assert_eq!(reorgs.len() as u64, MAX_REORG_JOURNAL_ENTRIES);
+    assert!(reorgs.windows(2).all(|w| w[1].fork_height == w[0].fork_height + 1));
+    assert_eq!(reorgs.last().unwrap().fork_height, num_reorgs - 1);
 }
 
 #[test]
@@ -342,6 +407,16 @@ fn handle_tip_reorg() {
     // Check that B2 was removed from the block orphans and A2 has been orphaned.
     assert!(store.fetch_orphan(orphan_blocks[2].hash().clone()).is_err());
     assert!(store.fetch_orphan(blocks[2].hash().clone()).is_ok());
+
+    // Check that the reorg was journaled and that the A2 transaction was dropped
+    let reorgs = store.fetch_reorgs(0, 10).unwrap().reorgs;
+    assert_eq!(reorgs.len(), 1);
+    assert_eq!(reorgs[0].fork_height, 1);
+    assert_eq!(&reorgs[0].fork_hash, blocks[1].hash());
+    assert_eq!(reorgs[0].depth(), 1);
+    assert_eq!(reorgs[0].removed_blocks, vec![blocks[2].hash().clone()]);
+    assert_eq!(reorgs[0].added_blocks, vec![orphan_blocks[2].hash().clone()]);
+    assert_eq!(reorgs[0].dropped_kernels.as_ref().map(Vec::len), Some(1));
 }
 
 #[test]
@@ -2170,3 +2245,329 @@ fn pruned_mode_fetch_insert_and_commit() {
     // );
     unimplemented!()
 }
+
+#[test]
+fn snapshot_export_and_import() {
+    let factories = CryptoFactories::default();
+    let (db, blocks, _, consensus_manager) = create_blockchain_db_no_cut_through();
+    let mut snapshot = Vec::new();
+    let horizon_data = db.export_snapshot(5, &mut snapshot).unwrap();
+
+    // Snapshots can only be imported into pruned nodes
+    let archival_db = create_store_with_consensus(consensus_manager.clone());
+    let err = archival_db
+        .import_snapshot(&snapshot[..], &MockValidator::new(true), &factories.range_proof)
+        .unwrap_err();
+    assert!(matches!(err, SnapshotError::DatabaseNotEmpty(_)));
+
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let config = BlockchainDatabaseConfig {
+        pruning_horizon: 2,
+        ..Default::default()
+    };
+    let pruned_db = create_store_with_consensus_and_validators_and_config(consensus_manager, validators, config);
+
+    // A failed import is rolled back, leaving only the genesis block
+    let err = pruned_db
+        .import_snapshot(&snapshot[..], &MockValidator::new(false), &factories.range_proof)
+        .unwrap_err();
+    assert!(matches!(err, SnapshotError::FinalStateValidationFailed(_)));
+    assert_eq!(pruned_db.get_height().unwrap(), 0);
+    assert_eq!(pruned_db.fetch_last_header().unwrap().height, 0);
+    assert!(pruned_db.fetch_header(1).unwrap().is_none());
+    assert!(pruned_db
+        .fetch_block_accumulated_data(blocks[1].hash().clone())
+        .is_err());
+
+    // The same database can then import the snapshot
+    let tip = pruned_db
+        .import_snapshot(&snapshot[..], &MockValidator::new(true), &factories.range_proof)
+        .unwrap();
+    assert_eq!(tip.hash(), blocks[5].hash());
+
+    let metadata = pruned_db.get_chain_metadata().unwrap();
+    assert_eq!(metadata.height_of_longest_chain(), 5);
+    assert_eq!(metadata.pruned_height(), 5);
+    let imported_horizon_data = pruned_db.fetch_horizon_data().unwrap().unwrap();
+    assert_eq!(imported_horizon_data.kernel_sum(), horizon_data.kernel_sum());
+    assert_eq!(imported_horizon_data.utxo_sum(), horizon_data.utxo_sum());
+    assert_eq!(
+        pruned_db
+            .fetch_block_accumulated_data(blocks[5].hash().clone())
+            .unwrap()
+            .deleted(),
+        db.fetch_block_accumulated_data(blocks[5].hash().clone())
+            .unwrap()
+            .deleted()
+    );
+}
+
+#[test]
+fn snapshot_import_rejects_different_genesis_block() {
+    let factories = CryptoFactories::default();
+    let (db, _, _, _) = create_blockchain_db_no_cut_through();
+    let mut snapshot = Vec::new();
+    db.export_snapshot(3, &mut snapshot).unwrap();
+
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let config = BlockchainDatabaseConfig {
+        pruning_horizon: 2,
+        ..Default::default()
+    };
+    let consensus_manager = ConsensusManagerBuilder::new(Network::Weatherwax).build();
+    let other_db = create_store_with_consensus_and_validators_and_config(consensus_manager, validators, config);
+    let err = other_db
+        .import_snapshot(&snapshot[..], &MockValidator::new(true), &factories.range_proof)
+        .unwrap_err();
+    unpack_enum!(SnapshotError::InvalidSnapshot(msg) = err);
+    assert!(msg.contains("genesis block"));
+}
+
+#[test]
+fn output_index() {
+    let temp_path = create_temporary_data_path();
+    {
+        let network = Network::LocalNet;
+        let validators = || {
+            Validators::new(
+                MockValidator::new(true),
+                MockValidator::new(true),
+                MockValidator::new(true),
+            )
+        };
+        let (mut store, mut blocks, mut outputs, consensus_manager) =
+            create_new_blockchain_lmdb(network, &temp_path, validators(), Default::default());
+        let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T, 10 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+        let output = blocks[1].block().body.outputs()[0].clone();
+        let err = store
+            .fetch_outputs_by_commitment(output.commitment.clone())
+            .unwrap_err();
+        assert!(matches!(err, ChainStorageError::OutputIndexNotEnabled));
+        drop(store);
+
+        // Enabling the index on an existing database builds it from the stored blocks
+        let mut backend = create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap();
+        backend.set_output_index_enabled(true).unwrap();
+        let mut store = BlockchainDatabase::new(
+            backend,
+            consensus_manager.clone(),
+            validators(),
+            Default::default(),
+            DifficultyCalculator::new(consensus_manager.clone(), Default::default()),
+            false,
+        )
+        .unwrap();
+
+        let found = store.fetch_outputs_by_commitment(output.commitment.clone()).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, output);
+        assert_eq!(found[0].2, 1);
+        let script_hash = output.script.as_hash::<HashDigest>().unwrap().to_vec();
+        let found = store.fetch_outputs_by_script_hash(script_hash).unwrap();
+        assert!(found.iter().any(|(o, _, _)| *o == output));
+        let genesis_output_hash = blocks[0].block().body.outputs()[0].hash();
+        assert_eq!(
+            store.fetch_output_spent_in_block(genesis_output_hash).unwrap(),
+            Some((blocks[1].hash().clone(), 1))
+        );
+        assert_eq!(store.fetch_output_spent_in_block(output.hash()).unwrap(), None);
+
+        // New blocks are indexed when they are added, and removed from the index when they are rewound
+        let txs = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![5 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+        let spent_hash = blocks[2].block().body.inputs()[0].output_hash();
+        assert_eq!(
+            store.fetch_output_spent_in_block(spent_hash.clone()).unwrap(),
+            Some((blocks[2].hash().clone(), 2))
+        );
+        let new_output = blocks[2].block().body.outputs()[0].clone();
+        assert_eq!(
+            store
+                .fetch_outputs_by_commitment(new_output.commitment.clone())
+                .unwrap()
+                .len(),
+            1
+        );
+
+        store.rewind_to_height(1).unwrap();
+        assert_eq!(store.fetch_output_spent_in_block(spent_hash).unwrap(), None);
+        assert!(store
+            .fetch_outputs_by_commitment(new_output.commitment)
+            .unwrap()
+            .is_empty());
+    }
+    // Cleanup test data - in Windows the LMBD `set_mapsize` sets file size equals to map size; Linux use sparse files
+    if std::path::Path::new(&temp_path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&temp_path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
+
+#[test]
+fn archived_blocks_are_read_from_block_files() {
+    let temp_path = create_temporary_data_path();
+    {
+        let network = Network::LocalNet;
+        let validators = || {
+            Validators::new(
+                MockValidator::new(true),
+                MockValidator::new(true),
+                MockValidator::new(true),
+            )
+        };
+        let (store, mut blocks, mut outputs, consensus_manager) =
+            create_new_blockchain_lmdb(network, &temp_path, validators(), Default::default());
+        drop(store);
+
+        let mut backend = create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap();
+        backend.set_archive_depth(Some(1));
+        let mut store = BlockchainDatabase::new(
+            backend,
+            consensus_manager.clone(),
+            validators(),
+            Default::default(),
+            DifficultyCalculator::new(consensus_manager.clone(), Default::default()),
+            false,
+        )
+        .unwrap();
+        let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T, 10 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+        let txs = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![5 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+        let txs = vec![txn_schema!(from: vec![outputs[1][1].clone()], to: vec![5 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+
+        // Blocks 0 and 1 are buried deeper than the archive depth and have been moved to the block file
+        assert!(std::path::Path::new(&temp_path)
+            .join("archive")
+            .join("blocks_00000.dat")
+            .exists());
+        for (height, block) in blocks.iter().enumerate() {
+            let fetched = Block::from(store.fetch_block(height as u64).unwrap());
+            assert_eq!(block.block(), &fetched);
+        }
+        let fetched = store.fetch_blocks(0..=1).unwrap();
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(fetched[1].block(), blocks[1].block());
+        assert!(store.check_integrity().unwrap().is_ok());
+
+        // The unspent outputs and kernels of archived blocks are read from the archive
+        let coinbase = blocks[1]
+            .block()
+            .body
+            .outputs()
+            .iter()
+            .find(|o| o.is_coinbase())
+            .unwrap();
+        let output = store.fetch_utxo(coinbase.hash()).unwrap().unwrap();
+        assert_eq!(output.hash(), coinbase.hash());
+        let kernel = &blocks[1].block().body.kernels()[0];
+        let (fetched, header_hash) = store
+            .fetch_kernel_by_excess_sig(kernel.excess_sig.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(&fetched, kernel);
+        assert_eq!(header_hash, blocks[1].block().hash());
+
+        // Outputs that become unspent when a block is rewound are read from the archive
+        let spent_hash = blocks[2].block().body.inputs()[0].output_hash();
+        store.rewind_to_height(1).unwrap();
+        let output = store.fetch_utxo(spent_hash.clone()).unwrap().unwrap();
+        assert_eq!(output.hash(), spent_hash);
+
+        // Rewinding an archived block removes it from the archive
+        let spent_hash = blocks[1].block().body.inputs()[0].output_hash();
+        store.rewind_to_height(0).unwrap();
+        assert!(store.fetch_utxo(spent_hash).unwrap().is_some());
+        assert!(store.check_integrity().unwrap().is_ok());
+    }
+    // Cleanup test data - in Windows the LMBD `set_mapsize` sets file size equals to map size; Linux use sparse files
+    if std::path::Path::new(&temp_path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&temp_path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
+
+#[test]
+fn integrity_check_and_repair() {
+    let temp_path = create_temporary_data_path();
+    {
+        let network = Network::LocalNet;
+        let validators = Validators::new(
+            MockValidator::new(true),
+            MockValidator::new(true),
+            MockValidator::new(true),
+        );
+        let (mut store, mut blocks, mut outputs, consensus_manager) =
+            create_new_blockchain_lmdb(network, &temp_path, validators, Default::default());
+        let txs = vec![txn_schema!(from: vec![outputs[0][0].clone()], to: vec![10 * T, 10 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+        let txs = vec![txn_schema!(from: vec![outputs[1][0].clone()], to: vec![5 * T])];
+        generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+        assert!(store.check_integrity().unwrap().is_ok());
+
+        // A chain tip that does not refer to a stored orphan
+        let mut txn = DbTransaction::new();
+        txn.insert_orphan_chain_tip(vec![1u8; 32]);
+        store.commit(txn).unwrap();
+
+        let report = store.check_integrity().unwrap();
+        assert_eq!(report.issues().len(), 1);
+        assert_eq!(report.issues()[0].table, "orphan_chain_tips");
+        assert!(report.has_repairable_issues());
+
+        let report = store.repair_integrity().unwrap();
+        assert_eq!(report.issues().len(), 1);
+        assert!(store.check_integrity().unwrap().is_ok());
+    }
+    // Cleanup test data - in Windows the LMBD `set_mapsize` sets file size equals to map size; Linux use sparse files
+    if std::path::Path::new(&temp_path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&temp_path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
+
+#[test]
+fn lmdb_schema_migrations() {
+    let temp_path = create_temporary_data_path();
+    {
+        let report = migrate_lmdb_database(&temp_path, LMDBConfig::default(), true).unwrap();
+        assert!(report.is_up_to_date());
+        assert!(report.dry_run);
+
+        let network = Network::LocalNet;
+        let validators = Validators::new(
+            MockValidator::new(true),
+            MockValidator::new(true),
+            MockValidator::new(true),
+        );
+        let (store, _, _, _) = create_new_blockchain_lmdb(network, &temp_path, validators, Default::default());
+        let tip_height = store.get_chain_metadata().unwrap().height_of_longest_chain();
+        drop(store);
+
+        // A database created at the latest version has nothing to migrate
+        let report = migrate_lmdb_database(&temp_path, LMDBConfig::default(), false).unwrap();
+        assert!(report.is_up_to_date());
+        assert_eq!(report.from_version, report.to_version);
+        let db = create_lmdb_database(&temp_path, LMDBConfig::default()).unwrap();
+        assert_eq!(db.fetch_chain_metadata().unwrap().height_of_longest_chain(), tip_height);
+    }
+    // Cleanup test data - in Windows the LMBD `set_mapsize` sets file size equals to map size; Linux use sparse files
+    if std::path::Path::new(&temp_path).exists() {
+        if let Err(e) = std::fs::remove_dir_all(&temp_path) {
+            println!("\n{:?}\n", e)
+        }
+    }
+}
@@ -201,6 +201,83 @@ fn test_insert_and_process_published_block() {
     assert_eq!(stats.total_weight, 30);
 }
 
+#[test]
+#[allow(clippy::identity_op)]
+fn test_persist_and_restore() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager) = create_new_blockchain(network);
+    let mempool_validator = TxInputAndMaturityValidator::new(store.clone());
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator.clone()));
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T, 2 * T, 2 * T],fee: 25.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(&mut store, &mut blocks, &mut outputs, txs, &consensus_manager).unwrap();
+
+    let tx2 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx2 = Arc::new(spend_utxos(tx2).0);
+    let tx3 = txn_schema!(from: vec![outputs[1][1].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx3 = Arc::new(spend_utxos(tx3).0);
+    let tx4 = txn_schema!(from: vec![outputs[1][2].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx4 = Arc::new(spend_utxos(tx4).0);
+    mempool.insert(tx2.clone()).unwrap();
+    mempool.insert(tx3.clone()).unwrap();
+    mempool.insert(tx4.clone()).unwrap();
+
+    // tx4 is mined and moves to the reorg pool before the mempool is written to disk
+    generate_block(&store, &mut blocks, vec![tx4.deref().clone()], &consensus_manager).unwrap();
+    mempool.process_published_block(blocks[2].to_arc_block()).unwrap();
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("mempool.dat");
+    mempool.save_to_file(&path).unwrap();
+
+    // tx2 is mined while the node is offline
+    generate_block(&store, &mut blocks, vec![tx2.deref().clone()], &consensus_manager).unwrap();
+
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator.clone()));
+    assert_eq!(mempool.load_from_file(&path).unwrap(), 2);
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(tx2.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::NotStored
+    );
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(tx3.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(tx4.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::ReorgPool
+    );
+
+    // Unconfirmed transactions that expired while the node was offline are not restored
+    let mut mempool_config = MempoolConfig::default();
+    mempool_config.unconfirmed_pool.tx_expiry = Duration::from_secs(0);
+    let mempool = Mempool::new(mempool_config, Arc::new(mempool_validator.clone()));
+    assert_eq!(mempool.load_from_file(&path).unwrap(), 1);
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(tx3.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::NotStored
+    );
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(tx4.body.kernels()[0].excess_sig.clone())
+            .unwrap(),
+        TxStorageResponse::ReorgPool
+    );
+
+    // Loading a file that does not exist leaves the mempool empty
+    let mempool = Mempool::new(MempoolConfig::default(), Arc::new(mempool_validator));
+    assert_eq!(mempool.load_from_file(&temp_dir.path().join("missing.dat")).unwrap(), 0);
+}
+
 #[test]
 #[allow(clippy::identity_op)]
 fn test_time_locked() {
@@ -1079,7 +1156,7 @@ fn block_event_and_reorg_event_handling() {
     // There are 5 transactions created
     // TX1 the base transaction and then TX2A and TX3A that spend it
     // Double spends TX2B and TX3B are also created spending TX1
-    // Both nodes have all transactions in their mempools
+    // Both nodes have TX2A and TX3A in their mempools. TX2B and TX3B pay the same fee, so they cannot replace them
     // When block B2A is submitted, then both nodes have TX2A and TX3A in their reorg pools
     // When block B2B is submitted with TX2B, TX3B, then TX2A, TX3A are discarded (Not Stored)
     let factories = CryptoFactories::default();
@@ -1166,14 +1243,18 @@ fn block_event_and_reorg_event_handling() {
             max_attempts = 20,
             interval = Duration::from_millis(1000)
         );
-        alice.mempool.insert(Arc::new(tx2a.clone())).unwrap();
-        alice.mempool.insert(Arc::new(tx3a.clone())).unwrap();
-        alice.mempool.insert(Arc::new(tx2b.clone())).unwrap();
-        alice.mempool.insert(Arc::new(tx3b.clone())).unwrap();
-        bob.mempool.insert(Arc::new(tx2a.clone())).unwrap();
-        bob.mempool.insert(Arc::new(tx3a.clone())).unwrap();
-        bob.mempool.insert(Arc::new(tx2b.clone())).unwrap();
-        bob.mempool.insert(Arc::new(tx3b.clone())).unwrap();
+        for mempool in &[&alice.mempool, &bob.mempool] {
+            mempool.insert(Arc::new(tx2a.clone())).unwrap();
+            mempool.insert(Arc::new(tx3a.clone())).unwrap();
+            assert_eq!(
+                mempool.insert(Arc::new(tx2b.clone())).unwrap(),
+                TxStorageResponse::NotStoredFeeTooLow
+            );
+            assert_eq!(
+                mempool.insert(Arc::new(tx3b.clone())).unwrap(),
+                TxStorageResponse::NotStoredFeeTooLow
+            );
+        }
 
         let mut block2a = bob
             .blockchain_db
@@ -1212,11 +1293,11 @@ fn block_event_and_reorg_event_handling() {
         );
         assert_eq!(
             alice.mempool.has_tx_with_excess_sig(tx2b_excess_sig.clone()).unwrap(),
-            TxStorageResponse::ReorgPool
+            TxStorageResponse::NotStored
         );
         assert_eq!(
             alice.mempool.has_tx_with_excess_sig(tx3b_excess_sig.clone()).unwrap(),
-            TxStorageResponse::ReorgPool
+            TxStorageResponse::NotStored
         );
     });
 }
@@ -34,12 +34,13 @@ use tari_core::{
         comms_interface::{CommsInterfaceError, InboundNodeCommsHandlers, NodeCommsRequest, NodeCommsResponse},
         OutboundNodeCommsInterface,
     },
-    blocks::{BlockBuilder, BlockHeader},
+    blocks::{BlockBuilder, BlockHeader, CompactBlock, ShortIdHasher, SHORT_ID_LENGTH},
     chain_storage::{BlockchainDatabaseConfig, DbTransaction, HistoricalBlock, Validators},
     consensus::{ConsensusManager, NetworkConsensus},
     mempool::{Mempool, MempoolConfig},
     test_helpers::blockchain::{create_store_with_consensus_and_validators_and_config, create_test_blockchain_db},
     transactions::{helpers::create_utxo, tari_amount::MicroTari, types::CryptoFactories},
+    tx,
     validation::{mocks::MockValidator, transaction_validators::TxInputAndMaturityValidator},
 };
 use tari_crypto::{script::TariScript, tari_utilities::hash::Hashable};
@@ -138,6 +139,77 @@ async fn inbound_fetch_kernel_by_excess_sig() {
     }
 }
 
+#[tokio_macros::test]
+async fn inbound_fetch_mempool_transactions_by_short_ids() {
+    let store = create_test_blockchain_db();
+    let mempool = new_mempool();
+    let tx = Arc::new(tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0);
+    mempool.insert(tx.clone()).unwrap();
+
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build();
+    let (block_event_sender, _) = broadcast::channel(50);
+    let (request_sender, _) = reply_channel::unbounded();
+    let (block_sender, _) = mpsc::unbounded();
+    let outbound_nci = OutboundNodeCommsInterface::new(request_sender, block_sender.clone());
+    let inbound_nch = InboundNodeCommsHandlers::new(
+        block_event_sender,
+        store.clone().into(),
+        mempool,
+        consensus_manager,
+        outbound_nci,
+    );
+    let block_hash = vec![7u8; 32];
+    let short_id = ShortIdHasher::new(&block_hash, 1).short_id(&tx.body.kernels()[0]);
+    let unknown_short_id = [0u8; SHORT_ID_LENGTH];
+
+    if let Ok(NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse {
+        transactions,
+        not_found,
+    }) = inbound_nch
+        .handle_request(NodeCommsRequest::FetchMempoolTransactionsByShortIds {
+            block_hash,
+            short_id_nonce: 1,
+            short_ids: vec![short_id, unknown_short_id],
+        })
+        .await
+    {
+        assert_eq!(transactions, vec![(*tx).clone()]);
+        assert_eq!(not_found, vec![unknown_short_id]);
+    } else {
+        panic!("transactions not returned");
+    }
+}
+
+#[test]
+fn compact_block_reconstruction() {
+    let tx1 = tx!(MicroTari(5_000), fee: MicroTari(50), inputs: 2, outputs: 1).0;
+    let tx2 = tx!(MicroTari(5_000), fee: MicroTari(20), inputs: 1, outputs: 2).0;
+    let block = BlockBuilder::new(1)
+        .with_transactions(vec![tx1.clone(), tx2.clone()])
+        .build();
+    let compact = CompactBlock::from(&block);
+    assert_eq!(compact.short_ids.len(), 2);
+    // The short IDs are truncated and salted with a nonce that differs between compact blocks
+    assert_eq!(compact.short_ids[0].len(), SHORT_ID_LENGTH);
+    assert_ne!(CompactBlock::from(&block).short_id_nonce, compact.short_id_nonce);
+    assert!(compact.coinbase_kernels.is_empty());
+
+    let prev_header = BlockHeader::new(1);
+    assert_eq!(
+        compact
+            .clone()
+            .reconstruct(&[tx2.clone(), tx1.clone()], Some(&prev_header)),
+        Some(block)
+    );
+    // A transaction is missing
+    assert_eq!(compact.clone().reconstruct(&[tx1.clone()], Some(&prev_header)), None);
+    // The header does not expect the outputs of these transactions
+    let mut prev_header = BlockHeader::new(1);
+    prev_header.output_mmr_size = 1;
+    assert_eq!(compact.reconstruct(&[tx1, tx2], Some(&prev_header)), None);
+}
+
 #[tokio_macros::test]
 async fn outbound_fetch_headers() {
     let (request_sender, mut request_receiver) = reply_channel::unbounded();
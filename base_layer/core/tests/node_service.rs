@@ -22,7 +22,7 @@
 
 #[allow(dead_code)]
 mod helpers;
-use crate::helpers::block_builders::{construct_chained_blocks, create_coinbase};
+use crate::helpers::block_builders::{append_block_with_coinbase, construct_chained_blocks, create_coinbase};
 use futures::join;
 use helpers::{
     block_builders::{
@@ -50,7 +50,7 @@ use tari_core::{
         service::BaseNodeServiceConfig,
         state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
     },
-    blocks::NewBlock,
+    blocks::{CompactBlock, NewBlock},
     chain_storage::ChainBlock,
     consensus::{ConsensusConstantsBuilder, ConsensusManager, ConsensusManagerBuilder, NetworkConsensus},
     mempool::{MempoolServiceConfig, TxStorageResponse},
@@ -410,9 +410,10 @@ fn propagate_and_forward_invalid_block_hash() {
     let mut carol_message_events = carol_node.messaging_events.subscribe();
 
     runtime.block_on(async {
+        // Only announce the hash, so that Bob has to request the block
         alice_node
             .outbound_nci
-            .propagate_block(NewBlock::from(block1.block()), vec![])
+            .propagate_block(NewBlock::new(block1.hash().clone()), vec![])
             .await
             .unwrap();
 
@@ -556,6 +557,103 @@ fn propagate_and_forward_invalid_block() {
     });
 }
 
+#[test]
+fn propagate_compact_block_that_reconstructs_to_invalid_block() {
+    let mut runtime = Runtime::new().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let factories = CryptoFactories::default();
+    // Alice will propagate a compact block with a tampered coinbase output to Bob. The block that Bob reconstructs
+    // from it fails validation, after which Bob requests the full block from Alice and accepts it.
+    let alice_node_identity = random_node_identity();
+    let bob_node_identity = random_node_identity();
+    let network = Network::LocalNet;
+    let consensus_constants = ConsensusConstantsBuilder::new(network)
+        .with_emission_amounts(100_000_000.into(), &EMISSION, 100.into())
+        .build();
+    let (block0, _) = create_genesis_block(&factories, &consensus_constants);
+    let rules = ConsensusManager::builder(network)
+        .with_consensus_constants(consensus_constants)
+        .with_block(block0.clone())
+        .build();
+    let stateless_block_validator = OrphanBlockValidator::new(rules.clone(), factories.clone());
+
+    let (mut alice_node, rules) = BaseNodeBuilder::new(network.into())
+        .with_node_identity(alice_node_identity.clone())
+        .with_consensus_manager(rules)
+        .start(&mut runtime, temp_dir.path().join("alice").to_str().unwrap());
+    let (mut bob_node, rules) = BaseNodeBuilder::new(network.into())
+        .with_node_identity(bob_node_identity)
+        .with_peers(vec![alice_node_identity])
+        .with_consensus_manager(rules)
+        .with_validators(
+            MockValidator::new(true),
+            MockValidator::new(true),
+            stateless_block_validator,
+        )
+        .start(&mut runtime, temp_dir.path().join("bob").to_str().unwrap());
+
+    wait_until_online(&mut runtime, &[&alice_node, &bob_node]);
+
+    alice_node.mock_base_node_state_machine.publish_status(StatusInfo {
+        bootstrapped: true,
+        state_info: StateInfo::Listening(ListeningInfo::new(true)),
+    });
+    bob_node.mock_base_node_state_machine.publish_status(StatusInfo {
+        bootstrapped: true,
+        state_info: StateInfo::Listening(ListeningInfo::new(true)),
+    });
+
+    let (block1, _) =
+        append_block_with_coinbase(&factories, &alice_node.blockchain_db, &block0, vec![], &rules, 1.into()).unwrap();
+    let block1_hash = block1.hash();
+
+    // Swap the coinbase output for one of the same value that does not match the coinbase kernel
+    let mut compact = CompactBlock::from(block1.block());
+    let (tampered_coinbase, _, _) = create_coinbase(
+        &factories,
+        rules.emission_schedule().block_reward(1),
+        1 + rules.consensus_constants(0).coinbase_lock_height(),
+    );
+    compact.coinbase_outputs = vec![tampered_coinbase];
+    let new_block = NewBlock {
+        block_hash: block1_hash.clone(),
+        compact: Some(compact),
+    };
+
+    runtime.block_on(async {
+        let mut bob_block_event_stream = bob_node.local_nci.get_block_event_stream();
+
+        alice_node
+            .outbound_nci
+            .propagate_block(new_block, vec![])
+            .await
+            .unwrap();
+
+        let bob_block_event = event_stream_next(&mut bob_block_event_stream, Duration::from_millis(20000))
+            .await
+            .unwrap();
+        if let BlockEvent::AddBlockFailed(received_block, _) = &*bob_block_event.unwrap() {
+            assert_eq!(&received_block.hash(), block1_hash);
+            assert_ne!(received_block.body, block1.block().body);
+        } else {
+            panic!("Bob's node should have rejected the reconstructed block");
+        }
+
+        let bob_block_event = event_stream_next(&mut bob_block_event_stream, Duration::from_millis(20000))
+            .await
+            .unwrap();
+        if let BlockEvent::ValidBlockAdded(received_block, _, _) = &*bob_block_event.unwrap() {
+            assert_eq!(&received_block.hash(), block1_hash);
+            assert_eq!(received_block.body, block1.block().body);
+        } else {
+            panic!("Bob's node should have fetched and accepted the full block");
+        }
+
+        alice_node.shutdown().await;
+        bob_node.shutdown().await;
+    });
+}
+
 #[test]
 fn service_request_timeout() {
     let mut runtime = Runtime::new().unwrap();
@@ -664,7 +762,7 @@ fn local_get_new_block_with_zero_conf() {
     let (mut node, rules) = BaseNodeBuilder::new(network.into())
         .with_consensus_manager(rules.clone())
         .with_validators(
-            BodyOnlyValidator::default(),
+            BodyOnlyValidator::new(rules.clone()),
             HeaderValidator::new(rules.clone()),
             OrphanBlockValidator::new(rules, factories.clone()),
         )
@@ -743,7 +841,7 @@ fn local_get_new_block_with_combined_transaction() {
     let (mut node, rules) = BaseNodeBuilder::new(network.into())
         .with_consensus_manager(rules.clone())
         .with_validators(
-            BodyOnlyValidator::default(),
+            BodyOnlyValidator::new(rules.clone()),
             HeaderValidator::new(rules.clone()),
             OrphanBlockValidator::new(rules, factories.clone()),
         )
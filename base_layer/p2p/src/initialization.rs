@@ -125,6 +125,8 @@ pub struct CommsConfig {
     pub dht: DhtConfig,
     /// The p2p network currently being connected to.
     pub network: Network,
+    /// The byte that identifies a custom network in the wire protocol, used instead of the byte of `network`
+    pub custom_network_byte: Option<u8>,
     /// The identity of this node on the network
     pub node_identity: Arc<NodeIdentity>,
     /// The type of transport to use
@@ -542,7 +544,7 @@ impl ServiceInitializer for P2pInitializer {
             .with_node_info(NodeNetworkInfo {
                 major_version: MAJOR_NETWORK_VERSION,
                 minor_version: MINOR_NETWORK_VERSION,
-                network_byte: config.network.as_byte(),
+                network_byte: config.custom_network_byte.unwrap_or_else(|| config.network.as_byte()),
                 user_agent: config.user_agent.clone(),
             });
 
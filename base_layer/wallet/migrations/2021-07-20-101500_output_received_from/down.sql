@@ -0,0 +1 @@
+-- SQLite cannot drop the column, so this migration is not reversible
@@ -0,0 +1,2 @@
+ALTER TABLE outputs
+    ADD COLUMN received_from BLOB NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE completed_transactions
+    ADD COLUMN replaced_by INTEGER NULL;
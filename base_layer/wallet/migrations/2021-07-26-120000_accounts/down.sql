@@ -0,0 +1,2 @@
+-- SQLite cannot drop the columns, so this migration is not reversible
+DROP TABLE IF EXISTS accounts;
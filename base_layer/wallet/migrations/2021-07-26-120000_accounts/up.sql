@@ -0,0 +1,13 @@
+CREATE TABLE accounts (
+    name TEXT PRIMARY KEY NOT NULL,
+    key_index INTEGER NOT NULL DEFAULT 0
+);
+
+ALTER TABLE outputs
+    ADD COLUMN account TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE inbound_transactions
+    ADD COLUMN account TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE outbound_transactions
+    ADD COLUMN account TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE completed_transactions
+    ADD COLUMN account TEXT NOT NULL DEFAULT 'default';
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::time::Duration;
+use tari_core::transactions::tari_amount::MicroTari;
 use tari_key_manager::mnemonic::MnemonicLanguage;
 
 #[derive(Clone, Debug)]
@@ -30,6 +31,10 @@ pub struct OutputManagerServiceConfig {
     pub prevent_fee_gt_amount: bool,
     pub peer_dial_retry_timeout: Duration,
     pub seed_word_language: MnemonicLanguage,
+    /// The `ConsolidateWhenCheap` UTXO selection strategy only consolidates UTXOs at or below this fee per gram
+    pub consolidation_fee_per_gram_threshold: MicroTari,
+    /// The maximum number of inputs the `ConsolidateWhenCheap` UTXO selection strategy will spend in one transaction
+    pub max_consolidation_inputs: usize,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -40,6 +45,8 @@ impl Default for OutputManagerServiceConfig {
             prevent_fee_gt_amount: true,
             peer_dial_retry_timeout: Duration::from_secs(20),
             seed_word_language: MnemonicLanguage::English,
+            consolidation_fee_per_gram_threshold: MicroTari::from(5),
+            max_consolidation_inputs: 50,
         }
     }
 }
@@ -22,6 +22,7 @@
 
 use crate::base_node_service::error::BaseNodeServiceError;
 use diesel::result::Error as DieselError;
+use serde_json::Error as SerdeJsonError;
 use tari_comms::{peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
@@ -65,6 +66,10 @@ pub enum OutputManagerError {
     NotEnoughFunds,
     #[error("Funds are still pending. Unable to fulfil transaction right now.")]
     FundsPending,
+    #[error(
+        "Not enough funds received from a single counterparty to fulfil transaction without linking counterparties"
+    )]
+    InsufficientSingleSourceFunds,
     #[error("Output already exists")]
     DuplicateOutput,
     #[error("Error sending a message to the public API")]
@@ -109,6 +114,18 @@ pub enum OutputManagerError {
     MasterSecretKeyMismatch,
     #[error("Private Key is not found in the current Key Chain")]
     KeyNotFoundInKeyChain,
+    #[error("Offline signing error: `{0}`")]
+    OfflineSigningError(String),
+    #[error("Serde json error: `{0}`")]
+    SerdeJsonError(#[from] SerdeJsonError),
+    #[error("This operation requires a spend key which a watch-only wallet does not have")]
+    WatchOnlyWallet,
+    #[error("Invalid account name `{0}`: names must be between 1 and 21 bytes long and not `default`")]
+    InvalidAccountName(String),
+    #[error("Account `{0}` does not exist")]
+    AccountNotFound(String),
+    #[error("Account `{0}` already exists")]
+    AccountAlreadyExists(String),
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -119,6 +136,8 @@ pub enum OutputManagerStorageError {
         "Tried to insert an pending transaction encumberance for a transaction ID that already exists in the database"
     )]
     DuplicateTransaction,
+    #[error("Tried to insert an account that already exists in the database")]
+    DuplicateAccount,
     #[error("Value not found")]
     ValueNotFound,
     #[error("Unexpected result: `{0}`")]
@@ -23,15 +23,18 @@
 use crate::{
     output_manager_service::{
         error::OutputManagerError,
-        service::Balance,
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+        service::{Balance, UTXOSelectionStrategy},
         storage::{database::PendingTransactionOutputs, models::KnownOneSidedPaymentScript},
         tasks::TxoValidationType,
         TxId,
+        DEFAULT_ACCOUNT,
     },
     types::ValidationRetryStrategy,
 };
 use aes_gcm::Aes256Gcm;
 use futures::{stream::Fuse, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
 use tari_comms::types::CommsPublicKey;
 use tari_core::transactions::{
@@ -42,7 +45,7 @@ use tari_core::transactions::{
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
 };
-use tari_crypto::script::TariScript;
+use tari_crypto::{script::TariScript, tari_utilities::hex::Hex};
 use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
@@ -50,52 +53,112 @@ use tower::Service;
 /// API Request enum
 pub enum OutputManagerRequest {
     GetBalance,
+    GetAccountBalance(String),
+    CreateAccount(String),
+    GetAccounts,
     AddOutput(Box<UnblindedOutput>),
     AddOutputWithTxId((TxId, Box<UnblindedOutput>)),
     UpdateOutputMetadataSignature(Box<TransactionOutput>),
-    GetRecipientTransaction(TransactionSenderMessage),
+    GetRecipientTransaction((TransactionSenderMessage, CommsPublicKey, String)),
     GetCoinbaseTransaction((u64, MicroTari, MicroTari, u64)),
     ConfirmPendingTransaction(u64),
     ConfirmTransaction((u64, Vec<TransactionInput>, Vec<TransactionOutput>)),
-    PrepareToSendTransaction((MicroTari, MicroTari, Option<u64>, String, TariScript)),
-    CreatePayToSelfTransaction((MicroTari, MicroTari, Option<u64>, String)),
+    PrepareToSendTransaction(
+        (
+            MicroTari,
+            MicroTari,
+            Option<u64>,
+            String,
+            TariScript,
+            Option<UTXOSelectionStrategy>,
+            String,
+        ),
+    ),
+    CreatePayToSelfTransaction((MicroTari, MicroTari, Option<u64>, String, String)),
+    PrepareFeeBumpTransaction((TxId, MicroTari, MicroTari, String, TariScript)),
+    CreateFeeBumpPayToSelfTransaction((TxId, MicroTari, MicroTari, String)),
+    ReassignPendingInputs((TxId, TxId)),
+    PrepareUnsignedTransaction((PublicKey, MicroTari, MicroTari, Option<u64>, String)),
+    BuildOfflineTransaction(Box<UnsignedTransaction>),
+    ImportSignedTransaction(Box<SignedTransaction>),
     CancelTransaction(u64),
     TimeoutTransactions(Duration),
     GetPendingTransactions,
     GetSpentOutputs,
     GetUnspentOutputs,
+    GetAccountUnspentOutputs(String),
     GetInvalidOutputs,
     GetSeedWords,
     SetBaseNodePublicKey(CommsPublicKey),
     ValidateUtxos(TxoValidationType, ValidationRetryStrategy),
-    CreateCoinSplit((MicroTari, usize, MicroTari, Option<u64>)),
+    CreateCoinSplit((MicroTari, usize, MicroTari, Option<u64>, String)),
     ApplyEncryption(Box<Aes256Gcm>),
     RemoveEncryption,
     GetPublicRewindKeys,
-    FeeEstimate((MicroTari, MicroTari, u64, u64)),
+    FeeEstimate((MicroTari, MicroTari, u64, u64, String)),
     ScanForRecoverableOutputs(Vec<TransactionOutput>),
     ScanOutputs(Vec<TransactionOutput>),
     AddKnownOneSidedPaymentScript(KnownOneSidedPaymentScript),
 }
 
+impl OutputManagerRequest {
+    /// Returns true if servicing this request needs the wallet's spend keys, i.e. it cannot be done by a watch-only
+    /// wallet.
+    pub fn requires_spend_key(&self) -> bool {
+        use OutputManagerRequest::*;
+        matches!(
+            self,
+            GetRecipientTransaction(_) |
+                GetCoinbaseTransaction(_) |
+                PrepareToSendTransaction(_) |
+                CreatePayToSelfTransaction(_) |
+                PrepareFeeBumpTransaction(_) |
+                CreateFeeBumpPayToSelfTransaction(_) |
+                BuildOfflineTransaction(_) |
+                GetSeedWords |
+                CreateCoinSplit(_)
+        )
+    }
+}
+
 impl fmt::Display for OutputManagerRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use OutputManagerRequest::*;
         match self {
             GetBalance => write!(f, "GetBalance"),
+            GetAccountBalance(account) => write!(f, "GetAccountBalance ({})", account),
+            CreateAccount(account) => write!(f, "CreateAccount ({})", account),
+            GetAccounts => write!(f, "GetAccounts"),
             AddOutput(v) => write!(f, "AddOutput ({})", v.value),
             AddOutputWithTxId((t, v)) => write!(f, "AddOutputWithTxId ({}: {})", t, v.value),
             UpdateOutputMetadataSignature(v) => write!(f, "UpdateOutputMetadataSignature ({:?})", v.metadata_signature),
             GetRecipientTransaction(_) => write!(f, "GetRecipientTransaction"),
             ConfirmTransaction(v) => write!(f, "ConfirmTransaction ({})", v.0),
             ConfirmPendingTransaction(v) => write!(f, "ConfirmPendingTransaction ({})", v),
-            PrepareToSendTransaction((_, _, _, msg, _)) => write!(f, "PrepareToSendTransaction ({})", msg),
-            CreatePayToSelfTransaction((_, _, _, msg)) => write!(f, "CreatePayToSelfTransaction ({})", msg),
+            PrepareToSendTransaction((_, _, _, msg, _, _, account)) => {
+                write!(f, "PrepareToSendTransaction ({} from {})", msg, account)
+            },
+            CreatePayToSelfTransaction((_, _, _, msg, _)) => write!(f, "CreatePayToSelfTransaction ({})", msg),
+            PrepareFeeBumpTransaction((tx_id, _, fee_per_gram, _, _)) => write!(
+                f,
+                "PrepareFeeBumpTransaction (TxId: {}, fee per gram: {})",
+                tx_id, fee_per_gram
+            ),
+            CreateFeeBumpPayToSelfTransaction((tx_id, _, fee_per_gram, _)) => write!(
+                f,
+                "CreateFeeBumpPayToSelfTransaction (TxId: {}, fee per gram: {})",
+                tx_id, fee_per_gram
+            ),
+            ReassignPendingInputs((from, to)) => write!(f, "ReassignPendingInputs ({} -> {})", from, to),
+            PrepareUnsignedTransaction((_, _, _, _, msg)) => write!(f, "PrepareUnsignedTransaction ({})", msg),
+            BuildOfflineTransaction(v) => write!(f, "BuildOfflineTransaction ({})", v.tx_id),
+            ImportSignedTransaction(v) => write!(f, "ImportSignedTransaction ({})", v.tx_id),
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             TimeoutTransactions(d) => write!(f, "TimeoutTransactions ({}s)", d.as_secs()),
             GetPendingTransactions => write!(f, "GetPendingTransactions"),
             GetSpentOutputs => write!(f, "GetSpentOutputs"),
             GetUnspentOutputs => write!(f, "GetUnspentOutputs"),
+            GetAccountUnspentOutputs(account) => write!(f, "GetAccountUnspentOutputs ({})", account),
             GetInvalidOutputs => write!(f, "GetInvalidOutputs"),
             GetSeedWords => write!(f, "GetSeedWords"),
             SetBaseNodePublicKey(k) => write!(f, "SetBaseNodePublicKey ({})", k),
@@ -117,6 +180,8 @@ impl fmt::Display for OutputManagerRequest {
 #[derive(Debug, Clone)]
 pub enum OutputManagerResponse {
     Balance(Balance),
+    AccountCreated,
+    Accounts(Vec<String>),
     OutputAdded,
     OutputMetadataSignatureUpdated,
     RecipientTransactionGenerated(ReceiverTransactionProtocol),
@@ -127,6 +192,10 @@ pub enum OutputManagerResponse {
     TransactionConfirmed,
     TransactionToSend(SenderTransactionProtocol),
     TransactionCancelled,
+    PendingInputsReassigned,
+    UnsignedTransaction(Box<UnsignedTransaction>),
+    OfflineTransactionToSign((SenderTransactionProtocol, Option<u64>)),
+    SignedTransactionImported,
     TransactionsTimedOut,
     PendingTransactions(HashMap<u64, PendingTransactionOutputs>),
     SpentOutputs(Vec<UnblindedOutput>),
@@ -159,12 +228,40 @@ pub enum OutputManagerEvent {
     Error(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PublicRewindKeys {
     pub rewind_public_key: PublicKey,
     pub rewind_blinding_public_key: PublicKey,
 }
 
+impl PublicRewindKeys {
+    /// Encode both keys as a single hex string, which is how they are exported as the view keys of a wallet
+    pub fn to_view_key_hex(&self) -> String {
+        format!(
+            "{}{}",
+            self.rewind_public_key.to_hex(),
+            self.rewind_blinding_public_key.to_hex()
+        )
+    }
+
+    /// Decode view keys that were exported with `to_view_key_hex`
+    pub fn from_view_key_hex(hex: &str) -> Result<Self, OutputManagerError> {
+        let hex = hex.trim();
+        if hex.len() != 128 || !hex.is_ascii() {
+            return Err(OutputManagerError::ConversionError(
+                "View keys must be 128 hexadecimal characters".to_string(),
+            ));
+        }
+        let (rewind_public_key, rewind_blinding_public_key) = hex.split_at(64);
+        Ok(Self {
+            rewind_public_key: PublicKey::from_hex(rewind_public_key)
+                .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+            rewind_blinding_public_key: PublicKey::from_hex(rewind_blinding_public_key)
+                .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct OutputManagerHandle {
     handle: SenderService<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
@@ -233,13 +330,61 @@ impl OutputManagerHandle {
         }
     }
 
+    /// The balance of the outputs owned by the named account only
+    pub async fn get_account_balance(&mut self, account: String) -> Result<Balance, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountBalance(account))
+            .await??
+        {
+            OutputManagerResponse::Balance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a new named account. The account gets its own key branch derived from the wallet's seed, so its funds
+    /// are recovered along with the rest of the wallet.
+    pub async fn create_account(&mut self, account: String) -> Result<(), OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::CreateAccount(account)).await?? {
+            OutputManagerResponse::AccountCreated => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// The names of all the accounts in the wallet, starting with the default account
+    pub async fn get_accounts(&mut self) -> Result<Vec<String>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetAccounts).await?? {
+            OutputManagerResponse::Accounts(a) => Ok(a),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Generates the receiver's part of a transaction from the sender's message. The output being received is
+    /// recorded as coming from `source_public_key`.
     pub async fn get_recipient_transaction(
         &mut self,
         sender_message: TransactionSenderMessage,
+        source_public_key: CommsPublicKey,
+    ) -> Result<ReceiverTransactionProtocol, OutputManagerError> {
+        self.get_recipient_transaction_for_account(sender_message, source_public_key, DEFAULT_ACCOUNT.to_string())
+            .await
+    }
+
+    /// Generates the receiver's part of a transaction from the sender's message, receiving the output into the named
+    /// account.
+    pub async fn get_recipient_transaction_for_account(
+        &mut self,
+        sender_message: TransactionSenderMessage,
+        source_public_key: CommsPublicKey,
+        account: String,
     ) -> Result<ReceiverTransactionProtocol, OutputManagerError> {
         match self
             .handle
-            .call(OutputManagerRequest::GetRecipientTransaction(sender_message))
+            .call(OutputManagerRequest::GetRecipientTransaction((
+                sender_message,
+                source_public_key,
+                account,
+            )))
             .await??
         {
             OutputManagerResponse::RecipientTransactionGenerated(rtp) => Ok(rtp),
@@ -276,6 +421,32 @@ impl OutputManagerHandle {
         lock_height: Option<u64>,
         message: String,
         recipient_script: TariScript,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.prepare_transaction_to_send_from_account(
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+            recipient_script,
+            utxo_selection,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Prepare a transaction that is funded only by the outputs of the named account. Any change is returned to the
+    /// same account.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn prepare_transaction_to_send_from_account(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_script: TariScript,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+        account: String,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         match self
             .handle
@@ -285,6 +456,8 @@ impl OutputManagerHandle {
                 lock_height,
                 message,
                 recipient_script,
+                utxo_selection,
+                account,
             )))
             .await??
         {
@@ -301,6 +474,25 @@ impl OutputManagerHandle {
         fee_per_gram: MicroTari,
         num_kernels: u64,
         num_outputs: u64,
+    ) -> Result<MicroTari, OutputManagerError> {
+        self.fee_estimate_for_account(
+            amount,
+            fee_per_gram,
+            num_kernels,
+            num_outputs,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Get a fee estimate for a transaction funded only by the outputs of the named account.
+    pub async fn fee_estimate_for_account(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        num_kernels: u64,
+        num_outputs: u64,
+        account: String,
     ) -> Result<MicroTari, OutputManagerError> {
         match self
             .handle
@@ -309,6 +501,7 @@ impl OutputManagerHandle {
                 fee_per_gram,
                 num_kernels,
                 num_outputs,
+                account,
             )))
             .await??
         {
@@ -359,6 +552,22 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Move the encumbered inputs of one pending transaction over to another pending transaction that spends them.
+    pub async fn reassign_pending_inputs(
+        &mut self,
+        from_tx_id: TxId,
+        to_tx_id: TxId,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ReassignPendingInputs((from_tx_id, to_tx_id)))
+            .await??
+        {
+            OutputManagerResponse::PendingInputsReassigned => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn timeout_transactions(&mut self, period: Duration) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -394,6 +603,21 @@ impl OutputManagerHandle {
         }
     }
 
+    /// The unspent outputs of the named account, sorted from lowest value to highest
+    pub async fn get_account_unspent_outputs(
+        &mut self,
+        account: String,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountUnspentOutputs(account))
+            .await??
+        {
+            OutputManagerResponse::UnspentOutputs(s) => Ok(s),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_invalid_outputs(&mut self) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetInvalidOutputs).await?? {
             OutputManagerResponse::InvalidOutputs(s) => Ok(s),
@@ -449,6 +673,27 @@ impl OutputManagerHandle {
         split_count: usize,
         fee_per_gram: MicroTari,
         lock_height: Option<u64>,
+    ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
+        self.create_coin_split_from_account(
+            amount_per_split,
+            split_count,
+            fee_per_gram,
+            lock_height,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Create a coin split transaction that splits the outputs of the named account into new outputs of the same
+    /// account.
+    /// Returns (tx_id, tx, fee, utxos_total_value).
+    pub async fn create_coin_split_from_account(
+        &mut self,
+        amount_per_split: MicroTari,
+        split_count: usize,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        account: String,
     ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
         match self
             .handle
@@ -457,6 +702,7 @@ impl OutputManagerHandle {
                 split_count,
                 fee_per_gram,
                 lock_height,
+                account,
             )))
             .await??
         {
@@ -524,6 +770,25 @@ impl OutputManagerHandle {
         fee_per_gram: MicroTari,
         lock_height: Option<u64>,
         message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError> {
+        self.create_pay_to_self_transaction_from_account(
+            amount,
+            fee_per_gram,
+            lock_height,
+            message,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Create a pay-to-self transaction that is funded by, and pays into, the named account
+    pub async fn create_pay_to_self_transaction_from_account(
+        &mut self,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        account: String,
     ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError> {
         match self
             .handle
@@ -532,6 +797,110 @@ impl OutputManagerHandle {
                 fee_per_gram,
                 lock_height,
                 message,
+                account,
+            )))
+            .await??
+        {
+            OutputManagerResponse::PayToSelfTransaction(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Prepare a Sender Transaction Protocol that replaces the pending transaction `original_tx_id` by spending the
+    /// same inputs at a new fee per gram.
+    pub async fn prepare_fee_bump_transaction(
+        &mut self,
+        original_tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        recipient_script: TariScript,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareFeeBumpTransaction((
+                original_tx_id,
+                amount,
+                fee_per_gram,
+                message,
+                recipient_script,
+            )))
+            .await??
+        {
+            OutputManagerResponse::TransactionToSend(stp) => Ok(stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Select and encumber the inputs for a one-sided transaction to `recipient` that will be signed offline.
+    pub async fn prepare_unsigned_transaction(
+        &mut self,
+        recipient: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<UnsignedTransaction, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareUnsignedTransaction((
+                recipient,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )))
+            .await??
+        {
+            OutputManagerResponse::UnsignedTransaction(unsigned) => Ok(*unsigned),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Build the Sender Transaction Protocol for an unsigned transaction using the keys of this wallet. Returns the
+    /// protocol and the key chain index of its change output, if there is one.
+    pub async fn build_offline_transaction(
+        &mut self,
+        unsigned: UnsignedTransaction,
+    ) -> Result<(SenderTransactionProtocol, Option<u64>), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::BuildOfflineTransaction(Box::new(unsigned)))
+            .await??
+        {
+            OutputManagerResponse::OfflineTransactionToSign(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Store the outputs of a transaction that was signed offline against the TxId it was prepared with.
+    pub async fn import_signed_transaction(&mut self, signed: SignedTransaction) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ImportSignedTransaction(Box::new(signed)))
+            .await??
+        {
+            OutputManagerResponse::SignedTransactionImported => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Create a pay-to-self transaction that replaces the pending pay-to-self transaction `original_tx_id` by spending
+    /// the same inputs at a new fee per gram.
+    pub async fn create_fee_bump_pay_to_self_transaction(
+        &mut self,
+        original_tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateFeeBumpPayToSelfTransaction((
+                original_tx_id,
+                amount,
+                fee_per_gram,
+                message,
             )))
             .await??
         {
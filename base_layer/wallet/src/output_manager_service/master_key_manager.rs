@@ -24,17 +24,25 @@ use crate::{
     output_manager_service::{
         error::OutputManagerError,
         handle::PublicRewindKeys,
-        storage::database::{KeyManagerState, OutputManagerBackend, OutputManagerDatabase},
+        storage::database::{AccountState, KeyManagerState, OutputManagerBackend, OutputManagerDatabase},
+        DEFAULT_ACCOUNT,
     },
     types::KeyDigest,
 };
+use digest::Digest;
 use futures::lock::Mutex;
 use log::*;
+use std::collections::HashMap;
 use tari_core::transactions::{
+    tari_amount::MicroTari,
     transaction_protocol::RewindData,
-    types::{PrivateKey, PublicKey},
+    types::{Commitment, CommitmentFactory, PrivateKey, PublicKey},
+};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::PublicKey as PublicKeyTrait,
+    range_proof::REWIND_USER_MESSAGE_LENGTH,
 };
-use tari_crypto::{keys::PublicKey as PublicKeyTrait, range_proof::REWIND_USER_MESSAGE_LENGTH};
 use tari_key_manager::{
     key_manager::KeyManager,
     mnemonic::{from_secret_key, MnemonicLanguage},
@@ -47,8 +55,79 @@ const KEY_MANAGER_COINBASE_SCRIPT_BRANCH_KEY: &str = "coinbase_script";
 const KEY_MANAGER_SCRIPT_BRANCH_KEY: &str = "script";
 const KEY_MANAGER_RECOVERY_VIEWONLY_BRANCH_KEY: &str = "recovery_viewonly";
 const KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY: &str = "recovery_blinding";
+const KEY_MANAGER_ACCOUNT_BRANCH_PREFIX: &str = "account_";
 const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
 
+/// The spending and script key chains of a named account. Each account derives its keys from its own master key so
+/// that the outputs of different accounts can never share keys. The account master key is a branch key of the wallet
+/// master key at an index derived from the account name alone, so recovery can rebuild it from the name stored in the
+/// range proof message, whatever order the accounts were created in.
+struct AccountKeyManager {
+    utxo_key_manager: KeyManager<PrivateKey, KeyDigest>,
+    utxo_script_key_manager: KeyManager<PrivateKey, KeyDigest>,
+}
+
+impl AccountKeyManager {
+    fn new(master_key: &PrivateKey, account: &str, key_index: u64) -> Result<Self, OutputManagerError> {
+        let branch_seed = format!("{}{}", KEY_MANAGER_ACCOUNT_BRANCH_PREFIX, account);
+        // The top bit keeps the account index clear of the indices the default key chains will ever reach
+        let mut index_bytes = [0u8; 8];
+        index_bytes.copy_from_slice(&KeyDigest::digest(branch_seed.as_bytes())[..8]);
+        let account_index = u64::from_le_bytes(index_bytes) | (1 << 63);
+        let account_master_key =
+            KeyManager::<PrivateKey, KeyDigest>::from(master_key.clone(), branch_seed.clone(), account_index)
+                .derive_key(account_index)?
+                .k;
+        Ok(Self {
+            utxo_key_manager: KeyManager::<PrivateKey, KeyDigest>::from(
+                account_master_key.clone(),
+                branch_seed.clone(),
+                key_index,
+            ),
+            utxo_script_key_manager: KeyManager::<PrivateKey, KeyDigest>::from(
+                account_master_key,
+                format!("{}_{}", branch_seed, KEY_MANAGER_SCRIPT_BRANCH_KEY),
+                key_index,
+            ),
+        })
+    }
+}
+
+/// Account names are stored in the range proof message of every output the account owns, so they must fit in it.
+fn validate_account_name(account: &str) -> Result<(), OutputManagerError> {
+    if account.is_empty() ||
+        account.len() > REWIND_USER_MESSAGE_LENGTH ||
+        account == DEFAULT_ACCOUNT ||
+        account.contains('\0')
+    {
+        return Err(OutputManagerError::InvalidAccountName(account.to_string()));
+    }
+    Ok(())
+}
+
+/// The range proof message that marks an output as belonging to the given account. Outputs of the default account
+/// carry an empty message, as they always have.
+pub(crate) fn account_proof_message(account: &str) -> Result<[u8; REWIND_USER_MESSAGE_LENGTH], OutputManagerError> {
+    let mut proof_message = [0u8; REWIND_USER_MESSAGE_LENGTH];
+    if account != DEFAULT_ACCOUNT {
+        validate_account_name(account)?;
+        proof_message[..account.len()].copy_from_slice(account.as_bytes());
+    }
+    Ok(proof_message)
+}
+
+/// The account an output belongs to, read from its rewound range proof message
+pub(crate) fn account_from_proof_message(proof_message: &[u8; REWIND_USER_MESSAGE_LENGTH]) -> String {
+    let len = proof_message
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(REWIND_USER_MESSAGE_LENGTH);
+    match std::str::from_utf8(&proof_message[..len]) {
+        Ok(account) if !account.is_empty() => account.to_string(),
+        _ => DEFAULT_ACCOUNT.to_string(),
+    }
+}
+
 pub(crate) struct MasterKeyManager<TBackend>
 where TBackend: OutputManagerBackend + 'static
 {
@@ -56,6 +135,7 @@ where TBackend: OutputManagerBackend + 'static
     utxo_script_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
     coinbase_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
     coinbase_script_key_manager: Mutex<KeyManager<PrivateKey, KeyDigest>>,
+    accounts: Mutex<HashMap<String, AccountKeyManager>>,
     rewind_data: RewindData,
     db: OutputManagerDatabase<TBackend>,
 }
@@ -118,7 +198,7 @@ where TBackend: OutputManagerBackend + 'static
         let rewind_key = rewind_key_manager.derive_key(0)?.k;
 
         let rewind_blinding_key_manager = KeyManager::<PrivateKey, KeyDigest>::from(
-            key_manager_state.master_key,
+            key_manager_state.master_key.clone(),
             KEY_MANAGER_RECOVERY_BLINDING_BRANCH_KEY.to_string(),
             0,
         );
@@ -130,11 +210,19 @@ where TBackend: OutputManagerBackend + 'static
             proof_message: [0u8; REWIND_USER_MESSAGE_LENGTH],
         };
 
+        let mut accounts = HashMap::new();
+        for account in db.get_accounts().await? {
+            let account_key_manager =
+                AccountKeyManager::new(&key_manager_state.master_key, &account.name, account.key_index)?;
+            accounts.insert(account.name, account_key_manager);
+        }
+
         Ok(Self {
             utxo_key_manager: Mutex::new(utxo_key_manager),
             utxo_script_key_manager: Mutex::new(utxo_script_key_manager),
             coinbase_key_manager: Mutex::new(coinbase_key_manager),
             coinbase_script_key_manager: Mutex::new(coinbase_script_key_manager),
+            accounts: Mutex::new(accounts),
             rewind_data,
             db,
         })
@@ -144,6 +232,68 @@ where TBackend: OutputManagerBackend + 'static
         &self.rewind_data
     }
 
+    /// The rewind data used for the outputs of the given account. The rewind keys are shared by all accounts so that
+    /// recovery from the seed words finds every output; the proof message records which account owns the output.
+    pub fn rewind_data_for_account(&self, account: &str) -> Result<RewindData, OutputManagerError> {
+        Ok(RewindData {
+            proof_message: account_proof_message(account)?,
+            ..self.rewind_data.clone()
+        })
+    }
+
+    /// Create a new named account with its own key branch
+    pub async fn create_account(&self, account: &str) -> Result<(), OutputManagerError> {
+        validate_account_name(account)?;
+        let mut accounts = self.accounts.lock().await;
+        if accounts.contains_key(account) {
+            return Err(OutputManagerError::AccountAlreadyExists(account.to_string()));
+        }
+        let master_key = self.utxo_key_manager.lock().await.master_key().clone();
+        let account_key_manager = AccountKeyManager::new(&master_key, account, 0)?;
+        self.db
+            .add_account(AccountState {
+                name: account.to_string(),
+                key_index: 0,
+            })
+            .await?;
+        accounts.insert(account.to_string(), account_key_manager);
+        debug!(target: LOG_TARGET, "Created account `{}`", account);
+        Ok(())
+    }
+
+    /// The names of all the accounts in this wallet, starting with the default account
+    pub async fn account_names(&self) -> Vec<String> {
+        let mut names = self.accounts.lock().await.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names.insert(0, DEFAULT_ACCOUNT.to_string());
+        names
+    }
+
+    pub async fn has_account(&self, account: &str) -> bool {
+        account == DEFAULT_ACCOUNT || self.accounts.lock().await.contains_key(account)
+    }
+
+    /// Return the next pair of (spending_key, script_private_key) from the key chains of the given account
+    pub async fn get_next_spend_and_script_key_for_account(
+        &self,
+        account: &str,
+    ) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
+        if account == DEFAULT_ACCOUNT {
+            return self.get_next_spend_and_script_key().await;
+        }
+        let mut accounts = self.accounts.lock().await;
+        let account_key_manager = accounts
+            .get_mut(account)
+            .ok_or_else(|| OutputManagerError::AccountNotFound(account.to_string()))?;
+        let key = account_key_manager.utxo_key_manager.next_key()?;
+        let script_key = account_key_manager.utxo_script_key_manager.next_key()?;
+
+        self.db
+            .set_account_key_index(account.to_string(), account_key_manager.utxo_key_manager.key_index())
+            .await?;
+        Ok((key.k, script_key.k))
+    }
+
     /// Return the next pair of (spending_key, script_private_key) from the key managers. These will always be generated
     /// in tandem and at corresponding increments
     pub async fn get_next_spend_and_script_key(&self) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
@@ -157,6 +307,24 @@ where TBackend: OutputManagerBackend + 'static
         Ok((key.k, script_key.k))
     }
 
+    /// Return the (spending_key, script_private_key) pair at the specified index of the UTXO key chain
+    pub async fn get_spend_and_script_key_at_index(
+        &self,
+        index: u64,
+    ) -> Result<(PrivateKey, PrivateKey), OutputManagerError> {
+        let km = self.utxo_key_manager.lock().await;
+        let key = km.derive_key(index)?;
+
+        let skm = self.utxo_script_key_manager.lock().await;
+        let script_key = skm.derive_key(index)?;
+        Ok((key.k, script_key.k))
+    }
+
+    /// The index of the last key that was issued from the UTXO key chain
+    pub async fn key_index(&self) -> u64 {
+        self.utxo_key_manager.lock().await.key_index()
+    }
+
     pub async fn get_script_key_at_index(&self, index: u64) -> Result<PrivateKey, OutputManagerError> {
         let skm = self.utxo_script_key_manager.lock().await;
         let script_key = skm.derive_key(index)?;
@@ -206,6 +374,66 @@ where TBackend: OutputManagerBackend + 'static
         Err(OutputManagerError::KeyNotFoundInKeyChain)
     }
 
+    /// Search the current key manager key chain to find the index of the key that blinds the specified commitment to
+    /// the given value. This is used for outputs that were exported by a watch-only wallet without their spending key.
+    pub async fn find_utxo_key_index_for_commitment(
+        &self,
+        commitment: &Commitment,
+        value: MicroTari,
+        factory: &CommitmentFactory,
+    ) -> Result<u64, OutputManagerError> {
+        let utxo_key_manager = self.utxo_key_manager.lock().await;
+        let current_index = (*utxo_key_manager).key_index();
+
+        for i in 0u64..current_index + KEY_MANAGER_MAX_SEARCH_DEPTH {
+            if &factory.commit_value(&(*utxo_key_manager).derive_key(i)?.k, value.as_u64()) == commitment {
+                trace!(target: LOG_TARGET, "Commitment key found in Key Chain at index {}", i);
+                return Ok(i);
+            }
+        }
+
+        Err(OutputManagerError::KeyNotFoundInKeyChain)
+    }
+
+    /// Search the key chain of the given account for the specified spending key. If it is found the account's key index
+    /// is moved past it and the matching script private key is returned.
+    pub async fn recover_script_key_for_account(
+        &self,
+        account: &str,
+        key: PrivateKey,
+    ) -> Result<PrivateKey, OutputManagerError> {
+        if account == DEFAULT_ACCOUNT {
+            let found_index = self.find_utxo_key_index(key).await?;
+            self.update_current_index_if_higher(found_index).await?;
+            return self.get_script_key_at_index(found_index).await;
+        }
+
+        let mut accounts = self.accounts.lock().await;
+        let account_key_manager = accounts
+            .get_mut(account)
+            .ok_or_else(|| OutputManagerError::AccountNotFound(account.to_string()))?;
+        let current_index = account_key_manager.utxo_key_manager.key_index();
+        for i in 0u64..current_index + KEY_MANAGER_MAX_SEARCH_DEPTH {
+            if account_key_manager.utxo_key_manager.derive_key(i)?.k != key {
+                continue;
+            }
+            trace!(
+                target: LOG_TARGET,
+                "Key found in `{}` account Key Chain at index {}",
+                account,
+                i
+            );
+            if i > current_index {
+                account_key_manager.utxo_key_manager.update_key_index(i);
+                account_key_manager.utxo_script_key_manager.update_key_index(i);
+                self.db.set_account_key_index(account.to_string(), i).await?;
+            }
+            return Ok(account_key_manager.utxo_script_key_manager.derive_key(i)?.k);
+        }
+
+        Err(OutputManagerError::KeyNotFoundInKeyChain)
+    }
+
     /// If the supplied index is higher than the current UTXO key chain indices then they will be updated.
     pub async fn update_current_index_if_higher(&self, index: u64) -> Result<(), OutputManagerError> {
         let mut utxo_key_manager = self.utxo_key_manager.lock().await;
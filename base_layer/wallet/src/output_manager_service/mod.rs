@@ -24,7 +24,7 @@ use crate::{
     base_node_service::handle::BaseNodeServiceHandle,
     output_manager_service::{
         config::OutputManagerServiceConfig,
-        handle::OutputManagerHandle,
+        handle::{OutputManagerHandle, PublicRewindKeys},
         service::OutputManagerService,
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
     },
@@ -50,20 +50,27 @@ pub mod config;
 pub mod error;
 pub mod handle;
 mod master_key_manager;
+pub mod offline_signing;
 mod recovery;
 pub mod resources;
 #[allow(unused_assignments)]
 pub mod service;
 pub mod storage;
 mod tasks;
+mod utxo_selection;
 
 pub(crate) use master_key_manager::MasterKeyManager;
+pub(crate) use recovery::import_tx_id;
 pub use tasks::TxoValidationType;
 
 const LOG_TARGET: &str = "wallet::output_manager_service::initializer";
 
 pub type TxId = u64;
 
+/// The name of the account that owns all funds not explicitly assigned to a named account. Its keys are derived from
+/// the wallet's original key branches so wallets created before accounts existed keep working unchanged.
+pub const DEFAULT_ACCOUNT: &str = "default";
+
 pub struct OutputManagerServiceInitializer<T>
 where T: OutputManagerBackend
 {
@@ -72,6 +79,7 @@ where T: OutputManagerBackend
     factories: CryptoFactories,
     network: NetworkConsensus,
     master_secret_key: CommsSecretKey,
+    watch_only_keys: Option<PublicRewindKeys>,
 }
 
 impl<T> OutputManagerServiceInitializer<T>
@@ -90,8 +98,15 @@ where T: OutputManagerBackend + 'static
             factories,
             network,
             master_secret_key,
+            watch_only_keys: None,
         }
     }
+
+    /// Start the service as a watch-only wallet for the given rewind public keys
+    pub fn with_watch_only_keys(mut self, watch_only_keys: Option<PublicRewindKeys>) -> Self {
+        self.watch_only_keys = watch_only_keys;
+        self
+    }
 }
 
 #[async_trait]
@@ -120,6 +135,7 @@ where T: OutputManagerBackend + 'static
         let config = self.config.clone();
         let constants = ConsensusConstantsBuilder::new(self.network.as_network()).build();
         let master_secret_key = self.master_secret_key.clone();
+        let watch_only_keys = self.watch_only_keys.clone();
         context.spawn_when_ready(move |handles| async move {
             let transaction_service = handles.expect_handle::<TransactionServiceHandle>();
             let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
@@ -140,6 +156,7 @@ where T: OutputManagerBackend + 'static
             )
             .await
             .expect("Could not initialize Output Manager Service")
+            .with_watch_only_keys(watch_only_keys)
             .start();
 
             futures::pin_mut!(service);
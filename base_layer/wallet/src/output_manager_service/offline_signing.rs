@@ -0,0 +1,110 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::output_manager_service::{error::OutputManagerError, TxId};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{OutputFeatures, Transaction},
+    types::{ComSignature, Commitment, PublicKey},
+};
+use tari_crypto::script::TariScript;
+
+/// An input of an unsigned transaction. Only the public parts of the output being spent are exported, the signing
+/// wallet derives its spending and script private keys from the master key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedInput {
+    pub commitment: Commitment,
+    pub value: MicroTari,
+    pub features: OutputFeatures,
+    pub script: TariScript,
+    pub sender_offset_public_key: PublicKey,
+    pub metadata_signature: ComSignature,
+    /// The index of the output's keys in the UTXO key chain. This is None if the preparing wallet does not know it,
+    /// e.g. a watch-only wallet, in which case the signing wallet searches its key chain for the commitment.
+    pub key_index: Option<u64>,
+}
+
+/// A one-sided transaction that has been prepared by an online wallet and still has to be signed by a wallet that
+/// holds the master key. No private keys are exported.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub tx_id: TxId,
+    pub inputs: Vec<UnsignedInput>,
+    pub recipient: PublicKey,
+    pub amount: MicroTari,
+    pub fee_per_gram: MicroTari,
+    pub fee: MicroTari,
+    /// The value of the change output, if the inputs cover the cost of one
+    pub change: Option<MicroTari>,
+    pub lock_height: Option<u64>,
+    pub message: String,
+    /// The UTXO key chain index that the online wallet reserved for the change output when it prepared the
+    /// transaction, so that the key is not issued for anything else before the signed transaction is imported. A
+    /// watch-only wallet cannot reserve keys in the signing wallet's key chain, in which case this is None and the
+    /// signing wallet uses its next unused key.
+    pub change_key_index: Option<u64>,
+}
+
+/// A transaction that was signed by the offline wallet and is ready to be imported into the online wallet that
+/// prepared it for broadcast.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    /// The TxId of the unsigned transaction this was signed from
+    pub tx_id: TxId,
+    pub transaction: Transaction,
+    pub recipient: PublicKey,
+    pub amount: MicroTari,
+    pub fee: MicroTari,
+    pub change: Option<MicroTari>,
+    /// The UTXO key chain index of the change output, if there is one
+    pub change_key_index: Option<u64>,
+    pub message: String,
+}
+
+impl UnsignedTransaction {
+    pub fn to_json(&self) -> Result<String, OutputManagerError> {
+        to_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, OutputManagerError> {
+        from_json(json)
+    }
+}
+
+impl SignedTransaction {
+    pub fn to_json(&self) -> Result<String, OutputManagerError> {
+        to_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, OutputManagerError> {
+        from_json(json)
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, OutputManagerError> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, OutputManagerError> {
+    Ok(serde_json::from_str(json)?)
+}
@@ -21,5 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod standard_outputs_recoverer;
+mod watch_only_outputs_recoverer;
 
 pub(crate) use standard_outputs_recoverer::StandardUtxoRecoverer;
+pub(crate) use watch_only_outputs_recoverer::{import_tx_id, WatchOnlyUtxoRecoverer};
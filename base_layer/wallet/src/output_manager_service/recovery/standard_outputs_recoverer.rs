@@ -22,6 +22,7 @@
 
 use crate::output_manager_service::{
     error::OutputManagerError,
+    master_key_manager::account_from_proof_message,
     storage::{
         database::{OutputManagerBackend, OutputManagerDatabase},
         models::DbUnblindedOutput,
@@ -60,12 +61,13 @@ where TBackend: OutputManagerBackend + 'static
     }
 
     /// Attempt to rewind all of the given transaction outputs into unblinded outputs. If they can be rewound then add
-    /// them to the database and increment the key manager index
+    /// them to the database and increment the key manager index. Each output is restored into the account named in its
+    /// range proof message, creating that account if the wallet does not have it yet.
     pub async fn scan_and_recover_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
     ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
-        let mut rewound_outputs: Vec<UnblindedOutput> = outputs
+        let mut rewound_outputs: Vec<(UnblindedOutput, String)> = outputs
             .into_iter()
             .filter_map(|output| {
                 output
@@ -87,7 +89,8 @@ where TBackend: OutputManagerBackend + 'static
             })
             .map(
                 |(output, features, script, sender_offset_public_key, metadata_signature)| {
-                    UnblindedOutput::new(
+                    let account = account_from_proof_message(&output.proof_message);
+                    let unblinded_output = UnblindedOutput::new(
                         output.committed_value,
                         output.blinding_factor.clone(),
                         Some(features),
@@ -96,16 +99,21 @@ where TBackend: OutputManagerBackend + 'static
                         output.blinding_factor,
                         sender_offset_public_key,
                         metadata_signature,
-                    )
+                    );
+                    (unblinded_output, account)
                 },
             )
             .collect();
 
-        for output in rewound_outputs.iter_mut() {
-            self.update_outputs_script_private_key_and_update_key_manager_index(output)
+        for (output, account) in rewound_outputs.iter_mut() {
+            if !self.master_key_manager.has_account(account).await {
+                self.master_key_manager.create_account(account).await?;
+            }
+            self.update_outputs_script_private_key_and_update_key_manager_index(output, account)
                 .await?;
 
-            let db_output = DbUnblindedOutput::from_unblinded_output(output.clone(), &self.factories)?;
+            let mut db_output = DbUnblindedOutput::from_unblinded_output(output.clone(), &self.factories)?;
+            db_output.account = account.clone();
             self.db.add_unspent_output(db_output).await?;
 
             trace!(
@@ -120,26 +128,21 @@ where TBackend: OutputManagerBackend + 'static
             );
         }
 
-        Ok(rewound_outputs)
+        Ok(rewound_outputs.into_iter().map(|(output, _)| output).collect())
     }
 
-    /// Find the key manager index that corresponds to the spending key in the rewound output, if found then modify
-    /// output to contain correct associated script private key and update the key manager to the highest index it has
-    /// seen so far.
+    /// Find the index in the account's key chain that corresponds to the spending key in the rewound output, if found
+    /// then modify output to contain correct associated script private key and update the account's key chain to the
+    /// highest index it has seen so far.
     async fn update_outputs_script_private_key_and_update_key_manager_index(
         &mut self,
         output: &mut UnblindedOutput,
+        account: &str,
     ) -> Result<(), OutputManagerError> {
-        let found_index = self
+        let script_private_key = self
             .master_key_manager
-            .find_utxo_key_index(output.spending_key.clone())
-            .await?;
-
-        self.master_key_manager
-            .update_current_index_if_higher(found_index)
+            .recover_script_key_for_account(account, output.spending_key.clone())
             .await?;
-
-        let script_private_key = self.master_key_manager.get_script_key_at_index(found_index).await?;
         output.input_data = inputs!(PublicKey::from_secret_key(&script_private_key));
         output.script_private_key = script_private_key;
         Ok(())
@@ -0,0 +1,198 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::output_manager_service::{
+    error::{OutputManagerError, OutputManagerStorageError},
+    handle::PublicRewindKeys,
+    master_key_manager::account_from_proof_message,
+    storage::{
+        database::{OutputManagerBackend, OutputManagerDatabase},
+        models::DbUnblindedOutput,
+    },
+    TxId,
+};
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use tari_core::transactions::{
+    tari_amount::MicroTari,
+    transaction::{TransactionOutput, UnblindedOutput},
+    types::{CryptoFactories, PrivateKey},
+};
+use tari_crypto::{
+    script::ExecutionStack,
+    tari_utilities::{hex::Hex, ByteArray},
+};
+
+const LOG_TARGET: &str = "wallet::output_manager_service::recovery::watch_only";
+
+/// Recovers outputs for a watch-only wallet. Only the rewind public keys are available, so the value of an output can
+/// be rewound but not its blinding factor. Recovered outputs are stored with their on-chain commitment and hash and
+/// zeroed spending and script keys; they count towards the balance and history but cannot be spent by this wallet.
+/// Spends by the wallet holding the keys are detected when TXO validation finds the outputs missing from the UTXO set.
+/// The outputs of the spending block that rewind to this wallet are taken to be the change of the spend.
+pub(crate) struct WatchOnlyUtxoRecoverer<TBackend: OutputManagerBackend + 'static> {
+    rewind_keys: PublicRewindKeys,
+    factories: CryptoFactories,
+    db: OutputManagerDatabase<TBackend>,
+}
+
+impl<TBackend> WatchOnlyUtxoRecoverer<TBackend>
+where TBackend: OutputManagerBackend + 'static
+{
+    pub fn new(rewind_keys: PublicRewindKeys, factories: CryptoFactories, db: OutputManagerDatabase<TBackend>) -> Self {
+        Self {
+            rewind_keys,
+            factories,
+            db,
+        }
+    }
+
+    /// Attempt to rewind the value of each of the given transaction outputs. Outputs that belong to this wallet and are
+    /// not yet known are added to the database and returned.
+    pub async fn scan_and_recover_outputs(
+        &mut self,
+        outputs: Vec<TransactionOutput>,
+    ) -> Result<Vec<UnblindedOutput>, OutputManagerError> {
+        let mut rewound_outputs = Vec::new();
+        for output in outputs {
+            let db_output = match self.rewind_output(output) {
+                Some(o) => o,
+                None => continue,
+            };
+            let commitment = db_output.commitment.clone();
+            let unblinded_output = db_output.unblinded_output.clone();
+            match self.db.add_unspent_output(db_output).await {
+                Ok(_) => {},
+                Err(OutputManagerStorageError::DuplicateOutput) => continue,
+                Err(e) => return Err(e.into()),
+            }
+
+            trace!(
+                target: LOG_TARGET,
+                "Watch-only output {} with value {} with {} recovered",
+                commitment.to_hex(),
+                unblinded_output.value,
+                unblinded_output.features,
+            );
+            rewound_outputs.push(unblinded_output);
+        }
+
+        Ok(rewound_outputs)
+    }
+
+    /// Mark tracked outputs that were spent in the same block as spent, under a single TxId. A watch-only wallet cannot
+    /// spend its outputs, so they were spent by the wallet that holds the keys. The outputs of the spending block that
+    /// belong to this wallet are its change: they are added to the database if they are not yet known and their value
+    /// is netted out of the spend. Returns the TxId, the net value that left the wallet and the TxIds under which the
+    /// change outputs were imported by the UTXO scanner, if it got to them first.
+    pub async fn record_spent_outputs(
+        &self,
+        outputs: Vec<DbUnblindedOutput>,
+        block_outputs: Vec<TransactionOutput>,
+    ) -> Result<(TxId, MicroTari, Vec<TxId>), OutputManagerError> {
+        let tx_id = OsRng.next_u64();
+        let spent_value = outputs
+            .iter()
+            .fold(MicroTari::from(0), |total, o| total + o.unblinded_output.value);
+
+        // Other outputs received in the same block cannot be told apart from change, so only as much as was spent is
+        // counted as change; anything beyond that is left for the UTXO scanner to import as received
+        let mut change_outputs = block_outputs
+            .into_iter()
+            .filter_map(|o| self.rewind_output(o))
+            .collect::<Vec<_>>();
+        change_outputs.sort_by_key(|o| o.unblinded_output.value);
+        let mut change_value = MicroTari::from(0);
+        let mut replaced_imports = Vec::new();
+        for change in change_outputs {
+            if change_value + change.unblinded_output.value > spent_value {
+                break;
+            }
+            change_value += change.unblinded_output.value;
+            let import_tx_id = import_tx_id(&change.unblinded_output);
+            let commitment = change.commitment.clone();
+            match self.db.add_unspent_output(change).await {
+                Ok(_) => {},
+                Err(OutputManagerStorageError::DuplicateOutput) => replaced_imports.push(import_tx_id),
+                Err(e) => return Err(e.into()),
+            }
+            trace!(
+                target: LOG_TARGET,
+                "Watch-only output {} is change of spend (TxId: {})",
+                commitment.to_hex(),
+                tx_id,
+            );
+        }
+
+        for output in outputs.iter() {
+            trace!(
+                target: LOG_TARGET,
+                "Watch-only output {} with value {} spent (TxId: {})",
+                output.commitment.to_hex(),
+                output.unblinded_output.value,
+                tx_id,
+            );
+        }
+        self.db.encumber_outputs(tx_id, outputs, Vec::new()).await?;
+        self.db.confirm_encumbered_outputs(tx_id).await?;
+        self.db.confirm_pending_transaction_outputs(tx_id).await?;
+
+        Ok((tx_id, spent_value - change_value, replaced_imports))
+    }
+
+    /// Rewind the value of a single output using the public rewind keys, returning `None` if it does not belong to
+    /// this wallet.
+    pub fn rewind_output(&self, output: TransactionOutput) -> Option<DbUnblindedOutput> {
+        let rewound = output
+            .rewind_range_proof_value_only(
+                &self.factories.range_proof,
+                &self.rewind_keys.rewind_public_key,
+                &self.rewind_keys.rewind_blinding_public_key,
+            )
+            .ok()?;
+        let hash = output.hash();
+        Some(DbUnblindedOutput {
+            commitment: output.commitment,
+            unblinded_output: UnblindedOutput::new(
+                rewound.committed_value,
+                PrivateKey::default(),
+                Some(output.features),
+                output.script,
+                ExecutionStack::default(),
+                PrivateKey::default(),
+                output.sender_offset_public_key,
+                output.metadata_signature,
+            ),
+            hash,
+            received_from: None,
+            account: account_from_proof_message(&rewound.proof_message),
+        })
+    }
+}
+
+/// The TxId under which the UTXO scanner imports a recovered output. It is derived from the output's metadata
+/// signature, so a watch-only wallet that later finds the output to be change can cancel the import.
+pub(crate) fn import_tx_id(output: &UnblindedOutput) -> TxId {
+    let mut tx_id_bytes = [0u8; 8];
+    tx_id_bytes.copy_from_slice(&output.metadata_signature.u().as_bytes()[..8]);
+    u64::from_le_bytes(tx_id_bytes)
+}
@@ -23,7 +23,7 @@
 use crate::{
     output_manager_service::{
         config::OutputManagerServiceConfig,
-        handle::OutputManagerEventSender,
+        handle::{OutputManagerEventSender, PublicRewindKeys},
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
         MasterKeyManager,
     },
@@ -46,6 +46,8 @@ where TBackend: OutputManagerBackend + 'static
     pub base_node_public_key: Option<CommsPublicKey>,
     pub event_publisher: OutputManagerEventSender,
     pub master_key_manager: Arc<MasterKeyManager<TBackend>>,
+    /// The rewind public keys of a watch-only wallet. When set the wallet has no spend keys of its own.
+    pub watch_only_keys: Option<PublicRewindKeys>,
     pub consensus_constants: ConsensusConstants,
     pub connectivity_manager: ConnectivityRequester,
     pub shutdown_signal: ShutdownSignal,
@@ -25,16 +25,19 @@ use crate::{
     output_manager_service::{
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
-        handle::{OutputManagerEventSender, OutputManagerRequest, OutputManagerResponse},
-        recovery::StandardUtxoRecoverer,
+        handle::{OutputManagerEventSender, OutputManagerRequest, OutputManagerResponse, PublicRewindKeys},
+        offline_signing::{SignedTransaction, UnsignedInput, UnsignedTransaction},
+        recovery::{StandardUtxoRecoverer, WatchOnlyUtxoRecoverer},
         resources::OutputManagerResources,
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase, PendingTransactionOutputs},
             models::{DbUnblindedOutput, KnownOneSidedPaymentScript},
         },
         tasks::{TxoValidationTask, TxoValidationType},
+        utxo_selection,
         MasterKeyManager,
         TxId,
+        DEFAULT_ACCOUNT,
     },
     transaction_service::handle::TransactionServiceHandle,
     types::{HashDigest, ValidationRetryStrategy},
@@ -76,6 +79,7 @@ use tari_core::{
     },
 };
 use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
     inputs,
     keys::{DiffieHellmanSharedSecret, PublicKey as PublicKeyTrait, SecretKey},
     script,
@@ -137,6 +141,7 @@ where TBackend: OutputManagerBackend + 'static
             base_node_public_key: None,
             event_publisher,
             master_key_manager: Arc::new(master_key_manager),
+            watch_only_keys: None,
             consensus_constants,
             connectivity_manager,
             shutdown_signal,
@@ -152,6 +157,13 @@ where TBackend: OutputManagerBackend + 'static
         })
     }
 
+    /// Run the service as a watch-only wallet that tracks the outputs belonging to the given rewind public keys. The
+    /// service will refuse any request that needs a spend key.
+    pub fn with_watch_only_keys(mut self, watch_only_keys: Option<PublicRewindKeys>) -> Self {
+        self.resources.watch_only_keys = watch_only_keys;
+        self
+    }
+
     pub async fn start(mut self) -> Result<(), OutputManagerError> {
         let request_stream = self
             .request_stream
@@ -197,6 +209,9 @@ where TBackend: OutputManagerBackend + 'static
         request: OutputManagerRequest,
     ) -> Result<OutputManagerResponse, OutputManagerError> {
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        if self.resources.watch_only_keys.is_some() && request.requires_spend_key() {
+            return Err(OutputManagerError::WatchOnlyWallet);
+        }
         match request {
             OutputManagerRequest::AddOutput(uo) => self
                 .add_output(None, *uo)
@@ -219,8 +234,26 @@ where TBackend: OutputManagerBackend + 'static
                     .await
                     .map(OutputManagerResponse::Balance)
             },
-            OutputManagerRequest::GetRecipientTransaction(tsm) => self
-                .get_recipient_transaction(tsm)
+            OutputManagerRequest::GetAccountBalance(account) => {
+                let current_chain_tip = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+                    Err(_) => None,
+                };
+                self.get_account_balance(&account, current_chain_tip)
+                    .await
+                    .map(OutputManagerResponse::Balance)
+            },
+            OutputManagerRequest::CreateAccount(account) => self
+                .resources
+                .master_key_manager
+                .create_account(&account)
+                .await
+                .map(|_| OutputManagerResponse::AccountCreated),
+            OutputManagerRequest::GetAccounts => Ok(OutputManagerResponse::Accounts(
+                self.resources.master_key_manager.account_names().await,
+            )),
+            OutputManagerRequest::GetRecipientTransaction((tsm, source_public_key, account)) => self
+                .get_recipient_transaction(tsm, source_public_key, account)
                 .await
                 .map(OutputManagerResponse::RecipientTransactionGenerated),
             OutputManagerRequest::GetCoinbaseTransaction((tx_id, reward, fees, block_height)) => self
@@ -233,16 +266,71 @@ where TBackend: OutputManagerBackend + 'static
                 lock_height,
                 message,
                 recipient_script,
+                utxo_selection,
+                account,
             )) => self
-                .prepare_transaction_to_send(amount, fee_per_gram, lock_height, message, recipient_script)
+                .prepare_transaction_to_send(
+                    amount,
+                    fee_per_gram,
+                    lock_height,
+                    message,
+                    recipient_script,
+                    utxo_selection,
+                    account,
+                )
                 .await
                 .map(OutputManagerResponse::TransactionToSend),
-            OutputManagerRequest::CreatePayToSelfTransaction((amount, fee_per_gram, lock_height, message)) => self
-                .create_pay_to_self_transaction(amount, fee_per_gram, lock_height, message)
+            OutputManagerRequest::CreatePayToSelfTransaction((amount, fee_per_gram, lock_height, message, account)) => {
+                self.create_pay_to_self_transaction(amount, fee_per_gram, lock_height, message, &account)
+                    .await
+                    .map(OutputManagerResponse::PayToSelfTransaction)
+            },
+            OutputManagerRequest::PrepareFeeBumpTransaction((
+                original_tx_id,
+                amount,
+                fee_per_gram,
+                message,
+                recipient_script,
+            )) => self
+                .prepare_fee_bump_transaction(original_tx_id, amount, fee_per_gram, message, recipient_script)
+                .await
+                .map(OutputManagerResponse::TransactionToSend),
+            OutputManagerRequest::CreateFeeBumpPayToSelfTransaction((
+                original_tx_id,
+                amount,
+                fee_per_gram,
+                message,
+            )) => self
+                .create_fee_bump_pay_to_self_transaction(original_tx_id, amount, fee_per_gram, message)
                 .await
                 .map(OutputManagerResponse::PayToSelfTransaction),
-            OutputManagerRequest::FeeEstimate((amount, fee_per_gram, num_kernels, num_outputs)) => self
-                .fee_estimate(amount, fee_per_gram, num_kernels, num_outputs)
+            OutputManagerRequest::ReassignPendingInputs((from_tx_id, to_tx_id)) => self
+                .resources
+                .db
+                .reassign_pending_inputs(from_tx_id, to_tx_id)
+                .await
+                .map(|_| OutputManagerResponse::PendingInputsReassigned)
+                .map_err(OutputManagerError::from),
+            OutputManagerRequest::PrepareUnsignedTransaction((
+                recipient,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+            )) => self
+                .prepare_unsigned_transaction(recipient, amount, fee_per_gram, lock_height, message)
+                .await
+                .map(|unsigned| OutputManagerResponse::UnsignedTransaction(Box::new(unsigned))),
+            OutputManagerRequest::BuildOfflineTransaction(unsigned) => self
+                .build_offline_transaction(*unsigned)
+                .await
+                .map(OutputManagerResponse::OfflineTransactionToSign),
+            OutputManagerRequest::ImportSignedTransaction(signed) => self
+                .import_signed_transaction(*signed)
+                .await
+                .map(|_| OutputManagerResponse::SignedTransactionImported),
+            OutputManagerRequest::FeeEstimate((amount, fee_per_gram, num_kernels, num_outputs, account)) => self
+                .fee_estimate(amount, fee_per_gram, num_kernels, num_outputs, &account)
                 .await
                 .map(OutputManagerResponse::FeeEstimate),
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
@@ -283,6 +371,17 @@ where TBackend: OutputManagerBackend + 'static
                     .collect();
                 Ok(OutputManagerResponse::UnspentOutputs(outputs))
             },
+            OutputManagerRequest::GetAccountUnspentOutputs(account) => {
+                self.check_account_exists(&account).await?;
+                let outputs = self
+                    .fetch_unspent_outputs()
+                    .await?
+                    .into_iter()
+                    .filter(|o| o.account == account)
+                    .map(|v| v.into())
+                    .collect();
+                Ok(OutputManagerResponse::UnspentOutputs(outputs))
+            },
             OutputManagerRequest::GetSeedWords => self
                 .resources
                 .master_key_manager
@@ -305,8 +404,14 @@ where TBackend: OutputManagerBackend + 'static
                     .collect();
                 Ok(OutputManagerResponse::InvalidOutputs(outputs))
             },
-            OutputManagerRequest::CreateCoinSplit((amount_per_split, split_count, fee_per_gram, lock_height)) => self
-                .create_coin_split(amount_per_split, split_count, fee_per_gram, lock_height)
+            OutputManagerRequest::CreateCoinSplit((
+                amount_per_split,
+                split_count,
+                fee_per_gram,
+                lock_height,
+                account,
+            )) => self
+                .create_coin_split(amount_per_split, split_count, fee_per_gram, lock_height, &account)
                 .await
                 .map(OutputManagerResponse::Transaction),
             OutputManagerRequest::ApplyEncryption(cipher) => self
@@ -325,20 +430,31 @@ where TBackend: OutputManagerBackend + 'static
                 .map_err(OutputManagerError::OutputManagerStorageError),
 
             OutputManagerRequest::GetPublicRewindKeys => Ok(OutputManagerResponse::PublicRewindKeys(Box::new(
-                self.resources.master_key_manager.get_rewind_public_keys(),
+                match self.resources.watch_only_keys.clone() {
+                    Some(keys) => keys,
+                    None => self.resources.master_key_manager.get_rewind_public_keys(),
+                },
             ))),
-            OutputManagerRequest::ScanForRecoverableOutputs(outputs) => StandardUtxoRecoverer::new(
-                self.resources.master_key_manager.clone(),
-                self.resources.factories.clone(),
-                self.resources.db.clone(),
-            )
-            .scan_and_recover_outputs(outputs)
-            .await
+            OutputManagerRequest::ScanForRecoverableOutputs(outputs) => match self.watch_only_recoverer() {
+                Some(mut recoverer) => recoverer.scan_and_recover_outputs(outputs).await,
+                None => {
+                    StandardUtxoRecoverer::new(
+                        self.resources.master_key_manager.clone(),
+                        self.resources.factories.clone(),
+                        self.resources.db.clone(),
+                    )
+                    .scan_and_recover_outputs(outputs)
+                    .await
+                },
+            }
             .map(OutputManagerResponse::RewoundOutputs),
-            OutputManagerRequest::ScanOutputs(outputs) => self
-                .scan_outputs_for_one_sided_payments(outputs)
-                .await
-                .map(OutputManagerResponse::ScanOutputs),
+            // A watch-only wallet has no known one-sided payment scripts, it picks up its outputs by rewinding them
+            // while the chain is being scanned
+            OutputManagerRequest::ScanOutputs(outputs) => match self.watch_only_recoverer() {
+                Some(mut recoverer) => recoverer.scan_and_recover_outputs(outputs).await,
+                None => self.scan_outputs_for_one_sided_payments(outputs).await,
+            }
+            .map(OutputManagerResponse::ScanOutputs),
             OutputManagerRequest::AddKnownOneSidedPaymentScript(known_script) => self
                 .add_known_script(known_script)
                 .await
@@ -346,6 +462,13 @@ where TBackend: OutputManagerBackend + 'static
         }
     }
 
+    fn watch_only_recoverer(&self) -> Option<WatchOnlyUtxoRecoverer<TBackend>> {
+        self.resources
+            .watch_only_keys
+            .clone()
+            .map(|keys| WatchOnlyUtxoRecoverer::new(keys, self.resources.factories.clone(), self.resources.db.clone()))
+    }
+
     fn validate_outputs(
         &mut self,
         validation_type: TxoValidationType,
@@ -416,10 +539,35 @@ where TBackend: OutputManagerBackend + 'static
         Ok(balance)
     }
 
-    /// Request a receiver transaction be generated from the supplied Sender Message
+    async fn get_account_balance(
+        &self,
+        account: &str,
+        current_chain_tip: Option<u64>,
+    ) -> Result<Balance, OutputManagerError> {
+        self.check_account_exists(account).await?;
+        let balance = self
+            .resources
+            .db
+            .get_account_balance(account.to_string(), current_chain_tip)
+            .await?;
+        trace!(target: LOG_TARGET, "Balance of account `{}`: {:?}", account, balance);
+        Ok(balance)
+    }
+
+    async fn check_account_exists(&self, account: &str) -> Result<(), OutputManagerError> {
+        if !self.resources.master_key_manager.has_account(account).await {
+            return Err(OutputManagerError::AccountNotFound(account.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Request a receiver transaction be generated from the supplied Sender Message. The output is received into the
+    /// given account.
     async fn get_recipient_transaction(
         &mut self,
         sender_message: TransactionSenderMessage,
+        source_public_key: CommsPublicKey,
+        account: String,
     ) -> Result<ReceiverTransactionProtocol, OutputManagerError> {
         let single_round_sender_data = match sender_message.single() {
             Some(data) => data,
@@ -431,13 +579,14 @@ where TBackend: OutputManagerBackend + 'static
             return Err(OutputManagerError::InvalidScriptHash);
         }
 
+        let rewind_data = self.resources.master_key_manager.rewind_data_for_account(&account)?;
         let (spending_key, script_private_key) = self
             .resources
             .master_key_manager
-            .get_next_spend_and_script_key()
+            .get_next_spend_and_script_key_for_account(&account)
             .await?;
 
-        let output = DbUnblindedOutput::from_unblinded_output(
+        let mut output = DbUnblindedOutput::from_unblinded_output(
             UnblindedOutput::new(
                 single_round_sender_data.amount,
                 spending_key.clone(),
@@ -459,6 +608,8 @@ where TBackend: OutputManagerBackend + 'static
             ),
             &self.resources.factories,
         )?;
+        output.received_from = Some(source_public_key);
+        output.account = account;
 
         self.resources
             .db
@@ -475,7 +626,7 @@ where TBackend: OutputManagerBackend + 'static
             spending_key,
             single_round_sender_data.features.clone(),
             &self.resources.factories,
-            self.resources.master_key_manager.rewind_data(),
+            &rewind_data,
         );
 
         Ok(rtp)
@@ -529,6 +680,7 @@ where TBackend: OutputManagerBackend + 'static
         fee_per_gram: MicroTari,
         num_kernels: u64,
         num_outputs: u64,
+        account: &str,
     ) -> Result<MicroTari, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
@@ -540,7 +692,7 @@ where TBackend: OutputManagerBackend + 'static
         );
 
         let (utxos, _, _) = self
-            .select_utxos(amount, fee_per_gram, num_outputs as usize, None)
+            .select_utxos(amount, fee_per_gram, num_outputs as usize, None, account)
             .await?;
         debug!(target: LOG_TARGET, "{} utxos selected.", utxos.len());
 
@@ -550,8 +702,9 @@ where TBackend: OutputManagerBackend + 'static
         Ok(fee)
     }
 
-    /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
-    /// will be produced.
+    /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified, funded from the outputs of the
+    /// given account. If required a change output will be produced for the same account.
+    #[allow(clippy::too_many_arguments)]
     pub async fn prepare_transaction_to_send(
         &mut self,
         amount: MicroTari,
@@ -559,12 +712,411 @@ where TBackend: OutputManagerBackend + 'static
         lock_height: Option<u64>,
         message: String,
         recipient_script: TariScript,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+        account: String,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        debug!(
+            target: LOG_TARGET,
+            "Preparing to send transaction from account `{}`. Amount: {}. Fee per gram: {}. ",
+            account,
+            amount,
+            fee_per_gram,
+        );
+        self.check_account_exists(&account).await?;
+        let (outputs, _, _) = self
+            .select_utxos(amount, fee_per_gram, 1, utxo_selection, &account)
+            .await?;
+
+        let (stp, change_output) = self
+            .build_transaction_to_send(
+                &outputs,
+                amount,
+                fee_per_gram,
+                lock_height,
+                message,
+                recipient_script,
+                &account,
+                None,
+            )
+            .await?;
+
+        let tx_id = stp.get_tx_id()?;
+        // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
+        // store them until the transaction times out OR is confirmed
+        self.resources
+            .db
+            .encumber_outputs(tx_id, outputs, change_output)
+            .await?;
+
+        debug!(target: LOG_TARGET, "Prepared transaction (TxId: {}) to send", tx_id);
+        debug!(
+            target: LOG_TARGET_STRESS,
+            "Prepared transaction (TxId: {}) to send", tx_id
+        );
+
+        Ok(stp)
+    }
+
+    /// Prepare a Sender Transaction Protocol that replaces the pending transaction `original_tx_id` by spending exactly
+    /// the same inputs at the new `fee_per_gram`. The inputs are moved over to the replacement transaction, the
+    /// original transaction keeps its outputs to be received until one of the two versions is mined or cancelled.
+    pub async fn prepare_fee_bump_transaction(
+        &mut self,
+        original_tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        recipient_script: TariScript,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
-            "Preparing to send transaction. Amount: {}. Fee per gram: {}. ", amount, fee_per_gram,
+            "Preparing fee bump for transaction (TxId: {}). Amount: {}. Fee per gram: {}. ",
+            original_tx_id,
+            amount,
+            fee_per_gram,
+        );
+        let inputs = self.fetch_inputs_to_replace(original_tx_id).await?;
+        // The change of the replacement goes back to the account that funded the original transaction
+        let account = inputs[0].account.clone();
+
+        let (stp, change_output) = self
+            .build_transaction_to_send(
+                &inputs,
+                amount,
+                fee_per_gram,
+                None,
+                message,
+                recipient_script,
+                &account,
+                None,
+            )
+            .await?;
+
+        let tx_id = stp.get_tx_id()?;
+        self.resources
+            .db
+            .encumber_outputs(tx_id, Vec::new(), change_output)
+            .await?;
+        self.confirm_encumberance(tx_id).await?;
+        self.resources.db.reassign_pending_inputs(original_tx_id, tx_id).await?;
+
+        debug!(
+            target: LOG_TARGET,
+            "Prepared fee bump transaction (TxId: {}) to replace TxId: {}", tx_id, original_tx_id
         );
-        let (outputs, _, total) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
+
+        Ok(stp)
+    }
+
+    /// Prepare a one-sided transaction to `recipient` that will be signed by an offline wallet holding the master key.
+    /// The selected inputs are encumbered against the returned TxId until the signed transaction is imported or the
+    /// transaction is cancelled. Only outputs of the default account are used, as the signing wallet derives their keys
+    /// from its default key chain.
+    pub async fn prepare_unsigned_transaction(
+        &mut self,
+        recipient: PublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+    ) -> Result<UnsignedTransaction, OutputManagerError> {
+        debug!(
+            target: LOG_TARGET,
+            "Preparing unsigned transaction. Amount: {}. Fee per gram: {}. ", amount, fee_per_gram,
+        );
+        let (outputs, _, total) = self
+            .select_utxos(amount, fee_per_gram, 1, None, DEFAULT_ACCOUNT)
+            .await?;
+
+        // This must match the change calculation in `build_transaction_to_send`, which the signing wallet will use
+        let fee_with_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 2);
+        let (fee, change) = if total > amount + fee_with_change {
+            (fee_with_change, Some(total - amount - fee_with_change))
+        } else {
+            (total - amount, None)
+        };
+
+        let is_watch_only = self.watch_only_recoverer().is_some();
+        let mut inputs = Vec::with_capacity(outputs.len());
+        for output in &outputs {
+            let key_index = if is_watch_only {
+                None
+            } else {
+                self.resources
+                    .master_key_manager
+                    .find_utxo_key_index(output.unblinded_output.spending_key.clone())
+                    .await
+                    .ok()
+            };
+            let uo = &output.unblinded_output;
+            inputs.push(UnsignedInput {
+                commitment: output.commitment.clone(),
+                value: uo.value,
+                features: uo.features.clone(),
+                script: uo.script.clone(),
+                sender_offset_public_key: uo.sender_offset_public_key.clone(),
+                metadata_signature: uo.metadata_signature.clone(),
+                key_index,
+            });
+        }
+
+        // Reserve the next key for the change output so that it is not issued again before the signed transaction is
+        // imported
+        let change_key_index = if change.is_some() && !is_watch_only {
+            let index = self.resources.master_key_manager.key_index().await + 1;
+            self.resources
+                .master_key_manager
+                .update_current_index_if_higher(index)
+                .await?;
+            Some(index)
+        } else {
+            None
+        };
+
+        let tx_id = OsRng.next_u64();
+        self.resources.db.encumber_outputs(tx_id, outputs, Vec::new()).await?;
+        self.confirm_encumberance(tx_id).await?;
+
+        debug!(target: LOG_TARGET, "Prepared unsigned transaction (TxId: {})", tx_id);
+
+        Ok(UnsignedTransaction {
+            tx_id,
+            inputs,
+            recipient,
+            amount,
+            fee_per_gram,
+            fee,
+            change,
+            lock_height,
+            message,
+            change_key_index,
+        })
+    }
+
+    /// Build the Sender Transaction Protocol for a transaction that was prepared by an online wallet. This wallet must
+    /// hold the master key the inputs were derived from; only inputs from the UTXO key chain can be signed this way.
+    /// The key chain index of the change output is returned along with the protocol.
+    pub async fn build_offline_transaction(
+        &mut self,
+        unsigned: UnsignedTransaction,
+    ) -> Result<(SenderTransactionProtocol, Option<u64>), OutputManagerError> {
+        let mut inputs = Vec::with_capacity(unsigned.inputs.len());
+        let mut max_key_index = unsigned.change_key_index.unwrap_or(0);
+        for input in unsigned.inputs {
+            let index = match input.key_index {
+                Some(index) => index,
+                None => {
+                    self.resources
+                        .master_key_manager
+                        .find_utxo_key_index_for_commitment(
+                            &input.commitment,
+                            input.value,
+                            &self.resources.factories.commitment,
+                        )
+                        .await?
+                },
+            };
+            let (spending_key, script_private_key) = self
+                .resources
+                .master_key_manager
+                .get_spend_and_script_key_at_index(index)
+                .await?;
+            if self
+                .resources
+                .factories
+                .commitment
+                .commit_value(&spending_key, input.value.as_u64()) !=
+                input.commitment
+            {
+                return Err(OutputManagerError::OfflineSigningError(format!(
+                    "The input at key index {} was not derived from this wallet's master key",
+                    index
+                )));
+            }
+            max_key_index = max_key_index.max(index);
+            let unblinded_output = UnblindedOutput::new(
+                input.value,
+                spending_key,
+                Some(input.features),
+                input.script,
+                inputs!(PublicKey::from_secret_key(&script_private_key)),
+                script_private_key,
+                input.sender_offset_public_key,
+                input.metadata_signature,
+            );
+            inputs.push(DbUnblindedOutput::from_unblinded_output(
+                unblinded_output,
+                &self.resources.factories,
+            )?);
+        }
+        // Keep this wallet's own key chain ahead of every key used by the transaction
+        self.resources
+            .master_key_manager
+            .update_current_index_if_higher(max_key_index)
+            .await?;
+
+        let change_keys = match unsigned.change_key_index {
+            Some(index) => Some(
+                self.resources
+                    .master_key_manager
+                    .get_spend_and_script_key_at_index(index)
+                    .await?,
+            ),
+            None => None,
+        };
+        let (stp, change_output) = self
+            .build_transaction_to_send(
+                &inputs,
+                unsigned.amount,
+                unsigned.fee_per_gram,
+                unsigned.lock_height,
+                unsigned.message,
+                script!(PushPubKey(Box::new(unsigned.recipient))),
+                DEFAULT_ACCOUNT,
+                change_keys,
+            )
+            .await?;
+
+        let change = change_output.first().map(|o| o.unblinded_output.value);
+        if change != unsigned.change || stp.get_fee_amount()? != unsigned.fee {
+            return Err(OutputManagerError::OfflineSigningError(
+                "The signed transaction does not match the fee and change of the unsigned transaction".to_string(),
+            ));
+        }
+        let change_key_index = match (change, unsigned.change_key_index) {
+            (Some(_), Some(index)) => Some(index),
+            (Some(_), None) => Some(self.resources.master_key_manager.key_index().await),
+            (None, _) => None,
+        };
+
+        Ok((stp, change_key_index))
+    }
+
+    /// Import a transaction that was signed by an offline wallet. The change output is recovered from the key chain and
+    /// the inputs that were encumbered when the transaction was prepared are re-encumbered along with it.
+    pub async fn import_signed_transaction(&mut self, signed: SignedTransaction) -> Result<(), OutputManagerError> {
+        let pending_transaction = self
+            .resources
+            .db
+            .fetch_pending_transaction_outputs(signed.tx_id)
+            .await?;
+        let inputs = signed.transaction.body.inputs();
+        if inputs.len() != pending_transaction.outputs_to_be_spent.len() ||
+            pending_transaction
+                .outputs_to_be_spent
+                .iter()
+                .any(|o| inputs.iter().all(|input| input.commitment != o.commitment))
+        {
+            return Err(OutputManagerError::OfflineSigningError(
+                "The signed transaction does not spend the prepared inputs".to_string(),
+            ));
+        }
+
+        let mut change_output = Vec::new();
+        if let (Some(value), Some(recoverer)) = (signed.change, self.watch_only_recoverer()) {
+            // A watch-only wallet cannot derive the change key, it finds the change output by rewinding its value
+            let output = signed
+                .transaction
+                .body
+                .outputs()
+                .iter()
+                .filter_map(|o| recoverer.rewind_output(o.clone()))
+                .find(|o| o.unblinded_output.value == value)
+                .ok_or_else(|| {
+                    OutputManagerError::OfflineSigningError(
+                        "The change output is missing from the signed transaction".to_string(),
+                    )
+                })?;
+            change_output.push(output);
+        } else if let (Some(value), Some(index)) = (signed.change, signed.change_key_index) {
+            let (spending_key, script_private_key) = self
+                .resources
+                .master_key_manager
+                .get_spend_and_script_key_at_index(index)
+                .await?;
+            let commitment = self
+                .resources
+                .factories
+                .commitment
+                .commit_value(&spending_key, value.as_u64());
+            let output = signed
+                .transaction
+                .body
+                .outputs()
+                .iter()
+                .find(|o| o.commitment == commitment)
+                .ok_or_else(|| {
+                    OutputManagerError::OfflineSigningError(
+                        "The change output is missing from the signed transaction".to_string(),
+                    )
+                })?;
+            let unblinded_output = UnblindedOutput::new(
+                value,
+                spending_key,
+                Some(output.features.clone()),
+                output.script.clone(),
+                inputs!(PublicKey::from_secret_key(&script_private_key)),
+                script_private_key,
+                output.sender_offset_public_key.clone(),
+                output.metadata_signature.clone(),
+            );
+            change_output.push(DbUnblindedOutput::from_unblinded_output(
+                unblinded_output,
+                &self.resources.factories,
+            )?);
+            self.resources
+                .master_key_manager
+                .update_current_index_if_higher(index)
+                .await?;
+        }
+
+        // Now that the change output is known the pending transaction can be stored in full
+        self.resources
+            .db
+            .cancel_pending_transaction_outputs(signed.tx_id)
+            .await?;
+        self.resources
+            .db
+            .encumber_outputs(signed.tx_id, pending_transaction.outputs_to_be_spent, change_output)
+            .await?;
+        self.confirm_encumberance(signed.tx_id).await?;
+
+        debug!(
+            target: LOG_TARGET,
+            "Imported signed transaction (TxId: {})", signed.tx_id
+        );
+
+        Ok(())
+    }
+
+    /// Fetch the inputs that a pending transaction is spending so that they can be spent by a replacement transaction.
+    async fn fetch_inputs_to_replace(&self, tx_id: TxId) -> Result<Vec<DbUnblindedOutput>, OutputManagerError> {
+        let pending_transaction = self.resources.db.fetch_pending_transaction_outputs(tx_id).await?;
+        if pending_transaction.outputs_to_be_spent.is_empty() {
+            return Err(OutputManagerError::IncompleteTransaction(
+                "pending transaction has no inputs to replace",
+            ));
+        }
+        Ok(pending_transaction.outputs_to_be_spent)
+    }
+
+    /// Build a single recipient Sender Transaction Protocol that spends all of the provided inputs. A change output for
+    /// `account` is returned if one was required, it uses `change_keys` if given or else the next keys of the account.
+    /// If all of the inputs were received from the same counterparty the change output is recorded as received from
+    /// them too, so that privacy preserving UTXO selection keeps it apart from the outputs of other counterparties.
+    #[allow(clippy::too_many_arguments)]
+    async fn build_transaction_to_send(
+        &mut self,
+        outputs: &[DbUnblindedOutput],
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        recipient_script: TariScript,
+        account: &str,
+        change_keys: Option<(PrivateKey, PrivateKey)>,
+    ) -> Result<(SenderTransactionProtocol, Vec<DbUnblindedOutput>), OutputManagerError> {
+        let total: MicroTari = outputs.iter().map(|o| o.unblinded_output.value).sum();
 
         let offset = PrivateKey::random(&mut OsRng);
         let nonce = PrivateKey::random(&mut OsRng);
@@ -599,17 +1151,22 @@ where TBackend: OutputManagerBackend + 'static
             amount,
             outputs.len()
         );
-        let fee_without_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 1);
-        // If the input values > the amount to be sent + fee_without_change then we will need to include a change
-        // output
-        if total > amount + fee_without_change {
-            let (spending_key, script_private_key) = self
-                .resources
-                .master_key_manager
-                .get_next_spend_and_script_key()
-                .await?;
+        // If the input values cover the amount to be sent, the fee and the cost of a change output then we will need to
+        // include a change output. Anything less than the cost of a change output is paid as fee.
+        let fee_with_change = Fee::calculate(fee_per_gram, 1, outputs.len(), 2);
+        let include_change_output = total > amount + fee_with_change;
+        if include_change_output {
+            let (spending_key, script_private_key) = match change_keys {
+                Some(keys) => keys,
+                None => {
+                    self.resources
+                        .master_key_manager
+                        .get_next_spend_and_script_key_for_account(account)
+                        .await?
+                },
+            };
             builder.with_change_secret(spending_key);
-            builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
+            builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data_for_account(account)?);
             builder.with_change_script(
                 script!(Nop),
                 inputs!(PublicKey::from_secret_key(&script_private_key)),
@@ -623,33 +1180,19 @@ where TBackend: OutputManagerBackend + 'static
 
         // If a change output was created add it to the pending_outputs list.
         let mut change_output = Vec::<DbUnblindedOutput>::new();
-        if total > amount + fee_without_change {
+        if include_change_output {
             let unblinded_output = stp.get_change_unblinded_output()?.ok_or_else(|| {
                 OutputManagerError::BuildError(
                     "There should be a change output metadata signature available".to_string(),
                 )
             })?;
-            change_output.push(DbUnblindedOutput::from_unblinded_output(
-                unblinded_output,
-                &self.resources.factories,
-            )?);
+            let mut output = DbUnblindedOutput::from_unblinded_output(unblinded_output, &self.resources.factories)?;
+            output.account = account.to_string();
+            output.received_from = utxo_selection::common_source(outputs);
+            change_output.push(output);
         }
 
-        let tx_id = stp.get_tx_id()?;
-        // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
-        // store them until the transaction times out OR is confirmed
-        self.resources
-            .db
-            .encumber_outputs(tx_id, outputs, change_output)
-            .await?;
-
-        debug!(target: LOG_TARGET, "Prepared transaction (TxId: {}) to send", tx_id);
-        debug!(
-            target: LOG_TARGET_STRESS,
-            "Prepared transaction (TxId: {}) to send", tx_id
-        );
-
-        Ok(stp)
+        Ok((stp, change_output))
     }
 
     /// Request a Coinbase transaction for a specific block height. All existing pending transactions with
@@ -717,8 +1260,55 @@ where TBackend: OutputManagerBackend + 'static
         fee_per_gram: MicroTari,
         lock_height: Option<u64>,
         message: String,
+        account: &str,
     ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError> {
-        let (inputs, _, total) = self.select_utxos(amount, fee_per_gram, 1, None).await?;
+        let (inputs, _, _) = self.select_utxos(amount, fee_per_gram, 1, None, account).await?;
+        self.build_pay_to_self_transaction(inputs, None, amount, fee_per_gram, lock_height, message, account)
+            .await
+    }
+
+    /// Create a pay-to-self transaction that replaces the pending pay-to-self transaction `original_tx_id` by spending
+    /// the same inputs at the new `fee_per_gram`.
+    async fn create_fee_bump_pay_to_self_transaction(
+        &mut self,
+        original_tx_id: TxId,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError> {
+        let inputs = self.fetch_inputs_to_replace(original_tx_id).await?;
+        // The replacement pays into the account that funded the original transaction
+        let account = inputs
+            .first()
+            .map(|o| o.account.clone())
+            .unwrap_or_else(|| DEFAULT_ACCOUNT.to_string());
+        self.build_pay_to_self_transaction(
+            inputs,
+            Some(original_tx_id),
+            amount,
+            fee_per_gram,
+            None,
+            message,
+            &account,
+        )
+        .await
+    }
+
+    /// Build, encumber and finalize a pay-to-self transaction spending all of the provided inputs. The outputs are
+    /// credited to the given account. If the transaction replaces another pending transaction the inputs are moved over
+    /// from that transaction instead of being encumbered afresh.
+    #[allow(clippy::too_many_arguments)]
+    async fn build_pay_to_self_transaction(
+        &mut self,
+        inputs: Vec<DbUnblindedOutput>,
+        replaces: Option<TxId>,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        lock_height: Option<u64>,
+        message: String,
+        account: &str,
+    ) -> Result<(TxId, MicroTari, Transaction), OutputManagerError> {
+        let total: MicroTari = inputs.iter().map(|o| o.unblinded_output.value).sum();
 
         let offset = PrivateKey::random(&mut OsRng);
         let nonce = PrivateKey::random(&mut OsRng);
@@ -747,7 +1337,7 @@ where TBackend: OutputManagerBackend + 'static
         let (spending_key, script_private_key) = self
             .resources
             .master_key_manager
-            .get_next_spend_and_script_key()
+            .get_next_spend_and_script_key_for_account(account)
             .await?;
         let metadata_signature = TransactionOutput::create_final_metadata_signature(
             &amount,
@@ -756,7 +1346,7 @@ where TBackend: OutputManagerBackend + 'static
             &output_features,
             &&sender_offset_private_key,
         )?;
-        let utxo = DbUnblindedOutput::from_unblinded_output(
+        let mut utxo = DbUnblindedOutput::from_unblinded_output(
             UnblindedOutput::new(
                 amount,
                 spending_key.clone(),
@@ -769,6 +1359,7 @@ where TBackend: OutputManagerBackend + 'static
             ),
             &self.resources.factories,
         )?;
+        utxo.account = account.to_string();
         builder
             .with_output(utxo.unblinded_output.clone(), sender_offset_private_key.clone())
             .map_err(|e| OutputManagerError::BuildError(e.message))?;
@@ -781,10 +1372,10 @@ where TBackend: OutputManagerBackend + 'static
             let (spending_key, script_private_key) = self
                 .resources
                 .master_key_manager
-                .get_next_spend_and_script_key()
+                .get_next_spend_and_script_key_for_account(account)
                 .await?;
             builder.with_change_secret(spending_key);
-            builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
+            builder.with_rewindable_outputs(self.resources.master_key_manager.rewind_data_for_account(account)?);
             builder.with_change_script(
                 script!(Nop),
                 inputs!(PublicKey::from_secret_key(&script_private_key)),
@@ -803,7 +1394,9 @@ where TBackend: OutputManagerBackend + 'static
                     "There should be a change output metadata signature available".to_string(),
                 )
             })?;
-            let change_output = DbUnblindedOutput::from_unblinded_output(unblinded_output, &self.resources.factories)?;
+            let mut change_output =
+                DbUnblindedOutput::from_unblinded_output(unblinded_output, &self.resources.factories)?;
+            change_output.account = account.to_string();
 
             outputs.push(change_output);
         }
@@ -814,8 +1407,17 @@ where TBackend: OutputManagerBackend + 'static
             "Encumber send to self transaction ({}) outputs.",
             tx_id
         );
-        self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
-        self.confirm_encumberance(tx_id).await?;
+        match replaces {
+            Some(original_tx_id) => {
+                self.resources.db.encumber_outputs(tx_id, Vec::new(), outputs).await?;
+                self.confirm_encumberance(tx_id).await?;
+                self.resources.db.reassign_pending_inputs(original_tx_id, tx_id).await?;
+            },
+            None => {
+                self.resources.db.encumber_outputs(tx_id, inputs, outputs).await?;
+                self.confirm_encumberance(tx_id).await?;
+            },
+        }
         let fee = stp.get_fee_amount()?;
         trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
         stp.finalize(KernelFeatures::empty(), &factories)?;
@@ -904,21 +1506,26 @@ where TBackend: OutputManagerBackend + 'static
         fee_per_gram: MicroTari,
         output_count: usize,
         strategy: Option<UTXOSelectionStrategy>,
+        account: &str,
     ) -> Result<(Vec<DbUnblindedOutput>, bool, MicroTari), OutputManagerError> {
         debug!(
             target: LOG_TARGET,
-            "select_utxos amount: {}, fee_per_gram: {}, output_count: {}, strategy: {:?}",
+            "select_utxos amount: {}, fee_per_gram: {}, output_count: {}, strategy: {:?}, account: {}",
             amount,
             fee_per_gram,
             output_count,
-            strategy
+            strategy,
+            account
         );
-        let mut utxos = Vec::new();
-        let mut utxos_total_value = MicroTari::from(0);
-        let mut fee_without_change = MicroTari::from(0);
-        let mut fee_with_change = MicroTari::from(0);
-
-        let uo = self.resources.db.fetch_sorted_unspent_outputs().await?;
+        // Only the outputs of the given account may fund the transaction
+        let uo = self
+            .resources
+            .db
+            .fetch_sorted_unspent_outputs()
+            .await?
+            .into_iter()
+            .filter(|o| o.account == account)
+            .collect::<Vec<_>>();
 
         // Attempt to get the chain tip height
         let chain_metadata = self.base_node_service.get_chain_metadata().await?;
@@ -970,53 +1577,69 @@ where TBackend: OutputManagerBackend + 'static
         };
         debug!(target: LOG_TARGET, "select_utxos selection strategy: {}", strategy);
 
-        let uo = match strategy {
-            UTXOSelectionStrategy::Smallest => uo,
+        trace!(target: LOG_TARGET, "We found {} UTXOs to select from", uo.len());
+
+        let selection = match strategy {
+            UTXOSelectionStrategy::Smallest => utxo_selection::select_in_order(uo, amount, fee_per_gram, output_count),
             UTXOSelectionStrategy::MaturityThenSmallest => {
-                let mut uo = uo;
-                uo.sort_by(|a, b| {
-                    match a
-                        .unblinded_output
-                        .features
-                        .maturity
-                        .cmp(&b.unblinded_output.features.maturity)
-                    {
-                        Ordering::Equal => a.unblinded_output.value.cmp(&b.unblinded_output.value),
-                        Ordering::Less => Ordering::Less,
-                        Ordering::Greater => Ordering::Greater,
-                    }
-                });
-                uo
+                utxo_selection::select_in_order(sort_by_maturity_then_value(uo), amount, fee_per_gram, output_count)
+            },
+            UTXOSelectionStrategy::Largest => {
+                utxo_selection::select_in_order(uo.into_iter().rev(), amount, fee_per_gram, output_count)
+            },
+            UTXOSelectionStrategy::BranchAndBound => {
+                match utxo_selection::select_branch_and_bound(&uo, amount, fee_per_gram, output_count) {
+                    Some(selection) => selection,
+                    None => {
+                        debug!(
+                            target: LOG_TARGET,
+                            "No changeless UTXO selection found, falling back to MaturityThenSmallest"
+                        );
+                        utxo_selection::select_in_order(
+                            sort_by_maturity_then_value(uo),
+                            amount,
+                            fee_per_gram,
+                            output_count,
+                        )
+                    },
+                }
+            },
+            UTXOSelectionStrategy::ConsolidateWhenCheap => {
+                if fee_per_gram <= self.resources.config.consolidation_fee_per_gram_threshold {
+                    utxo_selection::select_with_consolidation(
+                        uo,
+                        amount,
+                        fee_per_gram,
+                        output_count,
+                        self.resources.config.max_consolidation_inputs,
+                    )
+                } else {
+                    utxo_selection::select_in_order(uo.into_iter().rev(), amount, fee_per_gram, output_count)
+                }
+            },
+            UTXOSelectionStrategy::Privacy => {
+                match utxo_selection::select_from_single_source(uo.clone(), amount, fee_per_gram, output_count) {
+                    Some(selection) => selection,
+                    None => {
+                        let selection =
+                            utxo_selection::select_in_order(uo.into_iter().rev(), amount, fee_per_gram, output_count);
+                        if selection.is_funded(amount, fee_per_gram, output_count) {
+                            return Err(OutputManagerError::InsufficientSingleSourceFunds);
+                        }
+                        selection
+                    },
+                }
             },
-            UTXOSelectionStrategy::Largest => uo.into_iter().rev().collect(),
         };
-        trace!(target: LOG_TARGET, "We found {} UTXOs to select from", uo.len());
-
-        let mut require_change_output = false;
-        for o in uo.iter() {
-            utxos.push(o.clone());
-            utxos_total_value += o.unblinded_output.value;
-            // The assumption here is that the only output will be the payment output and change if required
-            fee_without_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count);
-            if utxos_total_value == amount + fee_without_change {
-                break;
-            }
-            fee_with_change = Fee::calculate(fee_per_gram, 1, utxos.len(), output_count + 1);
-            if utxos_total_value >= amount + fee_with_change {
-                require_change_output = true;
-                break;
-            }
-        }
 
         let current_chain_tip = chain_metadata.map(|cm| cm.height_of_longest_chain());
-        let balance = self.get_balance(current_chain_tip).await?;
+        let balance = self.get_account_balance(account, current_chain_tip).await?;
         let pending_incoming = balance.pending_incoming_balance;
 
-        let perfect_utxo_selection = utxos_total_value == amount + fee_without_change;
-        let not_enough_spendable = utxos_total_value < amount + fee_with_change;
-        let enough_with_pending = utxos_total_value + pending_incoming >= amount + fee_with_change;
+        let fee_with_change = selection.fee_with_change(fee_per_gram, output_count);
+        let enough_with_pending = selection.total_value + pending_incoming >= amount + fee_with_change;
 
-        if !perfect_utxo_selection && not_enough_spendable {
+        if !selection.is_funded(amount, fee_per_gram, output_count) {
             if enough_with_pending {
                 return Err(OutputManagerError::FundsPending);
             } else {
@@ -1024,7 +1647,7 @@ where TBackend: OutputManagerBackend + 'static
             }
         }
 
-        Ok((utxos, require_change_output, utxos_total_value))
+        Ok((selection.utxos, selection.requires_change_output, selection.total_value))
     }
 
     /// Set the base node public key to the list that will be used to check the status of UTXO's on the base chain. If
@@ -1075,6 +1698,7 @@ where TBackend: OutputManagerBackend + 'static
         split_count: usize,
         fee_per_gram: MicroTari,
         lock_height: Option<u64>,
+        account: &str,
     ) -> Result<(u64, Transaction, MicroTari, MicroTari), OutputManagerError> {
         trace!(
             target: LOG_TARGET,
@@ -1088,6 +1712,7 @@ where TBackend: OutputManagerBackend + 'static
                 fee_per_gram,
                 output_count,
                 Some(UTXOSelectionStrategy::Largest),
+                account,
             )
             .await?;
         let input_count = inputs.len();
@@ -1106,7 +1731,7 @@ where TBackend: OutputManagerBackend + 'static
             .with_fee_per_gram(fee_per_gram)
             .with_offset(offset.clone())
             .with_private_nonce(nonce.clone())
-            .with_rewindable_outputs(self.resources.master_key_manager.rewind_data().clone());
+            .with_rewindable_outputs(self.resources.master_key_manager.rewind_data_for_account(account)?);
 
         trace!(target: LOG_TARGET, "Add inputs to coin split transaction.");
         for uo in inputs.iter() {
@@ -1133,7 +1758,7 @@ where TBackend: OutputManagerBackend + 'static
             let (spending_key, script_private_key) = self
                 .resources
                 .master_key_manager
-                .get_next_spend_and_script_key()
+                .get_next_spend_and_script_key_for_account(account)
                 .await?;
             let sender_offset_private_key = PrivateKey::random(&mut OsRng);
 
@@ -1147,7 +1772,7 @@ where TBackend: OutputManagerBackend + 'static
                 &output_features,
                 &sender_offset_private_key,
             )?;
-            let utxo = DbUnblindedOutput::from_unblinded_output(
+            let mut utxo = DbUnblindedOutput::from_unblinded_output(
                 UnblindedOutput::new(
                     output_amount,
                     spending_key.clone(),
@@ -1160,6 +1785,7 @@ where TBackend: OutputManagerBackend + 'static
                 ),
                 &self.resources.factories,
             )?;
+            utxo.account = account.to_string();
             outputs.push(utxo.clone());
             builder
                 .with_output(utxo.unblinded_output, sender_offset_private_key)
@@ -1259,9 +1885,25 @@ where TBackend: OutputManagerBackend + 'static
     }
 }
 
+/// Sorts UTXOs by maturity, oldest first, and then by value, smallest first
+fn sort_by_maturity_then_value(mut uo: Vec<DbUnblindedOutput>) -> Vec<DbUnblindedOutput> {
+    uo.sort_by(|a, b| {
+        match a
+            .unblinded_output
+            .features
+            .maturity
+            .cmp(&b.unblinded_output.features.maturity)
+        {
+            Ordering::Equal => a.unblinded_output.value.cmp(&b.unblinded_output.value),
+            Ordering::Less => Ordering::Less,
+            Ordering::Greater => Ordering::Greater,
+        }
+    });
+    uo
+}
+
 /// Different UTXO selection strategies for choosing which UTXO's are used to fulfill a transaction
-/// TODO Investigate and implement more optimal strategies
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UTXOSelectionStrategy {
     // Start from the smallest UTXOs and work your way up until the amount is covered. Main benefit
     // is removing small UTXOs from the blockchain, con is that it costs more in fees
@@ -1270,6 +1912,14 @@ pub enum UTXOSelectionStrategy {
     MaturityThenSmallest,
     // A strategy that selects the largest UTXOs first. Preferred when the amount is large
     Largest,
+    // Search for a set of UTXOs that covers the amount exactly, within the cost of a change output, so that no change
+    // output is created. Falls back to MaturityThenSmallest if there is no such set
+    BranchAndBound,
+    // When the fee per gram is at or below the configured threshold, spend the smallest UTXOs and merge extra small
+    // UTXOs into the change output. Otherwise spend the largest UTXOs to keep the fee down
+    ConsolidateWhenCheap,
+    // Only spend UTXOs received from a single counterparty so that different counterparties are never linked
+    Privacy,
 }
 
 impl Display for UTXOSelectionStrategy {
@@ -1278,6 +1928,9 @@ impl Display for UTXOSelectionStrategy {
             UTXOSelectionStrategy::Smallest => write!(f, "Smallest"),
             UTXOSelectionStrategy::MaturityThenSmallest => write!(f, "MaturityThenSmallest"),
             UTXOSelectionStrategy::Largest => write!(f, "Largest"),
+            UTXOSelectionStrategy::BranchAndBound => write!(f, "BranchAndBound"),
+            UTXOSelectionStrategy::ConsolidateWhenCheap => write!(f, "ConsolidateWhenCheap"),
+            UTXOSelectionStrategy::Privacy => write!(f, "Privacy"),
         }
     }
 }
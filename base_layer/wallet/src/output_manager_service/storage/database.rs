@@ -75,6 +75,9 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     /// `UnspentOutputs` pool. The `outputs_to_be_received`'` will be marked as cancelled inbound outputs in case they
     /// need to be recovered.
     fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    /// This method moves the `outputs_to_be_spent` of one pending transaction to another pending transaction. This is
+    /// used when a transaction is replaced by a version that spends the same inputs, i.e. when bumping its fee.
+    fn reassign_pending_inputs(&self, from_tx_id: TxId, to_tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     /// This method must run through all the `PendingTransactionOutputs` and test if any have existed for longer that
     /// the specified duration. If they have they should be cancelled.
     fn timeout_pending_transactions(&self, period: Duration) -> Result<(), OutputManagerStorageError>;
@@ -83,6 +86,8 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn increment_key_index(&self) -> Result<(), OutputManagerStorageError>;
     /// This method will set the currently stored key index for the key manager
     fn set_key_index(&self, index: u64) -> Result<(), OutputManagerStorageError>;
+    /// This method will set the currently stored key index for the key branch of the named account
+    fn set_account_key_index(&self, account: &str, index: u64) -> Result<(), OutputManagerStorageError>;
     /// If an unspent output is detected as invalid (i.e. not available on the blockchain) then it should be moved to
     /// the invalid outputs collection. The function will return the last recorded TxId associated with this output.
     fn invalidate_unspent_output(&self, output: &DbUnblindedOutput) -> Result<Option<TxId>, OutputManagerStorageError>;
@@ -122,6 +127,13 @@ pub struct KeyManagerState {
     pub primary_key_index: u64,
 }
 
+/// Holds the state of a named account's key branch
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountState {
+    pub name: String,
+    pub key_index: u64,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DbKey {
     SpentOutput(BlindingFactor),
@@ -135,6 +147,7 @@ pub enum DbKey {
     KeyManagerState,
     InvalidOutputs,
     KnownOneSidedPaymentScripts,
+    Accounts,
 }
 
 #[derive(Debug)]
@@ -149,6 +162,7 @@ pub enum DbValue {
     KeyManagerState(KeyManagerState),
     KnownOneSidedPaymentScripts(Vec<KnownOneSidedPaymentScript>),
     AnyOutput(Box<DbUnblindedOutput>),
+    Accounts(Vec<AccountState>),
 }
 
 pub enum DbKeyValuePair {
@@ -158,6 +172,7 @@ pub enum DbKeyValuePair {
     PendingTransactionOutputs(TxId, Box<PendingTransactionOutputs>),
     KeyManagerState(KeyManagerState),
     KnownOneSidedPaymentScripts(KnownOneSidedPaymentScript),
+    Account(AccountState),
 }
 
 pub enum WriteOperation {
@@ -234,6 +249,36 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    pub async fn get_accounts(&self) -> Result<Vec<AccountState>, OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::Accounts) {
+            Ok(None) => Ok(Vec::new()),
+            Ok(Some(DbValue::Accounts(a))) => Ok(a),
+            Ok(Some(other)) => unexpected_result(DbKey::Accounts, other),
+            Err(e) => log_error(DbKey::Accounts, e),
+        })
+        .await
+        .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+        .and_then(|inner_result| inner_result)
+    }
+
+    pub async fn add_account(&self, account: AccountState) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.write(WriteOperation::Insert(DbKeyValuePair::Account(account))))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+
+        Ok(())
+    }
+
+    pub async fn set_account_key_index(&self, account: String, index: u64) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.set_account_key_index(&account, index))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
     pub async fn add_unspent_output(&self, output: DbUnblindedOutput) -> Result<(), OutputManagerStorageError> {
         let db_clone = self.db.clone();
         tokio::task::spawn_blocking(move || {
@@ -267,6 +312,24 @@ where T: OutputManagerBackend + 'static
     }
 
     pub async fn get_balance(&self, current_chain_tip: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
+        self.get_balance_for(None, current_chain_tip).await
+    }
+
+    /// Calculate the balance of the outputs that belong to the named account only
+    pub async fn get_account_balance(
+        &self,
+        account: String,
+        current_chain_tip: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        self.get_balance_for(Some(account), current_chain_tip).await
+    }
+
+    async fn get_balance_for(
+        &self,
+        account: Option<String>,
+        current_chain_tip: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        let in_account = |o: &&DbUnblindedOutput| account.as_ref().map_or(true, |a| &o.account == a);
         let db_clone = self.db.clone();
         let db_clone2 = self.db.clone();
         let db_clone3 = self.db.clone();
@@ -293,6 +356,7 @@ where T: OutputManagerBackend + 'static
             if let DbValue::AllPendingTransactionOutputs(pto) = pending_txs {
                 let available_balance = uo
                     .iter()
+                    .filter(in_account)
                     .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                 let time_locked_balance = if let Some(tip) = current_chain_tip {
                     let time_locked_outputs = tokio::task::spawn_blocking(move || {
@@ -308,6 +372,7 @@ where T: OutputManagerBackend + 'static
                         Some(
                             time_locked_uo
                                 .iter()
+                                .filter(in_account)
                                 .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value),
                         )
                     } else {
@@ -323,10 +388,12 @@ where T: OutputManagerBackend + 'static
                     pending_incoming += v
                         .outputs_to_be_received
                         .iter()
+                        .filter(in_account)
                         .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                     pending_outgoing += v
                         .outputs_to_be_spent
                         .iter()
+                        .filter(in_account)
                         .fold(MicroTari::from(0), |acc, x| acc + x.unblinded_output.value);
                 }
 
@@ -457,6 +524,20 @@ where T: OutputManagerBackend + 'static
             .and_then(|inner_result| inner_result)
     }
 
+    /// Move the encumbered inputs of the `from_tx_id` pending transaction over to the `to_tx_id` pending transaction.
+    /// The outputs to be received by `from_tx_id` are left untouched.
+    pub async fn reassign_pending_inputs(
+        &self,
+        from_tx_id: TxId,
+        to_tx_id: TxId,
+    ) -> Result<(), OutputManagerStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.reassign_pending_inputs(from_tx_id, to_tx_id))
+            .await
+            .map_err(|err| OutputManagerStorageError::BlockingTaskSpawnError(err.to_string()))
+            .and_then(|inner_result| inner_result)
+    }
+
     /// This method is check all pending transactions to see if any are older that the provided duration. If they are
     /// they will be cancelled.
     pub async fn timeout_pending_transaction_outputs(&self, period: Duration) -> Result<(), OutputManagerStorageError> {
@@ -737,6 +818,7 @@ impl Display for DbKey {
             DbKey::TimeLockedUnspentOutputs(_t) => f.write_str(&"Timelocked Outputs"),
             DbKey::KnownOneSidedPaymentScripts => f.write_str(&"Known claiming scripts"),
             DbKey::AnyOutputByCommitment(_) => f.write_str(&"AnyOutputByCommitment"),
+            DbKey::Accounts => f.write_str(&"Accounts"),
         }
     }
 }
@@ -754,6 +836,7 @@ impl Display for DbValue {
             DbValue::InvalidOutputs(_) => f.write_str("Invalid Outputs"),
             DbValue::KnownOneSidedPaymentScripts(_) => f.write_str(&"Known claiming scripts"),
             DbValue::AnyOutput(_) => f.write_str(&"Any Output"),
+            DbValue::Accounts(_) => f.write_str(&"Accounts"),
         }
     }
 }
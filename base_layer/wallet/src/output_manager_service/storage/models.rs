@@ -20,14 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::output_manager_service::error::OutputManagerStorageError;
+use crate::output_manager_service::{error::OutputManagerStorageError, DEFAULT_ACCOUNT};
 use std::cmp::Ordering;
 use tari_core::{
     tari_utilities::hash::Hashable,
     transactions::{
         transaction::UnblindedOutput,
         transaction_protocol::RewindData,
-        types::{Commitment, CryptoFactories, HashOutput, PrivateKey},
+        types::{Commitment, CryptoFactories, HashOutput, PrivateKey, PublicKey},
     },
 };
 use tari_crypto::script::{ExecutionStack, TariScript};
@@ -37,6 +37,10 @@ pub struct DbUnblindedOutput {
     pub commitment: Commitment,
     pub unblinded_output: UnblindedOutput,
     pub hash: HashOutput,
+    /// The public key of the counterparty this output was received from, if it was received in a transaction
+    pub received_from: Option<PublicKey>,
+    /// The name of the account this output belongs to
+    pub account: String,
 }
 
 impl DbUnblindedOutput {
@@ -49,6 +53,8 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            received_from: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         })
     }
 
@@ -62,6 +68,8 @@ impl DbUnblindedOutput {
             hash: tx_out.hash(),
             commitment: tx_out.commitment,
             unblinded_output: output,
+            received_from: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         })
     }
 }
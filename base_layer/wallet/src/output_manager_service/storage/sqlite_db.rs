@@ -25,6 +25,7 @@ use crate::{
         error::OutputManagerStorageError,
         storage::{
             database::{
+                AccountState,
                 DbKey,
                 DbKeyValuePair,
                 DbValue,
@@ -37,7 +38,7 @@ use crate::{
         },
         TxId,
     },
-    schema::{key_manager_states, known_one_sided_payment_scripts, outputs, pending_transaction_outputs},
+    schema::{accounts, key_manager_states, known_one_sided_payment_scripts, outputs, pending_transaction_outputs},
     storage::sqlite_utilities::WalletDbConnection,
     util::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
 };
@@ -268,6 +269,12 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                         .collect::<Result<Vec<_>, _>>()?,
                 ))
             },
+            DbKey::Accounts => Some(DbValue::Accounts(
+                AccountSql::index(&(*conn))?
+                    .into_iter()
+                    .map(AccountState::from)
+                    .collect(),
+            )),
         };
 
         Ok(result)
@@ -337,6 +344,12 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                     self.encrypt_if_necessary(&mut script_sql)?;
                     script_sql.commit(&(*conn))?
                 },
+                DbKeyValuePair::Account(account) => {
+                    if AccountSql::find(&account.name, &(*conn)).is_ok() {
+                        return Err(OutputManagerStorageError::DuplicateAccount);
+                    }
+                    AccountSql::from(account).commit(&(*conn))?
+                },
             },
             WriteOperation::Remove(k) => match k {
                 DbKey::SpentOutput(s) => match OutputSql::find_status(&s.to_vec(), OutputStatus::Spent, &(*conn)) {
@@ -409,6 +422,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                 DbKey::InvalidOutputs => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::TimeLockedUnspentOutputs(_) => return Err(OutputManagerStorageError::OperationNotSupported),
                 DbKey::KnownOneSidedPaymentScripts => return Err(OutputManagerStorageError::OperationNotSupported),
+                DbKey::Accounts => return Err(OutputManagerStorageError::OperationNotSupported),
             },
         }
 
@@ -589,6 +603,40 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn reassign_pending_inputs(&self, from_tx_id: TxId, to_tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        for tx_id in &[from_tx_id, to_tx_id] {
+            if let Err(e) = PendingTransactionOutputSql::find(*tx_id, &(*conn)) {
+                return match e {
+                    OutputManagerStorageError::DieselError(DieselError::NotFound) => {
+                        Err(OutputManagerStorageError::ValueNotFound)
+                    },
+                    e => Err(e),
+                };
+            }
+        }
+
+        let outputs = OutputSql::find_by_tx_id_and_encumbered(from_tx_id, &(*conn))?;
+        for o in outputs {
+            if o.status == (OutputStatus::EncumberedToBeSpent as i32) {
+                o.update(
+                    UpdateOutput {
+                        status: None,
+                        tx_id: Some(to_tx_id),
+                        spending_key: None,
+                        script_private_key: None,
+                        metadata_signature_nonce: None,
+                        metadata_signature_u_key: None,
+                    },
+                    &(*conn),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn timeout_pending_transactions(&self, period: Duration) -> Result<(), OutputManagerStorageError> {
         let conn = self.database_connection.acquire_lock();
 
@@ -619,6 +667,14 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn set_account_key_index(&self, account: &str, index: u64) -> Result<(), OutputManagerStorageError> {
+        let conn = self.database_connection.acquire_lock();
+
+        AccountSql::find(account, &(*conn))?.set_index(index, &(*conn))?;
+
+        Ok(())
+    }
+
     fn invalidate_unspent_output(&self, output: &DbUnblindedOutput) -> Result<Option<TxId>, OutputManagerStorageError> {
         let conn = self.database_connection.acquire_lock();
         let output = OutputSql::find_by_commitment_and_cancelled(&output.commitment.to_vec(), false, &conn)?;
@@ -887,6 +943,8 @@ struct NewOutputSql {
     metadata_signature_nonce: Vec<u8>,
     metadata_signature_u_key: Vec<u8>,
     metadata_signature_v_key: Vec<u8>,
+    received_from: Option<Vec<u8>>,
+    account: String,
 }
 
 impl NewOutputSql {
@@ -911,6 +969,8 @@ impl NewOutputSql {
             metadata_signature_nonce: output.unblinded_output.metadata_signature.public_nonce().to_vec(),
             metadata_signature_u_key: output.unblinded_output.metadata_signature.u().to_vec(),
             metadata_signature_v_key: output.unblinded_output.metadata_signature.v().to_vec(),
+            received_from: output.received_from.map(|pk| pk.to_vec()),
+            account: output.account,
         })
     }
 
@@ -954,6 +1014,8 @@ struct OutputSql {
     metadata_signature_nonce: Vec<u8>,
     metadata_signature_u_key: Vec<u8>,
     metadata_signature_v_key: Vec<u8>,
+    received_from: Option<Vec<u8>>,
+    account: String,
 }
 
 impl OutputSql {
@@ -985,6 +1047,12 @@ impl OutputSql {
             .first::<OutputSql>(conn)?)
     }
 
+    /// Find a particular Output by its database id. Watch-only outputs do not have unique spending keys, so updates
+    /// must refer to outputs by id.
+    fn find_by_id(id: i32, conn: &SqliteConnection) -> Result<OutputSql, OutputManagerStorageError> {
+        Ok(outputs::table.filter(outputs::id.eq(id)).first::<OutputSql>(conn)?)
+    }
+
     pub fn find_by_commitment(
         commitment: &[u8],
         conn: &SqliteConnection,
@@ -1039,8 +1107,7 @@ impl OutputSql {
     }
 
     pub fn delete(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
-        let num_deleted =
-            diesel::delete(outputs::table.filter(outputs::spending_key.eq(&self.spending_key))).execute(conn)?;
+        let num_deleted = diesel::delete(outputs::table.filter(outputs::id.eq(&self.id))).execute(conn)?;
 
         if num_deleted == 0 {
             return Err(OutputManagerStorageError::ValuesNotFound);
@@ -1064,7 +1131,7 @@ impl OutputSql {
             ));
         }
 
-        OutputSql::find(&self.spending_key, conn)
+        OutputSql::find_by_id(self.id, conn)
     }
 
     /// This function is used to update an existing record to set fields to null
@@ -1073,7 +1140,7 @@ impl OutputSql {
         updated_null: NullOutputSql,
         conn: &SqliteConnection,
     ) -> Result<OutputSql, OutputManagerStorageError> {
-        let num_updated = diesel::update(outputs::table.filter(outputs::spending_key.eq(&self.spending_key)))
+        let num_updated = diesel::update(outputs::table.filter(outputs::id.eq(&self.id)))
             .set(updated_null)
             .execute(conn)?;
 
@@ -1083,7 +1150,7 @@ impl OutputSql {
             ));
         }
 
-        OutputSql::find(&self.spending_key, conn)
+        OutputSql::find_by_id(self.id, conn)
     }
 
     /// Update the changed fields of this record after encryption/decryption is performed
@@ -1179,10 +1246,20 @@ impl TryFrom<OutputSql> for DbUnblindedOutput {
             Some(c) => Commitment::from_vec(&c)?,
         };
 
+        let received_from = match o.received_from {
+            None => None,
+            Some(pk) => Some(PublicKey::from_vec(&pk).map_err(|_| {
+                error!(target: LOG_TARGET, "Could not create PublicKey from stored bytes");
+                OutputManagerStorageError::ConversionError
+            })?),
+        };
+
         Ok(Self {
             commitment,
             unblinded_output,
             hash,
+            received_from,
+            account: o.account,
         })
     }
 }
@@ -1219,6 +1296,8 @@ impl From<OutputSql> for NewOutputSql {
             metadata_signature_nonce: o.metadata_signature_nonce,
             metadata_signature_u_key: o.metadata_signature_u_key,
             metadata_signature_v_key: o.metadata_signature_v_key,
+            received_from: o.received_from,
+            account: o.account,
         }
     }
 }
@@ -1682,6 +1761,63 @@ impl Encryptable<Aes256Gcm> for KnownOneSidedPaymentScriptSql {
     }
 }
 
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[table_name = "accounts"]
+struct AccountSql {
+    name: String,
+    key_index: i64,
+}
+
+impl AccountSql {
+    pub fn commit(&self, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(accounts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(conn: &SqliteConnection) -> Result<Vec<AccountSql>, OutputManagerStorageError> {
+        Ok(accounts::table.order(accounts::name.asc()).load::<AccountSql>(conn)?)
+    }
+
+    pub fn find(name: &str, conn: &SqliteConnection) -> Result<AccountSql, OutputManagerStorageError> {
+        Ok(accounts::table
+            .filter(accounts::name.eq(name))
+            .first::<AccountSql>(conn)?)
+    }
+
+    pub fn set_index(&self, index: u64, conn: &SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        let num_updated = diesel::update(accounts::table.filter(accounts::name.eq(&self.name)))
+            .set(accounts::key_index.eq(index as i64))
+            .execute(conn)?;
+
+        if num_updated == 0 {
+            return Err(OutputManagerStorageError::UnexpectedResult(
+                "Database update error".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<AccountState> for AccountSql {
+    fn from(account: AccountState) -> Self {
+        Self {
+            name: account.name,
+            key_index: account.key_index as i64,
+        }
+    }
+}
+
+impl From<AccountSql> for AccountState {
+    fn from(account: AccountSql) -> Self {
+        Self {
+            name: account.name,
+            key_index: account.key_index as u64,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
@@ -23,7 +23,8 @@
 use crate::{
     output_manager_service::{
         error::{OutputManagerError, OutputManagerProtocolError},
-        handle::OutputManagerEvent,
+        handle::{OutputManagerEvent, PublicRewindKeys},
+        recovery::WatchOnlyUtxoRecoverer,
         resources::OutputManagerResources,
         storage::{database::OutputManagerBackend, models::DbUnblindedOutput},
     },
@@ -398,6 +399,79 @@ where TBackend: OutputManagerBackend + 'static
         Ok(self.id)
     }
 
+    /// Record the spends of watch-only outputs by the wallet holding the keys, one net outbound transaction per
+    /// spending block. Outputs the base node cannot place in a block are left unspent and checked again on the next
+    /// validation.
+    async fn record_watch_only_spends(
+        &mut self,
+        keys: PublicRewindKeys,
+        spent_outputs: Vec<DbUnblindedOutput>,
+        client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<(), OutputManagerProtocolError> {
+        let request = FetchMatchingUtxos {
+            output_hashes: spent_outputs.iter().map(|o| o.hash.clone()).collect(),
+        };
+        let blocks = match client.fetch_spending_blocks(request).await {
+            Ok(response) => response.blocks,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not fetch the blocks that spent {} watch-only outputs: {}",
+                    spent_outputs.len(),
+                    e
+                );
+                return Ok(());
+            },
+        };
+
+        let recoverer = WatchOnlyUtxoRecoverer::new(keys, self.resources.factories.clone(), self.resources.db.clone());
+        for block in blocks {
+            let inputs = spent_outputs
+                .iter()
+                .filter(|o| block.spent_output_hashes.contains(&o.hash))
+                .cloned()
+                .collect::<Vec<_>>();
+            if inputs.is_empty() {
+                continue;
+            }
+            let block_outputs = block
+                .outputs
+                .into_iter()
+                .map(TransactionOutput::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| {
+                    OutputManagerProtocolError::new(
+                        self.id,
+                        OutputManagerError::ConversionError("Could not convert protobuf TransactionOutput".to_string()),
+                    )
+                })?;
+
+            let (tx_id, value, replaced_imports) = recoverer
+                .record_spent_outputs(inputs, block_outputs)
+                .await
+                .map_err(|e| OutputManagerProtocolError::new(self.id, e))?;
+            info!(
+                target: LOG_TARGET,
+                "Watch-only outputs spent in block {} sent {} (TxId: {})", block.height, value, tx_id
+            );
+            if let Err(e) = self
+                .resources
+                .transaction_service
+                .import_spent_utxo(
+                    tx_id,
+                    value,
+                    "Spent by the wallet holding the keys".to_string(),
+                    replaced_imports,
+                )
+                .await
+            {
+                warn!(target: LOG_TARGET, "Problem recording spent watch-only outputs: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn send_query_batch(
         &mut self,
         batch: Vec<Vec<u8>>,
@@ -452,6 +526,15 @@ where TBackend: OutputManagerBackend + 'static
                     let _ = output_hashes.remove(&response_hash);
                 }
 
+                // A watch-only wallet only tracks outputs it found on chain and cannot spend them itself, so the
+                // remaining outputs were spent by the wallet that holds their keys rather than being invalid
+                if let Some(keys) = self.resources.watch_only_keys.clone() {
+                    let spent_outputs = output_hashes.drain().map(|(_, v)| v).collect::<Vec<_>>();
+                    if !spent_outputs.is_empty() {
+                        self.record_watch_only_spends(keys, spent_outputs, client).await?;
+                    }
+                }
+
                 // If there are any remaining Unspent Outputs we will move them to the invalid collection
                 for (_k, v) in output_hashes {
                     // Get the transaction these belonged to so we can display the kernel signature of the transaction
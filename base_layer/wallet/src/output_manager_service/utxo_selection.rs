@@ -0,0 +1,354 @@
+// Copyright 2021. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! UTXO selection algorithms used by the Output Manager Service. The functions in this module are pure: they are given
+//! the spendable outputs, already filtered for maturity, and decide which of them should fund a transaction.
+
+use crate::output_manager_service::storage::models::DbUnblindedOutput;
+use std::collections::HashMap;
+use tari_core::transactions::{fee::Fee, tari_amount::MicroTari, types::PublicKey};
+use tari_crypto::tari_utilities::ByteArray;
+
+/// The maximum number of branches the branch-and-bound search will explore before giving up
+const BRANCH_AND_BOUND_MAX_TRIES: usize = 100_000;
+
+/// The outputs chosen to fund a transaction
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UtxoSelection {
+    pub utxos: Vec<DbUnblindedOutput>,
+    pub total_value: MicroTari,
+    pub requires_change_output: bool,
+}
+
+impl UtxoSelection {
+    fn push(&mut self, utxo: DbUnblindedOutput) {
+        self.total_value += utxo.unblinded_output.value;
+        self.utxos.push(utxo);
+    }
+
+    /// The fee for spending the selected outputs to `output_count` outputs, excluding a change output
+    pub fn fee_without_change(&self, fee_per_gram: MicroTari, output_count: usize) -> MicroTari {
+        Fee::calculate(fee_per_gram, 1, self.utxos.len(), output_count)
+    }
+
+    /// The fee for spending the selected outputs to `output_count` outputs and a change output
+    pub fn fee_with_change(&self, fee_per_gram: MicroTari, output_count: usize) -> MicroTari {
+        Fee::calculate(fee_per_gram, 1, self.utxos.len(), output_count + 1)
+    }
+
+    /// Returns true if the selected outputs cover the amount and fee. Any value left over that is less than the cost
+    /// of a change output is paid as fee.
+    pub fn is_funded(&self, amount: MicroTari, fee_per_gram: MicroTari, output_count: usize) -> bool {
+        self.total_value >= amount + self.fee_without_change(fee_per_gram, output_count)
+    }
+}
+
+/// Selects outputs in the order given until the amount and fee are covered
+pub(crate) fn select_in_order<I>(
+    utxos: I,
+    amount: MicroTari,
+    fee_per_gram: MicroTari,
+    output_count: usize,
+) -> UtxoSelection
+where
+    I: IntoIterator<Item = DbUnblindedOutput>,
+{
+    let mut selection = UtxoSelection::default();
+    for utxo in utxos {
+        selection.push(utxo);
+        // The assumption here is that the only output will be the payment output and change if required
+        if selection.total_value == amount + selection.fee_without_change(fee_per_gram, output_count) {
+            break;
+        }
+        if selection.total_value >= amount + selection.fee_with_change(fee_per_gram, output_count) {
+            selection.requires_change_output = true;
+            break;
+        }
+    }
+    selection
+}
+
+/// Searches for a set of outputs that covers the amount and fee without needing a change output, i.e. any value left
+/// over is less than what a change output would cost and is paid as fee. Of the sets found, the one that pays the
+/// least excess is returned. Returns None if no such set exists or the search gives up.
+pub(crate) fn select_branch_and_bound(
+    utxos: &[DbUnblindedOutput],
+    amount: MicroTari,
+    fee_per_gram: MicroTari,
+    output_count: usize,
+) -> Option<UtxoSelection> {
+    let mut candidates = utxos.iter().collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.unblinded_output.value.cmp(&a.unblinded_output.value));
+    let values = candidates
+        .iter()
+        .map(|utxo| utxo.unblinded_output.value.as_u64())
+        .collect::<Vec<_>>();
+    let amount = amount.as_u64();
+    let lower_bound = |count: usize| amount + Fee::calculate(fee_per_gram, 1, count, output_count).as_u64();
+    let upper_bound = |count: usize| amount + Fee::calculate(fee_per_gram, 1, count, output_count + 1).as_u64();
+
+    // Depth first search over include/exclude decisions for each candidate, largest first
+    let mut included = vec![false; values.len()];
+    let mut index = 0;
+    let mut count = 0;
+    let mut current = 0u64;
+    let mut remaining = values.iter().sum::<u64>();
+    let mut best: Option<(Vec<bool>, u64)> = None;
+
+    for _ in 0..BRANCH_AND_BOUND_MAX_TRIES {
+        let mut backtrack = false;
+        if current + remaining < lower_bound(count) || current > upper_bound(count) {
+            backtrack = true;
+        } else if count > 0 && current >= lower_bound(count) {
+            let excess = current - lower_bound(count);
+            if best
+                .as_ref()
+                .map(|(_, best_excess)| excess < *best_excess)
+                .unwrap_or(true)
+            {
+                best = Some((included.clone(), excess));
+            }
+            if excess == 0 {
+                break;
+            }
+            backtrack = true;
+        } else if index == values.len() {
+            backtrack = true;
+        }
+
+        if backtrack {
+            // Undo decisions up to the most recently included candidate and exclude it instead
+            loop {
+                if index == 0 {
+                    return build_selection(&candidates, best);
+                }
+                index -= 1;
+                if included[index] {
+                    included[index] = false;
+                    current -= values[index];
+                    count -= 1;
+                    index += 1;
+                    break;
+                }
+                remaining += values[index];
+            }
+        } else {
+            remaining -= values[index];
+            current += values[index];
+            included[index] = true;
+            count += 1;
+            index += 1;
+        }
+    }
+
+    build_selection(&candidates, best)
+}
+
+fn build_selection(candidates: &[&DbUnblindedOutput], best: Option<(Vec<bool>, u64)>) -> Option<UtxoSelection> {
+    let (included, _) = best?;
+    let mut selection = UtxoSelection::default();
+    for (utxo, _) in candidates.iter().zip(included).filter(|(_, included)| *included) {
+        selection.push((*utxo).clone());
+    }
+    Some(selection)
+}
+
+/// Selects outputs smallest first to cover the amount and fee, then keeps adding the next smallest outputs until
+/// `max_inputs` are selected, consolidating them into the change output. Outputs that cost more in fees to spend than
+/// they are worth are never selected. The outputs must be sorted from smallest to largest.
+pub(crate) fn select_with_consolidation(
+    utxos: Vec<DbUnblindedOutput>,
+    amount: MicroTari,
+    fee_per_gram: MicroTari,
+    output_count: usize,
+    max_inputs: usize,
+) -> UtxoSelection {
+    let input_fee = Fee::calculate(fee_per_gram, 0, 1, 0);
+    let mut utxos = utxos.into_iter().filter(|utxo| utxo.unblinded_output.value > input_fee);
+    let mut selection = select_in_order(utxos.by_ref(), amount, fee_per_gram, output_count);
+    if !selection.is_funded(amount, fee_per_gram, output_count) {
+        return selection;
+    }
+
+    for utxo in utxos.take(max_inputs.saturating_sub(selection.utxos.len())) {
+        selection.push(utxo);
+    }
+    selection.requires_change_output =
+        selection.total_value >= amount + selection.fee_with_change(fee_per_gram, output_count);
+    selection
+}
+
+/// Selects outputs that were all received from the same counterparty so that spending them does not link
+/// counterparties together. Change outputs inherit the counterparty of the inputs that funded them (see
+/// [common_source]), so they stay in the group they came from. Outputs with no known counterparty (coinbases, imported
+/// outputs and change from mixed inputs) form their own group. The group that can fund the transaction with the fewest
+/// inputs is used. Returns None if no single group can fund the transaction.
+pub(crate) fn select_from_single_source(
+    utxos: Vec<DbUnblindedOutput>,
+    amount: MicroTari,
+    fee_per_gram: MicroTari,
+    output_count: usize,
+) -> Option<UtxoSelection> {
+    let mut groups = HashMap::<Option<Vec<u8>>, Vec<DbUnblindedOutput>>::new();
+    for utxo in utxos {
+        let source = utxo.received_from.as_ref().map(|pk| pk.to_vec());
+        groups.entry(source).or_insert_with(Vec::new).push(utxo);
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, mut group)| {
+            group.sort_by(|a, b| b.unblinded_output.value.cmp(&a.unblinded_output.value));
+            select_in_order(group, amount, fee_per_gram, output_count)
+        })
+        .filter(|selection| selection.is_funded(amount, fee_per_gram, output_count))
+        .min_by(|a, b| {
+            a.utxos
+                .len()
+                .cmp(&b.utxos.len())
+                .then_with(|| a.total_value.cmp(&b.total_value))
+        })
+}
+
+/// Returns the counterparty that all of the outputs were received from. Returns None if the outputs came from
+/// different counterparties or the counterparty of any of them is unknown.
+pub(crate) fn common_source(utxos: &[DbUnblindedOutput]) -> Option<PublicKey> {
+    let (first, rest) = utxos.split_first()?;
+    let source = first.received_from.as_ref()?;
+    if rest.iter().all(|utxo| utxo.received_from.as_ref() == Some(source)) {
+        Some(source.clone())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testnet_utils::make_input;
+    use rand::rngs::OsRng;
+    use tari_core::transactions::{
+        tari_amount::uT,
+        types::{CryptoFactories, PublicKey},
+    };
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    fn make_utxo(value: MicroTari, received_from: Option<PublicKey>) -> DbUnblindedOutput {
+        let factories = CryptoFactories::default();
+        let (_, uo) = make_input(value, &factories);
+        let mut utxo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+        utxo.received_from = received_from;
+        utxo
+    }
+
+    fn values(selection: &UtxoSelection) -> Vec<MicroTari> {
+        let mut values = selection
+            .utxos
+            .iter()
+            .map(|u| u.unblinded_output.value)
+            .collect::<Vec<_>>();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn branch_and_bound_finds_a_changeless_selection() {
+        let fee_per_gram = MicroTari::from(5);
+        let fee = Fee::calculate(fee_per_gram, 1, 2, 1);
+        let utxos = vec![
+            make_utxo(1_000 * uT, None),
+            make_utxo(3_000 * uT, None),
+            make_utxo(5_000 * uT, None),
+            make_utxo(9_000 * uT, None),
+        ];
+        let amount = 8_000 * uT - fee;
+
+        let selection = select_branch_and_bound(&utxos, amount, fee_per_gram, 1).unwrap();
+        assert_eq!(values(&selection), vec![3_000 * uT, 5_000 * uT]);
+        assert_eq!(selection.total_value, amount + fee);
+        assert!(!selection.requires_change_output);
+
+        // No combination of the outputs lands within the cost of change of this amount
+        let amount = 500 * uT;
+        assert!(select_branch_and_bound(&utxos[1..], amount, fee_per_gram, 1).is_none());
+    }
+
+    #[test]
+    fn consolidation_adds_small_outputs_to_the_change() {
+        let fee_per_gram = MicroTari::from(1);
+        let utxos = vec![
+            make_utxo(MicroTari::from(1), None),
+            make_utxo(2_000 * uT, None),
+            make_utxo(3_000 * uT, None),
+            make_utxo(4_000 * uT, None),
+            make_utxo(100_000 * uT, None),
+        ];
+
+        let selection = select_with_consolidation(utxos, 1_000 * uT, fee_per_gram, 1, 4);
+        // The dust output is worth less than it costs to spend and is skipped
+        assert_eq!(values(&selection), vec![
+            2_000 * uT,
+            3_000 * uT,
+            4_000 * uT,
+            100_000 * uT
+        ]);
+        assert!(selection.requires_change_output);
+    }
+
+    #[test]
+    fn single_source_selection_does_not_merge_counterparties() {
+        let fee_per_gram = MicroTari::from(5);
+        let (_, alice) = PublicKey::random_keypair(&mut OsRng);
+        let (_, bob) = PublicKey::random_keypair(&mut OsRng);
+        let utxos = vec![
+            make_utxo(4_000 * uT, Some(alice.clone())),
+            make_utxo(4_000 * uT, Some(alice)),
+            make_utxo(6_000 * uT, Some(bob.clone())),
+            make_utxo(3_000 * uT, None),
+        ];
+
+        let selection = select_from_single_source(utxos.clone(), 5_000 * uT, fee_per_gram, 1).unwrap();
+        assert_eq!(values(&selection), vec![6_000 * uT]);
+        assert_eq!(selection.utxos[0].received_from, Some(bob));
+
+        let selection = select_from_single_source(utxos.clone(), 7_000 * uT, fee_per_gram, 1).unwrap();
+        assert_eq!(values(&selection), vec![4_000 * uT, 4_000 * uT]);
+
+        // Only merging the outputs of different counterparties would fund this
+        assert!(select_from_single_source(utxos, 10_000 * uT, fee_per_gram, 1).is_none());
+    }
+
+    #[test]
+    fn common_source_requires_a_single_known_counterparty() {
+        let (_, alice) = PublicKey::random_keypair(&mut OsRng);
+        let (_, bob) = PublicKey::random_keypair(&mut OsRng);
+        let from_alice = make_utxo(1_000 * uT, Some(alice.clone()));
+        let from_bob = make_utxo(1_000 * uT, Some(bob));
+        let unknown = make_utxo(1_000 * uT, None);
+
+        assert_eq!(common_source(&[from_alice.clone(), from_alice.clone()]), Some(alice));
+        assert_eq!(common_source(&[from_alice.clone(), from_bob]), None);
+        assert_eq!(common_source(&[from_alice, unknown.clone()]), None);
+        assert_eq!(common_source(&[unknown]), None);
+        assert_eq!(common_source(&[]), None);
+    }
+}
@@ -1,3 +1,10 @@
+table! {
+    accounts (name) {
+        name -> Text,
+        key_index -> BigInt,
+    }
+}
+
 table! {
     client_key_values (key) {
         key -> Text,
@@ -24,6 +31,8 @@ table! {
         valid -> Integer,
         confirmations -> Nullable<BigInt>,
         mined_height -> Nullable<BigInt>,
+        replaced_by -> Nullable<BigInt>,
+        account -> Text,
     }
 }
 
@@ -46,6 +55,7 @@ table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        account -> Text,
     }
 }
 
@@ -81,6 +91,7 @@ table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        account -> Text,
     }
 }
 
@@ -102,6 +113,8 @@ table! {
         metadata_signature_nonce -> Binary,
         metadata_signature_u_key -> Binary,
         metadata_signature_v_key -> Binary,
+        received_from -> Nullable<Binary>,
+        account -> Text,
     }
 }
 
@@ -122,6 +135,7 @@ table! {
 }
 
 allow_tables_to_appear_in_same_query!(
+    accounts,
     client_key_values,
     completed_transactions,
     contacts,
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::error::WalletStorageError;
+use crate::{error::WalletStorageError, output_manager_service::handle::PublicRewindKeys};
 use aes_gcm::Aes256Gcm;
 use log::*;
 use std::{
@@ -58,6 +58,8 @@ pub enum DbKey {
     ClientKey(String),
     MasterSecretKey,
     MasterPublicKey,
+    WatchOnlyKeys,
+    ReceivingAccount,
 }
 
 pub enum DbValue {
@@ -69,6 +71,8 @@ pub enum DbValue {
     BaseNodeChainMetadata(ChainMetadata),
     MasterSecretKey(CommsSecretKey),
     MasterPublicKey(CommsPublicKey),
+    WatchOnlyKeys(PublicRewindKeys),
+    ReceivingAccount(String),
 }
 
 #[derive(Clone)]
@@ -79,6 +83,8 @@ pub enum DbKeyValuePair {
     MasterSecretKey(CommsSecretKey),
     CommsAddress(Multiaddr),
     CommsFeatures(PeerFeatures),
+    WatchOnlyKeys(PublicRewindKeys),
+    ReceivingAccount(String),
 }
 
 pub enum WriteOperation {
@@ -131,6 +137,59 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    /// Fetch the rewind public keys this wallet was created from, if it is a watch-only wallet.
+    pub async fn get_watch_only_keys(&self) -> Result<Option<PublicRewindKeys>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::WatchOnlyKeys) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WatchOnlyKeys(k))) => Ok(Some(k)),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchOnlyKeys, other),
+            Err(e) => log_error(DbKey::WatchOnlyKeys, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    /// Mark this wallet as watch-only by storing the rewind public keys it will scan the chain with.
+    pub async fn set_watch_only_keys(&self, keys: PublicRewindKeys) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyKeys(keys)))
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    /// Fetch the account that received transactions are credited to, if one other than the default has been chosen.
+    pub async fn get_receiving_account(&self) -> Result<Option<String>, WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        let c = tokio::task::spawn_blocking(move || match db_clone.fetch(&DbKey::ReceivingAccount) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::ReceivingAccount(a))) => Ok(Some(a)),
+            Ok(Some(other)) => unexpected_result(DbKey::ReceivingAccount, other),
+            Err(e) => log_error(DbKey::ReceivingAccount, e),
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(c)
+    }
+
+    pub async fn set_receiving_account(&self, account: String) -> Result<(), WalletStorageError> {
+        let db_clone = self.db.clone();
+
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::ReceivingAccount(account)))
+        })
+        .await
+        .map_err(|err| WalletStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
     pub async fn get_tor_id(&self) -> Result<Option<TorIdentity>, WalletStorageError> {
         let db_clone = self.db.clone();
 
@@ -317,6 +376,8 @@ impl Display for DbKey {
         match self {
             DbKey::MasterSecretKey => f.write_str(&"MasterSecretKey".to_string()),
             DbKey::MasterPublicKey => f.write_str(&"MasterPublicKey".to_string()),
+            DbKey::WatchOnlyKeys => f.write_str(&"WatchOnlyKeys".to_string()),
+            DbKey::ReceivingAccount => f.write_str(&"ReceivingAccount".to_string()),
             DbKey::CommsAddress => f.write_str(&"CommsAddress".to_string()),
             DbKey::CommsFeatures => f.write_str(&"Node features".to_string()),
             DbKey::TorId => f.write_str(&"TorId".to_string()),
@@ -331,6 +392,8 @@ impl Display for DbValue {
         match self {
             DbValue::MasterSecretKey(k) => f.write_str(&format!("MasterSecretKey: {:?}", k)),
             DbValue::MasterPublicKey(k) => f.write_str(&format!("MasterPublicKey: {:?}", k)),
+            DbValue::WatchOnlyKeys(k) => f.write_str(&format!("WatchOnlyKeys: {:?}", k)),
+            DbValue::ReceivingAccount(a) => f.write_str(&format!("ReceivingAccount: {}", a)),
             DbValue::ClientValue(v) => f.write_str(&format!("ClientValue: {:?}", v)),
             DbValue::ValueCleared => f.write_str(&"ValueCleared".to_string()),
             DbValue::CommsFeatures(_) => f.write_str(&"Node features".to_string()),
@@ -390,6 +453,15 @@ mod test {
         runtime.block_on(db.clear_master_secret_key()).unwrap();
         assert!(runtime.block_on(db.get_master_secret_key()).unwrap().is_none());
 
+        assert!(runtime.block_on(db.get_receiving_account()).unwrap().is_none());
+        runtime
+            .block_on(db.set_receiving_account("savings".to_string()))
+            .unwrap();
+        assert_eq!(
+            runtime.block_on(db.get_receiving_account()).unwrap(),
+            Some("savings".to_string())
+        );
+
         let client_key_values = vec![
             ("key1".to_string(), "value1".to_string()),
             ("key2".to_string(), "value2".to_string()),
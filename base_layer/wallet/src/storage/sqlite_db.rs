@@ -22,6 +22,7 @@
 
 use crate::{
     error::WalletStorageError,
+    output_manager_service::handle::PublicRewindKeys,
     schema::{client_key_values, wallet_settings},
     storage::{
         database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
@@ -213,6 +214,22 @@ impl WalletSqliteDatabase {
         }
     }
 
+    fn set_watch_only_keys(&self, keys: PublicRewindKeys, conn: &SqliteConnection) -> Result<(), WalletStorageError> {
+        let bytes = bincode::serialize(&keys).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+        WalletSettingSql::new(DbKey::WatchOnlyKeys.to_string(), bytes.to_hex()).set(&conn)?;
+        Ok(())
+    }
+
+    fn get_watch_only_keys(&self, conn: &SqliteConnection) -> Result<Option<PublicRewindKeys>, WalletStorageError> {
+        if let Some(key_str) = WalletSettingSql::get(DbKey::WatchOnlyKeys.to_string(), &conn)? {
+            let keys = bincode::deserialize(&from_hex(&key_str)?)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+            Ok(Some(keys))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn insert_key_value_pair(&self, kvp: DbKeyValuePair) -> Result<Option<DbValue>, WalletStorageError> {
         let conn = self.database_connection.acquire_lock();
         match kvp {
@@ -247,6 +264,12 @@ impl WalletSqliteDatabase {
             DbKeyValuePair::CommsFeatures(cf) => {
                 WalletSettingSql::new(DbKey::CommsFeatures.to_string(), cf.bits().to_string()).set(&conn)?;
             },
+            DbKeyValuePair::WatchOnlyKeys(keys) => {
+                self.set_watch_only_keys(keys, &(*conn))?;
+            },
+            DbKeyValuePair::ReceivingAccount(account) => {
+                WalletSettingSql::new(DbKey::ReceivingAccount.to_string(), account).set(&conn)?;
+            },
         }
         Ok(None)
     }
@@ -275,6 +298,12 @@ impl WalletSqliteDatabase {
             DbKey::TorId => {
                 let _ = WalletSettingSql::clear(DbKey::TorId.to_string(), &conn)?;
             },
+            DbKey::WatchOnlyKeys => {
+                return Err(WalletStorageError::OperationNotSupported);
+            },
+            DbKey::ReceivingAccount => {
+                let _ = WalletSettingSql::clear(DbKey::ReceivingAccount.to_string(), &conn)?;
+            },
         };
         Ok(None)
     }
@@ -304,6 +333,10 @@ impl WalletBackend for WalletSqliteDatabase {
             DbKey::TorId => self.get_tor_id(&conn)?,
             DbKey::CommsFeatures => self.get_comms_features(&conn)?.map(DbValue::CommsFeatures),
             DbKey::BaseNodeChainMetadata => self.get_chain_metadata(&conn)?.map(DbValue::BaseNodeChainMetadata),
+            DbKey::WatchOnlyKeys => self.get_watch_only_keys(&conn)?.map(DbValue::WatchOnlyKeys),
+            DbKey::ReceivingAccount => {
+                WalletSettingSql::get(DbKey::ReceivingAccount.to_string(), &conn)?.map(DbValue::ReceivingAccount)
+            },
         };
 
         Ok(result)
@@ -131,6 +131,7 @@ pub async fn create_wallet(
     ));
     let comms_config = CommsConfig {
         network: Network::Weatherwax,
+        custom_network_byte: None,
         transport_type: TransportType::Memory {
             listener_address: public_address,
         },
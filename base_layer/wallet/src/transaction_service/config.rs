@@ -22,6 +22,7 @@
 
 use log::*;
 use std::{fmt, time::Duration};
+use tari_core::transactions::tari_amount::MicroTari;
 
 const LOG_TARGET: &str = "wallet::transaction_service::config";
 
@@ -38,6 +39,10 @@ pub struct TransactionServiceConfig {
     pub num_confirmations_required: u64,
     pub max_tx_query_batch_size: usize,
     pub transaction_routing_mechanism: TransactionRoutingMechanism,
+    /// The amount by which the fee per gram of a fee bump must exceed the average fee per gram of the transaction it
+    /// replaces. This should not be less than the `min_replacement_fee_per_gram_increment` of the base node mempools,
+    /// otherwise they will reject the replacement.
+    pub min_fee_bump_per_gram_increment: MicroTari,
 }
 
 impl Default for TransactionServiceConfig {
@@ -54,6 +59,7 @@ impl Default for TransactionServiceConfig {
             num_confirmations_required: 3,
             max_tx_query_batch_size: 5000,
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
+            min_fee_bump_per_gram_increment: MicroTari::from(1),
         }
     }
 }
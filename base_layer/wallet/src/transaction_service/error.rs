@@ -42,6 +42,14 @@ pub enum TransactionServiceError {
     InvalidStateError,
     #[error("One-sided transaction error: `{0}`")]
     OneSidedTransactionError(String),
+    #[error("Fee bump error: `{0}`")]
+    FeeBumpError(String),
+    #[error("Offline signing error: `{0}`")]
+    OfflineSigningError(String),
+    #[error("This operation requires a spend key which a watch-only wallet does not have")]
+    WatchOnlyWallet,
+    #[error("Account error: `{0}`")]
+    AccountError(String),
     #[error("Transaction Protocol Error: `{0}`")]
     TransactionProtocolError(#[from] TransactionProtocolError),
     #[error("The message being processed is not recognized by the Transaction Manager")]
@@ -129,6 +137,10 @@ pub enum TransactionServiceError {
     MempoolRejectionOrphan,
     #[error("Transaction detected as rejected by mempool due to containing double spend")]
     MempoolRejectionDoubleSpend,
+    #[error(
+        "Transaction detected as rejected by mempool due to not paying enough to replace a conflicting transaction"
+    )]
+    MempoolRejectionFeeTooLow,
     #[error("Transaction detected as rejected by mempool due to invalid transaction")]
     MempoolRejectionInvalidTransaction,
     #[error("Transaction is malformed")]
@@ -21,7 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::TxId,
+    output_manager_service::{
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+        service::UTXOSelectionStrategy,
+        TxId,
+        DEFAULT_ACCOUNT,
+    },
     transaction_service::{
         error::TransactionServiceError,
         storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
@@ -46,16 +51,38 @@ pub enum TransactionServiceRequest {
     GetPendingInboundTransactions,
     GetPendingOutboundTransactions,
     GetCompletedTransactions,
+    GetAccountCompletedTransactions(String),
     GetCancelledPendingInboundTransactions,
     GetCancelledPendingOutboundTransactions,
     GetCancelledCompletedTransactions,
     GetCompletedTransaction(TxId),
     GetAnyTransaction(TxId),
     SetBaseNodePublicKey(CommsPublicKey),
-    SendTransaction(CommsPublicKey, MicroTari, MicroTari, String),
-    SendOneSidedTransaction(CommsPublicKey, MicroTari, MicroTari, String),
+    SendTransaction(
+        CommsPublicKey,
+        MicroTari,
+        MicroTari,
+        String,
+        Option<UTXOSelectionStrategy>,
+        String,
+    ),
+    SendOneSidedTransaction(
+        CommsPublicKey,
+        MicroTari,
+        MicroTari,
+        String,
+        Option<UTXOSelectionStrategy>,
+        String,
+    ),
+    SetReceivingAccount(String),
+    GetReceivingAccount,
     CancelTransaction(TxId),
-    ImportUtxo(MicroTari, CommsPublicKey, String, Option<u64>),
+    BumpFee(TxId, MicroTari),
+    PrepareUnsignedTransaction(CommsPublicKey, MicroTari, MicroTari, String, String),
+    SignTransaction(Box<UnsignedTransaction>),
+    ImportSignedTransaction(Box<SignedTransaction>),
+    ImportUtxo(MicroTari, CommsPublicKey, String, Option<u64>, Option<TxId>),
+    ImportSpentUtxo(TxId, MicroTari, String, Vec<TxId>),
     SubmitCoinSplitTransaction(TxId, Transaction, MicroTari, MicroTari, String),
     SetLowPowerMode,
     SetNormalPowerMode,
@@ -80,29 +107,62 @@ pub enum TransactionServiceRequest {
     BroadcastTransaction(TxId),
 }
 
+impl TransactionServiceRequest {
+    /// Returns true if servicing this request needs the wallet's spend keys, i.e. it cannot be done by a watch-only
+    /// wallet.
+    pub fn requires_spend_key(&self) -> bool {
+        use TransactionServiceRequest::*;
+        matches!(
+            self,
+            SendTransaction(..) |
+                SendOneSidedTransaction(..) |
+                BumpFee(..) |
+                SignTransaction(_) |
+                GenerateCoinbaseTransaction(..)
+        )
+    }
+}
+
 impl fmt::Display for TransactionServiceRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::GetPendingInboundTransactions => f.write_str("GetPendingInboundTransactions"),
             Self::GetPendingOutboundTransactions => f.write_str("GetPendingOutboundTransactions"),
             Self::GetCompletedTransactions => f.write_str("GetCompletedTransactions"),
+            Self::GetAccountCompletedTransactions(account) => {
+                f.write_str(&format!("GetAccountCompletedTransactions ({})", account))
+            },
             Self::GetCancelledPendingInboundTransactions => f.write_str("GetCancelledPendingInboundTransactions"),
             Self::GetCancelledPendingOutboundTransactions => f.write_str("GetCancelledPendingOutboundTransactions"),
             Self::GetCancelledCompletedTransactions => f.write_str("GetCancelledCompletedTransactions"),
             Self::GetCompletedTransaction(t) => f.write_str(&format!("GetCompletedTransaction({})", t)),
             Self::SetBaseNodePublicKey(k) => f.write_str(&format!("SetBaseNodePublicKey ({})", k)),
-            Self::SendTransaction(k, v, _, msg) => f.write_str(&format!("SendTransaction (to {}, {}, {})", k, v, msg)),
-            Self::SendOneSidedTransaction(k, v, _, msg) => {
-                f.write_str(&format!("SendOneSidedTransaction (to {}, {}, {})", k, v, msg))
+            Self::SendTransaction(k, v, _, msg, _, account) => {
+                f.write_str(&format!("SendTransaction (to {}, {}, {}, from {})", k, v, msg, account))
             },
+            Self::SendOneSidedTransaction(k, v, _, msg, _, account) => f.write_str(&format!(
+                "SendOneSidedTransaction (to {}, {}, {}, from {})",
+                k, v, msg, account
+            )),
+            Self::SetReceivingAccount(account) => f.write_str(&format!("SetReceivingAccount ({})", account)),
+            Self::GetReceivingAccount => f.write_str("GetReceivingAccount"),
             Self::CancelTransaction(t) => f.write_str(&format!("CancelTransaction ({})", t)),
-            Self::ImportUtxo(v, k, msg, maturity) => f.write_str(&format!(
+            Self::BumpFee(t, fee_per_gram) => f.write_str(&format!("BumpFee ({}, {})", t, fee_per_gram)),
+            Self::PrepareUnsignedTransaction(k, v, _, msg, _) => {
+                f.write_str(&format!("PrepareUnsignedTransaction (to {}, {}, {})", k, v, msg))
+            },
+            Self::SignTransaction(t) => f.write_str(&format!("SignTransaction ({})", t.tx_id)),
+            Self::ImportSignedTransaction(t) => f.write_str(&format!("ImportSignedTransaction ({})", t.tx_id)),
+            Self::ImportUtxo(v, k, msg, maturity, _) => f.write_str(&format!(
                 "ImportUtxo (from {}, {}, {} with maturity: {})",
                 k,
                 v,
                 msg,
                 maturity.unwrap_or(0)
             )),
+            Self::ImportSpentUtxo(tx_id, v, msg, _) => {
+                f.write_str(&format!("ImportSpentUtxo ({}, {}, {})", tx_id, v, msg))
+            },
             Self::SubmitCoinSplitTransaction(tx_id, _, _, _, _) => {
                 f.write_str(&format!("SubmitTransaction ({})", tx_id))
             },
@@ -145,6 +205,8 @@ impl fmt::Display for TransactionServiceRequest {
 #[derive(Debug)]
 pub enum TransactionServiceResponse {
     TransactionSent(TxId),
+    UnsignedTransaction(Box<UnsignedTransaction>),
+    SignedTransaction(Box<SignedTransaction>),
     TransactionCancelled,
     PendingInboundTransactions(HashMap<u64, InboundTransaction>),
     PendingOutboundTransactions(HashMap<u64, OutboundTransaction>),
@@ -164,6 +226,8 @@ pub enum TransactionServiceResponse {
     NumConfirmationsSet,
     ValidationStarted(u64),
     CompletedTransactionValidityChanged,
+    ReceivingAccountSet,
+    ReceivingAccount(String),
     #[cfg(feature = "test_harness")]
     CompletedPendingTransaction,
     #[cfg(feature = "test_harness")]
@@ -188,6 +252,8 @@ pub enum TransactionEvent {
     TransactionCompletedImmediately(TxId),
     TransactionStoreForwardSendResult(TxId, bool),
     TransactionCancelled(TxId),
+    /// The first transaction was replaced by the second, fee bumped, transaction
+    TransactionFeeBumped(TxId, TxId),
     TransactionBroadcast(TxId),
     TransactionImported(TxId),
     TransactionMined(TxId),
@@ -233,6 +299,41 @@ impl TransactionServiceHandle {
         amount: MicroTari,
         fee_per_gram: MicroTari,
         message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.send_transaction_with_utxo_selection(dest_pubkey, amount, fee_per_gram, message, None)
+            .await
+    }
+
+    /// Sends a transaction, funding it with UTXOs chosen by the given selection strategy. If no strategy is given the
+    /// Output Manager chooses one.
+    pub async fn send_transaction_with_utxo_selection(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.send_transaction_from_account(
+            dest_pubkey,
+            amount,
+            fee_per_gram,
+            message,
+            utxo_selection,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Sends a transaction funded only by the outputs of the named account
+    pub async fn send_transaction_from_account(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+        account: String,
     ) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -241,6 +342,8 @@ impl TransactionServiceHandle {
                 amount,
                 fee_per_gram,
                 message,
+                utxo_selection,
+                account,
             ))
             .await??
         {
@@ -255,6 +358,41 @@ impl TransactionServiceHandle {
         amount: MicroTari,
         fee_per_gram: MicroTari,
         message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.send_one_sided_transaction_with_utxo_selection(dest_pubkey, amount, fee_per_gram, message, None)
+            .await
+    }
+
+    /// Sends a one-sided transaction, funding it with UTXOs chosen by the given selection strategy. If no strategy is
+    /// given the Output Manager chooses one.
+    pub async fn send_one_sided_transaction_with_utxo_selection(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.send_one_sided_transaction_from_account(
+            dest_pubkey,
+            amount,
+            fee_per_gram,
+            message,
+            utxo_selection,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Sends a one-sided transaction funded only by the outputs of the named account
+    pub async fn send_one_sided_transaction_from_account(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+        account: String,
     ) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -263,6 +401,8 @@ impl TransactionServiceHandle {
                 amount,
                 fee_per_gram,
                 message,
+                utxo_selection,
+                account,
             ))
             .await??
         {
@@ -271,6 +411,118 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Set the account that transactions received from other wallets are paid into. This applies to transactions
+    /// negotiated after the call and resets to the default account when the wallet restarts.
+    pub async fn set_receiving_account(&mut self, account: String) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SetReceivingAccount(account))
+            .await??
+        {
+            TransactionServiceResponse::ReceivingAccountSet => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_receiving_account(&mut self) -> Result<String, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetReceivingAccount)
+            .await??
+        {
+            TransactionServiceResponse::ReceivingAccount(account) => Ok(account),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Replace a stuck outbound transaction with a transaction that spends the same inputs at a higher fee per gram.
+    /// Only one-sided and pay-to-self transactions can be replaced as this wallet must control all the parties. The
+    /// TxId of the replacement transaction is returned.
+    pub async fn bump_fee(&mut self, tx_id: TxId, fee_per_gram: MicroTari) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::BumpFee(tx_id, fee_per_gram))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Prepare a one-sided transaction to be signed by an offline wallet that holds the master key of this wallet.
+    pub async fn prepare_unsigned_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+    ) -> Result<UnsignedTransaction, TransactionServiceError> {
+        self.prepare_unsigned_transaction_from_account(
+            dest_pubkey,
+            amount,
+            fee_per_gram,
+            message,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Prepare a transaction for offline signing that is funded by the named account. Only the default account is
+    /// supported, as the signing wallet derives the input keys from its default key chain.
+    pub async fn prepare_unsigned_transaction_from_account(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        account: String,
+    ) -> Result<UnsignedTransaction, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::PrepareUnsignedTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                account,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::UnsignedTransaction(unsigned) => Ok(*unsigned),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sign a transaction that was prepared by an online wallet using the keys of this wallet.
+    pub async fn sign_transaction(
+        &mut self,
+        unsigned: UnsignedTransaction,
+    ) -> Result<SignedTransaction, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SignTransaction(Box::new(unsigned)))
+            .await??
+        {
+            TransactionServiceResponse::SignedTransaction(signed) => Ok(*signed),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Import a transaction that was signed offline and broadcast it. Returns the TxId of the transaction.
+    pub async fn import_signed_transaction(
+        &mut self,
+        signed: SignedTransaction,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportSignedTransaction(Box::new(signed)))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
@@ -347,6 +599,21 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// The completed transactions of the named account
+    pub async fn get_account_completed_transactions(
+        &mut self,
+        account: String,
+    ) -> Result<HashMap<u64, CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetAccountCompletedTransactions(account))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_cancelled_completed_transactions(
         &mut self,
     ) -> Result<HashMap<u64, CompletedTransaction>, TransactionServiceError> {
@@ -416,6 +683,57 @@ impl TransactionServiceHandle {
                 source_public_key,
                 message,
                 maturity,
+                None,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::UtxoImported(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Import a UTXO under the given TxId, so that the import can be found again from the output alone
+    pub async fn import_utxo_with_tx_id(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        source_public_key: CommsPublicKey,
+        message: String,
+        maturity: Option<u64>,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportUtxo(
+                amount,
+                source_public_key,
+                message,
+                maturity,
+                Some(tx_id),
+            ))
+            .await??
+        {
+            TransactionServiceResponse::UtxoImported(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Record an outbound transaction for outputs that were spent by another wallet, e.g. a spend of a watch-only
+    /// wallet's outputs by the wallet that holds its keys. `amount` is the value that left the wallet; the imports of
+    /// the change outputs it was netted against, given by `replaced_imports`, are cancelled.
+    pub async fn import_spent_utxo(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroTari,
+        message: String,
+        replaced_imports: Vec<TxId>,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportSpentUtxo(
+                tx_id,
+                amount,
+                message,
+                replaced_imports,
             ))
             .await??
         {
@@ -72,6 +72,7 @@ where T: TransactionBackend
     backend: Option<T>,
     node_identity: Arc<NodeIdentity>,
     factories: CryptoFactories,
+    watch_only: bool,
 }
 
 impl<T> TransactionServiceInitializer<T>
@@ -90,9 +91,16 @@ where T: TransactionBackend
             backend: Some(backend),
             node_identity,
             factories,
+            watch_only: false,
         }
     }
 
+    /// Set up the service for a watch-only wallet, which cannot send or sign transactions
+    pub fn with_watch_only(mut self, watch_only: bool) -> Self {
+        self.watch_only = watch_only;
+        self
+    }
+
     /// Get a stream of inbound Text messages
     fn transaction_stream(&self) -> impl Stream<Item = DomainMessage<proto::TransactionSenderMessage>> {
         trace!(
@@ -187,6 +195,7 @@ where T: TransactionBackend + 'static
         let node_identity = self.node_identity.clone();
         let factories = self.factories.clone();
         let config = self.config.clone();
+        let watch_only = self.watch_only;
 
         context.spawn_when_ready(move |handles| async move {
             let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
@@ -210,6 +219,7 @@ where T: TransactionBackend + 'static
                 factories,
                 handles.get_shutdown_signal(),
             )
+            .with_watch_only(watch_only)
             .start()
             .await;
 
@@ -276,6 +276,10 @@ where TBackend: TransactionBackend + 'static
                             TxBroadcastMode::TransactionQuery => {
                                 if result? {
                                     // We are done!
+                                    self.claim_replaced_inputs()
+                                        .await
+                                        .map_err(|e| TransactionServiceProtocolError::new(self.tx_id, e))?;
+
                                     self.resources
                                         .output_manager_service
                                         .confirm_transaction(
@@ -292,6 +296,8 @@ where TBackend: TransactionBackend + 'static
                                         .await
                                         .map_err(|e| TransactionServiceProtocolError::new(self.tx_id, TransactionServiceError::from(e)))?;
 
+                                    self.cancel_other_versions().await;
+
                                    let _ = self
                                         .resources
                                         .event_publisher
@@ -343,7 +349,8 @@ where TBackend: TransactionBackend + 'static
     /// Attempt to submit the transaction to the base node via RPC.
     /// # Returns:
     /// `Ok(true)` => Transaction was successfully submitted to UnconfirmedPool
-    /// `Ok(false)` => There was a problem with the RPC call and this should be retried
+    /// `Ok(false)` => There was a problem with the RPC call, or the transaction pays less than the current minimum fee
+    /// of the mempool, and this should be retried
     /// `Err(_)` => The transaction was rejected by the base node and the protocol should end.
     async fn submit_transaction(
         &mut self,
@@ -375,6 +382,18 @@ where TBackend: TransactionBackend + 'static
             return Ok(false);
         }
 
+        if response.rejection_reason == TxSubmissionRejectionReason::BelowMinimumFee {
+            // The minimum fee of the mempool rises while it is full and decays again, so the transaction is kept and
+            // resubmitted. It can also be replaced with a higher fee using a fee bump in the meantime.
+            info!(
+                target: LOG_TARGET,
+                "Transaction (TxId: {}) pays less than the current minimum fee of the Base Node mempool, submission \
+                 will be retried.",
+                self.tx_id
+            );
+            return Ok(false);
+        }
+
         if !response.accepted && response.rejection_reason != TxSubmissionRejectionReason::AlreadyMined {
             error!(
                 target: LOG_TARGET,
@@ -401,6 +420,9 @@ where TBackend: TransactionBackend + 'static
                     TransactionServiceError::MempoolRejectionInvalidTransaction
                 },
                 TxSubmissionRejectionReason::DoubleSpend => TransactionServiceError::MempoolRejectionDoubleSpend,
+                // A fee bump that does not pay enough to replace the previous version is cancelled, which hands the
+                // inputs back to the previous version
+                TxSubmissionRejectionReason::FeeTooLow => TransactionServiceError::MempoolRejectionFeeTooLow,
                 TxSubmissionRejectionReason::Orphan => TransactionServiceError::MempoolRejectionOrphan,
                 TxSubmissionRejectionReason::TimeLocked => TransactionServiceError::MempoolRejectionTimeLocked,
                 _ => TransactionServiceError::UnexpectedBaseNodeResponse,
@@ -606,8 +628,117 @@ where TBackend: TransactionBackend + 'static
         }
     }
 
+    /// Fetch every version of this transaction, oldest first, if it is part of a chain of fee bump replacements. A
+    /// transaction that has never been replaced results in a chain containing just that transaction.
+    async fn fetch_replacement_chain(&self) -> Result<Vec<CompletedTransaction>, TransactionServiceError> {
+        let db = &self.resources.db;
+        let current = db.get_completed_transaction_cancelled_or_not(self.tx_id).await?;
+
+        let mut chain = Vec::new();
+        let mut previous = db.get_replaced_transaction(self.tx_id).await?;
+        while let Some(tx) = previous {
+            previous = db.get_replaced_transaction(tx.tx_id).await?;
+            chain.insert(0, tx);
+        }
+
+        let mut next = current.replaced_by;
+        chain.push(current);
+        while let Some(tx_id) = next {
+            let tx = db.get_completed_transaction_cancelled_or_not(tx_id).await?;
+            next = tx.replaced_by;
+            chain.push(tx);
+        }
+
+        Ok(chain)
+    }
+
+    /// The inputs shared by a chain of fee bump replacements are encumbered against the latest version that has not
+    /// been cancelled. When this version is the one that got mined, the inputs are moved over to it before it is
+    /// confirmed.
+    async fn claim_replaced_inputs(&mut self) -> Result<(), TransactionServiceError> {
+        let chain = self.fetch_replacement_chain().await?;
+        if let Some(holder) = chain.iter().rev().find(|tx| !tx.cancelled) {
+            if holder.tx_id != self.tx_id {
+                info!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) was mined instead of its replacement (TxId: {})", self.tx_id, holder.tx_id
+                );
+                self.resources
+                    .output_manager_service
+                    .reassign_pending_inputs(holder.tx_id, self.tx_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Once a version of a fee bumped transaction is mined every other version can never be mined, so they are
+    /// cancelled.
+    async fn cancel_other_versions(&mut self) {
+        let chain = match self.fetch_replacement_chain().await {
+            Ok(chain) => chain,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to fetch the replacements of TxId: {} with error {:?}", self.tx_id, e
+                );
+                return;
+            },
+        };
+        for tx in chain.iter().filter(|tx| tx.tx_id != self.tx_id && !tx.cancelled) {
+            if let Err(e) = self.resources.output_manager_service.cancel_transaction(tx.tx_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to Cancel outputs for replaced TxId: {} with error {:?}", tx.tx_id, e
+                );
+            }
+            if let Err(e) = self.resources.db.cancel_completed_transaction(tx.tx_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to Cancel replaced TxId: {} with error {:?}", tx.tx_id, e
+                );
+                continue;
+            }
+            let _ = self
+                .resources
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionCancelled(tx.tx_id)))
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+        }
+    }
+
+    /// If this transaction holds the inputs of a chain of fee bump replacements, hand them back to the latest earlier
+    /// version that is still live so that cancelling this version does not release inputs that may still be spent.
+    async fn release_replaced_inputs(&mut self) -> Result<(), TransactionServiceError> {
+        let chain = self.fetch_replacement_chain().await?;
+        let mut live = chain.iter().rev().filter(|tx| !tx.cancelled);
+        if let (Some(holder), Some(previous)) = (live.next(), live.next()) {
+            if holder.tx_id == self.tx_id {
+                self.resources
+                    .output_manager_service
+                    .reassign_pending_inputs(self.tx_id, previous.tx_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
     async fn cancel_transaction(&mut self) {
-        if let Err(e) = self
+        if let Err(e) = self.release_replaced_inputs().await {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to hand back the inputs of replaced TxId: {}, its outputs will not be cancelled: {:?}",
+                self.tx_id,
+                e
+            );
+        } else if let Err(e) = self
             .resources
             .output_manager_service
             .cancel_transaction(self.tx_id)
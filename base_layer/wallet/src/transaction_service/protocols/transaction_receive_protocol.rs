@@ -65,6 +65,7 @@ where TBackend: TransactionBackend + 'static
     id: u64,
     source_pubkey: CommsPublicKey,
     sender_message: TransactionSenderMessage,
+    account: String,
     stage: TransactionReceiveProtocolStage,
     resources: TransactionServiceResources<TBackend>,
     transaction_finalize_receiver: Option<mpsc::Receiver<(CommsPublicKey, TxId, Transaction)>>,
@@ -78,6 +79,7 @@ where TBackend: TransactionBackend + 'static
         id: u64,
         source_pubkey: CommsPublicKey,
         sender_message: TransactionSenderMessage,
+        account: String,
         stage: TransactionReceiveProtocolStage,
         resources: TransactionServiceResources<TBackend>,
         transaction_finalize_receiver: mpsc::Receiver<(CommsPublicKey, TxId, Transaction)>,
@@ -87,6 +89,7 @@ where TBackend: TransactionBackend + 'static
             id,
             source_pubkey,
             sender_message,
+            account,
             stage,
             resources,
             transaction_finalize_receiver: Some(transaction_finalize_receiver),
@@ -141,11 +144,15 @@ where TBackend: TransactionBackend + 'static
             let rtp = self
                 .resources
                 .output_manager_service
-                .get_recipient_transaction(self.sender_message.clone())
+                .get_recipient_transaction_for_account(
+                    self.sender_message.clone(),
+                    self.source_pubkey.clone(),
+                    self.account.clone(),
+                )
                 .await
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
-            let inbound_transaction = InboundTransaction::new(
+            let mut inbound_transaction = InboundTransaction::new(
                 data.tx_id,
                 self.source_pubkey.clone(),
                 amount,
@@ -154,6 +161,7 @@ where TBackend: TransactionBackend + 'static
                 data.message.clone(),
                 Utc::now().naive_utc(),
             );
+            inbound_transaction.account = self.account.clone();
 
             self.resources
                 .db
@@ -404,7 +412,7 @@ where TBackend: TransactionBackend + 'static
                 continue;
             }
 
-            let completed_transaction = CompletedTransaction::new(
+            let mut completed_transaction = CompletedTransaction::new(
                 self.id,
                 self.source_pubkey.clone(),
                 self.resources.node_identity.public_key().clone(),
@@ -417,6 +425,7 @@ where TBackend: TransactionBackend + 'static
                 TransactionDirection::Inbound,
                 None,
             );
+            completed_transaction.account = inbound_tx.account.clone();
 
             finalized_transaction
                 .validate_internal_consistency(&Default::default(), None)
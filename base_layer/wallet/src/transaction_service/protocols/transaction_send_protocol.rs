@@ -72,6 +72,7 @@ where TBackend: TransactionBackend + 'static
     dest_pubkey: CommsPublicKey,
     amount: MicroTari,
     message: String,
+    account: String,
     sender_protocol: SenderTransactionProtocol,
     stage: TransactionSendProtocolStage,
     resources: TransactionServiceResources<TBackend>,
@@ -91,6 +92,7 @@ where TBackend: TransactionBackend + 'static
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         message: String,
+        account: String,
         sender_protocol: SenderTransactionProtocol,
         stage: TransactionSendProtocolStage,
     ) -> Self {
@@ -102,6 +104,7 @@ where TBackend: TransactionBackend + 'static
             dest_pubkey,
             amount,
             message,
+            account,
             sender_protocol,
             stage,
         }
@@ -165,7 +168,7 @@ where TBackend: TransactionBackend + 'static
                 .sender_protocol
                 .get_fee_amount()
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
-            let outbound_tx = OutboundTransaction::new(
+            let mut outbound_tx = OutboundTransaction::new(
                 tx_id,
                 self.dest_pubkey.clone(),
                 self.amount,
@@ -176,6 +179,7 @@ where TBackend: TransactionBackend + 'static
                 Utc::now().naive_utc(),
                 direct_send_result,
             );
+            outbound_tx.account = self.account.clone();
             info!(
                 target: LOG_TARGET,
                 "Pending Outbound Transaction TxId: {:?} added. Waiting for Reply or Cancellation", self.id,
@@ -419,7 +423,7 @@ where TBackend: TransactionBackend + 'static
             .get_transaction()
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
-        let completed_transaction = CompletedTransaction::new(
+        let mut completed_transaction = CompletedTransaction::new(
             tx_id,
             self.resources.node_identity.public_key().clone(),
             outbound_tx.destination_public_key.clone(),
@@ -432,6 +436,7 @@ where TBackend: TransactionBackend + 'static
             TransactionDirection::Outbound,
             None,
         );
+        completed_transaction.account = outbound_tx.account.clone();
 
         self.resources
             .db
@@ -21,7 +21,14 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::{
-    output_manager_service::{handle::OutputManagerHandle, TxId},
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::OutputManagerHandle,
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+        service::UTXOSelectionStrategy,
+        TxId,
+        DEFAULT_ACCOUNT,
+    },
     transaction_service::{
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError},
@@ -71,6 +78,7 @@ use tari_core::{
     crypto::keys::SecretKey,
     proto::base_node as base_node_proto,
     transactions::{
+        fee::Fee,
         tari_amount::MicroTari,
         transaction::{KernelFeatures, OutputFeatures, Transaction},
         transaction_protocol::{
@@ -81,6 +89,7 @@ use tari_core::{
         },
         types::{CryptoFactories, PrivateKey},
         ReceiverTransactionProtocol,
+        SenderTransactionProtocol,
     },
 };
 use tari_crypto::{keys::DiffieHellmanSharedSecret, script, tari_utilities::ByteArray};
@@ -138,6 +147,8 @@ pub struct TransactionService<
     timeout_update_publisher: broadcast::Sender<Duration>,
     base_node_update_publisher: broadcast::Sender<CommsPublicKey>,
     power_mode: PowerMode,
+    receiving_account: String,
+    watch_only: bool,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -212,9 +223,18 @@ where
             timeout_update_publisher,
             base_node_update_publisher,
             power_mode: PowerMode::Normal,
+            receiving_account: DEFAULT_ACCOUNT.to_string(),
+            watch_only: false,
         }
     }
 
+    /// Run the service for a watch-only wallet. The service will refuse any request that needs a spend key before a
+    /// transaction is started.
+    pub fn with_watch_only(mut self, watch_only: bool) -> Self {
+        self.watch_only = watch_only;
+        self
+    }
+
     #[warn(unreachable_code)]
     pub async fn start(mut self) -> Result<(), TransactionServiceError> {
         let request_stream = self
@@ -459,28 +479,71 @@ where
         >,
     ) -> Result<TransactionServiceResponse, TransactionServiceError> {
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        if self.watch_only && request.requires_spend_key() {
+            return Err(TransactionServiceError::WatchOnlyWallet);
+        }
         match request {
-            TransactionServiceRequest::SendTransaction(dest_pubkey, amount, fee_per_gram, message) => self
+            TransactionServiceRequest::SendTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                utxo_selection,
+                account,
+            ) => self
                 .send_transaction(
                     dest_pubkey,
                     amount,
                     fee_per_gram,
                     message,
+                    utxo_selection,
+                    account,
                     send_transaction_join_handles,
                     transaction_broadcast_join_handles,
                 )
                 .await
                 .map(TransactionServiceResponse::TransactionSent),
-            TransactionServiceRequest::SendOneSidedTransaction(dest_pubkey, amount, fee_per_gram, message) => self
+            TransactionServiceRequest::SendOneSidedTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                utxo_selection,
+                account,
+            ) => self
                 .send_one_sided_transaction(
                     dest_pubkey,
                     amount,
                     fee_per_gram,
                     message,
+                    utxo_selection,
+                    account,
                     transaction_broadcast_join_handles,
                 )
                 .await
                 .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::BumpFee(tx_id, fee_per_gram) => self
+                .bump_fee(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::PrepareUnsignedTransaction(
+                dest_pubkey,
+                amount,
+                fee_per_gram,
+                message,
+                account,
+            ) => self
+                .prepare_unsigned_transaction(dest_pubkey, amount, fee_per_gram, message, account)
+                .await
+                .map(|unsigned| TransactionServiceResponse::UnsignedTransaction(Box::new(unsigned))),
+            TransactionServiceRequest::SignTransaction(unsigned) => self
+                .sign_transaction(*unsigned)
+                .await
+                .map(|signed| TransactionServiceResponse::SignedTransaction(Box::new(signed))),
+            TransactionServiceRequest::ImportSignedTransaction(signed) => self
+                .import_signed_transaction(*signed, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::CancelTransaction(tx_id) => self
                 .cancel_pending_transaction(tx_id)
                 .await
@@ -499,6 +562,20 @@ where
             TransactionServiceRequest::GetCompletedTransactions => Ok(
                 TransactionServiceResponse::CompletedTransactions(self.db.get_completed_transactions().await?),
             ),
+            TransactionServiceRequest::GetAccountCompletedTransactions(account) => {
+                let mut completed_transactions = self.db.get_completed_transactions().await?;
+                completed_transactions.retain(|_, tx| tx.account == account);
+                Ok(TransactionServiceResponse::CompletedTransactions(
+                    completed_transactions,
+                ))
+            },
+            TransactionServiceRequest::SetReceivingAccount(account) => self
+                .set_receiving_account(account)
+                .await
+                .map(|_| TransactionServiceResponse::ReceivingAccountSet),
+            TransactionServiceRequest::GetReceivingAccount => Ok(TransactionServiceResponse::ReceivingAccount(
+                self.receiving_account.clone(),
+            )),
             TransactionServiceRequest::GetCancelledPendingInboundTransactions => {
                 Ok(TransactionServiceResponse::PendingInboundTransactions(
                     self.db.get_cancelled_pending_inbound_transactions().await?,
@@ -526,8 +603,12 @@ where
                 self.set_base_node_public_key(public_key).await;
                 Ok(TransactionServiceResponse::BaseNodePublicKeySet)
             },
-            TransactionServiceRequest::ImportUtxo(value, source_public_key, message, maturity) => self
-                .add_utxo_import_transaction(value, source_public_key, message, maturity)
+            TransactionServiceRequest::ImportUtxo(value, source_public_key, message, maturity, tx_id) => self
+                .add_utxo_import_transaction(value, source_public_key, message, maturity, tx_id)
+                .await
+                .map(TransactionServiceResponse::UtxoImported),
+            TransactionServiceRequest::ImportSpentUtxo(tx_id, value, message, replaced_imports) => self
+                .add_spent_utxo_import_transaction(tx_id, value, message, replaced_imports)
                 .await
                 .map(TransactionServiceResponse::UtxoImported),
             TransactionServiceRequest::SubmitCoinSplitTransaction(tx_id, tx, fee, amount, message) => self
@@ -619,12 +700,15 @@ where
     /// 'dest_pubkey': The Comms pubkey of the recipient node
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'account': The wallet account the inputs and change of the transaction belong to
     pub async fn send_transaction(
         &mut self,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         message: String,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+        account: String,
         join_handles: &mut FuturesUnordered<JoinHandle<Result<u64, TransactionServiceProtocolError>>>,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<u64, TransactionServiceProtocolError>>,
@@ -636,10 +720,9 @@ where
                 target: LOG_TARGET,
                 "Received transaction with spend-to-self transaction"
             );
-
             let (tx_id, fee, transaction) = self
                 .output_manager_service
-                .create_pay_to_self_transaction(amount, fee_per_gram, None, message.clone())
+                .create_pay_to_self_transaction_from_account(amount, fee_per_gram, None, message.clone(), account)
                 .await?;
 
             // Notify that the transaction was successfully resolved.
@@ -670,7 +753,15 @@ where
 
         let sender_protocol = self
             .output_manager_service
-            .prepare_transaction_to_send(amount, fee_per_gram, None, message.clone(), script!(Nop))
+            .prepare_transaction_to_send_from_account(
+                amount,
+                fee_per_gram,
+                None,
+                message.clone(),
+                script!(Nop),
+                utxo_selection,
+                account.clone(),
+            )
             .await?;
 
         let tx_id = sender_protocol.get_tx_id()?;
@@ -689,6 +780,7 @@ where
             dest_pubkey,
             amount,
             message,
+            account,
             sender_protocol,
             TransactionSendProtocolStage::Initial,
         );
@@ -704,12 +796,15 @@ where
     /// 'dest_pubkey': The Comms pubkey of the recipient node
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'account': The wallet account the inputs and change of the transaction belong to
     pub async fn send_one_sided_transaction(
         &mut self,
         dest_pubkey: CommsPublicKey,
         amount: MicroTari,
         fee_per_gram: MicroTari,
         message: String,
+        utxo_selection: Option<UTXOSelectionStrategy>,
+        account: String,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<u64, TransactionServiceProtocolError>>,
         >,
@@ -725,27 +820,74 @@ where
 
         let mut stp = self
             .output_manager_service
-            .prepare_transaction_to_send(
+            .prepare_transaction_to_send_from_account(
                 amount,
                 fee_per_gram,
                 None,
                 message.clone(),
                 script!(PushPubKey(Box::new(dest_pubkey.clone()))),
+                utxo_selection,
+                account.clone(),
             )
             .await?;
         let tx_id = stp.get_tx_id()?;
 
+        self.output_manager_service
+            .confirm_pending_transaction(tx_id)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        self.finalize_one_sided_transaction(&mut stp, &dest_pubkey).await?;
+
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        // Broadcast one-sided transaction
+
+        let tx = stp
+            .get_transaction()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let fee = stp
+            .get_fee_amount()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let mut completed_transaction = CompletedTransaction::new(
+            tx_id,
+            self.resources.node_identity.public_key().clone(),
+            dest_pubkey.clone(),
+            amount,
+            fee,
+            tx.clone(),
+            TransactionStatus::Completed,
+            message.clone(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+        );
+        completed_transaction.account = account;
+        self.submit_transaction(transaction_broadcast_join_handles, completed_transaction)
+            .await?;
+
+        Ok(tx_id)
+    }
+
+    /// Complete the receiver half of a one-sided transaction, whose recipient output is spendable with the
+    /// Diffie-Hellman shared secret of the sender offset key and `dest_pubkey`, and finalize the transaction.
+    async fn finalize_one_sided_transaction(
+        &mut self,
+        stp: &mut SenderTransactionProtocol,
+        dest_pubkey: &CommsPublicKey,
+    ) -> Result<(), TransactionServiceError> {
+        let tx_id = stp.get_tx_id()?;
+
         // This call is needed to advance the state from `SingleRoundMessageReady` to `SingleRoundMessageReady`,
         // but the returned value is not used
         let _ = stp
             .build_single_round_message()
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
 
-        self.output_manager_service
-            .confirm_pending_transaction(tx_id)
-            .await
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-
         // Prepare receiver part of the transaction
 
         // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` results in a public key, which is converted to
@@ -797,41 +939,256 @@ where
             })?;
         info!(target: LOG_TARGET, "Finalized one-side transaction TxId: {}", tx_id);
 
-        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
-        // there are no subscribers.
+        Ok(())
+    }
+
+    /// Prepare a one-sided transaction that will be signed by an offline wallet holding the master key. The selected
+    /// inputs stay encumbered until the signed transaction is imported or the Output Manager cancels the TxId.
+    pub async fn prepare_unsigned_transaction(
+        &mut self,
+        dest_pubkey: CommsPublicKey,
+        amount: MicroTari,
+        fee_per_gram: MicroTari,
+        message: String,
+        account: String,
+    ) -> Result<UnsignedTransaction, TransactionServiceError> {
+        if self.node_identity.public_key() == &dest_pubkey {
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+        // The signing wallet derives the input and change keys from its default key chain
+        if account != DEFAULT_ACCOUNT {
+            return Err(TransactionServiceError::AccountError(format!(
+                "Offline signing is only supported from the `{}` account",
+                DEFAULT_ACCOUNT
+            )));
+        }
+
+        Ok(self
+            .output_manager_service
+            .prepare_unsigned_transaction(dest_pubkey, amount, fee_per_gram, None, message)
+            .await?)
+    }
+
+    /// Sign a transaction that was prepared by an online wallet. Nothing is stored or broadcast by this wallet, the
+    /// signed transaction has to be imported into the online wallet.
+    pub async fn sign_transaction(
+        &mut self,
+        unsigned: UnsignedTransaction,
+    ) -> Result<SignedTransaction, TransactionServiceError> {
+        let tx_id = unsigned.tx_id;
+        let recipient = unsigned.recipient.clone();
+        let amount = unsigned.amount;
+        let change = unsigned.change;
+        let message = unsigned.message.clone();
+
+        let (mut stp, change_key_index) = self.output_manager_service.build_offline_transaction(unsigned).await?;
+        self.finalize_one_sided_transaction(&mut stp, &recipient).await?;
+        info!(target: LOG_TARGET, "Signed offline transaction (TxId: {})", tx_id);
+
+        Ok(SignedTransaction {
+            tx_id,
+            transaction: stp.get_transaction()?.clone(),
+            recipient,
+            amount,
+            fee: stp.get_fee_amount()?,
+            change,
+            change_key_index,
+            message,
+        })
+    }
+
+    /// Import a transaction that was signed offline and broadcast it. The transaction must have been prepared by this
+    /// wallet.
+    pub async fn import_signed_transaction(
+        &mut self,
+        signed: SignedTransaction,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let tx_id = signed.tx_id;
+        if self.db.transaction_exists(tx_id).await? {
+            return Err(TransactionServiceError::OfflineSigningError(format!(
+                "Transaction (TxId: {}) has already been imported",
+                tx_id
+            )));
+        }
+        signed
+            .transaction
+            .validate_internal_consistency(&self.resources.factories, None)?;
+
+        self.output_manager_service
+            .import_signed_transaction(signed.clone())
+            .await?;
+
         let _ = self
             .event_publisher
             .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
 
-        // Broadcast one-sided transaction
-
-        let tx = stp
-            .get_transaction()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        let fee = stp
-            .get_fee_amount()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
         self.submit_transaction(
             transaction_broadcast_join_handles,
             CompletedTransaction::new(
                 tx_id,
                 self.resources.node_identity.public_key().clone(),
-                dest_pubkey.clone(),
-                amount,
-                fee,
-                tx.clone(),
+                signed.recipient,
+                signed.amount,
+                signed.fee,
+                signed.transaction,
                 TransactionStatus::Completed,
-                message.clone(),
+                signed.message,
                 Utc::now().naive_utc(),
                 TransactionDirection::Outbound,
                 None,
             ),
         )
         .await?;
+        info!(target: LOG_TARGET, "Imported signed transaction (TxId: {})", tx_id);
 
         Ok(tx_id)
     }
 
+    /// Replace a stuck outbound transaction with a transaction that spends exactly the same inputs at a higher fee per
+    /// gram. This is only possible for transactions where this wallet controls every party, i.e. one-sided and
+    /// pay-to-self transactions. The original transaction is marked as replaced and both versions keep being monitored
+    /// until one of them is mined, at which point the other is cancelled.
+    pub async fn bump_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroTari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<u64, TransactionServiceProtocolError>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let original = self.db.get_completed_transaction(tx_id).await?;
+        if !(original.status == TransactionStatus::Completed || original.status == TransactionStatus::Broadcast) {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "Transaction (TxId: {}) is not waiting to be mined",
+                tx_id
+            )));
+        }
+        if let Some(replaced_by) = original.replaced_by {
+            if self.db.get_completed_transaction(replaced_by).await.is_ok() {
+                return Err(TransactionServiceError::FeeBumpError(format!(
+                    "Transaction (TxId: {}) has already been replaced by TxId: {}",
+                    tx_id, replaced_by
+                )));
+            }
+        }
+
+        let own_public_key = self.node_identity.public_key().clone();
+        if original.source_public_key != own_public_key {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "Transaction (TxId: {}) was not sent by this wallet",
+                tx_id
+            )));
+        }
+        let is_pay_to_self = original.destination_public_key == own_public_key;
+        let one_sided_script = script!(PushPubKey(Box::new(original.destination_public_key.clone())));
+        let is_one_sided = !is_pay_to_self &&
+            original
+                .transaction
+                .body
+                .outputs()
+                .iter()
+                .any(|o| o.script == one_sided_script);
+        if !(is_pay_to_self || is_one_sided) {
+            return Err(TransactionServiceError::FeeBumpError(
+                "Only one-sided and pay-to-self transactions can be replaced".to_string(),
+            ));
+        }
+
+        // Mempools only accept a replacement if its fee per gram exceeds that of the original by their minimum
+        // replacement increment and it pays a higher total fee
+        let min_fee_per_gram = original.transaction.calculate_ave_fee_per_gram() +
+            self.resources.config.min_fee_bump_per_gram_increment.0 as f64;
+        if (fee_per_gram.0 as f64) < min_fee_per_gram {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "A fee per gram of {} is less than the minimum of {:.2} µT required to replace TxId: {}",
+                fee_per_gram, min_fee_per_gram, tx_id
+            )));
+        }
+        let body = &original.transaction.body;
+        let new_fee = Fee::calculate(
+            fee_per_gram,
+            body.kernels().len(),
+            body.inputs().len(),
+            body.outputs().len(),
+        );
+        if new_fee <= original.fee {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "A fee per gram of {} does not increase the fee of {} paid by TxId: {}",
+                fee_per_gram, original.fee, tx_id
+            )));
+        }
+
+        let (new_tx_id, fee, transaction) = if is_pay_to_self {
+            self.output_manager_service
+                .create_fee_bump_pay_to_self_transaction(tx_id, original.amount, fee_per_gram, original.message.clone())
+                .await?
+        } else {
+            let mut stp = self
+                .output_manager_service
+                .prepare_fee_bump_transaction(
+                    tx_id,
+                    original.amount,
+                    fee_per_gram,
+                    original.message.clone(),
+                    one_sided_script,
+                )
+                .await?;
+            let new_tx_id = stp.get_tx_id()?;
+            if let Err(e) = self
+                .finalize_one_sided_transaction(&mut stp, &original.destination_public_key)
+                .await
+            {
+                // Hand the inputs back to the original transaction before dropping the replacement
+                self.output_manager_service
+                    .reassign_pending_inputs(new_tx_id, tx_id)
+                    .await?;
+                self.output_manager_service.cancel_transaction(new_tx_id).await?;
+                return Err(e);
+            }
+            (new_tx_id, stp.get_fee_amount()?, stp.get_transaction()?.clone())
+        };
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) replaced by TxId: {} with fee {} (was {})", tx_id, new_tx_id, fee, original.fee
+        );
+
+        // The replacement must be stored and linked to the original before its broadcast protocol starts, so that the
+        // protocol knows the inputs are shared with the original.
+        self.db
+            .insert_completed_transaction(
+                new_tx_id,
+                CompletedTransaction::new(
+                    new_tx_id,
+                    own_public_key,
+                    original.destination_public_key.clone(),
+                    original.amount,
+                    fee,
+                    transaction,
+                    TransactionStatus::Completed,
+                    original.message.clone(),
+                    Utc::now().naive_utc(),
+                    original.direction.clone(),
+                    None,
+                ),
+            )
+            .await?;
+        self.db.mark_completed_transaction_replaced(tx_id, new_tx_id).await?;
+
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionFeeBumped(tx_id, new_tx_id)));
+
+        self.complete_send_transaction_protocol(Ok(new_tx_id), transaction_broadcast_join_handles)
+            .await;
+
+        Ok(new_tx_id)
+    }
+
     /// Accept the public reply from a recipient and apply the reply to the relevant transaction protocol
     /// # Arguments
     /// 'recipient_reply' - The public response from a recipient with data required to complete the transaction
@@ -1016,6 +1373,16 @@ where
         }
     }
 
+    /// Select the account that the outputs of newly received transactions are credited to
+    async fn set_receiving_account(&mut self, account: String) -> Result<(), TransactionServiceError> {
+        let accounts = self.output_manager_service.get_accounts().await?;
+        if !accounts.contains(&account) {
+            return Err(OutputManagerError::AccountNotFound(account).into());
+        }
+        self.receiving_account = account;
+        Ok(())
+    }
+
     /// Cancel a pending transaction
     async fn cancel_pending_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         self.db.cancel_pending_transaction(tx_id).await.map_err(|e| {
@@ -1118,6 +1485,7 @@ where
                     tx.destination_public_key,
                     tx.amount,
                     tx.message,
+                    tx.account,
                     tx.sender_protocol,
                     TransactionSendProtocolStage::WaitForReply,
                 );
@@ -1226,6 +1594,7 @@ where
                 data.tx_id,
                 source_pubkey,
                 sender_message,
+                self.receiving_account.clone(),
                 TransactionReceiveProtocolStage::Initial,
                 self.resources.clone(),
                 tx_finalized_receiver,
@@ -1362,6 +1731,7 @@ where
                     tx_id,
                     tx.source_public_key,
                     TransactionSenderMessage::None,
+                    tx.account,
                     TransactionReceiveProtocolStage::WaitForFinalize,
                     self.resources.clone(),
                     tx_finalized_receiver,
@@ -1659,15 +2029,17 @@ where
         Ok(())
     }
 
-    /// Add a completed transaction to the Transaction Manager to record directly importing a spendable UTXO.
+    /// Add a completed transaction to the Transaction Manager to record directly importing a spendable UTXO. A random
+    /// TxId is used if none is given.
     pub async fn add_utxo_import_transaction(
         &mut self,
         value: MicroTari,
         source_public_key: CommsPublicKey,
         message: String,
         maturity: Option<u64>,
+        tx_id: Option<TxId>,
     ) -> Result<TxId, TransactionServiceError> {
-        let tx_id = OsRng.next_u64();
+        let tx_id = tx_id.unwrap_or_else(|| OsRng.next_u64());
         self.db
             .add_utxo_import_transaction(
                 tx_id,
@@ -1692,6 +2064,57 @@ where
         Ok(tx_id)
     }
 
+    /// Add a faux outbound transaction that records the spend of some of this wallet's outputs by another wallet. The
+    /// TxId is chosen by the Output Manager Service, which has already marked the outputs as spent under it. The
+    /// change of the spend is netted out of `value`, so the imports that recorded the change outputs as received are
+    /// cancelled.
+    pub async fn add_spent_utxo_import_transaction(
+        &mut self,
+        tx_id: TxId,
+        value: MicroTari,
+        message: String,
+        replaced_imports: Vec<TxId>,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.db
+            .add_spent_utxo_import_transaction(tx_id, value, self.node_identity.public_key().clone(), message)
+            .await?;
+        let _ = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionImported(tx_id)))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event, usually because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
+        for replaced_tx_id in replaced_imports {
+            // The change output may not have been imported yet, in which case there is nothing to cancel
+            if self.db.get_completed_transaction(replaced_tx_id).await.is_err() {
+                continue;
+            }
+            self.db.cancel_completed_transaction(replaced_tx_id).await?;
+            debug!(
+                target: LOG_TARGET,
+                "Import of change output (TxId: {}) replaced by spend (TxId: {})", replaced_tx_id, tx_id
+            );
+            let _ = self
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionCancelled(replaced_tx_id)))
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event, usually because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+        }
+        Ok(tx_id)
+    }
+
     /// Submit a completed transaction to the Transaction Manager
     async fn submit_transaction(
         &mut self,
@@ -2056,7 +2479,6 @@ where
             base_node_service::{handle::BaseNodeServiceHandle, mock_base_node_service::MockBaseNodeService},
             output_manager_service::{
                 config::OutputManagerServiceConfig,
-                error::OutputManagerError,
                 service::OutputManagerService,
                 storage::{database::OutputManagerDatabase, sqlite_db::OutputManagerSqliteDatabase},
             },
@@ -2116,7 +2538,15 @@ where
         fake_oms.add_output(None, uo).await?;
 
         let mut stp = fake_oms
-            .prepare_transaction_to_send(amount, MicroTari::from(25), None, "".to_string(), script!(Nop))
+            .prepare_transaction_to_send(
+                amount,
+                MicroTari::from(25),
+                None,
+                "".to_string(),
+                script!(Nop),
+                None,
+                DEFAULT_ACCOUNT.to_string(),
+            )
             .await?;
 
         let msg = stp.build_single_round_message()?;
@@ -2136,7 +2566,7 @@ where
 
         let rtp = self
             .output_manager_service
-            .get_recipient_transaction(sender_message)
+            .get_recipient_transaction(sender_message, source_public_key.clone())
             .await?;
 
         let inbound_transaction = InboundTransaction::new(
@@ -124,6 +124,17 @@ pub trait TransactionBackend: Send + Sync + Clone {
     fn update_confirmations(&self, tx_id: TxId, confirmations: u64) -> Result<(), TransactionStorageError>;
     /// Update a transactions mined height
     fn update_mined_height(&self, tx_id: TxId, mined_height: u64) -> Result<(), TransactionStorageError>;
+    /// Mark a completed transaction as replaced by the fee bumped transaction `replaced_by`
+    fn mark_completed_transaction_replaced(
+        &self,
+        tx_id: TxId,
+        replaced_by: TxId,
+    ) -> Result<(), TransactionStorageError>;
+    /// Fetch the completed transaction, cancelled or not, that was replaced by the transaction `replaced_by`
+    fn fetch_replaced_transaction(
+        &self,
+        replaced_by: TxId,
+    ) -> Result<Option<CompletedTransaction>, TransactionStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -638,6 +649,47 @@ where T: TransactionBackend + 'static
         Ok(())
     }
 
+    /// Add an imported outbound transaction for an output of this wallet that was spent elsewhere. The recipient is
+    /// not known, so the transaction is addressed to the default public key.
+    pub async fn add_spent_utxo_import_transaction(
+        &self,
+        tx_id: TxId,
+        amount: MicroTari,
+        comms_public_key: CommsPublicKey,
+        message: String,
+    ) -> Result<(), TransactionStorageError> {
+        let transaction = CompletedTransaction::new(
+            tx_id,
+            comms_public_key,
+            CommsPublicKey::default(),
+            amount,
+            MicroTari::from(0),
+            Transaction::new(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                BlindingFactor::default(),
+                BlindingFactor::default(),
+            ),
+            TransactionStatus::Imported,
+            message,
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+        );
+
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            db_clone.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+                tx_id,
+                Box::new(transaction),
+            )))
+        })
+        .await
+        .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
     pub async fn cancel_coinbase_transaction_at_block_height(
         &self,
         block_height: u64,
@@ -738,6 +790,29 @@ where T: TransactionBackend + 'static
             .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
         Ok(())
     }
+
+    pub async fn mark_completed_transaction_replaced(
+        &self,
+        tx_id: TxId,
+        replaced_by: TxId,
+    ) -> Result<(), TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.mark_completed_transaction_replaced(tx_id, replaced_by))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))??;
+        Ok(())
+    }
+
+    /// Returns the transaction, cancelled or not, that the fee bumped transaction `replaced_by` replaced
+    pub async fn get_replaced_transaction(
+        &self,
+        replaced_by: TxId,
+    ) -> Result<Option<CompletedTransaction>, TransactionStorageError> {
+        let db_clone = self.db.clone();
+        tokio::task::spawn_blocking(move || db_clone.fetch_replaced_transaction(replaced_by))
+            .await
+            .map_err(|err| TransactionStorageError::BlockingTaskSpawnError(err.to_string()))?
+    }
 }
 
 impl Display for DbKey {
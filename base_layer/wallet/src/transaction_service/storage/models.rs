@@ -20,7 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::{output_manager_service::TxId, transaction_service::error::TransactionStorageError};
+use crate::{
+    output_manager_service::{TxId, DEFAULT_ACCOUNT},
+    transaction_service::error::TransactionStorageError,
+};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -108,6 +111,8 @@ pub struct InboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    /// The wallet account this transaction belongs to
+    pub account: String,
 }
 
 impl InboundTransaction {
@@ -132,6 +137,7 @@ impl InboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         }
     }
 }
@@ -150,6 +156,8 @@ pub struct OutboundTransaction {
     pub direct_send_success: bool,
     pub send_count: u32,
     pub last_send_timestamp: Option<NaiveDateTime>,
+    /// The wallet account this transaction belongs to
+    pub account: String,
 }
 
 impl OutboundTransaction {
@@ -178,6 +186,7 @@ impl OutboundTransaction {
             direct_send_success,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         }
     }
 }
@@ -201,6 +210,10 @@ pub struct CompletedTransaction {
     pub valid: bool,
     pub confirmations: Option<u64>,
     pub mined_height: Option<u64>,
+    /// The fee bumped transaction that replaces this one, if any
+    pub replaced_by: Option<TxId>,
+    /// The wallet account this transaction belongs to
+    pub account: String,
 }
 
 impl CompletedTransaction {
@@ -236,6 +249,8 @@ impl CompletedTransaction {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         }
     }
 }
@@ -287,6 +302,7 @@ impl From<CompletedTransaction> for InboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: ct.account,
         }
     }
 }
@@ -306,6 +322,7 @@ impl From<CompletedTransaction> for OutboundTransaction {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: ct.account,
         }
     }
 }
@@ -330,6 +347,8 @@ impl From<OutboundTransaction> for CompletedTransaction {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: tx.account,
         }
     }
 }
@@ -354,6 +373,8 @@ impl From<InboundTransaction> for CompletedTransaction {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: tx.account,
         }
     }
 }
@@ -515,6 +515,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                             valid: None,
                             confirmations: None,
                             mined_height: None,
+                            replaced_by: None,
                         }),
                         &(*conn),
                     )?;
@@ -546,6 +547,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                         valid: None,
                         confirmations: None,
                         mined_height: None,
+                        replaced_by: None,
                     }),
                     &(*conn),
                 )?;
@@ -656,6 +658,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                     valid: None,
                     confirmations: None,
                     mined_height: None,
+                    replaced_by: None,
                 }),
                 &(*conn),
             )?;
@@ -813,6 +816,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
                 valid: None,
                 confirmations: None,
                 mined_height: None,
+                replaced_by: None,
             };
             tx.update(update, &conn)?;
         } else if let Ok(tx) = OutboundTransactionSql::find(tx_id, &conn) {
@@ -933,6 +937,51 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         };
         Ok(())
     }
+
+    fn mark_completed_transaction_replaced(&self, tx_id: u64, replaced_by: u64) -> Result<(), TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        match CompletedTransactionSql::find_by_cancelled(tx_id, false, &(*conn)) {
+            Ok(v) => {
+                v.update(
+                    UpdateCompletedTransactionSql::from(UpdateCompletedTransaction {
+                        status: None,
+                        timestamp: None,
+                        cancelled: None,
+                        direction: None,
+                        send_count: None,
+                        last_send_timestamp: None,
+                        valid: None,
+                        confirmations: None,
+                        mined_height: None,
+                        replaced_by: Some(Some(replaced_by)),
+                    }),
+                    &(*conn),
+                )?;
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                return Err(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
+                    tx_id,
+                )));
+            },
+            Err(e) => return Err(e),
+        };
+        Ok(())
+    }
+
+    fn fetch_replaced_transaction(
+        &self,
+        replaced_by: u64,
+    ) -> Result<Option<CompletedTransaction>, TransactionStorageError> {
+        let conn = self.database_connection.acquire_lock();
+        match CompletedTransactionSql::find_by_replaced_by(replaced_by, &(*conn)) {
+            Ok(mut v) => {
+                self.decrypt_if_necessary(&mut v)?;
+                Ok(Some(CompletedTransaction::try_from(v)?))
+            },
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
@@ -948,6 +997,7 @@ struct InboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    account: String,
 }
 
 impl InboundTransactionSql {
@@ -1081,6 +1131,7 @@ impl TryFrom<InboundTransaction> for InboundTransactionSql {
             direct_send_success: i.direct_send_success as i32,
             send_count: i.send_count as i32,
             last_send_timestamp: i.last_send_timestamp,
+            account: i.account,
         })
     }
 }
@@ -1102,6 +1153,7 @@ impl TryFrom<InboundTransactionSql> for InboundTransaction {
             direct_send_success: i.direct_send_success != 0,
             send_count: i.send_count as u32,
             last_send_timestamp: i.last_send_timestamp,
+            account: i.account,
         })
     }
 }
@@ -1131,6 +1183,7 @@ struct OutboundTransactionSql {
     direct_send_success: i32,
     send_count: i32,
     last_send_timestamp: Option<NaiveDateTime>,
+    account: String,
 }
 
 impl OutboundTransactionSql {
@@ -1265,6 +1318,7 @@ impl TryFrom<OutboundTransaction> for OutboundTransactionSql {
             direct_send_success: o.direct_send_success as i32,
             send_count: o.send_count as i32,
             last_send_timestamp: o.last_send_timestamp,
+            account: o.account,
         })
     }
 }
@@ -1287,6 +1341,7 @@ impl TryFrom<OutboundTransactionSql> for OutboundTransaction {
             direct_send_success: o.direct_send_success != 0,
             send_count: o.send_count as u32,
             last_send_timestamp: o.last_send_timestamp,
+            account: o.account,
         })
     }
 }
@@ -1322,6 +1377,8 @@ struct CompletedTransactionSql {
     valid: i32,
     confirmations: Option<i64>,
     mined_height: Option<i64>,
+    replaced_by: Option<i64>,
+    account: String,
 }
 
 impl CompletedTransactionSql {
@@ -1355,6 +1412,15 @@ impl CompletedTransactionSql {
             .load::<CompletedTransactionSql>(conn)?)
     }
 
+    pub fn find_by_replaced_by(
+        replaced_by: TxId,
+        conn: &SqliteConnection,
+    ) -> Result<CompletedTransactionSql, TransactionStorageError> {
+        Ok(completed_transactions::table
+            .filter(completed_transactions::replaced_by.eq(replaced_by as i64))
+            .first::<CompletedTransactionSql>(conn)?)
+    }
+
     pub fn find(tx_id: TxId, conn: &SqliteConnection) -> Result<CompletedTransactionSql, TransactionStorageError> {
         Ok(completed_transactions::table
             .filter(completed_transactions::tx_id.eq(tx_id as i64))
@@ -1416,6 +1482,7 @@ impl CompletedTransactionSql {
                 valid: None,
                 confirmations: None,
                 mined_height: None,
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1436,6 +1503,7 @@ impl CompletedTransactionSql {
                 valid: None,
                 confirmations: None,
                 mined_height: None,
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1456,6 +1524,7 @@ impl CompletedTransactionSql {
                 valid: None,
                 confirmations: None,
                 mined_height: None,
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1476,6 +1545,7 @@ impl CompletedTransactionSql {
                 valid: Some(valid as i32),
                 confirmations: None,
                 mined_height: None,
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1496,6 +1566,7 @@ impl CompletedTransactionSql {
                 valid: None,
                 confirmations: None,
                 mined_height: None,
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1520,6 +1591,7 @@ impl CompletedTransactionSql {
                 valid: None,
                 confirmations: Some(Some(confirmations as i64)),
                 mined_height: None,
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1544,6 +1616,7 @@ impl CompletedTransactionSql {
                 valid: None,
                 confirmations: None,
                 mined_height: Some(Some(mined_height as i64)),
+                replaced_by: None,
             },
             conn,
         )?;
@@ -1594,6 +1667,8 @@ impl TryFrom<CompletedTransaction> for CompletedTransactionSql {
             valid: c.valid as i32,
             confirmations: c.confirmations.map(|ic| ic as i64),
             mined_height: c.mined_height.map(|ic| ic as i64),
+            replaced_by: c.replaced_by.map(|r| r as i64),
+            account: c.account,
         })
     }
 }
@@ -1622,6 +1697,8 @@ impl TryFrom<CompletedTransactionSql> for CompletedTransaction {
             valid: c.valid != 0,
             confirmations: c.confirmations.map(|ic| ic as u64),
             mined_height: c.mined_height.map(|ic| ic as u64),
+            replaced_by: c.replaced_by.map(|r| r as u64),
+            account: c.account,
         })
     }
 }
@@ -1637,6 +1714,7 @@ pub struct UpdateCompletedTransaction {
     valid: Option<bool>,
     confirmations: Option<Option<u64>>,
     mined_height: Option<Option<u64>>,
+    replaced_by: Option<Option<TxId>>,
 }
 
 #[derive(AsChangeset)]
@@ -1652,6 +1730,7 @@ pub struct UpdateCompletedTransactionSql {
     valid: Option<i32>,
     confirmations: Option<Option<i64>>,
     mined_height: Option<Option<i64>>,
+    replaced_by: Option<Option<i64>>,
 }
 
 /// Map a Rust friendly UpdateCompletedTransaction to the Sql data type form
@@ -1668,6 +1747,7 @@ impl From<UpdateCompletedTransaction> for UpdateCompletedTransactionSql {
             valid: u.valid.map(|c| c as i32),
             confirmations: u.confirmations.map(|c| c.map(|ic| ic as i64)),
             mined_height: u.mined_height.map(|c| c.map(|ic| ic as i64)),
+            replaced_by: u.replaced_by.map(|r| r.map(|ir| ir as i64)),
         }
     }
 }
@@ -1677,6 +1757,7 @@ mod test {
     #[cfg(feature = "test_harness")]
     use crate::transaction_service::storage::sqlite_db::UpdateCompletedTransactionSql;
     use crate::{
+        output_manager_service::DEFAULT_ACCOUNT,
         storage::sqlite_utilities::WalletDbConnection,
         transaction_service::storage::{
             database::{DbKey, TransactionBackend},
@@ -1783,6 +1864,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         let outbound_tx2 = OutboundTransactionSql::try_from(OutboundTransaction {
@@ -1798,6 +1880,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         })
         .unwrap();
 
@@ -1838,6 +1921,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
         let inbound_tx2 = InboundTransaction {
             tx_id: 3,
@@ -1851,6 +1935,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         InboundTransactionSql::try_from(inbound_tx1.clone())
@@ -1899,6 +1984,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
         let completed_tx2 = CompletedTransaction {
             tx_id: 3,
@@ -1918,6 +2005,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone())
@@ -2035,6 +2124,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         let coinbase_tx2 = CompletedTransaction {
@@ -2055,6 +2146,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         let coinbase_tx3 = CompletedTransaction {
@@ -2075,6 +2168,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         CompletedTransactionSql::try_from(coinbase_tx1)
@@ -2112,6 +2207,7 @@ mod test {
                     valid: None,
                     confirmations: None,
                     mined_height: None,
+                    replaced_by: None,
                 },
                 &conn,
             )
@@ -2147,6 +2243,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
         let mut inbound_tx_sql = InboundTransactionSql::try_from(inbound_tx.clone()).unwrap();
         inbound_tx_sql.commit(&conn).unwrap();
@@ -2170,6 +2267,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         let mut outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx.clone()).unwrap();
@@ -2205,6 +2303,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
 
         let mut completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone()).unwrap();
@@ -2241,6 +2341,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
         let inbound_tx_sql = InboundTransactionSql::try_from(inbound_tx).unwrap();
         inbound_tx_sql.commit(&conn).unwrap();
@@ -2258,6 +2359,7 @@ mod test {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
         let outbound_tx_sql = OutboundTransactionSql::try_from(outbound_tx).unwrap();
         outbound_tx_sql.commit(&conn).unwrap();
@@ -2286,6 +2388,8 @@ mod test {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         };
         let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx).unwrap();
         completed_tx_sql.commit(&conn).unwrap();
@@ -22,7 +22,7 @@
 
 use crate::{
     error::WalletError,
-    output_manager_service::{handle::OutputManagerHandle, TxId},
+    output_manager_service::{handle::OutputManagerHandle, import_tx_id, TxId},
     storage::{
         database::{WalletBackend, WalletDatabase},
         sqlite_db::WalletSqliteDatabase,
@@ -534,7 +534,7 @@ where TBackend: WalletBackend + 'static
     }
 
     /// A faux incoming transaction will be created to provide a record of the event of importing a UTXO. The TxId of
-    /// the generated transaction is returned.
+    /// the generated transaction, which is derived from the UTXO, is returned.
     pub async fn import_unblinded_utxo_to_transaction_service(
         &mut self,
         unblinded_output: UnblindedOutput,
@@ -544,7 +544,8 @@ where TBackend: WalletBackend + 'static
         let tx_id = self
             .resources
             .transaction_service
-            .import_utxo(
+            .import_utxo_with_tx_id(
+                import_tx_id(&unblinded_output),
                 unblinded_output.value,
                 source_public_key.clone(),
                 message,
@@ -31,6 +31,7 @@ use crate::{
         storage::{database::OutputManagerBackend, models::KnownOneSidedPaymentScript},
         OutputManagerServiceInitializer,
         TxId,
+        DEFAULT_ACCOUNT,
     },
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
@@ -123,6 +124,14 @@ where
         shutdown_signal: ShutdownSignal,
         recovery_master_key: Option<CommsSecretKey>,
     ) -> Result<Wallet<T, U, V, W>, WalletError> {
+        let watch_only_keys = wallet_database.get_watch_only_keys().await?;
+        if watch_only_keys.is_some() && recovery_master_key.is_some() {
+            return Err(WalletError::WalletRecoveryError(
+                "A watch-only wallet cannot be recovered with a master key".to_string(),
+            ));
+        }
+        let is_watch_only = watch_only_keys.is_some();
+        // A watch-only wallet still has a master key, it is only used for its comms identity
         let master_secret_key =
             read_or_create_master_secret_key(recovery_master_key, &mut wallet_database.clone()).await?;
         let comms_secret_key = derive_comms_secret_key(&master_secret_key)?;
@@ -167,20 +176,26 @@ where
         );
         let stack = StackBuilder::new(shutdown_signal)
             .add_initializer(P2pInitializer::new(comms_config, publisher))
-            .add_initializer(OutputManagerServiceInitializer::new(
-                config.output_manager_service_config.unwrap_or_default(),
-                output_manager_backend,
-                factories.clone(),
-                config.network,
-                master_secret_key,
-            ))
-            .add_initializer(TransactionServiceInitializer::new(
-                config.transaction_service_config.unwrap_or_default(),
-                peer_message_subscription_factory,
-                transaction_backend,
-                node_identity.clone(),
-                factories.clone(),
-            ))
+            .add_initializer(
+                OutputManagerServiceInitializer::new(
+                    config.output_manager_service_config.unwrap_or_default(),
+                    output_manager_backend,
+                    factories.clone(),
+                    config.network,
+                    master_secret_key,
+                )
+                .with_watch_only_keys(watch_only_keys),
+            )
+            .add_initializer(
+                TransactionServiceInitializer::new(
+                    config.transaction_service_config.unwrap_or_default(),
+                    peer_message_subscription_factory,
+                    transaction_backend,
+                    node_identity.clone(),
+                    factories.clone(),
+                )
+                .with_watch_only(is_watch_only),
+            )
             .add_initializer(ContactsServiceInitializer::new(contacts_backend))
             .add_initializer(BaseNodeServiceInitializer::new(
                 config.base_node_service_config,
@@ -201,7 +216,7 @@ where
         let comms = initialization::spawn_comms_using_transport(comms, transport_type).await?;
 
         let mut output_manager_handle = handles.expect_handle::<OutputManagerHandle>();
-        let transaction_service_handle = handles.expect_handle::<TransactionServiceHandle>();
+        let mut transaction_service_handle = handles.expect_handle::<TransactionServiceHandle>();
         let contacts_handle = handles.expect_handle::<ContactsServiceHandle>();
         let dht = handles.expect_handle::<Dht>();
         let store_and_forward_requester = dht.store_and_forward_requester();
@@ -216,6 +231,10 @@ where
                 e
             })?;
 
+        if let Some(account) = wallet_database.get_receiving_account().await? {
+            transaction_service_handle.set_receiving_account(account).await?;
+        }
+
         // Persist the comms node address and features after it has been spawned to capture any modifications made
         // during comms startup. In the case of a Tor Transport the public address could have been generated
         wallet_database
@@ -417,10 +436,31 @@ where
         fee_per_gram: MicroTari,
         message: String,
         lock_height: Option<u64>,
+    ) -> Result<TxId, WalletError> {
+        self.coin_split_from_account(
+            amount_per_split,
+            split_count,
+            fee_per_gram,
+            message,
+            lock_height,
+            DEFAULT_ACCOUNT.to_string(),
+        )
+        .await
+    }
+
+    /// Do a coin split of the outputs of the named account
+    pub async fn coin_split_from_account(
+        &mut self,
+        amount_per_split: MicroTari,
+        split_count: usize,
+        fee_per_gram: MicroTari,
+        message: String,
+        lock_height: Option<u64>,
+        account: String,
     ) -> Result<TxId, WalletError> {
         let coin_split_tx = self
             .output_manager_service
-            .create_coin_split(amount_per_split, split_count, fee_per_gram, lock_height)
+            .create_coin_split_from_account(amount_per_split, split_count, fee_per_gram, lock_height, account)
             .await;
 
         match coin_split_tx {
@@ -438,6 +478,14 @@ where
         }
     }
 
+    /// Select the account that newly received transactions are credited to. The choice is stored in the wallet
+    /// database so that it survives a restart.
+    pub async fn set_receiving_account(&mut self, account: String) -> Result<(), WalletError> {
+        self.transaction_service.set_receiving_account(account.clone()).await?;
+        self.db.set_receiving_account(account).await?;
+        Ok(())
+    }
+
     /// Apply encryption to all the Wallet db backends. The Wallet backend will test if the db's are already encrypted
     /// in which case this will fail.
     pub async fn apply_encryption(&mut self, passphrase: String) -> Result<(), WalletError> {
@@ -469,6 +517,9 @@ where
     }
 }
 
+/// Read the wallet's master secret key from the database, storing the recovery key or a new random key if there is
+/// none yet. For a watch-only wallet this key is only used to derive the comms identity: it is unrelated to the
+/// watched wallet's keys and the Output Manager Service refuses every request that would spend with it.
 async fn read_or_create_master_secret_key<T: WalletBackend + 'static>(
     recovery_master_key: Option<CommsSecretKey>,
     db: &mut WalletDatabase<T>,
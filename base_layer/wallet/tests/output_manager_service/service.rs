@@ -70,7 +70,8 @@ use tari_wallet::{
     output_manager_service::{
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerStorageError},
-        handle::{OutputManagerEvent, OutputManagerHandle},
+        handle::{OutputManagerEvent, OutputManagerHandle, PublicRewindKeys},
+        offline_signing::UnsignedTransaction,
         service::OutputManagerService,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputManagerBackend, OutputManagerDatabase, WriteOperation},
@@ -79,6 +80,7 @@ use tari_wallet::{
         },
         TxId,
         TxoValidationType,
+        DEFAULT_ACCOUNT,
     },
     transaction_service::handle::TransactionServiceHandle,
     types::ValidationRetryStrategy,
@@ -103,6 +105,24 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
     Arc<NodeIdentity>,
     BaseNodeWalletRpcMockState,
     ConnectivityManagerMockState,
+) {
+    setup_output_manager_service_with_watch_only_keys(runtime, backend, with_connection, None)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn setup_output_manager_service_with_watch_only_keys<T: OutputManagerBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    with_connection: bool,
+    watch_only_keys: Option<PublicRewindKeys>,
+) -> (
+    OutputManagerHandle,
+    Shutdown,
+    TransactionServiceHandle,
+    MockRpcServer<BaseNodeWalletRpcServer<BaseNodeWalletRpcMockService>, Substream>,
+    Arc<NodeIdentity>,
+    BaseNodeWalletRpcMockState,
+    ConnectivityManagerMockState,
 ) {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -168,7 +188,8 @@ pub fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
             connectivity_manager,
             CommsSecretKey::default(),
         ))
-        .unwrap();
+        .unwrap()
+        .with_watch_only_keys(watch_only_keys);
     let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
 
     runtime.spawn(async move { output_manager_service.start().await.unwrap() });
@@ -368,7 +389,7 @@ fn test_utxo_selection_no_chain_metadata() {
     let amount = MicroTari::from(1000);
     let fee_per_gram = MicroTari::from(10);
     let err = runtime
-        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop)))
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop), None))
         .unwrap_err();
     assert!(matches!(err, OutputManagerError::NotEnoughFunds));
 
@@ -385,7 +406,7 @@ fn test_utxo_selection_no_chain_metadata() {
 
     // but we have no chain state so the lowest maturity should be used
     let stp = runtime
-        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop)))
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop), None))
         .unwrap();
     assert!(stp.get_tx_id().is_ok());
 
@@ -453,7 +474,7 @@ fn test_utxo_selection_with_chain_metadata() {
     let amount = MicroTari::from(1000);
     let fee_per_gram = MicroTari::from(10);
     let err = runtime
-        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop)))
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop), None))
         .unwrap_err();
     assert!(matches!(err, OutputManagerError::NotEnoughFunds));
 
@@ -498,7 +519,7 @@ fn test_utxo_selection_with_chain_metadata() {
 
     // test transactions
     let stp = runtime
-        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop)))
+        .block_on(oms.prepare_transaction_to_send(amount, fee_per_gram, None, "".to_string(), script!(Nop), None))
         .unwrap();
     assert!(stp.get_tx_id().is_ok());
 
@@ -514,7 +535,7 @@ fn test_utxo_selection_with_chain_metadata() {
 
     // when the amount is greater than the largest utxo, then "Largest" selection strategy is used
     let stp = runtime
-        .block_on(oms.prepare_transaction_to_send(6 * amount, fee_per_gram, None, "".to_string(), script!(Nop)))
+        .block_on(oms.prepare_transaction_to_send(6 * amount, fee_per_gram, None, "".to_string(), script!(Nop), None))
         .unwrap();
     assert!(stp.get_tx_id().is_ok());
 
@@ -565,6 +586,7 @@ fn sending_transaction_and_confirmation() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
 
@@ -658,6 +680,7 @@ fn send_not_enough_funds() {
         None,
         "".to_string(),
         script!(Nop),
+        None,
     )) {
         Err(OutputManagerError::NotEnoughFunds) => {},
         _ => panic!(),
@@ -703,6 +726,7 @@ fn send_no_change() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
 
@@ -779,6 +803,7 @@ fn send_not_enough_for_change() {
         None,
         "".to_string(),
         script!(Nop),
+        None,
     )) {
         Err(OutputManagerError::NotEnoughFunds) => {},
         _ => panic!(),
@@ -796,7 +821,9 @@ fn receiving_and_confirmation() {
 
     let value = MicroTari::from(5000);
     let (tx_id, sender_message) = generate_sender_transaction_message(value);
-    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let rtp = runtime
+        .block_on(oms.get_recipient_transaction(sender_message, PublicKey::default()))
+        .unwrap();
     assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
     assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
 
@@ -813,6 +840,155 @@ fn receiving_and_confirmation() {
     assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 1);
 }
 
+#[test]
+fn account_balances_and_utxos_are_isolated() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    let account = "savings".to_string();
+    runtime.block_on(oms.create_account(account.clone())).unwrap();
+    assert!(matches!(
+        runtime.block_on(oms.create_account(account.clone())),
+        Err(OutputManagerError::AccountAlreadyExists(_))
+    ));
+    assert!(matches!(
+        runtime.block_on(oms.create_account(DEFAULT_ACCOUNT.to_string())),
+        Err(OutputManagerError::InvalidAccountName(_))
+    ));
+    assert!(matches!(
+        runtime.block_on(oms.get_account_balance("unknown".to_string())),
+        Err(OutputManagerError::AccountNotFound(_))
+    ));
+    assert_eq!(runtime.block_on(oms.get_accounts()).unwrap(), vec![
+        DEFAULT_ACCOUNT.to_string(),
+        account.clone()
+    ]);
+
+    let default_value = MicroTari::from(2000);
+    let (_ti, uo) = make_input(&mut OsRng.clone(), default_value, &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+
+    let account_value = MicroTari::from(100_000);
+    let (tx_id, sender_message) = generate_sender_transaction_message(account_value);
+    let rtp = runtime
+        .block_on(oms.get_recipient_transaction_for_account(sender_message, PublicKey::default(), account.clone()))
+        .unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output]))
+        .unwrap();
+
+    let account_balance = runtime.block_on(oms.get_account_balance(account.clone())).unwrap();
+    assert_eq!(account_balance.available_balance, account_value);
+    let default_balance = runtime
+        .block_on(oms.get_account_balance(DEFAULT_ACCOUNT.to_string()))
+        .unwrap();
+    assert_eq!(default_balance.available_balance, default_value);
+    let balance = runtime.block_on(oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, default_value + account_value);
+
+    let account_utxos = runtime
+        .block_on(oms.get_account_unspent_outputs(account.clone()))
+        .unwrap();
+    assert_eq!(account_utxos.len(), 1);
+    assert_eq!(account_utxos[0].value, account_value);
+
+    // The default account cannot spend the funds of another account
+    match runtime.block_on(oms.prepare_transaction_to_send(
+        MicroTari::from(10_000),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+        script!(Nop),
+        None,
+    )) {
+        Err(OutputManagerError::NotEnoughFunds) => {},
+        _ => panic!("Expected NotEnoughFunds"),
+    }
+
+    runtime
+        .block_on(oms.prepare_transaction_to_send_from_account(
+            MicroTari::from(10_000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            script!(Nop),
+            None,
+            account.clone(),
+        ))
+        .unwrap();
+    let account_balance = runtime.block_on(oms.get_account_balance(account)).unwrap();
+    assert_eq!(account_balance.available_balance, MicroTari::from(0));
+    assert!(account_balance.pending_incoming_balance > MicroTari::from(0));
+    let default_balance = runtime
+        .block_on(oms.get_account_balance(DEFAULT_ACCOUNT.to_string()))
+        .unwrap();
+    assert_eq!(default_balance.available_balance, default_value);
+}
+
+#[test]
+fn pay_to_self_from_account_stays_in_account() {
+    let mut runtime = Runtime::new().unwrap();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    let account = "savings".to_string();
+    runtime.block_on(oms.create_account(account.clone())).unwrap();
+
+    let account_value = MicroTari::from(100_000);
+    let (tx_id, sender_message) = generate_sender_transaction_message(account_value);
+    let rtp = runtime
+        .block_on(oms.get_recipient_transaction_for_account(sender_message, PublicKey::default(), account.clone()))
+        .unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(oms.confirm_transaction(tx_id, vec![], vec![output]))
+        .unwrap();
+
+    // The default account has nothing to fund the transaction with
+    assert!(matches!(
+        runtime.block_on(oms.create_pay_to_self_transaction(
+            MicroTari::from(10_000),
+            MicroTari::from(20),
+            None,
+            "".to_string()
+        )),
+        Err(OutputManagerError::NotEnoughFunds)
+    ));
+
+    let (_, fee, _) = runtime
+        .block_on(oms.create_pay_to_self_transaction_from_account(
+            MicroTari::from(10_000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            account.clone(),
+        ))
+        .unwrap();
+
+    let account_balance = runtime.block_on(oms.get_account_balance(account)).unwrap();
+    assert_eq!(account_balance.available_balance, MicroTari::from(0));
+    assert_eq!(account_balance.pending_incoming_balance, account_value - fee);
+    let default_balance = runtime
+        .block_on(oms.get_account_balance(DEFAULT_ACCOUNT.to_string()))
+        .unwrap();
+    assert_eq!(default_balance.pending_incoming_balance, MicroTari::from(0));
+}
+
 #[test]
 fn cancel_transaction() {
     let factories = CryptoFactories::default();
@@ -840,6 +1016,7 @@ fn cancel_transaction() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
 
@@ -855,6 +1032,154 @@ fn cancel_transaction() {
     assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), num_outputs);
 }
 
+#[test]
+fn prepare_and_cancel_unsigned_transaction() {
+    let factories = CryptoFactories::default();
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    let num_outputs = 20;
+    for _i in 0..num_outputs {
+        let (_ti, uo) = make_input(
+            &mut OsRng.clone(),
+            MicroTari::from(100 + OsRng.next_u64() % 1000),
+            &factories.commitment,
+        );
+        runtime.block_on(oms.add_output(uo)).unwrap();
+    }
+
+    let (_, recipient) = PublicKey::random_keypair(&mut OsRng);
+    let unsigned = runtime
+        .block_on(oms.prepare_unsigned_transaction(
+            recipient,
+            MicroTari::from(1000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+        ))
+        .unwrap();
+
+    // No private keys leave the online wallet, and these outputs were not derived from its key chain
+    assert!(unsigned.inputs.iter().all(|input| input.key_index.is_none()));
+    assert!(!unsigned.to_json().unwrap().contains("spending_key"));
+    // A change output needs a key that was reserved in the key chain
+    assert_eq!(unsigned.change.is_some(), unsigned.change_key_index.is_some());
+    let total: MicroTari = unsigned.inputs.iter().map(|input| input.value).sum();
+    assert_eq!(
+        total,
+        unsigned.amount + unsigned.fee + unsigned.change.unwrap_or_else(|| MicroTari::from(0))
+    );
+    assert_eq!(
+        runtime.block_on(oms.get_unspent_outputs()).unwrap().len(),
+        num_outputs - unsigned.inputs.len()
+    );
+
+    let imported = UnsignedTransaction::from_json(&unsigned.to_json().unwrap()).unwrap();
+    assert_eq!(imported.tx_id, unsigned.tx_id);
+    assert_eq!(imported.fee, unsigned.fee);
+    assert!(imported
+        .inputs
+        .iter()
+        .zip(unsigned.inputs.iter())
+        .all(|(a, b)| a.commitment == b.commitment && a.value == b.value));
+
+    runtime.block_on(oms.cancel_transaction(unsigned.tx_id)).unwrap();
+    assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), num_outputs);
+}
+
+#[test]
+fn watch_only_wallet_tracks_outputs_and_refuses_to_spend() {
+    let factories = CryptoFactories::default();
+
+    let mut runtime = Runtime::new().unwrap();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut oms, _shutdown, _, _, _, _, _) = setup_output_manager_service(&mut runtime, backend, true);
+
+    let (_ti, uo) = make_input(&mut OsRng.clone(), MicroTari::from(100_000), &factories.commitment);
+    runtime.block_on(oms.add_output(uo)).unwrap();
+    let stp = runtime
+        .block_on(oms.prepare_transaction_to_send(
+            MicroTari::from(10_000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            script!(Nop),
+            None,
+        ))
+        .unwrap();
+    let change = stp.get_amount_to_self().unwrap();
+    let tx = runtime.block_on(complete_transaction(stp, oms.clone()));
+    let view_keys = runtime.block_on(oms.get_rewind_public_keys()).unwrap();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let (mut watch_only_oms, _shutdown, _, _, _, _, _) =
+        setup_output_manager_service_with_watch_only_keys(&mut runtime, backend, true, Some(view_keys.clone()));
+
+    assert_eq!(
+        runtime.block_on(watch_only_oms.get_rewind_public_keys()).unwrap(),
+        view_keys
+    );
+
+    // Only the change output belongs to the watched wallet, and it is only picked up once
+    let found = runtime
+        .block_on(watch_only_oms.scan_outputs_for_one_sided_payments(tx.body.outputs().clone()))
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].value, change);
+    let found = runtime
+        .block_on(watch_only_oms.scan_outputs_for_one_sided_payments(tx.body.outputs().clone()))
+        .unwrap();
+    assert!(found.is_empty());
+
+    let balance = runtime.block_on(watch_only_oms.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, change);
+
+    match runtime.block_on(watch_only_oms.prepare_transaction_to_send(
+        MicroTari::from(100),
+        MicroTari::from(20),
+        None,
+        "".to_string(),
+        script!(Nop),
+        None,
+    )) {
+        Err(OutputManagerError::WatchOnlyWallet) => {},
+        _ => panic!("A watch-only wallet must not be able to send"),
+    }
+    assert!(matches!(
+        runtime.block_on(watch_only_oms.get_seed_words()),
+        Err(OutputManagerError::WatchOnlyWallet)
+    ));
+
+    // A watch-only wallet can still prepare a transaction for an offline wallet to sign
+    let (_, recipient) = PublicKey::random_keypair(&mut OsRng);
+    let unsigned = runtime
+        .block_on(watch_only_oms.prepare_unsigned_transaction(
+            recipient,
+            MicroTari::from(1000),
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+        ))
+        .unwrap();
+    assert_eq!(unsigned.inputs.len(), 1);
+    assert_eq!(unsigned.inputs[0].value, change);
+    assert_eq!(unsigned.inputs[0].key_index, None);
+    assert_eq!(unsigned.change_key_index, None);
+    assert!(tx
+        .body
+        .outputs()
+        .iter()
+        .any(|o| o.commitment == unsigned.inputs[0].commitment));
+}
+
 #[test]
 fn timeout_transaction() {
     let factories = CryptoFactories::default();
@@ -881,6 +1206,7 @@ fn timeout_transaction() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
 
@@ -930,14 +1256,23 @@ fn test_get_balance() {
 
     let send_value = MicroTari::from(1000);
     let stp = runtime
-        .block_on(oms.prepare_transaction_to_send(send_value, MicroTari::from(20), None, "".to_string(), script!(Nop)))
+        .block_on(oms.prepare_transaction_to_send(
+            send_value,
+            MicroTari::from(20),
+            None,
+            "".to_string(),
+            script!(Nop),
+            None,
+        ))
         .unwrap();
 
     let change_val = stp.get_change_amount().unwrap();
 
     let recv_value = MicroTari::from(1500);
     let (_tx_id, sender_message) = generate_sender_transaction_message(recv_value);
-    let _rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let _rtp = runtime
+        .block_on(oms.get_recipient_transaction(sender_message, PublicKey::default()))
+        .unwrap();
 
     let balance = runtime.block_on(oms.get_balance()).unwrap();
 
@@ -957,7 +1292,9 @@ fn test_confirming_received_output() {
 
     let value = MicroTari::from(5000);
     let (tx_id, sender_message) = generate_sender_transaction_message(value);
-    let rtp = runtime.block_on(oms.get_recipient_transaction(sender_message)).unwrap();
+    let rtp = runtime
+        .block_on(oms.get_recipient_transaction(sender_message, PublicKey::default()))
+        .unwrap();
     assert_eq!(runtime.block_on(oms.get_unspent_outputs()).unwrap().len(), 0);
     assert_eq!(runtime.block_on(oms.get_pending_transactions()).unwrap().len(), 1);
 
@@ -1004,6 +1341,7 @@ fn sending_transaction_with_short_term_clear() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
 
@@ -1025,6 +1363,7 @@ fn sending_transaction_with_short_term_clear() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
     let sender_tx_id = stp.get_tx_id().unwrap();
@@ -1044,6 +1383,7 @@ fn sending_transaction_with_short_term_clear() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
     let sender_tx_id = stp.get_tx_id().unwrap();
@@ -569,3 +569,58 @@ pub async fn test_no_duplicate_outputs() {
     let outputs = db.get_unspent_outputs().await.unwrap();
     assert_eq!(outputs.len(), 1);
 }
+
+#[tokio_macros::test]
+pub async fn test_reassign_pending_inputs() {
+    let factories = CryptoFactories::default();
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection, None);
+    let db = OutputManagerDatabase::new(backend);
+
+    let mut inputs = Vec::new();
+    for i in 1..3 {
+        let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(1000 * i), &factories.commitment);
+        let uo = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+        db.add_unspent_output(uo.clone()).await.unwrap();
+        inputs.push(uo);
+    }
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(900), &factories.commitment);
+    let original_change = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+    let (_ti, uo) = make_input(&mut OsRng, MicroTari::from(800), &factories.commitment);
+    let replacement_change = DbUnblindedOutput::from_unblinded_output(uo, &factories).unwrap();
+
+    let original_tx_id = 1u64;
+    let replacement_tx_id = 2u64;
+    db.encumber_outputs(original_tx_id, inputs.clone(), vec![original_change])
+        .await
+        .unwrap();
+    db.confirm_encumbered_outputs(original_tx_id).await.unwrap();
+    db.encumber_outputs(replacement_tx_id, vec![], vec![replacement_change.clone()])
+        .await
+        .unwrap();
+    db.confirm_encumbered_outputs(replacement_tx_id).await.unwrap();
+
+    assert!(matches!(
+        db.reassign_pending_inputs(original_tx_id, 3u64).await,
+        Err(OutputManagerStorageError::ValueNotFound)
+    ));
+    db.reassign_pending_inputs(original_tx_id, replacement_tx_id)
+        .await
+        .unwrap();
+
+    let original = db.fetch_pending_transaction_outputs(original_tx_id).await.unwrap();
+    assert!(original.outputs_to_be_spent.is_empty());
+    assert_eq!(original.outputs_to_be_received.len(), 1);
+    let replacement = db.fetch_pending_transaction_outputs(replacement_tx_id).await.unwrap();
+    assert_eq!(replacement.outputs_to_be_spent.len(), inputs.len());
+
+    // Cancelling the replaced transaction must not release the inputs now held by the replacement
+    db.cancel_pending_transaction_outputs(original_tx_id).await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
+    assert_eq!(balance.available_balance, MicroTari(0));
+
+    db.confirm_pending_transaction_outputs(replacement_tx_id).await.unwrap();
+    let balance = db.get_balance(None).await.unwrap();
+    assert_eq!(balance.available_balance, replacement_change.unblinded_output.value);
+    assert_eq!(db.fetch_spent_outputs().await.unwrap().len(), inputs.len());
+}
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::HashMap,
     convert::TryFrom,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
@@ -34,9 +35,13 @@ use tari_core::{
     proto::{
         base_node::{
             ChainMetadata,
+            FeeEstimateRequest,
+            FeeEstimateResponse,
             FetchMatchingUtxos,
+            FetchSpendingBlocksResponse,
             FetchUtxosResponse,
             Signatures as SignaturesProto,
+            SpendingBlock,
             TipInfoResponse,
             TxQueryBatchResponse as TxQueryBatchResponseProto,
             TxQueryBatchResponses as TxQueryBatchResponsesProto,
@@ -82,12 +87,15 @@ pub struct BaseNodeWalletRpcMockState {
     transaction_batch_query_calls: Arc<Mutex<Vec<Vec<Signature>>>>,
     submit_transaction_response: Arc<Mutex<TxSubmissionResponse>>,
     transaction_query_response: Arc<Mutex<TxQueryResponse>>,
+    transaction_query_responses_by_signature: Arc<Mutex<HashMap<Signature, TxQueryResponse>>>,
     tip_info_response: Arc<Mutex<TipInfoResponse>>,
+    fee_estimate_response: Arc<Mutex<FeeEstimateResponse>>,
     fetch_utxos_calls: Arc<Mutex<Vec<Vec<Vec<u8>>>>>,
     response_delay: Arc<Mutex<Option<Duration>>>,
     rpc_status_error: Arc<Mutex<Option<RpcStatus>>>,
     synced: Arc<Mutex<bool>>,
     utxos: Arc<Mutex<Vec<TransactionOutput>>>,
+    spending_blocks: Arc<Mutex<Vec<SpendingBlock>>>,
 }
 
 #[allow(clippy::mutex_atomic)]
@@ -109,6 +117,7 @@ impl BaseNodeWalletRpcMockState {
                 is_synced: true,
                 height_of_longest_chain: 0,
             })),
+            transaction_query_responses_by_signature: Arc::new(Mutex::new(HashMap::new())),
             tip_info_response: Arc::new(Mutex::new(TipInfoResponse {
                 metadata: Some(ChainMetadata {
                     height_of_longest_chain: Some(std::u64::MAX),
@@ -119,11 +128,16 @@ impl BaseNodeWalletRpcMockState {
                 }),
                 is_synced: true,
             })),
+            fee_estimate_response: Arc::new(Mutex::new(FeeEstimateResponse {
+                fee_per_gram: 25,
+                is_synced: true,
+            })),
             fetch_utxos_calls: Arc::new(Mutex::new(Vec::new())),
             response_delay: Arc::new(Mutex::new(None)),
             rpc_status_error: Arc::new(Mutex::new(None)),
             synced: Arc::new(Mutex::new(true)),
             utxos: Arc::new(Mutex::new(Vec::new())),
+            spending_blocks: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -132,6 +146,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = response;
     }
 
+    pub fn set_fee_estimate_response(&self, response: FeeEstimateResponse) {
+        let mut lock = acquire_lock!(self.fee_estimate_response);
+        *lock = response;
+    }
+
     pub fn set_submit_transaction_response(&self, response: TxSubmissionResponse) {
         let mut lock = acquire_lock!(self.submit_transaction_response);
         *lock = response;
@@ -142,6 +161,20 @@ impl BaseNodeWalletRpcMockState {
         *lock = response;
     }
 
+    /// Sets the response to queries for the transaction with the given excess signature, overriding the response set
+    /// by `set_transaction_query_response`
+    pub fn set_transaction_query_response_for_signature(&self, signature: Signature, response: TxQueryResponse) {
+        let mut lock = acquire_lock!(self.transaction_query_responses_by_signature);
+        lock.insert(signature, response);
+    }
+
+    fn transaction_query_response_for(&self, signature: &Signature) -> TxQueryResponse {
+        match acquire_lock!(self.transaction_query_responses_by_signature).get(signature) {
+            Some(response) => response.clone(),
+            None => acquire_lock!(self.transaction_query_response).clone(),
+        }
+    }
+
     pub fn set_response_delay(&mut self, delay: Option<Duration>) {
         let mut lock = acquire_lock!(self.response_delay);
         *lock = delay;
@@ -163,6 +196,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = utxos;
     }
 
+    pub fn set_spending_blocks(&self, blocks: Vec<SpendingBlock>) {
+        let mut lock = acquire_lock!(self.spending_blocks);
+        *lock = blocks;
+    }
+
     pub fn take_submit_transaction_calls(&self) -> Vec<Transaction> {
         acquire_lock!(self.submit_transaction_calls).drain(..).collect()
     }
@@ -350,16 +388,16 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
         log::info!("Transaction Query call received: {:?}", signature);
 
         let mut transaction_query_calls_lock = acquire_lock!(self.state.transaction_query_calls);
-        (*transaction_query_calls_lock).push(signature);
+        (*transaction_query_calls_lock).push(signature.clone());
 
         let status_lock = acquire_lock!(self.state.rpc_status_error);
         if let Some(status) = (*status_lock).clone() {
             return Err(status);
         }
 
-        let transaction_query_response_lock = acquire_lock!(self.state.transaction_query_response);
-
-        Ok(Response::new(transaction_query_response_lock.clone().into()))
+        Ok(Response::new(
+            self.state.transaction_query_response_for(&signature).into(),
+        ))
     }
 
     async fn transaction_batch_query(
@@ -387,10 +425,9 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             return Err(status);
         }
 
-        let transaction_query_response_lock = acquire_lock!(self.state.transaction_query_response);
-        let transaction_query_response = TxQueryResponseProto::from(transaction_query_response_lock.clone());
         let mut responses = Vec::new();
         for sig in signatures.iter() {
+            let transaction_query_response = TxQueryResponseProto::from(self.state.transaction_query_response_for(sig));
             let response = TxQueryBatchResponseProto {
                 signature: Some(sig.clone().into()),
                 location: transaction_query_response.location,
@@ -459,6 +496,64 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
 
         Ok(Response::new(tip_info_response_lock.clone()))
     }
+
+    async fn get_fee_estimate(
+        &self,
+        _request: Request<FeeEstimateRequest>,
+    ) -> Result<Response<FeeEstimateResponse>, RpcStatus> {
+        let delay_lock = *acquire_lock!(self.state.response_delay);
+        if let Some(delay) = delay_lock {
+            delay_for(delay).await;
+        }
+
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let fee_estimate_response_lock = acquire_lock!(self.state.fee_estimate_response);
+
+        Ok(Response::new(fee_estimate_response_lock.clone()))
+    }
+
+    async fn fetch_spending_blocks(
+        &self,
+        request: Request<FetchMatchingUtxos>,
+    ) -> Result<Response<FetchSpendingBlocksResponse>, RpcStatus> {
+        let delay_lock = *acquire_lock!(self.state.response_delay);
+        if let Some(delay) = delay_lock {
+            delay_for(delay).await;
+        }
+
+        let message = request.into_message();
+
+        let status_lock = acquire_lock!(self.state.rpc_status_error);
+        if let Some(status) = (*status_lock).clone() {
+            return Err(status);
+        }
+
+        let blocks_lock = acquire_lock!(self.state.spending_blocks);
+        let blocks = (*blocks_lock)
+            .iter()
+            .cloned()
+            .filter_map(|mut block| {
+                block
+                    .spent_output_hashes
+                    .retain(|hash| message.output_hashes.contains(hash));
+                if block.spent_output_hashes.is_empty() {
+                    None
+                } else {
+                    Some(block)
+                }
+            })
+            .collect();
+
+        let sync_lock = acquire_lock!(self.state.synced);
+        Ok(Response::new(FetchSpendingBlocksResponse {
+            blocks,
+            is_synced: *sync_lock,
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -73,8 +73,12 @@ use tari_core::{
         fee::Fee,
         helpers::{create_unblinded_output, TestParams as TestParamsHelpers},
         tari_amount::*,
-        transaction::{KernelBuilder, KernelFeatures, OutputFeatures, Transaction},
-        transaction_protocol::{proto, recipient::RecipientSignedMessage, sender::TransactionSenderMessage},
+        transaction::{KernelBuilder, KernelFeatures, OutputFeatures, Transaction, TransactionOutput},
+        transaction_protocol::{
+            proto,
+            recipient::{RecipientSignedMessage, RecipientState},
+            sender::TransactionSenderMessage,
+        },
         types::{CryptoFactories, PrivateKey, PublicKey, Signature},
         ReceiverTransactionProtocol,
         SenderTransactionProtocol,
@@ -87,6 +91,7 @@ use tari_crypto::{
     keys::{PublicKey as PK, SecretKey as SK},
     script,
     script::{ExecutionStack, TariScript},
+    tari_utilities::Hashable,
 };
 use tari_p2p::{comms_connector::pubsub_connector, domain_message::DomainMessage, Network};
 use tari_service_framework::{reply_channel, RegisterHandle, StackBuilder};
@@ -101,14 +106,18 @@ use tari_wallet::{
     },
     output_manager_service::{
         config::OutputManagerServiceConfig,
-        handle::OutputManagerHandle,
-        service::OutputManagerService,
+        error::OutputManagerError,
+        handle::{OutputManagerHandle, PublicRewindKeys},
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+        service::{OutputManagerService, UTXOSelectionStrategy},
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase},
             models::KnownOneSidedPaymentScript,
             sqlite_db::OutputManagerSqliteDatabase,
         },
         OutputManagerServiceInitializer,
+        TxoValidationType,
+        DEFAULT_ACCOUNT,
     },
     storage::{
         database::{WalletBackend, WalletDatabase},
@@ -245,7 +254,7 @@ pub fn setup_transaction_service_no_comms<T: TransactionBackend + 'static, K: Ou
     Arc<NodeIdentity>,
     BaseNodeWalletRpcMockState,
 ) {
-    setup_transaction_service_no_comms_and_oms_backend(runtime, factories, tx_backend, oms_backend, config)
+    setup_transaction_service_no_comms_and_oms_backend(runtime, factories, tx_backend, oms_backend, config, None)
 }
 
 #[allow(clippy::type_complexity)]
@@ -258,6 +267,7 @@ pub fn setup_transaction_service_no_comms_and_oms_backend<
     tx_backend: T,
     oms_backend: S,
     config: Option<TransactionServiceConfig>,
+    watch_only_keys: Option<PublicRewindKeys>,
 ) -> (
     TransactionServiceHandle,
     OutputManagerHandle,
@@ -340,7 +350,8 @@ pub fn setup_transaction_service_no_comms_and_oms_backend<
             connectivity_manager.clone(),
             CommsSecretKey::default(),
         ))
-        .unwrap();
+        .unwrap()
+        .with_watch_only_keys(watch_only_keys.clone());
 
     let output_manager_service_handle = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
 
@@ -377,7 +388,8 @@ pub fn setup_transaction_service_no_comms_and_oms_backend<
         )),
         factories,
         shutdown.to_signal(),
-    );
+    )
+    .with_watch_only(watch_only_keys.is_some());
     runtime.spawn(async move { output_manager_service.start().await.unwrap() });
     runtime.spawn(async move { ts_service.start().await.unwrap() });
     (
@@ -1437,6 +1449,7 @@ fn finalize_tx_with_incorrect_pubkey() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
     let msg = stp.build_single_round_message().unwrap();
@@ -1563,6 +1576,7 @@ fn finalize_tx_with_missing_output() {
             None,
             "".to_string(),
             script!(Nop),
+            None,
         ))
         .unwrap();
     let msg = stp.build_single_round_message().unwrap();
@@ -1843,6 +1857,8 @@ fn test_power_mode_updates() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        replaced_by: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -1863,6 +1879,8 @@ fn test_power_mode_updates() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        replaced_by: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
 
     tx_backend
@@ -2808,6 +2826,7 @@ fn test_restarting_transaction_protocols() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
 
     alice_backend
@@ -2830,6 +2849,7 @@ fn test_restarting_transaction_protocols() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
     bob_backend
         .write(WriteOperation::Insert(DbKeyValuePair::PendingOutboundTransaction(
@@ -3885,6 +3905,7 @@ fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        account: DEFAULT_ACCOUNT.to_string(),
     };
     let (_, alice_backend, oms_backend, _, _temp_dir) = make_wallet_databases(None);
     alice_backend
@@ -3989,6 +4010,7 @@ fn test_resend_on_startup() {
         direct_send_success: false,
         send_count: 0,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        account: DEFAULT_ACCOUNT.to_string(),
     };
     let (_, bob_backend, bob_oms_backend, _, _temp_dir) = make_wallet_databases(None);
 
@@ -4337,6 +4359,7 @@ fn test_transaction_timeout_cancellation() {
         direct_send_success: false,
         send_count: 1,
         last_send_timestamp: Some(Utc::now().naive_utc()),
+        account: DEFAULT_ACCOUNT.to_string(),
     };
     let (_, bob_backend, bob_oms_backend, _, _temp_dir) = make_wallet_databases(None);
 
@@ -4789,6 +4812,8 @@ fn broadcast_all_completed_transactions_on_startup() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        replaced_by: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -5127,6 +5152,8 @@ fn only_start_one_tx_broadcast_protocol_at_a_time() {
         valid: true,
         confirmations: None,
         mined_height: None,
+        replaced_by: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
 
     backend
@@ -5195,6 +5222,8 @@ fn dont_broadcast_invalid_transactions() {
         valid: false,
         confirmations: None,
         mined_height: None,
+        replaced_by: None,
+        account: DEFAULT_ACCOUNT.to_string(),
     };
 
     backend
@@ -5399,3 +5428,580 @@ fn start_validation_protocol_then_broadcast_protocol_change_base_node() {
         assert!(tx.valid);
     }
 }
+
+#[test]
+fn send_transaction_with_privacy_utxo_selection() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let temp_dir = tempdir().unwrap();
+    let db_folder = temp_dir.path().to_str().unwrap().to_string();
+    let connection = run_migration_and_create_sqlite_connection(&format!("{}/{}", db_folder, db_name)).unwrap();
+
+    let backend = TransactionServiceSqliteDatabase::new(connection.clone(), None);
+    let oms_backend = OutputManagerSqliteDatabase::new(connection, None);
+
+    let (mut alice_ts, mut alice_output_manager, _, _, _, _, _, _, _, _shutdown, _, _, _) =
+        setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend, oms_backend, None);
+
+    let (_, bob) = PublicKey::random_keypair(&mut OsRng);
+    let (_, carol) = PublicKey::random_keypair(&mut OsRng);
+    let (_, dave) = PublicKey::random_keypair(&mut OsRng);
+
+    // Alice receives one output from Bob and two from Carol
+    for (value, source) in vec![
+        (60_000 * uT, bob.clone()),
+        (30_000 * uT, carol.clone()),
+        (30_000 * uT, carol.clone()),
+    ] {
+        let mut builder = SenderTransactionProtocol::builder(1);
+        let (utxo, input) = make_input(&mut OsRng, 2 * value, &factories.commitment);
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(MicroTari::from(20))
+            .with_offset(PrivateKey::random(&mut OsRng))
+            .with_private_nonce(PrivateKey::random(&mut OsRng))
+            .with_change_secret(PrivateKey::random(&mut OsRng))
+            .with_input(utxo, input)
+            .with_amount(0, value)
+            .with_recipient_data(
+                0,
+                script!(Nop),
+                PrivateKey::random(&mut OsRng),
+                OutputFeatures::default(),
+                PrivateKey::random(&mut OsRng),
+            )
+            .with_change_script(script!(Nop), ExecutionStack::default(), PrivateKey::random(&mut OsRng));
+        let mut stp = builder.build::<HashDigest>(&factories).unwrap();
+        let tx_id = stp.get_tx_id().unwrap();
+        let sender_message =
+            TransactionSenderMessage::new_single_round_message(stp.build_single_round_message().unwrap());
+        let rtp = runtime
+            .block_on(alice_output_manager.get_recipient_transaction(sender_message, source))
+            .unwrap();
+        let output = match rtp.state {
+            RecipientState::Finalized(s) => s.output,
+            RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+        };
+        runtime
+            .block_on(alice_output_manager.confirm_transaction(tx_id, vec![], vec![output]))
+            .unwrap();
+    }
+
+    // The wallet holds enough in total, but only by linking Bob and Carol
+    let err = runtime
+        .block_on(alice_ts.send_transaction_with_utxo_selection(
+            dave.clone(),
+            70_000 * uT,
+            20 * uT,
+            "Linked".to_string(),
+            Some(UTXOSelectionStrategy::Privacy),
+        ))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TransactionServiceError::OutputManagerError(OutputManagerError::InsufficientSingleSourceFunds)
+    ));
+
+    for (amount, source) in vec![(40_000 * uT, bob), (50_000 * uT, carol)] {
+        let tx_id = runtime
+            .block_on(alice_ts.send_transaction_with_utxo_selection(
+                dave.clone(),
+                amount,
+                20 * uT,
+                "Private".to_string(),
+                Some(UTXOSelectionStrategy::Privacy),
+            ))
+            .unwrap();
+
+        let pending = runtime
+            .block_on(alice_output_manager.get_pending_transactions())
+            .unwrap()
+            .remove(&tx_id)
+            .unwrap();
+        assert!(!pending.outputs_to_be_spent.is_empty());
+        assert!(pending
+            .outputs_to_be_spent
+            .iter()
+            .all(|o| o.received_from.as_ref() == Some(&source)));
+        // The change stays with the counterparty that funded it
+        assert_eq!(pending.outputs_to_be_received.len(), 1);
+        assert_eq!(pending.outputs_to_be_received[0].received_from, Some(source));
+    }
+}
+
+/// Sends a one-sided transaction, replaces it with a fee bump and then mines either the original or the replacement.
+/// The version that was not mined must be cancelled and the balance must reflect the fee of the mined version.
+fn fee_bump_and_mine(mine_replacement: bool) {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (_, backend, oms_backend, _, _temp_dir) = make_wallet_databases(None);
+    let (
+        mut alice_ts,
+        mut alice_output_manager,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _,
+        _shutdown,
+        _mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+    ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend, oms_backend, None);
+    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+
+    runtime
+        .block_on(alice_ts.set_base_node_public_key(server_node_identity.public_key().clone()))
+        .unwrap();
+
+    let initial_value = MicroTari(1_000_000);
+    let (_utxo, uo) = make_input(&mut OsRng, initial_value, &factories.commitment);
+    runtime.block_on(alice_output_manager.add_output(uo)).unwrap();
+
+    let (_, bob) = PublicKey::random_keypair(&mut OsRng);
+    let amount = MicroTari(100_000);
+    let tx_id = runtime
+        .block_on(alice_ts.send_one_sided_transaction(bob, amount, 5 * uT, "Stuck".to_string()))
+        .unwrap();
+    let _ = runtime
+        .block_on(rpc_service_state.wait_pop_submit_transaction_calls(1, Duration::from_secs(30)))
+        .expect("Should receive the original tx submission");
+
+    // The mempool only accepts a replacement that raises the fee per gram by at least the minimum increment
+    let err = runtime.block_on(alice_ts.bump_fee(tx_id, 5 * uT)).unwrap_err();
+    assert!(matches!(err, TransactionServiceError::FeeBumpError(_)));
+
+    let replacement_tx_id = runtime.block_on(alice_ts.bump_fee(tx_id, 6 * uT)).unwrap();
+    let _ = runtime
+        .block_on(rpc_service_state.wait_pop_submit_transaction_calls(1, Duration::from_secs(30)))
+        .expect("Should receive the replacement tx submission");
+
+    let original = runtime.block_on(alice_ts.get_completed_transaction(tx_id)).unwrap();
+    assert_eq!(original.replaced_by, Some(replacement_tx_id));
+    let replacement = runtime
+        .block_on(alice_ts.get_completed_transaction(replacement_tx_id))
+        .unwrap();
+    assert!(replacement.fee > original.fee);
+    // A transaction that has been replaced cannot be replaced again
+    assert!(matches!(
+        runtime.block_on(alice_ts.bump_fee(tx_id, 7 * uT)),
+        Err(TransactionServiceError::FeeBumpError(_))
+    ));
+
+    let (mined, other) = if mine_replacement {
+        (replacement, original)
+    } else {
+        (original, replacement)
+    };
+    rpc_service_state.set_transaction_query_response_for_signature(
+        mined.transaction.first_kernel_excess_sig().unwrap().clone(),
+        TxQueryResponse {
+            location: TxLocation::Mined,
+            block_hash: None,
+            confirmations: TransactionServiceConfig::default().num_confirmations_required,
+            is_synced: true,
+            height_of_longest_chain: 0,
+        },
+    );
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut mined_event = false;
+        let mut cancelled_event = false;
+        loop {
+            futures::select! {
+                event = alice_event_stream.select_next_some() => {
+                    match &*event.unwrap() {
+                        TransactionEvent::TransactionMined(id) if *id == mined.tx_id => mined_event = true,
+                        TransactionEvent::TransactionCancelled(id) if *id == other.tx_id => cancelled_event = true,
+                        _ => (),
+                    }
+                    if mined_event && cancelled_event {
+                        break;
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(
+            mined_event,
+            "The {} should have been mined",
+            if mine_replacement { "replacement" } else { "original" }
+        );
+        assert!(
+            cancelled_event,
+            "The version that was not mined should have been cancelled"
+        );
+    });
+
+    let cancelled = runtime
+        .block_on(alice_ts.get_cancelled_completed_transactions())
+        .unwrap();
+    assert!(cancelled.contains_key(&other.tx_id));
+    assert!(!cancelled.contains_key(&mined.tx_id));
+
+    // Only the inputs and fee of the mined version are spent, the outputs of the other version are discarded
+    let balance = runtime.block_on(alice_output_manager.get_balance()).unwrap();
+    assert_eq!(balance.available_balance, initial_value - amount - mined.fee);
+    assert_eq!(balance.pending_incoming_balance, MicroTari(0));
+    assert_eq!(balance.pending_outgoing_balance, MicroTari(0));
+}
+
+#[test]
+fn fee_bump_original_mined_cancels_replacement() {
+    fee_bump_and_mine(false);
+}
+
+#[test]
+fn fee_bump_replacement_mined_cancels_original() {
+    fee_bump_and_mine(true);
+}
+
+#[test]
+fn fee_bump_rejects_standard_transactions() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let alice_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    let (_, backend, oms_backend, _, _temp_dir) = make_wallet_databases(None);
+    let (
+        mut alice_ts,
+        mut alice_output_manager,
+        alice_outbound_service,
+        _,
+        _,
+        mut alice_tx_ack_sender,
+        _,
+        _,
+        _,
+        _shutdown,
+        _,
+        _,
+        _,
+    ) = setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend, oms_backend, None);
+    let mut alice_event_stream = alice_ts.get_event_stream_fused();
+
+    let (_, backend2, oms_backend2, _, _temp_dir2) = make_wallet_databases(None);
+    let (_bob_ts, _bob_output_manager, bob_outbound_service, _, mut bob_tx_sender, _, _, _, _, _shutdown2, _, _, _) =
+        setup_transaction_service_no_comms(&mut runtime, factories.clone(), backend2, oms_backend2, None);
+
+    let (_utxo, uo) = make_input(&mut OsRng, MicroTari(250_000), &factories.commitment);
+    runtime.block_on(alice_output_manager.add_output(uo)).unwrap();
+
+    // Complete a standard Mimblewimble transaction between Alice and Bob
+    let tx_id = runtime
+        .block_on(alice_ts.send_transaction(
+            bob_node_identity.public_key().clone(),
+            MicroTari(10_000),
+            5 * uT,
+            "Interactive".to_string(),
+        ))
+        .unwrap();
+    alice_outbound_service
+        .wait_call_count(2, Duration::from_secs(60))
+        .expect("Alice call wait 1");
+    let _ = alice_outbound_service.pop_call().unwrap();
+    let call = alice_outbound_service.pop_call().unwrap();
+    let tx_sender_msg = try_decode_sender_message(call.1.to_vec()).unwrap();
+    runtime
+        .block_on(bob_tx_sender.send(create_dummy_message(
+            tx_sender_msg.into(),
+            alice_node_identity.public_key(),
+        )))
+        .unwrap();
+    bob_outbound_service
+        .wait_call_count(2, Duration::from_secs(60))
+        .expect("Bob call wait 1");
+    let _ = bob_outbound_service.pop_call().unwrap();
+    let call = bob_outbound_service.pop_call().unwrap();
+    let bob_tx_reply = try_decode_transaction_reply_message(call.1.to_vec()).unwrap();
+    runtime
+        .block_on(alice_tx_ack_sender.send(create_dummy_message(
+            bob_tx_reply.into(),
+            bob_node_identity.public_key(),
+        )))
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        let mut reply_received = false;
+        loop {
+            futures::select! {
+                event = alice_event_stream.select_next_some() => {
+                    if let TransactionEvent::ReceivedTransactionReply(id) = &*event.unwrap() {
+                        if *id == tx_id {
+                            reply_received = true;
+                            break;
+                        }
+                    }
+                },
+                () = delay => {
+                    break;
+                },
+            }
+        }
+        assert!(reply_received);
+    });
+
+    let completed = runtime.block_on(alice_ts.get_completed_transaction(tx_id)).unwrap();
+    assert_eq!(completed.status, TransactionStatus::Completed);
+
+    // Bob holds a key to the transaction, so Alice cannot rebuild it on her own
+    match runtime.block_on(alice_ts.bump_fee(tx_id, 50 * uT)) {
+        Err(TransactionServiceError::FeeBumpError(msg)) => {
+            assert!(msg.contains("Only one-sided and pay-to-self transactions can be replaced"))
+        },
+        r => panic!("Unexpected fee bump result: {:?}", r),
+    }
+    assert!(runtime
+        .block_on(alice_ts.get_completed_transaction(tx_id))
+        .unwrap()
+        .replaced_by
+        .is_none());
+}
+
+/// Receive an output of `value` from `source` on the wallet's UTXO key chain, returning the output as it appears on
+/// chain
+fn receive_key_chain_output(
+    runtime: &mut Runtime,
+    output_manager: &mut OutputManagerHandle,
+    factories: &CryptoFactories,
+    value: MicroTari,
+    source: PublicKey,
+) -> TransactionOutput {
+    let mut builder = SenderTransactionProtocol::builder(1);
+    let (utxo, input) = make_input(&mut OsRng, 2 * value, &factories.commitment);
+    builder
+        .with_lock_height(0)
+        .with_fee_per_gram(MicroTari::from(20))
+        .with_offset(PrivateKey::random(&mut OsRng))
+        .with_private_nonce(PrivateKey::random(&mut OsRng))
+        .with_change_secret(PrivateKey::random(&mut OsRng))
+        .with_input(utxo, input)
+        .with_amount(0, value)
+        .with_recipient_data(
+            0,
+            script!(Nop),
+            PrivateKey::random(&mut OsRng),
+            OutputFeatures::default(),
+            PrivateKey::random(&mut OsRng),
+        )
+        .with_change_script(script!(Nop), ExecutionStack::default(), PrivateKey::random(&mut OsRng));
+    let mut stp = builder.build::<HashDigest>(factories).unwrap();
+    let tx_id = stp.get_tx_id().unwrap();
+    let sender_message = TransactionSenderMessage::new_single_round_message(stp.build_single_round_message().unwrap());
+    let rtp = runtime
+        .block_on(output_manager.get_recipient_transaction(sender_message, source))
+        .unwrap();
+    let output = match rtp.state {
+        RecipientState::Finalized(s) => s.output,
+        RecipientState::Failed(_) => panic!("Should not be in Failed state"),
+    };
+    runtime
+        .block_on(output_manager.confirm_transaction(tx_id, vec![], vec![output.clone()]))
+        .unwrap();
+    output
+}
+
+#[test]
+fn offline_signing_round_trip() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    // Both wallets are set up from the same master key, one stays online and the other signs offline
+    let (_, online_backend, online_oms_backend, _, _online_temp_dir) = make_wallet_databases(None);
+    let (mut online_ts, mut online_oms, _, _, _, _, _, _, _, _online_shutdown, _, _, _) =
+        setup_transaction_service_no_comms(
+            &mut runtime,
+            factories.clone(),
+            online_backend,
+            online_oms_backend,
+            None,
+        );
+    let (_, signer_backend, signer_oms_backend, _, _signer_temp_dir) = make_wallet_databases(None);
+    let (mut signer_ts, _, _, _, _, _, _, _, _, _signer_shutdown, _, _, _) = setup_transaction_service_no_comms(
+        &mut runtime,
+        factories.clone(),
+        signer_backend,
+        signer_oms_backend,
+        None,
+    );
+
+    let (_, source) = PublicKey::random_keypair(&mut OsRng);
+    let (_, recipient) = PublicKey::random_keypair(&mut OsRng);
+    for value in vec![200_000 * uT, 300_000 * uT] {
+        receive_key_chain_output(&mut runtime, &mut online_oms, &factories, value, source.clone());
+    }
+
+    let unsigned = runtime
+        .block_on(online_ts.prepare_unsigned_transaction(
+            recipient.clone(),
+            250_000 * uT,
+            MicroTari::from(20),
+            "offline".to_string(),
+        ))
+        .unwrap();
+    assert!(unsigned.inputs.iter().all(|input| input.key_index.is_some()));
+    assert!(unsigned.change.is_some());
+    assert!(unsigned.change_key_index.is_some());
+    let json = unsigned.to_json().unwrap();
+    assert!(!json.contains("spending_key"));
+
+    // The reserved change key must not be issued to an output received before the signed transaction is imported
+    receive_key_chain_output(&mut runtime, &mut online_oms, &factories, 50_000 * uT, source);
+    let unspent_keys = runtime
+        .block_on(online_oms.get_unspent_outputs())
+        .unwrap()
+        .into_iter()
+        .map(|o| o.spending_key)
+        .collect::<Vec<_>>();
+
+    let signed = runtime
+        .block_on(signer_ts.sign_transaction(UnsignedTransaction::from_json(&json).unwrap()))
+        .unwrap();
+    assert!(signed
+        .transaction
+        .validate_internal_consistency(&factories, None)
+        .is_ok());
+    assert_eq!(signed.change_key_index, unsigned.change_key_index);
+    let signed = SignedTransaction::from_json(&signed.to_json().unwrap()).unwrap();
+
+    let tx_id = runtime
+        .block_on(online_ts.import_signed_transaction(signed.clone()))
+        .unwrap();
+    assert_eq!(tx_id, unsigned.tx_id);
+    assert!(runtime.block_on(online_ts.get_completed_transaction(tx_id)).is_ok());
+
+    // The change output is recovered with the key that was reserved for it
+    let pending = runtime.block_on(online_oms.get_pending_transactions()).unwrap();
+    let change = &pending.get(&tx_id).unwrap().outputs_to_be_received;
+    assert_eq!(change.len(), 1);
+    let change = &change[0].unblinded_output;
+    assert_eq!(Some(change.value), signed.change);
+    assert!(!unspent_keys.contains(&change.spending_key));
+    let commitment = factories
+        .commitment
+        .commit_value(&change.spending_key, change.value.as_u64());
+    assert!(signed
+        .transaction
+        .body
+        .outputs()
+        .iter()
+        .any(|o| o.commitment == commitment));
+
+    match runtime.block_on(online_ts.import_signed_transaction(signed)) {
+        Err(TransactionServiceError::OfflineSigningError(_)) => {},
+        r => panic!("A signed transaction must only be imported once: {:?}", r),
+    }
+}
+
+#[test]
+fn watch_only_wallet_records_spends_and_refuses_to_send() {
+    let factories = CryptoFactories::default();
+    let mut runtime = Runtime::new().unwrap();
+
+    let (_, tx_backend, oms_backend, _, _temp_dir) = make_wallet_databases(None);
+    let (_, mut full_oms, _, _, _, _, _, _, _, _full_shutdown, _, _, _) =
+        setup_transaction_service_no_comms(&mut runtime, factories.clone(), tx_backend, oms_backend, None);
+    let (_, source) = PublicKey::random_keypair(&mut OsRng);
+    let output = receive_key_chain_output(&mut runtime, &mut full_oms, &factories, 100_000 * uT, source.clone());
+    let change = receive_key_chain_output(&mut runtime, &mut full_oms, &factories, 30_000 * uT, source);
+    let view_keys = runtime.block_on(full_oms.get_rewind_public_keys()).unwrap();
+
+    let (_, tx_backend, oms_backend, _, _watch_only_temp_dir) = make_wallet_databases(None);
+    let (mut ts, mut oms, _, _, _, _, _, _, _, _shutdown, _, server_node_identity, rpc_service_state) =
+        setup_transaction_service_no_comms_and_oms_backend(
+            &mut runtime,
+            factories.clone(),
+            tx_backend,
+            oms_backend,
+            None,
+            Some(view_keys),
+        );
+    let output_hash = output.hash();
+    let found = runtime
+        .block_on(oms.scan_outputs_for_one_sided_payments(vec![output]))
+        .unwrap();
+    assert_eq!(found.len(), 1);
+
+    // Sending is refused before any transaction is started
+    let (_, recipient) = PublicKey::random_keypair(&mut OsRng);
+    assert!(matches!(
+        runtime.block_on(ts.send_transaction(recipient.clone(), 1000 * uT, 20 * uT, "".to_string())),
+        Err(TransactionServiceError::WatchOnlyWallet)
+    ));
+    assert!(matches!(
+        runtime.block_on(ts.send_one_sided_transaction(recipient, 1000 * uT, 20 * uT, "".to_string())),
+        Err(TransactionServiceError::WatchOnlyWallet)
+    ));
+    assert!(runtime
+        .block_on(ts.get_pending_outbound_transactions())
+        .unwrap()
+        .is_empty());
+
+    // The wallet holding the keys spends the output, so it is no longer in the UTXO set, and receives its change in the
+    // same block
+    let (_, foreign_output) = make_input(&mut OsRng, 50_000 * uT, &factories.commitment);
+    let foreign_output = foreign_output.as_transaction_output(&factories).unwrap();
+    let mut event_stream = ts.get_event_stream_fused();
+    rpc_service_state.set_utxos(vec![change.clone()]);
+    rpc_service_state.set_spending_blocks(vec![base_node_proto::SpendingBlock {
+        block_hash: vec![1u8; 32],
+        height: 10,
+        spent_output_hashes: vec![output_hash],
+        outputs: vec![change.clone().into(), foreign_output.into()],
+    }]);
+    runtime
+        .block_on(oms.set_base_node_public_key(server_node_identity.public_key().clone()))
+        .unwrap();
+    runtime
+        .block_on(oms.validate_txos(TxoValidationType::Unspent, ValidationRetryStrategy::UntilSuccess))
+        .unwrap();
+
+    let tx_id = runtime.block_on(async {
+        let mut delay = delay_for(Duration::from_secs(60)).fuse();
+        loop {
+            futures::select! {
+                event = event_stream.select_next_some() => {
+                    if let TransactionEvent::TransactionImported(tx_id) = &*event.unwrap() {
+                        break Some(*tx_id);
+                    }
+                },
+                () = delay => break None,
+            }
+        }
+    });
+    let tx_id = tx_id.expect("The spend was not recorded");
+
+    let spend = runtime.block_on(ts.get_completed_transaction(tx_id)).unwrap();
+    assert_eq!(spend.direction, TransactionDirection::Outbound);
+    assert_eq!(spend.status, TransactionStatus::Imported);
+    assert_eq!(spend.amount, 70_000 * uT);
+    assert_eq!(
+        runtime
+            .block_on(ts.get_completed_transactions())
+            .unwrap()
+            .values()
+            .filter(|tx| tx.status == TransactionStatus::Imported)
+            .count(),
+        1
+    );
+
+    let spent = runtime.block_on(oms.get_spent_outputs()).unwrap();
+    assert_eq!(spent.len(), 1);
+    assert_eq!(spent[0].value, 100_000 * uT);
+    let unspent = runtime.block_on(oms.get_unspent_outputs()).unwrap();
+    assert_eq!(unspent.len(), 1);
+    assert_eq!(unspent[0].value, 30_000 * uT);
+}
@@ -42,6 +42,7 @@ use tari_crypto::{
 };
 use tari_test_utils::random;
 use tari_wallet::{
+    output_manager_service::DEFAULT_ACCOUNT,
     storage::sqlite_utilities::run_migration_and_create_sqlite_connection,
     transaction_service::storage::{
         database::{TransactionBackend, TransactionDatabase},
@@ -115,6 +116,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         });
         assert!(
             !runtime.block_on(db.transaction_exists((i + 10) as u64)).unwrap(),
@@ -183,6 +185,7 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             direct_send_success: false,
             send_count: 0,
             last_send_timestamp: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         });
         assert!(
             !runtime.block_on(db.transaction_exists(i as u64)).unwrap(),
@@ -269,6 +272,8 @@ pub fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             valid: true,
             confirmations: None,
             mined_height: None,
+            replaced_by: None,
+            account: DEFAULT_ACCOUNT.to_string(),
         });
         runtime
             .block_on(db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone()))
@@ -496,6 +496,120 @@ async fn tx_broadcast_protocol_submit_rejection() {
     assert!(cancelled, "Should have cancelled transaction");
 }
 
+/// Test submitting a transaction that does not pay enough to replace a conflicting transaction in the mempool
+#[tokio_macros::test]
+#[allow(clippy::identity_op)]
+async fn tx_broadcast_protocol_submit_fee_too_low_rejection() {
+    let (
+        resources,
+        _connectivity_mock_state,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        timeout_update_publisher,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+    ) = setup(TxProtocolTestConfig::WithConnection).await;
+    let (base_node_update_publisher, _) = broadcast::channel(20);
+
+    add_transaction_to_database(1, 1 * T, true, None, resources.db.clone()).await;
+
+    let protocol = TransactionBroadcastProtocol::new(
+        1,
+        resources.clone(),
+        Duration::from_secs(1),
+        server_node_identity.public_key().clone(),
+        timeout_update_publisher.subscribe(),
+        base_node_update_publisher.subscribe(),
+    );
+
+    rpc_service_state.set_submit_transaction_response(TxSubmissionResponse {
+        accepted: false,
+        rejection_reason: TxSubmissionRejectionReason::FeeTooLow,
+        is_synced: true,
+    });
+
+    let err = task::spawn(protocol.execute()).await.unwrap().unwrap_err();
+    assert!(matches!(err.error, TransactionServiceError::MempoolRejectionFeeTooLow));
+
+    // The transaction is cancelled rather than reported as a double spend
+    assert!(resources.db.get_completed_transaction(1).await.is_err());
+}
+
+/// Test that a transaction paying less than the current minimum fee of the mempool is kept and resubmitted, rather
+/// than cancelled like a transaction that does not pay enough to replace a conflicting one
+#[tokio_macros::test]
+#[allow(clippy::identity_op)]
+async fn tx_broadcast_protocol_submit_below_minimum_fee_is_retried() {
+    let (
+        resources,
+        _connectivity_mock_state,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        timeout_update_publisher,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+    ) = setup(TxProtocolTestConfig::WithConnection).await;
+    let (base_node_update_publisher, _) = broadcast::channel(20);
+
+    add_transaction_to_database(1, 1 * T, true, None, resources.db.clone()).await;
+
+    let protocol = TransactionBroadcastProtocol::new(
+        1,
+        resources.clone(),
+        Duration::from_secs(1),
+        server_node_identity.public_key().clone(),
+        timeout_update_publisher.subscribe(),
+        base_node_update_publisher.subscribe(),
+    );
+
+    rpc_service_state.set_submit_transaction_response(TxSubmissionResponse {
+        accepted: false,
+        rejection_reason: TxSubmissionRejectionReason::BelowMinimumFee,
+        is_synced: true,
+    });
+
+    let join_handle = task::spawn(protocol.execute());
+
+    let _ = rpc_service_state
+        .wait_pop_submit_transaction_calls(2, Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    // The transaction is still waiting to be broadcast
+    let db_completed_tx = resources.db.get_completed_transaction(1).await.unwrap();
+    assert_eq!(db_completed_tx.status, TransactionStatus::Completed);
+
+    // The minimum fee falls and the transaction is accepted
+    rpc_service_state.set_submit_transaction_response(TxSubmissionResponse {
+        accepted: true,
+        rejection_reason: TxSubmissionRejectionReason::None,
+        is_synced: true,
+    });
+    let _ = rpc_service_state
+        .wait_pop_submit_transaction_calls(1, Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    rpc_service_state.set_transaction_query_response(TxQueryResponse {
+        location: TxLocation::Mined,
+        block_hash: None,
+        confirmations: resources.config.num_confirmations_required,
+        is_synced: true,
+        height_of_longest_chain: 10,
+    });
+
+    let result = join_handle.await.unwrap();
+    assert_eq!(result.unwrap(), 1);
+    let db_completed_tx = resources.db.get_completed_transaction(1).await.unwrap();
+    assert_eq!(db_completed_tx.status, TransactionStatus::MinedConfirmed);
+}
+
 /// Test restarting a protocol which means the first step is a query not a submission, detecting the Tx is not in the
 /// mempool, resubmit the tx and then have it mined
 #[tokio_macros::test]
@@ -97,6 +97,7 @@ async fn create_wallet(
     let node_identity = NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
     let comms_config = CommsConfig {
         network: NETWORK,
+        custom_network_byte: None,
         node_identity: Arc::new(node_identity.clone()),
         transport_type: TransportType::Memory {
             listener_address: node_identity.public_address(),
@@ -670,6 +671,7 @@ async fn test_import_utxo() {
     let (wallet_backend, tx_backend, oms_backend, contacts_backend, _temp_dir) = make_wallet_databases(None);
     let comms_config = CommsConfig {
         network: Network::Weatherwax,
+        custom_network_byte: None,
         node_identity: Arc::new(alice_identity.clone()),
         transport_type: TransportType::Tcp {
             listener_address: "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
@@ -763,6 +765,7 @@ async fn test_data_generation() {
     let temp_dir = tempdir().unwrap();
     let comms_config = CommsConfig {
         network: Network::Weatherwax,
+        custom_network_byte: None,
         node_identity: Arc::new(node_id.clone()),
         transport_type: TransportType::Memory {
             listener_address: node_id.public_address(),
@@ -2625,6 +2625,7 @@ pub unsafe extern "C" fn comms_config_create(
             );
             let config = TariCommsConfig {
                 network: Network::Weatherwax,
+                custom_network_byte: None,
                 node_identity: Arc::new(ni),
                 transport_type: (*transport_type).clone(),
                 datastore_path,
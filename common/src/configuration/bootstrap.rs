@@ -106,6 +106,29 @@ pub struct ConfigBootstrap {
     /// This will rebuild the db, adding block for block in
     #[structopt(long, alias = "rebuild_db")]
     pub rebuild_db: bool,
+    /// Load the chain state from a snapshot file into a new pruned node, then exit
+    #[structopt(long, alias = "import_snapshot", parse(from_os_str))]
+    pub import_snapshot: Option<PathBuf>,
+    /// Generate a genesis block and network definition for the configured network into the given directory, then
+    /// exit
+    #[structopt(long, alias = "generate_genesis", parse(from_os_str))]
+    pub generate_genesis: Option<PathBuf>,
+    /// Path to a file containing the hex encoded private key that can spend the coinbase of a generated genesis block
+    #[structopt(long, alias = "genesis_spend_key_file", parse(from_os_str))]
+    pub genesis_spend_key_file: Option<PathBuf>,
+    /// The coinbase amount of a generated genesis block in µT. Defaults to the block reward at height 0.
+    #[structopt(long, alias = "genesis_amount")]
+    pub genesis_amount: Option<u64>,
+    /// The timestamp of a generated genesis block in seconds since the Unix epoch. Defaults to the current time.
+    #[structopt(long, alias = "genesis_timestamp")]
+    pub genesis_timestamp: Option<u64>,
+    /// The network byte of a generated network definition. Not needed when the configured network definition file is
+    /// used, as its network byte is kept.
+    #[structopt(long, alias = "genesis_network_byte")]
+    pub genesis_network_byte: Option<u8>,
+    /// Report the database schema migrations that will be applied on startup without applying them, then exit
+    #[structopt(long, alias = "migrate_db_dry_run")]
+    pub migrate_db_dry_run: bool,
     /// Path to input file of commands
     #[structopt(short, long, aliases = &["input", "script"], parse(from_os_str))]
     pub input_file: Option<PathBuf>,
@@ -130,6 +153,9 @@ pub struct ConfigBootstrap {
     /// Supply the optional file name to save the wallet seed words into
     #[structopt(long, aliases = &["seed_words_file_name", "seed-words-file"], parse(from_os_str))]
     pub seed_words_file_name: Option<PathBuf>,
+    /// Create a watch-only wallet from the view keys exported by another wallet
+    #[structopt(long, aliases = &["watch_only", "view-keys"])]
+    pub watch_only: Option<String>,
     /// Wallet notify script
     #[structopt(long, alias = "notify")]
     pub wallet_notify: Option<PathBuf>,
@@ -165,6 +191,13 @@ impl Default for ConfigBootstrap {
             create_id: false,
             daemon_mode: false,
             rebuild_db: false,
+            import_snapshot: None,
+            generate_genesis: None,
+            genesis_spend_key_file: None,
+            genesis_amount: None,
+            genesis_timestamp: None,
+            genesis_network_byte: None,
+            migrate_db_dry_run: false,
             input_file: None,
             command: None,
             clean_orphans_db: false,
@@ -173,6 +206,7 @@ impl Default for ConfigBootstrap {
             recovery: false,
             seed_words: None,
             seed_words_file_name: None,
+            watch_only: None,
             wallet_notify: None,
             command_mode_auto_exit: false,
             mine_until_height: None,
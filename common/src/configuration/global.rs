@@ -22,11 +22,14 @@
 //
 //! # Global configuration of tari base layer system
 
-use crate::{configuration::Network, ConfigurationError};
+use crate::{
+    configuration::{CustomNetwork, Network},
+    ConfigurationError,
+};
 use config::{Config, ConfigError, Environment};
 use multiaddr::Multiaddr;
 use std::{
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     net::SocketAddr,
     num::{NonZeroU16, TryFromIntError},
     path::PathBuf,
@@ -64,6 +67,14 @@ pub struct GlobalConfig {
     pub orphan_db_clean_out_threshold: usize,
     pub pruning_horizon: u64,
     pub pruned_mode_cleanup_interval: u64,
+    pub output_index_enabled: bool,
+    pub archive_depth: Option<u64>,
+    pub persist_mempool: bool,
+    pub mempool_persistence_interval: u64,
+    pub checkpoints: Vec<(u64, String)>,
+    pub assume_valid_hash: Option<String>,
+    pub network_definition_file: Option<PathBuf>,
+    pub custom_network: Option<CustomNetwork>,
     pub core_threads: Option<usize>,
     pub max_threads: Option<usize>,
     pub base_node_identity_file: PathBuf,
@@ -115,6 +126,7 @@ pub struct GlobalConfig {
     pub proxy_host_address: SocketAddr,
     pub proxy_submit_to_origin: bool,
     pub force_sync_peers: Vec<String>,
+    pub block_sync_validation_window: usize,
     pub wait_for_initial_sync_at_startup: bool,
     pub max_randomx_vms: usize,
     pub console_wallet_notify_file: Option<PathBuf>,
@@ -145,11 +157,31 @@ impl GlobalConfig {
 fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, ConfigurationError> {
     let net_str = network.to_string().to_lowercase();
 
+    // Custom networks are defined in a file and run with the localnet configuration
+    let key = config_string("base_node", &net_str, "network_definition_file");
+    let network_definition_file = optional(cfg.get_str(&key))
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .map(PathBuf::from);
+    if network_definition_file.is_some() && network != Network::LocalNet {
+        return Err(ConfigurationError::new(
+            &key,
+            "A network definition file can only be used with the localnet network",
+        ));
+    }
+    let custom_network = network_definition_file
+        .as_ref()
+        .map(CustomNetwork::load_from_definition_file)
+        .transpose()?;
+
     let key = config_string("base_node", &net_str, "data_dir");
-    let data_dir: PathBuf = cfg
+    let mut data_dir: PathBuf = cfg
         .get_str(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
         .into();
+    // Each custom network keeps its data apart from localnet and from other custom networks
+    if let Some(custom_network) = &custom_network {
+        data_dir = data_dir.join(&custom_network.name);
+    }
 
     let key = config_string("base_node", &net_str, "db_type");
     let db_type = cfg
@@ -245,6 +277,52 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         .get_int(&key)
         .map_err(|e| ConfigurationError::new(&key, &e.to_string()))? as u64;
 
+    let key = config_string("base_node", &net_str, "output_index_enabled");
+    let output_index_enabled = optional(cfg.get_bool(&key))
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .unwrap_or(false);
+
+    let key = config_string("base_node", &net_str, "archive_depth");
+    let archive_depth =
+        optional(cfg.get_int(&key).map(|n| n as u64)).map_err(|e| ConfigurationError::new(&key, &e.to_string()))?;
+
+    let key = config_string("base_node", &net_str, "persist_mempool");
+    let persist_mempool = optional(cfg.get_bool(&key))
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .unwrap_or(false);
+
+    let key = config_string("base_node", &net_str, "mempool_persistence_interval");
+    let mempool_persistence_interval = optional(cfg.get_int(&key).map(|n| n as u64))
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .unwrap_or(300);
+
+    // Checkpoints in the form "height:header_hash"
+    let key = config_string("base_node", &net_str, "checkpoints");
+    let checkpoints = optional(cfg.get_array(&key))
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| {
+            let v = v
+                .into_str()
+                .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?;
+            let mut parts = v.splitn(2, ':');
+            match (parts.next().map(|h| h.trim().parse::<u64>()), parts.next()) {
+                (Some(Ok(height)), Some(hash)) => Ok((height, parse_header_hash(&key, hash)?)),
+                _ => Err(ConfigurationError::new(
+                    &key,
+                    &format!("Invalid checkpoint `{}`, expected `height:hash`", v),
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let key = config_string("base_node", &net_str, "assume_valid_hash");
+    let assume_valid_hash = optional(cfg.get_str(&key))
+        .map_err(|e| ConfigurationError::new(&key, &e.to_string()))?
+        .map(|hash| parse_header_hash(&key, &hash))
+        .transpose()?;
+
     // Thread counts
     let key = config_string("base_node", &net_str, "core_threads");
     let core_threads =
@@ -361,6 +439,12 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         .map(|v| v.into_str().unwrap())
         .collect::<Vec<_>>();
 
+    // The seeds configured for localnet belong to other networks, a custom network only uses its own peer seeds
+    let (peer_seeds, dns_seeds) = match &custom_network {
+        Some(custom_network) => (custom_network.peer_seeds.clone(), Vec::new()),
+        None => (peer_seeds, dns_seeds),
+    };
+
     // Peer DB path
     let peer_db_path = data_dir.join("peer_db");
     let wallet_peer_db_path = data_dir.join("wallet_peer_db");
@@ -390,6 +474,15 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     )?
     .unwrap_or_default();
 
+    let key = config_string("base_node", &net_str, "block_sync_validation_window");
+    let block_sync_validation_window = optional(cfg.get_int(&key))?
+        .map(|n| match usize::try_from(n) {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(ConfigurationError::new(&key, "must be a positive integer")),
+        })
+        .transpose()?
+        .unwrap_or(8);
+
     // Liveness auto ping interval
     let key = config_string("base_node", &net_str, "auto_ping_interval");
     let auto_ping_interval = match cfg.get_int(&key) {
@@ -652,6 +745,14 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         orphan_db_clean_out_threshold,
         pruning_horizon,
         pruned_mode_cleanup_interval,
+        output_index_enabled,
+        archive_depth,
+        persist_mempool,
+        mempool_persistence_interval,
+        checkpoints,
+        assume_valid_hash,
+        network_definition_file,
+        custom_network,
         core_threads,
         max_threads,
         base_node_identity_file,
@@ -703,6 +804,7 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
         monerod_password,
         monerod_use_auth,
         force_sync_peers,
+        block_sync_validation_window,
         wait_for_initial_sync_at_startup,
         max_randomx_vms,
         console_wallet_notify_file,
@@ -714,6 +816,18 @@ fn convert_node_config(network: Network, cfg: Config) -> Result<GlobalConfig, Co
     })
 }
 
+/// Checks that a configured block header hash is a 32 byte hex string
+fn parse_header_hash(key: &str, hash: &str) -> Result<String, ConfigurationError> {
+    let hash = hash.trim();
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ConfigurationError::new(
+            key,
+            &format!("Invalid header hash `{}`, expected 64 hex characters", hash),
+        ));
+    }
+    Ok(hash.to_string())
+}
+
 /// Changes ConfigError::NotFound into None
 fn optional<T>(result: Result<T, ConfigError>) -> Result<Option<T>, ConfigError> {
     match result {
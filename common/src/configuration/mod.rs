@@ -39,7 +39,7 @@ pub mod error;
 pub mod global;
 pub mod loader;
 mod network;
-pub use network::Network;
+pub use network::{CustomNetwork, Network};
 pub mod seconds;
 pub mod utils;
 pub mod writer;
@@ -24,6 +24,8 @@ use crate::ConfigurationError;
 use std::{
     fmt,
     fmt::{Display, Formatter},
+    fs,
+    path::Path,
     str::FromStr,
 };
 
@@ -43,6 +45,86 @@ impl Network {
     pub fn as_byte(self) -> u8 {
         self as u8
     }
+
+    /// Returns the hardcoded network that uses the given network byte, if any
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        use Network::*;
+        [MainNet, LocalNet, Ridcully, Stibbons, Weatherwax]
+            .iter()
+            .copied()
+            .find(|network| network.as_byte() == byte)
+    }
+}
+
+/// The identity of a private network that is defined in a network definition file. Custom networks run with the
+/// [LocalNet](Network::LocalNet) configuration, but use their own network byte, so that they do not connect to LocalNet
+/// nodes or other custom networks, keep their data in a directory named after the network and take their peer seeds
+/// from the definition file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomNetwork {
+    /// The name of the network, which is also the name of its data directory
+    pub name: String,
+    /// The byte that identifies the network in the p2p wire protocol
+    pub network_byte: u8,
+    /// The peer seeds of the network
+    pub peer_seeds: Vec<String>,
+}
+
+impl CustomNetwork {
+    /// Creates the identity of a custom network. The name must be usable as a directory name and the network byte must
+    /// not be used by any hardcoded network.
+    pub fn new(name: String, network_byte: u8, peer_seeds: Vec<String>) -> Result<Self, ConfigurationError> {
+        let is_valid_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid_name {
+            return Err(ConfigurationError::new(
+                "name",
+                &format!(
+                    "Invalid custom network name `{}`, only letters, digits, `-` and `_` are allowed",
+                    name
+                ),
+            ));
+        }
+        if let Some(network) = Network::from_byte(network_byte) {
+            return Err(ConfigurationError::new(
+                "network_byte",
+                &format!("Network byte {:#04x} is already used by {}", network_byte, network),
+            ));
+        }
+        Ok(Self {
+            name,
+            network_byte,
+            peer_seeds,
+        })
+    }
+
+    /// Reads the identity of a custom network from the `name`, `network_byte` and `peer_seeds` fields of its network
+    /// definition file
+    pub fn load_from_definition_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigurationError> {
+        let path = path.as_ref();
+        let field = path.display().to_string();
+        let contents = fs::read_to_string(path).map_err(|e| ConfigurationError::new(&field, &e.to_string()))?;
+        let definition = serde_json::from_str::<serde_json::Value>(&contents)?;
+        let name = definition["name"]
+            .as_str()
+            .ok_or_else(|| ConfigurationError::new(&field, "The network definition has no name"))?;
+        let network_byte = definition["network_byte"]
+            .as_u64()
+            .filter(|byte| *byte <= u64::from(u8::MAX))
+            .ok_or_else(|| ConfigurationError::new(&field, "The network definition has no valid network byte"))?;
+        let peer_seeds = match &definition["peer_seeds"] {
+            serde_json::Value::Null => Vec::new(),
+            serde_json::Value::Array(seeds) => seeds
+                .iter()
+                .map(|seed| {
+                    seed.as_str()
+                        .map(ToString::to_string)
+                        .ok_or_else(|| ConfigurationError::new(&field, "Peer seeds must be strings"))
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(ConfigurationError::new(&field, "Peer seeds must be an array")),
+        };
+        Self::new(name.to_string(), network_byte as u8, peer_seeds)
+    }
 }
 
 impl Default for Network {
@@ -83,3 +165,33 @@ impl Display for Network {
         f.write_str(msg)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn load_custom_network_from_definition_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("staging.json");
+        fs::write(
+            &path,
+            r#"{"name": "staging", "network_byte": 127, "peer_seeds": ["seed"], "genesis_block": {}}"#,
+        )
+        .unwrap();
+        let network = CustomNetwork::load_from_definition_file(&path).unwrap();
+        assert_eq!(network, CustomNetwork {
+            name: "staging".to_string(),
+            network_byte: 127,
+            peer_seeds: vec!["seed".to_string()],
+        });
+
+        fs::write(&path, r#"{"name": "staging", "network_byte": 16}"#).unwrap();
+        assert!(CustomNetwork::load_from_definition_file(&path).is_err());
+        fs::write(&path, r#"{"name": "../staging", "network_byte": 127}"#).unwrap();
+        assert!(CustomNetwork::load_from_definition_file(&path).is_err());
+        fs::write(&path, r#"{"name": "staging"}"#).unwrap();
+        assert!(CustomNetwork::load_from_definition_file(&path).is_err());
+    }
+}